@@ -1,32 +1,47 @@
 use axum::{
     Router,
-    extract::{Json, Query},
-    http::{StatusCode, header},
-    response::{Html, IntoResponse, Response},
-    routing::get,
+    extract::{DefaultBodyLimit, FromRef, Json, Path, Query, State},
+    http::{HeaderValue, Method, StatusCode, header},
+    response::{
+        Html, IntoResponse, Response,
+        sse::{Event, Sse},
+    },
+    routing::{get, post},
 };
 use clap::{Parser, ValueEnum};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
 use tokio::net::TcpListener;
+use tower_http::{
+    compression::CompressionLayer,
+    cors::{AllowOrigin, CorsLayer},
+};
 
 use crate::core::{
-    AgeResult, CashflowYearResult, ContributionAllocation, GoalSolveConfig, GoalSolveIteration,
-    GoalSolveResult, GoalType, Inputs, ModelResult, PensionTaxMode, WithdrawalOrder,
-    WithdrawalStrategy, run_coast_model, run_model, run_yearly_cashflow_trace, solve_goal,
+    AgeResult, CashflowYearResult, ContributionAllocation, Debt, GoalSolveConfig,
+    GoalSolveIteration, GoalSolveResult, GoalType, Inputs, MarketModel, ModelResult,
+    PensionTaxMode, ReturnDistribution, SelectionConfidence, SuccessCriterion, ValidationError,
+    WithdrawalOrder, WithdrawalStrategy, run_coast_model_with_progress, run_model,
+    run_model_with_age_results, run_model_with_progress, run_terminal_distribution,
+    run_yearly_cashflow_trace, solve_goal, validate_inputs,
 };
 
 const INDEX_HTML: &str = include_str!("../../web/index.html");
 const STYLES_CSS: &str = include_str!("../../web/styles.css");
 const APP_JS: &str = include_str!("../../web/app.js");
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 enum CliWithdrawalOrder {
     ProRata,
     IsaFirst,
     TaxableFirst,
     PensionFirst,
     BondLadderFirst,
+    FillBasicRate,
+    HarvestCgtAllowance,
 }
 
 impl From<CliWithdrawalOrder> for WithdrawalOrder {
@@ -37,13 +52,49 @@ impl From<CliWithdrawalOrder> for WithdrawalOrder {
             CliWithdrawalOrder::TaxableFirst => WithdrawalOrder::TaxableFirst,
             CliWithdrawalOrder::PensionFirst => WithdrawalOrder::PensionFirst,
             CliWithdrawalOrder::BondLadderFirst => WithdrawalOrder::BondLadderFirst,
+            CliWithdrawalOrder::FillBasicRate => WithdrawalOrder::FillBasicRate,
+            CliWithdrawalOrder::HarvestCgtAllowance => WithdrawalOrder::HarvestCgtAllowance,
+        }
+    }
+}
+
+impl From<WithdrawalOrder> for CliWithdrawalOrder {
+    fn from(value: WithdrawalOrder) -> Self {
+        match value {
+            WithdrawalOrder::ProRata => CliWithdrawalOrder::ProRata,
+            WithdrawalOrder::IsaFirst => CliWithdrawalOrder::IsaFirst,
+            WithdrawalOrder::TaxableFirst => CliWithdrawalOrder::TaxableFirst,
+            WithdrawalOrder::PensionFirst => CliWithdrawalOrder::PensionFirst,
+            WithdrawalOrder::BondLadderFirst => CliWithdrawalOrder::BondLadderFirst,
+            WithdrawalOrder::FillBasicRate => CliWithdrawalOrder::FillBasicRate,
+            WithdrawalOrder::HarvestCgtAllowance => CliWithdrawalOrder::HarvestCgtAllowance,
         }
     }
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum CliMarketModel {
+    Parametric,
+    HistoricalBootstrap,
+    Deterministic,
+    Garch,
+    RegimeSwitching,
+    Ensemble,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum CliReturnDistribution {
+    Normal,
+    StudentT,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 enum CliPensionTaxMode {
     UkBands,
+    ScottishBands,
     FlatRate,
 }
 
@@ -51,18 +102,90 @@ impl From<CliPensionTaxMode> for PensionTaxMode {
     fn from(value: CliPensionTaxMode) -> Self {
         match value {
             CliPensionTaxMode::UkBands => PensionTaxMode::UkBands,
+            CliPensionTaxMode::ScottishBands => PensionTaxMode::ScottishBands,
             CliPensionTaxMode::FlatRate => PensionTaxMode::FlatRate,
         }
     }
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+impl From<PensionTaxMode> for CliPensionTaxMode {
+    fn from(value: PensionTaxMode) -> Self {
+        match value {
+            PensionTaxMode::UkBands => CliPensionTaxMode::UkBands,
+            PensionTaxMode::ScottishBands => CliPensionTaxMode::ScottishBands,
+            PensionTaxMode::FlatRate => CliPensionTaxMode::FlatRate,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum CliSelectionConfidence {
+    PointEstimate,
+    Conservative,
+    Optimistic,
+}
+
+impl From<CliSelectionConfidence> for SelectionConfidence {
+    fn from(value: CliSelectionConfidence) -> Self {
+        match value {
+            CliSelectionConfidence::PointEstimate => SelectionConfidence::PointEstimate,
+            CliSelectionConfidence::Conservative => SelectionConfidence::Conservative,
+            CliSelectionConfidence::Optimistic => SelectionConfidence::Optimistic,
+        }
+    }
+}
+
+impl From<SelectionConfidence> for CliSelectionConfidence {
+    fn from(value: SelectionConfidence) -> Self {
+        match value {
+            SelectionConfidence::PointEstimate => CliSelectionConfidence::PointEstimate,
+            SelectionConfidence::Conservative => CliSelectionConfidence::Conservative,
+            SelectionConfidence::Optimistic => CliSelectionConfidence::Optimistic,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum CliSuccessCriterion {
+    FullSpending,
+    NoDepletion,
+    EssentialFloorMet,
+}
+
+impl From<CliSuccessCriterion> for SuccessCriterion {
+    fn from(value: CliSuccessCriterion) -> Self {
+        match value {
+            CliSuccessCriterion::FullSpending => SuccessCriterion::FullSpending,
+            CliSuccessCriterion::NoDepletion => SuccessCriterion::NoDepletion,
+            CliSuccessCriterion::EssentialFloorMet => SuccessCriterion::EssentialFloorMet,
+        }
+    }
+}
+
+impl From<SuccessCriterion> for CliSuccessCriterion {
+    fn from(value: SuccessCriterion) -> Self {
+        match value {
+            SuccessCriterion::FullSpending => CliSuccessCriterion::FullSpending,
+            SuccessCriterion::NoDepletion => CliSuccessCriterion::NoDepletion,
+            SuccessCriterion::EssentialFloorMet => CliSuccessCriterion::EssentialFloorMet,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 enum CliWithdrawalStrategy {
     Guardrails,
     GuytonKlinger,
     Vpw,
     FloorUpside,
     Bucket,
+    FixedReal,
+    ConstantPercentage,
+    CapeBased,
+    Amortization,
 }
 
 impl From<CliWithdrawalStrategy> for WithdrawalStrategy {
@@ -73,6 +196,26 @@ impl From<CliWithdrawalStrategy> for WithdrawalStrategy {
             CliWithdrawalStrategy::Vpw => WithdrawalStrategy::Vpw,
             CliWithdrawalStrategy::FloorUpside => WithdrawalStrategy::FloorUpside,
             CliWithdrawalStrategy::Bucket => WithdrawalStrategy::Bucket,
+            CliWithdrawalStrategy::FixedReal => WithdrawalStrategy::FixedReal,
+            CliWithdrawalStrategy::ConstantPercentage => WithdrawalStrategy::ConstantPercentage,
+            CliWithdrawalStrategy::CapeBased => WithdrawalStrategy::CapeBased,
+            CliWithdrawalStrategy::Amortization => WithdrawalStrategy::Amortization,
+        }
+    }
+}
+
+impl From<WithdrawalStrategy> for CliWithdrawalStrategy {
+    fn from(value: WithdrawalStrategy) -> Self {
+        match value {
+            WithdrawalStrategy::Guardrails => CliWithdrawalStrategy::Guardrails,
+            WithdrawalStrategy::GuytonKlinger => CliWithdrawalStrategy::GuytonKlinger,
+            WithdrawalStrategy::Vpw => CliWithdrawalStrategy::Vpw,
+            WithdrawalStrategy::FloorUpside => CliWithdrawalStrategy::FloorUpside,
+            WithdrawalStrategy::Bucket => CliWithdrawalStrategy::Bucket,
+            WithdrawalStrategy::FixedReal => CliWithdrawalStrategy::FixedReal,
+            WithdrawalStrategy::ConstantPercentage => CliWithdrawalStrategy::ConstantPercentage,
+            WithdrawalStrategy::CapeBased => CliWithdrawalStrategy::CapeBased,
+            WithdrawalStrategy::Amortization => CliWithdrawalStrategy::Amortization,
         }
     }
 }
@@ -83,7 +226,7 @@ enum AnalysisMode {
     CoastFire,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 enum ApiWithdrawalOrder {
     #[serde(alias = "proRata", alias = "pro_rata")]
@@ -96,6 +239,10 @@ enum ApiWithdrawalOrder {
     PensionFirst,
     #[serde(alias = "bondLadderFirst", alias = "bond_ladder_first")]
     BondLadderFirst,
+    #[serde(alias = "fillBasicRate", alias = "fill_basic_rate")]
+    FillBasicRate,
+    #[serde(alias = "harvestCgtAllowance", alias = "harvest_cgt_allowance")]
+    HarvestCgtAllowance,
 }
 
 impl From<ApiWithdrawalOrder> for CliWithdrawalOrder {
@@ -106,15 +253,71 @@ impl From<ApiWithdrawalOrder> for CliWithdrawalOrder {
             ApiWithdrawalOrder::TaxableFirst => CliWithdrawalOrder::TaxableFirst,
             ApiWithdrawalOrder::PensionFirst => CliWithdrawalOrder::PensionFirst,
             ApiWithdrawalOrder::BondLadderFirst => CliWithdrawalOrder::BondLadderFirst,
+            ApiWithdrawalOrder::FillBasicRate => CliWithdrawalOrder::FillBasicRate,
+            ApiWithdrawalOrder::HarvestCgtAllowance => CliWithdrawalOrder::HarvestCgtAllowance,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum ApiMarketModel {
+    Parametric,
+    #[serde(
+        alias = "historicalBootstrap",
+        alias = "historical_bootstrap",
+        alias = "historical"
+    )]
+    HistoricalBootstrap,
+    Deterministic,
+    Garch,
+    #[serde(
+        alias = "regimeSwitching",
+        alias = "regime_switching",
+        alias = "regime"
+    )]
+    RegimeSwitching,
+    Ensemble,
+}
+
+impl From<ApiMarketModel> for CliMarketModel {
+    fn from(value: ApiMarketModel) -> Self {
+        match value {
+            ApiMarketModel::Parametric => CliMarketModel::Parametric,
+            ApiMarketModel::HistoricalBootstrap => CliMarketModel::HistoricalBootstrap,
+            ApiMarketModel::Deterministic => CliMarketModel::Deterministic,
+            ApiMarketModel::Garch => CliMarketModel::Garch,
+            ApiMarketModel::RegimeSwitching => CliMarketModel::RegimeSwitching,
+            ApiMarketModel::Ensemble => CliMarketModel::Ensemble,
         }
     }
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum ApiReturnDistribution {
+    #[serde(alias = "gaussian")]
+    Normal,
+    #[serde(alias = "studentT", alias = "student_t", alias = "t")]
+    StudentT,
+}
+
+impl From<ApiReturnDistribution> for CliReturnDistribution {
+    fn from(value: ApiReturnDistribution) -> Self {
+        match value {
+            ApiReturnDistribution::Normal => CliReturnDistribution::Normal,
+            ApiReturnDistribution::StudentT => CliReturnDistribution::StudentT,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 enum ApiPensionTaxMode {
     #[serde(alias = "ukBands", alias = "uk_bands")]
     UkBands,
+    #[serde(alias = "scottishBands", alias = "scottish_bands", alias = "scottish")]
+    ScottishBands,
     #[serde(alias = "flat", alias = "flatRate", alias = "flat_rate")]
     FlatRate,
 }
@@ -123,6 +326,7 @@ impl From<ApiPensionTaxMode> for CliPensionTaxMode {
     fn from(value: ApiPensionTaxMode) -> Self {
         match value {
             ApiPensionTaxMode::UkBands => CliPensionTaxMode::UkBands,
+            ApiPensionTaxMode::ScottishBands => CliPensionTaxMode::ScottishBands,
             ApiPensionTaxMode::FlatRate => CliPensionTaxMode::FlatRate,
         }
     }
@@ -130,6 +334,50 @@ impl From<ApiPensionTaxMode> for CliPensionTaxMode {
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
+enum ApiSuccessCriterion {
+    #[serde(alias = "fullSpending", alias = "full_spending")]
+    FullSpending,
+    #[serde(alias = "noDepletion", alias = "no_depletion")]
+    NoDepletion,
+    #[serde(
+        alias = "essentialFloorMet",
+        alias = "essential_floor_met",
+        alias = "essential-floor"
+    )]
+    EssentialFloorMet,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum ApiSelectionConfidence {
+    #[serde(alias = "pointEstimate", alias = "point_estimate")]
+    PointEstimate,
+    Conservative,
+    Optimistic,
+}
+
+impl From<ApiSelectionConfidence> for CliSelectionConfidence {
+    fn from(value: ApiSelectionConfidence) -> Self {
+        match value {
+            ApiSelectionConfidence::PointEstimate => CliSelectionConfidence::PointEstimate,
+            ApiSelectionConfidence::Conservative => CliSelectionConfidence::Conservative,
+            ApiSelectionConfidence::Optimistic => CliSelectionConfidence::Optimistic,
+        }
+    }
+}
+
+impl From<ApiSuccessCriterion> for CliSuccessCriterion {
+    fn from(value: ApiSuccessCriterion) -> Self {
+        match value {
+            ApiSuccessCriterion::FullSpending => CliSuccessCriterion::FullSpending,
+            ApiSuccessCriterion::NoDepletion => CliSuccessCriterion::NoDepletion,
+            ApiSuccessCriterion::EssentialFloorMet => CliSuccessCriterion::EssentialFloorMet,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
 enum ApiWithdrawalStrategy {
     #[serde(alias = "dynamic-guardrails", alias = "dynamicGuardrails")]
     Guardrails,
@@ -139,6 +387,18 @@ enum ApiWithdrawalStrategy {
     #[serde(alias = "floorUpside", alias = "floor_upside")]
     FloorUpside,
     Bucket,
+    #[serde(alias = "fixedReal", alias = "fixed_real", alias = "bengen")]
+    FixedReal,
+    #[serde(
+        alias = "constantPercentage",
+        alias = "constant_percentage",
+        alias = "endowment"
+    )]
+    ConstantPercentage,
+    #[serde(alias = "capeBased", alias = "cape_based", alias = "cape")]
+    CapeBased,
+    #[serde(alias = "life-expectancy", alias = "lifeExpectancy", alias = "rmd")]
+    Amortization,
 }
 
 impl From<ApiWithdrawalStrategy> for CliWithdrawalStrategy {
@@ -149,6 +409,10 @@ impl From<ApiWithdrawalStrategy> for CliWithdrawalStrategy {
             ApiWithdrawalStrategy::Vpw => CliWithdrawalStrategy::Vpw,
             ApiWithdrawalStrategy::FloorUpside => CliWithdrawalStrategy::FloorUpside,
             ApiWithdrawalStrategy::Bucket => CliWithdrawalStrategy::Bucket,
+            ApiWithdrawalStrategy::FixedReal => CliWithdrawalStrategy::FixedReal,
+            ApiWithdrawalStrategy::ConstantPercentage => CliWithdrawalStrategy::ConstantPercentage,
+            ApiWithdrawalStrategy::CapeBased => CliWithdrawalStrategy::CapeBased,
+            ApiWithdrawalStrategy::Amortization => CliWithdrawalStrategy::Amortization,
         }
     }
 }
@@ -161,11 +425,15 @@ impl From<WithdrawalStrategy> for ApiWithdrawalStrategy {
             WithdrawalStrategy::Vpw => ApiWithdrawalStrategy::Vpw,
             WithdrawalStrategy::FloorUpside => ApiWithdrawalStrategy::FloorUpside,
             WithdrawalStrategy::Bucket => ApiWithdrawalStrategy::Bucket,
+            WithdrawalStrategy::FixedReal => ApiWithdrawalStrategy::FixedReal,
+            WithdrawalStrategy::ConstantPercentage => ApiWithdrawalStrategy::ConstantPercentage,
+            WithdrawalStrategy::CapeBased => ApiWithdrawalStrategy::CapeBased,
+            WithdrawalStrategy::Amortization => ApiWithdrawalStrategy::Amortization,
         }
     }
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 enum ApiAnalysisMode {
     #[serde(alias = "retirementSweep", alias = "retirement")]
@@ -190,6 +458,22 @@ enum ApiGoalType {
     RequiredContribution,
     #[serde(alias = "maxIncome", alias = "max_income")]
     MaxIncome,
+    #[serde(
+        alias = "earliestRetirementAge",
+        alias = "earliest_retirement_age",
+        alias = "earliest-age"
+    )]
+    EarliestRetirementAge,
+    #[serde(
+        alias = "maxIncomeCvarConstrained",
+        alias = "max_income_cvar_constrained"
+    )]
+    MaxIncomeCvarConstrained,
+    #[serde(
+        alias = "optimalContributionSplit",
+        alias = "optimal_contribution_split"
+    )]
+    OptimalContributionSplit,
 }
 
 impl From<ApiGoalType> for GoalType {
@@ -197,6 +481,9 @@ impl From<ApiGoalType> for GoalType {
         match value {
             ApiGoalType::RequiredContribution => GoalType::RequiredContribution,
             ApiGoalType::MaxIncome => GoalType::MaxIncome,
+            ApiGoalType::EarliestRetirementAge => GoalType::EarliestRetirementAge,
+            ApiGoalType::MaxIncomeCvarConstrained => GoalType::MaxIncomeCvarConstrained,
+            ApiGoalType::OptimalContributionSplit => GoalType::OptimalContributionSplit,
         }
     }
 }
@@ -206,6 +493,9 @@ impl From<GoalType> for ApiGoalType {
         match value {
             GoalType::RequiredContribution => ApiGoalType::RequiredContribution,
             GoalType::MaxIncome => ApiGoalType::MaxIncome,
+            GoalType::EarliestRetirementAge => ApiGoalType::EarliestRetirementAge,
+            GoalType::MaxIncomeCvarConstrained => ApiGoalType::MaxIncomeCvarConstrained,
+            GoalType::OptimalContributionSplit => ApiGoalType::OptimalContributionSplit,
         }
     }
 }
@@ -226,35 +516,93 @@ impl From<AnalysisMode> for ResponseMode {
     }
 }
 
-#[derive(Debug, Default, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct ApiLumpExpense {
+    age: u32,
+    amount: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ApiDebt {
+    annual_payment: f64,
+    end_age: u32,
+}
+
+/// One year of a deterministic market path, as percentages.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct ApiMarketPathYear {
+    isa: f64,
+    taxable: f64,
+    pension: f64,
+    inflation: f64,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
 #[serde(default, rename_all = "camelCase")]
 struct SimulatePayload {
     current_age: Option<u32>,
     pension_access_age: Option<u32>,
     max_age: Option<u32>,
     horizon_age: Option<u32>,
+    bequest_target: Option<f64>,
+    success_criterion: Option<ApiSuccessCriterion>,
     simulations: Option<u32>,
     seed: Option<u64>,
+    seed_label: Option<String>,
+    age_invariant_draws: Option<bool>,
+    stratified_inflation: Option<bool>,
 
     isa_start: Option<f64>,
     taxable_start: Option<f64>,
     taxable_basis_start: Option<f64>,
     pension_start: Option<f64>,
+    household: Option<bool>,
+    partner_pension_start: Option<f64>,
+    partner_pension_access_age: Option<u32>,
+    partner_personal_allowance: Option<f64>,
+    marriage_allowance_transfer: Option<f64>,
     cash_start: Option<f64>,
     bond_ladder_start: Option<f64>,
 
     isa_contribution: Option<f64>,
     isa_limit: Option<f64>,
+    lisa_start: Option<f64>,
+    lisa_contribution: Option<f64>,
+    lisa_access_age: Option<u32>,
     taxable_contribution: Option<f64>,
     pension_contribution: Option<f64>,
+    employer_match_rate: Option<f64>,
+    employer_match_cap: Option<f64>,
+    pension_annual_allowance: Option<f64>,
+    pension_overflow_to_taxable: Option<bool>,
+    contributions_track_inflation: Option<bool>,
     contribution_growth: Option<f64>,
+    coast_contribution_fraction: Option<f64>,
+    pre_retirement_earnings: Option<f64>,
 
+    /// Flat CGT rate applied to both bands; the banded fields below take precedence.
     cgt_rate: Option<f64>,
+    cgt_basic_rate: Option<f64>,
+    cgt_higher_rate: Option<f64>,
     cgt_allowance: Option<f64>,
     taxable_tax_drag: Option<f64>,
+    #[serde(alias = "dividendYield")]
+    taxable_dividend_yield: Option<f64>,
+    dividend_allowance: Option<f64>,
+    dividend_basic_rate: Option<f64>,
+    dividend_higher_rate: Option<f64>,
+    dividend_additional_rate: Option<f64>,
+    isa_fee_rate: Option<f64>,
+    taxable_fee_rate: Option<f64>,
+    pension_fee_rate: Option<f64>,
+    glide_start_equity: Option<f64>,
+    glide_end_equity: Option<f64>,
+    glide_end_age: Option<u32>,
 
     pension_tax_mode: Option<ApiPensionTaxMode>,
     pension_income_tax_rate: Option<f64>,
+    pension_tax_free_pct: Option<f64>,
     uk_personal_allowance: Option<f64>,
     uk_basic_rate_limit: Option<f64>,
     uk_higher_rate_limit: Option<f64>,
@@ -263,8 +611,38 @@ struct SimulatePayload {
     uk_additional_rate: Option<f64>,
     uk_allowance_taper_start: Option<f64>,
     uk_allowance_taper_end: Option<f64>,
+    thresholds_frozen_until_age: Option<u32>,
+    scottish_starter_rate_limit: Option<f64>,
+    scottish_basic_rate_limit: Option<f64>,
+    scottish_intermediate_rate_limit: Option<f64>,
+    scottish_higher_rate_limit: Option<f64>,
+    scottish_advanced_rate_limit: Option<f64>,
+    scottish_starter_rate: Option<f64>,
+    scottish_basic_rate: Option<f64>,
+    scottish_intermediate_rate: Option<f64>,
+    scottish_higher_rate: Option<f64>,
+    scottish_advanced_rate: Option<f64>,
+    scottish_top_rate: Option<f64>,
     state_pension_start_age: Option<u32>,
     state_pension_income: Option<f64>,
+    state_pension_real_growth: Option<f64>,
+    state_pension_growth_before_start: Option<bool>,
+    state_pension_deferral_years: Option<u32>,
+    state_pension_deferral_uplift_rate: Option<f64>,
+    db_pension_start_age: Option<u32>,
+    db_pension_income: Option<f64>,
+    annuity_purchase_age: Option<u32>,
+    annuity_purchase_amount: Option<f64>,
+    annuity_rate: Option<f64>,
+    conversion_ladder_annual_amount: Option<f64>,
+    conversion_ladder_start_age: Option<u32>,
+    conversion_ladder_end_age: Option<u32>,
+    rental_income_start_age: Option<u32>,
+    rental_income: Option<f64>,
+    rental_growth_rate: Option<f64>,
+    part_time_income: Option<f64>,
+    part_time_start_age: Option<u32>,
+    part_time_end_age: Option<u32>,
 
     isa_mean: Option<f64>,
     isa_vol: Option<f64>,
@@ -273,33 +651,93 @@ struct SimulatePayload {
     pension_mean: Option<f64>,
     pension_vol: Option<f64>,
     correlation: Option<f64>,
+    isa_taxable_correlation: Option<f64>,
+    taxable_pension_correlation: Option<f64>,
+    isa_beta: Option<f64>,
+    taxable_beta: Option<f64>,
+    pension_beta: Option<f64>,
+    return_distribution: Option<ApiReturnDistribution>,
+    market_model: Option<ApiMarketModel>,
+    bootstrap_block_years: Option<u32>,
+    market_burn_in_years: Option<u32>,
+    bootstrap_fraction: Option<f64>,
+    market_path: Option<Vec<ApiMarketPathYear>>,
+    student_t_dof: Option<f64>,
+    garch_alpha: Option<f64>,
+    garch_beta: Option<f64>,
+    garch_long_run_vol: Option<f64>,
+    normal_to_crisis_probability: Option<f64>,
+    crisis_to_normal_probability: Option<f64>,
+    #[serde(alias = "crisisMean")]
+    crisis_return_mean: Option<f64>,
+    #[serde(alias = "crisisVol")]
+    crisis_return_volatility: Option<f64>,
     inflation_mean: Option<f64>,
     inflation_vol: Option<f64>,
+    inflation_return_correlation: Option<f64>,
+    return_clamp_min: Option<f64>,
+    return_clamp_max: Option<f64>,
+    inflation_clamp_min: Option<f64>,
+    inflation_clamp_max: Option<f64>,
 
     target_income: Option<f64>,
+    essential_income: Option<f64>,
+    spending_decline_rate: Option<f64>,
+    care_cost_age: Option<u32>,
+    care_cost_uplift: Option<f64>,
+    active_phase_end_age: Option<u32>,
+    active_phase_uplift: Option<f64>,
+    late_phase_start_age: Option<u32>,
+    late_phase_uplift: Option<f64>,
     mortgage_annual_payment: Option<f64>,
     mortgage_end_age: Option<u32>,
+    debts: Option<Vec<ApiDebt>>,
+    mortgage_balance: Option<f64>,
+    mortgage_rate: Option<f64>,
+    mortgage_term_years: Option<u32>,
+    mortgage_overpayments: Option<Vec<ApiLumpExpense>>,
+    lump_expenses: Option<Vec<ApiLumpExpense>>,
     success_threshold: Option<f64>,
+    selection_confidence: Option<ApiSelectionConfidence>,
+    risk_aversion: Option<f64>,
+    utility_discount_rate: Option<f64>,
+    utility_spending_floor: Option<f64>,
     bad_threshold: Option<f64>,
     good_threshold: Option<f64>,
     bad_cut: Option<f64>,
     good_raise: Option<f64>,
     min_floor: Option<f64>,
     max_ceiling: Option<f64>,
+    min_real_spending_floor: Option<f64>,
     withdrawal_policy: Option<ApiWithdrawalStrategy>,
     gk_lower_guardrail: Option<f64>,
     gk_upper_guardrail: Option<f64>,
+    gk_adjustment: Option<f64>,
     vpw_real_return: Option<f64>,
+    constant_withdrawal_rate: Option<f64>,
+    cape_a: Option<f64>,
+    cape_b: Option<f64>,
+    cape_start: Option<f64>,
+    cape_long_run_mean: Option<f64>,
+    cape_reversion_speed: Option<f64>,
     floor_upside_capture: Option<f64>,
     bucket_years_target: Option<f64>,
     extra_to_cash: Option<f64>,
+    min_cash_months: Option<f64>,
     cash_growth: Option<f64>,
     bond_ladder_yield: Option<f64>,
+    bond_ladder_yield_vol: Option<f64>,
+    bond_ladder_inflation_correlation: Option<f64>,
     bond_ladder_years: Option<u32>,
+    bond_ladder_reinvest: Option<bool>,
+    bond_ladder_target_at_retirement: Option<f64>,
     withdrawal_order: Option<ApiWithdrawalOrder>,
+    pre_access_withdrawal_order: Option<ApiWithdrawalOrder>,
 
     analysis_mode: Option<ApiAnalysisMode>,
     coast_retirement_age: Option<u32>,
+    cashflow_age: Option<u32>,
+    summary_only: Option<bool>,
 }
 
 #[derive(Debug, Default, Deserialize, Clone)]
@@ -308,14 +746,99 @@ struct SolveGoalPayload {
     #[serde(flatten)]
     simulation: SimulatePayload,
     goal_type: Option<ApiGoalType>,
+    #[serde(alias = "targetAge")]
     target_retirement_age: Option<u32>,
+    #[serde(alias = "targetThreshold")]
     target_success_threshold: Option<f64>,
+    /// Percent floor on `cvar5MinIncomeRatio`; required by `max-income-cvar-constrained`.
+    target_cvar5_income_ratio: Option<f64>,
     search_min: Option<f64>,
     search_max: Option<f64>,
     tolerance: Option<f64>,
     max_iterations: Option<u32>,
     simulations_per_iteration: Option<u32>,
     final_simulations: Option<u32>,
+    secant_acceleration: Option<bool>,
+}
+
+/// A base simulation run once per withdrawal strategy with the same seed.
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(default, rename_all = "camelCase")]
+struct ComparePayload {
+    #[serde(flatten)]
+    simulation: SimulatePayload,
+    strategies: Vec<ApiWithdrawalStrategy>,
+}
+
+/// A base simulation rerun with one numeric payload field set to each of `values`.
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(default, rename_all = "camelCase")]
+struct SensitivityPayload {
+    #[serde(flatten)]
+    simulation: SimulatePayload,
+    parameter: String,
+    values: Vec<f64>,
+}
+
+/// Maps each payload key `/api/sensitivity` can sweep to the field it sets. Values are in
+/// the same units as the payload (percent for rates).
+macro_rules! sensitivity_parameters {
+    ($($key:literal => $field:ident,)*) => {
+        const SENSITIVITY_PARAMETERS: &[&str] = &[$($key),*];
+
+        fn set_sensitivity_parameter(payload: &mut SimulatePayload, key: &str, value: f64) -> bool {
+            match key {
+                $($key => payload.$field = Some(value),)*
+                _ => return false,
+            }
+            true
+        }
+    };
+}
+
+sensitivity_parameters! {
+    "targetIncome" => target_income,
+    "essentialIncome" => essential_income,
+    "bequestTarget" => bequest_target,
+    "isaStart" => isa_start,
+    "taxableStart" => taxable_start,
+    "pensionStart" => pension_start,
+    "cashStart" => cash_start,
+    "isaContribution" => isa_contribution,
+    "taxableContribution" => taxable_contribution,
+    "pensionContribution" => pension_contribution,
+    "contributionGrowth" => contribution_growth,
+    "isaMean" => isa_mean,
+    "isaVol" => isa_vol,
+    "taxableMean" => taxable_mean,
+    "taxableVol" => taxable_vol,
+    "pensionMean" => pension_mean,
+    "pensionVol" => pension_vol,
+    "inflationMean" => inflation_mean,
+    "inflationVol" => inflation_vol,
+    "isaFeeRate" => isa_fee_rate,
+    "taxableFeeRate" => taxable_fee_rate,
+    "pensionFeeRate" => pension_fee_rate,
+    "statePensionIncome" => state_pension_income,
+    "dbPensionIncome" => db_pension_income,
+    "rentalIncome" => rental_income,
+    "partTimeIncome" => part_time_income,
+    "mortgageAnnualPayment" => mortgage_annual_payment,
+    "spendingDeclineRate" => spending_decline_rate,
+    "successThreshold" => success_threshold,
+}
+
+/// The library defaults the CLI flags fall back to, so `fire --help`, the
+/// web API and `Inputs::default()` cannot drift apart.
+fn input_defaults() -> &'static Inputs {
+    static DEFAULTS: std::sync::LazyLock<Inputs> = std::sync::LazyLock::new(Inputs::default);
+    &DEFAULTS
+}
+
+/// Converts a stored fraction back to the percent the CLI takes, rounding
+/// away the float noise so `--help` shows e.g. `4` rather than `4.000000000000001`.
+fn percent(fraction: f64) -> f64 {
+    (fraction * 100.0 * 1e9).round() / 1e9
 }
 
 #[derive(Parser, Debug)]
@@ -340,11 +863,42 @@ struct Cli {
     taxable_cost_basis_start: f64,
     #[arg(long)]
     pension_start: f64,
-    #[arg(long, default_value_t = 0.0)]
+    #[arg(
+        long,
+        default_value_t = input_defaults().household,
+        action = clap::ArgAction::Set,
+        help = "Model a couple with a second pension pot taxed on the partner's own allowance"
+    )]
+    household: bool,
+    #[arg(
+        long,
+        default_value_t = input_defaults().partner_pension_start,
+        help = "Partner's pension pot at start (with --household true)"
+    )]
+    partner_pension_start: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().partner_pension_access_age,
+        help = "Your age when the partner can first draw their pension"
+    )]
+    partner_pension_access_age: u32,
+    #[arg(
+        long,
+        default_value_t = input_defaults().partner_personal_allowance,
+        help = "Partner's personal allowance (today's money)"
+    )]
+    partner_personal_allowance: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().marriage_allowance_transfer,
+        help = "Marriage allowance moved from the partner to you (today's money, up to 10% of --uk-personal-allowance)"
+    )]
+    marriage_allowance_transfer: f64,
+    #[arg(long, default_value_t = input_defaults().cash_start)]
     cash_start: f64,
     #[arg(
         long,
-        default_value_t = 0.0,
+        default_value_t = input_defaults().bond_ladder_start,
         help = "Starting value of bond ladder reserved for retirement withdrawals"
     )]
     bond_ladder_start: f64,
@@ -352,25 +906,82 @@ struct Cli {
     isa_annual_contribution: f64,
     #[arg(
         long,
-        default_value_t = 20000.0,
+        default_value_t = input_defaults().isa_annual_contribution_limit,
         help = "Annual ISA contribution allowance"
     )]
     isa_annual_contribution_limit: f64,
+    #[arg(long, default_value_t = input_defaults().lisa_start, help = "Starting Lifetime ISA balance")]
+    lisa_start: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().lisa_annual_contribution,
+        help = "Annual Lifetime ISA contribution before the 25% bonus; capped at 4000, stops at 50"
+    )]
+    lisa_annual_contribution: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().lisa_access_age,
+        help = "Age from which Lifetime ISA withdrawals avoid the 25% penalty"
+    )]
+    lisa_access_age: u32,
     #[arg(long, default_value_t = 0.0)]
     taxable_annual_contribution: f64,
     #[arg(long)]
     pension_annual_contribution: f64,
     #[arg(
         long,
-        default_value_t = 0.0,
+        default_value_t = percent(input_defaults().employer_match_rate),
+        help = "Employer pension top-up as a percent of your own pension contribution"
+    )]
+    employer_match_rate: f64,
+    #[arg(
+        long,
+        help = "Maximum employer pension top-up per year (default: uncapped)"
+    )]
+    employer_match_cap: Option<f64>,
+    #[arg(
+        long,
+        default_value_t = input_defaults().pension_annual_allowance,
+        help = "Annual allowance for total pension contributions (own plus employer)"
+    )]
+    pension_annual_allowance: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().pension_overflow_to_taxable,
+        action = clap::ArgAction::Set,
+        help = "Redirect own pension contributions above the annual allowance to the taxable account (false drops them)"
+    )]
+    pension_overflow_to_taxable: bool,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().contribution_growth_rate),
         help = "Annual growth rate for all pre-retirement contributions in percent (e.g. pay rises)"
     )]
     contribution_growth_rate: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().contributions_track_inflation,
+        action = clap::ArgAction::Set,
+        help = "Raise contributions with realized inflation instead of --contribution-growth-rate, keeping them constant in real terms"
+    )]
+    contributions_track_inflation: bool,
+    #[arg(
+        long,
+        default_value_t = input_defaults().coast_contribution_fraction,
+        help = "Share of full contributions (0 to 1) still paid after the coast age until retirement, e.g. 0.5 for part-time work"
+    )]
+    coast_contribution_fraction: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().pre_retirement_earnings,
+        help = "Taxable earnings per year before retirement in today's money; pre-retirement dividends and gains are taxed on top of them"
+    )]
+    pre_retirement_earnings: f64,
     #[arg(long, help = "Expected annual ISA return in percent, e.g. 5")]
     isa_growth_rate: f64,
     #[arg(
         long,
-        default_value_t = 12.0,
+        default_value_t = percent(input_defaults().isa_return_vol),
         help = "ISA annual return volatility in percent"
     )]
     isa_return_volatility: f64,
@@ -388,1334 +999,5997 @@ struct Cli {
     pension_growth_rate: f64,
     #[arg(
         long,
-        default_value_t = 12.0,
+        default_value_t = percent(input_defaults().pension_return_vol),
         help = "Pension annual return volatility in percent"
     )]
     pension_return_volatility: f64,
     #[arg(
         long,
-        default_value_t = 0.8,
+        default_value_t = input_defaults().return_correlation,
         help = "Correlation between ISA and pension returns"
     )]
     return_correlation: f64,
     #[arg(
         long,
-        default_value_t = 20.0,
-        help = "Capital gains tax rate on taxable account gains in percent"
+        default_value_t = input_defaults().isa_taxable_correlation,
+        help = "Correlation between ISA and taxable account returns"
     )]
-    capital_gains_tax_rate: f64,
+    isa_taxable_correlation: f64,
     #[arg(
         long,
-        default_value_t = 3000.0,
-        help = "Annual CGT allowance when realizing gains"
+        help = "Correlation between taxable account and pension returns; defaults to the product of the ISA correlations"
     )]
-    capital_gains_allowance: f64,
+    taxable_pension_correlation: Option<f64>,
     #[arg(
         long,
-        default_value_t = 1.0,
-        help = "Annual tax drag on taxable account returns in percent"
+        allow_hyphen_values = true,
+        help = "ISA loading on a common equity factor; with the taxable and pension betas it replaces the correlations"
     )]
-    taxable_return_tax_drag: f64,
+    isa_beta: Option<f64>,
     #[arg(
         long,
-        value_enum,
-        default_value_t = CliPensionTaxMode::UkBands,
-        help = "Pension tax model: UK progressive bands or legacy flat rate"
+        allow_hyphen_values = true,
+        help = "Taxable account loading on the common equity factor"
     )]
-    pension_tax_mode: CliPensionTaxMode,
+    taxable_beta: Option<f64>,
     #[arg(
         long,
-        default_value_t = 20.0,
-        help = "Flat pension tax rate in percent, used when --pension-tax-mode=flat-rate"
+        allow_hyphen_values = true,
+        help = "Pension loading on the common equity factor"
     )]
-    pension_income_tax_rate: f64,
+    pension_beta: Option<f64>,
     #[arg(
         long,
-        default_value_t = 12570.0,
-        help = "UK personal allowance (today's money)"
+        value_enum,
+        default_value_t = CliReturnDistribution::Normal,
+        help = "Distribution of annual return shocks: normal or fat-tailed student-t"
     )]
-    uk_personal_allowance: f64,
+    return_distribution: CliReturnDistribution,
     #[arg(
         long,
-        default_value_t = 50270.0,
-        help = "Upper income bound for UK basic rate band (today's money)"
+        value_enum,
+        default_value_t = CliMarketModel::Parametric,
+        help = "Market model: parametric draws, resampled historical years (1970-2023 US data), a replayed --market-path-year sequence, parametric draws with GARCH volatility, a normal/crisis regime chain or an ensemble of parametric and bootstrap scenarios"
     )]
-    uk_basic_rate_limit: f64,
+    market_model: CliMarketModel,
     #[arg(
-        long,
-        default_value_t = 125140.0,
-        help = "Upper income bound for UK higher rate band (today's money)"
+        long = "market-path-year",
+        value_name = "ISA:TAXABLE:PENSION:INFLATION",
+        value_parser = parse_market_path_year,
+        help = "One year of returns and inflation in percent for --market-model=deterministic; repeat in year order from --current-age, the last year repeating"
     )]
-    uk_higher_rate_limit: f64,
-    #[arg(long, default_value_t = 20.0, help = "UK basic tax rate in percent")]
-    uk_basic_rate: f64,
-    #[arg(long, default_value_t = 40.0, help = "UK higher tax rate in percent")]
-    uk_higher_rate: f64,
+    market_path: Vec<(f64, f64, f64, f64)>,
     #[arg(
         long,
-        default_value_t = 45.0,
-        help = "UK additional tax rate in percent"
+        default_value_t = input_defaults().bootstrap_block_years,
+        help = "Consecutive historical years drawn per block when --market-model=historical-bootstrap or ensemble (1 = independent years)"
     )]
-    uk_additional_rate: f64,
+    bootstrap_block_years: u32,
     #[arg(
         long,
-        default_value_t = 100000.0,
-        help = "Income where personal allowance taper starts (today's money)"
+        default_value_t = input_defaults().market_burn_in_years,
+        help = "Years to run the GARCH variance or crisis regime forward before each scenario, so it starts in its long-run state (0 starts calm)"
     )]
-    uk_allowance_taper_start: f64,
+    market_burn_in_years: u32,
     #[arg(
         long,
-        default_value_t = 125140.0,
-        help = "Income where personal allowance is fully tapered away (today's money)"
+        default_value_t = 0.5,
+        help = "Share of scenarios (0-1) that resample history when --market-model=ensemble; the rest are parametric"
     )]
-    uk_allowance_taper_end: f64,
-    #[arg(long, default_value_t = 67, help = "State pension start age")]
-    state_pension_start_age: u32,
+    bootstrap_fraction: f64,
     #[arg(
         long,
-        default_value_t = 0.0,
-        help = "Annual state pension income in today's money"
+        default_value_t = 5.0,
+        help = "Student-t degrees of freedom, used when --return-distribution=student-t"
     )]
-    state_pension_annual_income: f64,
+    student_t_dof: f64,
     #[arg(
         long,
-        default_value_t = 2.5,
-        help = "Expected annual inflation in percent"
+        default_value_t = 0.1,
+        help = "Weight of last year's squared ISA shock in this year's variance when --market-model=garch"
     )]
-    inflation_rate: f64,
-    #[arg(long, default_value_t = 1.0, help = "Inflation volatility in percent")]
-    inflation_volatility: f64,
-    #[arg(long)]
-    target_annual_income: f64,
+    garch_alpha: f64,
     #[arg(
         long,
-        default_value_t = 0.0,
-        help = "Annual mortgage payment in today's money while mortgage is active"
+        default_value_t = 0.85,
+        help = "Weight of last year's variance in this year's variance when --market-model=garch; alpha + beta must be < 1"
     )]
-    mortgage_annual_payment: f64,
+    garch_beta: f64,
     #[arg(
         long,
-        help = "Age when mortgage payments stop; required when --mortgage-annual-payment > 0"
+        default_value_t = 12.0,
+        help = "Long-run ISA return volatility in percent when --market-model=garch; replaces --isa-return-volatility, other pots scale with it"
     )]
-    mortgage_end_age: Option<u32>,
-    #[arg(long, default_value_t = 75, help = "Latest retirement age to test")]
-    max_age: u32,
-    #[arg(long, default_value_t = 95, help = "Age to fund through")]
-    horizon_age: u32,
-    #[arg(long, default_value_t = 10000)]
-    simulations: u32,
+    garch_long_run_vol: f64,
     #[arg(
         long,
-        default_value_t = 90.0,
-        help = "Required Monte Carlo success probability in percent"
+        default_value_t = 0.1,
+        help = "Chance each normal year that the next is a crisis year when --market-model=regime-switching"
     )]
-    success_threshold: f64,
-    #[arg(long, default_value_t = 42)]
-    seed: u64,
-    #[arg(long, default_value_t = -5.0, help = "Bad-year real return threshold in percent")]
-    bad_year_threshold: f64,
+    normal_to_crisis_probability: f64,
     #[arg(
         long,
-        default_value_t = 10.0,
-        help = "Good-year real return threshold in percent"
+        default_value_t = 0.5,
+        help = "Chance each crisis year that the next is a normal year when --market-model=regime-switching"
     )]
-    good_year_threshold: f64,
+    crisis_to_normal_probability: f64,
     #[arg(
         long,
-        default_value_t = 10.0,
-        help = "Bad-year spending cut in percent"
+        default_value_t = -10.0,
+        allow_hyphen_values = true,
+        help = "Mean return of every pot in crisis years in percent when --market-model=regime-switching"
     )]
-    bad_year_cut: f64,
+    crisis_return_mean: f64,
     #[arg(
         long,
-        default_value_t = 5.0,
-        help = "Good-year spending raise in percent"
+        default_value_t = 25.0,
+        help = "Return volatility of every pot in crisis years in percent when --market-model=regime-switching"
     )]
-    good_year_raise: f64,
+    crisis_return_volatility: f64,
     #[arg(
         long,
-        default_value_t = 80.0,
-        help = "Minimum income floor vs target in percent"
+        default_value_t = percent(input_defaults().cgt_basic_rate),
+        help = "CGT rate in percent on gains that fit in the unused basic-rate band"
     )]
-    min_income_floor: f64,
+    cgt_basic_rate: f64,
     #[arg(
         long,
-        default_value_t = 130.0,
-        help = "Maximum income ceiling vs target in percent"
+        default_value_t = percent(input_defaults().cgt_higher_rate),
+        help = "CGT rate in percent on gains above the basic-rate band"
     )]
-    max_income_ceiling: f64,
+    cgt_higher_rate: f64,
     #[arg(
         long,
-        value_enum,
-        default_value_t = CliWithdrawalStrategy::Guardrails,
-        help = "Withdrawal strategy: guardrails, Guyton-Klinger, VPW, floor+upside, or bucket"
+        conflicts_with_all = ["cgt_basic_rate", "cgt_higher_rate"],
+        help = "Flat CGT rate in percent for both bands; shorthand for --cgt-basic-rate and --cgt-higher-rate"
     )]
-    withdrawal_strategy: CliWithdrawalStrategy,
+    capital_gains_tax_rate: Option<f64>,
     #[arg(
         long,
-        default_value_t = 80.0,
-        help = "Guyton-Klinger lower guardrail as percent of initial withdrawal rate"
+        default_value_t = input_defaults().capital_gains_allowance,
+        help = "Annual CGT allowance when realizing gains"
     )]
-    gk_lower_guardrail: f64,
+    capital_gains_allowance: f64,
     #[arg(
         long,
-        default_value_t = 120.0,
-        help = "Guyton-Klinger upper guardrail as percent of initial withdrawal rate"
+        default_value_t = percent(input_defaults().taxable_return_tax_drag),
+        help = "Annual tax drag on taxable account returns in percent"
     )]
-    gk_upper_guardrail: f64,
+    taxable_return_tax_drag: f64,
     #[arg(
         long,
-        default_value_t = 3.5,
-        help = "VPW expected real return assumption in percent"
+        default_value_t = percent(input_defaults().taxable_dividend_yield),
+        help = "Dividend yield of the taxable account in percent (part of its return), taxed each year"
     )]
-    vpw_expected_real_return: f64,
+    taxable_dividend_yield: f64,
     #[arg(
         long,
-        default_value_t = 50.0,
-        help = "Floor+upside: share of positive real returns converted into spending growth in percent"
+        default_value_t = input_defaults().dividend_allowance,
+        help = "Annual dividend allowance taxed at 0%"
     )]
-    floor_upside_capture: f64,
+    dividend_allowance: f64,
     #[arg(
         long,
-        default_value_t = 2.0,
-        help = "Bucket strategy target cash reserve in years of spending"
+        default_value_t = percent(input_defaults().dividend_basic_rate),
+        help = "Dividend tax rate in the basic band in percent"
     )]
-    bucket_target_years: f64,
+    dividend_basic_rate: f64,
     #[arg(
         long,
-        default_value_t = 10.0,
-        help = "In good years, extra withdrawal to store in cash buffer (percent of spending)"
+        default_value_t = percent(input_defaults().dividend_higher_rate),
+        help = "Dividend tax rate in the higher band in percent"
     )]
-    good_year_extra_buffer_withdrawal: f64,
-    #[arg(long, default_value_t = 1.0, help = "Cash buffer growth in percent")]
-    cash_growth_rate: f64,
+    dividend_higher_rate: f64,
     #[arg(
         long,
-        default_value_t = 3.0,
-        help = "Bond ladder annual yield in percent"
+        default_value_t = percent(input_defaults().dividend_additional_rate),
+        help = "Dividend tax rate in the additional band in percent"
+    )]
+    dividend_additional_rate: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().isa_fee_rate),
+        help = "Annual ISA fund fee (TER/platform) in percent"
+    )]
+    isa_fee_rate: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().taxable_fee_rate),
+        help = "Annual taxable account fund fee (TER/platform) in percent"
+    )]
+    taxable_fee_rate: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().pension_fee_rate),
+        help = "Annual pension fund fee (TER/platform) in percent"
+    )]
+    pension_fee_rate: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().glide_start_equity),
+        help = "Equity share of each pot at --current-age in percent; the rest earns the bond return"
+    )]
+    glide_start_equity: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().glide_end_equity),
+        help = "Equity share of each pot at --glide-end-age in percent"
+    )]
+    glide_end_equity: f64,
+    #[arg(
+        long,
+        help = "Age at which the glide path reaches --glide-end-equity (default: --horizon-age)"
+    )]
+    glide_end_age: Option<u32>,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = input_defaults().pension_tax_mode.into(),
+        help = "Pension tax model: UK progressive bands or legacy flat rate"
+    )]
+    pension_tax_mode: CliPensionTaxMode,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().pension_flat_tax_rate),
+        help = "Flat pension tax rate in percent, used when --pension-tax-mode=flat-rate"
+    )]
+    pension_income_tax_rate: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().pension_tax_free_pct),
+        help = "Share of the pension value at access that can be drawn tax-free, in percent"
+    )]
+    pension_tax_free_pct: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().uk_personal_allowance,
+        help = "UK personal allowance (today's money)"
+    )]
+    uk_personal_allowance: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().uk_basic_rate_limit,
+        help = "Upper income bound for UK basic rate band (today's money)"
+    )]
+    uk_basic_rate_limit: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().uk_higher_rate_limit,
+        help = "Upper income bound for UK higher rate band (today's money)"
+    )]
+    uk_higher_rate_limit: f64,
+    #[arg(long, default_value_t = percent(input_defaults().uk_basic_rate), help = "UK basic tax rate in percent")]
+    uk_basic_rate: f64,
+    #[arg(long, default_value_t = percent(input_defaults().uk_higher_rate), help = "UK higher tax rate in percent")]
+    uk_higher_rate: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().uk_additional_rate),
+        help = "UK additional tax rate in percent"
+    )]
+    uk_additional_rate: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().uk_allowance_taper_start,
+        help = "Income where personal allowance taper starts (today's money)"
+    )]
+    uk_allowance_taper_start: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().uk_allowance_taper_end,
+        help = "Income where personal allowance is fully tapered away (today's money)"
+    )]
+    uk_allowance_taper_end: f64,
+    #[arg(
+        long,
+        help = "Keep income tax thresholds at today's nominal values before this age (default: index every year)"
+    )]
+    thresholds_frozen_until_age: Option<u32>,
+    #[arg(
+        long,
+        default_value_t = input_defaults().scottish_starter_rate_limit,
+        help = "Upper income bound for the Scottish starter rate band (today's money)"
+    )]
+    scottish_starter_rate_limit: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().scottish_basic_rate_limit,
+        help = "Upper income bound for the Scottish basic rate band (today's money)"
+    )]
+    scottish_basic_rate_limit: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().scottish_intermediate_rate_limit,
+        help = "Upper income bound for the Scottish intermediate rate band (today's money)"
+    )]
+    scottish_intermediate_rate_limit: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().scottish_higher_rate_limit,
+        help = "Upper income bound for the Scottish higher rate band (today's money)"
+    )]
+    scottish_higher_rate_limit: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().scottish_advanced_rate_limit,
+        help = "Upper income bound for the Scottish advanced rate band (today's money)"
+    )]
+    scottish_advanced_rate_limit: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().scottish_starter_rate),
+        help = "Scottish starter tax rate in percent"
+    )]
+    scottish_starter_rate: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().scottish_basic_rate),
+        help = "Scottish basic tax rate in percent"
+    )]
+    scottish_basic_rate: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().scottish_intermediate_rate),
+        help = "Scottish intermediate tax rate in percent"
+    )]
+    scottish_intermediate_rate: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().scottish_higher_rate),
+        help = "Scottish higher tax rate in percent"
+    )]
+    scottish_higher_rate: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().scottish_advanced_rate),
+        help = "Scottish advanced tax rate in percent"
+    )]
+    scottish_advanced_rate: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().scottish_top_rate),
+        help = "Scottish top tax rate in percent"
+    )]
+    scottish_top_rate: f64,
+    #[arg(long, default_value_t = input_defaults().state_pension_start_age, help = "State pension start age")]
+    state_pension_start_age: u32,
+    #[arg(
+        long,
+        default_value_t = input_defaults().state_pension_annual_income,
+        help = "Annual state pension income in today's money"
+    )]
+    state_pension_annual_income: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().state_pension_real_growth),
+        help = "Real annual growth of the state pension in percent, e.g. the triple lock over CPI"
+    )]
+    state_pension_real_growth: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().state_pension_growth_before_start,
+        action = clap::ArgAction::Set,
+        help = "Apply --state-pension-real-growth from today rather than from --state-pension-start-age"
+    )]
+    state_pension_growth_before_start: bool,
+    #[arg(
+        long,
+        default_value_t = input_defaults().state_pension_deferral_years,
+        help = "Years to defer claiming the state pension past --state-pension-start-age"
+    )]
+    state_pension_deferral_years: u32,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().state_pension_deferral_uplift_rate),
+        help = "Compound uplift per deferred year of state pension in percent"
+    )]
+    state_pension_deferral_uplift_rate: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().db_pension_start_age,
+        help = "Age the defined-benefit pension starts paying"
+    )]
+    db_pension_start_age: u32,
+    #[arg(
+        long,
+        default_value_t = input_defaults().db_pension_annual_income,
+        help = "Annual defined-benefit pension income in today's money"
+    )]
+    db_pension_annual_income: f64,
+    #[arg(
+        long,
+        help = "Age at which to buy a level real annuity from the pension (default: never)"
+    )]
+    annuity_purchase_age: Option<u32>,
+    #[arg(
+        long,
+        default_value_t = input_defaults().annuity_purchase_amount,
+        help = "Pension amount in today's money used to buy the annuity"
+    )]
+    annuity_purchase_amount: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().annuity_rate),
+        help = "Annuity income per year as a percent of the purchase amount"
+    )]
+    annuity_rate: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().conversion_ladder_annual_amount,
+        help = "Pension in today's money converted into the ISA each year of the conversion ladder"
+    )]
+    conversion_ladder_annual_amount: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().conversion_ladder_start_age,
+        help = "First age of the pension conversion ladder"
+    )]
+    conversion_ladder_start_age: u32,
+    #[arg(
+        long,
+        default_value_t = input_defaults().conversion_ladder_end_age,
+        help = "Age at which the pension conversion ladder stops (exclusive)"
+    )]
+    conversion_ladder_end_age: u32,
+    #[arg(
+        long,
+        default_value_t = input_defaults().rental_income_start_age,
+        help = "Age from which net rental income is received"
+    )]
+    rental_income_start_age: u32,
+    #[arg(
+        long,
+        default_value_t = input_defaults().rental_annual_income,
+        help = "Annual net rental income in today's money, taxed as non-savings income"
+    )]
+    rental_annual_income: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().rental_growth_rate),
+        help = "Real annual growth of rental income in percent"
+    )]
+    rental_growth_rate: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().part_time_annual_income,
+        help = "Annual part-time earnings in today's money during early retirement, taxed as non-savings income"
+    )]
+    part_time_annual_income: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().part_time_start_age,
+        help = "Age from which part-time earnings are received"
+    )]
+    part_time_start_age: u32,
+    #[arg(long, default_value_t = input_defaults().part_time_end_age, help = "Age when part-time earnings stop")]
+    part_time_end_age: u32,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().inflation_mean),
+        help = "Expected annual inflation in percent"
+    )]
+    inflation_rate: f64,
+    #[arg(long, default_value_t = percent(input_defaults().inflation_vol), help = "Inflation volatility in percent")]
+    inflation_volatility: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().inflation_return_correlation,
+        help = "Correlation between inflation and ISA (equity) returns"
+    )]
+    inflation_return_correlation: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().return_clamp_min),
+        help = "Lower bound on each sampled annual pot return in percent"
+    )]
+    return_clamp_min: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().return_clamp_max),
+        help = "Upper bound on each sampled annual pot return in percent"
+    )]
+    return_clamp_max: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().inflation_clamp_min),
+        help = "Lower bound on each sampled annual inflation rate in percent"
+    )]
+    inflation_clamp_min: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().inflation_clamp_max),
+        help = "Upper bound on each sampled annual inflation rate in percent"
+    )]
+    inflation_clamp_max: f64,
+    #[arg(long)]
+    target_annual_income: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().essential_annual_income,
+        help = "Essential annual spending in today's money; when > 0 it sets the spending floor and only shortfalls below it count as failure"
+    )]
+    essential_annual_income: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().spending_decline_rate),
+        help = "Real decline in target spending per year of retirement in percent"
+    )]
+    spending_decline_rate: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().care_cost_age,
+        help = "Age from which late-life care costs raise target spending"
+    )]
+    care_cost_age: u32,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().care_cost_uplift),
+        help = "Uplift to target spending from --care-cost-age in percent"
+    )]
+    care_cost_uplift: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().active_phase_end_age,
+        help = "Age at which the active (go-go) spending phase ends"
+    )]
+    active_phase_end_age: u32,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().active_phase_uplift),
+        allow_hyphen_values = true,
+        help = "Change to target spending before --active-phase-end-age in percent"
+    )]
+    active_phase_uplift: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().late_phase_start_age,
+        help = "Age at which the late (no-go) spending phase starts"
+    )]
+    late_phase_start_age: u32,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().late_phase_uplift),
+        allow_hyphen_values = true,
+        help = "Change to target spending from --late-phase-start-age in percent, usually negative"
+    )]
+    late_phase_uplift: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().mortgage_annual_payment,
+        help = "Annual mortgage payment in today's money while mortgage is active"
+    )]
+    mortgage_annual_payment: f64,
+    #[arg(
+        long,
+        help = "Age when mortgage payments stop; required when --mortgage-annual-payment > 0"
+    )]
+    mortgage_end_age: Option<u32>,
+    #[arg(
+        long = "debt",
+        value_name = "PAYMENT:END_AGE",
+        value_parser = parse_debt,
+        help = "Fixed annual payment in today's money due until an age, e.g. a car loan; repeat for several"
+    )]
+    debts: Vec<(f64, u32)>,
+    #[arg(
+        long,
+        default_value_t = input_defaults().mortgage_balance,
+        help = "Mortgage balance owed today, amortized at --mortgage-rate over --mortgage-term-years; an alternative to --mortgage-annual-payment"
+    )]
+    mortgage_balance: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().mortgage_rate),
+        allow_hyphen_values = true,
+        help = "Nominal mortgage interest rate in percent for --mortgage-balance"
+    )]
+    mortgage_rate: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().mortgage_term_years,
+        help = "Years left on the mortgage for --mortgage-balance"
+    )]
+    mortgage_term_years: u32,
+    #[arg(
+        long = "mortgage-overpayment",
+        value_name = "AGE:AMOUNT",
+        value_parser = parse_lump_expense,
+        help = "Lump-sum mortgage overpayment in today's money at a given age, shortening the term; repeat for several"
+    )]
+    mortgage_overpayments: Vec<(u32, f64)>,
+    #[arg(
+        long = "lump-expense",
+        value_name = "AGE:AMOUNT",
+        value_parser = parse_lump_expense,
+        help = "One-off expense in today's money at a given age; repeat for several"
+    )]
+    lump_expenses: Vec<(u32, f64)>,
+    #[arg(
+        long,
+        default_value_t = input_defaults().max_retirement_age,
+        help = "Latest retirement age to test"
+    )]
+    max_age: u32,
+    #[arg(long, default_value_t = input_defaults().horizon_age, help = "Age to fund through")]
+    horizon_age: u32,
+    #[arg(
+        long,
+        default_value_t = input_defaults().bequest_target,
+        help = "Minimum estate in today's money that must remain at --horizon-age"
+    )]
+    bequest_target: f64,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = input_defaults().success_criterion.into(),
+        help = "What fails a scenario: missing planned spending, missing the essential spend (planned spending without one) or running out of money"
+    )]
+    success_criterion: CliSuccessCriterion,
+    #[arg(long, default_value_t = input_defaults().simulations)]
+    simulations: u32,
+    #[arg(
+        long,
+        default_value_t = input_defaults().threads,
+        help = "Worker threads for the simulation (0 = all cores, 1 = sequential); results do not depend on this"
+    )]
+    threads: usize,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().success_threshold),
+        help = "Required Monte Carlo success probability in percent"
+    )]
+    success_threshold: f64,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = input_defaults().selection_confidence.into(),
+        help = "Success rate compared with --success-threshold when selecting an age: the observed rate, or the lower (conservative) or upper (optimistic) end of its 95% confidence interval"
+    )]
+    selection_confidence: CliSelectionConfidence,
+    #[arg(
+        long,
+        help = "CRRA risk aversion (gamma) to score each age by expected utility of real spending; 1 is log utility"
+    )]
+    risk_aversion: Option<f64>,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().utility_discount_rate),
+        allow_hyphen_values = true,
+        help = "Real annual rate in percent for discounting later years' utility with --risk-aversion"
+    )]
+    utility_discount_rate: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().utility_spending_floor,
+        help = "Real annual spending below which --risk-aversion utility stops falling; years after a failure score here"
+    )]
+    utility_spending_floor: f64,
+    #[arg(long, help = "Random seed; overrides --seed-label [default: 42]")]
+    seed: Option<u64>,
+    #[arg(
+        long,
+        help = "Name to derive the random seed from, e.g. my-plan-v2, so a shared scenario is easy to reproduce"
+    )]
+    seed_label: Option<String>,
+    #[arg(
+        long,
+        default_value_t = input_defaults().age_invariant_draws,
+        action = clap::ArgAction::Set,
+        help = "Give every candidate age the same market paths (common random numbers)"
+    )]
+    age_invariant_draws: bool,
+    #[arg(
+        long,
+        default_value_t = input_defaults().stratified_inflation,
+        action = clap::ArgAction::Set,
+        help = "Draw inflation shocks from one stratum per simulation each year"
+    )]
+    stratified_inflation: bool,
+    #[arg(long, default_value_t = percent(input_defaults().bad_year_threshold), help = "Bad-year real return threshold in percent")]
+    bad_year_threshold: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().good_year_threshold),
+        help = "Good-year real return threshold in percent"
+    )]
+    good_year_threshold: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().bad_year_cut),
+        help = "Bad-year spending cut in percent"
+    )]
+    bad_year_cut: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().good_year_raise),
+        help = "Good-year spending raise in percent"
+    )]
+    good_year_raise: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().min_income_floor),
+        help = "Minimum income floor vs target in percent"
+    )]
+    min_income_floor: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().max_income_ceiling),
+        help = "Maximum income ceiling vs target in percent"
+    )]
+    max_income_ceiling: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().min_real_spending_floor,
+        help = "Minimum annual spending in today's money, whatever the target; raises --min-income-floor when it is lower"
+    )]
+    min_real_spending_floor: f64,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = input_defaults().withdrawal_strategy.into(),
+        help = "Withdrawal strategy: guardrails, Guyton-Klinger, VPW, floor+upside, bucket, fixed-real, constant-percentage, cape-based, or amortization"
+    )]
+    withdrawal_strategy: CliWithdrawalStrategy,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().gk_lower_guardrail),
+        help = "Guyton-Klinger lower guardrail as percent of initial withdrawal rate"
+    )]
+    gk_lower_guardrail: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().gk_upper_guardrail),
+        help = "Guyton-Klinger upper guardrail as percent of initial withdrawal rate"
+    )]
+    gk_upper_guardrail: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().gk_adjustment),
+        help = "Guyton-Klinger capital preservation cut / prosperity raise in percent"
+    )]
+    gk_adjustment: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().vpw_expected_real_return),
+        help = "VPW expected real return assumption in percent"
+    )]
+    vpw_expected_real_return: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().constant_withdrawal_rate),
+        help = "Constant-percentage strategy: share of spendable assets withdrawn each year in percent"
+    )]
+    constant_withdrawal_rate: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().cape_a),
+        help = "CAPE-based strategy: base withdrawal rate `a` in percent (rate = a + b / CAPE)"
+    )]
+    cape_a: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().cape_b,
+        help = "CAPE-based strategy: multiplier `b` on the CAPE earnings yield (rate = a + b / CAPE)"
+    )]
+    cape_b: f64,
+    #[arg(long, default_value_t = input_defaults().cape_start, help = "CAPE valuation today")]
+    cape_start: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().cape_long_run_mean,
+        help = "Long-run CAPE that valuations revert toward"
+    )]
+    cape_long_run_mean: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().cape_reversion_speed),
+        help = "Share of the gap to the long-run CAPE closed each year in percent"
+    )]
+    cape_reversion_speed: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().floor_upside_capture),
+        help = "Floor+upside: share of positive real returns converted into spending growth in percent"
+    )]
+    floor_upside_capture: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().bucket_target_years,
+        help = "Bucket strategy target cash reserve in years of spending"
+    )]
+    bucket_target_years: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().good_year_extra_buffer_withdrawal),
+        help = "In good years, extra withdrawal to store in cash buffer (percent of spending)"
+    )]
+    good_year_extra_buffer_withdrawal: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().min_cash_months,
+        help = "Cash floor in months of spending that good years refill under every strategy"
+    )]
+    min_cash_months: f64,
+    #[arg(long, default_value_t = percent(input_defaults().cash_growth_rate), help = "Cash buffer growth in percent")]
+    cash_growth_rate: f64,
+    #[arg(
+        long,
+        default_value_t = percent(input_defaults().bond_ladder_yield),
+        help = "Bond ladder annual yield in percent"
     )]
     bond_ladder_yield: f64,
     #[arg(
         long,
-        default_value_t = 10,
-        help = "Bond ladder drawdown horizon in retirement years"
+        default_value_t = percent(input_defaults().bond_ladder_yield_vol),
+        help = "Bond ladder return volatility in percent (0 = fixed yield)"
+    )]
+    bond_ladder_yield_vol: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().bond_ladder_inflation_correlation,
+        allow_hyphen_values = true,
+        help = "Correlation between bond ladder returns and inflation"
+    )]
+    bond_ladder_inflation_correlation: f64,
+    #[arg(
+        long,
+        default_value_t = input_defaults().bond_ladder_years,
+        help = "Bond ladder drawdown horizon in retirement years"
+    )]
+    bond_ladder_years: u32,
+    #[arg(
+        long,
+        default_value_t = input_defaults().bond_ladder_reinvest,
+        action = clap::ArgAction::Set,
+        help = "Roll unspent ladder rungs back in to keep --bond-ladder-years as a rolling length"
+    )]
+    bond_ladder_reinvest: bool,
+    #[arg(
+        long,
+        default_value_t = input_defaults().bond_ladder_target_at_retirement,
+        help = "Bond ladder size in today's money to build at retirement by selling ISA, then taxable, holdings"
+    )]
+    bond_ladder_target_at_retirement: f64,
+    #[arg(long, value_enum, default_value_t = input_defaults().post_access_withdrawal_order.into())]
+    post_access_withdrawal_order: CliWithdrawalOrder,
+    #[arg(
+        long,
+        value_enum,
+        help = "Withdrawal order before pension access (default: --post-access-withdrawal-order without its pension step)"
+    )]
+    pre_access_withdrawal_order: Option<CliWithdrawalOrder>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct ApiOptions {
+    mode: AnalysisMode,
+    coast_retirement_age: Option<u32>,
+    /// Candidate age to trace instead of the selected (or best) one.
+    cashflow_age: Option<u32>,
+    /// Skip the cashflow trace and return only the headline numbers.
+    summary_only: bool,
+}
+
+/// Joins validation messages for callers that report a single string, such as the CLI.
+fn validation_message(errors: &[ValidationError]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[derive(Debug)]
+struct ApiRequest {
+    inputs: Inputs,
+    options: ApiOptions,
+}
+
+#[derive(Copy, Clone)]
+struct CashflowResponse<'a> {
+    candidate_age: u32,
+    retirement_age: u32,
+    contribution_stop_age: u32,
+    years: &'a [CashflowYearResult],
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SimulateResponse {
+    mode: ResponseMode,
+    withdrawal_policy: ApiWithdrawalStrategy,
+    coast_retirement_age: Option<u32>,
+    success_threshold: f64,
+    selected_retirement_age: Option<u32>,
+    best_retirement_age: u32,
+    /// Headline numbers for the selected (or best) age; only in `summaryOnly` responses,
+    /// which leave out the cashflow fields and both arrays.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<AgeSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cashflow_candidate_age: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cashflow_retirement_age: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cashflow_contribution_stop_age: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    age_results: Option<Vec<AgeResult>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cashflow_years: Option<Vec<CashflowYearResult>>,
+    meta: RunMeta,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AgeSummary {
+    retirement_age: u32,
+    success_rate: f64,
+    success_ci_half_width: f64,
+    median_retirement_pot: f64,
+    median_terminal_pot: f64,
+    p10_terminal_pot: f64,
+    p10_min_income_ratio: f64,
+    median_avg_income_ratio: f64,
+}
+
+impl From<&AgeResult> for AgeSummary {
+    fn from(result: &AgeResult) -> Self {
+        Self {
+            retirement_age: result.retirement_age,
+            success_rate: result.success_rate,
+            success_ci_half_width: result.success_ci_half_width,
+            median_retirement_pot: result.median_retirement_pot,
+            median_terminal_pot: result.median_terminal_pot,
+            p10_terminal_pot: result.p10_terminal_pot,
+            p10_min_income_ratio: result.p10_min_income_ratio,
+            median_avg_income_ratio: result.median_avg_income_ratio,
+        }
+    }
+}
+
+/// What produced a response: equal `inputsHash`es mean the runs used identical resolved
+/// inputs, including every default the request left out.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RunMeta {
+    seed: u64,
+    simulations: u32,
+    horizon_age: u32,
+    engine_version: &'static str,
+    inputs_hash: String,
+}
+
+impl RunMeta {
+    fn for_inputs(inputs: &Inputs) -> Self {
+        Self {
+            seed: inputs.seed,
+            simulations: inputs.simulations,
+            horizon_age: inputs.horizon_age,
+            engine_version: env!("CARGO_PKG_VERSION"),
+            inputs_hash: inputs_hash(inputs),
+        }
+    }
+}
+
+/// 64-bit FNV-1a of the inputs' JSON, as 16 hex digits. The JSON lists fields in
+/// declaration order and writes floats in shortest round-trip form, so the hash is
+/// stable for equal inputs but changes when a field is added to `Inputs`.
+fn inputs_hash(inputs: &Inputs) -> String {
+    let json = serde_json::to_string(inputs).expect("inputs hold only plain data");
+    let hash = fnv1a(json.as_bytes());
+    format!("{hash:016x}")
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+const DEFAULT_SEED: u64 = 42;
+
+/// An explicit `--seed` wins; otherwise a label is hashed into the seed, so the same name
+/// always replays the same scenarios.
+fn resolve_seed(seed: Option<u64>, seed_label: Option<&str>) -> u64 {
+    seed.or_else(|| seed_label.map(|label| fnv1a(label.as_bytes())))
+        .unwrap_or(DEFAULT_SEED)
+}
+
+/// One swept value of `/api/sensitivity`. Success rates are fractions, as in `ageResults`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SensitivityPointResponse {
+    value: f64,
+    selected_retirement_age: Option<u32>,
+    selected_success_rate: Option<f64>,
+    best_retirement_age: u32,
+    best_success_rate: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SolveGoalIterationResponse {
+    iteration: u32,
+    lower_bound: f64,
+    upper_bound: f64,
+    candidate_value: f64,
+    success_rate: f64,
+    success_ci_half_width: f64,
+    cvar5_min_income_ratio: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SolveGoalResponse {
+    goal_type: ApiGoalType,
+    target_retirement_age: u32,
+    target_success_threshold: f64,
+    target_cvar5_income_ratio: f64,
+    search_min: f64,
+    search_max: f64,
+    tolerance: f64,
+    max_iterations: u32,
+    simulations_per_iteration: u32,
+    final_simulations: u32,
+    solved_value: Option<f64>,
+    solved_contribution_total: Option<f64>,
+    solved_contribution_isa: Option<f64>,
+    solved_contribution_taxable: Option<f64>,
+    solved_contribution_pension: Option<f64>,
+    achieved_success_rate: Option<f64>,
+    achieved_success_ci_half_width: Option<f64>,
+    achieved_cvar5_min_income_ratio: Option<f64>,
+    converged: bool,
+    feasible: bool,
+    message: String,
+    iterations: Vec<SolveGoalIterationResponse>,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+/// `error` repeats the messages as one string for clients that only read that key.
+#[derive(Debug, Serialize)]
+struct ValidationErrorResponse {
+    error: String,
+    errors: Vec<ValidationError>,
+}
+
+#[derive(Debug, Serialize)]
+struct HealthResponse {
+    status: &'static str,
+}
+
+/// `gitHash` is the `FIRE_GIT_HASH` environment variable at build time, `null` when unset.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VersionResponse {
+    version: &'static str,
+    git_hash: Option<&'static str>,
+}
+
+fn parse_lump_expense(value: &str) -> Result<(u32, f64), String> {
+    let (age, amount) = value
+        .split_once(':')
+        .ok_or_else(|| format!("expected AGE:AMOUNT, got '{value}'"))?;
+    let age = age
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| format!("invalid age in '{value}'"))?;
+    let amount = amount
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| format!("invalid amount in '{value}'"))?;
+    Ok((age, amount))
+}
+
+fn parse_debt(value: &str) -> Result<(f64, u32), String> {
+    let (payment, end_age) = value
+        .split_once(':')
+        .ok_or_else(|| format!("expected PAYMENT:END_AGE, got '{value}'"))?;
+    let payment = payment
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| format!("invalid payment in '{value}'"))?;
+    let end_age = end_age
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| format!("invalid end age in '{value}'"))?;
+    Ok((payment, end_age))
+}
+
+/// `(isa, taxable, pension, inflation)` rates for one replayed year.
+type MarketPathYear = (f64, f64, f64, f64);
+
+fn parse_market_path_year(value: &str) -> Result<MarketPathYear, String> {
+    let rates = value
+        .split(':')
+        .map(|part| part.trim().parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("invalid rate in '{value}'"))?;
+    match rates[..] {
+        [isa, taxable, pension, inflation] => Ok((isa, taxable, pension, inflation)),
+        _ => Err(format!(
+            "expected ISA:TAXABLE:PENSION:INFLATION, got '{value}'"
+        )),
+    }
+}
+
+/// Checks every flag and reports all violations at once, so a form can flag each
+/// offending field in one round trip.
+fn build_inputs(cli: Cli) -> Result<Inputs, Vec<ValidationError>> {
+    let betas = [cli.isa_beta, cli.taxable_beta, cli.pension_beta];
+    let partial_betas = betas.iter().any(Option::is_some) && betas.iter().any(Option::is_none);
+    let debts = cli
+        .debts
+        .iter()
+        .map(|&(annual_payment, end_age)| Debt {
+            annual_payment,
+            end_age,
+        })
+        .collect();
+    let taxable_growth_rate = cli.taxable_growth_rate.unwrap_or(cli.isa_growth_rate);
+    let taxable_return_volatility = cli
+        .taxable_return_volatility
+        .unwrap_or(cli.isa_return_volatility);
+
+    let inputs = Inputs {
+        current_age: cli.current_age,
+        pension_access_age: cli.pension_access_age,
+        isa_start: cli.isa_start,
+        taxable_start: cli.taxable_start,
+        taxable_cost_basis_start: if cli.taxable_cost_basis_start == 0.0 && cli.taxable_start > 0.0
+        {
+            cli.taxable_start
+        } else {
+            cli.taxable_cost_basis_start
+        },
+        pension_start: cli.pension_start,
+        household: cli.household,
+        partner_pension_start: cli.partner_pension_start,
+        partner_pension_access_age: cli.partner_pension_access_age,
+        partner_personal_allowance: cli.partner_personal_allowance,
+        marriage_allowance_transfer: cli.marriage_allowance_transfer,
+        cash_start: cli.cash_start,
+        bond_ladder_start: cli.bond_ladder_start,
+        isa_annual_contribution: cli.isa_annual_contribution,
+        isa_annual_contribution_limit: cli.isa_annual_contribution_limit,
+        lisa_start: cli.lisa_start,
+        lisa_annual_contribution: cli.lisa_annual_contribution,
+        lisa_access_age: cli.lisa_access_age,
+        taxable_annual_contribution: cli.taxable_annual_contribution,
+        pension_annual_contribution: cli.pension_annual_contribution,
+        employer_match_rate: cli.employer_match_rate / 100.0,
+        employer_match_cap: cli.employer_match_cap,
+        pension_annual_allowance: cli.pension_annual_allowance,
+        pension_overflow_to_taxable: cli.pension_overflow_to_taxable,
+        contribution_growth_rate: cli.contribution_growth_rate / 100.0,
+        contributions_track_inflation: cli.contributions_track_inflation,
+        coast_contribution_fraction: cli.coast_contribution_fraction,
+        pre_retirement_earnings: cli.pre_retirement_earnings,
+        isa_return_mean: cli.isa_growth_rate / 100.0,
+        isa_return_vol: cli.isa_return_volatility / 100.0,
+        taxable_return_mean: taxable_growth_rate / 100.0,
+        taxable_return_vol: taxable_return_volatility / 100.0,
+        pension_return_mean: cli.pension_growth_rate / 100.0,
+        pension_return_vol: cli.pension_return_volatility / 100.0,
+        return_correlation: cli.return_correlation,
+        isa_taxable_correlation: cli.isa_taxable_correlation,
+        taxable_pension_correlation: cli.taxable_pension_correlation,
+        return_factor_betas: match (cli.isa_beta, cli.taxable_beta, cli.pension_beta) {
+            (Some(isa), Some(taxable), Some(pension)) => Some([isa, taxable, pension]),
+            _ => None,
+        },
+        return_distribution: match cli.return_distribution {
+            CliReturnDistribution::Normal => ReturnDistribution::Normal,
+            CliReturnDistribution::StudentT => ReturnDistribution::StudentT {
+                dof: cli.student_t_dof,
+            },
+        },
+        market_model: match cli.market_model {
+            CliMarketModel::Parametric => MarketModel::Parametric,
+            CliMarketModel::HistoricalBootstrap => MarketModel::HistoricalBootstrap,
+            CliMarketModel::Deterministic => MarketModel::Deterministic {
+                paths: cli
+                    .market_path
+                    .iter()
+                    .map(|&(isa, taxable, pension, inflation)| {
+                        (
+                            isa / 100.0,
+                            taxable / 100.0,
+                            pension / 100.0,
+                            inflation / 100.0,
+                        )
+                    })
+                    .collect(),
+            },
+            CliMarketModel::Garch => MarketModel::Garch {
+                alpha: cli.garch_alpha,
+                beta: cli.garch_beta,
+                long_run_vol: cli.garch_long_run_vol / 100.0,
+            },
+            CliMarketModel::RegimeSwitching => MarketModel::RegimeSwitching {
+                p_normal_to_crisis: cli.normal_to_crisis_probability,
+                p_crisis_to_normal: cli.crisis_to_normal_probability,
+                crisis_return_mean: cli.crisis_return_mean / 100.0,
+                crisis_return_vol: cli.crisis_return_volatility / 100.0,
+            },
+            CliMarketModel::Ensemble => MarketModel::Ensemble {
+                bootstrap_fraction: cli.bootstrap_fraction,
+            },
+        },
+        bootstrap_block_years: cli.bootstrap_block_years,
+        market_burn_in_years: cli.market_burn_in_years,
+        cgt_basic_rate: cli.capital_gains_tax_rate.unwrap_or(cli.cgt_basic_rate) / 100.0,
+        cgt_higher_rate: cli.capital_gains_tax_rate.unwrap_or(cli.cgt_higher_rate) / 100.0,
+        capital_gains_allowance: cli.capital_gains_allowance,
+        taxable_return_tax_drag: cli.taxable_return_tax_drag / 100.0,
+        taxable_dividend_yield: cli.taxable_dividend_yield / 100.0,
+        dividend_allowance: cli.dividend_allowance,
+        dividend_basic_rate: cli.dividend_basic_rate / 100.0,
+        dividend_higher_rate: cli.dividend_higher_rate / 100.0,
+        dividend_additional_rate: cli.dividend_additional_rate / 100.0,
+        isa_fee_rate: cli.isa_fee_rate / 100.0,
+        taxable_fee_rate: cli.taxable_fee_rate / 100.0,
+        pension_fee_rate: cli.pension_fee_rate / 100.0,
+        glide_start_equity: cli.glide_start_equity / 100.0,
+        glide_end_equity: cli.glide_end_equity / 100.0,
+        glide_end_age: cli.glide_end_age,
+        pension_tax_mode: cli.pension_tax_mode.into(),
+        pension_flat_tax_rate: cli.pension_income_tax_rate / 100.0,
+        pension_tax_free_pct: cli.pension_tax_free_pct / 100.0,
+        uk_personal_allowance: cli.uk_personal_allowance,
+        uk_basic_rate_limit: cli.uk_basic_rate_limit,
+        uk_higher_rate_limit: cli.uk_higher_rate_limit,
+        uk_basic_rate: cli.uk_basic_rate / 100.0,
+        uk_higher_rate: cli.uk_higher_rate / 100.0,
+        uk_additional_rate: cli.uk_additional_rate / 100.0,
+        uk_allowance_taper_start: cli.uk_allowance_taper_start,
+        uk_allowance_taper_end: cli.uk_allowance_taper_end,
+        thresholds_frozen_until_age: cli.thresholds_frozen_until_age,
+        scottish_starter_rate_limit: cli.scottish_starter_rate_limit,
+        scottish_basic_rate_limit: cli.scottish_basic_rate_limit,
+        scottish_intermediate_rate_limit: cli.scottish_intermediate_rate_limit,
+        scottish_higher_rate_limit: cli.scottish_higher_rate_limit,
+        scottish_advanced_rate_limit: cli.scottish_advanced_rate_limit,
+        scottish_starter_rate: cli.scottish_starter_rate / 100.0,
+        scottish_basic_rate: cli.scottish_basic_rate / 100.0,
+        scottish_intermediate_rate: cli.scottish_intermediate_rate / 100.0,
+        scottish_higher_rate: cli.scottish_higher_rate / 100.0,
+        scottish_advanced_rate: cli.scottish_advanced_rate / 100.0,
+        scottish_top_rate: cli.scottish_top_rate / 100.0,
+        state_pension_start_age: cli.state_pension_start_age,
+        state_pension_annual_income: cli.state_pension_annual_income,
+        state_pension_real_growth: cli.state_pension_real_growth / 100.0,
+        state_pension_growth_before_start: cli.state_pension_growth_before_start,
+        state_pension_deferral_years: cli.state_pension_deferral_years,
+        state_pension_deferral_uplift_rate: cli.state_pension_deferral_uplift_rate / 100.0,
+        db_pension_start_age: cli.db_pension_start_age,
+        db_pension_annual_income: cli.db_pension_annual_income,
+        annuity_purchase_age: cli.annuity_purchase_age,
+        annuity_purchase_amount: cli.annuity_purchase_amount,
+        annuity_rate: cli.annuity_rate / 100.0,
+        conversion_ladder_annual_amount: cli.conversion_ladder_annual_amount,
+        conversion_ladder_start_age: cli.conversion_ladder_start_age,
+        conversion_ladder_end_age: cli.conversion_ladder_end_age,
+        rental_income_start_age: cli.rental_income_start_age,
+        rental_annual_income: cli.rental_annual_income,
+        rental_growth_rate: cli.rental_growth_rate / 100.0,
+        part_time_annual_income: cli.part_time_annual_income,
+        part_time_start_age: cli.part_time_start_age,
+        part_time_end_age: cli.part_time_end_age,
+        inflation_mean: cli.inflation_rate / 100.0,
+        inflation_vol: cli.inflation_volatility / 100.0,
+        inflation_return_correlation: cli.inflation_return_correlation,
+        return_clamp_min: cli.return_clamp_min / 100.0,
+        return_clamp_max: cli.return_clamp_max / 100.0,
+        inflation_clamp_min: cli.inflation_clamp_min / 100.0,
+        inflation_clamp_max: cli.inflation_clamp_max / 100.0,
+        target_annual_income: cli.target_annual_income,
+        essential_annual_income: cli.essential_annual_income,
+        spending_decline_rate: cli.spending_decline_rate / 100.0,
+        care_cost_age: cli.care_cost_age,
+        care_cost_uplift: cli.care_cost_uplift / 100.0,
+        active_phase_end_age: cli.active_phase_end_age,
+        active_phase_uplift: cli.active_phase_uplift / 100.0,
+        late_phase_start_age: cli.late_phase_start_age,
+        late_phase_uplift: cli.late_phase_uplift / 100.0,
+        debts,
+        mortgage_annual_payment: cli.mortgage_annual_payment,
+        mortgage_end_age: cli.mortgage_end_age,
+        mortgage_balance: cli.mortgage_balance,
+        mortgage_rate: cli.mortgage_rate / 100.0,
+        mortgage_term_years: cli.mortgage_term_years,
+        mortgage_overpayments: cli.mortgage_overpayments,
+        lump_expenses: cli.lump_expenses,
+        max_retirement_age: cli.max_age,
+        horizon_age: cli.horizon_age,
+        bequest_target: cli.bequest_target,
+        success_criterion: cli.success_criterion.into(),
+        simulations: cli.simulations,
+        threads: cli.threads,
+        success_threshold: cli.success_threshold / 100.0,
+        selection_confidence: cli.selection_confidence.into(),
+        risk_aversion: cli.risk_aversion,
+        utility_discount_rate: cli.utility_discount_rate / 100.0,
+        utility_spending_floor: cli.utility_spending_floor,
+        seed: resolve_seed(cli.seed, cli.seed_label.as_deref()),
+        age_invariant_draws: cli.age_invariant_draws,
+        stratified_inflation: cli.stratified_inflation,
+        bad_year_threshold: cli.bad_year_threshold / 100.0,
+        good_year_threshold: cli.good_year_threshold / 100.0,
+        bad_year_cut: cli.bad_year_cut / 100.0,
+        good_year_raise: cli.good_year_raise / 100.0,
+        min_income_floor: cli.min_income_floor / 100.0,
+        max_income_ceiling: cli.max_income_ceiling / 100.0,
+        min_real_spending_floor: cli.min_real_spending_floor,
+        withdrawal_strategy: cli.withdrawal_strategy.into(),
+        gk_lower_guardrail: cli.gk_lower_guardrail / 100.0,
+        gk_upper_guardrail: cli.gk_upper_guardrail / 100.0,
+        gk_adjustment: cli.gk_adjustment / 100.0,
+        vpw_expected_real_return: cli.vpw_expected_real_return / 100.0,
+        constant_withdrawal_rate: cli.constant_withdrawal_rate / 100.0,
+        cape_a: cli.cape_a / 100.0,
+        cape_b: cli.cape_b,
+        cape_start: cli.cape_start,
+        cape_long_run_mean: cli.cape_long_run_mean,
+        cape_reversion_speed: cli.cape_reversion_speed / 100.0,
+        floor_upside_capture: cli.floor_upside_capture / 100.0,
+        bucket_target_years: cli.bucket_target_years,
+        good_year_extra_buffer_withdrawal: cli.good_year_extra_buffer_withdrawal / 100.0,
+        min_cash_months: cli.min_cash_months,
+        cash_growth_rate: cli.cash_growth_rate / 100.0,
+        bond_ladder_yield: cli.bond_ladder_yield / 100.0,
+        bond_ladder_yield_vol: cli.bond_ladder_yield_vol / 100.0,
+        bond_ladder_inflation_correlation: cli.bond_ladder_inflation_correlation,
+        bond_ladder_years: cli.bond_ladder_years,
+        bond_ladder_reinvest: cli.bond_ladder_reinvest,
+        bond_ladder_target_at_retirement: cli.bond_ladder_target_at_retirement,
+        post_access_withdrawal_order: cli.post_access_withdrawal_order.into(),
+        pre_access_withdrawal_order: cli.pre_access_withdrawal_order.map(Into::into),
+    };
+
+    let mut errors = validate_inputs(&inputs).err().unwrap_or_default();
+    if partial_betas {
+        errors.push(ValidationError::new(
+            "isa-beta",
+            "--isa-beta, --taxable-beta and --pension-beta must be set together",
+        ));
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(inputs)
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "fire serve", about = "Serve the web app and HTTP API")]
+struct ServeCommand {
+    #[arg(help = "Port to listen on [default: $PORT, then 8080]")]
+    port: Option<u16>,
+    #[arg(
+        long = "cors-origin",
+        value_name = "ORIGIN",
+        help = "Allow cross-origin API calls from this origin, e.g. http://localhost:5173 (repeatable; default same-origin only)"
+    )]
+    cors_origins: Vec<String>,
+    #[arg(
+        long,
+        default_value_t = DEFAULT_MAX_SIMULATIONS,
+        help = "Reject API requests asking for more Monte Carlo simulations than this"
+    )]
+    max_simulations: u32,
+}
+
+/// Entry point for `fire serve ...`. `args[0]` is the program name; `--help` is printed
+/// and returns `Ok`.
+pub async fn run_serve_command<I, T>(args: I) -> Result<(), String>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<std::ffi::OsString> + Clone,
+{
+    let command = match ServeCommand::try_parse_from(args) {
+        Ok(command) => command,
+        Err(e)
+            if matches!(
+                e.kind(),
+                clap::error::ErrorKind::DisplayHelp | clap::error::ErrorKind::DisplayVersion
+            ) =>
+        {
+            print!("{e}");
+            return Ok(());
+        }
+        Err(e) => return Err(e.to_string()),
+    };
+    let port = command
+        .port
+        .or_else(|| std::env::var("PORT").ok().and_then(|s| s.parse().ok()))
+        .unwrap_or(8080);
+    let cors = cors_layer(&command.cors_origins)?;
+    let limits = ServerLimits {
+        max_simulations: command.max_simulations,
+    };
+    run_http_server(port, cors, limits)
+        .await
+        .map_err(|e| format!("Server error: {e}"))
+}
+
+/// `None` (no CORS headers, so browsers keep the same-origin policy) unless origins are
+/// listed. Listed origins may call every route with GET/POST and a JSON body.
+fn cors_layer(origins: &[String]) -> Result<Option<CorsLayer>, String> {
+    if origins.is_empty() {
+        return Ok(None);
+    }
+    let origins = origins
+        .iter()
+        .map(|origin| {
+            HeaderValue::from_str(origin.trim_end_matches('/'))
+                .map_err(|_| format!("--cors-origin {origin:?} is not a valid origin"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Some(
+        CorsLayer::new()
+            .allow_origin(AllowOrigin::list(origins))
+            .allow_methods([Method::GET, Method::POST])
+            .allow_headers([header::CONTENT_TYPE]),
+    ))
+}
+
+async fn run_http_server(
+    port: u16,
+    cors: Option<CorsLayer>,
+    limits: ServerLimits,
+) -> std::io::Result<()> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    let app = router(cors, limits);
+
+    let listener = TcpListener::bind(addr).await?;
+    println!("FIRE HTTP API listening on http://{addr}");
+    println!("Local access: http://127.0.0.1:{port}/");
+
+    axum::serve(listener, app).await
+}
+
+/// Shared by every handler: the saved scenarios plus the limits `fire serve` was started with.
+#[derive(Clone, Default)]
+struct AppState {
+    scenarios: ScenarioStore,
+    limits: ServerLimits,
+}
+
+impl FromRef<AppState> for ScenarioStore {
+    fn from_ref(state: &AppState) -> Self {
+        state.scenarios.clone()
+    }
+}
+
+impl FromRef<AppState> for ServerLimits {
+    fn from_ref(state: &AppState) -> Self {
+        state.limits
+    }
+}
+
+fn router(cors: Option<CorsLayer>, limits: ServerLimits) -> Router {
+    let app = Router::new()
+        .route("/", get(index_handler))
+        .route("/index.html", get(index_handler))
+        .route("/styles.css", get(styles_handler))
+        .route("/app.js", get(app_js_handler))
+        .route("/healthz", get(health_handler))
+        .route("/api/health", get(health_handler))
+        .route("/version", get(version_handler))
+        .route("/api/version", get(version_handler))
+        .route(
+            "/api/simulate",
+            get(simulate_get_handler).post(simulate_post_handler),
+        )
+        .route("/api/simulate/stream", get(simulate_stream_handler))
+        .route("/api/cashflow.csv", get(cashflow_csv_handler))
+        .route("/api/distribution", get(distribution_handler))
+        .route("/api/resolve", post(resolve_post_handler))
+        .route("/api/compare", post(compare_post_handler))
+        .route("/api/sensitivity", post(sensitivity_post_handler))
+        .route(
+            "/api/solve",
+            get(solve_goal_get_handler).post(solve_goal_post_handler),
+        )
+        .route(
+            "/api/solve-goal",
+            get(solve_goal_get_handler).post(solve_goal_post_handler),
+        )
+        .route("/api/scenarios", post(store_scenario_handler))
+        .route("/api/scenarios/:id", get(load_scenario_handler))
+        .fallback(not_found_handler)
+        .layer(DefaultBodyLimit::max(MAX_REQUEST_BODY_BYTES))
+        // The default predicate skips SSE, so streamed progress is not buffered.
+        .layer(CompressionLayer::new())
+        .with_state(AppState {
+            scenarios: ScenarioStore::default(),
+            limits,
+        });
+    match cors {
+        Some(cors) => app.layer(cors),
+        None => app,
+    }
+}
+
+async fn index_handler() -> impl IntoResponse {
+    with_cache_control(Html(INDEX_HTML))
+}
+
+async fn styles_handler() -> impl IntoResponse {
+    with_cache_control((
+        [(header::CONTENT_TYPE, "text/css; charset=utf-8")],
+        STYLES_CSS,
+    ))
+}
+
+async fn app_js_handler() -> impl IntoResponse {
+    with_cache_control((
+        [(
+            header::CONTENT_TYPE,
+            "application/javascript; charset=utf-8",
+        )],
+        APP_JS,
+    ))
+}
+
+async fn health_handler() -> Response {
+    json_response(StatusCode::OK, HealthResponse { status: "ok" })
+}
+
+async fn version_handler() -> Response {
+    json_response(
+        StatusCode::OK,
+        VersionResponse {
+            version: env!("CARGO_PKG_VERSION"),
+            git_hash: option_env!("FIRE_GIT_HASH"),
+        },
+    )
+}
+
+async fn not_found_handler() -> Response {
+    error_response(StatusCode::NOT_FOUND, "Not found")
+}
+
+async fn simulate_get_handler(
+    State(limits): State<ServerLimits>,
+    Query(payload): Query<SimulatePayload>,
+) -> Response {
+    simulate_handler_impl(payload, limits).await
+}
+
+async fn simulate_post_handler(
+    State(limits): State<ServerLimits>,
+    Json(payload): Json<SimulatePayload>,
+) -> Response {
+    simulate_handler_impl(payload, limits).await
+}
+
+/// Maps and validates a simulate payload without running it, so clients can see the
+/// exact `Inputs` behind a request.
+async fn resolve_post_handler(
+    State(limits): State<ServerLimits>,
+    Json(payload): Json<SimulatePayload>,
+) -> Response {
+    match api_request_from_payload(payload, limits) {
+        Ok(request) => json_response(StatusCode::OK, ResolvedRequestResponse::from(request)),
+        Err(error) => error.into_response(),
+    }
+}
+
+async fn compare_post_handler(
+    State(limits): State<ServerLimits>,
+    Json(payload): Json<ComparePayload>,
+) -> Response {
+    // One full model per strategy: keep them off the async workers.
+    match tokio::task::spawn_blocking(move || run_compare_request(payload, limits)).await {
+        Ok(Ok(responses)) => json_response(StatusCode::OK, responses),
+        Ok(Err(error)) => error.into_response(),
+        Err(error) => error_response(StatusCode::INTERNAL_SERVER_ERROR, &error.to_string()),
+    }
+}
+
+/// Runs the base payload once per strategy. Every run shares the payload seed, so the
+/// strategies see identical market draws and differ only in how they spend.
+fn run_compare_request(
+    payload: ComparePayload,
+    limits: ServerLimits,
+) -> Result<BTreeMap<ApiWithdrawalStrategy, SimulateResponse>, PayloadError> {
+    let strategy_count = CliWithdrawalStrategy::value_variants().len();
+    if payload.strategies.is_empty() || payload.strategies.len() > strategy_count {
+        return Err(vec![ValidationError::new(
+            "strategies",
+            format!("strategies must list between 1 and {strategy_count} withdrawal policies"),
+        )]
+        .into());
+    }
+    let mut strategies = payload.strategies.clone();
+    strategies.sort_unstable();
+    strategies.dedup();
+
+    let requests = strategies
+        .iter()
+        .map(|&strategy| {
+            let mut simulation = payload.simulation.clone();
+            simulation.withdrawal_policy = Some(strategy);
+            api_request_from_payload(simulation, limits).map(|request| (strategy, request))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(requests
+        .into_iter()
+        .map(|(strategy, request)| (strategy, run_simulate_request(&request)))
+        .collect())
+}
+
+async fn sensitivity_post_handler(
+    State(limits): State<ServerLimits>,
+    Json(payload): Json<SensitivityPayload>,
+) -> Response {
+    // One full model per value: keep them off the async workers.
+    match tokio::task::spawn_blocking(move || run_sensitivity_request(payload, limits)).await {
+        Ok(Ok(points)) => json_response(StatusCode::OK, points),
+        Ok(Err(error)) => error.into_response(),
+        Err(error) => error_response(StatusCode::INTERNAL_SERVER_ERROR, &error.to_string()),
+    }
+}
+
+/// Runs the retirement sweep once per value of `parameter`, holding the rest of the base
+/// payload fixed. Every run shares the payload seed, so only the parameter differs.
+fn run_sensitivity_request(
+    payload: SensitivityPayload,
+    limits: ServerLimits,
+) -> Result<Vec<SensitivityPointResponse>, PayloadError> {
+    if !SENSITIVITY_PARAMETERS.contains(&payload.parameter.as_str()) {
+        return Err(vec![ValidationError::new(
+            "parameter",
+            format!(
+                "parameter must be one of: {}",
+                SENSITIVITY_PARAMETERS.join(", ")
+            ),
+        )]
+        .into());
+    }
+    if payload.values.is_empty() {
+        return Err(vec![ValidationError::new(
+            "values",
+            "values must list at least one value",
+        )]
+        .into());
+    }
+    let mut values = Vec::with_capacity(payload.values.len());
+    for value in payload.values {
+        if !values.contains(&value) {
+            values.push(value);
+        }
+    }
+    if values.len() > MAX_SENSITIVITY_VALUES {
+        return Err(vec![ValidationError::new(
+            "values",
+            format!("values must list at most {MAX_SENSITIVITY_VALUES} distinct values"),
+        )]
+        .into());
+    }
+
+    let requests = values
+        .iter()
+        .map(|&value| {
+            let mut simulation = payload.simulation.clone();
+            set_sensitivity_parameter(&mut simulation, &payload.parameter, value);
+            api_request_from_payload(simulation, limits).map(|request| (value, request))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(requests
+        .into_iter()
+        .map(|(value, request)| {
+            let model = run_model(&request.inputs).expect(VALIDATED_INPUTS);
+            let selected = model.selected_index.map(|idx| &model.age_results[idx]);
+            let best = &model.age_results[model.best_index];
+            SensitivityPointResponse {
+                value,
+                selected_retirement_age: selected.map(|r| r.retirement_age),
+                selected_success_rate: selected.map(|r| r.success_rate),
+                best_retirement_age: best.retirement_age,
+                best_success_rate: best.success_rate,
+            }
+        })
+        .collect())
+}
+
+/// Most distinct values one `/api/sensitivity` request may sweep; each runs a full model.
+const MAX_SENSITIVITY_VALUES: usize = 25;
+
+/// Saved scenarios kept oldest-first; past this many the oldest is dropped.
+const MAX_STORED_SCENARIOS: usize = 1_000;
+/// Cap on the JSON bytes held across every stored scenario; the oldest go first.
+const MAX_STORED_SCENARIO_BYTES: usize = 16 * 1024 * 1024;
+
+/// In-memory saved `/api/simulate` payloads, shared by every request and lost on restart.
+#[derive(Clone, Default)]
+struct ScenarioStore {
+    inner: Arc<Mutex<StoredScenarios>>,
+}
+
+#[derive(Default)]
+struct StoredScenarios {
+    payloads: HashMap<String, String>,
+    order: VecDeque<String>,
+    bytes: usize,
+    issued: u64,
+}
+
+impl ScenarioStore {
+    fn insert(&self, payload: &SimulatePayload) -> String {
+        let json = stored_payload_json(payload);
+        let mut stored = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let id = loop {
+            stored.issued += 1;
+            let id = scenario_id(stored.issued);
+            if !stored.payloads.contains_key(&id) {
+                break id;
+            }
+        };
+        stored.bytes += json.len();
+        stored.payloads.insert(id.clone(), json);
+        stored.order.push_back(id.clone());
+        while stored.order.len() > MAX_STORED_SCENARIOS || stored.bytes > MAX_STORED_SCENARIO_BYTES
+        {
+            let Some(oldest) = stored.order.pop_front() else {
+                break;
+            };
+            if let Some(json) = stored.payloads.remove(&oldest) {
+                stored.bytes -= json.len();
+            }
+        }
+        id
+    }
+
+    fn get(&self, id: &str) -> Option<serde_json::Value> {
+        let stored = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        stored
+            .payloads
+            .get(id)
+            .map(|json| serde_json::from_str(json).expect("stored payloads are valid JSON"))
+    }
+}
+
+/// Compact JSON of the fields a payload sets. Re-serialising the parsed payload drops
+/// unknown keys, so a client cannot park arbitrary data in the store.
+fn stored_payload_json(payload: &SimulatePayload) -> String {
+    let mut value = serde_json::to_value(payload).expect("payload serializes");
+    if let serde_json::Value::Object(fields) = &mut value {
+        fields.retain(|_, field| !field.is_null());
+    }
+    value.to_string()
+}
+
+/// Ten base-36 characters scrambled from a per-process key and the issue counter. IDs are
+/// hard to guess by counting but are not secrets: anyone with an ID can read the payload.
+fn scenario_id(issued: u64) -> String {
+    static PROCESS_KEY: std::sync::OnceLock<u64> = std::sync::OnceLock::new();
+    let key = *PROCESS_KEY.get_or_init(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+            ^ u64::from(std::process::id()).rotate_left(32)
+    });
+    // splitmix64 finalizer
+    let mut x = key.wrapping_add(issued.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^= x >> 31;
+
+    const ALPHABET: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    (0..10)
+        .map(|_| {
+            let digit = ALPHABET[(x % 36) as usize] as char;
+            x /= 36;
+            digit
+        })
+        .collect()
+}
+
+/// What `/api/simulate` would model for a payload, with every default filled in and
+/// percents converted to fractions.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ResolvedRequestResponse {
+    mode: ResponseMode,
+    /// The payload's coast age; `null` in coast mode means the sweep would pick it.
+    coast_retirement_age: Option<u32>,
+    cashflow_age: Option<u32>,
+    summary_only: bool,
+    inputs: Inputs,
+}
+
+impl From<ApiRequest> for ResolvedRequestResponse {
+    fn from(request: ApiRequest) -> Self {
+        Self {
+            mode: request.options.mode.into(),
+            coast_retirement_age: request.options.coast_retirement_age,
+            cashflow_age: request.options.cashflow_age,
+            summary_only: request.options.summary_only,
+            inputs: request.inputs,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct StoredScenarioResponse {
+    id: String,
+}
+
+/// Saves the payload's known fields once it parses as a simulate payload. Inputs are not
+/// validated, so a half-finished form can be shared too.
+async fn store_scenario_handler(
+    State(store): State<ScenarioStore>,
+    Json(payload): Json<serde_json::Value>,
+) -> Response {
+    let payload = match serde_json::from_value::<SimulatePayload>(payload) {
+        Ok(payload) => payload,
+        Err(e) => {
+            return error_response(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                &format!("Invalid scenario payload: {e}"),
+            );
+        }
+    };
+    let id = store.insert(&payload);
+    json_response(StatusCode::CREATED, StoredScenarioResponse { id })
+}
+
+async fn load_scenario_handler(
+    State(store): State<ScenarioStore>,
+    Path(id): Path<String>,
+) -> Response {
+    match store.get(&id) {
+        Some(payload) => json_response(StatusCode::OK, payload),
+        None => error_response(StatusCode::NOT_FOUND, "No saved scenario with that id"),
+    }
+}
+
+async fn solve_goal_get_handler(
+    State(limits): State<ServerLimits>,
+    Query(payload): Query<SolveGoalPayload>,
+) -> Response {
+    solve_goal_handler_impl(payload, limits).await
+}
+
+async fn solve_goal_post_handler(
+    State(limits): State<ServerLimits>,
+    Json(payload): Json<SolveGoalPayload>,
+) -> Response {
+    solve_goal_handler_impl(payload, limits).await
+}
+
+async fn simulate_handler_impl(payload: SimulatePayload, limits: ServerLimits) -> Response {
+    let request = match api_request_from_payload(payload, limits) {
+        Ok(request) => request,
+        Err(error) => return error.into_response(),
+    };
+
+    json_response(StatusCode::OK, run_simulate_request(&request))
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProgressEvent {
+    completed: usize,
+    total: usize,
+    percent: f64,
+}
+
+/// Server-Sent Events version of `/api/simulate`: one `progress` event per finished
+/// candidate age, then a `result` event carrying the usual response.
+async fn simulate_stream_handler(
+    State(limits): State<ServerLimits>,
+    Query(payload): Query<SimulatePayload>,
+) -> Response {
+    let request = match api_request_from_payload(payload, limits) {
+        Ok(request) => request,
+        Err(error) => return error.into_response(),
+    };
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<Result<Event, axum::Error>>();
+    tokio::task::spawn_blocking(move || {
+        let progress = |completed: usize, total: usize| {
+            let event = ProgressEvent {
+                completed,
+                total,
+                percent: 100.0 * completed as f64 / total.max(1) as f64,
+            };
+            let _ = tx.send(Event::default().event("progress").json_data(event));
+        };
+        let response = run_simulate_request_with_progress(&request, &progress);
+        let _ = tx.send(Event::default().event("result").json_data(response));
+    });
+
+    let events = futures_util::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|event| (event, rx))
+    });
+    Sse::new(events).into_response()
+}
+
+const MAX_DISTRIBUTION_BINS: u32 = 1_000;
+
+/// `/api/distribution` parameters on top of the usual simulate query.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct DistributionParams {
+    age: Option<u32>,
+    bins: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HistogramBin {
+    lower: f64,
+    upper: f64,
+    count: u32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DistributionResponse {
+    candidate_age: u32,
+    retirement_age: u32,
+    contribution_stop_age: u32,
+    simulations: u32,
+    /// Sorted real terminal totals, one per scenario; omitted when `bins` is given.
+    terminal_totals: Option<Vec<f64>>,
+    histogram: Option<Vec<HistogramBin>>,
+}
+
+/// Every scenario's terminal pot at one candidate age, for histograms. With `bins`
+/// the samples are counted into equal-width bins instead of returned raw.
+async fn distribution_handler(
+    State(limits): State<ServerLimits>,
+    Query(payload): Query<SimulatePayload>,
+    Query(params): Query<DistributionParams>,
+) -> Response {
+    let request = match api_request_from_payload(payload, limits) {
+        Ok(request) => request,
+        Err(error) => return error.into_response(),
+    };
+    match distribution_response(&request, &params) {
+        Ok(response) => json_response(StatusCode::OK, response),
+        Err(errors) => validation_error_response(errors),
+    }
+}
+
+fn distribution_response(
+    request: &ApiRequest,
+    params: &DistributionParams,
+) -> Result<DistributionResponse, Vec<ValidationError>> {
+    let inputs = &request.inputs;
+    let mut errors = Vec::new();
+    match params.age {
+        None => errors.push(ValidationError::new("age", "age is required")),
+        Some(age) if !(inputs.current_age..=inputs.max_retirement_age).contains(&age) => errors
+            .push(ValidationError::new(
+                "age",
+                "age must be between currentAge and maxAge",
+            )),
+        Some(_) => {}
+    }
+    if params
+        .bins
+        .is_some_and(|bins| !(1..=MAX_DISTRIBUTION_BINS).contains(&bins))
+    {
+        errors.push(ValidationError::new(
+            "bins",
+            "bins must be between 1 and 1000",
+        ));
+    }
+    let Some(age) = params.age.filter(|_| errors.is_empty()) else {
+        return Err(errors);
+    };
+
+    let (retirement_age, contribution_stop_age) = match request.options.mode {
+        AnalysisMode::RetirementSweep => (age, age),
+        AnalysisMode::CoastFire => (resolve_coast_retirement_age(request), age),
+    };
+    let terminal = run_terminal_distribution(inputs, retirement_age, contribution_stop_age, age);
+    let (terminal_totals, histogram) = match params.bins {
+        Some(bins) => (None, Some(histogram(&terminal, bins))),
+        None => (Some(terminal), None),
+    };
+    Ok(DistributionResponse {
+        candidate_age: age,
+        retirement_age,
+        contribution_stop_age,
+        simulations: inputs.simulations,
+        terminal_totals,
+        histogram,
+    })
+}
+
+/// Counts sorted `samples` into `bins` equal-width bins spanning their range. The last
+/// bin includes the maximum; a single-valued sample lands entirely in the first bin.
+fn histogram(samples: &[f64], bins: u32) -> Vec<HistogramBin> {
+    let (Some(&min), Some(&max)) = (samples.first(), samples.last()) else {
+        return Vec::new();
+    };
+    let width = (max - min) / bins as f64;
+    let mut histogram = (0..bins)
+        .map(|idx| HistogramBin {
+            lower: min + width * idx as f64,
+            upper: if idx + 1 == bins {
+                max
+            } else {
+                min + width * (idx + 1) as f64
+            },
+            count: 0,
+        })
+        .collect::<Vec<_>>();
+    for &sample in samples {
+        let idx = if width > 0.0 {
+            (((sample - min) / width) as usize).min(bins as usize - 1)
+        } else {
+            0
+        };
+        histogram[idx].count += 1;
+    }
+    histogram
+}
+
+/// The cashflow trace `/api/simulate` returns for the same query, as a CSV download.
+async fn cashflow_csv_handler(
+    State(limits): State<ServerLimits>,
+    Query(payload): Query<SimulatePayload>,
+) -> Response {
+    let mut request = match api_request_from_payload(payload, limits) {
+        Ok(request) => request,
+        Err(error) => return error.into_response(),
+    };
+
+    // The trace is the whole point of this endpoint, so `summaryOnly` is ignored.
+    request.options.summary_only = false;
+    let response = run_simulate_request(&request);
+    match cashflow_csv(response.cashflow_years.as_deref().unwrap_or_default()) {
+        Ok(csv) => with_cache_control((
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+                (
+                    header::CONTENT_DISPOSITION,
+                    "attachment; filename=\"cashflow.csv\"",
+                ),
+            ],
+            csv,
+        )),
+        Err(msg) => error_response(StatusCode::INTERNAL_SERVER_ERROR, &msg),
+    }
+}
+
+/// Columns are each row's serialized field names in declaration order, so the CSV
+/// always matches the `cashflowYears` JSON without a hand-kept column list.
+fn cashflow_csv(years: &[CashflowYearResult]) -> Result<String, String> {
+    let mut csv = String::new();
+    for (idx, year) in years.iter().enumerate() {
+        let json = serde_json::to_string(year).map_err(|e| e.to_string())?;
+        let OrderedFields(fields) = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+        if idx == 0 {
+            let names: Vec<&str> = fields.iter().map(|(name, _)| name.as_str()).collect();
+            csv.push_str(&names.join(","));
+            csv.push('\n');
+        }
+        let values: Vec<String> = fields.iter().map(|(_, value)| value.to_string()).collect();
+        csv.push_str(&values.join(","));
+        csv.push('\n');
+    }
+    Ok(csv)
+}
+
+/// A JSON object's fields in document order (`serde_json::Map` sorts its keys).
+struct OrderedFields(Vec<(String, serde_json::Value)>);
+
+impl<'de> Deserialize<'de> for OrderedFields {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FieldsVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for FieldsVisitor {
+            type Value = OrderedFields;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a JSON object")
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> Result<OrderedFields, A::Error> {
+                let mut fields = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    fields.push(entry);
+                }
+                Ok(OrderedFields(fields))
+            }
+        }
+
+        deserializer.deserialize_map(FieldsVisitor)
+    }
+}
+
+fn run_simulate_request(request: &ApiRequest) -> SimulateResponse {
+    run_simulate_request_with_progress(request, &|_, _| {})
+}
+
+/// `build_inputs` and the coast-age checks in `api_request_from_payload` reject everything
+/// `ModelError` covers before a request reaches the engine.
+const VALIDATED_INPUTS: &str = "request inputs are validated before running the model";
+
+/// The requested coast retirement age, or else the one a plain retirement sweep picks.
+fn resolve_coast_retirement_age(request: &ApiRequest) -> u32 {
+    request.options.coast_retirement_age.unwrap_or_else(|| {
+        let baseline = run_model(&request.inputs).expect(VALIDATED_INPUTS);
+        baseline
+            .selected_index
+            .map(|idx| baseline.age_results[idx].retirement_age)
+            .unwrap_or(baseline.age_results[baseline.best_index].retirement_age)
+    })
+}
+
+/// Progress covers the reported sweep only; the baseline run that picks a coast
+/// retirement age when none is given is not reported.
+fn run_simulate_request_with_progress(
+    request: &ApiRequest,
+    progress: &(dyn Fn(usize, usize) + Sync),
+) -> SimulateResponse {
+    let inputs = &request.inputs;
+    let (model, resolved_coast_retirement_age) = match request.options.mode {
+        AnalysisMode::RetirementSweep => (
+            run_model_with_progress(inputs, progress).expect(VALIDATED_INPUTS),
+            None,
+        ),
+        AnalysisMode::CoastFire => {
+            let coast_retirement_age = resolve_coast_retirement_age(request);
+            (
+                run_coast_model_with_progress(inputs, coast_retirement_age, progress)
+                    .expect(VALIDATED_INPUTS),
+                Some(coast_retirement_age),
+            )
+        }
+    };
+
+    if request.options.summary_only {
+        return build_simulate_response(
+            inputs,
+            &model,
+            request.options.mode,
+            resolved_coast_retirement_age,
+            None,
+        );
+    }
+
+    let trace_index = request
+        .options
+        .cashflow_age
+        .and_then(|age| {
+            model
+                .age_results
+                .iter()
+                .position(|result| result.retirement_age == age)
+        })
+        .unwrap_or(model.selected_index.unwrap_or(model.best_index));
+    let trace_reported_age = model.age_results[trace_index].retirement_age;
+    let (trace_retirement_age, trace_contribution_stop_age) = match request.options.mode {
+        AnalysisMode::RetirementSweep => (trace_reported_age, trace_reported_age),
+        AnalysisMode::CoastFire => (
+            resolved_coast_retirement_age.unwrap_or(trace_reported_age),
+            trace_reported_age,
+        ),
+    };
+    let cashflow_years = run_yearly_cashflow_trace(
+        inputs,
+        trace_retirement_age,
+        trace_contribution_stop_age,
+        trace_reported_age,
+    );
+    let cashflow = CashflowResponse {
+        candidate_age: trace_reported_age,
+        retirement_age: trace_retirement_age,
+        contribution_stop_age: trace_contribution_stop_age,
+        years: &cashflow_years,
+    };
+
+    build_simulate_response(
+        inputs,
+        &model,
+        request.options.mode,
+        resolved_coast_retirement_age,
+        Some(cashflow),
+    )
+}
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "fire simulate",
+    about = "Run the retirement-age sweep locally and print the results"
+)]
+struct SimulateCommand {
+    #[command(flatten)]
+    cli: Cli,
+    #[arg(long, help = "Print the same JSON document /api/simulate returns")]
+    json: bool,
+    #[arg(
+        long,
+        conflicts_with = "json",
+        help = "Print one AgeResult JSON object per line as each candidate age finishes"
     )]
-    bond_ladder_years: u32,
-    #[arg(long, value_enum, default_value_t = CliWithdrawalOrder::ProRata)]
-    post_access_withdrawal_order: CliWithdrawalOrder,
+    jsonl: bool,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "TOML (or .json) file of flag values keyed by flag name; flags given on the command line win"
+    )]
+    config: Option<std::path::PathBuf>,
+}
+
+/// `fire simulate --config` file: every `Cli` flag, keyed by its long name (`current-age`,
+/// or `current_age`) and typed like the flag, so a bad value is reported at its place in
+/// the file. Repeatable flags take a list of the strings the flag would.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct CliConfig {
+    #[serde(alias = "current_age")]
+    current_age: Option<u32>,
+    #[serde(alias = "pension_access_age")]
+    pension_access_age: Option<u32>,
+    #[serde(alias = "isa_start")]
+    isa_start: Option<f64>,
+    #[serde(alias = "taxable_start")]
+    taxable_start: Option<f64>,
+    #[serde(alias = "taxable_cost_basis_start")]
+    taxable_cost_basis_start: Option<f64>,
+    #[serde(alias = "pension_start")]
+    pension_start: Option<f64>,
+    household: Option<bool>,
+    #[serde(alias = "partner_pension_start")]
+    partner_pension_start: Option<f64>,
+    #[serde(alias = "partner_pension_access_age")]
+    partner_pension_access_age: Option<u32>,
+    #[serde(alias = "partner_personal_allowance")]
+    partner_personal_allowance: Option<f64>,
+    #[serde(alias = "marriage_allowance_transfer")]
+    marriage_allowance_transfer: Option<f64>,
+    #[serde(alias = "cash_start")]
+    cash_start: Option<f64>,
+    #[serde(alias = "bond_ladder_start")]
+    bond_ladder_start: Option<f64>,
+    #[serde(alias = "isa_annual_contribution")]
+    isa_annual_contribution: Option<f64>,
+    #[serde(alias = "isa_annual_contribution_limit")]
+    isa_annual_contribution_limit: Option<f64>,
+    #[serde(alias = "lisa_start")]
+    lisa_start: Option<f64>,
+    #[serde(alias = "lisa_annual_contribution")]
+    lisa_annual_contribution: Option<f64>,
+    #[serde(alias = "lisa_access_age")]
+    lisa_access_age: Option<u32>,
+    #[serde(alias = "taxable_annual_contribution")]
+    taxable_annual_contribution: Option<f64>,
+    #[serde(alias = "pension_annual_contribution")]
+    pension_annual_contribution: Option<f64>,
+    #[serde(alias = "employer_match_rate")]
+    employer_match_rate: Option<f64>,
+    #[serde(alias = "employer_match_cap")]
+    employer_match_cap: Option<f64>,
+    #[serde(alias = "pension_annual_allowance")]
+    pension_annual_allowance: Option<f64>,
+    #[serde(alias = "pension_overflow_to_taxable")]
+    pension_overflow_to_taxable: Option<bool>,
+    #[serde(alias = "contribution_growth_rate")]
+    contribution_growth_rate: Option<f64>,
+    #[serde(alias = "contributions_track_inflation")]
+    contributions_track_inflation: Option<bool>,
+    #[serde(alias = "coast_contribution_fraction")]
+    coast_contribution_fraction: Option<f64>,
+    #[serde(alias = "pre_retirement_earnings")]
+    pre_retirement_earnings: Option<f64>,
+    #[serde(alias = "isa_growth_rate")]
+    isa_growth_rate: Option<f64>,
+    #[serde(alias = "isa_return_volatility")]
+    isa_return_volatility: Option<f64>,
+    #[serde(alias = "taxable_growth_rate")]
+    taxable_growth_rate: Option<f64>,
+    #[serde(alias = "taxable_return_volatility")]
+    taxable_return_volatility: Option<f64>,
+    #[serde(alias = "pension_growth_rate")]
+    pension_growth_rate: Option<f64>,
+    #[serde(alias = "pension_return_volatility")]
+    pension_return_volatility: Option<f64>,
+    #[serde(alias = "return_correlation")]
+    return_correlation: Option<f64>,
+    #[serde(alias = "isa_taxable_correlation")]
+    isa_taxable_correlation: Option<f64>,
+    #[serde(alias = "taxable_pension_correlation")]
+    taxable_pension_correlation: Option<f64>,
+    #[serde(alias = "isa_beta")]
+    isa_beta: Option<f64>,
+    #[serde(alias = "taxable_beta")]
+    taxable_beta: Option<f64>,
+    #[serde(alias = "pension_beta")]
+    pension_beta: Option<f64>,
+    #[serde(alias = "return_distribution")]
+    return_distribution: Option<CliReturnDistribution>,
+    #[serde(alias = "market_model")]
+    market_model: Option<CliMarketModel>,
+    #[serde(
+        default,
+        deserialize_with = "config_market_path_years",
+        rename = "market-path-year",
+        alias = "market_path_year"
+    )]
+    market_path: Option<Vec<(f64, f64, f64, f64)>>,
+    #[serde(alias = "bootstrap_block_years")]
+    bootstrap_block_years: Option<u32>,
+    #[serde(alias = "market_burn_in_years")]
+    market_burn_in_years: Option<u32>,
+    #[serde(alias = "bootstrap_fraction")]
+    bootstrap_fraction: Option<f64>,
+    #[serde(alias = "student_t_dof")]
+    student_t_dof: Option<f64>,
+    #[serde(alias = "garch_alpha")]
+    garch_alpha: Option<f64>,
+    #[serde(alias = "garch_beta")]
+    garch_beta: Option<f64>,
+    #[serde(alias = "garch_long_run_vol")]
+    garch_long_run_vol: Option<f64>,
+    #[serde(alias = "normal_to_crisis_probability")]
+    normal_to_crisis_probability: Option<f64>,
+    #[serde(alias = "crisis_to_normal_probability")]
+    crisis_to_normal_probability: Option<f64>,
+    #[serde(alias = "crisis_return_mean")]
+    crisis_return_mean: Option<f64>,
+    #[serde(alias = "crisis_return_volatility")]
+    crisis_return_volatility: Option<f64>,
+    #[serde(alias = "cgt_basic_rate")]
+    cgt_basic_rate: Option<f64>,
+    #[serde(alias = "cgt_higher_rate")]
+    cgt_higher_rate: Option<f64>,
+    #[serde(alias = "capital_gains_tax_rate")]
+    capital_gains_tax_rate: Option<f64>,
+    #[serde(alias = "capital_gains_allowance")]
+    capital_gains_allowance: Option<f64>,
+    #[serde(alias = "taxable_return_tax_drag")]
+    taxable_return_tax_drag: Option<f64>,
+    #[serde(alias = "taxable_dividend_yield")]
+    taxable_dividend_yield: Option<f64>,
+    #[serde(alias = "dividend_allowance")]
+    dividend_allowance: Option<f64>,
+    #[serde(alias = "dividend_basic_rate")]
+    dividend_basic_rate: Option<f64>,
+    #[serde(alias = "dividend_higher_rate")]
+    dividend_higher_rate: Option<f64>,
+    #[serde(alias = "dividend_additional_rate")]
+    dividend_additional_rate: Option<f64>,
+    #[serde(alias = "isa_fee_rate")]
+    isa_fee_rate: Option<f64>,
+    #[serde(alias = "taxable_fee_rate")]
+    taxable_fee_rate: Option<f64>,
+    #[serde(alias = "pension_fee_rate")]
+    pension_fee_rate: Option<f64>,
+    #[serde(alias = "glide_start_equity")]
+    glide_start_equity: Option<f64>,
+    #[serde(alias = "glide_end_equity")]
+    glide_end_equity: Option<f64>,
+    #[serde(alias = "glide_end_age")]
+    glide_end_age: Option<u32>,
+    #[serde(alias = "pension_tax_mode")]
+    pension_tax_mode: Option<CliPensionTaxMode>,
+    #[serde(alias = "pension_income_tax_rate")]
+    pension_income_tax_rate: Option<f64>,
+    #[serde(alias = "pension_tax_free_pct")]
+    pension_tax_free_pct: Option<f64>,
+    #[serde(alias = "uk_personal_allowance")]
+    uk_personal_allowance: Option<f64>,
+    #[serde(alias = "uk_basic_rate_limit")]
+    uk_basic_rate_limit: Option<f64>,
+    #[serde(alias = "uk_higher_rate_limit")]
+    uk_higher_rate_limit: Option<f64>,
+    #[serde(alias = "uk_basic_rate")]
+    uk_basic_rate: Option<f64>,
+    #[serde(alias = "uk_higher_rate")]
+    uk_higher_rate: Option<f64>,
+    #[serde(alias = "uk_additional_rate")]
+    uk_additional_rate: Option<f64>,
+    #[serde(alias = "uk_allowance_taper_start")]
+    uk_allowance_taper_start: Option<f64>,
+    #[serde(alias = "uk_allowance_taper_end")]
+    uk_allowance_taper_end: Option<f64>,
+    #[serde(alias = "thresholds_frozen_until_age")]
+    thresholds_frozen_until_age: Option<u32>,
+    #[serde(alias = "scottish_starter_rate_limit")]
+    scottish_starter_rate_limit: Option<f64>,
+    #[serde(alias = "scottish_basic_rate_limit")]
+    scottish_basic_rate_limit: Option<f64>,
+    #[serde(alias = "scottish_intermediate_rate_limit")]
+    scottish_intermediate_rate_limit: Option<f64>,
+    #[serde(alias = "scottish_higher_rate_limit")]
+    scottish_higher_rate_limit: Option<f64>,
+    #[serde(alias = "scottish_advanced_rate_limit")]
+    scottish_advanced_rate_limit: Option<f64>,
+    #[serde(alias = "scottish_starter_rate")]
+    scottish_starter_rate: Option<f64>,
+    #[serde(alias = "scottish_basic_rate")]
+    scottish_basic_rate: Option<f64>,
+    #[serde(alias = "scottish_intermediate_rate")]
+    scottish_intermediate_rate: Option<f64>,
+    #[serde(alias = "scottish_higher_rate")]
+    scottish_higher_rate: Option<f64>,
+    #[serde(alias = "scottish_advanced_rate")]
+    scottish_advanced_rate: Option<f64>,
+    #[serde(alias = "scottish_top_rate")]
+    scottish_top_rate: Option<f64>,
+    #[serde(alias = "state_pension_start_age")]
+    state_pension_start_age: Option<u32>,
+    #[serde(alias = "state_pension_annual_income")]
+    state_pension_annual_income: Option<f64>,
+    #[serde(alias = "state_pension_real_growth")]
+    state_pension_real_growth: Option<f64>,
+    #[serde(alias = "state_pension_growth_before_start")]
+    state_pension_growth_before_start: Option<bool>,
+    #[serde(alias = "state_pension_deferral_years")]
+    state_pension_deferral_years: Option<u32>,
+    #[serde(alias = "state_pension_deferral_uplift_rate")]
+    state_pension_deferral_uplift_rate: Option<f64>,
+    #[serde(alias = "db_pension_start_age")]
+    db_pension_start_age: Option<u32>,
+    #[serde(alias = "db_pension_annual_income")]
+    db_pension_annual_income: Option<f64>,
+    #[serde(alias = "annuity_purchase_age")]
+    annuity_purchase_age: Option<u32>,
+    #[serde(alias = "annuity_purchase_amount")]
+    annuity_purchase_amount: Option<f64>,
+    #[serde(alias = "annuity_rate")]
+    annuity_rate: Option<f64>,
+    #[serde(alias = "conversion_ladder_annual_amount")]
+    conversion_ladder_annual_amount: Option<f64>,
+    #[serde(alias = "conversion_ladder_start_age")]
+    conversion_ladder_start_age: Option<u32>,
+    #[serde(alias = "conversion_ladder_end_age")]
+    conversion_ladder_end_age: Option<u32>,
+    #[serde(alias = "rental_income_start_age")]
+    rental_income_start_age: Option<u32>,
+    #[serde(alias = "rental_annual_income")]
+    rental_annual_income: Option<f64>,
+    #[serde(alias = "rental_growth_rate")]
+    rental_growth_rate: Option<f64>,
+    #[serde(alias = "part_time_annual_income")]
+    part_time_annual_income: Option<f64>,
+    #[serde(alias = "part_time_start_age")]
+    part_time_start_age: Option<u32>,
+    #[serde(alias = "part_time_end_age")]
+    part_time_end_age: Option<u32>,
+    #[serde(alias = "inflation_rate")]
+    inflation_rate: Option<f64>,
+    #[serde(alias = "inflation_volatility")]
+    inflation_volatility: Option<f64>,
+    #[serde(alias = "inflation_return_correlation")]
+    inflation_return_correlation: Option<f64>,
+    #[serde(alias = "return_clamp_min")]
+    return_clamp_min: Option<f64>,
+    #[serde(alias = "return_clamp_max")]
+    return_clamp_max: Option<f64>,
+    #[serde(alias = "inflation_clamp_min")]
+    inflation_clamp_min: Option<f64>,
+    #[serde(alias = "inflation_clamp_max")]
+    inflation_clamp_max: Option<f64>,
+    #[serde(alias = "target_annual_income")]
+    target_annual_income: Option<f64>,
+    #[serde(alias = "essential_annual_income")]
+    essential_annual_income: Option<f64>,
+    #[serde(alias = "spending_decline_rate")]
+    spending_decline_rate: Option<f64>,
+    #[serde(alias = "care_cost_age")]
+    care_cost_age: Option<u32>,
+    #[serde(alias = "care_cost_uplift")]
+    care_cost_uplift: Option<f64>,
+    #[serde(alias = "active_phase_end_age")]
+    active_phase_end_age: Option<u32>,
+    #[serde(alias = "active_phase_uplift")]
+    active_phase_uplift: Option<f64>,
+    #[serde(alias = "late_phase_start_age")]
+    late_phase_start_age: Option<u32>,
+    #[serde(alias = "late_phase_uplift")]
+    late_phase_uplift: Option<f64>,
+    #[serde(alias = "mortgage_annual_payment")]
+    mortgage_annual_payment: Option<f64>,
+    #[serde(alias = "mortgage_end_age")]
+    mortgage_end_age: Option<u32>,
+    #[serde(default, deserialize_with = "config_debts", rename = "debt")]
+    debts: Option<Vec<(f64, u32)>>,
+    #[serde(alias = "mortgage_balance")]
+    mortgage_balance: Option<f64>,
+    #[serde(alias = "mortgage_rate")]
+    mortgage_rate: Option<f64>,
+    #[serde(alias = "mortgage_term_years")]
+    mortgage_term_years: Option<u32>,
+    #[serde(
+        default,
+        deserialize_with = "config_age_amounts",
+        rename = "mortgage-overpayment",
+        alias = "mortgage_overpayment"
+    )]
+    mortgage_overpayments: Option<Vec<(u32, f64)>>,
+    #[serde(
+        default,
+        deserialize_with = "config_age_amounts",
+        rename = "lump-expense",
+        alias = "lump_expense"
+    )]
+    lump_expenses: Option<Vec<(u32, f64)>>,
+    #[serde(alias = "max_age")]
+    max_age: Option<u32>,
+    #[serde(alias = "horizon_age")]
+    horizon_age: Option<u32>,
+    #[serde(alias = "bequest_target")]
+    bequest_target: Option<f64>,
+    #[serde(alias = "success_criterion")]
+    success_criterion: Option<CliSuccessCriterion>,
+    simulations: Option<u32>,
+    threads: Option<usize>,
+    #[serde(alias = "success_threshold")]
+    success_threshold: Option<f64>,
+    #[serde(alias = "selection_confidence")]
+    selection_confidence: Option<CliSelectionConfidence>,
+    #[serde(alias = "risk_aversion")]
+    risk_aversion: Option<f64>,
+    #[serde(alias = "utility_discount_rate")]
+    utility_discount_rate: Option<f64>,
+    #[serde(alias = "utility_spending_floor")]
+    utility_spending_floor: Option<f64>,
+    seed: Option<u64>,
+    #[serde(alias = "seed_label")]
+    seed_label: Option<String>,
+    #[serde(alias = "age_invariant_draws")]
+    age_invariant_draws: Option<bool>,
+    #[serde(alias = "stratified_inflation")]
+    stratified_inflation: Option<bool>,
+    #[serde(alias = "bad_year_threshold")]
+    bad_year_threshold: Option<f64>,
+    #[serde(alias = "good_year_threshold")]
+    good_year_threshold: Option<f64>,
+    #[serde(alias = "bad_year_cut")]
+    bad_year_cut: Option<f64>,
+    #[serde(alias = "good_year_raise")]
+    good_year_raise: Option<f64>,
+    #[serde(alias = "min_income_floor")]
+    min_income_floor: Option<f64>,
+    #[serde(alias = "max_income_ceiling")]
+    max_income_ceiling: Option<f64>,
+    #[serde(alias = "min_real_spending_floor")]
+    min_real_spending_floor: Option<f64>,
+    #[serde(alias = "withdrawal_strategy")]
+    withdrawal_strategy: Option<CliWithdrawalStrategy>,
+    #[serde(alias = "gk_lower_guardrail")]
+    gk_lower_guardrail: Option<f64>,
+    #[serde(alias = "gk_upper_guardrail")]
+    gk_upper_guardrail: Option<f64>,
+    #[serde(alias = "gk_adjustment")]
+    gk_adjustment: Option<f64>,
+    #[serde(alias = "vpw_expected_real_return")]
+    vpw_expected_real_return: Option<f64>,
+    #[serde(alias = "constant_withdrawal_rate")]
+    constant_withdrawal_rate: Option<f64>,
+    #[serde(alias = "cape_a")]
+    cape_a: Option<f64>,
+    #[serde(alias = "cape_b")]
+    cape_b: Option<f64>,
+    #[serde(alias = "cape_start")]
+    cape_start: Option<f64>,
+    #[serde(alias = "cape_long_run_mean")]
+    cape_long_run_mean: Option<f64>,
+    #[serde(alias = "cape_reversion_speed")]
+    cape_reversion_speed: Option<f64>,
+    #[serde(alias = "floor_upside_capture")]
+    floor_upside_capture: Option<f64>,
+    #[serde(alias = "bucket_target_years")]
+    bucket_target_years: Option<f64>,
+    #[serde(alias = "good_year_extra_buffer_withdrawal")]
+    good_year_extra_buffer_withdrawal: Option<f64>,
+    #[serde(alias = "min_cash_months")]
+    min_cash_months: Option<f64>,
+    #[serde(alias = "cash_growth_rate")]
+    cash_growth_rate: Option<f64>,
+    #[serde(alias = "bond_ladder_yield")]
+    bond_ladder_yield: Option<f64>,
+    #[serde(alias = "bond_ladder_yield_vol")]
+    bond_ladder_yield_vol: Option<f64>,
+    #[serde(alias = "bond_ladder_inflation_correlation")]
+    bond_ladder_inflation_correlation: Option<f64>,
+    #[serde(alias = "bond_ladder_years")]
+    bond_ladder_years: Option<u32>,
+    #[serde(alias = "bond_ladder_reinvest")]
+    bond_ladder_reinvest: Option<bool>,
+    #[serde(alias = "bond_ladder_target_at_retirement")]
+    bond_ladder_target_at_retirement: Option<f64>,
+    #[serde(alias = "post_access_withdrawal_order")]
+    post_access_withdrawal_order: Option<CliWithdrawalOrder>,
+    #[serde(alias = "pre_access_withdrawal_order")]
+    pre_access_withdrawal_order: Option<CliWithdrawalOrder>,
+}
+
+impl CliConfig {
+    fn load(path: &std::path::Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read config {}: {e}", path.display()))?;
+        let config = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&text).map_err(|e| e.to_string())
+        } else {
+            toml::from_str(&text).map_err(|e| e.to_string())
+        };
+        config.map_err(|e| format!("Invalid config {}: {e}", path.display()))
+    }
+
+    /// Builds the `Cli` from command-line `matches` parsed with every flag optional: a
+    /// flag given on the command line wins, then the file's value, then the flag's
+    /// default. A repeatable flag given on the command line replaces the file's list.
+    fn overlay(self, matches: &clap::ArgMatches) -> Result<Cli, String> {
+        Ok(Cli {
+            current_age: overlaid_value(matches, "current_age", self.current_age)?,
+            pension_access_age: overlaid_value(
+                matches,
+                "pension_access_age",
+                self.pension_access_age,
+            )?,
+            isa_start: overlaid_value(matches, "isa_start", self.isa_start)?,
+            taxable_start: overlaid_value(matches, "taxable_start", self.taxable_start)?,
+            taxable_cost_basis_start: overlaid_value(
+                matches,
+                "taxable_cost_basis_start",
+                self.taxable_cost_basis_start,
+            )?,
+            pension_start: overlaid_value(matches, "pension_start", self.pension_start)?,
+            household: overlaid_value(matches, "household", self.household)?,
+            partner_pension_start: overlaid_value(
+                matches,
+                "partner_pension_start",
+                self.partner_pension_start,
+            )?,
+            partner_pension_access_age: overlaid_value(
+                matches,
+                "partner_pension_access_age",
+                self.partner_pension_access_age,
+            )?,
+            partner_personal_allowance: overlaid_value(
+                matches,
+                "partner_personal_allowance",
+                self.partner_personal_allowance,
+            )?,
+            marriage_allowance_transfer: overlaid_value(
+                matches,
+                "marriage_allowance_transfer",
+                self.marriage_allowance_transfer,
+            )?,
+            cash_start: overlaid_value(matches, "cash_start", self.cash_start)?,
+            bond_ladder_start: overlaid_value(
+                matches,
+                "bond_ladder_start",
+                self.bond_ladder_start,
+            )?,
+            isa_annual_contribution: overlaid_value(
+                matches,
+                "isa_annual_contribution",
+                self.isa_annual_contribution,
+            )?,
+            isa_annual_contribution_limit: overlaid_value(
+                matches,
+                "isa_annual_contribution_limit",
+                self.isa_annual_contribution_limit,
+            )?,
+            lisa_start: overlaid_value(matches, "lisa_start", self.lisa_start)?,
+            lisa_annual_contribution: overlaid_value(
+                matches,
+                "lisa_annual_contribution",
+                self.lisa_annual_contribution,
+            )?,
+            lisa_access_age: overlaid_value(matches, "lisa_access_age", self.lisa_access_age)?,
+            taxable_annual_contribution: overlaid_value(
+                matches,
+                "taxable_annual_contribution",
+                self.taxable_annual_contribution,
+            )?,
+            pension_annual_contribution: overlaid_value(
+                matches,
+                "pension_annual_contribution",
+                self.pension_annual_contribution,
+            )?,
+            employer_match_rate: overlaid_value(
+                matches,
+                "employer_match_rate",
+                self.employer_match_rate,
+            )?,
+            employer_match_cap: overlaid(matches, "employer_match_cap", self.employer_match_cap),
+            pension_annual_allowance: overlaid_value(
+                matches,
+                "pension_annual_allowance",
+                self.pension_annual_allowance,
+            )?,
+            pension_overflow_to_taxable: overlaid_value(
+                matches,
+                "pension_overflow_to_taxable",
+                self.pension_overflow_to_taxable,
+            )?,
+            contribution_growth_rate: overlaid_value(
+                matches,
+                "contribution_growth_rate",
+                self.contribution_growth_rate,
+            )?,
+            contributions_track_inflation: overlaid_value(
+                matches,
+                "contributions_track_inflation",
+                self.contributions_track_inflation,
+            )?,
+            coast_contribution_fraction: overlaid_value(
+                matches,
+                "coast_contribution_fraction",
+                self.coast_contribution_fraction,
+            )?,
+            pre_retirement_earnings: overlaid_value(
+                matches,
+                "pre_retirement_earnings",
+                self.pre_retirement_earnings,
+            )?,
+            isa_growth_rate: overlaid_value(matches, "isa_growth_rate", self.isa_growth_rate)?,
+            isa_return_volatility: overlaid_value(
+                matches,
+                "isa_return_volatility",
+                self.isa_return_volatility,
+            )?,
+            taxable_growth_rate: overlaid(matches, "taxable_growth_rate", self.taxable_growth_rate),
+            taxable_return_volatility: overlaid(
+                matches,
+                "taxable_return_volatility",
+                self.taxable_return_volatility,
+            ),
+            pension_growth_rate: overlaid_value(
+                matches,
+                "pension_growth_rate",
+                self.pension_growth_rate,
+            )?,
+            pension_return_volatility: overlaid_value(
+                matches,
+                "pension_return_volatility",
+                self.pension_return_volatility,
+            )?,
+            return_correlation: overlaid_value(
+                matches,
+                "return_correlation",
+                self.return_correlation,
+            )?,
+            isa_taxable_correlation: overlaid_value(
+                matches,
+                "isa_taxable_correlation",
+                self.isa_taxable_correlation,
+            )?,
+            taxable_pension_correlation: overlaid(
+                matches,
+                "taxable_pension_correlation",
+                self.taxable_pension_correlation,
+            ),
+            isa_beta: overlaid(matches, "isa_beta", self.isa_beta),
+            taxable_beta: overlaid(matches, "taxable_beta", self.taxable_beta),
+            pension_beta: overlaid(matches, "pension_beta", self.pension_beta),
+            return_distribution: overlaid_value(
+                matches,
+                "return_distribution",
+                self.return_distribution,
+            )?,
+            market_model: overlaid_value(matches, "market_model", self.market_model)?,
+            market_path: overlaid_list(matches, "market_path", self.market_path),
+            bootstrap_block_years: overlaid_value(
+                matches,
+                "bootstrap_block_years",
+                self.bootstrap_block_years,
+            )?,
+            market_burn_in_years: overlaid_value(
+                matches,
+                "market_burn_in_years",
+                self.market_burn_in_years,
+            )?,
+            bootstrap_fraction: overlaid_value(
+                matches,
+                "bootstrap_fraction",
+                self.bootstrap_fraction,
+            )?,
+            student_t_dof: overlaid_value(matches, "student_t_dof", self.student_t_dof)?,
+            garch_alpha: overlaid_value(matches, "garch_alpha", self.garch_alpha)?,
+            garch_beta: overlaid_value(matches, "garch_beta", self.garch_beta)?,
+            garch_long_run_vol: overlaid_value(
+                matches,
+                "garch_long_run_vol",
+                self.garch_long_run_vol,
+            )?,
+            normal_to_crisis_probability: overlaid_value(
+                matches,
+                "normal_to_crisis_probability",
+                self.normal_to_crisis_probability,
+            )?,
+            crisis_to_normal_probability: overlaid_value(
+                matches,
+                "crisis_to_normal_probability",
+                self.crisis_to_normal_probability,
+            )?,
+            crisis_return_mean: overlaid_value(
+                matches,
+                "crisis_return_mean",
+                self.crisis_return_mean,
+            )?,
+            crisis_return_volatility: overlaid_value(
+                matches,
+                "crisis_return_volatility",
+                self.crisis_return_volatility,
+            )?,
+            cgt_basic_rate: overlaid_value(matches, "cgt_basic_rate", self.cgt_basic_rate)?,
+            cgt_higher_rate: overlaid_value(matches, "cgt_higher_rate", self.cgt_higher_rate)?,
+            capital_gains_tax_rate: overlaid(
+                matches,
+                "capital_gains_tax_rate",
+                self.capital_gains_tax_rate,
+            ),
+            capital_gains_allowance: overlaid_value(
+                matches,
+                "capital_gains_allowance",
+                self.capital_gains_allowance,
+            )?,
+            taxable_return_tax_drag: overlaid_value(
+                matches,
+                "taxable_return_tax_drag",
+                self.taxable_return_tax_drag,
+            )?,
+            taxable_dividend_yield: overlaid_value(
+                matches,
+                "taxable_dividend_yield",
+                self.taxable_dividend_yield,
+            )?,
+            dividend_allowance: overlaid_value(
+                matches,
+                "dividend_allowance",
+                self.dividend_allowance,
+            )?,
+            dividend_basic_rate: overlaid_value(
+                matches,
+                "dividend_basic_rate",
+                self.dividend_basic_rate,
+            )?,
+            dividend_higher_rate: overlaid_value(
+                matches,
+                "dividend_higher_rate",
+                self.dividend_higher_rate,
+            )?,
+            dividend_additional_rate: overlaid_value(
+                matches,
+                "dividend_additional_rate",
+                self.dividend_additional_rate,
+            )?,
+            isa_fee_rate: overlaid_value(matches, "isa_fee_rate", self.isa_fee_rate)?,
+            taxable_fee_rate: overlaid_value(matches, "taxable_fee_rate", self.taxable_fee_rate)?,
+            pension_fee_rate: overlaid_value(matches, "pension_fee_rate", self.pension_fee_rate)?,
+            glide_start_equity: overlaid_value(
+                matches,
+                "glide_start_equity",
+                self.glide_start_equity,
+            )?,
+            glide_end_equity: overlaid_value(matches, "glide_end_equity", self.glide_end_equity)?,
+            glide_end_age: overlaid(matches, "glide_end_age", self.glide_end_age),
+            pension_tax_mode: overlaid_value(matches, "pension_tax_mode", self.pension_tax_mode)?,
+            pension_income_tax_rate: overlaid_value(
+                matches,
+                "pension_income_tax_rate",
+                self.pension_income_tax_rate,
+            )?,
+            pension_tax_free_pct: overlaid_value(
+                matches,
+                "pension_tax_free_pct",
+                self.pension_tax_free_pct,
+            )?,
+            uk_personal_allowance: overlaid_value(
+                matches,
+                "uk_personal_allowance",
+                self.uk_personal_allowance,
+            )?,
+            uk_basic_rate_limit: overlaid_value(
+                matches,
+                "uk_basic_rate_limit",
+                self.uk_basic_rate_limit,
+            )?,
+            uk_higher_rate_limit: overlaid_value(
+                matches,
+                "uk_higher_rate_limit",
+                self.uk_higher_rate_limit,
+            )?,
+            uk_basic_rate: overlaid_value(matches, "uk_basic_rate", self.uk_basic_rate)?,
+            uk_higher_rate: overlaid_value(matches, "uk_higher_rate", self.uk_higher_rate)?,
+            uk_additional_rate: overlaid_value(
+                matches,
+                "uk_additional_rate",
+                self.uk_additional_rate,
+            )?,
+            uk_allowance_taper_start: overlaid_value(
+                matches,
+                "uk_allowance_taper_start",
+                self.uk_allowance_taper_start,
+            )?,
+            uk_allowance_taper_end: overlaid_value(
+                matches,
+                "uk_allowance_taper_end",
+                self.uk_allowance_taper_end,
+            )?,
+            thresholds_frozen_until_age: overlaid(
+                matches,
+                "thresholds_frozen_until_age",
+                self.thresholds_frozen_until_age,
+            ),
+            scottish_starter_rate_limit: overlaid_value(
+                matches,
+                "scottish_starter_rate_limit",
+                self.scottish_starter_rate_limit,
+            )?,
+            scottish_basic_rate_limit: overlaid_value(
+                matches,
+                "scottish_basic_rate_limit",
+                self.scottish_basic_rate_limit,
+            )?,
+            scottish_intermediate_rate_limit: overlaid_value(
+                matches,
+                "scottish_intermediate_rate_limit",
+                self.scottish_intermediate_rate_limit,
+            )?,
+            scottish_higher_rate_limit: overlaid_value(
+                matches,
+                "scottish_higher_rate_limit",
+                self.scottish_higher_rate_limit,
+            )?,
+            scottish_advanced_rate_limit: overlaid_value(
+                matches,
+                "scottish_advanced_rate_limit",
+                self.scottish_advanced_rate_limit,
+            )?,
+            scottish_starter_rate: overlaid_value(
+                matches,
+                "scottish_starter_rate",
+                self.scottish_starter_rate,
+            )?,
+            scottish_basic_rate: overlaid_value(
+                matches,
+                "scottish_basic_rate",
+                self.scottish_basic_rate,
+            )?,
+            scottish_intermediate_rate: overlaid_value(
+                matches,
+                "scottish_intermediate_rate",
+                self.scottish_intermediate_rate,
+            )?,
+            scottish_higher_rate: overlaid_value(
+                matches,
+                "scottish_higher_rate",
+                self.scottish_higher_rate,
+            )?,
+            scottish_advanced_rate: overlaid_value(
+                matches,
+                "scottish_advanced_rate",
+                self.scottish_advanced_rate,
+            )?,
+            scottish_top_rate: overlaid_value(
+                matches,
+                "scottish_top_rate",
+                self.scottish_top_rate,
+            )?,
+            state_pension_start_age: overlaid_value(
+                matches,
+                "state_pension_start_age",
+                self.state_pension_start_age,
+            )?,
+            state_pension_annual_income: overlaid_value(
+                matches,
+                "state_pension_annual_income",
+                self.state_pension_annual_income,
+            )?,
+            state_pension_real_growth: overlaid_value(
+                matches,
+                "state_pension_real_growth",
+                self.state_pension_real_growth,
+            )?,
+            state_pension_growth_before_start: overlaid_value(
+                matches,
+                "state_pension_growth_before_start",
+                self.state_pension_growth_before_start,
+            )?,
+            state_pension_deferral_years: overlaid_value(
+                matches,
+                "state_pension_deferral_years",
+                self.state_pension_deferral_years,
+            )?,
+            state_pension_deferral_uplift_rate: overlaid_value(
+                matches,
+                "state_pension_deferral_uplift_rate",
+                self.state_pension_deferral_uplift_rate,
+            )?,
+            db_pension_start_age: overlaid_value(
+                matches,
+                "db_pension_start_age",
+                self.db_pension_start_age,
+            )?,
+            db_pension_annual_income: overlaid_value(
+                matches,
+                "db_pension_annual_income",
+                self.db_pension_annual_income,
+            )?,
+            annuity_purchase_age: overlaid(
+                matches,
+                "annuity_purchase_age",
+                self.annuity_purchase_age,
+            ),
+            annuity_purchase_amount: overlaid_value(
+                matches,
+                "annuity_purchase_amount",
+                self.annuity_purchase_amount,
+            )?,
+            annuity_rate: overlaid_value(matches, "annuity_rate", self.annuity_rate)?,
+            conversion_ladder_annual_amount: overlaid_value(
+                matches,
+                "conversion_ladder_annual_amount",
+                self.conversion_ladder_annual_amount,
+            )?,
+            conversion_ladder_start_age: overlaid_value(
+                matches,
+                "conversion_ladder_start_age",
+                self.conversion_ladder_start_age,
+            )?,
+            conversion_ladder_end_age: overlaid_value(
+                matches,
+                "conversion_ladder_end_age",
+                self.conversion_ladder_end_age,
+            )?,
+            rental_income_start_age: overlaid_value(
+                matches,
+                "rental_income_start_age",
+                self.rental_income_start_age,
+            )?,
+            rental_annual_income: overlaid_value(
+                matches,
+                "rental_annual_income",
+                self.rental_annual_income,
+            )?,
+            rental_growth_rate: overlaid_value(
+                matches,
+                "rental_growth_rate",
+                self.rental_growth_rate,
+            )?,
+            part_time_annual_income: overlaid_value(
+                matches,
+                "part_time_annual_income",
+                self.part_time_annual_income,
+            )?,
+            part_time_start_age: overlaid_value(
+                matches,
+                "part_time_start_age",
+                self.part_time_start_age,
+            )?,
+            part_time_end_age: overlaid_value(
+                matches,
+                "part_time_end_age",
+                self.part_time_end_age,
+            )?,
+            inflation_rate: overlaid_value(matches, "inflation_rate", self.inflation_rate)?,
+            inflation_volatility: overlaid_value(
+                matches,
+                "inflation_volatility",
+                self.inflation_volatility,
+            )?,
+            inflation_return_correlation: overlaid_value(
+                matches,
+                "inflation_return_correlation",
+                self.inflation_return_correlation,
+            )?,
+            return_clamp_min: overlaid_value(matches, "return_clamp_min", self.return_clamp_min)?,
+            return_clamp_max: overlaid_value(matches, "return_clamp_max", self.return_clamp_max)?,
+            inflation_clamp_min: overlaid_value(
+                matches,
+                "inflation_clamp_min",
+                self.inflation_clamp_min,
+            )?,
+            inflation_clamp_max: overlaid_value(
+                matches,
+                "inflation_clamp_max",
+                self.inflation_clamp_max,
+            )?,
+            target_annual_income: overlaid_value(
+                matches,
+                "target_annual_income",
+                self.target_annual_income,
+            )?,
+            essential_annual_income: overlaid_value(
+                matches,
+                "essential_annual_income",
+                self.essential_annual_income,
+            )?,
+            spending_decline_rate: overlaid_value(
+                matches,
+                "spending_decline_rate",
+                self.spending_decline_rate,
+            )?,
+            care_cost_age: overlaid_value(matches, "care_cost_age", self.care_cost_age)?,
+            care_cost_uplift: overlaid_value(matches, "care_cost_uplift", self.care_cost_uplift)?,
+            active_phase_end_age: overlaid_value(
+                matches,
+                "active_phase_end_age",
+                self.active_phase_end_age,
+            )?,
+            active_phase_uplift: overlaid_value(
+                matches,
+                "active_phase_uplift",
+                self.active_phase_uplift,
+            )?,
+            late_phase_start_age: overlaid_value(
+                matches,
+                "late_phase_start_age",
+                self.late_phase_start_age,
+            )?,
+            late_phase_uplift: overlaid_value(
+                matches,
+                "late_phase_uplift",
+                self.late_phase_uplift,
+            )?,
+            mortgage_annual_payment: overlaid_value(
+                matches,
+                "mortgage_annual_payment",
+                self.mortgage_annual_payment,
+            )?,
+            mortgage_end_age: overlaid(matches, "mortgage_end_age", self.mortgage_end_age),
+            debts: overlaid_list(matches, "debts", self.debts),
+            mortgage_balance: overlaid_value(matches, "mortgage_balance", self.mortgage_balance)?,
+            mortgage_rate: overlaid_value(matches, "mortgage_rate", self.mortgage_rate)?,
+            mortgage_term_years: overlaid_value(
+                matches,
+                "mortgage_term_years",
+                self.mortgage_term_years,
+            )?,
+            mortgage_overpayments: overlaid_list(
+                matches,
+                "mortgage_overpayments",
+                self.mortgage_overpayments,
+            ),
+            lump_expenses: overlaid_list(matches, "lump_expenses", self.lump_expenses),
+            max_age: overlaid_value(matches, "max_age", self.max_age)?,
+            horizon_age: overlaid_value(matches, "horizon_age", self.horizon_age)?,
+            bequest_target: overlaid_value(matches, "bequest_target", self.bequest_target)?,
+            success_criterion: overlaid_value(
+                matches,
+                "success_criterion",
+                self.success_criterion,
+            )?,
+            simulations: overlaid_value(matches, "simulations", self.simulations)?,
+            threads: overlaid_value(matches, "threads", self.threads)?,
+            success_threshold: overlaid_value(
+                matches,
+                "success_threshold",
+                self.success_threshold,
+            )?,
+            selection_confidence: overlaid_value(
+                matches,
+                "selection_confidence",
+                self.selection_confidence,
+            )?,
+            risk_aversion: overlaid(matches, "risk_aversion", self.risk_aversion),
+            utility_discount_rate: overlaid_value(
+                matches,
+                "utility_discount_rate",
+                self.utility_discount_rate,
+            )?,
+            utility_spending_floor: overlaid_value(
+                matches,
+                "utility_spending_floor",
+                self.utility_spending_floor,
+            )?,
+            seed: overlaid(matches, "seed", self.seed),
+            seed_label: overlaid(matches, "seed_label", self.seed_label),
+            age_invariant_draws: overlaid_value(
+                matches,
+                "age_invariant_draws",
+                self.age_invariant_draws,
+            )?,
+            stratified_inflation: overlaid_value(
+                matches,
+                "stratified_inflation",
+                self.stratified_inflation,
+            )?,
+            bad_year_threshold: overlaid_value(
+                matches,
+                "bad_year_threshold",
+                self.bad_year_threshold,
+            )?,
+            good_year_threshold: overlaid_value(
+                matches,
+                "good_year_threshold",
+                self.good_year_threshold,
+            )?,
+            bad_year_cut: overlaid_value(matches, "bad_year_cut", self.bad_year_cut)?,
+            good_year_raise: overlaid_value(matches, "good_year_raise", self.good_year_raise)?,
+            min_income_floor: overlaid_value(matches, "min_income_floor", self.min_income_floor)?,
+            max_income_ceiling: overlaid_value(
+                matches,
+                "max_income_ceiling",
+                self.max_income_ceiling,
+            )?,
+            min_real_spending_floor: overlaid_value(
+                matches,
+                "min_real_spending_floor",
+                self.min_real_spending_floor,
+            )?,
+            withdrawal_strategy: overlaid_value(
+                matches,
+                "withdrawal_strategy",
+                self.withdrawal_strategy,
+            )?,
+            gk_lower_guardrail: overlaid_value(
+                matches,
+                "gk_lower_guardrail",
+                self.gk_lower_guardrail,
+            )?,
+            gk_upper_guardrail: overlaid_value(
+                matches,
+                "gk_upper_guardrail",
+                self.gk_upper_guardrail,
+            )?,
+            gk_adjustment: overlaid_value(matches, "gk_adjustment", self.gk_adjustment)?,
+            vpw_expected_real_return: overlaid_value(
+                matches,
+                "vpw_expected_real_return",
+                self.vpw_expected_real_return,
+            )?,
+            constant_withdrawal_rate: overlaid_value(
+                matches,
+                "constant_withdrawal_rate",
+                self.constant_withdrawal_rate,
+            )?,
+            cape_a: overlaid_value(matches, "cape_a", self.cape_a)?,
+            cape_b: overlaid_value(matches, "cape_b", self.cape_b)?,
+            cape_start: overlaid_value(matches, "cape_start", self.cape_start)?,
+            cape_long_run_mean: overlaid_value(
+                matches,
+                "cape_long_run_mean",
+                self.cape_long_run_mean,
+            )?,
+            cape_reversion_speed: overlaid_value(
+                matches,
+                "cape_reversion_speed",
+                self.cape_reversion_speed,
+            )?,
+            floor_upside_capture: overlaid_value(
+                matches,
+                "floor_upside_capture",
+                self.floor_upside_capture,
+            )?,
+            bucket_target_years: overlaid_value(
+                matches,
+                "bucket_target_years",
+                self.bucket_target_years,
+            )?,
+            good_year_extra_buffer_withdrawal: overlaid_value(
+                matches,
+                "good_year_extra_buffer_withdrawal",
+                self.good_year_extra_buffer_withdrawal,
+            )?,
+            min_cash_months: overlaid_value(matches, "min_cash_months", self.min_cash_months)?,
+            cash_growth_rate: overlaid_value(matches, "cash_growth_rate", self.cash_growth_rate)?,
+            bond_ladder_yield: overlaid_value(
+                matches,
+                "bond_ladder_yield",
+                self.bond_ladder_yield,
+            )?,
+            bond_ladder_yield_vol: overlaid_value(
+                matches,
+                "bond_ladder_yield_vol",
+                self.bond_ladder_yield_vol,
+            )?,
+            bond_ladder_inflation_correlation: overlaid_value(
+                matches,
+                "bond_ladder_inflation_correlation",
+                self.bond_ladder_inflation_correlation,
+            )?,
+            bond_ladder_years: overlaid_value(
+                matches,
+                "bond_ladder_years",
+                self.bond_ladder_years,
+            )?,
+            bond_ladder_reinvest: overlaid_value(
+                matches,
+                "bond_ladder_reinvest",
+                self.bond_ladder_reinvest,
+            )?,
+            bond_ladder_target_at_retirement: overlaid_value(
+                matches,
+                "bond_ladder_target_at_retirement",
+                self.bond_ladder_target_at_retirement,
+            )?,
+            post_access_withdrawal_order: overlaid_value(
+                matches,
+                "post_access_withdrawal_order",
+                self.post_access_withdrawal_order,
+            )?,
+            pre_access_withdrawal_order: overlaid(
+                matches,
+                "pre_access_withdrawal_order",
+                self.pre_access_withdrawal_order,
+            ),
+        })
+    }
+}
+
+fn given_on_command_line(matches: &clap::ArgMatches, id: &str) -> bool {
+    matches.value_source(id) == Some(clap::parser::ValueSource::CommandLine)
+}
+
+fn overlaid<T: Clone + Send + Sync + 'static>(
+    matches: &clap::ArgMatches,
+    id: &str,
+    config: Option<T>,
+) -> Option<T> {
+    if given_on_command_line(matches, id) {
+        return matches.get_one::<T>(id).cloned();
+    }
+    config.or_else(|| matches.get_one::<T>(id).cloned())
+}
+
+fn overlaid_value<T: Clone + Send + Sync + 'static>(
+    matches: &clap::ArgMatches,
+    id: &str,
+    config: Option<T>,
+) -> Result<T, String> {
+    overlaid(matches, id, config).ok_or_else(|| {
+        format!(
+            "--{} is required on the command line or in the config file",
+            id.replace('_', "-")
+        )
+    })
+}
+
+fn overlaid_list<T: Clone + Send + Sync + 'static>(
+    matches: &clap::ArgMatches,
+    id: &str,
+    config: Option<Vec<T>>,
+) -> Vec<T> {
+    if given_on_command_line(matches, id) {
+        return matches
+            .get_many::<T>(id)
+            .map(|values| values.cloned().collect())
+            .unwrap_or_default();
+    }
+    config.unwrap_or_default()
+}
+
+/// Parses a config list with the repeatable flag's own value parser.
+fn config_list<'de, D, T>(
+    deserializer: D,
+    parse: fn(&str) -> Result<T, String>,
+) -> Result<Option<Vec<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|value| parse(value).map_err(serde::de::Error::custom))
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+fn config_market_path_years<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<MarketPathYear>>, D::Error> {
+    config_list(deserializer, parse_market_path_year)
+}
+
+fn config_debts<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<(f64, u32)>>, D::Error> {
+    config_list(deserializer, parse_debt)
+}
+
+fn config_age_amounts<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<(u32, f64)>>, D::Error> {
+    config_list(deserializer, parse_lump_expense)
+}
+
+/// Parses `fire simulate` flags, filling the ones not given on the command line from
+/// `--config`. The strict parse is kept unless a config file is given, so its errors
+/// (such as a missing required flag) are reported as usual.
+fn parse_simulate_command(
+    args: Vec<std::ffi::OsString>,
+    strict: Result<SimulateCommand, clap::Error>,
+) -> Result<SimulateCommand, String> {
+    let matches = <SimulateCommand as clap::CommandFactory>::command()
+        .mut_args(|arg| arg.required(false))
+        .try_get_matches_from(args)
+        .map_err(|e| e.to_string())?;
+    let Some(path) = matches.get_one::<std::path::PathBuf>("config") else {
+        return strict.map_err(|e| e.to_string());
+    };
+    Ok(SimulateCommand {
+        cli: CliConfig::load(path)?.overlay(&matches)?,
+        json: matches.get_flag("json"),
+        jsonl: matches.get_flag("jsonl"),
+        config: Some(path.clone()),
+    })
+}
+
+/// Entry point for `fire simulate ...`: parses the same flags as the API model, runs the
+/// sweep and writes a text table, the `/api/simulate` JSON or, with `--jsonl`, one line
+/// per candidate age to `out`, flushing each line as soon as that age finishes.
+/// `args[0]` is the program name; `--help` text is written to `out` like any output.
+pub fn run_simulate_command<I, T, W>(args: I, out: &mut W) -> Result<(), String>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<std::ffi::OsString> + Clone,
+    W: std::io::Write + Send,
+{
+    let args: Vec<std::ffi::OsString> = args.into_iter().map(Into::into).collect();
+    let command = match SimulateCommand::try_parse_from(&args) {
+        Ok(command) if command.config.is_none() => command,
+        Err(e)
+            if matches!(
+                e.kind(),
+                clap::error::ErrorKind::DisplayHelp | clap::error::ErrorKind::DisplayVersion
+            ) =>
+        {
+            return write_output(out, &e.to_string());
+        }
+        strict => parse_simulate_command(args, strict)?,
+    };
+
+    let request = ApiRequest {
+        inputs: build_inputs(command.cli).map_err(|errors| validation_message(&errors))?,
+        options: ApiOptions {
+            mode: AnalysisMode::RetirementSweep,
+            coast_retirement_age: None,
+            cashflow_age: None,
+            summary_only: false,
+        },
+    };
+    if command.jsonl {
+        let sink = Mutex::new((out, Ok(())));
+        run_model_with_age_results(&request.inputs, &|result| {
+            let mut sink = sink.lock().expect("jsonl output lock poisoned");
+            let (out, status) = &mut *sink;
+            if status.is_ok() {
+                *status = write_jsonl_line(out, result);
+            }
+        })
+        .expect(VALIDATED_INPUTS);
+        let (_, status) = sink.into_inner().expect("jsonl output lock poisoned");
+        return status.map_err(|e| e.to_string());
+    }
+    let response = run_simulate_request(&request);
+    if command.json {
+        let json = serde_json::to_string_pretty(&response).map_err(|e| e.to_string())?;
+        return write_output(out, &json);
+    }
+    write_output(out, &render_age_table(&response))
+}
+
+fn write_output(out: &mut impl std::io::Write, text: &str) -> Result<(), String> {
+    out.write_all(text.as_bytes()).map_err(|e| e.to_string())
+}
+
+fn write_jsonl_line(out: &mut impl std::io::Write, result: &AgeResult) -> std::io::Result<()> {
+    serde_json::to_writer(&mut *out, result)?;
+    out.write_all(b"\n")?;
+    out.flush()
+}
+
+fn render_age_table(response: &SimulateResponse) -> String {
+    let mut out = format!(
+        "{:>4}  {:>8}  {:>7}  {:>15}  {:>15}  {:>15}  {:>15}  {:>10}\n",
+        "age",
+        "success",
+        "+/-",
+        "median pot",
+        "p10 pot",
+        "median terminal",
+        "p10 terminal",
+        "p10 income"
+    );
+    for r in response.age_results.iter().flatten() {
+        let marker = if Some(r.retirement_age) == response.selected_retirement_age {
+            '*'
+        } else {
+            ' '
+        };
+        out.push_str(&format!(
+            "{:>3}{marker}  {:>7.1}%  {:>6.1}%  {:>15.0}  {:>15.0}  {:>15.0}  {:>15.0}  {:>10.3}\n",
+            r.retirement_age,
+            r.success_rate * 100.0,
+            r.success_ci_half_width * 100.0,
+            r.median_retirement_pot,
+            r.p10_retirement_pot,
+            r.median_terminal_pot,
+            r.p10_terminal_pot,
+            r.p10_min_income_ratio,
+        ));
+    }
+    match response.selected_retirement_age {
+        Some(age) => out.push_str(&format!(
+            "\nEarliest age meeting {:.0}% success: {age} (*)\n",
+            response.success_threshold * 100.0
+        )),
+        None => out.push_str(&format!(
+            "\nNo tested age meets {:.0}% success; best is {}\n",
+            response.success_threshold * 100.0,
+            response.best_retirement_age
+        )),
+    }
+    out
 }
 
-#[derive(Copy, Clone, Debug)]
-struct ApiOptions {
-    mode: AnalysisMode,
-    coast_retirement_age: Option<u32>,
+async fn solve_goal_handler_impl(payload: SolveGoalPayload, limits: ServerLimits) -> Response {
+    let request = match api_request_from_payload(payload.simulation.clone(), limits) {
+        Ok(request) => request,
+        Err(error) => return error.into_response(),
+    };
+
+    let config = match build_goal_solve_config(&request.inputs, &payload, limits) {
+        Ok(config) => config,
+        Err(msg) => return error_response(StatusCode::BAD_REQUEST, &msg),
+    };
+
+    let result = match solve_goal(&request.inputs, config) {
+        Ok(result) => result,
+        Err(msg) => return error_response(StatusCode::BAD_REQUEST, &msg),
+    };
+
+    json_response(StatusCode::OK, build_solve_goal_response(result))
 }
 
-#[derive(Debug)]
-struct ApiRequest {
-    inputs: Inputs,
-    options: ApiOptions,
+fn build_goal_solve_config(
+    inputs: &Inputs,
+    payload: &SolveGoalPayload,
+    limits: ServerLimits,
+) -> Result<GoalSolveConfig, String> {
+    let goal_type = payload
+        .goal_type
+        .unwrap_or(ApiGoalType::RequiredContribution);
+    let target_retirement_age = payload
+        .target_retirement_age
+        .unwrap_or(inputs.max_retirement_age);
+
+    let target_success_pct = payload
+        .target_success_threshold
+        .unwrap_or(inputs.success_threshold * 100.0);
+    if !target_success_pct.is_finite() || !(0.0..=100.0).contains(&target_success_pct) {
+        return Err("targetSuccessThreshold must be between 0 and 100".to_string());
+    }
+
+    let target_cvar_pct = match (goal_type, payload.target_cvar5_income_ratio) {
+        (_, Some(pct)) if !pct.is_finite() || pct < 0.0 => {
+            return Err("targetCvar5IncomeRatio must be >= 0".to_string());
+        }
+        (_, Some(pct)) => pct,
+        (ApiGoalType::MaxIncomeCvarConstrained, None) => {
+            return Err(
+                "targetCvar5IncomeRatio is required for max-income-cvar-constrained".to_string(),
+            );
+        }
+        (_, None) => 0.0,
+    };
+
+    let default_search_max = match goal_type {
+        ApiGoalType::RequiredContribution => {
+            let base_total = inputs.isa_annual_contribution.max(0.0)
+                + inputs.taxable_annual_contribution.max(0.0)
+                + inputs.pension_annual_contribution.max(0.0);
+            (base_total.max(1.0) * 4.0).max(200_000.0)
+        }
+        ApiGoalType::MaxIncome | ApiGoalType::MaxIncomeCvarConstrained => {
+            (inputs.target_annual_income * 2.0)
+                .max(inputs.target_annual_income + 20_000.0)
+                .max(100_000.0)
+        }
+        // Ignored by the age scan; only needs to pass bound validation.
+        ApiGoalType::EarliestRetirementAge => inputs.max_retirement_age as f64,
+        // Ignored by the split grid, which keeps the current contribution total.
+        ApiGoalType::OptimalContributionSplit => 1.0,
+    };
+
+    let search_min = payload.search_min.unwrap_or(0.0);
+    let search_max = payload.search_max.unwrap_or(default_search_max);
+    let tolerance = payload.tolerance.unwrap_or(100.0);
+    let max_iterations = payload.max_iterations.unwrap_or(24);
+
+    let simulations_per_iteration = payload
+        .simulations_per_iteration
+        .unwrap_or(inputs.simulations.clamp(1_000, 5_000));
+    let final_simulations = payload.final_simulations.unwrap_or(
+        simulations_per_iteration
+            .saturating_mul(2)
+            .max(simulations_per_iteration)
+            .min(20_000),
+    );
+
+    for (key, simulations) in [
+        ("simulationsPerIteration", simulations_per_iteration),
+        ("finalSimulations", final_simulations),
+    ] {
+        if let Err(PayloadError::OverLimit(msg)) = limits.check_simulations(key, simulations) {
+            return Err(msg);
+        }
+    }
+
+    Ok(GoalSolveConfig {
+        goal_type: goal_type.into(),
+        target_retirement_age,
+        target_success_threshold: target_success_pct / 100.0,
+        min_cvar5_income_ratio: target_cvar_pct / 100.0,
+        search_min,
+        search_max,
+        tolerance,
+        max_iterations,
+        simulations_per_iteration,
+        final_simulations,
+        secant_acceleration: payload.secant_acceleration.unwrap_or(false),
+    })
 }
 
-#[derive(Copy, Clone)]
-struct CashflowResponse<'a> {
-    candidate_age: u32,
-    retirement_age: u32,
-    contribution_stop_age: u32,
-    years: &'a [CashflowYearResult],
+fn build_solve_goal_response(result: GoalSolveResult) -> SolveGoalResponse {
+    let solved_contribution_total = if matches!(
+        result.goal_type,
+        GoalType::RequiredContribution | GoalType::OptimalContributionSplit
+    ) {
+        result.solved_value
+    } else {
+        None
+    };
+
+    let (solved_contribution_isa, solved_contribution_taxable, solved_contribution_pension) =
+        if let Some(ContributionAllocation {
+            isa,
+            taxable,
+            pension,
+        }) = result.solved_contributions
+        {
+            (Some(isa), Some(taxable), Some(pension))
+        } else {
+            (None, None, None)
+        };
+
+    SolveGoalResponse {
+        goal_type: result.goal_type.into(),
+        target_retirement_age: result.target_retirement_age,
+        target_success_threshold: result.target_success_threshold,
+        target_cvar5_income_ratio: result.min_cvar5_income_ratio,
+        search_min: result.search_min,
+        search_max: result.search_max,
+        tolerance: result.tolerance,
+        max_iterations: result.max_iterations,
+        simulations_per_iteration: result.simulations_per_iteration,
+        final_simulations: result.final_simulations,
+        solved_value: result.solved_value,
+        solved_contribution_total,
+        solved_contribution_isa,
+        solved_contribution_taxable,
+        solved_contribution_pension,
+        achieved_success_rate: result.achieved_success_rate,
+        achieved_success_ci_half_width: result.achieved_success_ci_half_width,
+        achieved_cvar5_min_income_ratio: result.achieved_cvar5_min_income_ratio,
+        converged: result.converged,
+        feasible: result.feasible,
+        message: result.message,
+        iterations: result
+            .iterations
+            .into_iter()
+            .map(
+                |GoalSolveIteration {
+                     iteration,
+                     lower_bound,
+                     upper_bound,
+                     candidate_value,
+                     success_rate,
+                     success_ci_half_width,
+                     cvar5_min_income_ratio,
+                 }| SolveGoalIterationResponse {
+                    iteration,
+                    lower_bound,
+                    upper_bound,
+                    candidate_value,
+                    success_rate,
+                    success_ci_half_width,
+                    cvar5_min_income_ratio,
+                },
+            )
+            .collect(),
+    }
 }
 
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct SimulateResponse {
-    mode: ResponseMode,
-    withdrawal_policy: ApiWithdrawalStrategy,
-    coast_retirement_age: Option<u32>,
-    success_threshold: f64,
-    selected_retirement_age: Option<u32>,
-    best_retirement_age: u32,
-    cashflow_candidate_age: u32,
-    cashflow_retirement_age: u32,
-    cashflow_contribution_stop_age: u32,
-    age_results: Vec<AgeResult>,
-    cashflow_years: Vec<CashflowYearResult>,
+fn with_cache_control<R: IntoResponse>(response: R) -> Response {
+    let mut response = response.into_response();
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        "no-store".parse().expect("valid header"),
+    );
+    response
 }
 
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct SolveGoalIterationResponse {
-    iteration: u32,
-    lower_bound: f64,
-    upper_bound: f64,
-    candidate_value: f64,
-    success_rate: f64,
-    success_ci_half_width: f64,
+fn json_response<T: Serialize>(status: StatusCode, body: T) -> Response {
+    let mut response = (status, Json(body)).into_response();
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        "no-store".parse().expect("valid header"),
+    );
+    response
+}
+
+fn error_response(status: StatusCode, msg: &str) -> Response {
+    json_response(
+        status,
+        ErrorResponse {
+            error: msg.to_string(),
+        },
+    )
+}
+
+fn validation_error_response(errors: Vec<ValidationError>) -> Response {
+    json_response(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        ValidationErrorResponse {
+            error: validation_message(&errors),
+            errors,
+        },
+    )
+}
+
+#[cfg(test)]
+fn api_request_from_json(json: &str) -> Result<ApiRequest, String> {
+    let payload = serde_json::from_str::<SimulatePayload>(json)
+        .map_err(|e| format!("Invalid API JSON payload: {e}"))?;
+    api_request_from_payload(payload, ServerLimits::default()).map_err(|error| match error {
+        PayloadError::OverLimit(msg) => msg,
+        PayloadError::Invalid(errors) => validation_message(&errors),
+    })
 }
 
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct SolveGoalResponse {
-    goal_type: ApiGoalType,
-    target_retirement_age: u32,
-    target_success_threshold: f64,
-    search_min: f64,
-    search_max: f64,
-    tolerance: f64,
-    max_iterations: u32,
-    simulations_per_iteration: u32,
-    final_simulations: u32,
-    solved_value: Option<f64>,
-    solved_contribution_total: Option<f64>,
-    solved_contribution_isa: Option<f64>,
-    solved_contribution_taxable: Option<f64>,
-    solved_contribution_pension: Option<f64>,
-    achieved_success_rate: Option<f64>,
-    achieved_success_ci_half_width: Option<f64>,
-    converged: bool,
-    feasible: bool,
-    message: String,
-    iterations: Vec<SolveGoalIterationResponse>,
-}
+fn api_request_from_payload(
+    payload: SimulatePayload,
+    limits: ServerLimits,
+) -> Result<ApiRequest, PayloadError> {
+    let mut cli = default_cli_for_api();
+    let mut options = ApiOptions {
+        mode: AnalysisMode::RetirementSweep,
+        coast_retirement_age: None,
+        cashflow_age: None,
+        summary_only: false,
+    };
+
+    if let Some(v) = payload.current_age {
+        cli.current_age = v;
+    }
+    if let Some(v) = payload.pension_access_age {
+        cli.pension_access_age = v;
+    }
+    if let Some(v) = payload.max_age {
+        cli.max_age = v;
+    }
+    if let Some(v) = payload.horizon_age {
+        cli.horizon_age = v;
+    }
+    if let Some(v) = payload.bequest_target {
+        cli.bequest_target = v;
+    }
+    if let Some(v) = payload.success_criterion {
+        cli.success_criterion = v.into();
+    }
+    if let Some(v) = payload.simulations {
+        cli.simulations = v;
+    }
+    if let Some(v) = payload.seed {
+        cli.seed = Some(v);
+    }
+    if let Some(v) = payload.seed_label {
+        cli.seed_label = Some(v);
+    }
+    if let Some(v) = payload.age_invariant_draws {
+        cli.age_invariant_draws = v;
+    }
+    if let Some(v) = payload.stratified_inflation {
+        cli.stratified_inflation = v;
+    }
+
+    if let Some(v) = payload.isa_start {
+        cli.isa_start = v;
+    }
+    if let Some(v) = payload.taxable_start {
+        cli.taxable_start = v;
+    }
+    if let Some(v) = payload.taxable_basis_start {
+        cli.taxable_cost_basis_start = v;
+    }
+    if let Some(v) = payload.pension_start {
+        cli.pension_start = v;
+    }
+    if let Some(v) = payload.household {
+        cli.household = v;
+    }
+    if let Some(v) = payload.partner_pension_start {
+        cli.partner_pension_start = v;
+    }
+    if let Some(v) = payload.partner_pension_access_age {
+        cli.partner_pension_access_age = v;
+    }
+    if let Some(v) = payload.partner_personal_allowance {
+        cli.partner_personal_allowance = v;
+    }
+    if let Some(v) = payload.marriage_allowance_transfer {
+        cli.marriage_allowance_transfer = v;
+    }
+    if let Some(v) = payload.cash_start {
+        cli.cash_start = v;
+    }
+    if let Some(v) = payload.bond_ladder_start {
+        cli.bond_ladder_start = v;
+    }
+
+    if let Some(v) = payload.isa_contribution {
+        cli.isa_annual_contribution = v;
+    }
+    if let Some(v) = payload.isa_limit {
+        cli.isa_annual_contribution_limit = v;
+    }
+    if let Some(v) = payload.lisa_start {
+        cli.lisa_start = v;
+    }
+    if let Some(v) = payload.lisa_contribution {
+        cli.lisa_annual_contribution = v;
+    }
+    if let Some(v) = payload.lisa_access_age {
+        cli.lisa_access_age = v;
+    }
+    if let Some(v) = payload.taxable_contribution {
+        cli.taxable_annual_contribution = v;
+    }
+    if let Some(v) = payload.pension_contribution {
+        cli.pension_annual_contribution = v;
+    }
+    if let Some(v) = payload.employer_match_rate {
+        cli.employer_match_rate = v;
+    }
+    if let Some(v) = payload.employer_match_cap {
+        cli.employer_match_cap = Some(v);
+    }
+    if let Some(v) = payload.pension_annual_allowance {
+        cli.pension_annual_allowance = v;
+    }
+    if let Some(v) = payload.pension_overflow_to_taxable {
+        cli.pension_overflow_to_taxable = v;
+    }
+    if let Some(v) = payload.contribution_growth {
+        cli.contribution_growth_rate = v;
+    }
+    if let Some(v) = payload.contributions_track_inflation {
+        cli.contributions_track_inflation = v;
+    }
+    if let Some(v) = payload.coast_contribution_fraction {
+        cli.coast_contribution_fraction = v;
+    }
+    if let Some(v) = payload.pre_retirement_earnings {
+        cli.pre_retirement_earnings = v;
+    }
 
-#[derive(Debug, Serialize)]
-struct ErrorResponse {
-    error: String,
-}
+    if let Some(v) = payload.cgt_rate {
+        cli.cgt_basic_rate = v;
+        cli.cgt_higher_rate = v;
+    }
+    if let Some(v) = payload.cgt_basic_rate {
+        cli.cgt_basic_rate = v;
+    }
+    if let Some(v) = payload.cgt_higher_rate {
+        cli.cgt_higher_rate = v;
+    }
+    if let Some(v) = payload.cgt_allowance {
+        cli.capital_gains_allowance = v;
+    }
+    if let Some(v) = payload.taxable_tax_drag {
+        cli.taxable_return_tax_drag = v;
+    }
+    if let Some(v) = payload.taxable_dividend_yield {
+        cli.taxable_dividend_yield = v;
+    }
+    if let Some(v) = payload.dividend_allowance {
+        cli.dividend_allowance = v;
+    }
+    if let Some(v) = payload.dividend_basic_rate {
+        cli.dividend_basic_rate = v;
+    }
+    if let Some(v) = payload.dividend_higher_rate {
+        cli.dividend_higher_rate = v;
+    }
+    if let Some(v) = payload.dividend_additional_rate {
+        cli.dividend_additional_rate = v;
+    }
+    if let Some(v) = payload.isa_fee_rate {
+        cli.isa_fee_rate = v;
+    }
+    if let Some(v) = payload.taxable_fee_rate {
+        cli.taxable_fee_rate = v;
+    }
+    if let Some(v) = payload.pension_fee_rate {
+        cli.pension_fee_rate = v;
+    }
+    if let Some(v) = payload.glide_start_equity {
+        cli.glide_start_equity = v;
+    }
+    if let Some(v) = payload.glide_end_equity {
+        cli.glide_end_equity = v;
+    }
+    if let Some(v) = payload.glide_end_age {
+        cli.glide_end_age = Some(v);
+    }
 
-#[derive(Debug, Serialize)]
-struct HealthResponse {
-    status: &'static str,
-}
+    if let Some(v) = payload.pension_tax_mode {
+        cli.pension_tax_mode = v.into();
+    }
+    if let Some(v) = payload.pension_income_tax_rate {
+        cli.pension_income_tax_rate = v;
+    }
+    if let Some(v) = payload.pension_tax_free_pct {
+        cli.pension_tax_free_pct = v;
+    }
+    if let Some(v) = payload.uk_personal_allowance {
+        cli.uk_personal_allowance = v;
+    }
+    if let Some(v) = payload.uk_basic_rate_limit {
+        cli.uk_basic_rate_limit = v;
+    }
+    if let Some(v) = payload.uk_higher_rate_limit {
+        cli.uk_higher_rate_limit = v;
+    }
+    if let Some(v) = payload.uk_basic_rate {
+        cli.uk_basic_rate = v;
+    }
+    if let Some(v) = payload.uk_higher_rate {
+        cli.uk_higher_rate = v;
+    }
+    if let Some(v) = payload.uk_additional_rate {
+        cli.uk_additional_rate = v;
+    }
+    if let Some(v) = payload.uk_allowance_taper_start {
+        cli.uk_allowance_taper_start = v;
+    }
+    if let Some(v) = payload.uk_allowance_taper_end {
+        cli.uk_allowance_taper_end = v;
+    }
+    if let Some(v) = payload.thresholds_frozen_until_age {
+        cli.thresholds_frozen_until_age = Some(v);
+    }
+    if let Some(v) = payload.scottish_starter_rate_limit {
+        cli.scottish_starter_rate_limit = v;
+    }
+    if let Some(v) = payload.scottish_basic_rate_limit {
+        cli.scottish_basic_rate_limit = v;
+    }
+    if let Some(v) = payload.scottish_intermediate_rate_limit {
+        cli.scottish_intermediate_rate_limit = v;
+    }
+    if let Some(v) = payload.scottish_higher_rate_limit {
+        cli.scottish_higher_rate_limit = v;
+    }
+    if let Some(v) = payload.scottish_advanced_rate_limit {
+        cli.scottish_advanced_rate_limit = v;
+    }
+    if let Some(v) = payload.scottish_starter_rate {
+        cli.scottish_starter_rate = v;
+    }
+    if let Some(v) = payload.scottish_basic_rate {
+        cli.scottish_basic_rate = v;
+    }
+    if let Some(v) = payload.scottish_intermediate_rate {
+        cli.scottish_intermediate_rate = v;
+    }
+    if let Some(v) = payload.scottish_higher_rate {
+        cli.scottish_higher_rate = v;
+    }
+    if let Some(v) = payload.scottish_advanced_rate {
+        cli.scottish_advanced_rate = v;
+    }
+    if let Some(v) = payload.scottish_top_rate {
+        cli.scottish_top_rate = v;
+    }
+    if let Some(v) = payload.state_pension_start_age {
+        cli.state_pension_start_age = v;
+    }
+    if let Some(v) = payload.state_pension_income {
+        cli.state_pension_annual_income = v;
+    }
+    if let Some(v) = payload.state_pension_real_growth {
+        cli.state_pension_real_growth = v;
+    }
+    if let Some(v) = payload.state_pension_growth_before_start {
+        cli.state_pension_growth_before_start = v;
+    }
+    if let Some(v) = payload.state_pension_deferral_years {
+        cli.state_pension_deferral_years = v;
+    }
+    if let Some(v) = payload.state_pension_deferral_uplift_rate {
+        cli.state_pension_deferral_uplift_rate = v;
+    }
+    if let Some(v) = payload.db_pension_start_age {
+        cli.db_pension_start_age = v;
+    }
+    if let Some(v) = payload.db_pension_income {
+        cli.db_pension_annual_income = v;
+    }
+    if let Some(v) = payload.annuity_purchase_age {
+        cli.annuity_purchase_age = Some(v);
+    }
+    if let Some(v) = payload.annuity_purchase_amount {
+        cli.annuity_purchase_amount = v;
+    }
+    if let Some(v) = payload.annuity_rate {
+        cli.annuity_rate = v;
+    }
+    if let Some(v) = payload.conversion_ladder_annual_amount {
+        cli.conversion_ladder_annual_amount = v;
+    }
+    if let Some(v) = payload.conversion_ladder_start_age {
+        cli.conversion_ladder_start_age = v;
+    }
+    if let Some(v) = payload.conversion_ladder_end_age {
+        cli.conversion_ladder_end_age = v;
+    }
+    if let Some(v) = payload.rental_income_start_age {
+        cli.rental_income_start_age = v;
+    }
+    if let Some(v) = payload.rental_income {
+        cli.rental_annual_income = v;
+    }
+    if let Some(v) = payload.rental_growth_rate {
+        cli.rental_growth_rate = v;
+    }
+    if let Some(v) = payload.part_time_income {
+        cli.part_time_annual_income = v;
+    }
+    if let Some(v) = payload.part_time_start_age {
+        cli.part_time_start_age = v;
+    }
+    if let Some(v) = payload.part_time_end_age {
+        cli.part_time_end_age = v;
+    }
 
-fn build_inputs(cli: Cli) -> Result<Inputs, String> {
-    if cli.pension_access_age < cli.current_age {
-        return Err("--pension-access-age must be >= --current-age".to_string());
+    if let Some(v) = payload.isa_mean {
+        cli.isa_growth_rate = v;
+    }
+    if let Some(v) = payload.isa_vol {
+        cli.isa_return_volatility = v;
+    }
+    if let Some(v) = payload.taxable_mean {
+        cli.taxable_growth_rate = Some(v);
+    }
+    if let Some(v) = payload.taxable_vol {
+        cli.taxable_return_volatility = Some(v);
+    }
+    if let Some(v) = payload.pension_mean {
+        cli.pension_growth_rate = v;
+    }
+    if let Some(v) = payload.pension_vol {
+        cli.pension_return_volatility = v;
+    }
+    if let Some(v) = payload.correlation {
+        cli.return_correlation = v;
+    }
+    if let Some(v) = payload.isa_taxable_correlation {
+        cli.isa_taxable_correlation = v;
+    }
+    if let Some(v) = payload.taxable_pension_correlation {
+        cli.taxable_pension_correlation = Some(v);
+    }
+    if let Some(v) = payload.isa_beta {
+        cli.isa_beta = Some(v);
+    }
+    if let Some(v) = payload.taxable_beta {
+        cli.taxable_beta = Some(v);
+    }
+    if let Some(v) = payload.pension_beta {
+        cli.pension_beta = Some(v);
+    }
+    if let Some(v) = payload.return_distribution {
+        cli.return_distribution = v.into();
+    }
+    if let Some(v) = payload.market_model {
+        cli.market_model = v.into();
+    }
+    if let Some(v) = payload.bootstrap_block_years {
+        cli.bootstrap_block_years = v;
+    }
+    if let Some(v) = payload.market_burn_in_years {
+        cli.market_burn_in_years = v;
+    }
+    if let Some(v) = payload.bootstrap_fraction {
+        cli.bootstrap_fraction = v;
+    }
+    if let Some(v) = payload.market_path {
+        cli.market_path = v
+            .into_iter()
+            .map(|year| (year.isa, year.taxable, year.pension, year.inflation))
+            .collect();
+    }
+    if let Some(v) = payload.student_t_dof {
+        cli.student_t_dof = v;
+    }
+    if let Some(v) = payload.garch_alpha {
+        cli.garch_alpha = v;
+    }
+    if let Some(v) = payload.garch_beta {
+        cli.garch_beta = v;
+    }
+    if let Some(v) = payload.garch_long_run_vol {
+        cli.garch_long_run_vol = v;
+    }
+    if let Some(v) = payload.normal_to_crisis_probability {
+        cli.normal_to_crisis_probability = v;
+    }
+    if let Some(v) = payload.crisis_to_normal_probability {
+        cli.crisis_to_normal_probability = v;
+    }
+    if let Some(v) = payload.crisis_return_mean {
+        cli.crisis_return_mean = v;
+    }
+    if let Some(v) = payload.crisis_return_volatility {
+        cli.crisis_return_volatility = v;
+    }
+    if let Some(v) = payload.inflation_mean {
+        cli.inflation_rate = v;
+    }
+    if let Some(v) = payload.inflation_vol {
+        cli.inflation_volatility = v;
+    }
+    if let Some(v) = payload.inflation_return_correlation {
+        cli.inflation_return_correlation = v;
+    }
+    if let Some(v) = payload.return_clamp_min {
+        cli.return_clamp_min = v;
+    }
+    if let Some(v) = payload.return_clamp_max {
+        cli.return_clamp_max = v;
+    }
+    if let Some(v) = payload.inflation_clamp_min {
+        cli.inflation_clamp_min = v;
+    }
+    if let Some(v) = payload.inflation_clamp_max {
+        cli.inflation_clamp_max = v;
     }
 
-    if cli.max_age < cli.current_age {
-        return Err("--max-age must be >= --current-age".to_string());
+    if let Some(v) = payload.target_income {
+        cli.target_annual_income = v;
+    }
+    if let Some(v) = payload.essential_income {
+        cli.essential_annual_income = v;
+    }
+    if let Some(v) = payload.spending_decline_rate {
+        cli.spending_decline_rate = v;
+    }
+    if let Some(v) = payload.care_cost_age {
+        cli.care_cost_age = v;
+    }
+    if let Some(v) = payload.care_cost_uplift {
+        cli.care_cost_uplift = v;
+    }
+    if let Some(v) = payload.active_phase_end_age {
+        cli.active_phase_end_age = v;
+    }
+    if let Some(v) = payload.active_phase_uplift {
+        cli.active_phase_uplift = v;
+    }
+    if let Some(v) = payload.late_phase_start_age {
+        cli.late_phase_start_age = v;
+    }
+    if let Some(v) = payload.late_phase_uplift {
+        cli.late_phase_uplift = v;
+    }
+    if let Some(v) = payload.mortgage_annual_payment {
+        cli.mortgage_annual_payment = v;
+    }
+    if let Some(v) = payload.mortgage_end_age {
+        cli.mortgage_end_age = Some(v);
+    }
+    if let Some(v) = payload.debts {
+        cli.debts = v
+            .into_iter()
+            .map(|debt| (debt.annual_payment, debt.end_age))
+            .collect();
+    }
+    if let Some(v) = payload.mortgage_balance {
+        cli.mortgage_balance = v;
+    }
+    if let Some(v) = payload.mortgage_rate {
+        cli.mortgage_rate = v;
+    }
+    if let Some(v) = payload.mortgage_term_years {
+        cli.mortgage_term_years = v;
+    }
+    if let Some(v) = payload.mortgage_overpayments {
+        cli.mortgage_overpayments = v
+            .into_iter()
+            .map(|overpayment| (overpayment.age, overpayment.amount))
+            .collect();
+    }
+    if let Some(v) = payload.lump_expenses {
+        cli.lump_expenses = v
+            .into_iter()
+            .map(|expense| (expense.age, expense.amount))
+            .collect();
+    }
+    if let Some(v) = payload.success_threshold {
+        cli.success_threshold = v;
+    }
+    if let Some(v) = payload.selection_confidence {
+        cli.selection_confidence = v.into();
+    }
+    if let Some(v) = payload.risk_aversion {
+        cli.risk_aversion = Some(v);
+    }
+    if let Some(v) = payload.utility_discount_rate {
+        cli.utility_discount_rate = v;
+    }
+    if let Some(v) = payload.utility_spending_floor {
+        cli.utility_spending_floor = v;
+    }
+    if let Some(v) = payload.bad_threshold {
+        cli.bad_year_threshold = v;
+    }
+    if let Some(v) = payload.good_threshold {
+        cli.good_year_threshold = v;
+    }
+    if let Some(v) = payload.bad_cut {
+        cli.bad_year_cut = v;
+    }
+    if let Some(v) = payload.good_raise {
+        cli.good_year_raise = v;
+    }
+    if let Some(v) = payload.min_floor {
+        cli.min_income_floor = v;
+    }
+    if let Some(v) = payload.min_real_spending_floor {
+        cli.min_real_spending_floor = v;
+    }
+    if let Some(v) = payload.max_ceiling {
+        cli.max_income_ceiling = v;
+    }
+    if let Some(v) = payload.withdrawal_policy {
+        cli.withdrawal_strategy = v.into();
+    }
+    if let Some(v) = payload.gk_lower_guardrail {
+        cli.gk_lower_guardrail = v;
+    }
+    if let Some(v) = payload.gk_upper_guardrail {
+        cli.gk_upper_guardrail = v;
     }
-
-    if cli.horizon_age <= cli.max_age {
-        return Err("--horizon-age must be > --max-age".to_string());
+    if let Some(v) = payload.gk_adjustment {
+        cli.gk_adjustment = v;
     }
-
-    if cli.simulations == 0 {
-        return Err("--simulations must be > 0".to_string());
+    if let Some(v) = payload.vpw_real_return {
+        cli.vpw_expected_real_return = v;
     }
-
-    if !(0.0..=100.0).contains(&cli.success_threshold) {
-        return Err("--success-threshold must be between 0 and 100".to_string());
+    if let Some(v) = payload.constant_withdrawal_rate {
+        cli.constant_withdrawal_rate = v;
     }
-
-    if !(-1.0..=1.0).contains(&cli.return_correlation) {
-        return Err("--return-correlation must be between -1 and 1".to_string());
+    if let Some(v) = payload.cape_a {
+        cli.cape_a = v;
     }
-
-    if cli.target_annual_income <= 0.0 {
-        return Err("--target-annual-income must be > 0".to_string());
+    if let Some(v) = payload.cape_b {
+        cli.cape_b = v;
     }
-
-    if !cli.mortgage_annual_payment.is_finite() || cli.mortgage_annual_payment < 0.0 {
-        return Err("--mortgage-annual-payment must be >= 0".to_string());
+    if let Some(v) = payload.cape_start {
+        cli.cape_start = v;
     }
-
-    if cli.mortgage_annual_payment > 0.0 {
-        let Some(end_age) = cli.mortgage_end_age else {
-            return Err(
-                "--mortgage-end-age is required when --mortgage-annual-payment > 0".to_string(),
-            );
-        };
-        if end_age <= cli.current_age {
-            return Err("--mortgage-end-age must be > --current-age".to_string());
-        }
+    if let Some(v) = payload.cape_long_run_mean {
+        cli.cape_long_run_mean = v;
     }
-
-    if cli.cash_start < 0.0 {
-        return Err("--cash-start must be >= 0".to_string());
+    if let Some(v) = payload.cape_reversion_speed {
+        cli.cape_reversion_speed = v;
     }
-
-    if cli.bond_ladder_start < 0.0 {
-        return Err("--bond-ladder-start must be >= 0".to_string());
+    if let Some(v) = payload.floor_upside_capture {
+        cli.floor_upside_capture = v;
     }
-
-    if !cli.bond_ladder_yield.is_finite() || cli.bond_ladder_yield <= -100.0 {
-        return Err("--bond-ladder-yield must be > -100".to_string());
+    if let Some(v) = payload.bucket_years_target {
+        cli.bucket_target_years = v;
     }
-
-    if !(0.0..=100.0).contains(&cli.capital_gains_tax_rate) {
-        return Err("--capital-gains-tax-rate must be between 0 and 100".to_string());
+    if let Some(v) = payload.extra_to_cash {
+        cli.good_year_extra_buffer_withdrawal = v;
     }
-
-    if cli.capital_gains_allowance < 0.0 {
-        return Err("--capital-gains-allowance must be >= 0".to_string());
+    if let Some(v) = payload.min_cash_months {
+        cli.min_cash_months = v;
     }
-
-    if !(0.0..=100.0).contains(&cli.taxable_return_tax_drag) {
-        return Err("--taxable-return-tax-drag must be between 0 and 100".to_string());
+    if let Some(v) = payload.cash_growth {
+        cli.cash_growth_rate = v;
     }
-
-    if cli.taxable_cost_basis_start < 0.0 || cli.taxable_cost_basis_start > cli.taxable_start {
-        return Err("--taxable-cost-basis-start must be between 0 and taxable-start".to_string());
+    if let Some(v) = payload.bond_ladder_yield {
+        cli.bond_ladder_yield = v;
     }
-
-    if cli.min_income_floor <= 0.0 || cli.max_income_ceiling <= 0.0 {
-        return Err("--min-income-floor and --max-income-ceiling must be > 0".to_string());
+    if let Some(v) = payload.bond_ladder_yield_vol {
+        cli.bond_ladder_yield_vol = v;
     }
-
-    if cli.min_income_floor > cli.max_income_ceiling {
-        return Err("--min-income-floor cannot exceed --max-income-ceiling".to_string());
+    if let Some(v) = payload.bond_ladder_inflation_correlation {
+        cli.bond_ladder_inflation_correlation = v;
     }
-
-    if !cli.gk_lower_guardrail.is_finite() || cli.gk_lower_guardrail <= 0.0 {
-        return Err("--gk-lower-guardrail must be > 0".to_string());
+    if let Some(v) = payload.bond_ladder_years {
+        cli.bond_ladder_years = v;
     }
-
-    if !cli.gk_upper_guardrail.is_finite() || cli.gk_upper_guardrail <= 0.0 {
-        return Err("--gk-upper-guardrail must be > 0".to_string());
+    if let Some(v) = payload.bond_ladder_reinvest {
+        cli.bond_ladder_reinvest = v;
     }
-
-    if cli.gk_upper_guardrail < cli.gk_lower_guardrail {
-        return Err("--gk-upper-guardrail must be >= --gk-lower-guardrail".to_string());
+    if let Some(v) = payload.bond_ladder_target_at_retirement {
+        cli.bond_ladder_target_at_retirement = v;
     }
-
-    if !cli.vpw_expected_real_return.is_finite() || cli.vpw_expected_real_return <= -100.0 {
-        return Err("--vpw-expected-real-return must be > -100".to_string());
+    if let Some(v) = payload.withdrawal_order {
+        cli.post_access_withdrawal_order = v.into();
     }
-
-    if !(0.0..=300.0).contains(&cli.floor_upside_capture) {
-        return Err("--floor-upside-capture must be between 0 and 300".to_string());
+    if let Some(v) = payload.pre_access_withdrawal_order {
+        cli.pre_access_withdrawal_order = Some(v.into());
     }
 
-    if !cli.bucket_target_years.is_finite() || cli.bucket_target_years < 0.0 {
-        return Err("--bucket-target-years must be >= 0".to_string());
+    if let Some(v) = payload.analysis_mode {
+        options.mode = v.into();
     }
-
-    if cli.isa_annual_contribution_limit < 0.0 {
-        return Err("--isa-annual-contribution-limit must be >= 0".to_string());
+    if let Some(v) = payload.coast_retirement_age {
+        options.coast_retirement_age = Some(v);
     }
-
-    if !cli.contribution_growth_rate.is_finite() || cli.contribution_growth_rate <= -100.0 {
-        return Err("--contribution-growth-rate must be > -100".to_string());
+    if let Some(v) = payload.cashflow_age {
+        options.cashflow_age = Some(v);
     }
-
-    if !(0.0..=100.0).contains(&cli.pension_income_tax_rate) {
-        return Err("--pension-income-tax-rate must be between 0 and 100".to_string());
+    if let Some(v) = payload.summary_only {
+        options.summary_only = v;
     }
 
-    for (name, rate) in [
-        ("--uk-basic-rate", cli.uk_basic_rate),
-        ("--uk-higher-rate", cli.uk_higher_rate),
-        ("--uk-additional-rate", cli.uk_additional_rate),
-    ] {
-        if !(0.0..=100.0).contains(&rate) {
-            return Err(format!("{name} must be between 0 and 100"));
+    let mut option_errors = Vec::new();
+    if let Some(age) = options.coast_retirement_age {
+        if age < cli.current_age {
+            option_errors.push(ValidationError::new(
+                "coastRetirementAge",
+                "coastRetirementAge must be >= currentAge",
+            ));
+        } else if age >= cli.horizon_age {
+            option_errors.push(ValidationError::new(
+                "coastRetirementAge",
+                "coastRetirementAge must be < horizonAge",
+            ));
         }
     }
-
-    if cli.uk_personal_allowance < 0.0
-        || cli.uk_basic_rate_limit < 0.0
-        || cli.uk_higher_rate_limit < 0.0
-        || cli.uk_allowance_taper_start < 0.0
-        || cli.uk_allowance_taper_end < 0.0
+    if let Some(age) = options.cashflow_age
+        && !(cli.current_age..=cli.max_age).contains(&age)
     {
-        return Err("UK tax thresholds must be >= 0".to_string());
-    }
-
-    if cli.uk_basic_rate_limit < cli.uk_personal_allowance {
-        return Err("--uk-basic-rate-limit must be >= --uk-personal-allowance".to_string());
-    }
-
-    if cli.uk_higher_rate_limit < cli.uk_basic_rate_limit {
-        return Err("--uk-higher-rate-limit must be >= --uk-basic-rate-limit".to_string());
-    }
-
-    if cli.uk_allowance_taper_end <= cli.uk_allowance_taper_start {
-        return Err("--uk-allowance-taper-end must be > --uk-allowance-taper-start".to_string());
-    }
-
-    if cli.state_pension_annual_income < 0.0 {
-        return Err("--state-pension-annual-income must be >= 0".to_string());
+        option_errors.push(ValidationError::new(
+            "cashflowAge",
+            "cashflowAge must be between currentAge and maxAge",
+        ));
+    }
+
+    limits.check_simulations("simulations", cli.simulations)?;
+    if cli.horizon_age > MAX_HORIZON_AGE {
+        return Err(PayloadError::OverLimit(format!(
+            "horizonAge {} exceeds this server's limit of {MAX_HORIZON_AGE}",
+            cli.horizon_age
+        )));
+    }
+
+    match build_inputs(cli) {
+        Ok(inputs) if option_errors.is_empty() => Ok(ApiRequest { inputs, options }),
+        Ok(_) => Err(PayloadError::Invalid(option_errors)),
+        Err(input_errors) => Err(PayloadError::Invalid(
+            input_errors
+                .into_iter()
+                .map(|error| ValidationError {
+                    field: payload_key_for_flag(&error.field),
+                    message: error.message,
+                })
+                .chain(option_errors)
+                .collect(),
+        )),
     }
-
-    let taxable_growth_rate = cli.taxable_growth_rate.unwrap_or(cli.isa_growth_rate);
-    let taxable_return_volatility = cli
-        .taxable_return_volatility
-        .unwrap_or(cli.isa_return_volatility);
-
-    Ok(Inputs {
-        current_age: cli.current_age,
-        pension_access_age: cli.pension_access_age,
-        isa_start: cli.isa_start,
-        taxable_start: cli.taxable_start,
-        taxable_cost_basis_start: if cli.taxable_cost_basis_start == 0.0 && cli.taxable_start > 0.0
-        {
-            cli.taxable_start
-        } else {
-            cli.taxable_cost_basis_start
-        },
-        pension_start: cli.pension_start,
-        cash_start: cli.cash_start,
-        bond_ladder_start: cli.bond_ladder_start,
-        isa_annual_contribution: cli.isa_annual_contribution,
-        isa_annual_contribution_limit: cli.isa_annual_contribution_limit,
-        taxable_annual_contribution: cli.taxable_annual_contribution,
-        pension_annual_contribution: cli.pension_annual_contribution,
-        contribution_growth_rate: cli.contribution_growth_rate / 100.0,
-        isa_return_mean: cli.isa_growth_rate / 100.0,
-        isa_return_vol: cli.isa_return_volatility / 100.0,
-        taxable_return_mean: taxable_growth_rate / 100.0,
-        taxable_return_vol: taxable_return_volatility / 100.0,
-        pension_return_mean: cli.pension_growth_rate / 100.0,
-        pension_return_vol: cli.pension_return_volatility / 100.0,
-        return_correlation: cli.return_correlation,
-        capital_gains_tax_rate: cli.capital_gains_tax_rate / 100.0,
-        capital_gains_allowance: cli.capital_gains_allowance,
-        taxable_return_tax_drag: cli.taxable_return_tax_drag / 100.0,
-        pension_tax_mode: cli.pension_tax_mode.into(),
-        pension_flat_tax_rate: cli.pension_income_tax_rate / 100.0,
-        uk_personal_allowance: cli.uk_personal_allowance,
-        uk_basic_rate_limit: cli.uk_basic_rate_limit,
-        uk_higher_rate_limit: cli.uk_higher_rate_limit,
-        uk_basic_rate: cli.uk_basic_rate / 100.0,
-        uk_higher_rate: cli.uk_higher_rate / 100.0,
-        uk_additional_rate: cli.uk_additional_rate / 100.0,
-        uk_allowance_taper_start: cli.uk_allowance_taper_start,
-        uk_allowance_taper_end: cli.uk_allowance_taper_end,
-        state_pension_start_age: cli.state_pension_start_age,
-        state_pension_annual_income: cli.state_pension_annual_income,
-        inflation_mean: cli.inflation_rate / 100.0,
-        inflation_vol: cli.inflation_volatility / 100.0,
-        target_annual_income: cli.target_annual_income,
-        mortgage_annual_payment: cli.mortgage_annual_payment,
-        mortgage_end_age: cli.mortgage_end_age,
-        max_retirement_age: cli.max_age,
-        horizon_age: cli.horizon_age,
-        simulations: cli.simulations,
-        success_threshold: cli.success_threshold / 100.0,
-        seed: cli.seed,
-        bad_year_threshold: cli.bad_year_threshold / 100.0,
-        good_year_threshold: cli.good_year_threshold / 100.0,
-        bad_year_cut: cli.bad_year_cut / 100.0,
-        good_year_raise: cli.good_year_raise / 100.0,
-        min_income_floor: cli.min_income_floor / 100.0,
-        max_income_ceiling: cli.max_income_ceiling / 100.0,
-        withdrawal_strategy: cli.withdrawal_strategy.into(),
-        gk_lower_guardrail: cli.gk_lower_guardrail / 100.0,
-        gk_upper_guardrail: cli.gk_upper_guardrail / 100.0,
-        vpw_expected_real_return: cli.vpw_expected_real_return / 100.0,
-        floor_upside_capture: cli.floor_upside_capture / 100.0,
-        bucket_target_years: cli.bucket_target_years,
-        good_year_extra_buffer_withdrawal: cli.good_year_extra_buffer_withdrawal / 100.0,
-        cash_growth_rate: cli.cash_growth_rate / 100.0,
-        bond_ladder_yield: cli.bond_ladder_yield / 100.0,
-        bond_ladder_years: cli.bond_ladder_years,
-        post_access_withdrawal_order: cli.post_access_withdrawal_order.into(),
-    })
-}
-
-pub async fn run_http_server(port: u16) -> std::io::Result<()> {
-    let addr = SocketAddr::from(([0, 0, 0, 0], port));
-    let app = Router::new()
-        .route("/", get(index_handler))
-        .route("/index.html", get(index_handler))
-        .route("/styles.css", get(styles_handler))
-        .route("/app.js", get(app_js_handler))
-        .route("/healthz", get(health_handler))
-        .route("/api/health", get(health_handler))
-        .route(
-            "/api/simulate",
-            get(simulate_get_handler).post(simulate_post_handler),
-        )
-        .route(
-            "/api/solve-goal",
-            get(solve_goal_get_handler).post(solve_goal_post_handler),
-        )
-        .fallback(not_found_handler);
-
-    let listener = TcpListener::bind(addr).await?;
-    println!("FIRE HTTP API listening on http://{addr}");
-    println!("Local access: http://127.0.0.1:{port}/");
-
-    axum::serve(listener, app).await
-}
-
-async fn index_handler() -> impl IntoResponse {
-    with_cache_control(Html(INDEX_HTML))
-}
-
-async fn styles_handler() -> impl IntoResponse {
-    with_cache_control((
-        [(header::CONTENT_TYPE, "text/css; charset=utf-8")],
-        STYLES_CSS,
-    ))
-}
-
-async fn app_js_handler() -> impl IntoResponse {
-    with_cache_control((
-        [(
-            header::CONTENT_TYPE,
-            "application/javascript; charset=utf-8",
-        )],
-        APP_JS,
-    ))
 }
 
-async fn health_handler() -> Response {
-    json_response(StatusCode::OK, HealthResponse { status: "ok" })
+const DEFAULT_MAX_SIMULATIONS: u32 = 100_000;
+/// Traces and per-year buffers grow with the horizon, so it is capped like `simulations`.
+const MAX_HORIZON_AGE: u32 = 130;
+/// POST bodies above this are refused with `413` before they are parsed.
+const MAX_REQUEST_BODY_BYTES: usize = 1024 * 1024;
+
+/// Per-server request limits, fixed when `fire serve` starts and carried in router state.
+#[derive(Clone, Copy, Debug)]
+struct ServerLimits {
+    /// Largest `simulations` (and solver simulation count) one request may ask for.
+    max_simulations: u32,
 }
 
-async fn not_found_handler() -> Response {
-    error_response(StatusCode::NOT_FOUND, "Not found")
+impl Default for ServerLimits {
+    fn default() -> Self {
+        Self {
+            max_simulations: DEFAULT_MAX_SIMULATIONS,
+        }
+    }
 }
 
-async fn simulate_get_handler(Query(payload): Query<SimulatePayload>) -> Response {
-    simulate_handler_impl(payload).await
+impl ServerLimits {
+    fn check_simulations(&self, key: &str, simulations: u32) -> Result<(), PayloadError> {
+        let max = self.max_simulations;
+        if simulations > max {
+            return Err(PayloadError::OverLimit(format!(
+                "{key} {simulations} exceeds this server's limit of {max} (see --max-simulations)"
+            )));
+        }
+        Ok(())
+    }
 }
 
-async fn simulate_post_handler(Json(payload): Json<SimulatePayload>) -> Response {
-    simulate_handler_impl(payload).await
+/// Why a payload could not become an `ApiRequest`.
+#[derive(Debug)]
+enum PayloadError {
+    /// Over a server resource limit; answered with `400`.
+    OverLimit(String),
+    /// Invalid model inputs; answered with `422` and one entry per field.
+    Invalid(Vec<ValidationError>),
 }
 
-async fn solve_goal_get_handler(Query(payload): Query<SolveGoalPayload>) -> Response {
-    solve_goal_handler_impl(payload).await
+impl PayloadError {
+    fn into_response(self) -> Response {
+        match self {
+            PayloadError::OverLimit(msg) => error_response(StatusCode::BAD_REQUEST, &msg),
+            PayloadError::Invalid(errors) => validation_error_response(errors),
+        }
+    }
 }
 
-async fn solve_goal_post_handler(Json(payload): Json<SolveGoalPayload>) -> Response {
-    solve_goal_handler_impl(payload).await
+impl From<Vec<ValidationError>> for PayloadError {
+    fn from(errors: Vec<ValidationError>) -> Self {
+        PayloadError::Invalid(errors)
+    }
 }
 
-async fn simulate_handler_impl(payload: SimulatePayload) -> Response {
-    let request = match api_request_from_payload(payload) {
-        Ok(request) => request,
-        Err(msg) => return error_response(StatusCode::BAD_REQUEST, &msg),
-    };
-
-    let inputs = &request.inputs;
-    let (model, resolved_coast_retirement_age) = match request.options.mode {
-        AnalysisMode::RetirementSweep => (run_model(inputs), None),
-        AnalysisMode::CoastFire => {
-            let coast_retirement_age = request.options.coast_retirement_age.unwrap_or_else(|| {
-                let baseline = run_model(inputs);
-                baseline
-                    .selected_index
-                    .map(|idx| baseline.age_results[idx].retirement_age)
-                    .unwrap_or(baseline.age_results[baseline.best_index].retirement_age)
-            });
-            (
-                run_coast_model(inputs, coast_retirement_age),
-                Some(coast_retirement_age),
-            )
+/// CLI flags whose JSON payload key is not simply the flag in camelCase.
+const PAYLOAD_KEY_OVERRIDES: [(&str, &str); 36] = [
+    ("taxable-cost-basis-start", "taxableBasisStart"),
+    ("isa-annual-contribution", "isaContribution"),
+    ("isa-annual-contribution-limit", "isaLimit"),
+    ("lisa-annual-contribution", "lisaContribution"),
+    ("taxable-annual-contribution", "taxableContribution"),
+    ("pension-annual-contribution", "pensionContribution"),
+    ("contribution-growth-rate", "contributionGrowth"),
+    ("capital-gains-allowance", "cgtAllowance"),
+    ("taxable-return-tax-drag", "taxableTaxDrag"),
+    ("state-pension-annual-income", "statePensionIncome"),
+    ("db-pension-annual-income", "dbPensionIncome"),
+    ("rental-annual-income", "rentalIncome"),
+    ("part-time-annual-income", "partTimeIncome"),
+    ("isa-growth-rate", "isaMean"),
+    ("isa-return-volatility", "isaVol"),
+    ("taxable-growth-rate", "taxableMean"),
+    ("taxable-return-volatility", "taxableVol"),
+    ("pension-growth-rate", "pensionMean"),
+    ("pension-return-volatility", "pensionVol"),
+    ("return-correlation", "correlation"),
+    ("inflation-rate", "inflationMean"),
+    ("inflation-volatility", "inflationVol"),
+    ("target-annual-income", "targetIncome"),
+    ("essential-annual-income", "essentialIncome"),
+    ("bad-year-threshold", "badThreshold"),
+    ("good-year-threshold", "goodThreshold"),
+    ("bad-year-cut", "badCut"),
+    ("good-year-raise", "goodRaise"),
+    ("min-income-floor", "minFloor"),
+    ("max-income-ceiling", "maxCeiling"),
+    ("vpw-expected-real-return", "vpwRealReturn"),
+    ("bucket-target-years", "bucketYearsTarget"),
+    ("good-year-extra-buffer-withdrawal", "extraToCash"),
+    ("cash-growth-rate", "cashGrowth"),
+    ("lump-expense", "lumpExpenses"),
+    ("market-path-year", "marketPath"),
+];
+
+/// Maps a CLI flag to the `SimulatePayload` key (and web form field) that sets it.
+fn payload_key_for_flag(flag: &str) -> String {
+    if let Some((_, key)) = PAYLOAD_KEY_OVERRIDES.iter().find(|(name, _)| *name == flag) {
+        return key.to_string();
+    }
+    let mut key = String::with_capacity(flag.len());
+    let mut upper_next = false;
+    for c in flag.chars() {
+        if c == '-' {
+            upper_next = true;
+        } else if upper_next {
+            key.push(c.to_ascii_uppercase());
+            upper_next = false;
+        } else {
+            key.push(c);
         }
-    };
+    }
+    key
+}
 
-    let trace_index = model.selected_index.unwrap_or(model.best_index);
-    let trace_reported_age = model.age_results[trace_index].retirement_age;
-    let (trace_retirement_age, trace_contribution_stop_age) = match request.options.mode {
-        AnalysisMode::RetirementSweep => (trace_reported_age, trace_reported_age),
-        AnalysisMode::CoastFire => (
-            resolved_coast_retirement_age.unwrap_or(trace_reported_age),
-            trace_reported_age,
+/// The CLI equivalent of `Inputs::default()`: the flags clap requires (and
+/// the taxable account, which the CLI starts empty) come from the library
+/// defaults, and every other flag keeps its clap default, which does too.
+fn default_cli_for_api() -> Cli {
+    let d = input_defaults();
+    let args = [
+        ("--current-age", d.current_age.to_string()),
+        ("--pension-access-age", d.pension_access_age.to_string()),
+        ("--isa-start", d.isa_start.to_string()),
+        ("--taxable-start", d.taxable_start.to_string()),
+        (
+            "--taxable-cost-basis-start",
+            d.taxable_cost_basis_start.to_string(),
         ),
-    };
-    let cashflow_years = run_yearly_cashflow_trace(
-        inputs,
-        trace_retirement_age,
-        trace_contribution_stop_age,
-        trace_reported_age,
+        ("--pension-start", d.pension_start.to_string()),
+        (
+            "--isa-annual-contribution",
+            d.isa_annual_contribution.to_string(),
+        ),
+        (
+            "--taxable-annual-contribution",
+            d.taxable_annual_contribution.to_string(),
+        ),
+        (
+            "--pension-annual-contribution",
+            d.pension_annual_contribution.to_string(),
+        ),
+        ("--isa-growth-rate", percent(d.isa_return_mean).to_string()),
+        (
+            "--taxable-growth-rate",
+            percent(d.taxable_return_mean).to_string(),
+        ),
+        (
+            "--taxable-return-volatility",
+            percent(d.taxable_return_vol).to_string(),
+        ),
+        (
+            "--pension-growth-rate",
+            percent(d.pension_return_mean).to_string(),
+        ),
+        ("--target-annual-income", d.target_annual_income.to_string()),
+    ];
+    let argv = std::iter::once("fire".to_string()).chain(
+        args.into_iter()
+            .flat_map(|(flag, value)| [flag.to_string(), value]),
     );
-    let cashflow = CashflowResponse {
-        candidate_age: trace_reported_age,
-        retirement_age: trace_retirement_age,
-        contribution_stop_age: trace_contribution_stop_age,
-        years: &cashflow_years,
-    };
+    Cli::try_parse_from(argv).expect("library defaults parse as CLI flags")
+}
 
-    let response = build_simulate_response(
-        inputs,
-        &model,
-        request.options.mode,
-        resolved_coast_retirement_age,
-        cashflow,
-    );
-    json_response(StatusCode::OK, response)
+fn build_simulate_response(
+    inputs: &Inputs,
+    model: &ModelResult,
+    mode: AnalysisMode,
+    coast_retirement_age: Option<u32>,
+    cashflow: Option<CashflowResponse<'_>>,
+) -> SimulateResponse {
+    let headline = &model.age_results[model.selected_index.unwrap_or(model.best_index)];
+    SimulateResponse {
+        mode: mode.into(),
+        withdrawal_policy: inputs.withdrawal_strategy.into(),
+        coast_retirement_age,
+        success_threshold: inputs.success_threshold,
+        selected_retirement_age: model
+            .selected_index
+            .map(|idx| model.age_results[idx].retirement_age),
+        best_retirement_age: model.age_results[model.best_index].retirement_age,
+        summary: cashflow.is_none().then(|| headline.into()),
+        cashflow_candidate_age: cashflow.map(|c| c.candidate_age),
+        cashflow_retirement_age: cashflow.map(|c| c.retirement_age),
+        cashflow_contribution_stop_age: cashflow.map(|c| c.contribution_stop_age),
+        age_results: cashflow.is_some().then(|| model.age_results.clone()),
+        cashflow_years: cashflow.map(|c| c.years.to_vec()),
+        meta: RunMeta::for_inputs(inputs),
+    }
 }
 
-async fn solve_goal_handler_impl(payload: SolveGoalPayload) -> Response {
-    let request = match api_request_from_payload(payload.simulation.clone()) {
-        Ok(request) => request,
-        Err(msg) => return error_response(StatusCode::BAD_REQUEST, &msg),
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::run_coast_model;
+    use std::fs;
+    use std::path::Path;
 
-    let config = match build_goal_solve_config(&request.inputs, &payload) {
-        Ok(config) => config,
-        Err(msg) => return error_response(StatusCode::BAD_REQUEST, &msg),
-    };
+    const EPS: f64 = 1e-6;
 
-    let result = match solve_goal(&request.inputs, config) {
-        Ok(result) => result,
-        Err(msg) => return error_response(StatusCode::BAD_REQUEST, &msg),
-    };
+    fn assert_approx(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() <= EPS,
+            "expected {expected}, got {actual}"
+        );
+    }
 
-    json_response(StatusCode::OK, build_solve_goal_response(result))
-}
+    fn sample_cli() -> Cli {
+        default_cli_for_api()
+    }
 
-fn build_goal_solve_config(
-    inputs: &Inputs,
-    payload: &SolveGoalPayload,
-) -> Result<GoalSolveConfig, String> {
-    let goal_type = payload
-        .goal_type
-        .unwrap_or(ApiGoalType::RequiredContribution);
-    let target_retirement_age = payload
-        .target_retirement_age
-        .unwrap_or(inputs.max_retirement_age);
+    /// Masks `meta.inputsHash`, which `simulate_meta_hash_tracks_the_resolved_inputs`
+    /// covers, so adding an `Inputs` field does not churn every snapshot.
+    fn golden_json(mut response: SimulateResponse) -> String {
+        response.meta.inputs_hash = "masked".to_string();
+        format!(
+            "{}\n",
+            serde_json::to_string(&response).expect("response should serialize")
+        )
+    }
 
-    let target_success_pct = payload
-        .target_success_threshold
-        .unwrap_or(inputs.success_threshold * 100.0);
-    if !target_success_pct.is_finite() || !(0.0..=100.0).contains(&target_success_pct) {
-        return Err("--targetSuccessThreshold must be between 0 and 100".to_string());
+    /// Numbers in a snapshot match within this fraction of the larger magnitude (or
+    /// absolutely, below 1): far tighter than any modelling change, far looser than the
+    /// last-digit noise of reordered float arithmetic.
+    const GOLDEN_RELATIVE_TOLERANCE: f64 = 1e-9;
+
+    /// The JSON path of the first difference between two snapshots, if any.
+    fn golden_difference(
+        at: &str,
+        actual: &serde_json::Value,
+        expected: &serde_json::Value,
+    ) -> Option<String> {
+        use serde_json::Value;
+        match (actual, expected) {
+            (Value::Number(a), Value::Number(e)) => {
+                let (a, e) = (
+                    a.as_f64().unwrap_or(f64::NAN),
+                    e.as_f64().unwrap_or(f64::NAN),
+                );
+                let scale = a.abs().max(e.abs()).max(1.0);
+                ((a - e).abs() > GOLDEN_RELATIVE_TOLERANCE * scale)
+                    .then(|| format!("{at}: {a} != {e}"))
+            }
+            (Value::Array(a), Value::Array(e)) if a.len() == e.len() => a
+                .iter()
+                .zip(e)
+                .enumerate()
+                .find_map(|(i, (a, e))| golden_difference(&format!("{at}[{i}]"), a, e)),
+            (Value::Object(a), Value::Object(e))
+                if a.len() == e.len() && a.keys().all(|key| e.contains_key(key)) =>
+            {
+                a.iter()
+                    .find_map(|(key, a)| golden_difference(&format!("{at}.{key}"), a, &e[key]))
+            }
+            _ => (actual != expected).then(|| format!("{at}: {actual} != {expected}")),
+        }
     }
 
-    let default_search_max = match goal_type {
-        ApiGoalType::RequiredContribution => {
-            let base_total = inputs.isa_annual_contribution.max(0.0)
-                + inputs.taxable_annual_contribution.max(0.0)
-                + inputs.pension_annual_contribution.max(0.0);
-            (base_total.max(1.0) * 4.0).max(200_000.0)
+    fn assert_golden_snapshot(path: &str, actual: &str) {
+        let update = matches!(
+            std::env::var("UPDATE_GOLDEN").as_deref(),
+            Ok("1") | Ok("true") | Ok("TRUE")
+        );
+        let snapshot_path = Path::new(path);
+
+        if update {
+            if let Some(parent) = snapshot_path.parent() {
+                fs::create_dir_all(parent).expect("failed to create snapshot directory");
+            }
+            fs::write(snapshot_path, actual).expect("failed to write golden snapshot");
+            return;
         }
-        ApiGoalType::MaxIncome => (inputs.target_annual_income * 2.0)
-            .max(inputs.target_annual_income + 20_000.0)
-            .max(100_000.0),
-    };
 
-    let search_min = payload.search_min.unwrap_or(0.0);
-    let search_max = payload.search_max.unwrap_or(default_search_max);
-    let tolerance = payload.tolerance.unwrap_or(100.0);
-    let max_iterations = payload.max_iterations.unwrap_or(24);
+        let expected = fs::read_to_string(snapshot_path).unwrap_or_else(|_| {
+            panic!("missing golden snapshot at {path}; run with UPDATE_GOLDEN=1 to generate")
+        });
+        let parse = |text: &str| {
+            serde_json::from_str::<serde_json::Value>(text).expect("snapshot should be JSON")
+        };
+        if let Some(difference) = golden_difference("$", &parse(actual), &parse(&expected)) {
+            panic!(
+                "snapshot mismatch for {path} at {difference}; run with UPDATE_GOLDEN=1 to refresh if expected"
+            );
+        }
+    }
 
-    let simulations_per_iteration = payload
-        .simulations_per_iteration
-        .unwrap_or(inputs.simulations.clamp(1_000, 5_000));
-    let final_simulations = payload.final_simulations.unwrap_or(
-        simulations_per_iteration
-            .saturating_mul(2)
-            .max(simulations_per_iteration)
-            .min(20_000),
-    );
+    #[test]
+    fn build_inputs_defaults_taxable_basis_to_start_when_zero() {
+        let mut cli = sample_cli();
+        cli.taxable_start = 20_000.0;
+        cli.taxable_cost_basis_start = 0.0;
 
-    Ok(GoalSolveConfig {
-        goal_type: goal_type.into(),
-        target_retirement_age,
-        target_success_threshold: target_success_pct / 100.0,
-        search_min,
-        search_max,
-        tolerance,
-        max_iterations,
-        simulations_per_iteration,
-        final_simulations,
-    })
-}
+        let inputs = build_inputs(cli).expect("valid inputs");
+        assert_approx(inputs.taxable_cost_basis_start, 20_000.0);
+    }
 
-fn build_solve_goal_response(result: GoalSolveResult) -> SolveGoalResponse {
-    let solved_contribution_total = if result.goal_type == GoalType::RequiredContribution {
-        result.solved_value
-    } else {
-        None
-    };
+    #[test]
+    fn build_inputs_rejects_invalid_taxable_basis() {
+        let mut cli = sample_cli();
+        cli.taxable_start = 10_000.0;
+        cli.taxable_cost_basis_start = 12_000.0;
 
-    let (solved_contribution_isa, solved_contribution_taxable, solved_contribution_pension) =
-        if let Some(ContributionAllocation {
-            isa,
-            taxable,
-            pension,
-        }) = result.solved_contributions
-        {
-            (Some(isa), Some(taxable), Some(pension))
-        } else {
-            (None, None, None)
-        };
+        let err = validation_message(&build_inputs(cli).expect_err("must reject invalid basis"));
+        assert!(err.contains("--taxable-cost-basis-start"));
+    }
 
-    SolveGoalResponse {
-        goal_type: result.goal_type.into(),
-        target_retirement_age: result.target_retirement_age,
-        target_success_threshold: result.target_success_threshold,
-        search_min: result.search_min,
-        search_max: result.search_max,
-        tolerance: result.tolerance,
-        max_iterations: result.max_iterations,
-        simulations_per_iteration: result.simulations_per_iteration,
-        final_simulations: result.final_simulations,
-        solved_value: result.solved_value,
-        solved_contribution_total,
-        solved_contribution_isa,
-        solved_contribution_taxable,
-        solved_contribution_pension,
-        achieved_success_rate: result.achieved_success_rate,
-        achieved_success_ci_half_width: result.achieved_success_ci_half_width,
-        converged: result.converged,
-        feasible: result.feasible,
-        message: result.message,
-        iterations: result
-            .iterations
-            .into_iter()
-            .map(
-                |GoalSolveIteration {
-                     iteration,
-                     lower_bound,
-                     upper_bound,
-                     candidate_value,
-                     success_rate,
-                     success_ci_half_width,
-                 }| SolveGoalIterationResponse {
-                    iteration,
-                    lower_bound,
-                    upper_bound,
-                    candidate_value,
-                    success_rate,
-                    success_ci_half_width,
-                },
-            )
-            .collect(),
+    #[test]
+    fn build_inputs_rejects_invalid_contribution_growth_rate() {
+        let mut cli = sample_cli();
+        cli.contribution_growth_rate = -100.0;
+        let err =
+            validation_message(&build_inputs(cli).expect_err("must reject <= -100 growth rate"));
+        assert!(err.contains("--contribution-growth-rate"));
     }
-}
 
-fn with_cache_control<R: IntoResponse>(response: R) -> Response {
-    let mut response = response.into_response();
-    response.headers_mut().insert(
-        header::CACHE_CONTROL,
-        "no-store".parse().expect("valid header"),
-    );
-    response
-}
+    #[test]
+    fn build_inputs_rejects_invalid_uk_band_order() {
+        let mut cli = sample_cli();
+        cli.uk_basic_rate_limit = 10_000.0;
+        cli.uk_personal_allowance = 12_570.0;
 
-fn json_response<T: Serialize>(status: StatusCode, body: T) -> Response {
-    let mut response = (status, Json(body)).into_response();
-    response.headers_mut().insert(
-        header::CACHE_CONTROL,
-        "no-store".parse().expect("valid header"),
-    );
-    response
-}
+        let err =
+            validation_message(&build_inputs(cli).expect_err("must reject bad UK threshold order"));
+        assert!(err.contains("--uk-basic-rate-limit"));
+    }
 
-fn error_response(status: StatusCode, msg: &str) -> Response {
-    json_response(
-        status,
-        ErrorResponse {
-            error: msg.to_string(),
-        },
-    )
-}
+    #[test]
+    fn build_inputs_uses_isa_defaults_for_taxable_return_params() {
+        let mut cli = sample_cli();
+        cli.taxable_growth_rate = None;
+        cli.taxable_return_volatility = None;
 
-#[cfg(test)]
-fn api_request_from_json(json: &str) -> Result<ApiRequest, String> {
-    let payload = serde_json::from_str::<SimulatePayload>(json)
-        .map_err(|e| format!("Invalid API JSON payload: {e}"))?;
-    api_request_from_payload(payload)
-}
+        let inputs = build_inputs(cli).expect("valid inputs");
+        assert_approx(inputs.taxable_return_mean, inputs.isa_return_mean);
+        assert_approx(inputs.taxable_return_vol, inputs.isa_return_vol);
+    }
+
+    #[test]
+    fn api_request_from_json_parses_web_keys() {
+        let json = r#"{
+          "currentAge": 31,
+          "pensionAccessAge": 58,
+          "isaStart": 120000,
+          "taxableStart": 20000,
+          "taxableBasisStart": 15000,
+          "pensionStart": 250000,
+          "cashStart": 5000,
+          "bondLadderStart": 25000,
+          "targetIncome": 45000,
+          "mortgageAnnualPayment": 12000,
+          "mortgageEndAge": 40,
+          "withdrawalOrder": "taxable-first",
+          "simulations": 1234,
+          "contributionGrowth": 3,
+          "contributionsTrackInflation": true,
+          "pensionTaxMode": "uk-bands",
+          "statePensionStartAge": 67,
+          "statePensionIncome": 12000,
+          "withdrawalPolicy": "vpw",
+          "vpwRealReturn": 4.2,
+          "bondLadderYield": 3.2,
+          "bondLadderYears": 8,
+          "bondLadderTargetAtRetirement": 60000
+        }"#;
+        let request = api_request_from_json(json).expect("json should parse");
+        let inputs = request.inputs;
+
+        assert_eq!(inputs.current_age, 31);
+        assert_eq!(inputs.pension_access_age, 58);
+        assert_approx(inputs.isa_start, 120_000.0);
+        assert_approx(inputs.taxable_start, 20_000.0);
+        assert_approx(inputs.taxable_cost_basis_start, 15_000.0);
+        assert_approx(inputs.pension_start, 250_000.0);
+        assert_approx(inputs.cash_start, 5_000.0);
+        assert_approx(inputs.bond_ladder_start, 25_000.0);
+        assert_approx(inputs.target_annual_income, 45_000.0);
+        assert_approx(inputs.mortgage_annual_payment, 12_000.0);
+        assert_eq!(inputs.mortgage_end_age, Some(40));
+        assert_approx(inputs.contribution_growth_rate, 0.03);
+        assert!(inputs.contributions_track_inflation);
+        assert_eq!(inputs.state_pension_start_age, 67);
+        assert_approx(inputs.state_pension_annual_income, 12_000.0);
+        assert_eq!(inputs.simulations, 1234);
+        assert_eq!(inputs.withdrawal_strategy, WithdrawalStrategy::Vpw);
+        assert_approx(inputs.vpw_expected_real_return, 0.042);
+        assert_approx(inputs.bond_ladder_yield, 0.032);
+        assert_eq!(inputs.bond_ladder_years, 8);
+        assert_approx(inputs.bond_ladder_target_at_retirement, 60_000.0);
+        assert_eq!(
+            inputs.post_access_withdrawal_order,
+            WithdrawalOrder::TaxableFirst
+        );
+        assert_eq!(inputs.pension_tax_mode, PensionTaxMode::UkBands);
+    }
 
-fn api_request_from_payload(payload: SimulatePayload) -> Result<ApiRequest, String> {
-    let mut cli = default_cli_for_api();
-    let mut options = ApiOptions {
-        mode: AnalysisMode::RetirementSweep,
-        coast_retirement_age: None,
-    };
+    #[test]
+    fn build_inputs_rejects_mortgage_payment_without_end_age() {
+        let mut cli = sample_cli();
+        cli.mortgage_annual_payment = 10_000.0;
+        cli.mortgage_end_age = None;
 
-    if let Some(v) = payload.current_age {
-        cli.current_age = v;
-    }
-    if let Some(v) = payload.pension_access_age {
-        cli.pension_access_age = v;
-    }
-    if let Some(v) = payload.max_age {
-        cli.max_age = v;
-    }
-    if let Some(v) = payload.horizon_age {
-        cli.horizon_age = v;
-    }
-    if let Some(v) = payload.simulations {
-        cli.simulations = v;
-    }
-    if let Some(v) = payload.seed {
-        cli.seed = v;
+        let err =
+            validation_message(&build_inputs(cli).expect_err("must require mortgage end age"));
+        assert!(err.contains("--mortgage-end-age"));
     }
 
-    if let Some(v) = payload.isa_start {
-        cli.isa_start = v;
-    }
-    if let Some(v) = payload.taxable_start {
-        cli.taxable_start = v;
-    }
-    if let Some(v) = payload.taxable_basis_start {
-        cli.taxable_cost_basis_start = v;
-    }
-    if let Some(v) = payload.pension_start {
-        cli.pension_start = v;
-    }
-    if let Some(v) = payload.cash_start {
-        cli.cash_start = v;
-    }
-    if let Some(v) = payload.bond_ladder_start {
-        cli.bond_ladder_start = v;
+    #[test]
+    fn api_request_from_json_combines_mortgage_shorthand_with_debts() {
+        let json = r#"{
+          "mortgageAnnualPayment": 9000,
+          "mortgageEndAge": 55,
+          "debts": [{"annualPayment": 3000, "endAge": 42}]
+        }"#;
+        let request = api_request_from_json(json).expect("json should parse");
+        assert_approx(request.inputs.mortgage_annual_payment, 9_000.0);
+        assert_eq!(request.inputs.mortgage_end_age, Some(55));
+        assert_eq!(
+            request.inputs.all_debts().collect::<Vec<_>>(),
+            vec![
+                Debt {
+                    annual_payment: 3_000.0,
+                    end_age: 42,
+                },
+                Debt {
+                    annual_payment: 9_000.0,
+                    end_age: 55,
+                },
+            ]
+        );
     }
 
-    if let Some(v) = payload.isa_contribution {
-        cli.isa_annual_contribution = v;
-    }
-    if let Some(v) = payload.isa_limit {
-        cli.isa_annual_contribution_limit = v;
-    }
-    if let Some(v) = payload.taxable_contribution {
-        cli.taxable_annual_contribution = v;
-    }
-    if let Some(v) = payload.pension_contribution {
-        cli.pension_annual_contribution = v;
-    }
-    if let Some(v) = payload.contribution_growth {
-        cli.contribution_growth_rate = v;
+    #[test]
+    fn parse_debt_reads_payment_and_end_age() {
+        assert_eq!(parse_debt("6000:45"), Ok((6_000.0, 45)));
+        assert_eq!(parse_debt(" 2400.5 : 40"), Ok((2_400.5, 40)));
+        assert!(parse_debt("6000").is_err());
+        assert!(parse_debt("6000:forty").is_err());
     }
 
-    if let Some(v) = payload.cgt_rate {
-        cli.capital_gains_tax_rate = v;
-    }
-    if let Some(v) = payload.cgt_allowance {
-        cli.capital_gains_allowance = v;
+    #[test]
+    fn api_request_from_json_parses_coast_mode_and_retirement_age() {
+        let json = r#"{
+          "analysisMode": "coast-fire",
+          "coastRetirementAge": 60,
+          "currentAge": 31,
+          "horizonAge": 90
+        }"#;
+        let request = api_request_from_json(json).expect("json should parse");
+        assert_eq!(request.options.mode, AnalysisMode::CoastFire);
+        assert_eq!(request.options.coast_retirement_age, Some(60));
+        assert_eq!(request.inputs.current_age, 31);
     }
-    if let Some(v) = payload.taxable_tax_drag {
-        cli.taxable_return_tax_drag = v;
+
+    #[test]
+    fn api_request_from_json_parses_bond_ladder_withdrawal_order() {
+        let json = r#"{
+          "withdrawalOrder": "bond-ladder-first"
+        }"#;
+        let request = api_request_from_json(json).expect("json should parse");
+        assert_eq!(
+            request.inputs.post_access_withdrawal_order,
+            WithdrawalOrder::BondLadderFirst
+        );
     }
 
-    if let Some(v) = payload.pension_tax_mode {
-        cli.pension_tax_mode = v.into();
+    #[test]
+    fn api_request_from_json_parses_fill_basic_rate_withdrawal_order() {
+        for order in ["fill-basic-rate", "fillBasicRate"] {
+            let json = format!(r#"{{"withdrawalOrder": "{order}"}}"#);
+            let request = api_request_from_json(&json).expect("json should parse");
+            assert_eq!(
+                request.inputs.post_access_withdrawal_order,
+                WithdrawalOrder::FillBasicRate
+            );
+        }
     }
-    if let Some(v) = payload.pension_income_tax_rate {
-        cli.pension_income_tax_rate = v;
+
+    #[test]
+    fn api_request_from_json_parses_harvest_cgt_allowance_withdrawal_order() {
+        let request = api_request_from_json(r#"{"withdrawalOrder": "harvest-cgt-allowance"}"#)
+            .expect("json should parse");
+        assert_eq!(
+            request.inputs.post_access_withdrawal_order,
+            WithdrawalOrder::HarvestCgtAllowance
+        );
     }
-    if let Some(v) = payload.uk_personal_allowance {
-        cli.uk_personal_allowance = v;
+
+    #[test]
+    fn api_request_from_json_maps_pre_access_withdrawal_order() {
+        let request = api_request_from_json(
+            r#"{"preAccessWithdrawalOrder": "taxable-first", "withdrawalOrder": "pro-rata"}"#,
+        )
+        .expect("json should parse");
+        assert_eq!(
+            request.inputs.pre_access_withdrawal_order,
+            Some(WithdrawalOrder::TaxableFirst)
+        );
+        assert_eq!(
+            request.inputs.post_access_withdrawal_order,
+            WithdrawalOrder::ProRata
+        );
+
+        let err = api_request_from_json(r#"{"preAccessWithdrawalOrder": "pension-first"}"#)
+            .expect_err("a pre-access order cannot lead with the pension");
+        assert!(err.contains("--pre-access-withdrawal-order"));
     }
-    if let Some(v) = payload.uk_basic_rate_limit {
-        cli.uk_basic_rate_limit = v;
+
+    #[test]
+    fn api_request_from_json_parses_fixed_real_strategy_aliases() {
+        for policy in ["fixed-real", "fixedReal", "fixed_real", "bengen"] {
+            let json = format!(r#"{{"withdrawalPolicy": "{policy}"}}"#);
+            let request = api_request_from_json(&json).expect("json should parse");
+            assert_eq!(
+                request.inputs.withdrawal_strategy,
+                WithdrawalStrategy::FixedReal
+            );
+        }
     }
-    if let Some(v) = payload.uk_higher_rate_limit {
-        cli.uk_higher_rate_limit = v;
+
+    #[test]
+    fn api_request_from_json_parses_amortization_strategy_aliases() {
+        for policy in ["amortization", "life-expectancy", "lifeExpectancy", "rmd"] {
+            let json = format!(r#"{{"withdrawalPolicy": "{policy}"}}"#);
+            let request = api_request_from_json(&json).expect("json should parse");
+            assert_eq!(
+                request.inputs.withdrawal_strategy,
+                WithdrawalStrategy::Amortization
+            );
+        }
     }
-    if let Some(v) = payload.uk_basic_rate {
-        cli.uk_basic_rate = v;
+
+    #[test]
+    fn api_request_from_json_parses_constant_percentage_strategy() {
+        let json = r#"{
+          "withdrawalPolicy": "constant-percentage",
+          "constantWithdrawalRate": 3.5
+        }"#;
+        let request = api_request_from_json(json).expect("json should parse");
+        assert_eq!(
+            request.inputs.withdrawal_strategy,
+            WithdrawalStrategy::ConstantPercentage
+        );
+        assert_approx(request.inputs.constant_withdrawal_rate, 0.035);
     }
-    if let Some(v) = payload.uk_higher_rate {
-        cli.uk_higher_rate = v;
+
+    #[test]
+    fn api_request_from_json_parses_spending_smile() {
+        let json = r#"{
+          "spendingDeclineRate": 1.5,
+          "careCostAge": 82,
+          "careCostUplift": 40
+        }"#;
+        let request = api_request_from_json(json).expect("json should parse");
+        assert_approx(request.inputs.spending_decline_rate, 0.015);
+        assert_eq!(request.inputs.care_cost_age, 82);
+        assert_approx(request.inputs.care_cost_uplift, 0.40);
     }
-    if let Some(v) = payload.uk_additional_rate {
-        cli.uk_additional_rate = v;
+
+    #[test]
+    fn api_request_from_json_parses_spending_phases() {
+        let json = r#"{
+          "activePhaseEndAge": 72,
+          "activePhaseUplift": 15,
+          "latePhaseStartAge": 82,
+          "latePhaseUplift": -25
+        }"#;
+        let request = api_request_from_json(json).expect("json should parse");
+        assert_eq!(request.inputs.active_phase_end_age, 72);
+        assert_approx(request.inputs.active_phase_uplift, 0.15);
+        assert_eq!(request.inputs.late_phase_start_age, 82);
+        assert_approx(request.inputs.late_phase_uplift, -0.25);
+
+        let err = api_request_from_json(r#"{"activePhaseEndAge": 80, "latePhaseStartAge": 78}"#)
+            .expect_err("phases must be ordered");
+        assert!(err.contains("--late-phase-start-age"), "{err}");
     }
-    if let Some(v) = payload.uk_allowance_taper_start {
-        cli.uk_allowance_taper_start = v;
+
+    #[test]
+    fn api_request_from_json_parses_amortized_mortgage() {
+        let json = r#"{
+          "mortgageBalance": 150000,
+          "mortgageRate": 4.5,
+          "mortgageTermYears": 20,
+          "mortgageOverpayments": [{"age": 40, "amount": 10000}]
+        }"#;
+        let request = api_request_from_json(json).expect("json should parse");
+        assert_approx(request.inputs.mortgage_balance, 150_000.0);
+        assert_approx(request.inputs.mortgage_rate, 0.045);
+        assert_eq!(request.inputs.mortgage_term_years, 20);
+        assert_eq!(request.inputs.mortgage_overpayments, vec![(40, 10_000.0)]);
+
+        let err = api_request_from_json(
+            r#"{"mortgageBalance": 150000, "mortgageAnnualPayment": 9000, "mortgageEndAge": 50}"#,
+        )
+        .expect_err("both mortgage models at once are rejected");
+        assert!(err.contains("--mortgage-balance"), "{err}");
     }
-    if let Some(v) = payload.uk_allowance_taper_end {
-        cli.uk_allowance_taper_end = v;
+
+    #[test]
+    fn api_request_from_json_parses_min_cash_months() {
+        let request = api_request_from_json(r#"{"minCashMonths": 6}"#).expect("json should parse");
+        assert_approx(request.inputs.min_cash_months, 6.0);
+
+        let err = api_request_from_json(r#"{"minCashMonths": -1}"#)
+            .expect_err("negative floor is rejected");
+        assert!(err.contains("--min-cash-months"), "{err}");
     }
-    if let Some(v) = payload.state_pension_start_age {
-        cli.state_pension_start_age = v;
+
+    #[test]
+    fn api_request_from_json_parses_conversion_ladder() {
+        let json = r#"{
+          "conversionLadderAnnualAmount": 12570,
+          "conversionLadderStartAge": 55,
+          "conversionLadderEndAge": 60
+        }"#;
+        let request = api_request_from_json(json).expect("json should parse");
+        assert_approx(request.inputs.conversion_ladder_annual_amount, 12_570.0);
+        assert_eq!(request.inputs.conversion_ladder_start_age, 55);
+        assert_eq!(request.inputs.conversion_ladder_end_age, 60);
+
+        let err = api_request_from_json(
+            r#"{"conversionLadderStartAge": 60, "conversionLadderEndAge": 55}"#,
+        )
+        .expect_err("ladder window must be ordered");
+        assert!(err.contains("--conversion-ladder-end-age"), "{err}");
     }
-    if let Some(v) = payload.state_pension_income {
-        cli.state_pension_annual_income = v;
+
+    #[test]
+    fn api_request_from_json_parses_lump_expenses() {
+        let json = r#"{
+          "lumpExpenses": [
+            {"age": 65, "amount": 25000},
+            {"age": 72, "amount": 12000.5}
+          ]
+        }"#;
+        let request = api_request_from_json(json).expect("json should parse");
+        assert_eq!(
+            request.inputs.lump_expenses,
+            vec![(65, 25_000.0), (72, 12_000.5)]
+        );
     }
 
-    if let Some(v) = payload.isa_mean {
-        cli.isa_growth_rate = v;
+    #[test]
+    fn cli_parses_repeated_lump_expense_args() {
+        let required = [
+            "fire",
+            "--current-age",
+            "40",
+            "--pension-access-age",
+            "57",
+            "--isa-start",
+            "100000",
+            "--pension-start",
+            "50000",
+            "--isa-annual-contribution",
+            "10000",
+            "--pension-annual-contribution",
+            "5000",
+            "--isa-growth-rate",
+            "5",
+            "--pension-growth-rate",
+            "5",
+            "--target-annual-income",
+            "40000",
+        ];
+        let cli = Cli::try_parse_from(required).expect("cli should parse without expenses");
+        assert!(cli.lump_expenses.is_empty());
+
+        let cli = Cli::try_parse_from(required.into_iter().chain([
+            "--lump-expense",
+            "65:25000",
+            "--lump-expense",
+            "70: 8000",
+        ]))
+        .expect("cli should parse");
+        assert_eq!(cli.lump_expenses, vec![(65, 25_000.0), (70, 8_000.0)]);
+
+        assert!(parse_lump_expense("65").is_err());
+        assert!(parse_lump_expense("sixty:100").is_err());
     }
-    if let Some(v) = payload.isa_vol {
-        cli.isa_return_volatility = v;
+
+    #[test]
+    fn capital_gains_tax_rate_sets_both_bands_and_conflicts_with_them() {
+        let required = [
+            "fire",
+            "--current-age",
+            "40",
+            "--pension-access-age",
+            "57",
+            "--isa-start",
+            "100000",
+            "--pension-start",
+            "50000",
+            "--isa-annual-contribution",
+            "10000",
+            "--pension-annual-contribution",
+            "5000",
+            "--isa-growth-rate",
+            "5",
+            "--pension-growth-rate",
+            "5",
+            "--target-annual-income",
+            "40000",
+        ];
+        let cli = Cli::try_parse_from(
+            required
+                .into_iter()
+                .chain(["--capital-gains-tax-rate", "20"]),
+        )
+        .expect("cli should parse");
+        let inputs = build_inputs(cli).expect("inputs should build");
+        assert_approx(inputs.cgt_basic_rate, 0.20);
+        assert_approx(inputs.cgt_higher_rate, 0.20);
+        assert!(
+            Cli::try_parse_from(required.into_iter().chain([
+                "--capital-gains-tax-rate",
+                "20",
+                "--cgt-higher-rate",
+                "28",
+            ]))
+            .is_err()
+        );
     }
-    if let Some(v) = payload.taxable_mean {
-        cli.taxable_growth_rate = Some(v);
+
+    #[test]
+    fn simulate_config_file_fills_flags_and_command_line_wins() {
+        let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simulate.toml");
+        let parse = |args: &[&str]| {
+            let args: Vec<std::ffi::OsString> = ["fire simulate"]
+                .iter()
+                .chain(args)
+                .map(std::ffi::OsString::from)
+                .collect();
+            let strict = SimulateCommand::try_parse_from(&args);
+            parse_simulate_command(args, strict)
+        };
+        let command = parse(&[
+            "--config",
+            fixture,
+            "--target-annual-income",
+            "25000",
+            "--lump-expense=70:100",
+            "--seed-label",
+            "override",
+            "--pre-access-withdrawal-order",
+            "isa-first",
+        ])
+        .expect("config should load");
+
+        assert_eq!(command.cli.current_age, 30);
+        assert_eq!(command.cli.simulations, 200);
+        assert_eq!(
+            command.cli.withdrawal_strategy,
+            CliWithdrawalStrategy::GuytonKlinger
+        );
+        assert_eq!(command.cli.target_annual_income, 25_000.0);
+        assert_eq!(command.cli.lump_expenses, vec![(70, 100.0)]);
+        assert_eq!(command.cli.seed_label.as_deref(), Some("override"));
+        assert_eq!(
+            command.cli.pre_access_withdrawal_order,
+            Some(CliWithdrawalOrder::IsaFirst)
+        );
+        // Flags in neither place keep their defaults.
+        assert_eq!(command.cli.cash_start, input_defaults().cash_start);
+
+        let command = parse(&["--config", fixture]).expect("config should load");
+        assert_eq!(command.cli.target_annual_income, 30_000.0);
+        assert_eq!(
+            command.cli.lump_expenses,
+            vec![(45, 20_000.0), (60, 5_000.0)]
+        );
+
+        let dir = std::env::temp_dir().join(format!("fire-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir");
+        let bad = dir.join("bad.json");
+        std::fs::write(&bad, r#"{"current_age": 30, "isa-startt": 1}"#).expect("write config");
+        let bad = bad.to_str().expect("utf-8 temp path");
+        let err = parse(&["--config", bad]).expect_err("unknown keys should be rejected");
+        assert!(err.contains("unknown field `isa-startt`"), "{err}");
+
+        let mistyped = dir.join("mistyped.toml");
+        std::fs::write(&mistyped, "current-age = 30\nseed = \"five\"\n").expect("write config");
+        let mistyped = mistyped.to_str().expect("utf-8 temp path");
+        let err = parse(&["--config", mistyped]).expect_err("a string seed should be rejected");
+        assert!(err.contains("line 2"), "{err}");
+
+        let partial = dir.join("partial.toml");
+        std::fs::write(&partial, "current-age = 30\n").expect("write config");
+        let partial = partial.to_str().expect("utf-8 temp path");
+        let err = parse(&["--config", partial]).expect_err("required flags are still required");
+        assert!(err.contains("--pension-access-age is required"), "{err}");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    /// Sets every simulate flag to a non-default value once through a config file and
+    /// once on the command line. A flag missing from `CliConfig`, or one `overlay` drops
+    /// or routes to the wrong field, makes the two `Cli`s differ.
+    #[test]
+    fn config_file_covers_every_simulate_flag() {
+        let command = <SimulateCommand as clap::CommandFactory>::command();
+        let mut flags = Vec::new();
+        for arg in command.get_arguments() {
+            let id = arg.get_id().as_str();
+            if ["help", "config", "json", "jsonl"].contains(&id) {
+                continue;
+            }
+            let long = arg.get_long().expect("every simulate flag is long");
+            let possible = arg.get_possible_values();
+            let default = arg
+                .get_default_values()
+                .first()
+                .and_then(|value| value.to_str());
+            let values: Vec<(serde_json::Value, String)> = match id {
+                "market_path" => vec![("1:2:3:4".into(), "1:2:3:4".into())],
+                "debts" => vec![("1000:70".into(), "1000:70".into())],
+                "mortgage_overpayments" | "lump_expenses" => vec![
+                    ("50:100".into(), "50:100".into()),
+                    ("60:200".into(), "60:200".into()),
+                ],
+                "seed_label" => vec![("label".into(), "label".into())],
+                _ if !possible.is_empty() => {
+                    let value = possible
+                        .iter()
+                        .map(|value| value.get_name())
+                        .find(|name| Some(*name) != default)
+                        .expect("a non-default possible value");
+                    let json = match value {
+                        "true" | "false" => serde_json::Value::Bool(value == "true"),
+                        _ => value.into(),
+                    };
+                    vec![(json, value.to_string())]
+                }
+                _ => {
+                    let default = default.unwrap_or("6");
+                    let json: serde_json::Value = match default.parse::<i64>() {
+                        Ok(integer) => (integer + 1).into(),
+                        Err(_) => (default.parse::<f64>().expect("numeric default") + 1.0).into(),
+                    };
+                    vec![(json.clone(), json.to_string())]
+                }
+            };
+            assert!(
+                arg.get_action().takes_values(),
+                "--{long} must take a value to be set from a config file"
+            );
+            let json = match arg.get_action() {
+                clap::ArgAction::Append => {
+                    serde_json::Value::Array(values.iter().map(|(json, _)| json.clone()).collect())
+                }
+                _ => values[0].0.clone(),
+            };
+            let texts: Vec<String> = values
+                .into_iter()
+                .map(|(_, text)| format!("--{long}={text}"))
+                .collect();
+            flags.push((id.to_string(), long.to_string(), json, texts));
+        }
+
+        let dir = std::env::temp_dir().join(format!("fire-config-flags-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir");
+        // `--capital-gains-tax-rate` conflicts with the two CGT bands, so each side gets a run.
+        let runs: [(&str, &[&str]); 2] = [
+            ("bands", &["capital_gains_tax_rate"]),
+            ("single_rate", &["cgt_basic_rate", "cgt_higher_rate"]),
+        ];
+        for (name, skipped) in runs {
+            let mut config = serde_json::Map::new();
+            let mut argv = vec!["fire simulate".to_string()];
+            for (id, long, json, texts) in &flags {
+                if !skipped.contains(&id.as_str()) {
+                    config.insert(long.clone(), json.clone());
+                    argv.extend(texts.iter().cloned());
+                }
+            }
+            let path = dir.join(format!("{name}.json"));
+            std::fs::write(&path, serde_json::Value::Object(config).to_string())
+                .expect("write config");
+            let from_argv = SimulateCommand::try_parse_from(&argv)
+                .unwrap_or_else(|e| panic!("{argv:?}: {e}"))
+                .cli;
+            let config_args: Vec<std::ffi::OsString> =
+                vec!["fire simulate".into(), "--config".into(), path.into()];
+            let strict = SimulateCommand::try_parse_from(&config_args);
+            let from_config = parse_simulate_command(config_args, strict)
+                .expect("config should load")
+                .cli;
+            assert_eq!(format!("{from_config:?}"), format!("{from_argv:?}"));
+        }
+        std::fs::remove_dir_all(&dir).ok();
     }
-    if let Some(v) = payload.taxable_vol {
-        cli.taxable_return_volatility = Some(v);
+
+    #[test]
+    fn simulate_jsonl_writes_one_age_result_per_line() {
+        let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/simulate.toml");
+        let mut out = Vec::new();
+        run_simulate_command(
+            [
+                "fire simulate",
+                "--config",
+                fixture,
+                "--jsonl",
+                "--threads",
+                "2",
+            ],
+            &mut out,
+        )
+        .expect("jsonl run should succeed");
+
+        let text = String::from_utf8(out).expect("utf-8 output");
+        let mut ages = text
+            .lines()
+            .map(|line| {
+                let value: serde_json::Value =
+                    serde_json::from_str(line).expect("each line is one JSON object");
+                assert!(value.is_object(), "{line}");
+                value["retirementAge"].as_u64().expect("age on every line")
+            })
+            .collect::<Vec<_>>();
+        ages.sort_unstable();
+        let expected = (30..=34).collect::<Vec<_>>();
+        assert_eq!(ages, expected);
+
+        let err = run_simulate_command(
+            ["fire simulate", "--config", fixture, "--jsonl", "--json"],
+            &mut Vec::new(),
+        )
+        .expect_err("--json and --jsonl conflict");
+        assert!(err.contains("--json"), "{err}");
     }
-    if let Some(v) = payload.pension_mean {
-        cli.pension_growth_rate = v;
+
+    #[test]
+    fn build_inputs_rejects_negative_lump_expense() {
+        let mut cli = sample_cli();
+        cli.lump_expenses = vec![(65, -1.0)];
+
+        let err = validation_message(&build_inputs(cli).expect_err("must reject negative expense"));
+        assert!(err.contains("--lump-expense"));
     }
-    if let Some(v) = payload.pension_vol {
-        cli.pension_return_volatility = v;
+
+    #[test]
+    fn build_inputs_rejects_invalid_isa_taxable_correlation() {
+        let mut cli = sample_cli();
+        cli.isa_taxable_correlation = 1.5;
+
+        let err =
+            validation_message(&build_inputs(cli).expect_err("must reject correlation above 1"));
+        assert!(err.contains("--isa-taxable-correlation"));
     }
-    if let Some(v) = payload.correlation {
-        cli.return_correlation = v;
+
+    #[test]
+    fn build_inputs_rejects_invalid_inflation_return_correlation() {
+        let mut cli = sample_cli();
+        cli.inflation_return_correlation = -1.2;
+
+        let err =
+            validation_message(&build_inputs(cli).expect_err("must reject correlation below -1"));
+        assert!(err.contains("--inflation-return-correlation"));
     }
-    if let Some(v) = payload.inflation_mean {
-        cli.inflation_rate = v;
+
+    #[test]
+    fn api_request_from_json_parses_student_t_distribution() {
+        let json = r#"{"returnDistribution": "student-t", "studentTDof": 4}"#;
+        let request = api_request_from_json(json).expect("json should parse");
+        assert_eq!(
+            request.inputs.return_distribution,
+            ReturnDistribution::StudentT { dof: 4.0 }
+        );
+
+        let request = api_request_from_json("{}").expect("json should parse");
+        assert_eq!(
+            request.inputs.return_distribution,
+            ReturnDistribution::Normal
+        );
     }
-    if let Some(v) = payload.inflation_vol {
-        cli.inflation_volatility = v;
+
+    #[test]
+    fn api_request_from_json_parses_historical_market_model() {
+        for model in ["historical-bootstrap", "historicalBootstrap", "historical"] {
+            let json = format!(r#"{{"marketModel": "{model}"}}"#);
+            let request = api_request_from_json(&json).expect("json should parse");
+            assert_eq!(
+                request.inputs.market_model,
+                MarketModel::HistoricalBootstrap
+            );
+        }
     }
 
-    if let Some(v) = payload.target_income {
-        cli.target_annual_income = v;
+    #[test]
+    fn api_request_from_json_parses_deterministic_market_path() {
+        let json = r#"{
+          "marketModel": "deterministic",
+          "marketPath": [
+            {"isa": -25, "taxable": -20, "pension": -15, "inflation": 9},
+            {"isa": 30, "taxable": 25, "pension": 20, "inflation": 4}
+          ]
+        }"#;
+        let request = api_request_from_json(json).expect("json should parse");
+        let MarketModel::Deterministic { paths } = &request.inputs.market_model else {
+            panic!("expected deterministic market model");
+        };
+        assert_eq!(paths.len(), 2);
+        assert_approx(paths[0].0, -0.25);
+        assert_approx(paths[0].1, -0.20);
+        assert_approx(paths[0].2, -0.15);
+        assert_approx(paths[0].3, 0.09);
+        assert_approx(paths[1].0, 0.30);
+
+        let err = api_request_from_json(r#"{"marketModel": "deterministic"}"#)
+            .expect_err("must require a path");
+        assert!(err.contains("--market-path-year"), "{err}");
+        let err = api_request_from_json(
+            r#"{"marketModel": "deterministic", "marketPath": [{"isa": -100, "taxable": 0, "pension": 0, "inflation": 0}]}"#,
+        )
+        .expect_err("must reject a total loss");
+        assert!(err.contains("--market-path-year"), "{err}");
+        let err = api_request_from_json(
+            r#"{"marketModel": "deterministic", "marketPath": [{"isa": 5, "taxable": 5, "pension": 5, "inflation": 2}], "bondLadderYieldVol": 5}"#,
+        )
+        .expect_err("must reject a ladder volatility the replay would ignore");
+        assert!(err.contains("--bond-ladder-yield-vol"), "{err}");
+
+        assert_eq!(
+            parse_market_path_year("-40:-35:-30:12"),
+            Ok((-40.0, -35.0, -30.0, 12.0))
+        );
+        assert!(parse_market_path_year("1:2:3").is_err());
+        assert!(parse_market_path_year("a:2:3:4").is_err());
     }
-    if let Some(v) = payload.mortgage_annual_payment {
-        cli.mortgage_annual_payment = v;
+
+    #[test]
+    fn api_request_from_json_parses_garch_market_model() {
+        let json = r#"{"marketModel": "garch", "garchAlpha": 0.2, "garchBeta": 0.7, "garchLongRunVol": 18}"#;
+        let request = api_request_from_json(json).expect("json should parse");
+        assert_eq!(
+            request.inputs.market_model,
+            MarketModel::Garch {
+                alpha: 0.2,
+                beta: 0.7,
+                long_run_vol: 0.18,
+            }
+        );
+
+        let err = api_request_from_json(
+            r#"{"marketModel": "garch", "garchAlpha": 0.3, "garchBeta": 0.7}"#,
+        )
+        .expect_err("must reject a non-stationary variance");
+        assert!(err.contains("--garch-beta"), "{err}");
     }
-    if let Some(v) = payload.mortgage_end_age {
-        cli.mortgage_end_age = Some(v);
+
+    #[test]
+    fn api_request_from_json_parses_ensemble_market_model() {
+        let json = r#"{"marketModel": "ensemble", "bootstrapFraction": 0.3}"#;
+        let request = api_request_from_json(json).expect("json should parse");
+        assert_eq!(
+            request.inputs.market_model,
+            MarketModel::Ensemble {
+                bootstrap_fraction: 0.3
+            }
+        );
+
+        let err = api_request_from_json(r#"{"marketModel": "ensemble", "bootstrapFraction": 1.5}"#)
+            .expect_err("must reject a fraction above 1");
+        assert!(err.contains("--bootstrap-fraction"), "{err}");
     }
-    if let Some(v) = payload.success_threshold {
-        cli.success_threshold = v;
+
+    #[test]
+    fn api_request_from_json_parses_regime_switching_market_model() {
+        let json = r#"{
+          "marketModel": "regime-switching",
+          "normalToCrisisProbability": 0.05,
+          "crisisToNormalProbability": 0.3,
+          "crisisReturnMean": -20,
+          "crisisReturnVolatility": 30
+        }"#;
+        let request = api_request_from_json(json).expect("json should parse");
+        assert_eq!(
+            request.inputs.market_model,
+            MarketModel::RegimeSwitching {
+                p_normal_to_crisis: 0.05,
+                p_crisis_to_normal: 0.3,
+                crisis_return_mean: -0.20,
+                crisis_return_vol: 0.30,
+            }
+        );
+
+        let legacy = r#"{"marketModel": "regime-switching", "crisisMean": -20, "crisisVol": 30}"#;
+        let request = api_request_from_json(legacy).expect("old keys are still accepted");
+        assert!(matches!(
+            request.inputs.market_model,
+            MarketModel::RegimeSwitching {
+                crisis_return_mean,
+                crisis_return_vol,
+                ..
+            } if (crisis_return_mean + 0.20).abs() < 1e-12 && (crisis_return_vol - 0.30).abs() < 1e-12
+        ));
+
+        let err =
+            api_request_from_json(r#"{"marketModel": "regime", "crisisToNormalProbability": 1.5}"#)
+                .expect_err("must reject a probability above 1");
+        assert!(err.contains("--crisis-to-normal-probability"), "{err}");
     }
-    if let Some(v) = payload.bad_threshold {
-        cli.bad_year_threshold = v;
+
+    #[test]
+    fn api_request_from_json_parses_success_criterion() {
+        let request = api_request_from_json("{}").expect("json should parse");
+        assert_eq!(
+            request.inputs.success_criterion,
+            SuccessCriterion::EssentialFloorMet
+        );
+        for (value, expected) in [
+            ("full-spending", SuccessCriterion::FullSpending),
+            ("noDepletion", SuccessCriterion::NoDepletion),
+            ("essential_floor_met", SuccessCriterion::EssentialFloorMet),
+        ] {
+            let json = format!(r#"{{"successCriterion": "{value}"}}"#);
+            let request = api_request_from_json(&json).expect("json should parse");
+            assert_eq!(request.inputs.success_criterion, expected);
+        }
     }
-    if let Some(v) = payload.good_threshold {
-        cli.good_year_threshold = v;
+
+    #[test]
+    fn api_request_from_json_maps_fund_fees_from_percent() {
+        let request = api_request_from_json(
+            r#"{"isaFeeRate": 0.2, "taxableFeeRate": 0.5, "pensionFeeRate": 0.75}"#,
+        )
+        .expect("json should parse");
+        assert!((request.inputs.isa_fee_rate - 0.002).abs() < 1e-12);
+        assert!((request.inputs.taxable_fee_rate - 0.005).abs() < 1e-12);
+        assert!((request.inputs.pension_fee_rate - 0.0075).abs() < 1e-12);
     }
-    if let Some(v) = payload.bad_cut {
-        cli.bad_year_cut = v;
+
+    #[test]
+    fn api_request_from_json_maps_employer_match() {
+        let request =
+            api_request_from_json(r#"{"employerMatchRate": 50, "employerMatchCap": 3000}"#)
+                .expect("json should parse");
+        assert!((request.inputs.employer_match_rate - 0.5).abs() < 1e-12);
+        assert_eq!(request.inputs.employer_match_cap, Some(3_000.0));
+
+        let err = api_request_from_json(r#"{"employerMatchCap": -1}"#).expect_err("must fail");
+        assert!(err.contains("--employer-match-cap"), "{err}");
     }
-    if let Some(v) = payload.good_raise {
-        cli.good_year_raise = v;
+
+    #[test]
+    fn api_request_from_json_maps_pension_annual_allowance() {
+        let request = api_request_from_json(
+            r#"{"pensionAnnualAllowance": 40000, "pensionOverflowToTaxable": false}"#,
+        )
+        .expect("json should parse");
+        assert_eq!(request.inputs.pension_annual_allowance, 40_000.0);
+        assert!(!request.inputs.pension_overflow_to_taxable);
+
+        let err =
+            api_request_from_json(r#"{"pensionAnnualAllowance": -1}"#).expect_err("must fail");
+        assert!(err.contains("--pension-annual-allowance"), "{err}");
     }
-    if let Some(v) = payload.min_floor {
-        cli.min_income_floor = v;
+
+    #[test]
+    fn api_request_from_json_maps_db_pension() {
+        let request =
+            api_request_from_json(r#"{"dbPensionStartAge": 62, "dbPensionIncome": 9000}"#)
+                .expect("json should parse");
+        assert_eq!(request.inputs.db_pension_start_age, 62);
+        assert_eq!(request.inputs.db_pension_annual_income, 9_000.0);
+
+        let err = api_request_from_json(r#"{"dbPensionIncome": -1}"#).expect_err("must fail");
+        assert!(err.contains("--db-pension-annual-income"), "{err}");
     }
-    if let Some(v) = payload.max_ceiling {
-        cli.max_income_ceiling = v;
+
+    #[test]
+    fn api_request_from_json_maps_annuity_purchase() {
+        let request = api_request_from_json(
+            r#"{"annuityPurchaseAge": 67, "annuityPurchaseAmount": 100000, "annuityRate": 6}"#,
+        )
+        .expect("json should parse");
+        assert_eq!(request.inputs.annuity_purchase_age, Some(67));
+        assert_eq!(request.inputs.annuity_purchase_amount, 100_000.0);
+        assert!((request.inputs.annuity_rate - 0.06).abs() < 1e-12);
+
+        let err = api_request_from_json(r#"{"pensionAccessAge": 57, "annuityPurchaseAge": 55}"#)
+            .expect_err("must fail");
+        assert!(err.contains("--annuity-purchase-age"), "{err}");
     }
-    if let Some(v) = payload.withdrawal_policy {
-        cli.withdrawal_strategy = v.into();
+
+    #[test]
+    fn api_request_from_json_maps_clamp_bounds() {
+        let request = api_request_from_json(
+            r#"{"returnClampMin": -99, "returnClampMax": 400, "inflationClampMin": -10, "inflationClampMax": 50}"#,
+        )
+        .expect("json should parse");
+        assert!((request.inputs.return_clamp_min + 0.99).abs() < 1e-12);
+        assert!((request.inputs.return_clamp_max - 4.0).abs() < 1e-12);
+        assert!((request.inputs.inflation_clamp_min + 0.10).abs() < 1e-12);
+        assert!((request.inputs.inflation_clamp_max - 0.50).abs() < 1e-12);
+
+        let err = api_request_from_json(r#"{"inflationClampMin": 25}"#).expect_err("must fail");
+        assert!(err.contains("--inflation-clamp-min"), "{err}");
+        let err = api_request_from_json(r#"{"returnClampMin": -100}"#).expect_err("must fail");
+        assert!(err.contains("--return-clamp-min"), "{err}");
     }
-    if let Some(v) = payload.gk_lower_guardrail {
-        cli.gk_lower_guardrail = v;
+
+    #[test]
+    fn api_request_from_json_maps_rental_income() {
+        let request = api_request_from_json(
+            r#"{"rentalIncomeStartAge": 45, "rentalIncome": 12000, "rentalGrowthRate": 1.5}"#,
+        )
+        .expect("json should parse");
+        assert_eq!(request.inputs.rental_income_start_age, 45);
+        assert_eq!(request.inputs.rental_annual_income, 12_000.0);
+        assert!((request.inputs.rental_growth_rate - 0.015).abs() < 1e-12);
+
+        let err = api_request_from_json(r#"{"rentalIncome": -1}"#).expect_err("must fail");
+        assert!(err.contains("--rental-annual-income"), "{err}");
     }
-    if let Some(v) = payload.gk_upper_guardrail {
-        cli.gk_upper_guardrail = v;
+
+    #[test]
+    fn seed_label_hashes_to_a_stable_seed_unless_a_seed_is_given() {
+        let seed = |json| {
+            api_request_from_json(json)
+                .expect("json should parse")
+                .inputs
+                .seed
+        };
+        let plan = seed(r#"{"seedLabel": "my-plan-v2"}"#);
+        assert_eq!(plan, seed(r#"{"seedLabel": "my-plan-v2"}"#));
+        assert_eq!(plan, resolve_seed(None, Some("my-plan-v2")));
+        assert_ne!(plan, seed(r#"{"seedLabel": "my-plan-v3"}"#));
+        assert_ne!(plan, DEFAULT_SEED);
+        assert_eq!(seed(r#"{"seedLabel": "my-plan-v2", "seed": 7}"#), 7);
+        assert_eq!(seed("{}"), DEFAULT_SEED);
     }
-    if let Some(v) = payload.vpw_real_return {
-        cli.vpw_expected_real_return = v;
+
+    #[test]
+    fn api_request_from_json_parses_selection_confidence() {
+        let request = api_request_from_json(r#"{"selectionConfidence": "conservative"}"#)
+            .expect("json should parse");
+        assert_eq!(
+            request.inputs.selection_confidence,
+            SelectionConfidence::Conservative
+        );
+        let request = api_request_from_json("{}").expect("json should parse");
+        assert_eq!(
+            request.inputs.selection_confidence,
+            SelectionConfidence::PointEstimate
+        );
     }
-    if let Some(v) = payload.floor_upside_capture {
-        cli.floor_upside_capture = v;
+
+    #[test]
+    fn api_request_from_json_maps_part_time_income() {
+        let request = api_request_from_json(
+            r#"{"partTimeIncome": 18000, "partTimeStartAge": 50, "partTimeEndAge": 55}"#,
+        )
+        .expect("json should parse");
+        assert_eq!(request.inputs.part_time_annual_income, 18_000.0);
+        assert_eq!(request.inputs.part_time_start_age, 50);
+        assert_eq!(request.inputs.part_time_end_age, 55);
+
+        let err = api_request_from_json(
+            r#"{"partTimeIncome": 18000, "partTimeStartAge": 55, "partTimeEndAge": 50}"#,
+        )
+        .expect_err("window must be ordered");
+        assert!(err.contains("--part-time-end-age"), "{err}");
     }
-    if let Some(v) = payload.bucket_years_target {
-        cli.bucket_target_years = v;
+
+    #[test]
+    fn api_request_from_json_maps_lisa() {
+        let request = api_request_from_json(
+            r#"{"lisaStart": 8000, "lisaContribution": 4000, "lisaAccessAge": 61}"#,
+        )
+        .expect("json should parse");
+        assert_eq!(request.inputs.lisa_start, 8_000.0);
+        assert_eq!(request.inputs.lisa_annual_contribution, 4_000.0);
+        assert_eq!(request.inputs.lisa_access_age, 61);
+
+        let err = api_request_from_json(r#"{"lisaContribution": -1}"#).expect_err("must fail");
+        assert!(err.contains("--lisa-annual-contribution"), "{err}");
     }
-    if let Some(v) = payload.extra_to_cash {
-        cli.good_year_extra_buffer_withdrawal = v;
+
+    #[test]
+    fn api_request_from_json_maps_cgt_bands() {
+        let request = api_request_from_json(r#"{"cgtBasicRate": 10, "cgtHigherRate": 20}"#)
+            .expect("json should parse");
+        assert!((request.inputs.cgt_basic_rate - 0.10).abs() < 1e-12);
+        assert!((request.inputs.cgt_higher_rate - 0.20).abs() < 1e-12);
+
+        let request = api_request_from_json(r#"{"cgtRate": 15, "cgtHigherRate": 28}"#)
+            .expect("json should parse");
+        assert!((request.inputs.cgt_basic_rate - 0.15).abs() < 1e-12);
+        assert!((request.inputs.cgt_higher_rate - 0.28).abs() < 1e-12);
+
+        let err = api_request_from_json(r#"{"cgtHigherRate": 101}"#).expect_err("must fail");
+        assert!(err.contains("--cgt-higher-rate"), "{err}");
     }
-    if let Some(v) = payload.cash_growth {
-        cli.cash_growth_rate = v;
+
+    #[test]
+    fn api_request_from_json_parses_scottish_tax_bands() {
+        let request = api_request_from_json(
+            r#"{"pensionTaxMode": "scottish", "scottishTopRate": 47, "scottishHigherRateLimit": 80000}"#,
+        )
+        .expect("json should parse");
+        assert_eq!(
+            request.inputs.pension_tax_mode,
+            PensionTaxMode::ScottishBands
+        );
+        assert!((request.inputs.scottish_top_rate - 0.47).abs() < 1e-12);
+        assert!((request.inputs.scottish_starter_rate - 0.19).abs() < 1e-12);
+        assert_eq!(request.inputs.scottish_higher_rate_limit, 80_000.0);
+
+        let err = api_request_from_json(r#"{"scottishIntermediateRateLimit": 20000}"#)
+            .expect_err("must fail");
+        assert!(err.contains("--scottish-intermediate-rate-limit"), "{err}");
     }
-    if let Some(v) = payload.bond_ladder_yield {
-        cli.bond_ladder_yield = v;
+
+    #[test]
+    fn api_request_from_json_maps_glide_path() {
+        let request = api_request_from_json(
+            r#"{"glideStartEquity": 90, "glideEndEquity": 40, "glideEndAge": 70}"#,
+        )
+        .expect("json should parse");
+        assert!((request.inputs.glide_start_equity - 0.9).abs() < 1e-12);
+        assert!((request.inputs.glide_end_equity - 0.4).abs() < 1e-12);
+        assert_eq!(request.inputs.glide_end_age, Some(70));
+
+        let err = api_request_from_json(r#"{"glideEndEquity": 120}"#).expect_err("must fail");
+        assert!(err.contains("--glide-end-equity"), "{err}");
+        let err = api_request_from_json(r#"{"currentAge": 40, "glideEndAge": 40}"#)
+            .expect_err("must fail");
+        assert!(err.contains("--glide-end-age"), "{err}");
     }
-    if let Some(v) = payload.bond_ladder_years {
-        cli.bond_ladder_years = v;
+
+    #[test]
+    fn api_request_from_json_maps_state_pension_growth() {
+        let request = api_request_from_json(
+            r#"{"statePensionRealGrowth": 1.5, "statePensionGrowthBeforeStart": true}"#,
+        )
+        .expect("json should parse");
+        assert!((request.inputs.state_pension_real_growth - 0.015).abs() < 1e-12);
+        assert!(request.inputs.state_pension_growth_before_start);
+
+        let err = api_request_from_json(r#"{"statePensionRealGrowth": -100}"#)
+            .expect_err("growth of -100% must be rejected");
+        assert!(err.contains("--state-pension-real-growth"));
     }
-    if let Some(v) = payload.withdrawal_order {
-        cli.post_access_withdrawal_order = v.into();
+
+    #[test]
+    fn api_request_from_json_rejects_deferral_past_horizon() {
+        let request = api_request_from_json(
+            r#"{"statePensionDeferralYears": 3, "statePensionDeferralUpliftRate": 5.8}"#,
+        )
+        .expect("json should parse");
+        assert_eq!(request.inputs.state_pension_deferral_years, 3);
+        assert!((request.inputs.state_pension_deferral_uplift_rate - 0.058).abs() < 1e-12);
+
+        let err = api_request_from_json(
+            r#"{"statePensionStartAge": 68, "horizonAge": 75, "statePensionDeferralYears": 7}"#,
+        )
+        .expect_err("deferral to the horizon must be rejected");
+        assert!(err.contains("--state-pension-deferral-years"));
     }
 
-    if let Some(v) = payload.analysis_mode {
-        options.mode = v.into();
+    #[test]
+    fn api_request_from_json_maps_thresholds_frozen_until_age() {
+        let request = api_request_from_json(r#"{"thresholdsFrozenUntilAge": 33}"#)
+            .expect("json should parse");
+        assert_eq!(request.inputs.thresholds_frozen_until_age, Some(33));
+        let request = api_request_from_json("{}").expect("json should parse");
+        assert_eq!(request.inputs.thresholds_frozen_until_age, None);
     }
-    if let Some(v) = payload.coast_retirement_age {
-        options.coast_retirement_age = Some(v);
+
+    #[test]
+    fn api_request_from_json_maps_household_fields() {
+        let request = api_request_from_json(
+            r#"{
+                "household": true,
+                "partnerPensionStart": 150000,
+                "partnerPensionAccessAge": 60,
+                "partnerPersonalAllowance": 10000
+            }"#,
+        )
+        .expect("json should parse");
+        assert!(request.inputs.household);
+        assert_eq!(request.inputs.partner_pension_start, 150_000.0);
+        assert_eq!(request.inputs.partner_pension_access_age, 60);
+        assert_eq!(request.inputs.partner_personal_allowance, 10_000.0);
+
+        let err = api_request_from_json(r#"{"household": true, "partnerPensionStart": -1}"#)
+            .expect_err("negative partner pension must be rejected");
+        assert!(err.contains("--partner-pension-start"));
     }
 
-    let inputs = build_inputs(cli)?;
-    if let Some(age) = options.coast_retirement_age {
-        if age < inputs.current_age {
-            return Err("--coastRetirementAge must be >= currentAge".to_string());
-        }
-        if age >= inputs.horizon_age {
-            return Err("--coastRetirementAge must be < horizonAge".to_string());
-        }
+    #[test]
+    fn api_request_from_json_caps_marriage_allowance_transfer() {
+        let request = api_request_from_json(r#"{"marriageAllowanceTransfer": 1260}"#)
+            .expect("json should parse");
+        assert_eq!(request.inputs.marriage_allowance_transfer, 1_260.0);
+
+        let err = api_request_from_json(r#"{"marriageAllowanceTransfer": 1300}"#)
+            .expect_err("a transfer above the statutory cap must be rejected");
+        assert!(err.contains("--marriage-allowance-transfer"));
     }
 
-    Ok(ApiRequest { inputs, options })
-}
+    #[test]
+    fn api_request_from_json_maps_age_invariant_draws() {
+        let request =
+            api_request_from_json(r#"{"ageInvariantDraws": true}"#).expect("json should parse");
+        assert!(request.inputs.age_invariant_draws);
+        let request = api_request_from_json("{}").expect("json should parse");
+        assert!(!request.inputs.age_invariant_draws);
+        assert!(!request.inputs.stratified_inflation);
 
-fn default_cli_for_api() -> Cli {
-    Cli {
-        current_age: 30,
-        pension_access_age: 57,
-        isa_start: 100_000.0,
-        taxable_start: 15_000.0,
-        taxable_cost_basis_start: 12_000.0,
-        pension_start: 200_000.0,
-        cash_start: 0.0,
-        bond_ladder_start: 0.0,
-        isa_annual_contribution: 30_000.0,
-        isa_annual_contribution_limit: 20_000.0,
-        taxable_annual_contribution: 5_000.0,
-        pension_annual_contribution: 0.0,
-        contribution_growth_rate: 0.0,
-        isa_growth_rate: 8.0,
-        isa_return_volatility: 12.0,
-        taxable_growth_rate: Some(8.0),
-        taxable_return_volatility: Some(12.0),
-        pension_growth_rate: 8.0,
-        pension_return_volatility: 12.0,
-        return_correlation: 0.8,
-        capital_gains_tax_rate: 20.0,
-        capital_gains_allowance: 3_000.0,
-        taxable_return_tax_drag: 1.0,
-        pension_tax_mode: CliPensionTaxMode::UkBands,
-        pension_income_tax_rate: 20.0,
-        uk_personal_allowance: 12_570.0,
-        uk_basic_rate_limit: 50_270.0,
-        uk_higher_rate_limit: 125_140.0,
-        uk_basic_rate: 20.0,
-        uk_higher_rate: 40.0,
-        uk_additional_rate: 45.0,
-        uk_allowance_taper_start: 100_000.0,
-        uk_allowance_taper_end: 125_140.0,
-        state_pension_start_age: 67,
-        state_pension_annual_income: 0.0,
-        inflation_rate: 2.5,
-        inflation_volatility: 1.0,
-        target_annual_income: 50_000.0,
-        mortgage_annual_payment: 0.0,
-        mortgage_end_age: None,
-        max_age: 70,
-        horizon_age: 90,
-        simulations: 3_000,
-        success_threshold: 90.0,
-        seed: 42,
-        bad_year_threshold: -5.0,
-        good_year_threshold: 10.0,
-        bad_year_cut: 10.0,
-        good_year_raise: 5.0,
-        min_income_floor: 80.0,
-        max_income_ceiling: 200.0,
-        withdrawal_strategy: CliWithdrawalStrategy::Guardrails,
-        gk_lower_guardrail: 80.0,
-        gk_upper_guardrail: 120.0,
-        vpw_expected_real_return: 3.5,
-        floor_upside_capture: 50.0,
-        bucket_target_years: 2.0,
-        good_year_extra_buffer_withdrawal: 10.0,
-        cash_growth_rate: 1.0,
-        bond_ladder_yield: 3.0,
-        bond_ladder_years: 10,
-        post_access_withdrawal_order: CliWithdrawalOrder::ProRata,
+        let request =
+            api_request_from_json(r#"{"stratifiedInflation": true}"#).expect("json should parse");
+        assert!(request.inputs.stratified_inflation);
     }
-}
 
-fn build_simulate_response(
-    inputs: &Inputs,
-    model: &ModelResult,
-    mode: AnalysisMode,
-    coast_retirement_age: Option<u32>,
-    cashflow: CashflowResponse<'_>,
-) -> SimulateResponse {
-    SimulateResponse {
-        mode: mode.into(),
-        withdrawal_policy: inputs.withdrawal_strategy.into(),
-        coast_retirement_age,
-        success_threshold: inputs.success_threshold,
-        selected_retirement_age: model
-            .selected_index
-            .map(|idx| model.age_results[idx].retirement_age),
-        best_retirement_age: model.age_results[model.best_index].retirement_age,
-        cashflow_candidate_age: cashflow.candidate_age,
-        cashflow_retirement_age: cashflow.retirement_age,
-        cashflow_contribution_stop_age: cashflow.contribution_stop_age,
-        age_results: model.age_results.clone(),
-        cashflow_years: cashflow.years.to_vec(),
+    #[test]
+    fn api_request_from_json_maps_bond_ladder_reinvest() {
+        let request =
+            api_request_from_json(r#"{"bondLadderReinvest": true}"#).expect("json should parse");
+        assert!(request.inputs.bond_ladder_reinvest);
+        let request = api_request_from_json("{}").expect("json should parse");
+        assert!(!request.inputs.bond_ladder_reinvest);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
-    use std::path::Path;
+    #[test]
+    fn api_request_from_json_maps_bootstrap_block_years() {
+        let request =
+            api_request_from_json(r#"{"marketModel": "historical", "bootstrapBlockYears": 10}"#)
+                .expect("json should parse");
+        assert_eq!(request.inputs.bootstrap_block_years, 10);
+    }
 
-    const EPS: f64 = 1e-6;
+    #[test]
+    fn build_inputs_rejects_student_t_dof_without_finite_variance() {
+        let mut cli = sample_cli();
+        cli.return_distribution = CliReturnDistribution::StudentT;
+        cli.student_t_dof = 2.0;
 
-    fn assert_approx(actual: f64, expected: f64) {
-        assert!(
-            (actual - expected).abs() <= EPS,
-            "expected {expected}, got {actual}"
-        );
+        let err = validation_message(&build_inputs(cli).expect_err("must reject dof <= 2"));
+        assert!(err.contains("--student-t-dof"));
     }
 
-    fn sample_cli() -> Cli {
-        default_cli_for_api()
+    #[test]
+    fn build_inputs_rejects_zero_bootstrap_block_years() {
+        let mut cli = sample_cli();
+        cli.bootstrap_block_years = 0;
+
+        let err = validation_message(&build_inputs(cli).expect_err("must reject empty blocks"));
+        assert!(err.contains("--bootstrap-block-years"));
     }
 
-    fn assert_golden_snapshot(path: &str, actual: &str) {
-        let update = matches!(
-            std::env::var("UPDATE_GOLDEN").as_deref(),
-            Ok("1") | Ok("true") | Ok("TRUE")
-        );
-        let snapshot_path = Path::new(path);
+    #[test]
+    fn build_inputs_requires_all_three_factor_betas() {
+        let mut cli = sample_cli();
+        cli.isa_beta = Some(0.9);
+        cli.pension_beta = Some(0.9);
 
-        if update {
-            if let Some(parent) = snapshot_path.parent() {
-                fs::create_dir_all(parent).expect("failed to create snapshot directory");
-            }
-            fs::write(snapshot_path, actual).expect("failed to write golden snapshot");
-            return;
-        }
+        let err = validation_message(&build_inputs(cli).expect_err("must reject partial betas"));
+        assert!(err.contains("must be set together"));
 
-        let expected = fs::read_to_string(snapshot_path).unwrap_or_else(|_| {
-            panic!("missing golden snapshot at {path}; run with UPDATE_GOLDEN=1 to generate")
-        });
-        assert_eq!(
-            actual, expected,
-            "snapshot mismatch for {path}; run with UPDATE_GOLDEN=1 to refresh if expected"
-        );
+        let payload = r#"{"isaBeta":0.9,"taxableBeta":0.8,"pensionBeta":0.7}"#;
+        let request = api_request_from_json(payload).expect("payload should parse");
+        assert_eq!(request.inputs.return_factor_betas, Some([0.9, 0.8, 0.7]));
     }
 
     #[test]
-    fn build_inputs_defaults_taxable_basis_to_start_when_zero() {
+    fn build_inputs_rejects_non_psd_correlation_matrix() {
         let mut cli = sample_cli();
-        cli.taxable_start = 20_000.0;
-        cli.taxable_cost_basis_start = 0.0;
+        cli.return_correlation = 0.9;
+        cli.isa_taxable_correlation = 0.9;
+        cli.taxable_pension_correlation = Some(-0.9);
 
-        let inputs = build_inputs(cli).expect("valid inputs");
-        assert_approx(inputs.taxable_cost_basis_start, 20_000.0);
+        let err =
+            validation_message(&build_inputs(cli).expect_err("must reject non-PSD correlations"));
+        assert!(err.contains("positive semidefinite"));
     }
 
     #[test]
-    fn build_inputs_rejects_invalid_taxable_basis() {
+    fn build_inputs_rejects_negative_bequest_target() {
+        let mut cli = sample_cli();
+        cli.bequest_target = -1.0;
+
+        let err = validation_message(&build_inputs(cli).expect_err("must reject negative bequest"));
+        assert!(err.contains("--bequest-target"));
+    }
+
+    #[test]
+    fn build_inputs_rejects_essential_income_above_target() {
         let mut cli = sample_cli();
-        cli.taxable_start = 10_000.0;
-        cli.taxable_cost_basis_start = 12_000.0;
+        cli.target_annual_income = 40_000.0;
+        cli.essential_annual_income = 45_000.0;
 
-        let err = build_inputs(cli).expect_err("must reject invalid basis");
-        assert!(err.contains("--taxable-cost-basis-start"));
+        let err =
+            validation_message(&build_inputs(cli).expect_err("must reject essential above target"));
+        assert!(err.contains("--essential-annual-income"));
     }
 
     #[test]
-    fn build_inputs_rejects_invalid_contribution_growth_rate() {
-        let mut cli = sample_cli();
-        cli.contribution_growth_rate = -100.0;
-        let err = build_inputs(cli).expect_err("must reject <= -100 growth rate");
-        assert!(err.contains("--contribution-growth-rate"));
+    fn api_request_from_json_parses_essential_income() {
+        let json = r#"{"targetIncome": 40000, "essentialIncome": 24000}"#;
+        let request = api_request_from_json(json).expect("json should parse");
+        assert_approx(request.inputs.essential_annual_income, 24_000.0);
     }
 
     #[test]
-    fn build_inputs_rejects_invalid_uk_band_order() {
+    fn build_inputs_rejects_invalid_spending_decline_rate() {
         let mut cli = sample_cli();
-        cli.uk_basic_rate_limit = 10_000.0;
-        cli.uk_personal_allowance = 12_570.0;
+        cli.spending_decline_rate = 100.0;
 
-        let err = build_inputs(cli).expect_err("must reject bad UK threshold order");
-        assert!(err.contains("--uk-basic-rate-limit"));
+        let err = validation_message(&build_inputs(cli).expect_err("must reject full decline"));
+        assert!(err.contains("--spending-decline-rate"));
     }
 
     #[test]
-    fn build_inputs_uses_isa_defaults_for_taxable_return_params() {
+    fn build_inputs_rejects_invalid_constant_withdrawal_rate() {
         let mut cli = sample_cli();
-        cli.taxable_growth_rate = None;
-        cli.taxable_return_volatility = None;
+        cli.constant_withdrawal_rate = 120.0;
 
-        let inputs = build_inputs(cli).expect("valid inputs");
-        assert_approx(inputs.taxable_return_mean, inputs.isa_return_mean);
-        assert_approx(inputs.taxable_return_vol, inputs.isa_return_vol);
+        let err = validation_message(&build_inputs(cli).expect_err("must reject rate above 100"));
+        assert!(err.contains("--constant-withdrawal-rate"));
     }
 
     #[test]
-    fn api_request_from_json_parses_web_keys() {
+    fn api_request_from_json_parses_cape_based_strategy() {
         let json = r#"{
-          "currentAge": 31,
-          "pensionAccessAge": 58,
-          "isaStart": 120000,
-          "taxableStart": 20000,
-          "taxableBasisStart": 15000,
-          "pensionStart": 250000,
-          "cashStart": 5000,
-          "bondLadderStart": 25000,
-          "targetIncome": 45000,
-          "mortgageAnnualPayment": 12000,
-          "mortgageEndAge": 40,
-          "withdrawalOrder": "taxable-first",
-          "simulations": 1234,
-          "contributionGrowth": 3,
-          "pensionTaxMode": "uk-bands",
-          "statePensionStartAge": 67,
-          "statePensionIncome": 12000,
-          "withdrawalPolicy": "vpw",
-          "vpwRealReturn": 4.2,
-          "bondLadderYield": 3.2,
-          "bondLadderYears": 8
+          "withdrawalPolicy": "cape-based",
+          "capeA": 1.5,
+          "capeB": 0.4,
+          "capeStart": 32,
+          "capeLongRunMean": 18,
+          "capeReversionSpeed": 15
         }"#;
         let request = api_request_from_json(json).expect("json should parse");
         let inputs = request.inputs;
+        assert_eq!(inputs.withdrawal_strategy, WithdrawalStrategy::CapeBased);
+        assert_approx(inputs.cape_a, 0.015);
+        assert_approx(inputs.cape_b, 0.4);
+        assert_approx(inputs.cape_start, 32.0);
+        assert_approx(inputs.cape_long_run_mean, 18.0);
+        assert_approx(inputs.cape_reversion_speed, 0.15);
+    }
 
-        assert_eq!(inputs.current_age, 31);
-        assert_eq!(inputs.pension_access_age, 58);
-        assert_approx(inputs.isa_start, 120_000.0);
-        assert_approx(inputs.taxable_start, 20_000.0);
-        assert_approx(inputs.taxable_cost_basis_start, 15_000.0);
-        assert_approx(inputs.pension_start, 250_000.0);
-        assert_approx(inputs.cash_start, 5_000.0);
-        assert_approx(inputs.bond_ladder_start, 25_000.0);
-        assert_approx(inputs.target_annual_income, 45_000.0);
-        assert_approx(inputs.mortgage_annual_payment, 12_000.0);
-        assert_eq!(inputs.mortgage_end_age, Some(40));
-        assert_approx(inputs.contribution_growth_rate, 0.03);
-        assert_eq!(inputs.state_pension_start_age, 67);
-        assert_approx(inputs.state_pension_annual_income, 12_000.0);
-        assert_eq!(inputs.simulations, 1234);
-        assert_eq!(inputs.withdrawal_strategy, WithdrawalStrategy::Vpw);
-        assert_approx(inputs.vpw_expected_real_return, 0.042);
-        assert_approx(inputs.bond_ladder_yield, 0.032);
-        assert_eq!(inputs.bond_ladder_years, 8);
-        assert_eq!(
-            inputs.post_access_withdrawal_order,
-            WithdrawalOrder::TaxableFirst
-        );
-        assert_eq!(inputs.pension_tax_mode, PensionTaxMode::UkBands);
+    #[test]
+    fn build_inputs_rejects_non_positive_cape_start() {
+        let mut cli = sample_cli();
+        cli.cape_start = 0.0;
+
+        let err = validation_message(&build_inputs(cli).expect_err("must reject zero CAPE"));
+        assert!(err.contains("--cape-start"));
     }
 
     #[test]
-    fn build_inputs_rejects_mortgage_payment_without_end_age() {
+    fn build_inputs_rejects_invalid_guardrail_range() {
         let mut cli = sample_cli();
-        cli.mortgage_annual_payment = 10_000.0;
-        cli.mortgage_end_age = None;
+        cli.gk_lower_guardrail = 130.0;
+        cli.gk_upper_guardrail = 120.0;
 
-        let err = build_inputs(cli).expect_err("must require mortgage end age");
-        assert!(err.contains("--mortgage-end-age"));
+        let err = validation_message(
+            &build_inputs(cli).expect_err("must reject invalid guardrail range"),
+        );
+        assert!(err.contains("--gk-upper-guardrail"));
     }
 
     #[test]
-    fn api_request_from_json_parses_coast_mode_and_retirement_age() {
-        let json = r#"{
-          "analysisMode": "coast-fire",
-          "coastRetirementAge": 60,
-          "currentAge": 31,
-          "horizonAge": 90
-        }"#;
-        let request = api_request_from_json(json).expect("json should parse");
-        assert_eq!(request.options.mode, AnalysisMode::CoastFire);
-        assert_eq!(request.options.coast_retirement_age, Some(60));
-        assert_eq!(request.inputs.current_age, 31);
+    fn default_cli_builds_the_library_defaults() {
+        assert_eq!(build_inputs(sample_cli()), Ok(Inputs::default()));
     }
 
     #[test]
-    fn api_request_from_json_parses_bond_ladder_withdrawal_order() {
-        let json = r#"{
-          "withdrawalOrder": "bond-ladder-first"
-        }"#;
-        let request = api_request_from_json(json).expect("json should parse");
-        assert_eq!(
-            request.inputs.post_access_withdrawal_order,
-            WithdrawalOrder::BondLadderFirst
-        );
+    fn inputs_builder_rejects_the_same_cases_as_build_inputs() {
+        let mut cli = sample_cli();
+        cli.horizon_age = 65;
+        cli.simulations = 0;
+        cli.return_correlation = 1.5;
+        cli.gk_adjustment = 100.0;
+        cli.market_model = CliMarketModel::Deterministic;
+
+        let from_cli = build_inputs(cli).expect_err("cli must be rejected");
+        let from_builder = Inputs::builder()
+            .horizon_age(65)
+            .simulations(0)
+            .return_correlation(1.5)
+            .gk_adjustment(1.0)
+            .market_model(MarketModel::Deterministic { paths: Vec::new() })
+            .build()
+            .expect_err("builder must be rejected");
+        assert_eq!(from_builder, from_cli);
+        assert_eq!(from_cli.len(), 5);
     }
 
     #[test]
-    fn build_inputs_rejects_invalid_guardrail_range() {
+    fn build_inputs_rejects_invalid_gk_adjustment() {
         let mut cli = sample_cli();
-        cli.gk_lower_guardrail = 130.0;
-        cli.gk_upper_guardrail = 120.0;
+        cli.gk_adjustment = 100.0;
 
-        let err = build_inputs(cli).expect_err("must reject invalid guardrail range");
-        assert!(err.contains("--gk-upper-guardrail"));
+        let err =
+            validation_message(&build_inputs(cli).expect_err("must reject a 100% adjustment"));
+        assert!(err.contains("--gk-adjustment"));
     }
 
     #[test]
@@ -1732,7 +7006,7 @@ mod tests {
         cli.inflation_volatility = 0.0;
 
         let inputs = build_inputs(cli).expect("valid inputs");
-        let model = run_model(&inputs);
+        let model = run_model(&inputs).expect("inputs are valid");
         let trace_index = model.selected_index.unwrap_or(model.best_index);
         let trace_candidate_age = model.age_results[trace_index].retirement_age;
         let cashflow = run_yearly_cashflow_trace(
@@ -1752,7 +7026,7 @@ mod tests {
             &model,
             AnalysisMode::RetirementSweep,
             None,
-            cashflow_response,
+            Some(cashflow_response),
         );
         let json = serde_json::to_string(&response).expect("response should serialize");
         assert!(json.contains("\"ageResults\""));
@@ -1778,7 +7052,8 @@ mod tests {
             ..SolveGoalPayload::default()
         };
 
-        let config = build_goal_solve_config(&inputs, &payload).expect("config should build");
+        let config = build_goal_solve_config(&inputs, &payload, ServerLimits::default())
+            .expect("config should build");
         assert_eq!(config.goal_type, GoalType::MaxIncome);
         assert_eq!(config.target_retirement_age, 65);
         assert_approx(config.target_success_threshold, 0.93);
@@ -1795,9 +7070,62 @@ mod tests {
             ..SolveGoalPayload::default()
         };
 
-        let err =
-            build_goal_solve_config(&inputs, &payload).expect_err("must reject bad threshold");
-        assert!(err.contains("--targetSuccessThreshold"));
+        let err = build_goal_solve_config(&inputs, &payload, ServerLimits::default())
+            .expect_err("must reject bad threshold");
+        assert!(err.starts_with("targetSuccessThreshold must"), "{err}");
+    }
+
+    #[test]
+    fn build_goal_solve_config_requires_a_cvar_floor_for_the_cvar_goal() {
+        let inputs = build_inputs(sample_cli()).expect("valid inputs");
+        let payload: SolveGoalPayload = serde_json::from_str(
+            r#"{"goalType": "max-income-cvar-constrained", "targetCvar5IncomeRatio": 80}"#,
+        )
+        .expect("payload should parse");
+        let config = build_goal_solve_config(&inputs, &payload, ServerLimits::default())
+            .expect("config should build");
+        assert_eq!(config.goal_type, GoalType::MaxIncomeCvarConstrained);
+        assert_approx(config.min_cvar5_income_ratio, 0.8);
+
+        let payload: SolveGoalPayload =
+            serde_json::from_str(r#"{"goalType": "max-income-cvar-constrained"}"#)
+                .expect("payload should parse");
+        let err = build_goal_solve_config(&inputs, &payload, ServerLimits::default())
+            .expect_err("floor is required");
+        assert!(
+            err.starts_with("targetCvar5IncomeRatio is required"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn build_goal_solve_config_maps_secant_acceleration() {
+        let inputs = build_inputs(sample_cli()).expect("valid inputs");
+        let payload: SolveGoalPayload =
+            serde_json::from_str(r#"{"goalType": "max-income"}"#).expect("payload should parse");
+        let config = build_goal_solve_config(&inputs, &payload, ServerLimits::default())
+            .expect("config should build");
+        assert!(!config.secant_acceleration);
+
+        let payload: SolveGoalPayload =
+            serde_json::from_str(r#"{"goalType": "max-income", "secantAcceleration": true}"#)
+                .expect("payload should parse");
+        let config = build_goal_solve_config(&inputs, &payload, ServerLimits::default())
+            .expect("config should build");
+        assert!(config.secant_acceleration);
+    }
+
+    #[test]
+    fn build_goal_solve_config_accepts_the_contribution_split_goal() {
+        let inputs = build_inputs(sample_cli()).expect("valid inputs");
+        for goal in ["optimal-contribution-split", "optimalContributionSplit"] {
+            let payload: SolveGoalPayload =
+                serde_json::from_str(&format!(r#"{{"goalType": "{goal}"}}"#))
+                    .expect("payload should parse");
+            let config = build_goal_solve_config(&inputs, &payload, ServerLimits::default())
+                .expect("config should build");
+            assert_eq!(config.goal_type, GoalType::OptimalContributionSplit);
+        }
     }
 
     #[test]
@@ -1807,7 +7135,7 @@ mod tests {
         cli.max_age = 31;
         cli.horizon_age = 32;
         cli.simulations = 1;
-        cli.seed = 7;
+        cli.seed = Some(7);
         cli.isa_start = 0.0;
         cli.taxable_start = 0.0;
         cli.taxable_cost_basis_start = 0.0;
@@ -1826,7 +7154,8 @@ mod tests {
         cli.inflation_rate = 0.0;
         cli.inflation_volatility = 0.0;
         cli.taxable_return_tax_drag = 0.0;
-        cli.capital_gains_tax_rate = 0.0;
+        cli.cgt_basic_rate = 0.0;
+        cli.cgt_higher_rate = 0.0;
         cli.capital_gains_allowance = 0.0;
         cli.pension_tax_mode = CliPensionTaxMode::FlatRate;
         cli.pension_income_tax_rate = 0.0;
@@ -1846,12 +7175,14 @@ mod tests {
             goal_type: GoalType::RequiredContribution,
             target_retirement_age: 31,
             target_success_threshold: 1.0,
+            min_cvar5_income_ratio: 0.0,
             search_min: 0.0,
             search_max: 200.0,
             tolerance: 1.0,
             max_iterations: 24,
             simulations_per_iteration: 1,
             final_simulations: 1,
+            secant_acceleration: false,
         };
         let result = solve_goal(&inputs, config).expect("solver should run");
         let response = build_solve_goal_response(result);
@@ -1863,6 +7194,506 @@ mod tests {
         assert!(json.contains("\"achievedSuccessRate\""));
     }
 
+    #[tokio::test]
+    async fn solve_endpoint_finds_feasible_required_contribution() {
+        let payload: SolveGoalPayload = serde_json::from_str(
+            r#"{
+                "currentAge": 30,
+                "maxAge": 45,
+                "horizonAge": 80,
+                "isaStart": 50000,
+                "taxableStart": 0,
+                "taxableBasisStart": 0,
+                "pensionStart": 0,
+                "targetIncome": 25000,
+                "isaVol": 0,
+                "taxableVol": 0,
+                "pensionVol": 0,
+                "inflationVol": 0,
+                "seed": 3,
+                "goalType": "required-contribution",
+                "targetAge": 45,
+                "targetThreshold": 90,
+                "searchMin": 0,
+                "searchMax": 200000,
+                "tolerance": 100,
+                "simulationsPerIteration": 50,
+                "finalSimulations": 50
+            }"#,
+        )
+        .expect("payload should parse");
+
+        let response = solve_goal_post_handler(State(ServerLimits::default()), Json(payload)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body should read");
+        let json: serde_json::Value = serde_json::from_slice(&body).expect("valid json");
+
+        assert_eq!(json["feasible"], true);
+        assert_eq!(json["targetRetirementAge"], 45);
+        let solved = json["solvedValue"].as_f64().expect("solved value");
+        assert!(solved > 0.0 && solved < 200_000.0, "{solved}");
+        assert!(
+            json["iterations"]
+                .as_array()
+                .is_some_and(|it| !it.is_empty())
+        );
+    }
+
+    #[tokio::test]
+    async fn solve_endpoint_scans_for_earliest_retirement_age() {
+        let payload: SolveGoalPayload = serde_json::from_str(
+            r#"{
+                "currentAge": 30,
+                "maxAge": 60,
+                "horizonAge": 80,
+                "isaStart": 300000,
+                "taxableStart": 0,
+                "taxableBasisStart": 0,
+                "pensionStart": 0,
+                "isaContribution": 20000,
+                "targetIncome": 20000,
+                "isaVol": 0,
+                "taxableVol": 0,
+                "pensionVol": 0,
+                "inflationVol": 0,
+                "seed": 3,
+                "goalType": "earliestRetirementAge",
+                "targetThreshold": 90,
+                "finalSimulations": 20
+            }"#,
+        )
+        .expect("payload should parse");
+
+        let response = solve_goal_post_handler(State(ServerLimits::default()), Json(payload)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body should read");
+        let json: serde_json::Value = serde_json::from_slice(&body).expect("valid json");
+
+        assert_eq!(json["goalType"], "earliest-retirement-age");
+        assert_eq!(json["feasible"], true);
+        let age = json["solvedValue"].as_f64().expect("solved age");
+        assert!((30.0..=60.0).contains(&age), "{age}");
+        let iterations = json["iterations"].as_array().expect("iterations");
+        assert_eq!(
+            iterations.last().expect("at least one age")["candidateValue"].as_f64(),
+            Some(age)
+        );
+    }
+
+    #[test]
+    fn summary_only_omits_the_arrays_but_keeps_the_headline() {
+        let base = r#""currentAge": 50, "maxAge": 55, "horizonAge": 70, "simulations": 50"#;
+        let full = serde_json::to_value(run_simulate_request(
+            &api_request_from_json(&format!("{{{base}}}")).expect("valid"),
+        ))
+        .expect("serializes");
+        let summary = serde_json::to_value(run_simulate_request(
+            &api_request_from_json(&format!(r#"{{{base}, "summaryOnly": true}}"#)).expect("valid"),
+        ))
+        .expect("serializes");
+
+        for key in ["cashflowYears", "ageResults", "cashflowCandidateAge"] {
+            assert!(full.get(key).is_some(), "{key}");
+            assert!(summary.get(key).is_none(), "{key}");
+        }
+        assert!(full.get("summary").is_none());
+        for key in [
+            "selectedRetirementAge",
+            "bestRetirementAge",
+            "successThreshold",
+        ] {
+            assert_eq!(summary[key], full[key], "{key}");
+        }
+
+        let headline_age = full["selectedRetirementAge"]
+            .as_u64()
+            .or(full["bestRetirementAge"].as_u64())
+            .expect("an age");
+        let headline = full["ageResults"]
+            .as_array()
+            .expect("age results")
+            .iter()
+            .find(|result| result["retirementAge"].as_u64() == Some(headline_age))
+            .expect("headline age is a candidate");
+        for key in [
+            "retirementAge",
+            "successRate",
+            "medianTerminalPot",
+            "p10MinIncomeRatio",
+        ] {
+            assert_eq!(summary["summary"][key], headline[key], "{key}");
+        }
+    }
+
+    #[test]
+    fn cashflow_age_pins_the_traced_candidate_without_changing_the_sweep() {
+        let base = r#""currentAge": 50, "maxAge": 55, "horizonAge": 70, "simulations": 50"#;
+        let default_response =
+            run_simulate_request(&api_request_from_json(&format!("{{{base}}}")).expect("valid"));
+        let pinned_request =
+            api_request_from_json(&format!(r#"{{{base}, "cashflowAge": 53}}"#)).expect("valid");
+        let pinned_response = run_simulate_request(&pinned_request);
+
+        assert_eq!(pinned_response.cashflow_candidate_age, Some(53));
+        assert_eq!(pinned_response.cashflow_retirement_age, Some(53));
+        assert_ne!(default_response.cashflow_retirement_age, Some(53));
+        assert_ne!(
+            default_response.cashflow_years.expect("trace")[5].median_spending_total,
+            pinned_response.cashflow_years.expect("trace")[5].median_spending_total
+        );
+        assert_eq!(
+            serde_json::to_value(&default_response.age_results).expect("serializes"),
+            serde_json::to_value(&pinned_response.age_results).expect("serializes")
+        );
+
+        let err = api_request_from_json(&format!(r#"{{{base}, "cashflowAge": 56}}"#))
+            .expect_err("must stay inside the candidate range");
+        assert!(err.starts_with("cashflowAge must"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn simulate_reports_every_invalid_field_at_once() {
+        let payload: SimulatePayload = serde_json::from_str(
+            r#"{
+                "currentAge": 40,
+                "maxAge": 50,
+                "horizonAge": 45,
+                "simulations": 0,
+                "correlation": 1.5,
+                "cashStart": -1,
+                "coastRetirementAge": 30
+            }"#,
+        )
+        .expect("payload should parse");
+
+        let response = simulate_post_handler(State(ServerLimits::default()), Json(payload)).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body should read");
+        let json: serde_json::Value = serde_json::from_slice(&body).expect("valid json");
+
+        let fields: Vec<&str> = json["errors"]
+            .as_array()
+            .expect("errors list")
+            .iter()
+            .map(|error| error["field"].as_str().expect("field"))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "horizonAge",
+                "simulations",
+                "correlation",
+                "cashStart",
+                "coastRetirementAge"
+            ]
+        );
+        assert!(
+            json["errors"][0]["message"]
+                .as_str()
+                .is_some_and(|message| message.contains("--horizon-age"))
+        );
+        assert!(json["error"].as_str().is_some_and(|e| e.contains("; ")));
+    }
+
+    #[test]
+    fn payload_key_for_flag_matches_payload_field_names() {
+        assert_eq!(
+            payload_key_for_flag("pension-access-age"),
+            "pensionAccessAge"
+        );
+        assert_eq!(payload_key_for_flag("isa-growth-rate"), "isaMean");
+        assert_eq!(payload_key_for_flag("lump-expense"), "lumpExpenses");
+        assert_eq!(
+            payload_key_for_flag("taxable-dividend-yield"),
+            "taxableDividendYield"
+        );
+    }
+
+    #[test]
+    fn api_request_from_json_accepts_the_old_dividend_yield_key() {
+        for key in ["taxableDividendYield", "dividendYield"] {
+            let request =
+                api_request_from_json(&format!(r#"{{"{key}": 2.5}}"#)).expect("json should parse");
+            assert_approx(request.inputs.taxable_dividend_yield, 0.025);
+        }
+    }
+
+    #[tokio::test]
+    async fn simulate_stream_emits_progress_then_result() {
+        let payload: SimulatePayload = serde_json::from_str(
+            r#"{"currentAge": 30, "maxAge": 33, "horizonAge": 60, "simulations": 20, "seed": 5}"#,
+        )
+        .expect("payload should parse");
+
+        let response =
+            simulate_stream_handler(State(ServerLimits::default()), Query(payload)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body should read");
+        let body = String::from_utf8(body.to_vec()).expect("utf-8 body");
+
+        assert_eq!(body.matches("event: progress").count(), 4);
+        assert!(body.contains(r#""completed":4,"total":4,"percent":100.0"#));
+        let result = body
+            .split("event: result\ndata: ")
+            .nth(1)
+            .expect("result event after progress");
+        let json: serde_json::Value =
+            serde_json::from_str(result.trim_end()).expect("result is json");
+        assert_eq!(json["ageResults"].as_array().map(Vec::len), Some(4));
+
+        let payload: SimulatePayload =
+            serde_json::from_str(r#"{"targetIncome": -1}"#).expect("payload");
+        let response =
+            simulate_stream_handler(State(ServerLimits::default()), Query(payload)).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn resolve_returns_converted_inputs_without_simulating() {
+        let payload: SimulatePayload = serde_json::from_str(
+            r#"{"contributionGrowth": 3, "analysisMode": "coast-fire", "coastRetirementAge": 55}"#,
+        )
+        .expect("payload should parse");
+
+        let response = resolve_post_handler(State(ServerLimits::default()), Json(payload)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body should read");
+        let json: serde_json::Value = serde_json::from_slice(&body).expect("json body");
+
+        assert_eq!(json["mode"], "coast");
+        assert_eq!(json["coastRetirementAge"], 55);
+        assert_approx(
+            json["inputs"]["contributionGrowthRate"]
+                .as_f64()
+                .expect("rate is a number"),
+            0.03,
+        );
+        let inputs: Inputs =
+            serde_json::from_value(json["inputs"].clone()).expect("inputs round-trip");
+        assert_eq!(inputs.simulations, default_cli_for_api().simulations);
+
+        let payload: SimulatePayload =
+            serde_json::from_str(r#"{"targetIncome": -1}"#).expect("payload");
+        let response = resolve_post_handler(State(ServerLimits::default()), Json(payload)).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn distribution_bins_count_every_scenario() {
+        use tower::ServiceExt;
+        let fetch = |query: &str| {
+            router(None, ServerLimits::default()).oneshot(
+                axum::http::Request::get(format!(
+                    "/api/distribution?currentAge=30&maxAge=33&horizonAge=60&simulations=40&seed=5{query}"
+                ))
+                .body(axum::body::Body::empty())
+                .expect("valid request"),
+            )
+        };
+        let json = |response: Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .expect("body should read");
+            serde_json::from_slice::<serde_json::Value>(&body).expect("valid json")
+        };
+
+        let binned = fetch("&age=32&bins=8").await.expect("router is infallible");
+        assert_eq!(binned.status(), StatusCode::OK);
+        let binned = json(binned).await;
+        assert_eq!(binned["candidateAge"], 32);
+        assert!(binned["terminalTotals"].is_null());
+        let bins = binned["histogram"].as_array().expect("histogram");
+        assert_eq!(bins.len(), 8);
+        let total: u64 = bins
+            .iter()
+            .map(|bin| bin["count"].as_u64().expect("count"))
+            .sum();
+        assert_eq!(total, 40);
+
+        let raw = json(fetch("&age=32").await.expect("router is infallible")).await;
+        let totals: Vec<f64> = raw["terminalTotals"]
+            .as_array()
+            .expect("raw totals")
+            .iter()
+            .map(|v| v.as_f64().expect("number"))
+            .collect();
+        assert_eq!(totals.len(), 40);
+        assert!(totals.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(bins[0]["lower"].as_f64(), totals.first().copied());
+        assert_eq!(bins[7]["upper"].as_f64(), totals.last().copied());
+
+        for bad in ["", "&age=40", "&age=32&bins=0"] {
+            let response = fetch(bad).await.expect("router is infallible");
+            assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY, "{bad}");
+        }
+    }
+
+    #[test]
+    fn histogram_puts_a_constant_sample_in_the_first_bin() {
+        let bins = histogram(&[5.0, 5.0, 5.0], 4);
+        let counts: Vec<u32> = bins.iter().map(|bin| bin.count).collect();
+        assert_eq!(counts, vec![3, 0, 0, 0]);
+        assert!(histogram(&[], 4).is_empty());
+    }
+
+    #[tokio::test]
+    async fn cashflow_csv_has_a_header_and_one_row_per_year() {
+        let payload: SimulatePayload = serde_json::from_str(
+            r#"{"currentAge": 30, "maxAge": 33, "horizonAge": 60, "simulations": 20, "seed": 5}"#,
+        )
+        .expect("payload should parse");
+
+        let response = cashflow_csv_handler(State(ServerLimits::default()), Query(payload)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/csv; charset=utf-8"
+        );
+        assert!(
+            response.headers()[header::CONTENT_DISPOSITION]
+                .to_str()
+                .expect("ascii header")
+                .starts_with("attachment")
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body should read");
+        let body = String::from_utf8(body.to_vec()).expect("utf-8 body");
+
+        let mut lines = body.lines();
+        let header_row: Vec<&str> = lines.next().expect("header").split(',').collect();
+        assert_eq!(header_row[0], "age");
+        assert!(header_row.contains(&"medianEndTotal"));
+        let rows: Vec<&str> = lines.collect();
+        assert_eq!(rows.len(), 60 - 30);
+        assert!(
+            rows.iter()
+                .all(|row| row.split(',').count() == header_row.len())
+        );
+        assert!(rows[0].starts_with("30,"));
+    }
+
+    #[tokio::test]
+    async fn compare_endpoint_runs_each_strategy_on_the_same_draws() {
+        let payload: ComparePayload = serde_json::from_str(
+            r#"{
+                "currentAge": 30,
+                "maxAge": 34,
+                "horizonAge": 60,
+                "simulations": 40,
+                "seed": 11,
+                "strategies": ["guardrails", "vpw", "guardrails"]
+            }"#,
+        )
+        .expect("payload should parse");
+
+        let response = compare_post_handler(State(ServerLimits::default()), Json(payload)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body should read");
+        let json: serde_json::Value = serde_json::from_slice(&body).expect("valid json");
+
+        let results = json.as_object().expect("strategy map");
+        assert_eq!(results.len(), 2);
+        for (key, policy) in [("guardrails", "guardrails"), ("vpw", "vpw")] {
+            let result = &results[key];
+            assert_eq!(result["withdrawalPolicy"], policy);
+            assert!(result["bestRetirementAge"].is_u64());
+            assert_eq!(result["ageResults"].as_array().map(Vec::len), Some(5));
+        }
+        // Same seed, same draws: the pre-retirement pots cannot depend on the strategy.
+        assert_eq!(
+            results["guardrails"]["ageResults"][0]["medianRetirementPot"],
+            results["vpw"]["ageResults"][0]["medianRetirementPot"]
+        );
+
+        let too_many = format!(
+            r#"{{"strategies": {:?}}}"#,
+            vec!["vpw"; CliWithdrawalStrategy::value_variants().len() + 1]
+        );
+        for body in [r#"{"strategies": []}"#, too_many.as_str()] {
+            let payload: ComparePayload = serde_json::from_str(body).expect("payload");
+            let response =
+                compare_post_handler(State(ServerLimits::default()), Json(payload)).await;
+            assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        }
+    }
+
+    #[tokio::test]
+    async fn sensitivity_endpoint_sweeps_target_income() {
+        let payload: SensitivityPayload = serde_json::from_str(
+            r#"{
+                "currentAge": 40,
+                "maxAge": 50,
+                "horizonAge": 90,
+                "simulations": 200,
+                "seed": 11,
+                "ageInvariantDraws": true,
+                "withdrawalPolicy": "fixed-real",
+                "parameter": "targetIncome",
+                "values": [20000, 35000, 20000, 50000]
+            }"#,
+        )
+        .expect("payload should parse");
+
+        let response =
+            sensitivity_post_handler(State(ServerLimits::default()), Json(payload)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body should read");
+        let json: serde_json::Value = serde_json::from_slice(&body).expect("valid json");
+
+        let points = json.as_array().expect("one point per distinct value");
+        assert_eq!(points.len(), 3);
+        assert_eq!(points[0]["value"], 20_000.0);
+        let rates: Vec<f64> = points
+            .iter()
+            .map(|point| point["bestSuccessRate"].as_f64().expect("success rate"))
+            .collect();
+        assert!(rates[0] > rates[2], "sweep should move success: {rates:?}");
+        assert!(
+            rates.windows(2).all(|pair| pair[1] <= pair[0]),
+            "success should fall as income rises: {rates:?}"
+        );
+
+        let too_many = format!(
+            r#"{{"parameter": "targetIncome", "values": {:?}}}"#,
+            (0..=MAX_SENSITIVITY_VALUES)
+                .map(|i| 20_000.0 + i as f64)
+                .collect::<Vec<_>>()
+        );
+        for body in [
+            r#"{"parameter": "notAField", "values": [1]}"#,
+            r#"{"parameter": "targetIncome", "values": []}"#,
+            too_many.as_str(),
+        ] {
+            let payload: SensitivityPayload = serde_json::from_str(body).expect("payload");
+            let response =
+                sensitivity_post_handler(State(ServerLimits::default()), Json(payload)).await;
+            assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        }
+    }
+
+    #[tokio::test]
+    async fn solve_endpoint_rejects_invalid_goal_config() {
+        let payload: SolveGoalPayload =
+            serde_json::from_str(r#"{"searchMin": 10, "searchMax": 5}"#).expect("payload");
+        let response = solve_goal_post_handler(State(ServerLimits::default()), Json(payload)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
     #[test]
     fn golden_snapshot_retirement_sweep_json() {
         let mut cli = sample_cli();
@@ -1870,14 +7701,15 @@ mod tests {
         cli.max_age = 34;
         cli.horizon_age = 45;
         cli.simulations = 80;
-        cli.seed = 7;
+        cli.threads = 1;
+        cli.seed = Some(7);
         cli.taxable_return_volatility = Some(10.0);
         cli.pension_return_volatility = 10.0;
         cli.inflation_volatility = 0.8;
         cli.withdrawal_strategy = CliWithdrawalStrategy::Guardrails;
 
         let inputs = build_inputs(cli).expect("valid inputs");
-        let model = run_model(&inputs);
+        let model = run_model(&inputs).expect("inputs are valid");
         let trace_index = model.selected_index.unwrap_or(model.best_index);
         let trace_candidate_age = model.age_results[trace_index].retirement_age;
         let cashflow = run_yearly_cashflow_trace(
@@ -1897,12 +7729,9 @@ mod tests {
             &model,
             AnalysisMode::RetirementSweep,
             None,
-            cashflow_response,
-        );
-        let json = format!(
-            "{}\n",
-            serde_json::to_string(&response).expect("response should serialize")
+            Some(cashflow_response),
         );
+        let json = golden_json(response);
 
         assert_golden_snapshot("tests/golden/retirement_sweep_guardrails.json", &json);
     }
@@ -1914,7 +7743,8 @@ mod tests {
         cli.max_age = 36;
         cli.horizon_age = 50;
         cli.simulations = 80;
-        cli.seed = 11;
+        cli.threads = 1;
+        cli.seed = Some(11);
         cli.target_annual_income = 45_000.0;
         cli.withdrawal_strategy = CliWithdrawalStrategy::Vpw;
         cli.vpw_expected_real_return = 3.0;
@@ -1924,7 +7754,7 @@ mod tests {
 
         let inputs = build_inputs(cli).expect("valid inputs");
         let retirement_age = 35;
-        let model = run_coast_model(&inputs, retirement_age);
+        let model = run_coast_model(&inputs, retirement_age).expect("inputs are valid");
         let trace_index = model.selected_index.unwrap_or(model.best_index);
         let trace_candidate_age = model.age_results[trace_index].retirement_age;
         let cashflow = run_yearly_cashflow_trace(
@@ -1944,13 +7774,346 @@ mod tests {
             &model,
             AnalysisMode::CoastFire,
             Some(retirement_age),
-            cashflow_response,
-        );
-        let json = format!(
-            "{}\n",
-            serde_json::to_string(&response).expect("response should serialize")
+            Some(cashflow_response),
         );
+        let json = golden_json(response);
 
         assert_golden_snapshot("tests/golden/coast_fire_vpw.json", &json);
     }
+
+    async fn get_route(uri: &str) -> Response {
+        get_route_with_encoding(uri, "identity").await
+    }
+
+    async fn get_route_with_encoding(uri: &str, accept_encoding: &str) -> Response {
+        use tower::ServiceExt;
+        router(None, ServerLimits::default())
+            .oneshot(
+                axum::http::Request::get(uri)
+                    .header(header::ACCEPT_ENCODING, accept_encoding)
+                    .body(axum::body::Body::empty())
+                    .expect("valid request"),
+            )
+            .await
+            .expect("router is infallible")
+    }
+
+    #[tokio::test]
+    async fn healthz_route_reports_ok() {
+        let response = get_route("/healthz").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-store");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body should read");
+        let json: serde_json::Value = serde_json::from_slice(&body).expect("valid json");
+        assert_eq!(json, serde_json::json!({ "status": "ok" }));
+    }
+
+    #[tokio::test]
+    async fn version_route_reports_the_crate_version() {
+        let response = get_route("/version").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body should read");
+        let json: serde_json::Value = serde_json::from_slice(&body).expect("valid json");
+        assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+        assert!(json["gitHash"].is_string() || json["gitHash"].is_null());
+    }
+
+    async fn preflight(cors_origins: &[&str], origin: &str) -> Response {
+        use tower::ServiceExt;
+        let origins: Vec<String> = cors_origins.iter().map(|o| o.to_string()).collect();
+        router(
+            cors_layer(&origins).expect("valid origins"),
+            ServerLimits::default(),
+        )
+        .oneshot(
+            axum::http::Request::options("/api/simulate")
+                .header(header::ORIGIN, origin)
+                .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+                .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "content-type")
+                .body(axum::body::Body::empty())
+                .expect("valid request"),
+        )
+        .await
+        .expect("router is infallible")
+    }
+
+    #[tokio::test]
+    async fn cors_preflight_allows_only_listed_origins() {
+        let allowed = preflight(&["http://localhost:5173"], "http://localhost:5173").await;
+        assert_eq!(allowed.status(), StatusCode::OK);
+        assert_eq!(
+            allowed.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "http://localhost:5173"
+        );
+
+        let other = preflight(&["http://localhost:5173"], "http://evil.example").await;
+        assert!(
+            !other
+                .headers()
+                .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        );
+
+        let same_origin_only = preflight(&[], "http://localhost:5173").await;
+        assert!(
+            !same_origin_only
+                .headers()
+                .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        );
+        assert!(cors_layer(&["bad\norigin".to_string()]).is_err());
+    }
+
+    #[tokio::test]
+    async fn simulate_route_gzips_when_the_client_accepts_it() {
+        use std::io::Read;
+        use tower::ServiceExt;
+
+        let request = |accept_encoding: Option<&str>| {
+            let mut builder = axum::http::Request::post("/api/simulate")
+                .header(header::CONTENT_TYPE, "application/json");
+            if let Some(encoding) = accept_encoding {
+                builder = builder.header(header::ACCEPT_ENCODING, encoding);
+            }
+            builder
+                .body(axum::body::Body::from(
+                    r#"{"currentAge": 40, "maxAge": 50, "horizonAge": 90, "simulations": 50}"#,
+                ))
+                .expect("valid request")
+        };
+
+        let response = router(None, ServerLimits::default())
+            .oneshot(request(Some("gzip")))
+            .await
+            .expect("router is infallible");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-store");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body should read");
+        let mut json = String::new();
+        flate2::read::GzDecoder::new(&body[..])
+            .read_to_string(&mut json)
+            .expect("valid gzip");
+        assert!(json.len() > body.len());
+        let json: serde_json::Value = serde_json::from_str(&json).expect("valid json");
+        assert!(json["ageResults"].as_array().is_some_and(|a| !a.is_empty()));
+
+        let plain = router(None, ServerLimits::default())
+            .oneshot(request(None))
+            .await
+            .expect("router is infallible");
+        assert!(!plain.headers().contains_key(header::CONTENT_ENCODING));
+
+        let app_js = get_route_with_encoding("/app.js", "gzip").await;
+        assert_eq!(app_js.headers()[header::CONTENT_ENCODING], "gzip");
+        assert!(
+            app_js.headers()[header::CONTENT_TYPE]
+                .to_str()
+                .is_ok_and(|t| t.starts_with("application/javascript"))
+        );
+    }
+
+    #[tokio::test]
+    async fn simulate_rejects_requests_over_the_server_limits() {
+        let post = |json: &'static str| {
+            let payload: SimulatePayload =
+                serde_json::from_str(json).expect("payload should parse");
+            simulate_post_handler(State(ServerLimits::default()), Json(payload))
+        };
+
+        let response = post(r#"{"simulations": 4000000000}"#).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body should read");
+        let json: serde_json::Value = serde_json::from_slice(&body).expect("valid json");
+        let error = json["error"].as_str().expect("error message");
+        assert!(error.contains("simulations 4000000000 exceeds"), "{error}");
+        assert!(error.contains("--max-simulations"), "{error}");
+
+        let response = post(r#"{"horizonAge": 1000000}"#).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response =
+            post(r#"{"currentAge": 50, "maxAge": 55, "horizonAge": 90, "simulations": 20}"#).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn simulation_limit_comes_from_the_router_state() {
+        use tower::ServiceExt;
+        let request = || {
+            axum::http::Request::get("/api/simulate?currentAge=50&maxAge=55&simulations=20")
+                .body(axum::body::Body::empty())
+                .expect("valid request")
+        };
+
+        let strict = router(
+            None,
+            ServerLimits {
+                max_simulations: 10,
+            },
+        );
+        let response = strict
+            .oneshot(request())
+            .await
+            .expect("router is infallible");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = router(None, ServerLimits::default())
+            .oneshot(request())
+            .await
+            .expect("router is infallible");
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn solve_rejects_solver_simulations_over_the_server_limit() {
+        let payload: SolveGoalPayload = serde_json::from_str(r#"{"finalSimulations": 4000000000}"#)
+            .expect("payload should parse");
+        let response = solve_goal_post_handler(State(ServerLimits::default()), Json(payload)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn oversized_post_bodies_are_refused() {
+        use tower::ServiceExt;
+        let padding = " ".repeat(MAX_REQUEST_BODY_BYTES + 1);
+        let response = router(None, ServerLimits::default())
+            .oneshot(
+                axum::http::Request::post("/api/simulate")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(axum::body::Body::from(format!("{{}}{padding}")))
+                    .expect("valid request"),
+            )
+            .await
+            .expect("router is infallible");
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn scenarios_round_trip_through_store_and_fetch() {
+        use tower::ServiceExt;
+        let app = router(None, ServerLimits::default());
+        let payload = serde_json::json!({
+            "currentAge": 35,
+            "targetIncome": 32000.0,
+            "withdrawalOrder": "isa-first",
+            "lumpExpenses": [{ "age": 60, "amount": 15000.0 }],
+            "notAField": "x".repeat(1_000)
+        });
+
+        let stored = app
+            .clone()
+            .oneshot(
+                axum::http::Request::post("/api/scenarios")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(axum::body::Body::from(payload.to_string()))
+                    .expect("valid request"),
+            )
+            .await
+            .expect("router is infallible");
+        assert_eq!(stored.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(stored.into_body(), usize::MAX)
+            .await
+            .expect("body should read");
+        let json: serde_json::Value = serde_json::from_slice(&body).expect("valid json");
+        let id = json["id"].as_str().expect("id").to_string();
+        assert_eq!(id.len(), 10);
+
+        let fetch = |uri: String| {
+            app.clone().oneshot(
+                axum::http::Request::get(uri)
+                    .body(axum::body::Body::empty())
+                    .expect("valid request"),
+            )
+        };
+        let loaded = fetch(format!("/api/scenarios/{id}"))
+            .await
+            .expect("router is infallible");
+        assert_eq!(loaded.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(loaded.into_body(), usize::MAX)
+            .await
+            .expect("body should read");
+        let json: serde_json::Value = serde_json::from_slice(&body).expect("valid json");
+        let mut known = payload.clone();
+        known
+            .as_object_mut()
+            .expect("object payload")
+            .remove("notAField");
+        assert_eq!(json, known);
+
+        let missing = fetch("/api/scenarios/nope".to_string())
+            .await
+            .expect("router is infallible");
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn scenario_store_drops_the_oldest_past_capacity() {
+        let store = ScenarioStore::default();
+        let payload = |json: serde_json::Value| -> SimulatePayload {
+            serde_json::from_value(json).expect("payload should parse")
+        };
+        let first = store.insert(&payload(serde_json::json!({ "currentAge": 30 })));
+        let ids: Vec<_> = (0..MAX_STORED_SCENARIOS)
+            .map(|_| store.insert(&SimulatePayload::default()))
+            .collect();
+        assert!(store.get(&first).is_none());
+        assert!(ids.iter().all(|id| store.get(id).is_some()));
+        assert_ne!(ids[0], ids[1]);
+
+        // Large payloads are evicted by total size well before the count cap.
+        let store = ScenarioStore::default();
+        let expenses: Vec<_> = (0..20_000)
+            .map(|i| serde_json::json!({ "age": 60 + i % 40, "amount": 12_345.678 }))
+            .collect();
+        let large = payload(serde_json::json!({ "lumpExpenses": expenses }));
+        let per_payload = stored_payload_json(&large).len();
+        let fits = MAX_STORED_SCENARIO_BYTES / per_payload;
+        assert!(fits < MAX_STORED_SCENARIOS);
+        let ids: Vec<_> = (0..=fits).map(|_| store.insert(&large)).collect();
+        assert!(store.get(&ids[0]).is_none());
+        assert!(ids[1..].iter().all(|id| store.get(id).is_some()));
+    }
+
+    #[test]
+    fn simulate_meta_hash_tracks_the_resolved_inputs() {
+        let meta_for = |json: &str| {
+            let request = api_request_from_json(json).expect("valid payload");
+            let response = run_simulate_request(&request);
+            serde_json::to_value(&response).expect("response should serialize")["meta"].clone()
+        };
+        let base =
+            r#"{"currentAge": 50, "maxAge": 52, "horizonAge": 70, "simulations": 10, "seed": 9}"#;
+
+        let first = meta_for(base);
+        assert_eq!(first, meta_for(base));
+        assert_eq!(first["seed"], 9);
+        assert_eq!(first["simulations"], 10);
+        assert_eq!(first["horizonAge"], 70);
+        assert_eq!(first["engineVersion"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(first["inputsHash"].as_str().map(str::len), Some(16));
+
+        // Spelling out a default resolves to the same inputs.
+        let default_order = r#"{"currentAge": 50, "maxAge": 52, "horizonAge": 70, "simulations": 10, "seed": 9, "withdrawalOrder": "pro-rata"}"#;
+        assert_eq!(meta_for(default_order)["inputsHash"], first["inputsHash"]);
+
+        for changed in [
+            r#"{"currentAge": 50, "maxAge": 52, "horizonAge": 70, "simulations": 10, "seed": 10}"#,
+            r#"{"currentAge": 50, "maxAge": 52, "horizonAge": 70, "simulations": 10, "seed": 9, "targetIncome": 30001}"#,
+            r#"{"currentAge": 50, "maxAge": 52, "horizonAge": 70, "simulations": 10, "seed": 9, "isaFeeRate": 0.11}"#,
+        ] {
+            assert_ne!(
+                meta_for(changed)["inputsHash"],
+                first["inputsHash"],
+                "{changed}"
+            );
+        }
+    }
 }
@@ -0,0 +1,466 @@
+use super::types::{
+    Debt, Inputs, MarketModel, PensionTaxMode, ReturnDistribution, SelectionConfidence,
+    SuccessCriterion, WithdrawalOrder, WithdrawalStrategy,
+};
+use super::validation::{ValidationError, validate_inputs};
+
+/// Defaults match the CLI flags: a 30-year-old UK saver on 2025/26 rest-of-UK tax bands.
+impl Default for Inputs {
+    fn default() -> Self {
+        Self {
+            current_age: 30,
+            pension_access_age: 57,
+            isa_start: 100_000.0,
+            taxable_start: 15_000.0,
+            taxable_cost_basis_start: 12_000.0,
+            pension_start: 200_000.0,
+            household: false,
+            partner_pension_start: 0.0,
+            partner_pension_access_age: 57,
+            partner_personal_allowance: 12_570.0,
+            marriage_allowance_transfer: 0.0,
+            cash_start: 0.0,
+            bond_ladder_start: 0.0,
+            isa_annual_contribution: 30_000.0,
+            isa_annual_contribution_limit: 20_000.0,
+            lisa_start: 0.0,
+            lisa_annual_contribution: 0.0,
+            lisa_access_age: 60,
+            taxable_annual_contribution: 5_000.0,
+            pension_annual_contribution: 0.0,
+            employer_match_rate: 0.0,
+            employer_match_cap: None,
+            pension_annual_allowance: 60_000.0,
+            pension_overflow_to_taxable: true,
+            contribution_growth_rate: 0.0,
+            contributions_track_inflation: false,
+            coast_contribution_fraction: 0.0,
+            pre_retirement_earnings: 0.0,
+            isa_return_mean: 0.08,
+            isa_return_vol: 0.12,
+            taxable_return_mean: 0.08,
+            taxable_return_vol: 0.12,
+            pension_return_mean: 0.08,
+            pension_return_vol: 0.12,
+            return_correlation: 0.8,
+            isa_taxable_correlation: 1.0,
+            taxable_pension_correlation: None,
+            return_factor_betas: None,
+            return_distribution: ReturnDistribution::Normal,
+            market_model: MarketModel::Parametric,
+            bootstrap_block_years: 1,
+            market_burn_in_years: 0,
+            cgt_basic_rate: 0.18,
+            cgt_higher_rate: 0.24,
+            capital_gains_allowance: 3_000.0,
+            taxable_return_tax_drag: 0.01,
+            taxable_dividend_yield: 0.0,
+            dividend_allowance: 500.0,
+            dividend_basic_rate: 0.0875,
+            dividend_higher_rate: 0.3375,
+            dividend_additional_rate: 0.3935,
+            isa_fee_rate: 0.0,
+            taxable_fee_rate: 0.0,
+            pension_fee_rate: 0.0,
+            glide_start_equity: 1.0,
+            glide_end_equity: 1.0,
+            glide_end_age: None,
+            pension_tax_mode: PensionTaxMode::UkBands,
+            pension_flat_tax_rate: 0.2,
+            pension_tax_free_pct: 0.25,
+            uk_personal_allowance: 12_570.0,
+            uk_basic_rate_limit: 50_270.0,
+            uk_higher_rate_limit: 125_140.0,
+            uk_basic_rate: 0.2,
+            uk_higher_rate: 0.4,
+            uk_additional_rate: 0.45,
+            uk_allowance_taper_start: 100_000.0,
+            uk_allowance_taper_end: 125_140.0,
+            thresholds_frozen_until_age: None,
+            scottish_starter_rate_limit: 15_397.0,
+            scottish_basic_rate_limit: 27_491.0,
+            scottish_intermediate_rate_limit: 43_662.0,
+            scottish_higher_rate_limit: 75_000.0,
+            scottish_advanced_rate_limit: 125_140.0,
+            scottish_starter_rate: 0.19,
+            scottish_basic_rate: 0.2,
+            scottish_intermediate_rate: 0.21,
+            scottish_higher_rate: 0.42,
+            scottish_advanced_rate: 0.45,
+            scottish_top_rate: 0.48,
+            state_pension_start_age: 67,
+            state_pension_annual_income: 0.0,
+            state_pension_real_growth: 0.0,
+            state_pension_growth_before_start: false,
+            state_pension_deferral_years: 0,
+            // Written as the CLI converts it; 5.8 / 100 is not bit-identical to 0.058.
+            state_pension_deferral_uplift_rate: 5.8 / 100.0,
+            db_pension_start_age: 60,
+            db_pension_annual_income: 0.0,
+            annuity_purchase_age: None,
+            annuity_purchase_amount: 0.0,
+            annuity_rate: 0.05,
+            conversion_ladder_annual_amount: 0.0,
+            conversion_ladder_start_age: 57,
+            conversion_ladder_end_age: 67,
+            rental_income_start_age: 0,
+            rental_annual_income: 0.0,
+            rental_growth_rate: 0.0,
+            part_time_annual_income: 0.0,
+            part_time_start_age: 0,
+            part_time_end_age: 0,
+            inflation_mean: 0.025,
+            inflation_vol: 0.01,
+            inflation_return_correlation: 0.0,
+            return_clamp_min: -0.95,
+            return_clamp_max: 2.5,
+            inflation_clamp_min: -0.03,
+            inflation_clamp_max: 0.2,
+            target_annual_income: 50_000.0,
+            essential_annual_income: 0.0,
+            spending_decline_rate: 0.0,
+            care_cost_age: 85,
+            care_cost_uplift: 0.0,
+            active_phase_end_age: 75,
+            active_phase_uplift: 0.0,
+            late_phase_start_age: 85,
+            late_phase_uplift: 0.0,
+            debts: Vec::new(),
+            mortgage_annual_payment: 0.0,
+            mortgage_end_age: None,
+            mortgage_balance: 0.0,
+            mortgage_rate: 0.05,
+            mortgage_term_years: 25,
+            mortgage_overpayments: Vec::new(),
+            lump_expenses: Vec::new(),
+            max_retirement_age: 70,
+            horizon_age: 90,
+            bequest_target: 0.0,
+            success_criterion: SuccessCriterion::EssentialFloorMet,
+            simulations: 3_000,
+            threads: 0,
+            success_threshold: 0.9,
+            selection_confidence: SelectionConfidence::PointEstimate,
+            risk_aversion: None,
+            utility_discount_rate: 0.0,
+            utility_spending_floor: 1_000.0,
+            seed: 42,
+            age_invariant_draws: false,
+            stratified_inflation: false,
+            bad_year_threshold: -0.05,
+            good_year_threshold: 0.1,
+            bad_year_cut: 0.1,
+            good_year_raise: 0.05,
+            min_income_floor: 0.8,
+            max_income_ceiling: 2.0,
+            min_real_spending_floor: 0.0,
+            withdrawal_strategy: WithdrawalStrategy::Guardrails,
+            gk_lower_guardrail: 0.8,
+            gk_upper_guardrail: 1.2,
+            gk_adjustment: 0.1,
+            vpw_expected_real_return: 0.035,
+            constant_withdrawal_rate: 0.04,
+            cape_a: 0.0175,
+            cape_b: 0.5,
+            cape_start: 20.0,
+            cape_long_run_mean: 20.0,
+            cape_reversion_speed: 0.1,
+            floor_upside_capture: 0.5,
+            bucket_target_years: 2.0,
+            good_year_extra_buffer_withdrawal: 0.1,
+            min_cash_months: 0.0,
+            cash_growth_rate: 0.01,
+            bond_ladder_yield: 0.03,
+            bond_ladder_yield_vol: 0.0,
+            bond_ladder_inflation_correlation: 0.0,
+            bond_ladder_years: 10,
+            bond_ladder_reinvest: false,
+            bond_ladder_target_at_retirement: 0.0,
+            post_access_withdrawal_order: WithdrawalOrder::ProRata,
+            pre_access_withdrawal_order: None,
+        }
+    }
+}
+
+impl Inputs {
+    pub fn builder() -> InputsBuilder {
+        InputsBuilder::default()
+    }
+
+    /// Parses inputs saved with `to_json`. Missing fields take their defaults and the
+    /// result is not validated; run `validate_inputs` before simulating untrusted files.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("inputs hold only plain data")
+    }
+
+    /// `debts` plus the `mortgage_annual_payment` shorthand, once it has an end age.
+    pub fn all_debts(&self) -> impl Iterator<Item = Debt> + '_ {
+        let shorthand = self
+            .mortgage_end_age
+            .filter(|_| self.mortgage_annual_payment > 0.0)
+            .map(|end_age| Debt {
+                annual_payment: self.mortgage_annual_payment,
+                end_age,
+            });
+        self.debts.iter().copied().chain(shorthand)
+    }
+}
+
+/// Chainable setters over `Inputs::default()`. `build` runs the same checks as the CLI
+/// and API, so library callers cannot hand the engine inputs those front ends would reject.
+#[derive(Debug, Clone, Default)]
+pub struct InputsBuilder {
+    inputs: Inputs,
+}
+
+macro_rules! setters {
+    ($($field:ident: $ty:ty),* $(,)?) => {
+        $(
+            pub fn $field(mut self, value: $ty) -> Self {
+                self.inputs.$field = value;
+                self
+            }
+        )*
+    };
+}
+
+impl InputsBuilder {
+    setters! {
+        current_age: u32,
+        pension_access_age: u32,
+        isa_start: f64,
+        taxable_start: f64,
+        taxable_cost_basis_start: f64,
+        pension_start: f64,
+        household: bool,
+        partner_pension_start: f64,
+        partner_pension_access_age: u32,
+        partner_personal_allowance: f64,
+        marriage_allowance_transfer: f64,
+        cash_start: f64,
+        bond_ladder_start: f64,
+        isa_annual_contribution: f64,
+        isa_annual_contribution_limit: f64,
+        lisa_start: f64,
+        lisa_annual_contribution: f64,
+        lisa_access_age: u32,
+        taxable_annual_contribution: f64,
+        pension_annual_contribution: f64,
+        employer_match_rate: f64,
+        employer_match_cap: Option<f64>,
+        pension_annual_allowance: f64,
+        pension_overflow_to_taxable: bool,
+        contribution_growth_rate: f64,
+        contributions_track_inflation: bool,
+        coast_contribution_fraction: f64,
+        pre_retirement_earnings: f64,
+        isa_return_mean: f64,
+        isa_return_vol: f64,
+        taxable_return_mean: f64,
+        taxable_return_vol: f64,
+        pension_return_mean: f64,
+        pension_return_vol: f64,
+        return_correlation: f64,
+        isa_taxable_correlation: f64,
+        taxable_pension_correlation: Option<f64>,
+        return_factor_betas: Option<[f64; 3]>,
+        return_distribution: ReturnDistribution,
+        market_model: MarketModel,
+        bootstrap_block_years: u32,
+        market_burn_in_years: u32,
+        cgt_basic_rate: f64,
+        cgt_higher_rate: f64,
+        capital_gains_allowance: f64,
+        taxable_return_tax_drag: f64,
+        taxable_dividend_yield: f64,
+        dividend_allowance: f64,
+        dividend_basic_rate: f64,
+        dividend_higher_rate: f64,
+        dividend_additional_rate: f64,
+        isa_fee_rate: f64,
+        taxable_fee_rate: f64,
+        pension_fee_rate: f64,
+        glide_start_equity: f64,
+        glide_end_equity: f64,
+        glide_end_age: Option<u32>,
+        pension_tax_mode: PensionTaxMode,
+        pension_flat_tax_rate: f64,
+        pension_tax_free_pct: f64,
+        uk_personal_allowance: f64,
+        uk_basic_rate_limit: f64,
+        uk_higher_rate_limit: f64,
+        uk_basic_rate: f64,
+        uk_higher_rate: f64,
+        uk_additional_rate: f64,
+        uk_allowance_taper_start: f64,
+        uk_allowance_taper_end: f64,
+        thresholds_frozen_until_age: Option<u32>,
+        scottish_starter_rate_limit: f64,
+        scottish_basic_rate_limit: f64,
+        scottish_intermediate_rate_limit: f64,
+        scottish_higher_rate_limit: f64,
+        scottish_advanced_rate_limit: f64,
+        scottish_starter_rate: f64,
+        scottish_basic_rate: f64,
+        scottish_intermediate_rate: f64,
+        scottish_higher_rate: f64,
+        scottish_advanced_rate: f64,
+        scottish_top_rate: f64,
+        state_pension_start_age: u32,
+        state_pension_annual_income: f64,
+        state_pension_real_growth: f64,
+        state_pension_growth_before_start: bool,
+        state_pension_deferral_years: u32,
+        state_pension_deferral_uplift_rate: f64,
+        db_pension_start_age: u32,
+        db_pension_annual_income: f64,
+        annuity_purchase_age: Option<u32>,
+        annuity_purchase_amount: f64,
+        annuity_rate: f64,
+        conversion_ladder_annual_amount: f64,
+        conversion_ladder_start_age: u32,
+        conversion_ladder_end_age: u32,
+        rental_income_start_age: u32,
+        rental_annual_income: f64,
+        rental_growth_rate: f64,
+        part_time_annual_income: f64,
+        part_time_start_age: u32,
+        part_time_end_age: u32,
+        inflation_mean: f64,
+        inflation_vol: f64,
+        inflation_return_correlation: f64,
+        return_clamp_min: f64,
+        return_clamp_max: f64,
+        inflation_clamp_min: f64,
+        inflation_clamp_max: f64,
+        target_annual_income: f64,
+        essential_annual_income: f64,
+        spending_decline_rate: f64,
+        care_cost_age: u32,
+        care_cost_uplift: f64,
+        active_phase_end_age: u32,
+        active_phase_uplift: f64,
+        late_phase_start_age: u32,
+        late_phase_uplift: f64,
+        debts: Vec<Debt>,
+        mortgage_annual_payment: f64,
+        mortgage_end_age: Option<u32>,
+        mortgage_balance: f64,
+        mortgage_rate: f64,
+        mortgage_term_years: u32,
+        mortgage_overpayments: Vec<(u32, f64)>,
+        lump_expenses: Vec<(u32, f64)>,
+        max_retirement_age: u32,
+        horizon_age: u32,
+        bequest_target: f64,
+        success_criterion: SuccessCriterion,
+        simulations: u32,
+        threads: usize,
+        success_threshold: f64,
+        selection_confidence: SelectionConfidence,
+        risk_aversion: Option<f64>,
+        utility_discount_rate: f64,
+        utility_spending_floor: f64,
+        seed: u64,
+        age_invariant_draws: bool,
+        stratified_inflation: bool,
+        bad_year_threshold: f64,
+        good_year_threshold: f64,
+        bad_year_cut: f64,
+        good_year_raise: f64,
+        min_income_floor: f64,
+        max_income_ceiling: f64,
+        min_real_spending_floor: f64,
+        withdrawal_strategy: WithdrawalStrategy,
+        gk_lower_guardrail: f64,
+        gk_upper_guardrail: f64,
+        gk_adjustment: f64,
+        vpw_expected_real_return: f64,
+        constant_withdrawal_rate: f64,
+        cape_a: f64,
+        cape_b: f64,
+        cape_start: f64,
+        cape_long_run_mean: f64,
+        cape_reversion_speed: f64,
+        floor_upside_capture: f64,
+        bucket_target_years: f64,
+        good_year_extra_buffer_withdrawal: f64,
+        min_cash_months: f64,
+        cash_growth_rate: f64,
+        bond_ladder_yield: f64,
+        bond_ladder_yield_vol: f64,
+        bond_ladder_inflation_correlation: f64,
+        bond_ladder_years: u32,
+        bond_ladder_reinvest: bool,
+        bond_ladder_target_at_retirement: f64,
+        post_access_withdrawal_order: WithdrawalOrder,
+        pre_access_withdrawal_order: Option<WithdrawalOrder>,
+    }
+
+    pub fn build(self) -> Result<Inputs, Vec<ValidationError>> {
+        validate_inputs(&self.inputs)?;
+        Ok(self.inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_without_overrides_builds_the_defaults() {
+        assert_eq!(InputsBuilder::default().build(), Ok(Inputs::default()));
+    }
+
+    #[test]
+    fn builder_applies_overrides_and_validates_them() {
+        let inputs = Inputs::builder()
+            .current_age(40)
+            .withdrawal_strategy(WithdrawalStrategy::Vpw)
+            .build()
+            .expect("valid overrides should build");
+        assert_eq!(inputs.current_age, 40);
+        assert_eq!(inputs.withdrawal_strategy, WithdrawalStrategy::Vpw);
+
+        let errors = Inputs::builder()
+            .horizon_age(65)
+            .simulations(0)
+            .cgt_basic_rate(1.5)
+            .build()
+            .expect_err("out-of-range inputs should be rejected");
+        let fields: Vec<_> = errors.iter().map(|error| error.field.as_str()).collect();
+        assert_eq!(fields, ["horizon-age", "simulations", "cgt-basic-rate"]);
+    }
+
+    #[test]
+    fn inputs_round_trip_through_json() {
+        let inputs = Inputs::builder()
+            .return_factor_betas(Some([0.9, 0.8, 0.7]))
+            .market_model(MarketModel::Garch {
+                alpha: 0.1,
+                beta: 0.85,
+                long_run_vol: 0.16,
+            })
+            .return_distribution(ReturnDistribution::StudentT { dof: 5.0 })
+            .lump_expenses(vec![(45, 20_000.0), (60, 0.1 + 0.2)])
+            .pre_access_withdrawal_order(Some(WithdrawalOrder::HarvestCgtAllowance))
+            .withdrawal_strategy(WithdrawalStrategy::CapeBased)
+            .seed(u64::MAX)
+            .build()
+            .expect("valid inputs");
+
+        let json = inputs.to_json();
+        assert!(json.contains("\"longRunVol\": 0.16"));
+        assert!(json.contains("\"preAccessWithdrawalOrder\": \"harvestCgtAllowance\""));
+        assert_eq!(Inputs::from_json(&json).expect("round trip"), inputs);
+
+        let partial = Inputs::from_json(r#"{"currentAge": 41}"#).expect("partial json");
+        assert_eq!(partial.current_age, 41);
+        assert_eq!(partial.horizon_age, Inputs::default().horizon_age);
+        assert!(Inputs::from_json(r#"{"currentAge": "41"}"#).is_err());
+    }
+}
@@ -1,10 +1,37 @@
 use std::f64::consts::PI;
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use super::history::{HISTORICAL_YEARS, HistoricalYear};
+use super::mortality_table::remaining_life_expectancy;
 use super::types::{
-    AgeResult, CashflowYearResult, Inputs, ModelResult, PensionTaxMode, WithdrawalOrder,
-    WithdrawalStrategy,
+    AgeResult, CashflowYearResult, FailureAttribution, FailureFactor, Inputs, MarketModel,
+    ModelError, ModelResult, PensionTaxMode, ReturnDistribution, SelectionConfidence,
+    SuccessCriterion, WithdrawalOrder, WithdrawalStrategy,
 };
 
+/// Real earnings growth assumed by the CAPE process; equity real returns above this
+/// push valuations up.
+const CAPE_EARNINGS_REAL_GROWTH: f64 = 0.015;
+
+/// Guyton-Klinger stop applying the capital preservation cut once this few years remain.
+const GK_CAPITAL_PRESERVATION_SUNSET_YEARS: u32 = 15;
+
+/// Government bonus added to each Lifetime ISA contribution.
+const LISA_BONUS_RATE: f64 = 0.25;
+/// Share of a Lifetime ISA withdrawal lost when it is taken before `lisa_access_age`.
+const LISA_EARLY_WITHDRAWAL_PENALTY: f64 = 0.25;
+/// Most that can be paid into a Lifetime ISA each year, before the bonus.
+const LISA_ANNUAL_LIMIT: f64 = 4_000.0;
+/// Lifetime ISA contributions stop at this age.
+const LISA_CONTRIBUTION_END_AGE: u32 = 50;
+
+/// Runway reported when no retirement year has any spending to fund, and the most any
+/// one year can report; a century of spending in hand is as good as unlimited.
+const MAX_RUNWAY_YEARS: f64 = 100.0;
+
 #[derive(Debug)]
 struct ScenarioResult {
     success: bool,
@@ -22,18 +49,176 @@ struct ScenarioResult {
     reported_terminal_bond_ladder: f64,
     min_income_ratio: f64,
     avg_income_ratio: f64,
+    /// CGT, income tax and dividend tax paid over the retirement years, each year
+    /// deflated to today's money.
+    lifetime_tax_paid: f64,
+    /// Largest fall of the real total portfolio from its running peak during retirement,
+    /// as a fraction of that peak. Running out of money counts as a full drawdown.
+    max_drawdown: f64,
+    /// Smallest start-of-year real total portfolio divided by that year's required real
+    /// spending: how many years of spending the plan held at its tightest.
+    min_runway_years: f64,
+    /// Retirement years simulated, counting the year that failed.
+    retirement_years: u32,
+    /// Price rise from retirement to the end of the last simulated year.
+    cumulative_inflation: f64,
+    /// Worst completed retirement year's real return on the invested pots; zero when
+    /// none completed.
+    worst_real_return: f64,
+    /// Discounted CRRA utility of real spending to the horizon; zero without
+    /// `risk_aversion`.
+    lifetime_utility: f64,
+}
+
+/// What `evaluate_age_candidate` keeps from each scenario: one sample per reported
+/// statistic, folded in as scenarios finish.
+#[derive(Debug, Default)]
+struct AgeSamples {
+    successes: u32,
+    retirement: Vec<f64>,
+    retirement_isa: Vec<f64>,
+    retirement_taxable: Vec<f64>,
+    retirement_pension: Vec<f64>,
+    retirement_cash: Vec<f64>,
+    retirement_bond_ladder: Vec<f64>,
+    terminal: Vec<f64>,
+    terminal_isa: Vec<f64>,
+    terminal_taxable: Vec<f64>,
+    terminal_pension: Vec<f64>,
+    terminal_cash: Vec<f64>,
+    terminal_bond_ladder: Vec<f64>,
+    min_income_ratios: Vec<f64>,
+    avg_income_ratios: Vec<f64>,
+    lifetime_taxes: Vec<f64>,
+    max_drawdowns: Vec<f64>,
+    min_runways: Vec<f64>,
+    lifetime_utilities: Vec<f64>,
+    attribution: Vec<AttributionSample>,
+}
+
+impl AgeSamples {
+    fn push(mut self, scenario: ScenarioResult) -> Self {
+        if scenario.success {
+            self.successes += 1;
+        }
+        self.retirement.push(scenario.reported_retirement_total);
+        self.retirement_isa.push(scenario.reported_retirement_isa);
+        self.retirement_taxable
+            .push(scenario.reported_retirement_taxable);
+        self.retirement_pension
+            .push(scenario.reported_retirement_pension);
+        self.retirement_cash.push(scenario.reported_retirement_cash);
+        self.retirement_bond_ladder
+            .push(scenario.reported_retirement_bond_ladder);
+        self.terminal.push(scenario.reported_terminal_total);
+        self.terminal_isa.push(scenario.reported_terminal_isa);
+        self.terminal_taxable
+            .push(scenario.reported_terminal_taxable);
+        self.terminal_pension
+            .push(scenario.reported_terminal_pension);
+        self.terminal_cash.push(scenario.reported_terminal_cash);
+        self.terminal_bond_ladder
+            .push(scenario.reported_terminal_bond_ladder);
+        self.min_income_ratios.push(scenario.min_income_ratio);
+        self.avg_income_ratios.push(scenario.avg_income_ratio);
+        self.lifetime_taxes.push(scenario.lifetime_tax_paid);
+        self.max_drawdowns.push(scenario.max_drawdown);
+        self.min_runways.push(scenario.min_runway_years);
+        self.lifetime_utilities.push(scenario.lifetime_utility);
+        self.attribution.push(AttributionSample::from(&scenario));
+        self
+    }
+
+    /// Appends `other`, which must hold the scenarios that follow this one's.
+    fn append(mut self, mut other: Self) -> Self {
+        self.successes += other.successes;
+        self.retirement.append(&mut other.retirement);
+        self.retirement_isa.append(&mut other.retirement_isa);
+        self.retirement_taxable
+            .append(&mut other.retirement_taxable);
+        self.retirement_pension
+            .append(&mut other.retirement_pension);
+        self.retirement_cash.append(&mut other.retirement_cash);
+        self.retirement_bond_ladder
+            .append(&mut other.retirement_bond_ladder);
+        self.terminal.append(&mut other.terminal);
+        self.terminal_isa.append(&mut other.terminal_isa);
+        self.terminal_taxable.append(&mut other.terminal_taxable);
+        self.terminal_pension.append(&mut other.terminal_pension);
+        self.terminal_cash.append(&mut other.terminal_cash);
+        self.terminal_bond_ladder
+            .append(&mut other.terminal_bond_ladder);
+        self.min_income_ratios.append(&mut other.min_income_ratios);
+        self.avg_income_ratios.append(&mut other.avg_income_ratios);
+        self.lifetime_taxes.append(&mut other.lifetime_taxes);
+        self.max_drawdowns.append(&mut other.max_drawdowns);
+        self.min_runways.append(&mut other.min_runways);
+        self.lifetime_utilities
+            .append(&mut other.lifetime_utilities);
+        self.attribution.append(&mut other.attribution);
+        self
+    }
+
+    fn sort(&mut self) {
+        for samples in [
+            &mut self.retirement,
+            &mut self.retirement_isa,
+            &mut self.retirement_taxable,
+            &mut self.retirement_pension,
+            &mut self.retirement_cash,
+            &mut self.retirement_bond_ladder,
+            &mut self.terminal,
+            &mut self.terminal_isa,
+            &mut self.terminal_taxable,
+            &mut self.terminal_pension,
+            &mut self.terminal_cash,
+            &mut self.terminal_bond_ladder,
+            &mut self.min_income_ratios,
+            &mut self.avg_income_ratios,
+            &mut self.lifetime_taxes,
+            &mut self.max_drawdowns,
+            &mut self.min_runways,
+        ] {
+            sort_samples(samples);
+        }
+    }
+}
+
+/// The parts of a `ScenarioResult` that failure attribution ranks scenarios by.
+#[derive(Debug, Clone, Copy)]
+struct AttributionSample {
+    success: bool,
+    lifetime_tax_paid: f64,
+    retirement_years: u32,
+    cumulative_inflation: f64,
+    worst_real_return: f64,
+}
+
+impl From<&ScenarioResult> for AttributionSample {
+    fn from(scenario: &ScenarioResult) -> Self {
+        Self {
+            success: scenario.success,
+            lifetime_tax_paid: scenario.lifetime_tax_paid,
+            retirement_years: scenario.retirement_years,
+            cumulative_inflation: scenario.cumulative_inflation,
+            worst_real_return: scenario.worst_real_return,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
 struct ContributionFlow {
     isa: f64,
+    /// Own Lifetime ISA payment plus the government bonus on it.
+    lisa: f64,
     taxable: f64,
     pension: f64,
+    employer_pension: f64,
 }
 
 impl ContributionFlow {
     fn total(self) -> f64 {
-        self.isa + self.taxable + self.pension
+        self.isa + self.lisa + self.taxable + self.pension + self.employer_pension
     }
 }
 
@@ -55,16 +240,24 @@ impl WithdrawalYearOutcome {
 #[derive(Debug, Clone, Copy)]
 struct YearTracePoint {
     contribution_isa_real: f64,
+    contribution_lisa_real: f64,
     contribution_taxable_real: f64,
     contribution_pension_real: f64,
+    contribution_employer_real: f64,
     contribution_total_real: f64,
     withdrawal_portfolio_real: f64,
     withdrawal_non_pension_income_real: f64,
+    annuity_income_real: f64,
+    rental_income_real: f64,
+    pension_conversion_real: f64,
     spending_total_real: f64,
     tax_cgt_real: f64,
     tax_income_real: f64,
+    /// Income tax (excluding dividend tax) split by band; zero outside retirement years.
+    tax_income_bands_real: IncomeTaxBands,
     tax_total_real: f64,
     end_isa_real: f64,
+    end_lisa_real: f64,
     end_taxable_real: f64,
     end_pension_real: f64,
     end_cash_real: f64,
@@ -75,30 +268,214 @@ struct YearTracePoint {
 #[derive(Debug)]
 struct Portfolio {
     isa: f64,
+    lisa: f64,
     taxable: f64,
     taxable_basis: f64,
     pension: f64,
+    /// Second pension pot in household mode; always zero otherwise.
+    partner_pension: f64,
     cash_buffer: f64,
     bond_ladder: f64,
 }
 
+impl Portfolio {
+    /// Every pot, cash buffer included.
+    fn total(&self) -> f64 {
+        self.invested_total() + self.cash_buffer
+    }
+
+    /// Every pot that earns a market return, i.e. all but the cash buffer.
+    fn invested_total(&self) -> f64 {
+        self.isa + self.lisa + self.taxable + self.pension + self.partner_pension + self.bond_ladder
+    }
+
+    /// The pots in today's money at `price_index`, with both pensions combined.
+    fn to_real(&self, price_index: f64) -> RealPortfolio {
+        let deflator = price_index.max(1e-9);
+        RealPortfolio {
+            isa: self.isa / deflator,
+            lisa: self.lisa / deflator,
+            taxable: self.taxable / deflator,
+            pension: (self.pension + self.partner_pension) / deflator,
+            cash: self.cash_buffer / deflator,
+            bond_ladder: self.bond_ladder / deflator,
+            total: self.total() / deflator,
+        }
+    }
+}
+
+/// Real-valued snapshot of a `Portfolio`, as reported in results and traces.
+#[derive(Clone, Copy, Debug)]
+struct RealPortfolio {
+    isa: f64,
+    lisa: f64,
+    taxable: f64,
+    pension: f64,
+    cash: f64,
+    bond_ladder: f64,
+    total: f64,
+}
+
 #[derive(Debug)]
 struct CgtState {
     allowance_remaining: f64,
+    /// Basic-rate band still free for gains after the year's other taxable income.
+    basic_band_remaining: f64,
     tax_paid: f64,
 }
 
+#[derive(Debug, Clone, Copy)]
+struct CgtRates {
+    basic: f64,
+    higher: f64,
+}
+
+impl CgtRates {
+    fn from_inputs(inputs: &Inputs) -> Self {
+        Self {
+            basic: inputs.cgt_basic_rate,
+            higher: inputs.cgt_higher_rate,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 struct TaxYearState {
     non_pension_taxable_income: f64,
     pension_gross_withdrawn: f64,
+    /// Part of `pension_gross_withdrawn` drawn from the tax-free lump sum.
+    pension_tax_free_withdrawn: f64,
+    /// Tax-free lump sum still available; carried between years by the scenario.
+    pension_tax_free_remaining: f64,
+    /// The partner's pension draws in household mode, taxed on their own allowance.
+    partner: PartnerTaxState,
+    /// Index for tax thresholds: the price index, less any years they were frozen.
     price_index: f64,
 }
 
+#[derive(Debug, Clone, Copy, Default)]
+struct PartnerTaxState {
+    pension_gross_withdrawn: f64,
+    pension_tax_free_withdrawn: f64,
+    pension_tax_free_remaining: f64,
+}
+
+impl TaxYearState {
+    fn taxable_income(&self) -> f64 {
+        self.non_pension_taxable_income + self.pension_gross_withdrawn
+            - self.pension_tax_free_withdrawn
+    }
+
+    fn partner_taxable_income(&self) -> f64 {
+        self.partner.pension_gross_withdrawn - self.partner.pension_tax_free_withdrawn
+    }
+
+    fn income_tax(&self, inputs: &Inputs) -> f64 {
+        let (primary, partner) = (self.taxable_income(), self.partner_taxable_income());
+        income_tax_for_total_income(primary, partner, inputs, self.price_index)
+            + partner_income_tax(partner, primary, inputs, self.price_index)
+    }
+
+    /// `income_tax` split by band, for the cashflow trace.
+    fn income_tax_bands(&self, inputs: &Inputs) -> IncomeTaxBands {
+        let (primary, partner) = (self.taxable_income(), self.partner_taxable_income());
+        let transfer = marriage_allowance_moved(inputs, primary, partner, self.price_index);
+        let primary_bands = income_tax_bands_with_allowance(
+            primary,
+            inputs,
+            self.price_index,
+            primary_personal_allowance(inputs, transfer),
+        );
+        if partner <= 0.0 {
+            return primary_bands;
+        }
+        primary_bands.plus(income_tax_bands_with_allowance(
+            partner,
+            inputs,
+            self.price_index,
+            partner_personal_allowance(inputs, transfer),
+        ))
+    }
+}
+
+/// Income tax by band. `basic + higher + additional` is the whole tax; `taper_effect` is
+/// the part of that total caused by the high-income allowance taper, i.e. the tax minus
+/// what it would be on the full allowance. Scottish starter/basic/intermediate rates
+/// count as basic and advanced/top as additional. Flat-rate mode has no bands and
+/// reports zeros.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct IncomeTaxBands {
+    basic: f64,
+    higher: f64,
+    additional: f64,
+    taper_effect: f64,
+}
+
+impl IncomeTaxBands {
+    fn total(self) -> f64 {
+        self.basic + self.higher + self.additional
+    }
+
+    fn plus(self, other: Self) -> Self {
+        Self {
+            basic: self.basic + other.basic,
+            higher: self.higher + other.higher,
+            additional: self.additional + other.additional,
+            taper_effect: self.taper_effect + other.taper_effect,
+        }
+    }
+
+    fn scaled(self, factor: f64) -> Self {
+        Self {
+            basic: self.basic * factor,
+            higher: self.higher * factor,
+            additional: self.additional * factor,
+            taper_effect: self.taper_effect * factor,
+        }
+    }
+}
+
+/// Pension gross each person can draw this year: zero before their access age.
+#[derive(Debug, Clone, Copy)]
+struct PensionPots {
+    primary: f64,
+    partner: f64,
+}
+
+impl PensionPots {
+    fn accessible(inputs: &Inputs, age: u32, portfolio: &Portfolio) -> Self {
+        Self {
+            primary: if age >= inputs.pension_access_age {
+                portfolio.pension
+            } else {
+                0.0
+            },
+            partner: if inputs.household && age >= inputs.partner_pension_access_age {
+                portfolio.partner_pension
+            } else {
+                0.0
+            },
+        }
+    }
+
+    fn total(&self) -> f64 {
+        self.primary + self.partner
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 struct SpendingState {
     current_real_spending: f64,
     initial_withdrawal_rate: f64,
+    /// Core spending actually planned last year in nominal terms; zero before the first
+    /// retirement year.
+    prev_nominal_spending: f64,
+    /// Price index that will apply to the year being planned.
+    price_index: f64,
+    /// Modelled CAPE valuation at the start of the year being planned.
+    cape: f64,
+    /// Spending-smile multiplier on the target for the year being planned.
+    smile_multiplier: f64,
 }
 
 #[derive(Clone, Copy)]
@@ -107,100 +484,579 @@ struct MarketSample {
     taxable_return: f64,
     pension_return: f64,
     inflation: f64,
+    bond_ladder_return: f64,
+}
+
+pub fn run_model(inputs: &Inputs) -> Result<ModelResult, ModelError> {
+    run_model_with_progress(inputs, &|_, _| {})
 }
 
-pub fn run_model(inputs: &Inputs) -> ModelResult {
-    let mut age_results = Vec::new();
-    for retirement_age in inputs.current_age..=inputs.max_retirement_age {
-        age_results.push(evaluate_age_candidate(
+/// `run_model` that calls `progress(completed, total)` once per finished candidate age.
+/// Candidates run in parallel may report out of order, but each `completed` count is
+/// reported exactly once and the last reaches `total`.
+pub fn run_model_with_progress(
+    inputs: &Inputs,
+    progress: &(dyn Fn(usize, usize) + Sync),
+) -> Result<ModelResult, ModelError> {
+    check_model_inputs(inputs, inputs.max_retirement_age)?;
+    let age_results = with_thread_pool(inputs, || {
+        map_in_order_with_progress(
             inputs,
-            retirement_age,
-            retirement_age,
-            retirement_age,
-        ));
+            inputs.current_age..inputs.max_retirement_age + 1,
+            |retirement_age| {
+                evaluate_age_candidate(inputs, retirement_age, retirement_age, retirement_age)
+            },
+            progress,
+        )
+    });
+    Ok(build_model_result(age_results, inputs))
+}
+
+/// `run_model` that hands each candidate's `AgeResult` to `on_age_result` as soon as
+/// it finishes, so callers can stream results instead of waiting for the whole sweep.
+/// Candidates run in parallel may arrive out of age order.
+pub fn run_model_with_age_results(
+    inputs: &Inputs,
+    on_age_result: &(dyn Fn(&AgeResult) + Sync),
+) -> Result<ModelResult, ModelError> {
+    check_model_inputs(inputs, inputs.max_retirement_age)?;
+    let age_results = with_thread_pool(inputs, || {
+        map_in_order(
+            inputs,
+            inputs.current_age..inputs.max_retirement_age + 1,
+            |retirement_age| {
+                let result =
+                    evaluate_age_candidate(inputs, retirement_age, retirement_age, retirement_age);
+                on_age_result(&result);
+                result
+            },
+        )
+    });
+    Ok(build_model_result(age_results, inputs))
+}
+
+pub fn run_coast_model(inputs: &Inputs, retirement_age: u32) -> Result<ModelResult, ModelError> {
+    run_coast_model_with_progress(inputs, retirement_age, &|_, _| {})
+}
+
+/// `run_coast_model` with the same per-candidate progress reporting as
+/// `run_model_with_progress`.
+pub fn run_coast_model_with_progress(
+    inputs: &Inputs,
+    retirement_age: u32,
+    progress: &(dyn Fn(usize, usize) + Sync),
+) -> Result<ModelResult, ModelError> {
+    check_model_inputs(inputs, retirement_age)?;
+    let age_results = with_thread_pool(inputs, || {
+        map_in_order_with_progress(
+            inputs,
+            inputs.current_age..retirement_age + 1,
+            |coast_age| evaluate_age_candidate(inputs, retirement_age, coast_age, coast_age),
+            progress,
+        )
+    });
+    Ok(build_model_result(age_results, inputs))
+}
+
+/// Guards the public model entry points against inputs that never went through
+/// `validate_inputs`. `last_age` is the oldest candidate age.
+fn check_model_inputs(inputs: &Inputs, last_age: u32) -> Result<(), ModelError> {
+    if last_age < inputs.current_age {
+        return Err(ModelError::EmptyAgeRange {
+            current_age: inputs.current_age,
+            last_age,
+        });
+    }
+    if inputs.horizon_age <= last_age {
+        return Err(ModelError::InfeasibleHorizon {
+            max_retirement_age: last_age,
+            horizon_age: inputs.horizon_age,
+        });
+    }
+    if inputs.simulations == 0 {
+        return Err(ModelError::ZeroSimulations);
+    }
+    let parameters = [
+        ("isa_start", inputs.isa_start),
+        ("taxable_start", inputs.taxable_start),
+        ("taxable_cost_basis_start", inputs.taxable_cost_basis_start),
+        ("pension_start", inputs.pension_start),
+        ("partner_pension_start", inputs.partner_pension_start),
+        (
+            "partner_personal_allowance",
+            inputs.partner_personal_allowance,
+        ),
+        ("cash_start", inputs.cash_start),
+        ("bond_ladder_start", inputs.bond_ladder_start),
+        ("isa_annual_contribution", inputs.isa_annual_contribution),
+        (
+            "isa_annual_contribution_limit",
+            inputs.isa_annual_contribution_limit,
+        ),
+        ("lisa_start", inputs.lisa_start),
+        ("lisa_annual_contribution", inputs.lisa_annual_contribution),
+        (
+            "taxable_annual_contribution",
+            inputs.taxable_annual_contribution,
+        ),
+        (
+            "pension_annual_contribution",
+            inputs.pension_annual_contribution,
+        ),
+        ("employer_match_rate", inputs.employer_match_rate),
+        ("pension_annual_allowance", inputs.pension_annual_allowance),
+        ("contribution_growth_rate", inputs.contribution_growth_rate),
+        (
+            "coast_contribution_fraction",
+            inputs.coast_contribution_fraction,
+        ),
+        ("pre_retirement_earnings", inputs.pre_retirement_earnings),
+        ("isa_return_mean", inputs.isa_return_mean),
+        ("isa_return_vol", inputs.isa_return_vol),
+        ("taxable_return_mean", inputs.taxable_return_mean),
+        ("taxable_return_vol", inputs.taxable_return_vol),
+        ("pension_return_mean", inputs.pension_return_mean),
+        ("pension_return_vol", inputs.pension_return_vol),
+        ("return_correlation", inputs.return_correlation),
+        ("isa_taxable_correlation", inputs.isa_taxable_correlation),
+        ("cgt_basic_rate", inputs.cgt_basic_rate),
+        ("cgt_higher_rate", inputs.cgt_higher_rate),
+        ("capital_gains_allowance", inputs.capital_gains_allowance),
+        ("taxable_return_tax_drag", inputs.taxable_return_tax_drag),
+        ("taxable_dividend_yield", inputs.taxable_dividend_yield),
+        ("dividend_allowance", inputs.dividend_allowance),
+        ("dividend_basic_rate", inputs.dividend_basic_rate),
+        ("dividend_higher_rate", inputs.dividend_higher_rate),
+        ("dividend_additional_rate", inputs.dividend_additional_rate),
+        ("isa_fee_rate", inputs.isa_fee_rate),
+        ("taxable_fee_rate", inputs.taxable_fee_rate),
+        ("pension_fee_rate", inputs.pension_fee_rate),
+        ("glide_start_equity", inputs.glide_start_equity),
+        ("glide_end_equity", inputs.glide_end_equity),
+        ("pension_flat_tax_rate", inputs.pension_flat_tax_rate),
+        ("pension_tax_free_pct", inputs.pension_tax_free_pct),
+        ("uk_personal_allowance", inputs.uk_personal_allowance),
+        (
+            "marriage_allowance_transfer",
+            inputs.marriage_allowance_transfer,
+        ),
+        ("uk_basic_rate_limit", inputs.uk_basic_rate_limit),
+        ("uk_higher_rate_limit", inputs.uk_higher_rate_limit),
+        ("uk_basic_rate", inputs.uk_basic_rate),
+        ("uk_higher_rate", inputs.uk_higher_rate),
+        ("uk_additional_rate", inputs.uk_additional_rate),
+        ("uk_allowance_taper_start", inputs.uk_allowance_taper_start),
+        ("uk_allowance_taper_end", inputs.uk_allowance_taper_end),
+        (
+            "scottish_starter_rate_limit",
+            inputs.scottish_starter_rate_limit,
+        ),
+        (
+            "scottish_basic_rate_limit",
+            inputs.scottish_basic_rate_limit,
+        ),
+        (
+            "scottish_intermediate_rate_limit",
+            inputs.scottish_intermediate_rate_limit,
+        ),
+        (
+            "scottish_higher_rate_limit",
+            inputs.scottish_higher_rate_limit,
+        ),
+        (
+            "scottish_advanced_rate_limit",
+            inputs.scottish_advanced_rate_limit,
+        ),
+        ("scottish_starter_rate", inputs.scottish_starter_rate),
+        ("scottish_basic_rate", inputs.scottish_basic_rate),
+        (
+            "scottish_intermediate_rate",
+            inputs.scottish_intermediate_rate,
+        ),
+        ("scottish_higher_rate", inputs.scottish_higher_rate),
+        ("scottish_advanced_rate", inputs.scottish_advanced_rate),
+        ("scottish_top_rate", inputs.scottish_top_rate),
+        (
+            "state_pension_annual_income",
+            inputs.state_pension_annual_income,
+        ),
+        (
+            "state_pension_real_growth",
+            inputs.state_pension_real_growth,
+        ),
+        (
+            "state_pension_deferral_uplift_rate",
+            inputs.state_pension_deferral_uplift_rate,
+        ),
+        ("db_pension_annual_income", inputs.db_pension_annual_income),
+        ("annuity_purchase_amount", inputs.annuity_purchase_amount),
+        ("annuity_rate", inputs.annuity_rate),
+        (
+            "conversion_ladder_annual_amount",
+            inputs.conversion_ladder_annual_amount,
+        ),
+        ("rental_annual_income", inputs.rental_annual_income),
+        ("rental_growth_rate", inputs.rental_growth_rate),
+        ("part_time_annual_income", inputs.part_time_annual_income),
+        ("inflation_mean", inputs.inflation_mean),
+        ("inflation_vol", inputs.inflation_vol),
+        (
+            "inflation_return_correlation",
+            inputs.inflation_return_correlation,
+        ),
+        ("return_clamp_min", inputs.return_clamp_min),
+        ("return_clamp_max", inputs.return_clamp_max),
+        ("inflation_clamp_min", inputs.inflation_clamp_min),
+        ("inflation_clamp_max", inputs.inflation_clamp_max),
+        ("target_annual_income", inputs.target_annual_income),
+        ("essential_annual_income", inputs.essential_annual_income),
+        ("spending_decline_rate", inputs.spending_decline_rate),
+        ("care_cost_uplift", inputs.care_cost_uplift),
+        ("active_phase_uplift", inputs.active_phase_uplift),
+        ("late_phase_uplift", inputs.late_phase_uplift),
+        (
+            "debts",
+            inputs.debts.iter().map(|debt| debt.annual_payment).sum(),
+        ),
+        ("mortgage_annual_payment", inputs.mortgage_annual_payment),
+        ("mortgage_balance", inputs.mortgage_balance),
+        ("mortgage_rate", inputs.mortgage_rate),
+        (
+            "mortgage_overpayments",
+            inputs
+                .mortgage_overpayments
+                .iter()
+                .map(|(_, amount)| amount)
+                .sum(),
+        ),
+        ("bequest_target", inputs.bequest_target),
+        ("success_threshold", inputs.success_threshold),
+        ("bad_year_threshold", inputs.bad_year_threshold),
+        ("good_year_threshold", inputs.good_year_threshold),
+        ("bad_year_cut", inputs.bad_year_cut),
+        ("good_year_raise", inputs.good_year_raise),
+        ("min_income_floor", inputs.min_income_floor),
+        ("min_real_spending_floor", inputs.min_real_spending_floor),
+        ("risk_aversion", inputs.risk_aversion.unwrap_or(1.0)),
+        ("utility_discount_rate", inputs.utility_discount_rate),
+        ("utility_spending_floor", inputs.utility_spending_floor),
+        ("max_income_ceiling", inputs.max_income_ceiling),
+        ("gk_lower_guardrail", inputs.gk_lower_guardrail),
+        ("gk_upper_guardrail", inputs.gk_upper_guardrail),
+        ("gk_adjustment", inputs.gk_adjustment),
+        ("vpw_expected_real_return", inputs.vpw_expected_real_return),
+        ("constant_withdrawal_rate", inputs.constant_withdrawal_rate),
+        ("cape_a", inputs.cape_a),
+        ("cape_b", inputs.cape_b),
+        ("cape_start", inputs.cape_start),
+        ("cape_long_run_mean", inputs.cape_long_run_mean),
+        ("cape_reversion_speed", inputs.cape_reversion_speed),
+        ("floor_upside_capture", inputs.floor_upside_capture),
+        ("bucket_target_years", inputs.bucket_target_years),
+        (
+            "good_year_extra_buffer_withdrawal",
+            inputs.good_year_extra_buffer_withdrawal,
+        ),
+        ("min_cash_months", inputs.min_cash_months),
+        ("cash_growth_rate", inputs.cash_growth_rate),
+        ("bond_ladder_yield", inputs.bond_ladder_yield),
+        ("bond_ladder_yield_vol", inputs.bond_ladder_yield_vol),
+        (
+            "bond_ladder_inflation_correlation",
+            inputs.bond_ladder_inflation_correlation,
+        ),
+        (
+            "bond_ladder_target_at_retirement",
+            inputs.bond_ladder_target_at_retirement,
+        ),
+        (
+            "employer_match_cap",
+            inputs.employer_match_cap.unwrap_or(0.0),
+        ),
+        (
+            "taxable_pension_correlation",
+            inputs.taxable_pension_correlation.unwrap_or(0.0),
+        ),
+        (
+            "return_factor_betas",
+            inputs
+                .return_factor_betas
+                .map_or(0.0, |betas| betas.iter().sum()),
+        ),
+    ];
+    if let Some((name, _)) = parameters.iter().find(|(_, value)| !value.is_finite()) {
+        return Err(ModelError::NonFiniteParameter(name));
+    }
+    if let ReturnDistribution::StudentT { dof } = inputs.return_distribution
+        && !dof.is_finite()
+    {
+        return Err(ModelError::NonFiniteParameter("student_t_dof"));
     }
-    build_model_result(age_results, inputs.success_threshold)
+    if let MarketModel::Deterministic { paths } = &inputs.market_model
+        && paths.iter().any(|&(isa, taxable, pension, inflation)| {
+            ![isa, taxable, pension, inflation]
+                .iter()
+                .all(|rate| rate.is_finite())
+        })
+    {
+        return Err(ModelError::NonFiniteParameter("market_model"));
+    }
+    if let MarketModel::Garch {
+        alpha,
+        beta,
+        long_run_vol,
+    } = inputs.market_model
+        && ![alpha, beta, long_run_vol].iter().all(|v| v.is_finite())
+    {
+        return Err(ModelError::NonFiniteParameter("market_model"));
+    }
+    if let MarketModel::RegimeSwitching {
+        p_normal_to_crisis,
+        p_crisis_to_normal,
+        crisis_return_mean,
+        crisis_return_vol,
+    } = inputs.market_model
+        && ![
+            p_normal_to_crisis,
+            p_crisis_to_normal,
+            crisis_return_mean,
+            crisis_return_vol,
+        ]
+        .iter()
+        .all(|v| v.is_finite())
+    {
+        return Err(ModelError::NonFiniteParameter("market_model"));
+    }
+    if let MarketModel::Ensemble { bootstrap_fraction } = inputs.market_model
+        && !bootstrap_fraction.is_finite()
+    {
+        return Err(ModelError::NonFiniteParameter("market_model"));
+    }
+    Ok(())
+}
+
+pub fn run_retirement_age_evaluation(inputs: &Inputs, retirement_age: u32) -> AgeResult {
+    with_thread_pool(inputs, || {
+        evaluate_age_candidate(inputs, retirement_age, retirement_age, retirement_age)
+    })
 }
 
-pub fn run_coast_model(inputs: &Inputs, retirement_age: u32) -> ModelResult {
-    let mut age_results = Vec::new();
-    for coast_age in inputs.current_age..=retirement_age {
-        age_results.push(evaluate_age_candidate(
+/// Every scenario's real terminal total for one candidate age, sorted ascending. Uses
+/// the same seeds as the sweep, so its percentiles match that age's `AgeResult`.
+pub fn run_terminal_distribution(
+    inputs: &Inputs,
+    retirement_age: u32,
+    contribution_stop_age: u32,
+    reported_age: u32,
+) -> Vec<f64> {
+    let mut terminal = with_thread_pool(inputs, || {
+        fold_age_scenarios(
             inputs,
             retirement_age,
-            coast_age,
-            coast_age,
-        ));
+            contribution_stop_age,
+            reported_age,
+            |mut terminal: Vec<f64>, scenario| {
+                terminal.push(scenario.reported_terminal_total);
+                terminal
+            },
+            |mut left, mut right| {
+                left.append(&mut right);
+                left
+            },
+        )
+    });
+    terminal.sort_by(f64::total_cmp);
+    terminal
+}
+
+/// Runs all `inputs.simulations` scenarios for one candidate age and folds each result
+/// into `T` as it finishes, in scenario order, so no age holds every `ScenarioResult`.
+fn fold_age_scenarios<T: Default + Send>(
+    inputs: &Inputs,
+    retirement_age: u32,
+    contribution_stop_age: u32,
+    reported_age: u32,
+    fold: impl Fn(T, ScenarioResult) -> T + Sync + Send,
+    merge: impl Fn(T, T) -> T + Sync + Send,
+) -> T {
+    fold_in_order(
+        inputs,
+        0..inputs.simulations,
+        |acc, scenario_id| {
+            let scenario_seed = scenario_seed(inputs, reported_age, scenario_id);
+            let mut rng = Rng::new(scenario_seed);
+            let scenario = simulate_scenario_with_market(
+                inputs,
+                retirement_age,
+                contribution_stop_age,
+                &mut rng,
+                MarketSampler::for_scenario(inputs, scenario_id),
+                None,
+            );
+            fold(acc, scenario)
+        },
+        merge,
+    )
+}
+
+/// Runs `job` on a dedicated pool when `inputs.threads > 1`; otherwise on the caller
+/// (which means rayon's global pool for `threads == 0`).
+fn with_thread_pool<R: Send>(inputs: &Inputs, job: impl FnOnce() -> R + Send) -> R {
+    if inputs.threads <= 1 {
+        return job();
+    }
+    match rayon::ThreadPoolBuilder::new()
+        .num_threads(inputs.threads)
+        .build()
+    {
+        Ok(pool) => pool.install(job),
+        Err(_) => job(),
     }
-    build_model_result(age_results, inputs.success_threshold)
 }
 
-pub fn run_retirement_age_evaluation(inputs: &Inputs, retirement_age: u32) -> AgeResult {
-    evaluate_age_candidate(inputs, retirement_age, retirement_age, retirement_age)
+/// Maps `items` in parallel unless `inputs.threads == 1`. Output order always follows the
+/// input order, so downstream percentiles are bit-identical either way.
+fn map_in_order<T: Send>(
+    inputs: &Inputs,
+    items: Range<u32>,
+    f: impl Fn(u32) -> T + Sync + Send,
+) -> Vec<T> {
+    if inputs.threads == 1 {
+        items.map(f).collect()
+    } else {
+        items.into_par_iter().map(f).collect()
+    }
+}
+
+/// Folds `items` in parallel unless `inputs.threads == 1`. Rayon folds contiguous runs
+/// and merges neighbouring runs left to right, so `merge` always sees items in input
+/// order and order-sensitive accumulators match the sequential fold bit for bit.
+fn fold_in_order<T: Default + Send>(
+    inputs: &Inputs,
+    items: Range<u32>,
+    fold: impl Fn(T, u32) -> T + Sync + Send,
+    merge: impl Fn(T, T) -> T + Sync + Send,
+) -> T {
+    if inputs.threads == 1 {
+        items.fold(T::default(), fold)
+    } else {
+        items
+            .into_par_iter()
+            .fold(T::default, fold)
+            .reduce(T::default, merge)
+    }
+}
+
+/// `map_in_order` that counts finished items and reports `(completed, total)`.
+fn map_in_order_with_progress<T: Send>(
+    inputs: &Inputs,
+    items: Range<u32>,
+    f: impl Fn(u32) -> T + Sync + Send,
+    progress: &(dyn Fn(usize, usize) + Sync),
+) -> Vec<T> {
+    let total = items.len();
+    let completed = AtomicUsize::new(0);
+    map_in_order(inputs, items, |item| {
+        let result = f(item);
+        progress(completed.fetch_add(1, Ordering::Relaxed) + 1, total);
+        result
+    })
 }
 
 struct YearlyAccumulator {
     ages: Vec<u32>,
-    contribution_isa: Vec<Vec<f64>>,
-    contribution_taxable: Vec<Vec<f64>>,
-    contribution_pension: Vec<Vec<f64>>,
-    contribution_total: Vec<Vec<f64>>,
-    withdrawal_portfolio: Vec<Vec<f64>>,
-    withdrawal_non_pension_income: Vec<Vec<f64>>,
-    spending_total: Vec<Vec<f64>>,
-    tax_cgt: Vec<Vec<f64>>,
-    tax_income: Vec<Vec<f64>>,
-    tax_total: Vec<Vec<f64>>,
-    end_isa: Vec<Vec<f64>>,
-    end_taxable: Vec<Vec<f64>>,
-    end_pension: Vec<Vec<f64>>,
-    end_cash: Vec<Vec<f64>>,
-    end_bond_ladder: Vec<Vec<f64>>,
-    end_total: Vec<Vec<f64>>,
+    contribution_isa: Vec<StreamingPercentiles>,
+    contribution_lisa: Vec<StreamingPercentiles>,
+    contribution_taxable: Vec<StreamingPercentiles>,
+    contribution_pension: Vec<StreamingPercentiles>,
+    contribution_employer: Vec<StreamingPercentiles>,
+    contribution_total: Vec<StreamingPercentiles>,
+    withdrawal_portfolio: Vec<StreamingPercentiles>,
+    withdrawal_non_pension_income: Vec<StreamingPercentiles>,
+    annuity_income: Vec<StreamingPercentiles>,
+    rental_income: Vec<StreamingPercentiles>,
+    pension_conversion: Vec<StreamingPercentiles>,
+    spending_total: Vec<StreamingPercentiles>,
+    tax_cgt: Vec<StreamingPercentiles>,
+    tax_income: Vec<StreamingPercentiles>,
+    tax_income_basic: Vec<StreamingPercentiles>,
+    tax_income_higher: Vec<StreamingPercentiles>,
+    tax_income_additional: Vec<StreamingPercentiles>,
+    tax_income_taper: Vec<StreamingPercentiles>,
+    tax_total: Vec<StreamingPercentiles>,
+    end_isa: Vec<StreamingPercentiles>,
+    end_lisa: Vec<StreamingPercentiles>,
+    end_taxable: Vec<StreamingPercentiles>,
+    end_pension: Vec<StreamingPercentiles>,
+    end_cash: Vec<StreamingPercentiles>,
+    end_bond_ladder: Vec<StreamingPercentiles>,
+    end_total: Vec<StreamingPercentiles>,
 }
 
 impl YearlyAccumulator {
-    fn new(ages: Vec<u32>, expected_samples: usize) -> Self {
+    fn new(ages: Vec<u32>) -> Self {
         let year_count = ages.len();
-        let make = || {
+        let make_with = |percentiles: &'static [f64]| {
             (0..year_count)
-                .map(|_| Vec::with_capacity(expected_samples))
+                .map(|_| StreamingPercentiles::new(percentiles))
                 .collect::<Vec<_>>()
         };
+        let make = || make_with(&MEDIAN_ONLY);
 
         Self {
             ages,
             contribution_isa: make(),
+            contribution_lisa: make(),
             contribution_taxable: make(),
             contribution_pension: make(),
+            contribution_employer: make(),
             contribution_total: make(),
-            withdrawal_portfolio: make(),
+            withdrawal_portfolio: make_with(&BAND_PERCENTILES),
             withdrawal_non_pension_income: make(),
-            spending_total: make(),
+            annuity_income: make(),
+            rental_income: make(),
+            pension_conversion: make(),
+            spending_total: make_with(&BAND_PERCENTILES),
             tax_cgt: make(),
             tax_income: make(),
+            tax_income_basic: make(),
+            tax_income_higher: make(),
+            tax_income_additional: make(),
+            tax_income_taper: make(),
             tax_total: make(),
             end_isa: make(),
+            end_lisa: make(),
             end_taxable: make(),
             end_pension: make(),
             end_cash: make(),
             end_bond_ladder: make(),
-            end_total: make(),
+            end_total: make_with(&FAN_PERCENTILES),
         }
     }
 
     fn push(&mut self, index: usize, point: YearTracePoint) {
         self.contribution_isa[index].push(point.contribution_isa_real);
+        self.contribution_lisa[index].push(point.contribution_lisa_real);
         self.contribution_taxable[index].push(point.contribution_taxable_real);
         self.contribution_pension[index].push(point.contribution_pension_real);
+        self.contribution_employer[index].push(point.contribution_employer_real);
         self.contribution_total[index].push(point.contribution_total_real);
         self.withdrawal_portfolio[index].push(point.withdrawal_portfolio_real);
         self.withdrawal_non_pension_income[index].push(point.withdrawal_non_pension_income_real);
+        self.annuity_income[index].push(point.annuity_income_real);
+        self.rental_income[index].push(point.rental_income_real);
+        self.pension_conversion[index].push(point.pension_conversion_real);
         self.spending_total[index].push(point.spending_total_real);
         self.tax_cgt[index].push(point.tax_cgt_real);
         self.tax_income[index].push(point.tax_income_real);
+        let bands = point.tax_income_bands_real;
+        self.tax_income_basic[index].push(bands.basic);
+        self.tax_income_higher[index].push(bands.higher);
+        self.tax_income_additional[index].push(bands.additional);
+        self.tax_income_taper[index].push(bands.taper_effect);
         self.tax_total[index].push(point.tax_total_real);
         self.end_isa[index].push(point.end_isa_real);
+        self.end_lisa[index].push(point.end_lisa_real);
         self.end_taxable[index].push(point.end_taxable_real);
         self.end_pension[index].push(point.end_pension_real);
         self.end_cash[index].push(point.end_cash_real);
@@ -213,31 +1069,210 @@ impl YearlyAccumulator {
         for idx in 0..self.ages.len() {
             results.push(CashflowYearResult {
                 age: self.ages[idx],
-                median_contribution_isa: percentile(&mut self.contribution_isa[idx], 50.0),
-                median_contribution_taxable: percentile(&mut self.contribution_taxable[idx], 50.0),
-                median_contribution_pension: percentile(&mut self.contribution_pension[idx], 50.0),
-                median_contribution_total: percentile(&mut self.contribution_total[idx], 50.0),
-                median_withdrawal_portfolio: percentile(&mut self.withdrawal_portfolio[idx], 50.0),
-                median_withdrawal_non_pension_income: percentile(
-                    &mut self.withdrawal_non_pension_income[idx],
-                    50.0,
-                ),
-                median_spending_total: percentile(&mut self.spending_total[idx], 50.0),
-                median_tax_cgt: percentile(&mut self.tax_cgt[idx], 50.0),
-                median_tax_income: percentile(&mut self.tax_income[idx], 50.0),
-                median_tax_total: percentile(&mut self.tax_total[idx], 50.0),
-                median_end_isa: percentile(&mut self.end_isa[idx], 50.0),
-                median_end_taxable: percentile(&mut self.end_taxable[idx], 50.0),
-                median_end_pension: percentile(&mut self.end_pension[idx], 50.0),
-                median_end_cash: percentile(&mut self.end_cash[idx], 50.0),
-                median_end_bond_ladder: percentile(&mut self.end_bond_ladder[idx], 50.0),
-                median_end_total: percentile(&mut self.end_total[idx], 50.0),
+                median_contribution_isa: self.contribution_isa[idx].value(50.0),
+                median_contribution_lisa: self.contribution_lisa[idx].value(50.0),
+                median_contribution_taxable: self.contribution_taxable[idx].value(50.0),
+                median_contribution_pension: self.contribution_pension[idx].value(50.0),
+                median_contribution_employer: self.contribution_employer[idx].value(50.0),
+                median_contribution_total: self.contribution_total[idx].value(50.0),
+                median_withdrawal_portfolio: self.withdrawal_portfolio[idx].value(50.0),
+                p10_withdrawal_portfolio: self.withdrawal_portfolio[idx].value(10.0),
+                p90_withdrawal_portfolio: self.withdrawal_portfolio[idx].value(90.0),
+                median_withdrawal_non_pension_income: self.withdrawal_non_pension_income[idx]
+                    .value(50.0),
+                median_annuity_income: self.annuity_income[idx].value(50.0),
+                median_rental_income: self.rental_income[idx].value(50.0),
+                median_pension_conversion: self.pension_conversion[idx].value(50.0),
+                median_spending_total: self.spending_total[idx].value(50.0),
+                p10_spending_total: self.spending_total[idx].value(10.0),
+                p90_spending_total: self.spending_total[idx].value(90.0),
+                median_tax_cgt: self.tax_cgt[idx].value(50.0),
+                median_tax_income: self.tax_income[idx].value(50.0),
+                median_tax_income_basic: self.tax_income_basic[idx].value(50.0),
+                median_tax_income_higher: self.tax_income_higher[idx].value(50.0),
+                median_tax_income_additional: self.tax_income_additional[idx].value(50.0),
+                median_tax_income_taper: self.tax_income_taper[idx].value(50.0),
+                median_tax_total: self.tax_total[idx].value(50.0),
+                median_end_isa: self.end_isa[idx].value(50.0),
+                median_end_lisa: self.end_lisa[idx].value(50.0),
+                median_end_taxable: self.end_taxable[idx].value(50.0),
+                median_end_pension: self.end_pension[idx].value(50.0),
+                median_end_cash: self.end_cash[idx].value(50.0),
+                median_end_bond_ladder: self.end_bond_ladder[idx].value(50.0),
+                median_end_total: self.end_total[idx].value(50.0),
+                p5_end_total: self.end_total[idx].value(5.0),
+                p10_end_total: self.end_total[idx].value(10.0),
+                p25_end_total: self.end_total[idx].value(25.0),
+                p75_end_total: self.end_total[idx].value(75.0),
+                p90_end_total: self.end_total[idx].value(90.0),
+                p95_end_total: self.end_total[idx].value(95.0),
             });
         }
         results
     }
 }
 
+/// Percentiles reported for outcome fans (terminal pot, end-of-year total).
+const FAN_PERCENTILES: [f64; 7] = [5.0, 10.0, 25.0, 50.0, 75.0, 90.0, 95.0];
+const MEDIAN_ONLY: [f64; 1] = [50.0];
+/// Percentiles reported as P10-P90 bands around cashflow medians.
+const BAND_PERCENTILES: [f64; 3] = [10.0, 50.0, 90.0];
+
+/// Samples kept exactly per series before percentiles switch to P² estimates, which
+/// bounds trace memory at O(years) however many scenarios run.
+const EXACT_PERCENTILE_SAMPLE_LIMIT: usize = 1_024;
+
+/// Percentiles of a stream: exact while the sample fits in `EXACT_PERCENTILE_SAMPLE_LIMIT`,
+/// then one P² (Jain & Chlamtac) estimate per requested percentile, seeded from the
+/// buffered sample.
+struct StreamingPercentiles {
+    percentiles: &'static [f64],
+    exact: Vec<f64>,
+    exact_sorted: bool,
+    estimates: Vec<P2Quantile>,
+}
+
+impl StreamingPercentiles {
+    fn new(percentiles: &'static [f64]) -> Self {
+        Self {
+            percentiles,
+            exact: Vec::new(),
+            exact_sorted: true,
+            estimates: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, value: f64) {
+        if !self.estimates.is_empty() {
+            for estimate in &mut self.estimates {
+                estimate.push(value);
+            }
+            return;
+        }
+        self.exact.push(value);
+        self.exact_sorted = false;
+        if self.exact.len() > EXACT_PERCENTILE_SAMPLE_LIMIT {
+            sort_samples(&mut self.exact);
+            self.estimates = self
+                .percentiles
+                .iter()
+                .map(|p| P2Quantile::from_sorted(p / 100.0, &self.exact))
+                .collect();
+            self.exact = Vec::new();
+        }
+    }
+
+    /// `p` must be one of the (ascending) percentiles this stream was created with.
+    fn value(&mut self, p: f64) -> f64 {
+        debug_assert!(self.percentiles.contains(&p));
+        if self.estimates.is_empty() {
+            if !self.exact_sorted {
+                sort_samples(&mut self.exact);
+                self.exact_sorted = true;
+            }
+            return percentile_sorted(&self.exact, p);
+        }
+        // Independent P² markers can cross on lumpy data (e.g. many depleted pots at
+        // zero); sorting the estimates restores a monotonic fan.
+        let mut values = self
+            .estimates
+            .iter()
+            .map(P2Quantile::value)
+            .collect::<Vec<_>>();
+        sort_samples(&mut values);
+        self.percentiles
+            .iter()
+            .position(|&candidate| candidate == p)
+            .map_or(0.0, |rank| values[rank])
+    }
+}
+
+/// P² streaming quantile: five markers whose heights are nudged with piecewise-parabolic
+/// interpolation as observations arrive, using O(1) memory.
+struct P2Quantile {
+    heights: [f64; 5],
+    positions: [f64; 5],
+    desired: [f64; 5],
+    increments: [f64; 5],
+}
+
+impl P2Quantile {
+    /// Seeds the markers from an already sorted sample of at least five values.
+    fn from_sorted(p: f64, sorted: &[f64]) -> Self {
+        debug_assert!(sorted.len() >= 5);
+        let increments = [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0];
+        let last = (sorted.len() - 1) as f64;
+        let mut heights = [0.0; 5];
+        let mut positions = [0.0; 5];
+        let mut desired = [0.0; 5];
+        for i in 0..5 {
+            desired[i] = 1.0 + last * increments[i];
+            positions[i] = desired[i].round();
+            heights[i] = sorted[positions[i] as usize - 1];
+        }
+        Self {
+            heights,
+            positions,
+            desired,
+            increments,
+        }
+    }
+
+    fn push(&mut self, value: f64) {
+        let q = &mut self.heights;
+        let cell = if value < q[0] {
+            q[0] = value;
+            0
+        } else if value >= q[4] {
+            q[4] = value;
+            3
+        } else {
+            (0..4).find(|&i| value < q[i + 1]).unwrap_or(3)
+        };
+
+        for position in &mut self.positions[cell + 1..] {
+            *position += 1.0;
+        }
+        for (desired, increment) in self.desired.iter_mut().zip(self.increments) {
+            *desired += increment;
+        }
+
+        for i in 1..4 {
+            let drift = self.desired[i] - self.positions[i];
+            let gap_up = self.positions[i + 1] - self.positions[i];
+            let gap_down = self.positions[i - 1] - self.positions[i];
+            if (drift >= 1.0 && gap_up > 1.0) || (drift <= -1.0 && gap_down < -1.0) {
+                let step = drift.signum();
+                let parabolic = self.parabolic(i, step);
+                self.heights[i] =
+                    if self.heights[i - 1] < parabolic && parabolic < self.heights[i + 1] {
+                        parabolic
+                    } else {
+                        self.linear(i, step)
+                    };
+                self.positions[i] += step;
+            }
+        }
+    }
+
+    fn parabolic(&self, i: usize, step: f64) -> f64 {
+        let (q, n) = (&self.heights, &self.positions);
+        q[i] + step / (n[i + 1] - n[i - 1])
+            * ((n[i] - n[i - 1] + step) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                + (n[i + 1] - n[i] - step) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]))
+    }
+
+    fn linear(&self, i: usize, step: f64) -> f64 {
+        let j = if step > 0.0 { i + 1 } else { i - 1 };
+        self.heights[i]
+            + step * (self.heights[j] - self.heights[i]) / (self.positions[j] - self.positions[i])
+    }
+
+    fn value(&self) -> f64 {
+        self.heights[2]
+    }
+}
+
 pub fn run_yearly_cashflow_trace(
     inputs: &Inputs,
     retirement_age: u32,
@@ -249,22 +1284,25 @@ pub fn run_yearly_cashflow_trace(
         return Vec::new();
     }
 
-    let mut acc = YearlyAccumulator::new(ages.clone(), inputs.simulations as usize);
+    let mut acc = YearlyAccumulator::new(ages.clone());
+    // One scratch buffer reused by every scenario.
+    let mut trace = Vec::with_capacity(ages.len());
 
     for scenario_id in 0..inputs.simulations {
-        let scenario_seed = derive_seed(inputs.seed, reported_age, scenario_id);
+        let scenario_seed = scenario_seed(inputs, reported_age, scenario_id);
         let mut rng = Rng::new(scenario_seed);
-        let mut trace = Vec::with_capacity(ages.len());
-        let _ = simulate_scenario(
+        trace.clear();
+        let _ = simulate_scenario_with_market(
             inputs,
             retirement_age,
             contribution_stop_age,
             &mut rng,
+            MarketSampler::for_scenario(inputs, scenario_id),
             Some(&mut trace),
         );
 
         if trace.len() == ages.len() {
-            for (idx, point) in trace.into_iter().enumerate() {
+            for (idx, point) in trace.iter().copied().enumerate() {
                 acc.push(idx, point);
             }
             continue;
@@ -273,16 +1311,23 @@ pub fn run_yearly_cashflow_trace(
         for idx in 0..ages.len() {
             let fallback = trace.get(idx).copied().unwrap_or(YearTracePoint {
                 contribution_isa_real: 0.0,
+                contribution_lisa_real: 0.0,
                 contribution_taxable_real: 0.0,
                 contribution_pension_real: 0.0,
+                contribution_employer_real: 0.0,
                 contribution_total_real: 0.0,
                 withdrawal_portfolio_real: 0.0,
                 withdrawal_non_pension_income_real: 0.0,
+                annuity_income_real: 0.0,
+                rental_income_real: 0.0,
+                pension_conversion_real: 0.0,
                 spending_total_real: 0.0,
                 tax_cgt_real: 0.0,
                 tax_income_real: 0.0,
+                tax_income_bands_real: IncomeTaxBands::default(),
                 tax_total_real: 0.0,
                 end_isa_real: 0.0,
+                end_lisa_real: 0.0,
                 end_taxable_real: 0.0,
                 end_pension_real: 0.0,
                 end_cash_real: 0.0,
@@ -296,10 +1341,16 @@ pub fn run_yearly_cashflow_trace(
     acc.into_results()
 }
 
-fn build_model_result(age_results: Vec<AgeResult>, success_threshold: f64) -> ModelResult {
-    let selected_index = age_results
-        .iter()
-        .position(|r| r.success_rate >= success_threshold);
+fn build_model_result(age_results: Vec<AgeResult>, inputs: &Inputs) -> ModelResult {
+    let selected_index = age_results.iter().position(|r| {
+        let (lower, upper) = wilson_interval(r.success_rate, inputs.simulations);
+        let rate = match inputs.selection_confidence {
+            SelectionConfidence::PointEstimate => r.success_rate,
+            SelectionConfidence::Conservative => lower,
+            SelectionConfidence::Optimistic => upper,
+        };
+        rate >= inputs.success_threshold
+    });
     let best_index = age_results
         .iter()
         .enumerate()
@@ -320,171 +1371,324 @@ fn evaluate_age_candidate(
     contribution_stop_age: u32,
     reported_age: u32,
 ) -> AgeResult {
-    let mut successes = 0_u32;
-    let mut retirement = Vec::with_capacity(inputs.simulations as usize);
-    let mut retirement_isa = Vec::with_capacity(inputs.simulations as usize);
-    let mut retirement_taxable = Vec::with_capacity(inputs.simulations as usize);
-    let mut retirement_pension = Vec::with_capacity(inputs.simulations as usize);
-    let mut retirement_cash = Vec::with_capacity(inputs.simulations as usize);
-    let mut retirement_bond_ladder = Vec::with_capacity(inputs.simulations as usize);
-    let mut terminal = Vec::with_capacity(inputs.simulations as usize);
-    let mut terminal_isa = Vec::with_capacity(inputs.simulations as usize);
-    let mut terminal_taxable = Vec::with_capacity(inputs.simulations as usize);
-    let mut terminal_pension = Vec::with_capacity(inputs.simulations as usize);
-    let mut terminal_cash = Vec::with_capacity(inputs.simulations as usize);
-    let mut terminal_bond_ladder = Vec::with_capacity(inputs.simulations as usize);
-    let mut min_income_ratios = Vec::with_capacity(inputs.simulations as usize);
-    let mut avg_income_ratios = Vec::with_capacity(inputs.simulations as usize);
-
-    for scenario_id in 0..inputs.simulations {
-        let scenario_seed = derive_seed(inputs.seed, reported_age, scenario_id);
-        let mut rng = Rng::new(scenario_seed);
-        let scenario = simulate_scenario(
-            inputs,
-            retirement_age,
-            contribution_stop_age,
-            &mut rng,
-            None,
-        );
-        if scenario.success {
-            successes += 1;
-        }
-
-        retirement.push(scenario.reported_retirement_total);
-        retirement_isa.push(scenario.reported_retirement_isa);
-        retirement_taxable.push(scenario.reported_retirement_taxable);
-        retirement_pension.push(scenario.reported_retirement_pension);
-        retirement_cash.push(scenario.reported_retirement_cash);
-        retirement_bond_ladder.push(scenario.reported_retirement_bond_ladder);
-        terminal.push(scenario.reported_terminal_total);
-        terminal_isa.push(scenario.reported_terminal_isa);
-        terminal_taxable.push(scenario.reported_terminal_taxable);
-        terminal_pension.push(scenario.reported_terminal_pension);
-        terminal_cash.push(scenario.reported_terminal_cash);
-        terminal_bond_ladder.push(scenario.reported_terminal_bond_ladder);
-        min_income_ratios.push(scenario.min_income_ratio);
-        avg_income_ratios.push(scenario.avg_income_ratio);
-    }
+    let mut samples = fold_age_scenarios(
+        inputs,
+        retirement_age,
+        contribution_stop_age,
+        reported_age,
+        AgeSamples::push,
+        AgeSamples::append,
+    );
+    samples.sort();
+    let successes = samples.successes;
 
     AgeResult {
         retirement_age: reported_age,
         success_rate: successes as f64 / inputs.simulations as f64,
-        median_retirement_pot: percentile(&mut retirement, 50.0),
-        p10_retirement_pot: percentile(&mut retirement, 10.0),
-        median_retirement_isa: percentile(&mut retirement_isa, 50.0),
-        p10_retirement_isa: percentile(&mut retirement_isa, 10.0),
-        median_retirement_taxable: percentile(&mut retirement_taxable, 50.0),
-        p10_retirement_taxable: percentile(&mut retirement_taxable, 10.0),
-        median_retirement_pension: percentile(&mut retirement_pension, 50.0),
-        p10_retirement_pension: percentile(&mut retirement_pension, 10.0),
-        median_retirement_cash: percentile(&mut retirement_cash, 50.0),
-        p10_retirement_cash: percentile(&mut retirement_cash, 10.0),
-        median_retirement_bond_ladder: percentile(&mut retirement_bond_ladder, 50.0),
-        p10_retirement_bond_ladder: percentile(&mut retirement_bond_ladder, 10.0),
-        median_terminal_pot: percentile(&mut terminal, 50.0),
-        p10_terminal_pot: percentile(&mut terminal, 10.0),
-        median_terminal_isa: percentile(&mut terminal_isa, 50.0),
-        p10_terminal_isa: percentile(&mut terminal_isa, 10.0),
-        median_terminal_taxable: percentile(&mut terminal_taxable, 50.0),
-        p10_terminal_taxable: percentile(&mut terminal_taxable, 10.0),
-        median_terminal_pension: percentile(&mut terminal_pension, 50.0),
-        p10_terminal_pension: percentile(&mut terminal_pension, 10.0),
-        median_terminal_cash: percentile(&mut terminal_cash, 50.0),
-        p10_terminal_cash: percentile(&mut terminal_cash, 10.0),
-        median_terminal_bond_ladder: percentile(&mut terminal_bond_ladder, 50.0),
-        p10_terminal_bond_ladder: percentile(&mut terminal_bond_ladder, 10.0),
-        p10_min_income_ratio: percentile(&mut min_income_ratios, 10.0),
-        median_avg_income_ratio: percentile(&mut avg_income_ratios, 50.0),
+        success_ci_half_width: binomial_ci_half_width(
+            successes as f64 / inputs.simulations as f64,
+            inputs.simulations,
+        ),
+        median_retirement_pot: percentile_sorted(&samples.retirement, 50.0),
+        p10_retirement_pot: percentile_sorted(&samples.retirement, 10.0),
+        median_retirement_isa: percentile_sorted(&samples.retirement_isa, 50.0),
+        p10_retirement_isa: percentile_sorted(&samples.retirement_isa, 10.0),
+        median_retirement_taxable: percentile_sorted(&samples.retirement_taxable, 50.0),
+        p10_retirement_taxable: percentile_sorted(&samples.retirement_taxable, 10.0),
+        median_retirement_pension: percentile_sorted(&samples.retirement_pension, 50.0),
+        p10_retirement_pension: percentile_sorted(&samples.retirement_pension, 10.0),
+        median_retirement_cash: percentile_sorted(&samples.retirement_cash, 50.0),
+        p10_retirement_cash: percentile_sorted(&samples.retirement_cash, 10.0),
+        median_retirement_bond_ladder: percentile_sorted(&samples.retirement_bond_ladder, 50.0),
+        p10_retirement_bond_ladder: percentile_sorted(&samples.retirement_bond_ladder, 10.0),
+        median_terminal_pot: percentile_sorted(&samples.terminal, 50.0),
+        p5_terminal_pot: percentile_sorted(&samples.terminal, 5.0),
+        p10_terminal_pot: percentile_sorted(&samples.terminal, 10.0),
+        p25_terminal_pot: percentile_sorted(&samples.terminal, 25.0),
+        p75_terminal_pot: percentile_sorted(&samples.terminal, 75.0),
+        p90_terminal_pot: percentile_sorted(&samples.terminal, 90.0),
+        p95_terminal_pot: percentile_sorted(&samples.terminal, 95.0),
+        cvar5_terminal_pot: lower_tail_mean(&samples.terminal, CVAR_TAIL_FRACTION),
+        median_terminal_isa: percentile_sorted(&samples.terminal_isa, 50.0),
+        p10_terminal_isa: percentile_sorted(&samples.terminal_isa, 10.0),
+        median_terminal_taxable: percentile_sorted(&samples.terminal_taxable, 50.0),
+        p10_terminal_taxable: percentile_sorted(&samples.terminal_taxable, 10.0),
+        median_terminal_pension: percentile_sorted(&samples.terminal_pension, 50.0),
+        p10_terminal_pension: percentile_sorted(&samples.terminal_pension, 10.0),
+        median_terminal_cash: percentile_sorted(&samples.terminal_cash, 50.0),
+        p10_terminal_cash: percentile_sorted(&samples.terminal_cash, 10.0),
+        median_terminal_bond_ladder: percentile_sorted(&samples.terminal_bond_ladder, 50.0),
+        p10_terminal_bond_ladder: percentile_sorted(&samples.terminal_bond_ladder, 10.0),
+        p10_min_income_ratio: percentile_sorted(&samples.min_income_ratios, 10.0),
+        cvar5_min_income_ratio: lower_tail_mean(&samples.min_income_ratios, CVAR_TAIL_FRACTION),
+        median_avg_income_ratio: percentile_sorted(&samples.avg_income_ratios, 50.0),
+        median_lifetime_tax: percentile_sorted(&samples.lifetime_taxes, 50.0),
+        p90_lifetime_tax: percentile_sorted(&samples.lifetime_taxes, 90.0),
+        median_max_drawdown: percentile_sorted(&samples.max_drawdowns, 50.0),
+        p90_max_drawdown: percentile_sorted(&samples.max_drawdowns, 90.0),
+        median_min_runway_years: percentile_sorted(&samples.min_runways, 50.0),
+        p10_min_runway_years: percentile_sorted(&samples.min_runways, 10.0),
+        failure_attribution: failure_attribution(&samples.attribution),
+        expected_utility: inputs.risk_aversion.map(|_| {
+            samples.lifetime_utilities.iter().sum::<f64>()
+                / samples.lifetime_utilities.len().max(1) as f64
+        }),
     }
 }
 
-fn simulate_scenario(
-    inputs: &Inputs,
-    retirement_age: u32,
-    contribution_stop_age: u32,
-    rng: &mut Rng,
-    mut trace: Option<&mut Vec<YearTracePoint>>,
-) -> ScenarioResult {
-    let mut portfolio = Portfolio {
-        isa: inputs.isa_start,
-        taxable: inputs.taxable_start,
-        taxable_basis: inputs.taxable_cost_basis_start.min(inputs.taxable_start),
-        pension: inputs.pension_start,
+/// Summarises what the failed scenarios had in common. Each factor is measured per
+/// retirement year (annualised inflation, worst real return, average real tax) and
+/// every failure is ranked against all scenarios on it, so the scores are comparable
+/// across factors and across failures at different ages.
+fn failure_attribution(scenarios: &[AttributionSample]) -> Option<FailureAttribution> {
+    let failed: Vec<&AttributionSample> = scenarios.iter().filter(|s| !s.success).collect();
+    if failed.is_empty() {
+        return None;
+    }
+
+    let annual_inflation = |s: &AttributionSample| {
+        if s.retirement_years == 0 {
+            0.0
+        } else {
+            (1.0 + s.cumulative_inflation)
+                .max(0.0)
+                .powf(1.0 / s.retirement_years as f64)
+                - 1.0
+        }
+    };
+    let annual_tax = |s: &AttributionSample| s.lifetime_tax_paid / s.retirement_years.max(1) as f64;
+    // Each measure is oriented so that higher is worse.
+    let score = |measure: &dyn Fn(&AttributionSample) -> f64| {
+        let mut all: Vec<f64> = scenarios.iter().map(measure).collect();
+        sort_samples(&mut all);
+        failed
+            .iter()
+            .map(|s| adverse_rank(&all, measure(s)))
+            .sum::<f64>()
+            / failed.len() as f64
+    };
+    let inflation_score = score(&annual_inflation);
+    let market_score = score(&|s: &AttributionSample| -s.worst_real_return);
+    let tax_score = score(&annual_tax);
+
+    let dominant_factor = [
+        (FailureFactor::Inflation, inflation_score),
+        (FailureFactor::Market, market_score),
+        (FailureFactor::Tax, tax_score),
+    ]
+    .into_iter()
+    .filter(|(_, score)| *score > 0.5)
+    .fold(
+        None,
+        |best: Option<(FailureFactor, f64)>, candidate| match best {
+            Some(best) if best.1 >= candidate.1 => Some(best),
+            _ => Some(candidate),
+        },
+    )
+    .map(|(factor, _)| factor);
+
+    let median_of = |measure: &dyn Fn(&AttributionSample) -> f64| {
+        let mut values: Vec<f64> = failed.iter().map(|s| measure(s)).collect();
+        sort_samples(&mut values);
+        percentile_sorted(&values, 50.0)
+    };
+
+    Some(FailureAttribution {
+        failed_scenarios: failed.len() as u32,
+        median_cumulative_inflation: median_of(&|s: &AttributionSample| s.cumulative_inflation),
+        median_worst_real_return: median_of(&|s: &AttributionSample| s.worst_real_return),
+        median_tax_paid: median_of(&|s: &AttributionSample| s.lifetime_tax_paid),
+        inflation_score,
+        market_score,
+        tax_score,
+        dominant_factor,
+    })
+}
+
+/// Share of an already sorted sample below `value`, counting ties as half, so a value
+/// typical of the sample scores about 0.5.
+fn adverse_rank(sorted: &[f64], value: f64) -> f64 {
+    let below = sorted.partition_point(|x| *x < value);
+    let not_above = sorted.partition_point(|x| *x <= value);
+    (below as f64 + 0.5 * (not_above - below) as f64) / sorted.len() as f64
+}
+
+/// One scenario with a fresh, unstratified market sampler.
+#[cfg(test)]
+fn simulate_scenario(
+    inputs: &Inputs,
+    retirement_age: u32,
+    contribution_stop_age: u32,
+    rng: &mut Rng,
+    trace: Option<&mut Vec<YearTracePoint>>,
+) -> ScenarioResult {
+    simulate_scenario_with_market(
+        inputs,
+        retirement_age,
+        contribution_stop_age,
+        rng,
+        MarketSampler::new(),
+        trace,
+    )
+}
+
+/// Runs one scenario with a caller-built market sampler, so the sweep can hand each
+/// scenario its own inflation strata.
+fn simulate_scenario_with_market(
+    inputs: &Inputs,
+    retirement_age: u32,
+    contribution_stop_age: u32,
+    rng: &mut Rng,
+    mut market: MarketSampler,
+    mut trace: Option<&mut Vec<YearTracePoint>>,
+) -> ScenarioResult {
+    market.burn_in(inputs, rng);
+    let mut portfolio = Portfolio {
+        isa: inputs.isa_start,
+        lisa: inputs.lisa_start,
+        taxable: inputs.taxable_start,
+        taxable_basis: inputs.taxable_cost_basis_start.min(inputs.taxable_start),
+        pension: inputs.pension_start,
+        partner_pension: if inputs.household {
+            inputs.partner_pension_start
+        } else {
+            0.0
+        },
         cash_buffer: inputs.cash_start,
         bond_ladder: inputs.bond_ladder_start,
     };
 
     let mut price_index = 1.0;
+    let mut threshold_index = 1.0;
+    let mut cape = inputs.cape_start;
 
     for (years_since_start, age) in (inputs.current_age..retirement_age).enumerate() {
-        let sampled = sample_market(inputs, rng);
-        apply_pre_retirement_growth(inputs, &mut portfolio, &sampled);
-        let contributions = if age < contribution_stop_age {
-            apply_pre_retirement_contributions(inputs, &mut portfolio, years_since_start as u32)
+        let sampled = market.sample(inputs, rng);
+        let dividend_tax = apply_pre_retirement_growth(
+            inputs,
+            &mut portfolio,
+            &sampled,
+            age,
+            pre_retirement_income(inputs, price_index),
+            threshold_index,
+        );
+        cape = evolve_cape(inputs, cape, &sampled);
+        price_index *= 1.0 + sampled.inflation;
+        let contribution_fraction = if age < contribution_stop_age {
+            1.0
         } else {
-            ContributionFlow {
-                isa: 0.0,
-                taxable: 0.0,
-                pension: 0.0,
-            }
+            inputs.coast_contribution_fraction
         };
-        price_index *= 1.0 + sampled.inflation;
+        let contributions = apply_pre_retirement_contributions(
+            inputs,
+            &mut portfolio,
+            years_since_start as u32,
+            price_index,
+            contribution_fraction,
+        );
+        if tax_thresholds_indexed(inputs, age) {
+            threshold_index *= 1.0 + sampled.inflation;
+        }
+        let overpayment_cgt = fund_pre_retirement_overpayment(
+            inputs,
+            &mut portfolio,
+            years_since_start as u32,
+            price_index,
+            threshold_index,
+        );
 
         if let Some(trace_rows) = trace.as_deref_mut() {
             let deflator = price_index.max(1e-9);
+            let end = portfolio.to_real(price_index);
             trace_rows.push(YearTracePoint {
                 contribution_isa_real: contributions.isa / deflator,
+                contribution_lisa_real: contributions.lisa / deflator,
                 contribution_taxable_real: contributions.taxable / deflator,
                 contribution_pension_real: contributions.pension / deflator,
+                contribution_employer_real: contributions.employer_pension / deflator,
                 contribution_total_real: contributions.total() / deflator,
                 withdrawal_portfolio_real: 0.0,
                 withdrawal_non_pension_income_real: 0.0,
+                annuity_income_real: 0.0,
+                rental_income_real: 0.0,
+                pension_conversion_real: 0.0,
                 spending_total_real: 0.0,
-                tax_cgt_real: 0.0,
-                tax_income_real: 0.0,
-                tax_total_real: 0.0,
-                end_isa_real: portfolio.isa / deflator,
-                end_taxable_real: portfolio.taxable / deflator,
-                end_pension_real: portfolio.pension / deflator,
-                end_cash_real: portfolio.cash_buffer / deflator,
-                end_bond_ladder_real: portfolio.bond_ladder / deflator,
-                end_total_real: (portfolio.isa
-                    + portfolio.taxable
-                    + portfolio.pension
-                    + portfolio.cash_buffer
-                    + portfolio.bond_ladder)
-                    / deflator,
+                tax_cgt_real: overpayment_cgt / deflator,
+                tax_income_real: dividend_tax / deflator,
+                tax_income_bands_real: IncomeTaxBands::default(),
+                tax_total_real: (dividend_tax + overpayment_cgt) / deflator,
+                end_isa_real: end.isa,
+                end_lisa_real: end.lisa,
+                end_taxable_real: end.taxable,
+                end_pension_real: end.pension,
+                end_cash_real: end.cash,
+                end_bond_ladder_real: end.bond_ladder,
+                end_total_real: end.total,
             });
         }
     }
 
-    let retirement_deflator = price_index.max(1e-9);
-    let retirement_nominal_total = portfolio.isa
-        + portfolio.taxable
-        + portfolio.pension
-        + portfolio.cash_buffer
-        + portfolio.bond_ladder;
-    let retirement_total_real = retirement_nominal_total / retirement_deflator;
-    let retirement_isa_real = portfolio.isa / retirement_deflator;
-    let retirement_taxable_real = portfolio.taxable / retirement_deflator;
-    let retirement_pension_real = portfolio.pension / retirement_deflator;
-    let retirement_cash_real = portfolio.cash_buffer / retirement_deflator;
-    let retirement_bond_ladder_real = portfolio.bond_ladder / retirement_deflator;
+    let at_retirement = portfolio.to_real(price_index);
+    let retirement_total_real = at_retirement.total;
+    let retirement_price_index = price_index;
 
-    let initial_withdrawal_rate = inputs.target_annual_income / retirement_total_real.max(1e-9);
+    let initial_smile_multiplier =
+        spending_smile_multiplier(inputs, retirement_age, retirement_age);
+    let initial_real_spending = inputs.target_annual_income * initial_smile_multiplier;
+    let initial_withdrawal_rate = initial_real_spending / retirement_total_real.max(1e-9);
     let mut spending_state = SpendingState {
-        current_real_spending: inputs.target_annual_income,
+        current_real_spending: initial_real_spending,
         initial_withdrawal_rate,
+        prev_nominal_spending: 0.0,
+        price_index,
+        cape,
+        smile_multiplier: initial_smile_multiplier,
     };
     let mut prev_real_return = 0.0;
+    // Fixed from the pension value in the first retirement year the pension is accessible.
+    let mut pension_tax_free_remaining: Option<f64> = None;
+    let mut partner_tax_free_remaining: Option<f64> = None;
+    // Real annuity income, set once the annuity has been bought.
+    let mut annuity_income_real: Option<f64> = None;
     let mut min_income_ratio = f64::INFINITY;
     let mut income_ratio_sum = 0.0;
     let mut years = 0_u32;
+    let mut lifetime_tax_paid = 0.0;
+    let mut peak_total_real = retirement_total_real;
+    let mut max_drawdown: f64 = 0.0;
+    let mut min_runway_years = MAX_RUNWAY_YEARS;
+    let mut worst_real_return = f64::INFINITY;
+    let mut lifetime_utility = 0.0;
 
     for age in retirement_age..inputs.horizon_age {
-        let mortgage_real_spending = mortgage_payment_real(inputs, age);
+        // The year's inflation is not drawn yet, so the runway prices the mortgage at
+        // the expected year-end level.
+        let required_spending_real = required_real_spending(
+            inputs,
+            retirement_age,
+            age,
+            price_index * (1.0 + inputs.inflation_mean),
+        );
+        if required_spending_real > 0.0 {
+            let start_total_real = portfolio.total() / price_index.max(1e-9);
+            min_runway_years = min_runway_years.min(start_total_real / required_spending_real);
+        }
         let available_real = available_spendable_real(inputs, age, &portfolio, price_index);
-        let available_core_real = (available_real - mortgage_real_spending).max(0.0);
+
+        let sampled = market.sample(inputs, rng);
+        price_index *= 1.0 + sampled.inflation;
+        let fixed_real_spending =
+            debt_payment_real(inputs, age, price_index) + lump_expense_real(inputs, age);
+        let available_core_real = (available_real - fixed_real_spending).max(0.0);
+        if tax_thresholds_indexed(inputs, age) {
+            threshold_index *= 1.0 + sampled.inflation;
+        }
+        spending_state.price_index = price_index;
+        rebase_spending_smile(
+            &mut spending_state,
+            spending_smile_multiplier(inputs, retirement_age, age),
+        );
+
         let planned_core_real_spending = plan_real_spending(
             inputs,
             age,
@@ -492,26 +1696,67 @@ fn simulate_scenario(
             available_core_real,
             &mut spending_state,
         );
-        let planned_real_spending = planned_core_real_spending + mortgage_real_spending;
-
-        let sampled = sample_market(inputs, rng);
-        price_index *= 1.0 + sampled.inflation;
+        spending_state.prev_nominal_spending = planned_core_real_spending * price_index;
+        let planned_real_spending = planned_core_real_spending + fixed_real_spending;
 
         let planned_nominal_spending = planned_real_spending * price_index;
+
+        if annuity_income_real.is_none()
+            && inputs
+                .annuity_purchase_age
+                .is_some_and(|purchase_age| age >= purchase_age)
+        {
+            annuity_income_real = Some(buy_annuity(inputs, &mut portfolio, price_index));
+        }
+        let annuity_gross = annuity_income_real.unwrap_or(0.0) * price_index;
+
+        if pension_tax_free_remaining.is_none() && age >= inputs.pension_access_age {
+            pension_tax_free_remaining =
+                Some(portfolio.pension * inputs.pension_tax_free_pct.clamp(0.0, 1.0));
+        }
+        if partner_tax_free_remaining.is_none()
+            && inputs.household
+            && age >= inputs.partner_pension_access_age
+        {
+            partner_tax_free_remaining =
+                Some(portfolio.partner_pension * inputs.pension_tax_free_pct.clamp(0.0, 1.0));
+        }
+
+        let rental_gross = rental_gross_income(inputs, age, price_index);
+        let other_income_gross = state_pension_gross_income(inputs, age, price_index)
+            + annuity_gross
+            + rental_gross
+            + part_time_gross_income(inputs, age, price_index);
+        let other_income_net = net_income_after_tax(other_income_gross, inputs, threshold_index);
         let mut cgt_state = CgtState {
             allowance_remaining: inputs.capital_gains_allowance,
+            basic_band_remaining: cgt_basic_band_remaining(
+                inputs,
+                other_income_gross,
+                threshold_index,
+            ),
             tax_paid: 0.0,
         };
-
-        let state_pension_gross = state_pension_gross_income(inputs, age, price_index);
-        let state_pension_net = net_income_after_tax(state_pension_gross, inputs, price_index);
         let mut tax_state = TaxYearState {
-            non_pension_taxable_income: state_pension_gross,
+            non_pension_taxable_income: other_income_gross,
             pension_gross_withdrawn: 0.0,
-            price_index,
+            pension_tax_free_withdrawn: 0.0,
+            pension_tax_free_remaining: pension_tax_free_remaining.unwrap_or(0.0),
+            partner: PartnerTaxState {
+                pension_tax_free_remaining: partner_tax_free_remaining.unwrap_or(0.0),
+                ..PartnerTaxState::default()
+            },
+            price_index: threshold_index,
+        };
+        let pension_conversion =
+            apply_pension_conversion(inputs, age, &mut portfolio, &mut tax_state, price_index);
+        let ladder_funding_cgt = if age == retirement_age {
+            fund_bond_ladder_at_retirement(inputs, &mut portfolio, &mut cgt_state, price_index)
+        } else {
+            0.0
         };
 
-        let year_outcome = run_withdrawal_year(
+        let mut year_outcome = run_withdrawal_year(
             inputs,
             age,
             age.saturating_sub(retirement_age),
@@ -521,33 +1766,76 @@ fn simulate_scenario(
             &mut portfolio,
             &mut cgt_state,
             &mut tax_state,
-            state_pension_net,
+            other_income_net,
         );
+        year_outcome.cgt_tax_paid += ladder_funding_cgt;
+        if pension_tax_free_remaining.is_some() {
+            pension_tax_free_remaining = Some(tax_state.pension_tax_free_remaining);
+        }
+        if partner_tax_free_remaining.is_some() {
+            partner_tax_free_remaining = Some(tax_state.partner.pension_tax_free_remaining);
+        }
+        lifetime_tax_paid += year_outcome.total_tax_paid() / price_index.max(1e-9);
 
-        let required_real_spending = required_real_spending(inputs, age).max(1e-9);
+        let required_real_spending =
+            required_real_spending(inputs, retirement_age, age, price_index).max(1e-9);
         let income_ratio =
             (year_outcome.realized_spending_net / price_index) / required_real_spending;
         min_income_ratio = min_income_ratio.min(income_ratio);
         income_ratio_sum += income_ratio;
+        lifetime_utility += year_utility(
+            inputs,
+            years,
+            year_outcome.realized_spending_net / price_index.max(1e-9),
+        );
         years += 1;
 
-        let failed = year_outcome.realized_spending_net + 1e-9 < planned_nominal_spending;
+        let failed = match inputs.success_criterion {
+            SuccessCriterion::FullSpending => {
+                year_outcome.realized_spending_net + 1e-9 < planned_nominal_spending
+            }
+            // A shortfall while money is still locked away (e.g. a pension before its
+            // access age) is survived; only a shortfall with nothing left fails.
+            SuccessCriterion::NoDepletion => {
+                year_outcome.realized_spending_net + 1e-9 < planned_nominal_spending
+                    && portfolio.total() <= 1e-9
+            }
+            // With an essential spend, discretionary shortfalls above it are absorbed;
+            // only missing the essential amount fails the scenario.
+            SuccessCriterion::EssentialFloorMet => {
+                let failure_threshold_nominal = if has_essential_spending(inputs) {
+                    required_real_spending * price_index
+                } else {
+                    planned_nominal_spending
+                };
+                year_outcome.realized_spending_net + 1e-9 < failure_threshold_nominal
+            }
+        };
         if failed {
             if let Some(trace_rows) = trace.as_deref_mut() {
                 let deflator = price_index.max(1e-9);
                 trace_rows.push(YearTracePoint {
                     contribution_isa_real: 0.0,
+                    contribution_lisa_real: 0.0,
                     contribution_taxable_real: 0.0,
                     contribution_pension_real: 0.0,
+                    contribution_employer_real: 0.0,
                     contribution_total_real: 0.0,
                     withdrawal_portfolio_real: year_outcome.portfolio_withdrawn_net / deflator,
                     withdrawal_non_pension_income_real: year_outcome.non_pension_income_used
                         / deflator,
+                    annuity_income_real: annuity_gross / deflator,
+                    rental_income_real: rental_gross / deflator,
+                    pension_conversion_real: pension_conversion / deflator,
                     spending_total_real: year_outcome.realized_spending_net / deflator,
                     tax_cgt_real: year_outcome.cgt_tax_paid / deflator,
                     tax_income_real: year_outcome.income_tax_paid / deflator,
+                    tax_income_bands_real: tax_state
+                        .income_tax_bands(inputs)
+                        .scaled(1.0 / deflator),
                     tax_total_real: year_outcome.total_tax_paid() / deflator,
                     end_isa_real: 0.0,
+                    end_lisa_real: 0.0,
                     end_taxable_real: 0.0,
                     end_pension_real: 0.0,
                     end_cash_real: 0.0,
@@ -556,15 +1844,19 @@ fn simulate_scenario(
                 });
                 push_zero_trace_tail(trace_rows, age + 1, inputs.horizon_age);
             }
+            // The scenario stops here, so its remaining years score at the floor.
+            lifetime_utility += (years..years + inputs.horizon_age - age - 1)
+                .map(|year| year_utility(inputs, year, 0.0))
+                .sum::<f64>();
 
             return ScenarioResult {
                 success: false,
-                reported_retirement_total: retirement_total_real,
-                reported_retirement_isa: retirement_isa_real,
-                reported_retirement_taxable: retirement_taxable_real,
-                reported_retirement_pension: retirement_pension_real,
-                reported_retirement_cash: retirement_cash_real,
-                reported_retirement_bond_ladder: retirement_bond_ladder_real,
+                reported_retirement_total: at_retirement.total,
+                reported_retirement_isa: at_retirement.isa,
+                reported_retirement_taxable: at_retirement.taxable,
+                reported_retirement_pension: at_retirement.pension,
+                reported_retirement_cash: at_retirement.cash,
+                reported_retirement_bond_ladder: at_retirement.bond_ladder,
                 reported_terminal_total: 0.0,
                 reported_terminal_isa: 0.0,
                 reported_terminal_taxable: 0.0,
@@ -573,84 +1865,147 @@ fn simulate_scenario(
                 reported_terminal_bond_ladder: 0.0,
                 min_income_ratio,
                 avg_income_ratio: income_ratio_sum / years as f64,
+                lifetime_tax_paid,
+                max_drawdown: if peak_total_real > 0.0 { 1.0 } else { 0.0 },
+                min_runway_years,
+                retirement_years: years,
+                cumulative_inflation: price_index / retirement_price_index - 1.0,
+                worst_real_return: finite_or_zero(worst_real_return),
+                lifetime_utility,
             };
         }
 
-        let start_invested =
-            portfolio.isa + portfolio.taxable + portfolio.pension + portfolio.bond_ladder;
-        apply_post_retirement_growth(inputs, &mut portfolio, &sampled);
-        let end_invested =
-            portfolio.isa + portfolio.taxable + portfolio.pension + portfolio.bond_ladder;
+        let start_invested = portfolio.invested_total();
+        let dividend_tax = apply_post_retirement_growth(
+            inputs,
+            &mut portfolio,
+            &sampled,
+            age,
+            tax_state.taxable_income(),
+            threshold_index,
+        );
+        lifetime_tax_paid += dividend_tax / price_index.max(1e-9);
+        spending_state.cape = evolve_cape(inputs, spending_state.cape, &sampled);
+        let end_invested = portfolio.invested_total();
         prev_real_return = realized_real_return(start_invested, end_invested, sampled.inflation);
+        worst_real_return = worst_real_return.min(prev_real_return);
+
+        let end_total_real = portfolio.total() / price_index.max(1e-9);
+        peak_total_real = peak_total_real.max(end_total_real);
+        if peak_total_real > 0.0 {
+            max_drawdown = max_drawdown.max(1.0 - end_total_real / peak_total_real);
+        }
 
         if let Some(trace_rows) = trace.as_deref_mut() {
             let deflator = price_index.max(1e-9);
+            let end = portfolio.to_real(price_index);
             trace_rows.push(YearTracePoint {
                 contribution_isa_real: 0.0,
+                contribution_lisa_real: 0.0,
                 contribution_taxable_real: 0.0,
                 contribution_pension_real: 0.0,
+                contribution_employer_real: 0.0,
                 contribution_total_real: 0.0,
                 withdrawal_portfolio_real: year_outcome.portfolio_withdrawn_net / deflator,
                 withdrawal_non_pension_income_real: year_outcome.non_pension_income_used / deflator,
+                annuity_income_real: annuity_gross / deflator,
+                rental_income_real: rental_gross / deflator,
+                pension_conversion_real: pension_conversion / deflator,
                 spending_total_real: year_outcome.realized_spending_net / deflator,
                 tax_cgt_real: year_outcome.cgt_tax_paid / deflator,
-                tax_income_real: year_outcome.income_tax_paid / deflator,
-                tax_total_real: year_outcome.total_tax_paid() / deflator,
-                end_isa_real: portfolio.isa / deflator,
-                end_taxable_real: portfolio.taxable / deflator,
-                end_pension_real: portfolio.pension / deflator,
-                end_cash_real: portfolio.cash_buffer / deflator,
-                end_bond_ladder_real: portfolio.bond_ladder / deflator,
-                end_total_real: (portfolio.isa
-                    + portfolio.taxable
-                    + portfolio.pension
-                    + portfolio.cash_buffer
-                    + portfolio.bond_ladder)
-                    / deflator,
+                tax_income_real: (year_outcome.income_tax_paid + dividend_tax) / deflator,
+                tax_income_bands_real: tax_state.income_tax_bands(inputs).scaled(1.0 / deflator),
+                tax_total_real: (year_outcome.total_tax_paid() + dividend_tax) / deflator,
+                end_isa_real: end.isa,
+                end_lisa_real: end.lisa,
+                end_taxable_real: end.taxable,
+                end_pension_real: end.pension,
+                end_cash_real: end.cash,
+                end_bond_ladder_real: end.bond_ladder,
+                end_total_real: end.total,
             });
         }
     }
 
-    let inflation_deflator = price_index.max(1e-9);
-    let nominal_total = portfolio.isa
-        + portfolio.taxable
-        + portfolio.pension
-        + portfolio.cash_buffer
-        + portfolio.bond_ladder;
+    let terminal = portfolio.to_real(price_index);
+    // With no retirement years there is no spending to fund, so both ratios read as met
+    // rather than dividing by zero.
+    let (min_income_ratio, avg_income_ratio) = if years == 0 {
+        (1.0, 1.0)
+    } else {
+        (min_income_ratio, income_ratio_sum / years as f64)
+    };
 
     ScenarioResult {
-        success: true,
-        reported_retirement_total: retirement_total_real,
-        reported_retirement_isa: retirement_isa_real,
-        reported_retirement_taxable: retirement_taxable_real,
-        reported_retirement_pension: retirement_pension_real,
-        reported_retirement_cash: retirement_cash_real,
-        reported_retirement_bond_ladder: retirement_bond_ladder_real,
-        reported_terminal_total: nominal_total / inflation_deflator,
-        reported_terminal_isa: portfolio.isa / inflation_deflator,
-        reported_terminal_taxable: portfolio.taxable / inflation_deflator,
-        reported_terminal_pension: portfolio.pension / inflation_deflator,
-        reported_terminal_cash: portfolio.cash_buffer / inflation_deflator,
-        reported_terminal_bond_ladder: portfolio.bond_ladder / inflation_deflator,
+        success: terminal.total + 1e-9 >= inputs.bequest_target,
+        reported_retirement_total: at_retirement.total,
+        reported_retirement_isa: at_retirement.isa,
+        reported_retirement_taxable: at_retirement.taxable,
+        reported_retirement_pension: at_retirement.pension,
+        reported_retirement_cash: at_retirement.cash,
+        reported_retirement_bond_ladder: at_retirement.bond_ladder,
+        reported_terminal_total: terminal.total,
+        reported_terminal_isa: terminal.isa,
+        reported_terminal_taxable: terminal.taxable,
+        reported_terminal_pension: terminal.pension,
+        reported_terminal_cash: terminal.cash,
+        reported_terminal_bond_ladder: terminal.bond_ladder,
         min_income_ratio,
-        avg_income_ratio: income_ratio_sum / years as f64,
+        avg_income_ratio,
+        lifetime_tax_paid,
+        max_drawdown,
+        min_runway_years,
+        retirement_years: years,
+        cumulative_inflation: price_index / retirement_price_index - 1.0,
+        worst_real_return: finite_or_zero(worst_real_return),
+        lifetime_utility,
+    }
+}
+
+/// Discounted CRRA utility of `real_spending` in retirement year `year` (0 is the first),
+/// or zero when `risk_aversion` is unset.
+fn year_utility(inputs: &Inputs, year: u32, real_spending: f64) -> f64 {
+    let Some(gamma) = inputs.risk_aversion else {
+        return 0.0;
+    };
+    let discount = (1.0 + inputs.utility_discount_rate).powi(-(year as i32));
+    discount * crra_utility(real_spending.max(inputs.utility_spending_floor), gamma)
+}
+
+/// `c^(1 - gamma) / (1 - gamma)`, or `ln c` at `gamma = 1`.
+fn crra_utility(spending: f64, gamma: f64) -> f64 {
+    if (gamma - 1.0).abs() < 1e-9 {
+        spending.ln()
+    } else {
+        spending.powf(1.0 - gamma) / (1.0 - gamma)
     }
 }
 
+fn finite_or_zero(value: f64) -> f64 {
+    if value.is_finite() { value } else { 0.0 }
+}
+
 fn push_zero_trace_tail(trace: &mut Vec<YearTracePoint>, start_age: u32, horizon_age: u32) {
     for _ in start_age..horizon_age {
         trace.push(YearTracePoint {
             contribution_isa_real: 0.0,
+            contribution_lisa_real: 0.0,
             contribution_taxable_real: 0.0,
             contribution_pension_real: 0.0,
+            contribution_employer_real: 0.0,
             contribution_total_real: 0.0,
             withdrawal_portfolio_real: 0.0,
             withdrawal_non_pension_income_real: 0.0,
+            annuity_income_real: 0.0,
+            rental_income_real: 0.0,
+            pension_conversion_real: 0.0,
             spending_total_real: 0.0,
             tax_cgt_real: 0.0,
             tax_income_real: 0.0,
+            tax_income_bands_real: IncomeTaxBands::default(),
             tax_total_real: 0.0,
             end_isa_real: 0.0,
+            end_lisa_real: 0.0,
             end_taxable_real: 0.0,
             end_pension_real: 0.0,
             end_cash_real: 0.0,
@@ -660,85 +2015,453 @@ fn push_zero_trace_tail(trace: &mut Vec<YearTracePoint>, start_age: u32, horizon
     }
 }
 
-fn apply_pre_retirement_growth(inputs: &Inputs, portfolio: &mut Portfolio, sampled: &MarketSample) {
+/// Returns the dividend tax paid from the taxable pot.
+fn apply_pre_retirement_growth(
+    inputs: &Inputs,
+    portfolio: &mut Portfolio,
+    sampled: &MarketSample,
+    age: u32,
+    other_taxable_income: f64,
+    price_index: f64,
+) -> f64 {
+    let sampled = &glide_path_sample(inputs, sampled, age);
+    let dividends = (portfolio.taxable * inputs.taxable_dividend_yield).max(0.0);
     portfolio.isa = (portfolio.isa * (1.0 + sampled.isa_return)).max(0.0);
+    portfolio.lisa = (portfolio.lisa * (1.0 + sampled.isa_return)).max(0.0);
     portfolio.taxable = (portfolio.taxable * (1.0 + sampled.taxable_return)).max(0.0);
     portfolio.taxable *= 1.0 - inputs.taxable_return_tax_drag;
     portfolio.taxable = portfolio.taxable.max(0.0);
     portfolio.pension = (portfolio.pension * (1.0 + sampled.pension_return)).max(0.0);
-    portfolio.bond_ladder = (portfolio.bond_ladder * (1.0 + inputs.bond_ladder_yield)).max(0.0);
+    portfolio.partner_pension =
+        (portfolio.partner_pension * (1.0 + sampled.pension_return)).max(0.0);
+    apply_fund_fees(inputs, portfolio);
+    portfolio.bond_ladder = (portfolio.bond_ladder * (1.0 + sampled.bond_ladder_return)).max(0.0);
+    let tax = apply_dividend_tax(
+        inputs,
+        portfolio,
+        dividends,
+        other_taxable_income,
+        price_index,
+    );
     portfolio.taxable_basis = portfolio.taxable_basis.min(portfolio.taxable);
+    tax
+}
+
+/// Taxes the year's dividends (already inside the taxable return) and pays the tax from
+/// the taxable pot. The rest is treated as reinvested, so it adds to the cost basis.
+fn apply_dividend_tax(
+    inputs: &Inputs,
+    portfolio: &mut Portfolio,
+    dividends: f64,
+    other_taxable_income: f64,
+    price_index: f64,
+) -> f64 {
+    if dividends <= 0.0 {
+        return 0.0;
+    }
+    let tax =
+        dividend_tax(inputs, dividends, other_taxable_income, price_index).min(portfolio.taxable);
+    portfolio.taxable -= tax;
+    portfolio.taxable_basis += dividends - tax;
+    tax
 }
 
+/// Dividends sit on top of other taxable income. The allowance is taxed at 0% but still
+/// uses band space; the rest pays the dividend rate of the UK band it lands in.
+fn dividend_tax(
+    inputs: &Inputs,
+    dividends: f64,
+    other_taxable_income: f64,
+    price_index: f64,
+) -> f64 {
+    let taxed_dividends = (dividends - inputs.dividend_allowance.max(0.0)).max(0.0);
+    if taxed_dividends <= 0.0 {
+        return 0.0;
+    }
+    let start = other_taxable_income.max(0.0) + (dividends - taxed_dividends);
+    let end = start + taxed_dividends;
+
+    let transfer = marriage_allowance_moved(inputs, end, 0.0, price_index);
+    let allowance = tapered_personal_allowance(
+        end,
+        inputs,
+        price_index,
+        primary_personal_allowance(inputs, transfer),
+    );
+    let basic_limit = (inputs.uk_basic_rate_limit * price_index).max(allowance);
+    let higher_limit = (inputs.uk_higher_rate_limit * price_index).max(basic_limit);
+    let overlap = |lo: f64, hi: f64| (end.min(hi) - start.max(lo)).max(0.0);
+
+    overlap(allowance, basic_limit) * inputs.dividend_basic_rate.clamp(0.0, 1.0)
+        + overlap(basic_limit, higher_limit) * inputs.dividend_higher_rate.clamp(0.0, 1.0)
+        + overlap(higher_limit, f64::INFINITY) * inputs.dividend_additional_rate.clamp(0.0, 1.0)
+}
+
+/// Equity share of every pot at `age`, moving linearly from `glide_start_equity` at
+/// `current_age` to `glide_end_equity` at `glide_end_age` (default `horizon_age`).
+fn glide_equity_fraction(inputs: &Inputs, age: u32) -> f64 {
+    let end_age = inputs.glide_end_age.unwrap_or(inputs.horizon_age);
+    if end_age <= inputs.current_age {
+        return inputs.glide_end_equity;
+    }
+    let progress = (age.saturating_sub(inputs.current_age) as f64
+        / (end_age - inputs.current_age) as f64)
+        .min(1.0);
+    inputs.glide_start_equity + (inputs.glide_end_equity - inputs.glide_start_equity) * progress
+}
+
+/// Blends each pot's return with the year's bond return by the glide path's equity
+/// share. A fully invested glide returns the sample untouched.
+fn glide_path_sample(inputs: &Inputs, sampled: &MarketSample, age: u32) -> MarketSample {
+    let equity = glide_equity_fraction(inputs, age).clamp(0.0, 1.0);
+    if equity >= 1.0 {
+        return *sampled;
+    }
+    let blend = |pot_return: f64| equity * pot_return + (1.0 - equity) * sampled.bond_ladder_return;
+    MarketSample {
+        isa_return: blend(sampled.isa_return),
+        taxable_return: blend(sampled.taxable_return),
+        pension_return: blend(sampled.pension_return),
+        ..*sampled
+    }
+}
+
+fn apply_fund_fees(inputs: &Inputs, portfolio: &mut Portfolio) {
+    portfolio.isa = (portfolio.isa * (1.0 - inputs.isa_fee_rate)).max(0.0);
+    portfolio.lisa = (portfolio.lisa * (1.0 - inputs.isa_fee_rate)).max(0.0);
+    portfolio.taxable = (portfolio.taxable * (1.0 - inputs.taxable_fee_rate)).max(0.0);
+    portfolio.pension = (portfolio.pension * (1.0 - inputs.pension_fee_rate)).max(0.0);
+    portfolio.partner_pension =
+        (portfolio.partner_pension * (1.0 - inputs.pension_fee_rate)).max(0.0);
+}
+
+/// `price_index` is the index at the end of the year, when the contributions are paid.
+/// `fraction` scales the requested amounts: `1` while working full time, the coast fraction
+/// after the contribution stop age.
 fn apply_pre_retirement_contributions(
     inputs: &Inputs,
     portfolio: &mut Portfolio,
     years_since_start: u32,
+    price_index: f64,
+    fraction: f64,
 ) -> ContributionFlow {
-    let contribution_multiplier =
-        (1.0 + inputs.contribution_growth_rate).powi(years_since_start as i32);
+    let growth = if inputs.contributions_track_inflation {
+        price_index
+    } else {
+        (1.0 + inputs.contribution_growth_rate).powi(years_since_start as i32)
+    };
+    let contribution_multiplier = growth * fraction.max(0.0);
     let requested_isa_contribution = inputs.isa_annual_contribution * contribution_multiplier;
     let requested_taxable_contribution =
         inputs.taxable_annual_contribution * contribution_multiplier;
     let requested_pension_contribution =
         inputs.pension_annual_contribution * contribution_multiplier;
 
+    // The Lifetime ISA payment counts towards the overall ISA limit.
+    let lisa_contribution = if inputs.current_age + years_since_start < LISA_CONTRIBUTION_END_AGE {
+        (inputs.lisa_annual_contribution * contribution_multiplier).clamp(
+            0.0,
+            LISA_ANNUAL_LIMIT.min(inputs.isa_annual_contribution_limit.max(0.0)),
+        )
+    } else {
+        0.0
+    };
+    let lisa_bonus = lisa_contribution * LISA_BONUS_RATE;
+
     let isa_contribution = requested_isa_contribution
         .max(0.0)
-        .min(inputs.isa_annual_contribution_limit);
+        .min(inputs.isa_annual_contribution_limit - lisa_contribution);
     let overflow_to_taxable = (requested_isa_contribution - isa_contribution).max(0.0);
-    let taxable_contribution = requested_taxable_contribution.max(0.0) + overflow_to_taxable;
+
+    // Employer money uses the annual allowance first; own contributions fill what is left.
+    let requested_pension_contribution = requested_pension_contribution.max(0.0);
+    let pension_annual_allowance = inputs.pension_annual_allowance.max(0.0);
+    let employer_pension_contribution =
+        employer_match(inputs, requested_pension_contribution).min(pension_annual_allowance);
+    let pension_contribution = requested_pension_contribution
+        .min(pension_annual_allowance - employer_pension_contribution);
+    let pension_overflow_to_taxable = if inputs.pension_overflow_to_taxable {
+        requested_pension_contribution - pension_contribution
+    } else {
+        0.0
+    };
+
+    let taxable_contribution =
+        requested_taxable_contribution.max(0.0) + overflow_to_taxable + pension_overflow_to_taxable;
 
     portfolio.isa += isa_contribution;
+    portfolio.lisa += lisa_contribution + lisa_bonus;
     portfolio.taxable += taxable_contribution;
     portfolio.taxable_basis += taxable_contribution;
-    let pension_contribution = requested_pension_contribution.max(0.0);
-    portfolio.pension += pension_contribution;
+    portfolio.pension += pension_contribution + employer_pension_contribution;
 
     ContributionFlow {
         isa: isa_contribution,
+        lisa: lisa_contribution + lisa_bonus,
         taxable: taxable_contribution,
         pension: pension_contribution,
+        employer_pension: employer_pension_contribution,
     }
 }
 
+/// Employer top-up on the member's own pension contribution, limited to the cap.
+fn employer_match(inputs: &Inputs, pension_contribution: f64) -> f64 {
+    let matched = (pension_contribution * inputs.employer_match_rate).max(0.0);
+    match inputs.employer_match_cap {
+        Some(cap) => matched.min(cap.max(0.0)),
+        None => matched,
+    }
+}
+
+/// Returns the dividend tax paid from the taxable pot.
 fn apply_post_retirement_growth(
     inputs: &Inputs,
     portfolio: &mut Portfolio,
     sampled: &MarketSample,
-) {
+    age: u32,
+    other_taxable_income: f64,
+    price_index: f64,
+) -> f64 {
+    let sampled = &glide_path_sample(inputs, sampled, age);
+    let dividends = (portfolio.taxable * inputs.taxable_dividend_yield).max(0.0);
     portfolio.isa = (portfolio.isa * (1.0 + sampled.isa_return)).max(0.0);
+    portfolio.lisa = (portfolio.lisa * (1.0 + sampled.isa_return)).max(0.0);
     portfolio.taxable = (portfolio.taxable * (1.0 + sampled.taxable_return)).max(0.0);
     portfolio.taxable *= 1.0 - inputs.taxable_return_tax_drag;
     portfolio.taxable = portfolio.taxable.max(0.0);
     portfolio.pension = (portfolio.pension * (1.0 + sampled.pension_return)).max(0.0);
+    portfolio.partner_pension =
+        (portfolio.partner_pension * (1.0 + sampled.pension_return)).max(0.0);
+    apply_fund_fees(inputs, portfolio);
     portfolio.cash_buffer = (portfolio.cash_buffer * (1.0 + inputs.cash_growth_rate)).max(0.0);
-    portfolio.bond_ladder = (portfolio.bond_ladder * (1.0 + inputs.bond_ladder_yield)).max(0.0);
+    portfolio.bond_ladder = (portfolio.bond_ladder * (1.0 + sampled.bond_ladder_return)).max(0.0);
+    let tax = apply_dividend_tax(
+        inputs,
+        portfolio,
+        dividends,
+        other_taxable_income,
+        price_index,
+    );
     portfolio.taxable_basis = portfolio.taxable_basis.min(portfolio.taxable);
+    tax
+}
+
+/// Real spending range for the year. Library callers can skip `validate_inputs`, so an
+/// inverted floor and ceiling are swapped and negative bounds clamp to zero instead of
+/// handing `f64::clamp` an inverted range.
+fn spending_bounds(inputs: &Inputs, smile_multiplier: f64) -> (f64, f64) {
+    let target_real_spending = (inputs.target_annual_income * smile_multiplier).max(0.0);
+    let floor_ratio = inputs.min_income_floor.min(inputs.max_income_ceiling);
+    let ceiling_ratio = inputs.max_income_ceiling.max(inputs.min_income_floor);
+    let relative_min = if has_essential_spending(inputs) {
+        inputs.essential_annual_income
+    } else {
+        target_real_spending * floor_ratio
+    };
+    let min_real_spending = relative_min.max(inputs.min_real_spending_floor).max(0.0);
+    let max_real_spending = (target_real_spending * ceiling_ratio).max(min_real_spending);
+    (min_real_spending, max_real_spending)
 }
 
-fn spending_bounds(inputs: &Inputs) -> (f64, f64) {
-    let min_real_spending = inputs.target_annual_income * inputs.min_income_floor;
-    let max_real_spending = inputs.target_annual_income * inputs.max_income_ceiling;
-    (min_real_spending, max_real_spending.max(min_real_spending))
+/// Real debt service due in the year at `age`: the amortizing mortgage plus every fixed
+/// debt that has not yet ended. `price_index` is the price level when the year's
+/// mortgage payment is made.
+fn debt_payment_real(inputs: &Inputs, age: u32, price_index: f64) -> f64 {
+    amortized_mortgage_real(inputs, age, price_index)
+        + inputs
+            .all_debts()
+            .filter(|debt| age < debt.end_age)
+            .map(|debt| debt.annual_payment.max(0.0))
+            .sum::<f64>()
 }
 
-fn mortgage_payment_real(inputs: &Inputs, age: u32) -> f64 {
-    if inputs.mortgage_annual_payment <= 0.0 {
-        return 0.0;
+/// Level nominal payment that clears `mortgage_balance` over `mortgage_term_years`:
+/// `B r / (1 - (1 + r)^-n)`, or `B / n` at a zero rate.
+fn mortgage_level_payment(inputs: &Inputs) -> f64 {
+    let balance = inputs.mortgage_balance.max(0.0);
+    let years = f64::from(inputs.mortgage_term_years.max(1));
+    let rate = inputs.mortgage_rate;
+    if rate.abs() < 1e-12 {
+        balance / years
+    } else {
+        balance * rate / (1.0 - (1.0 + rate).powf(-years))
+    }
+}
+
+/// Expected price level at the end of mortgage year `year`, when that year's payment is
+/// made; amounts in today's money scale by it.
+fn mortgage_price_level(inputs: &Inputs, year: u32) -> f64 {
+    (1.0 + inputs.inflation_mean).powi(year as i32 + 1)
+}
+
+/// Nominal overpayments made at the end of mortgage year `year`.
+fn mortgage_overpayment_nominal(inputs: &Inputs, year: u32) -> f64 {
+    let age = inputs.current_age + year;
+    inputs
+        .mortgage_overpayments
+        .iter()
+        .filter(|(overpayment_age, _)| *overpayment_age == age)
+        .map(|(_, amount)| amount.max(0.0))
+        .sum::<f64>()
+        * mortgage_price_level(inputs, year)
+}
+
+/// Nominal balance owed at the start of mortgage year `year` (0 is `current_age`). Each
+/// year interest accrues on the balance, then the payment and any overpayment come off
+/// at the year end: `B_k = B g^k - P (g^k - 1) / r - sum O_j g^(k - 1 - j)` with
+/// `g = 1 + r`. Zero or negative once the mortgage is cleared.
+fn mortgage_balance_nominal(inputs: &Inputs, year: u32) -> f64 {
+    let rate = inputs.mortgage_rate;
+    let growth = 1.0 + rate;
+    let compounded = growth.powi(year as i32);
+    let paid = if rate.abs() < 1e-12 {
+        f64::from(year)
+    } else {
+        (compounded - 1.0) / rate
+    } * mortgage_level_payment(inputs);
+    let overpaid: f64 = inputs
+        .mortgage_overpayments
+        .iter()
+        .filter_map(|&(age, amount)| {
+            let j = age.checked_sub(inputs.current_age).filter(|&j| j < year)?;
+            Some(
+                amount.max(0.0)
+                    * mortgage_price_level(inputs, j)
+                    * growth.powi((year - 1 - j) as i32),
+            )
+        })
+        .sum();
+    inputs.mortgage_balance.max(0.0) * compounded - paid - overpaid
+}
+
+/// Nominal `(payment, overpayment)` made at the end of mortgage year `year`: the level
+/// payment (or what is left to clear) and any overpayment, capped at the rest owed.
+fn mortgage_payments_nominal(inputs: &Inputs, year: u32) -> (f64, f64) {
+    if inputs.mortgage_balance <= 0.0 {
+        return (0.0, 0.0);
     }
-    let Some(end_age) = inputs.mortgage_end_age else {
+    let balance = mortgage_balance_nominal(inputs, year);
+    if balance <= inputs.mortgage_balance * 1e-9 {
+        return (0.0, 0.0);
+    }
+    let owed = balance * (1.0 + inputs.mortgage_rate);
+    let payment = mortgage_level_payment(inputs).min(owed);
+    let overpayment = mortgage_overpayment_nominal(inputs, year).min(owed - payment);
+    (payment, overpayment)
+}
+
+/// Real cost of the amortizing mortgage in the year at `age`, deflated by the
+/// scenario's `price_index` because the payments are fixed in pounds.
+fn amortized_mortgage_real(inputs: &Inputs, age: u32, price_index: f64) -> f64 {
+    let Some(year) = age.checked_sub(inputs.current_age) else {
+        return 0.0;
+    };
+    let (payment, overpayment) = mortgage_payments_nominal(inputs, year);
+    (payment + overpayment) / price_index.max(1e-9)
+}
+
+/// Nominal pre-retirement earnings in a year ending at `price_index`.
+fn pre_retirement_income(inputs: &Inputs, price_index: f64) -> f64 {
+    inputs.pre_retirement_earnings.max(0.0) * price_index
+}
+
+/// Pays a mortgage overpayment due before retirement out of the portfolio: cash first,
+/// then the ISA, then taxable holdings sold net of CGT. Earnings are assumed to cover
+/// the level payment, and gains use whatever basic-rate band `pre_retirement_earnings`
+/// leave. Any part the pots cannot cover is taken to come from earnings. Returns the CGT
+/// paid.
+fn fund_pre_retirement_overpayment(
+    inputs: &Inputs,
+    portfolio: &mut Portfolio,
+    year: u32,
+    price_index: f64,
+    threshold_index: f64,
+) -> f64 {
+    let (_, overpayment) = mortgage_payments_nominal(inputs, year);
+    if overpayment <= 0.0 {
         return 0.0;
+    }
+    let from_cash = overpayment.min(portfolio.cash_buffer.max(0.0));
+    portfolio.cash_buffer -= from_cash;
+    let from_isa = (overpayment - from_cash).min(portfolio.isa.max(0.0));
+    portfolio.isa -= from_isa;
+    let mut cgt_state = CgtState {
+        allowance_remaining: inputs.capital_gains_allowance,
+        basic_band_remaining: cgt_basic_band_remaining(
+            inputs,
+            pre_retirement_income(inputs, price_index),
+            threshold_index,
+        ),
+        tax_paid: 0.0,
+    };
+    withdraw_from_taxable_for_net(
+        overpayment - from_cash - from_isa,
+        &mut portfolio.taxable,
+        &mut portfolio.taxable_basis,
+        &mut cgt_state,
+        CgtRates::from_inputs(inputs),
+    );
+    cgt_state.tax_paid
+}
+
+/// Age curve applied to `target_annual_income`: a real decline per year of retirement,
+/// stepped up from `care_cost_age` for late-life care, times the spending phase.
+fn spending_smile_multiplier(inputs: &Inputs, retirement_age: u32, age: u32) -> f64 {
+    let years_retired = age.saturating_sub(retirement_age);
+    let decline = (1.0 - inputs.spending_decline_rate.clamp(0.0, 1.0)).powi(years_retired as i32);
+    let care = if age >= inputs.care_cost_age {
+        1.0 + inputs.care_cost_uplift.max(0.0)
+    } else {
+        1.0
     };
-    if age < end_age {
-        inputs.mortgage_annual_payment.max(0.0)
+    decline * care * spending_phase_multiplier(inputs, age)
+}
+
+/// Go-go, slow-go and no-go phases: the active uplift before `active_phase_end_age`,
+/// the plain target in between, and the late uplift from `late_phase_start_age`.
+fn spending_phase_multiplier(inputs: &Inputs, age: u32) -> f64 {
+    let uplift = if age < inputs.active_phase_end_age {
+        inputs.active_phase_uplift
+    } else if age >= inputs.late_phase_start_age {
+        inputs.late_phase_uplift
     } else {
         0.0
-    }
+    };
+    (1.0 + uplift).max(0.0)
+}
+
+/// Moves rule-based spending onto this year's point of the smile so strategy adjustments
+/// compound on top of the age curve rather than being reset by it.
+fn rebase_spending_smile(spending_state: &mut SpendingState, smile_multiplier: f64) {
+    let ratio = smile_multiplier / spending_state.smile_multiplier.max(1e-9);
+    spending_state.current_real_spending *= ratio;
+    spending_state.prev_nominal_spending *= ratio;
+    spending_state.smile_multiplier = smile_multiplier;
+}
+
+fn lump_expense_real(inputs: &Inputs, age: u32) -> f64 {
+    inputs
+        .lump_expenses
+        .iter()
+        .filter(|(expense_age, _)| *expense_age == age)
+        .map(|(_, amount)| amount.max(0.0))
+        .sum()
 }
 
-fn required_real_spending(inputs: &Inputs, age: u32) -> f64 {
-    inputs.target_annual_income + mortgage_payment_real(inputs, age)
+fn has_essential_spending(inputs: &Inputs) -> bool {
+    inputs.essential_annual_income > 0.0
+}
+
+/// Real spending a year must deliver to count as funded: the essential spend when one is
+/// set, otherwise the (smiled) target. Mortgage payments and lump expenses are always
+/// required; `price_index` deflates the fixed-pound mortgage.
+fn required_real_spending(inputs: &Inputs, retirement_age: u32, age: u32, price_index: f64) -> f64 {
+    let core = if has_essential_spending(inputs) {
+        inputs.essential_annual_income
+    } else {
+        inputs.target_annual_income * spending_smile_multiplier(inputs, retirement_age, age)
+    };
+    core + debt_payment_real(inputs, age, price_index) + lump_expense_real(inputs, age)
 }
 
 fn available_spendable_real(
@@ -749,9 +2472,12 @@ fn available_spendable_real(
 ) -> f64 {
     let mut total =
         portfolio.cash_buffer + portfolio.isa + portfolio.taxable + portfolio.bond_ladder;
-    if age >= inputs.pension_access_age {
-        total += portfolio.pension;
-    }
+    total += if age >= inputs.lisa_access_age {
+        portfolio.lisa
+    } else {
+        portfolio.lisa * (1.0 - LISA_EARLY_WITHDRAWAL_PENALTY)
+    };
+    total += PensionPots::accessible(inputs, age, portfolio).total();
     total / price_index.max(1e-9)
 }
 
@@ -773,6 +2499,19 @@ fn annuity_withdrawal_rate(real_return: f64, years_remaining: u32) -> f64 {
     }
 }
 
+fn cape_withdrawal_rate(inputs: &Inputs, cape: f64) -> f64 {
+    (inputs.cape_a + inputs.cape_b / cape.max(1e-9)).clamp(0.0, 1.0)
+}
+
+/// Moves CAPE with the year's ISA (equity proxy) real return relative to trend earnings
+/// growth, then pulls it part of the way back toward its long-run mean.
+fn evolve_cape(inputs: &Inputs, cape: f64, sampled: &MarketSample) -> f64 {
+    let equity_real_return = (1.0 + sampled.isa_return) / (1.0 + sampled.inflation) - 1.0;
+    let drifted = cape * (1.0 + equity_real_return).max(0.0) / (1.0 + CAPE_EARNINGS_REAL_GROWTH);
+    let speed = inputs.cape_reversion_speed.clamp(0.0, 1.0);
+    (drifted + speed * (inputs.cape_long_run_mean - drifted)).max(1e-6)
+}
+
 fn plan_real_spending(
     inputs: &Inputs,
     age: u32,
@@ -780,7 +2519,8 @@ fn plan_real_spending(
     available_real: f64,
     spending_state: &mut SpendingState,
 ) -> f64 {
-    let (min_real_spending, max_real_spending) = spending_bounds(inputs);
+    let (min_real_spending, max_real_spending) =
+        spending_bounds(inputs, spending_state.smile_multiplier);
     let mut spending_real = match inputs.withdrawal_strategy {
         WithdrawalStrategy::Guardrails => {
             let mut spending = spending_state.current_real_spending;
@@ -793,17 +2533,29 @@ fn plan_real_spending(
         }
         WithdrawalStrategy::GuytonKlinger => {
             let mut spending = spending_state.current_real_spending;
-            let current_wr = spending / available_real.max(1e-9);
-            let lower_guardrail =
-                spending_state.initial_withdrawal_rate * inputs.gk_lower_guardrail;
-            let upper_guardrail =
-                spending_state.initial_withdrawal_rate * inputs.gk_upper_guardrail;
+            // Decision rules only kick in once there is a prior withdrawal to adjust.
+            if spending_state.prev_nominal_spending > 0.0 {
+                let portfolio_real = available_real.max(1e-9);
+                let initial_wr = spending_state.initial_withdrawal_rate;
+
+                // Withdrawal rule: skip the inflation raise (holding nominal spending flat)
+                // after a losing year while the withdrawal rate is above its starting level.
+                if prev_real_return < 0.0 && spending / portfolio_real > initial_wr {
+                    spending =
+                        spending_state.prev_nominal_spending / spending_state.price_index.max(1e-9);
+                }
 
-            if prev_real_return < inputs.bad_year_threshold && current_wr > upper_guardrail {
-                spending *= 1.0 - inputs.bad_year_cut;
-            } else if prev_real_return > inputs.good_year_threshold && current_wr < lower_guardrail
-            {
-                spending *= 1.0 + inputs.good_year_raise;
+                let current_wr = spending / portfolio_real;
+                let years_remaining = inputs.horizon_age.saturating_sub(age);
+                if current_wr > initial_wr * inputs.gk_upper_guardrail
+                    && years_remaining > GK_CAPITAL_PRESERVATION_SUNSET_YEARS
+                {
+                    // Capital preservation rule.
+                    spending *= 1.0 - inputs.gk_adjustment;
+                } else if current_wr < initial_wr * inputs.gk_lower_guardrail {
+                    // Prosperity rule.
+                    spending *= 1.0 + inputs.gk_adjustment;
+                }
             }
             spending
         }
@@ -833,9 +2585,24 @@ fn plan_real_spending(
             }
             spending
         }
+        WithdrawalStrategy::FixedReal => spending_state.current_real_spending,
+        WithdrawalStrategy::ConstantPercentage => {
+            available_real.max(0.0) * inputs.constant_withdrawal_rate.clamp(0.0, 1.0)
+        }
+        WithdrawalStrategy::CapeBased => {
+            available_real.max(0.0) * cape_withdrawal_rate(inputs, spending_state.cape)
+        }
+        WithdrawalStrategy::Amortization => {
+            available_real.max(0.0) / remaining_life_expectancy(age)
+        }
     };
 
-    spending_real = spending_real.clamp(min_real_spending, max_real_spending);
+    // Percentage-of-portfolio spending floats with the balance: forcing it up to the
+    // income floor would reintroduce the depletion failures the strategy is meant to
+    // avoid, and capping it at the ceiling would stop it being `rate * balance`.
+    if inputs.withdrawal_strategy != WithdrawalStrategy::ConstantPercentage {
+        spending_real = spending_real.clamp(min_real_spending, max_real_spending);
+    }
     spending_state.current_real_spending = spending_real;
     spending_real
 }
@@ -878,6 +2645,20 @@ fn run_withdrawal_year(
     realized += ladder_scheduled;
     portfolio_withdrawn_total += ladder_scheduled;
 
+    // Guyton-Klinger portfolio management rule: after a losing year, fund spending from
+    // the bond ladder rather than selling growth assets at depressed prices.
+    if inputs.withdrawal_strategy == WithdrawalStrategy::GuytonKlinger && prev_real_return < 0.0 {
+        let ladder_defensive = withdraw_from_bond_ladder_for_net(
+            inputs,
+            retirement_year_index,
+            (planned_nominal_spending - realized).max(0.0),
+            &mut portfolio.bond_ladder,
+            false,
+        );
+        realized += ladder_defensive;
+        portfolio_withdrawn_total += ladder_defensive;
+    }
+
     let needed = (planned_nominal_spending - realized).max(0.0);
     let main_withdrawn = withdraw_from_portfolio(
         inputs,
@@ -886,7 +2667,7 @@ fn run_withdrawal_year(
         portfolio,
         cgt_state,
         tax_state,
-        inputs.post_access_withdrawal_order,
+        withdrawal_order_for_age(inputs, age),
     );
     realized += main_withdrawn;
     portfolio_withdrawn_total += main_withdrawn;
@@ -904,10 +2685,12 @@ fn run_withdrawal_year(
     portfolio_withdrawn_total += ladder_backstop;
 
     if prev_real_return > inputs.good_year_threshold {
+        let cash_floor_years = inputs.min_cash_months.max(0.0) / 12.0;
         let extra = match inputs.withdrawal_strategy {
             WithdrawalStrategy::Bucket => {
                 let spending_for_bucket = planned_nominal_spending.max(planned_real_spending);
-                let target_cash = spending_for_bucket * inputs.bucket_target_years.max(0.0);
+                let target_cash =
+                    spending_for_bucket * inputs.bucket_target_years.max(cash_floor_years).max(0.0);
                 let shortfall = (target_cash - portfolio.cash_buffer).max(0.0);
                 let refill_cap =
                     spending_for_bucket * inputs.good_year_extra_buffer_withdrawal.max(0.0);
@@ -917,7 +2700,13 @@ fn run_withdrawal_year(
                     shortfall
                 }
             }
-            _ => planned_nominal_spending * inputs.good_year_extra_buffer_withdrawal.max(0.0),
+            _ => {
+                let buffer_extra =
+                    planned_nominal_spending * inputs.good_year_extra_buffer_withdrawal.max(0.0);
+                let floor_shortfall =
+                    (planned_nominal_spending * cash_floor_years - portfolio.cash_buffer).max(0.0);
+                buffer_extra.max(floor_shortfall)
+            }
         };
 
         if extra > 0.0 {
@@ -928,17 +2717,14 @@ fn run_withdrawal_year(
                 portfolio,
                 cgt_state,
                 tax_state,
-                inputs.post_access_withdrawal_order,
+                withdrawal_order_for_age(inputs, age),
             );
             portfolio.cash_buffer += extra_withdrawn;
             portfolio_withdrawn_total += extra_withdrawn;
         }
     }
 
-    let total_gross_income =
-        tax_state.non_pension_taxable_income + tax_state.pension_gross_withdrawn;
-    let income_tax_paid =
-        income_tax_for_total_income(total_gross_income, inputs, tax_state.price_index);
+    let income_tax_paid = tax_state.income_tax(inputs);
     let cgt_tax_paid = (cgt_state.tax_paid - starting_cgt_tax_paid).max(0.0);
 
     WithdrawalYearOutcome {
@@ -962,7 +2748,9 @@ fn withdraw_from_bond_ladder_for_net(
     }
 
     let max_available = if scheduled && inputs.bond_ladder_years > 0 {
-        if retirement_year_index >= inputs.bond_ladder_years {
+        if inputs.bond_ladder_reinvest {
+            *bond_ladder / inputs.bond_ladder_years as f64
+        } else if retirement_year_index >= inputs.bond_ladder_years {
             *bond_ladder
         } else {
             let years_left = (inputs.bond_ladder_years - retirement_year_index).max(1) as f64;
@@ -990,37 +2778,73 @@ fn withdraw_from_portfolio(
         return 0.0;
     }
 
-    let pension_access = age >= inputs.pension_access_age;
+    let pension_access = pension_accessible(inputs, age);
 
     if order == WithdrawalOrder::ProRata {
-        return withdraw_pro_rata(
-            inputs,
-            pension_access,
-            target_net,
-            portfolio,
-            cgt_state,
-            tax_state,
-        );
+        let realized = withdraw_pro_rata(inputs, age, target_net, portfolio, cgt_state, tax_state);
+        return realized
+            + withdraw_from_lisa_early(inputs, age, target_net - realized, &mut portfolio.lisa);
     }
 
+    // The Lifetime ISA follows the ISA once it can be drawn without penalty; before that
+    // it is only touched as a last resort, below.
     let sequence: &[PotKind] = if !pension_access {
         match order {
-            WithdrawalOrder::BondLadderFirst => {
-                &[PotKind::BondLadder, PotKind::Isa, PotKind::Taxable]
-            }
-            _ => &[PotKind::Isa, PotKind::Taxable],
-        }
+            WithdrawalOrder::TaxableFirst => &[PotKind::Taxable, PotKind::Isa, PotKind::Lisa],
+            WithdrawalOrder::BondLadderFirst => &[
+                PotKind::BondLadder,
+                PotKind::Isa,
+                PotKind::Lisa,
+                PotKind::Taxable,
+            ],
+            WithdrawalOrder::HarvestCgtAllowance => &[
+                PotKind::TaxableWithinAllowance,
+                PotKind::Isa,
+                PotKind::Lisa,
+                PotKind::Taxable,
+            ],
+            _ => &[PotKind::Isa, PotKind::Lisa, PotKind::Taxable],
+        }
     } else {
         match order {
-            WithdrawalOrder::IsaFirst => &[PotKind::Isa, PotKind::Taxable, PotKind::Pension],
-            WithdrawalOrder::TaxableFirst => &[PotKind::Taxable, PotKind::Isa, PotKind::Pension],
-            WithdrawalOrder::PensionFirst => &[PotKind::Pension, PotKind::Taxable, PotKind::Isa],
+            WithdrawalOrder::IsaFirst => &[
+                PotKind::Isa,
+                PotKind::Lisa,
+                PotKind::Taxable,
+                PotKind::Pension,
+            ],
+            WithdrawalOrder::TaxableFirst => &[
+                PotKind::Taxable,
+                PotKind::Isa,
+                PotKind::Lisa,
+                PotKind::Pension,
+            ],
+            WithdrawalOrder::PensionFirst => &[
+                PotKind::Pension,
+                PotKind::Taxable,
+                PotKind::Isa,
+                PotKind::Lisa,
+            ],
             WithdrawalOrder::BondLadderFirst => &[
                 PotKind::BondLadder,
                 PotKind::Isa,
+                PotKind::Lisa,
+                PotKind::Taxable,
+                PotKind::Pension,
+            ],
+            WithdrawalOrder::FillBasicRate => &[
+                PotKind::Isa,
+                PotKind::Lisa,
                 PotKind::Taxable,
                 PotKind::Pension,
             ],
+            WithdrawalOrder::HarvestCgtAllowance => &[
+                PotKind::TaxableWithinAllowance,
+                PotKind::Isa,
+                PotKind::Lisa,
+                PotKind::Pension,
+                PotKind::Taxable,
+            ],
             WithdrawalOrder::ProRata => unreachable!(),
         }
     };
@@ -1028,33 +2852,66 @@ fn withdraw_from_portfolio(
     let mut realized = 0.0;
     let mut remaining = target_net;
 
+    if order == WithdrawalOrder::FillBasicRate && pension_access {
+        let withdrawn =
+            withdraw_pension_within_basic_rate_band(inputs, age, remaining, portfolio, tax_state);
+        realized += withdrawn;
+        remaining -= withdrawn;
+    }
+
     for pot in sequence {
         if remaining <= 0.0 {
             break;
         }
 
         let withdrawn = withdraw_from_single_pot(
-            inputs,
-            *pot,
-            remaining,
-            pension_access,
-            portfolio,
-            cgt_state,
-            tax_state,
+            inputs, *pot, remaining, age, portfolio, cgt_state, tax_state,
         );
 
         realized += withdrawn;
         remaining -= withdrawn;
     }
 
-    realized
+    realized + withdraw_from_lisa_early(inputs, age, remaining, &mut portfolio.lisa)
+}
+
+/// The order in force at `age`: `pre_access_withdrawal_order` until either pension opens,
+/// `post_access_withdrawal_order` from then on. Without a pre-access order, orders that
+/// lead with a pension fall back to ISA first, as does taxable-first.
+fn withdrawal_order_for_age(inputs: &Inputs, age: u32) -> WithdrawalOrder {
+    let post_access = inputs.post_access_withdrawal_order;
+    if pension_accessible(inputs, age) {
+        return post_access;
+    }
+    inputs
+        .pre_access_withdrawal_order
+        .unwrap_or(match post_access {
+            WithdrawalOrder::TaxableFirst
+            | WithdrawalOrder::PensionFirst
+            | WithdrawalOrder::FillBasicRate => WithdrawalOrder::IsaFirst,
+            order => order,
+        })
+}
+
+/// Draws on the Lifetime ISA before `lisa_access_age`, grossing up for the penalty.
+fn withdraw_from_lisa_early(inputs: &Inputs, age: u32, target_net: f64, lisa: &mut f64) -> f64 {
+    if target_net <= 0.0 || *lisa <= 0.0 || age >= inputs.lisa_access_age {
+        return 0.0;
+    }
+    let kept = 1.0 - LISA_EARLY_WITHDRAWAL_PENALTY;
+    let gross = (target_net / kept).min(*lisa);
+    *lisa -= gross;
+    gross * kept
 }
 
 #[derive(Copy, Clone)]
 enum PotKind {
     BondLadder,
     Isa,
+    Lisa,
     Taxable,
+    /// The taxable pot, only as far as its gains stay within the CGT allowance.
+    TaxableWithinAllowance,
     Pension,
 }
 
@@ -1062,7 +2919,7 @@ fn withdraw_from_single_pot(
     inputs: &Inputs,
     pot: PotKind,
     target_net: f64,
-    pension_access: bool,
+    age: u32,
     portfolio: &mut Portfolio,
     cgt_state: &mut CgtState,
     tax_state: &mut TaxYearState,
@@ -1078,30 +2935,45 @@ fn withdraw_from_single_pot(
             portfolio.isa -= x;
             x
         }
-        PotKind::Pension => {
-            if !pension_access {
+        PotKind::Lisa => {
+            if age < inputs.lisa_access_age {
                 return 0.0;
             }
-            withdraw_from_pension_for_net(target_net, &mut portfolio.pension, inputs, tax_state)
+            let x = portfolio.lisa.min(target_net);
+            portfolio.lisa -= x;
+            x
+        }
+        PotKind::Pension => {
+            let pots = PensionPots::accessible(inputs, age, portfolio);
+            withdraw_from_pension_for_net(target_net, portfolio, pots, inputs, tax_state)
         }
         PotKind::Taxable => withdraw_from_taxable_for_net(
             target_net,
             &mut portfolio.taxable,
             &mut portfolio.taxable_basis,
             cgt_state,
-            inputs.capital_gains_tax_rate,
+            CgtRates::from_inputs(inputs),
+        ),
+        PotKind::TaxableWithinAllowance => withdraw_from_taxable_within_allowance(
+            target_net,
+            &mut portfolio.taxable,
+            &mut portfolio.taxable_basis,
+            cgt_state,
+            CgtRates::from_inputs(inputs),
         ),
     }
 }
 
 fn withdraw_pro_rata(
     inputs: &Inputs,
-    pension_access: bool,
+    age: u32,
     target_net: f64,
     portfolio: &mut Portfolio,
     cgt_state: &mut CgtState,
     tax_state: &mut TaxYearState,
 ) -> f64 {
+    let pension_access = pension_accessible(inputs, age);
+    let lisa_access = age >= inputs.lisa_access_age;
     let mut realized = 0.0;
     let mut remaining = target_net;
 
@@ -1111,28 +2983,36 @@ fn withdraw_pro_rata(
         }
 
         let isa_balance = portfolio.isa.max(0.0);
+        let lisa_balance = if lisa_access {
+            portfolio.lisa.max(0.0)
+        } else {
+            0.0
+        };
         let ladder_balance = portfolio.bond_ladder.max(0.0);
         let taxable_balance = net_from_taxable_gross(
             portfolio.taxable,
             portfolio.taxable,
             portfolio.taxable_basis,
-            cgt_state.allowance_remaining,
-            inputs.capital_gains_tax_rate,
+            cgt_state,
+            CgtRates::from_inputs(inputs),
         )
         .max(0.0);
 
         let pension_balance = if pension_access {
-            net_from_additional_pension_gross(portfolio.pension, tax_state, inputs).max(0.0)
+            let pots = PensionPots::accessible(inputs, age, portfolio);
+            net_from_additional_pension_gross(pots.total(), pots, tax_state, inputs).max(0.0)
         } else {
             0.0
         };
 
-        let total_capacity = isa_balance + taxable_balance + pension_balance + ladder_balance;
+        let total_capacity =
+            isa_balance + lisa_balance + taxable_balance + pension_balance + ladder_balance;
         if total_capacity <= 1e-9 {
             break;
         }
 
         let isa_target = remaining * (isa_balance / total_capacity);
+        let lisa_target = remaining * (lisa_balance / total_capacity);
         let ladder_target = remaining * (ladder_balance / total_capacity);
         let pension_target = remaining * (pension_balance / total_capacity);
         let taxable_target = remaining * (taxable_balance / total_capacity);
@@ -1142,7 +3022,7 @@ fn withdraw_pro_rata(
             inputs,
             PotKind::BondLadder,
             ladder_target,
-            pension_access,
+            age,
             portfolio,
             cgt_state,
             tax_state,
@@ -1152,7 +3032,17 @@ fn withdraw_pro_rata(
             inputs,
             PotKind::Isa,
             isa_target,
-            pension_access,
+            age,
+            portfolio,
+            cgt_state,
+            tax_state,
+        );
+
+        round_realized += withdraw_from_single_pot(
+            inputs,
+            PotKind::Lisa,
+            lisa_target,
+            age,
             portfolio,
             cgt_state,
             tax_state,
@@ -1163,7 +3053,7 @@ fn withdraw_pro_rata(
                 inputs,
                 PotKind::Pension,
                 pension_target,
-                pension_access,
+                age,
                 portfolio,
                 cgt_state,
                 tax_state,
@@ -1174,7 +3064,7 @@ fn withdraw_pro_rata(
             inputs,
             PotKind::Taxable,
             taxable_target,
-            pension_access,
+            age,
             portfolio,
             cgt_state,
             tax_state,
@@ -1191,12 +3081,18 @@ fn withdraw_pro_rata(
     let fallback: &[PotKind] = if pension_access {
         &[
             PotKind::Isa,
+            PotKind::Lisa,
             PotKind::Pension,
             PotKind::Taxable,
             PotKind::BondLadder,
         ]
     } else {
-        &[PotKind::Isa, PotKind::Taxable, PotKind::BondLadder]
+        &[
+            PotKind::Isa,
+            PotKind::Lisa,
+            PotKind::Taxable,
+            PotKind::BondLadder,
+        ]
     };
 
     for pot in fallback {
@@ -1205,13 +3101,7 @@ fn withdraw_pro_rata(
         }
 
         let withdrawn = withdraw_from_single_pot(
-            inputs,
-            *pot,
-            remaining,
-            pension_access,
-            portfolio,
-            cgt_state,
-            tax_state,
+            inputs, *pot, remaining, age, portfolio, cgt_state, tax_state,
         );
         realized += withdrawn;
         remaining -= withdrawn;
@@ -1222,26 +3112,27 @@ fn withdraw_pro_rata(
 
 fn withdraw_from_pension_for_net(
     target_net: f64,
-    pension_gross: &mut f64,
+    portfolio: &mut Portfolio,
+    pots: PensionPots,
     inputs: &Inputs,
     tax_state: &mut TaxYearState,
 ) -> f64 {
-    if target_net <= 0.0 || *pension_gross <= 0.0 {
+    if target_net <= 0.0 || pots.total() <= 0.0 {
         return 0.0;
     }
 
-    let max_net = net_from_additional_pension_gross(*pension_gross, tax_state, inputs);
+    let max_net = net_from_additional_pension_gross(pots.total(), pots, tax_state, inputs);
     let desired_net = target_net.min(max_net);
     if desired_net <= 0.0 {
         return 0.0;
     }
 
     let mut lo = 0.0;
-    let mut hi = *pension_gross;
+    let mut hi = pots.total();
 
     for _ in 0..40 {
         let mid = (lo + hi) * 0.5;
-        let net_mid = net_from_additional_pension_gross(mid, tax_state, inputs);
+        let net_mid = net_from_additional_pension_gross(mid, pots, tax_state, inputs);
         if net_mid < desired_net {
             lo = mid;
         } else {
@@ -1249,15 +3140,143 @@ fn withdraw_from_pension_for_net(
         }
     }
 
-    let gross_withdrawn = hi.min(*pension_gross);
-    let net = net_from_additional_pension_gross(gross_withdrawn, tax_state, inputs);
-    *pension_gross -= gross_withdrawn;
-    tax_state.pension_gross_withdrawn += gross_withdrawn;
+    let gross_withdrawn = hi.min(pots.total());
+    let net = net_from_additional_pension_gross(gross_withdrawn, pots, tax_state, inputs);
+    take_pension_gross(gross_withdrawn, portfolio, pots, tax_state);
     net
 }
 
+/// Debits `gross` from the accessible pension pots and records it, tax-free portion
+/// first, against each owner's tax-year state.
+fn take_pension_gross(
+    gross: f64,
+    portfolio: &mut Portfolio,
+    pots: PensionPots,
+    tax_state: &mut TaxYearState,
+) {
+    let (primary_gross, partner_gross) = split_pension_gross(gross, pots, tax_state);
+
+    let tax_free = primary_gross.min(tax_state.pension_tax_free_remaining.max(0.0));
+    portfolio.pension -= primary_gross;
+    tax_state.pension_gross_withdrawn += primary_gross;
+    tax_state.pension_tax_free_withdrawn += tax_free;
+    tax_state.pension_tax_free_remaining -= tax_free;
+
+    let partner = &mut tax_state.partner;
+    let partner_tax_free = partner_gross.min(partner.pension_tax_free_remaining.max(0.0));
+    portfolio.partner_pension -= partner_gross;
+    partner.pension_gross_withdrawn += partner_gross;
+    partner.pension_tax_free_withdrawn += partner_tax_free;
+    partner.pension_tax_free_remaining -= partner_tax_free;
+}
+
+/// Moves up to `conversion_ladder_annual_amount` (real) of accessible pension into the
+/// ISA, up to the subscription limit, and the taxable account beyond it. The gross
+/// counts towards this tax year's income. Returns the nominal gross converted.
+fn apply_pension_conversion(
+    inputs: &Inputs,
+    age: u32,
+    portfolio: &mut Portfolio,
+    tax_state: &mut TaxYearState,
+    price_index: f64,
+) -> f64 {
+    if inputs.conversion_ladder_annual_amount <= 0.0
+        || age < inputs.conversion_ladder_start_age
+        || age >= inputs.conversion_ladder_end_age
+    {
+        return 0.0;
+    }
+    let pots = PensionPots::accessible(inputs, age, portfolio);
+    let gross = (inputs.conversion_ladder_annual_amount * price_index).min(pots.total());
+    if gross <= 0.0 {
+        return 0.0;
+    }
+
+    let net = net_from_additional_pension_gross(gross, pots, tax_state, inputs);
+    take_pension_gross(gross, portfolio, pots, tax_state);
+    let to_isa = net.min(inputs.isa_annual_contribution_limit.max(0.0));
+    let to_taxable = net - to_isa;
+    portfolio.isa += to_isa;
+    portfolio.taxable += to_taxable;
+    portfolio.taxable_basis += to_taxable;
+    gross
+}
+
+/// Draws pension only until taxable income reaches `uk_basic_rate_limit`, leaving the
+/// rest of `target_net` for the other pots.
+fn withdraw_pension_within_basic_rate_band(
+    inputs: &Inputs,
+    age: u32,
+    target_net: f64,
+    portfolio: &mut Portfolio,
+    tax_state: &mut TaxYearState,
+) -> f64 {
+    let pots = PensionPots::accessible(inputs, age, portfolio);
+    let gross_cap = basic_rate_pension_headroom(pots, tax_state, inputs);
+    let net_cap = net_from_additional_pension_gross(gross_cap, pots, tax_state, inputs);
+    withdraw_from_pension_for_net(target_net.min(net_cap), portfolio, pots, inputs, tax_state)
+}
+
+/// Largest extra pension gross that keeps each drawing person's taxable income within
+/// the indexed basic-rate limit. Tax-free lump sum draws do not use up the band.
+fn basic_rate_pension_headroom(
+    pots: PensionPots,
+    tax_state: &TaxYearState,
+    inputs: &Inputs,
+) -> f64 {
+    let limit = (inputs.uk_basic_rate_limit * tax_state.price_index).max(0.0);
+    let within_band = |share: f64, taxable_income: f64, tax_free_remaining: f64| {
+        share <= 0.0 || taxable_income + share - share.min(tax_free_remaining.max(0.0)) <= limit
+    };
+    let fits = |gross: f64| {
+        let (primary, partner) = split_pension_gross(gross, pots, tax_state);
+        within_band(
+            primary,
+            tax_state.taxable_income(),
+            tax_state.pension_tax_free_remaining,
+        ) && within_band(
+            partner,
+            tax_state.partner_taxable_income(),
+            tax_state.partner.pension_tax_free_remaining,
+        )
+    };
+
+    if fits(pots.total()) {
+        return pots.total();
+    }
+    let mut lo = 0.0;
+    let mut hi = pots.total();
+    for _ in 0..60 {
+        let mid = (lo + hi) * 0.5;
+        if fits(mid) {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+/// Splits extra pension gross between the two pots so both people's taxable incomes end
+/// as level as the pots allow, filling the lower earner's allowance and bands first.
+/// Without a partner pot everything comes from the primary pension.
+fn split_pension_gross(
+    additional_gross: f64,
+    pots: PensionPots,
+    tax_state: &TaxYearState,
+) -> (f64, f64) {
+    let gross = additional_gross.clamp(0.0, pots.total());
+    let gap = tax_state.taxable_income() - tax_state.partner_taxable_income();
+    let partner = ((gross + gap) * 0.5)
+        .clamp(0.0, gross)
+        .min(pots.partner)
+        .max(gross - pots.primary);
+    (gross - partner, partner)
+}
+
 fn net_from_additional_pension_gross(
     additional_gross: f64,
+    pots: PensionPots,
     tax_state: &TaxYearState,
     inputs: &Inputs,
 ) -> f64 {
@@ -1265,31 +3284,176 @@ fn net_from_additional_pension_gross(
         return 0.0;
     }
 
-    let before_income = tax_state.non_pension_taxable_income + tax_state.pension_gross_withdrawn;
-    let after_income = before_income + additional_gross;
+    let (primary_gross, partner_gross) = split_pension_gross(additional_gross, pots, tax_state);
+    let price_index = tax_state.price_index;
+    let primary_net = net_from_person_pension_gross(
+        primary_gross,
+        tax_state.taxable_income(),
+        tax_state.pension_tax_free_remaining,
+        |income| {
+            income_tax_for_total_income(
+                income,
+                tax_state.partner_taxable_income(),
+                inputs,
+                price_index,
+            )
+        },
+    );
+    let partner_net = net_from_person_pension_gross(
+        partner_gross,
+        tax_state.partner_taxable_income(),
+        tax_state.partner.pension_tax_free_remaining,
+        |income| partner_income_tax(income, tax_state.taxable_income(), inputs, price_index),
+    );
+    primary_net + partner_net
+}
+
+fn net_from_person_pension_gross(
+    additional_gross: f64,
+    taxable_income: f64,
+    tax_free_remaining: f64,
+    income_tax: impl Fn(f64) -> f64,
+) -> f64 {
+    if additional_gross <= 0.0 {
+        return 0.0;
+    }
 
-    let before_tax = income_tax_for_total_income(before_income, inputs, tax_state.price_index);
-    let after_tax = income_tax_for_total_income(after_income, inputs, tax_state.price_index);
-    let incremental_tax = (after_tax - before_tax).max(0.0);
+    // The tax-free lump sum is used up before any of the withdrawal is taxed.
+    let tax_free = additional_gross.min(tax_free_remaining.max(0.0));
+    let after_income = taxable_income + additional_gross - tax_free;
+    let incremental_tax = (income_tax(after_income) - income_tax(taxable_income)).max(0.0);
 
     (additional_gross - incremental_tax).max(0.0)
 }
 
-fn income_tax_for_total_income(total_income: f64, inputs: &Inputs, price_index: f64) -> f64 {
+/// Either person's pension is open: the primary's from `pension_access_age`, the
+/// partner's from `partner_pension_access_age` in household mode.
+fn pension_accessible(inputs: &Inputs, age: u32) -> bool {
+    age >= inputs.pension_access_age
+        || (inputs.household && age >= inputs.partner_pension_access_age)
+}
+
+/// UK thresholds keep their nominal values until `thresholds_frozen_until_age`, so
+/// inflation in earlier years never reaches them (fiscal drag).
+fn tax_thresholds_indexed(inputs: &Inputs, age: u32) -> bool {
+    inputs
+        .thresholds_frozen_until_age
+        .is_none_or(|frozen_until| age >= frozen_until)
+}
+
+/// The primary person's income tax; `partner_income` only decides whether the marriage
+/// allowance moves.
+fn income_tax_for_total_income(
+    total_income: f64,
+    partner_income: f64,
+    inputs: &Inputs,
+    price_index: f64,
+) -> f64 {
+    let transfer = marriage_allowance_moved(inputs, total_income, partner_income, price_index);
+    income_tax_with_allowance(
+        total_income,
+        inputs,
+        price_index,
+        primary_personal_allowance(inputs, transfer),
+    )
+}
+
+/// The household partner's income tax: the same bands and rates on their own allowance.
+fn partner_income_tax(
+    total_income: f64,
+    primary_income: f64,
+    inputs: &Inputs,
+    price_index: f64,
+) -> f64 {
+    if total_income <= 0.0 {
+        return 0.0;
+    }
+    let transfer = marriage_allowance_moved(inputs, primary_income, total_income, price_index);
+    income_tax_with_allowance(
+        total_income,
+        inputs,
+        price_index,
+        partner_personal_allowance(inputs, transfer),
+    )
+}
+
+/// Marriage allowance that actually moves in a year, in today's money. The recipient
+/// must not pay tax above the basic rate (the intermediate rate under Scottish bands)
+/// and the transferor's income must stay under their own personal allowance.
+fn marriage_allowance_moved(
+    inputs: &Inputs,
+    primary_income: f64,
+    partner_income: f64,
+    price_index: f64,
+) -> f64 {
+    let basic_rate_limit = match inputs.pension_tax_mode {
+        PensionTaxMode::ScottishBands => inputs.scottish_intermediate_rate_limit,
+        PensionTaxMode::UkBands | PensionTaxMode::FlatRate => inputs.uk_basic_rate_limit,
+    };
+    let recipient_eligible = primary_income <= basic_rate_limit * price_index;
+    let transferor_eligible = partner_income < inputs.partner_personal_allowance * price_index;
+    if recipient_eligible && transferor_eligible {
+        inputs.marriage_allowance_transfer.max(0.0)
+    } else {
+        0.0
+    }
+}
+
+/// The primary person's allowance, including any marriage allowance received.
+fn primary_personal_allowance(inputs: &Inputs, transfer: f64) -> f64 {
+    inputs.uk_personal_allowance + transfer
+}
+
+/// The partner's allowance less any marriage allowance given away.
+fn partner_personal_allowance(inputs: &Inputs, transfer: f64) -> f64 {
+    (inputs.partner_personal_allowance - transfer).max(0.0)
+}
+
+fn income_tax_with_allowance(
+    total_income: f64,
+    inputs: &Inputs,
+    price_index: f64,
+    personal_allowance: f64,
+) -> f64 {
     let gross = total_income.max(0.0);
     match inputs.pension_tax_mode {
         PensionTaxMode::FlatRate => gross * inputs.pension_flat_tax_rate.clamp(0.0, 1.0),
-        PensionTaxMode::UkBands => uk_income_tax(gross, inputs, price_index),
+        PensionTaxMode::UkBands => uk_income_tax(gross, inputs, price_index, personal_allowance),
+        PensionTaxMode::ScottishBands => {
+            scottish_income_tax(gross, inputs, price_index, personal_allowance)
+        }
     }
 }
 
-fn uk_income_tax(gross_income: f64, inputs: &Inputs, price_index: f64) -> f64 {
-    let gross = gross_income.max(0.0);
+fn income_tax_bands_with_allowance(
+    total_income: f64,
+    inputs: &Inputs,
+    price_index: f64,
+    personal_allowance: f64,
+) -> IncomeTaxBands {
+    let gross = total_income.max(0.0);
+    match inputs.pension_tax_mode {
+        PensionTaxMode::FlatRate => IncomeTaxBands::default(),
+        PensionTaxMode::UkBands => {
+            uk_income_tax_bands(gross, inputs, price_index, personal_allowance)
+        }
+        PensionTaxMode::ScottishBands => {
+            scottish_income_tax_bands(gross, inputs, price_index, personal_allowance)
+        }
+    }
+}
 
+/// Personal allowance after the high-income taper, in the year's nominal terms.
+fn tapered_personal_allowance(
+    gross: f64,
+    inputs: &Inputs,
+    price_index: f64,
+    personal_allowance: f64,
+) -> f64 {
     let taper_start = (inputs.uk_allowance_taper_start * price_index).max(0.0);
     let taper_end = (inputs.uk_allowance_taper_end * price_index).max(taper_start);
 
-    let mut allowance = (inputs.uk_personal_allowance * price_index).max(0.0);
+    let mut allowance = (personal_allowance * price_index).max(0.0);
     if gross > taper_start {
         let reduction = (gross - taper_start) / 2.0;
         allowance = (allowance - reduction).max(0.0);
@@ -1297,7 +3461,39 @@ fn uk_income_tax(gross_income: f64, inputs: &Inputs, price_index: f64) -> f64 {
     if gross >= taper_end {
         allowance = 0.0;
     }
+    allowance
+}
+
+fn uk_income_tax(
+    gross_income: f64,
+    inputs: &Inputs,
+    price_index: f64,
+    personal_allowance: f64,
+) -> f64 {
+    let gross = gross_income.max(0.0);
+    let allowance = tapered_personal_allowance(gross, inputs, price_index, personal_allowance);
+    uk_band_taxes(gross, allowance, inputs, price_index).total()
+}
+
+fn uk_income_tax_bands(
+    gross_income: f64,
+    inputs: &Inputs,
+    price_index: f64,
+    personal_allowance: f64,
+) -> IncomeTaxBands {
+    let gross = gross_income.max(0.0);
+    let allowance = tapered_personal_allowance(gross, inputs, price_index, personal_allowance);
+    let full_allowance = (personal_allowance * price_index).max(0.0);
+    let untapered = uk_band_taxes(gross, full_allowance, inputs, price_index);
+    let bands = uk_band_taxes(gross, allowance, inputs, price_index);
+    IncomeTaxBands {
+        taper_effect: (bands.total() - untapered.total()).max(0.0),
+        ..bands
+    }
+}
 
+/// Tax in each UK band on `gross` after `allowance` (already in nominal terms).
+fn uk_band_taxes(gross: f64, allowance: f64, inputs: &Inputs, price_index: f64) -> IncomeTaxBands {
     let taxable_income = (gross - allowance).max(0.0);
 
     let basic_limit = (inputs.uk_basic_rate_limit * price_index).max(0.0);
@@ -1312,22 +3508,183 @@ fn uk_income_tax(gross_income: f64, inputs: &Inputs, price_index: f64) -> f64 {
         .max(0.0);
     let additional_taxable = (taxable_income - basic_taxable - higher_taxable).max(0.0);
 
-    basic_taxable * inputs.uk_basic_rate.clamp(0.0, 1.0)
-        + higher_taxable * inputs.uk_higher_rate.clamp(0.0, 1.0)
-        + additional_taxable * inputs.uk_additional_rate.clamp(0.0, 1.0)
+    IncomeTaxBands {
+        basic: basic_taxable * inputs.uk_basic_rate.clamp(0.0, 1.0),
+        higher: higher_taxable * inputs.uk_higher_rate.clamp(0.0, 1.0),
+        additional: additional_taxable * inputs.uk_additional_rate.clamp(0.0, 1.0),
+        taper_effect: 0.0,
+    }
+}
+
+/// Same allowance and band-width convention as `uk_income_tax`: the first band runs from
+/// the allowance to its limit, each later band from the previous limit to its own, and
+/// income above the advanced limit pays the top rate.
+fn scottish_income_tax(
+    gross_income: f64,
+    inputs: &Inputs,
+    price_index: f64,
+    personal_allowance: f64,
+) -> f64 {
+    let gross = gross_income.max(0.0);
+    let allowance = tapered_personal_allowance(gross, inputs, price_index, personal_allowance);
+    scottish_band_taxes(gross, allowance, inputs, price_index).total()
+}
+
+fn scottish_income_tax_bands(
+    gross_income: f64,
+    inputs: &Inputs,
+    price_index: f64,
+    personal_allowance: f64,
+) -> IncomeTaxBands {
+    let gross = gross_income.max(0.0);
+    let allowance = tapered_personal_allowance(gross, inputs, price_index, personal_allowance);
+    let full_allowance = (personal_allowance * price_index).max(0.0);
+    let untapered = scottish_band_taxes(gross, full_allowance, inputs, price_index);
+    let bands = scottish_band_taxes(gross, allowance, inputs, price_index);
+    IncomeTaxBands {
+        taper_effect: (bands.total() - untapered.total()).max(0.0),
+        ..bands
+    }
+}
+
+/// Tax in each Scottish band on `gross` after `allowance`, grouped as `IncomeTaxBands`.
+fn scottish_band_taxes(
+    gross: f64,
+    allowance: f64,
+    inputs: &Inputs,
+    price_index: f64,
+) -> IncomeTaxBands {
+    let bands = [
+        (
+            inputs.scottish_starter_rate_limit,
+            inputs.scottish_starter_rate,
+        ),
+        (inputs.scottish_basic_rate_limit, inputs.scottish_basic_rate),
+        (
+            inputs.scottish_intermediate_rate_limit,
+            inputs.scottish_intermediate_rate,
+        ),
+        (
+            inputs.scottish_higher_rate_limit,
+            inputs.scottish_higher_rate,
+        ),
+        (
+            inputs.scottish_advanced_rate_limit,
+            inputs.scottish_advanced_rate,
+        ),
+        (f64::INFINITY, inputs.scottish_top_rate),
+    ];
+
+    let mut remaining = (gross - allowance).max(0.0);
+    let mut band_floor = allowance;
+    let mut tax = IncomeTaxBands::default();
+    for (index, (limit, rate)) in bands.into_iter().enumerate() {
+        if remaining <= 0.0 {
+            break;
+        }
+        let band_top = (limit * price_index).max(band_floor);
+        let taxed = remaining.min(band_top - band_floor);
+        let band_tax = taxed * rate.clamp(0.0, 1.0);
+        match index {
+            0..=2 => tax.basic += band_tax,
+            3 => tax.higher += band_tax,
+            _ => tax.additional += band_tax,
+        }
+        remaining -= taxed;
+        band_floor = band_top;
+    }
+    tax
+}
+
+/// Tops the bond ladder up to `bond_ladder_target_at_retirement` by selling the ISA
+/// first, then taxable holdings sized so the ladder receives the amount after CGT.
+/// Returns the CGT paid.
+fn fund_bond_ladder_at_retirement(
+    inputs: &Inputs,
+    portfolio: &mut Portfolio,
+    cgt_state: &mut CgtState,
+    price_index: f64,
+) -> f64 {
+    let shortfall =
+        (inputs.bond_ladder_target_at_retirement * price_index - portfolio.bond_ladder).max(0.0);
+    if shortfall <= 0.0 {
+        return 0.0;
+    }
+    let from_isa = shortfall.min(portfolio.isa.max(0.0));
+    portfolio.isa -= from_isa;
+    let tax_before = cgt_state.tax_paid;
+    let from_taxable = withdraw_from_taxable_for_net(
+        shortfall - from_isa,
+        &mut portfolio.taxable,
+        &mut portfolio.taxable_basis,
+        cgt_state,
+        CgtRates::from_inputs(inputs),
+    );
+    portfolio.bond_ladder += from_isa + from_taxable;
+    cgt_state.tax_paid - tax_before
+}
+
+/// Moves the purchase amount (capped at the pot) out of the pension and returns the real
+/// annuity income it buys.
+fn buy_annuity(inputs: &Inputs, portfolio: &mut Portfolio, price_index: f64) -> f64 {
+    let price = (inputs.annuity_purchase_amount.max(0.0) * price_index).min(portfolio.pension);
+    portfolio.pension -= price;
+    price / price_index.max(1e-9) * inputs.annuity_rate.max(0.0)
 }
 
+/// Guaranteed taxable income for the year: the state pension plus any defined-benefit
+/// pension, each from its own start age. The state pension compounds its real growth
+/// from its start age, or from today when it grows while deferred. Deferring the claim
+/// delays payment and compounds the uplift once per deferred year.
 fn state_pension_gross_income(inputs: &Inputs, age: u32, price_index: f64) -> f64 {
-    if age < inputs.state_pension_start_age {
+    let deferral_years = inputs.state_pension_deferral_years;
+    let state = if age
+        < inputs
+            .state_pension_start_age
+            .saturating_add(deferral_years)
+    {
         0.0
     } else {
-        (inputs.state_pension_annual_income * price_index).max(0.0)
+        let growth_from = if inputs.state_pension_growth_before_start {
+            inputs.current_age
+        } else {
+            inputs.state_pension_start_age
+        };
+        let years = age.saturating_sub(growth_from) as i32;
+        let uplift = (1.0 + inputs.state_pension_deferral_uplift_rate).powi(deferral_years as i32);
+        let real = inputs.state_pension_annual_income
+            * uplift
+            * (1.0 + inputs.state_pension_real_growth).powi(years);
+        (real * price_index).max(0.0)
+    };
+    let db = if age < inputs.db_pension_start_age {
+        0.0
+    } else {
+        (inputs.db_pension_annual_income * price_index).max(0.0)
+    };
+    state + db
+}
+
+fn rental_gross_income(inputs: &Inputs, age: u32, price_index: f64) -> f64 {
+    if age < inputs.rental_income_start_age {
+        return 0.0;
+    }
+    let years = age.saturating_sub(inputs.current_age) as i32;
+    let real = inputs.rental_annual_income * (1.0 + inputs.rental_growth_rate).powi(years);
+    (real * price_index).max(0.0)
+}
+
+fn part_time_gross_income(inputs: &Inputs, age: u32, price_index: f64) -> f64 {
+    if (inputs.part_time_start_age..inputs.part_time_end_age).contains(&age) {
+        (inputs.part_time_annual_income * price_index).max(0.0)
+    } else {
+        0.0
     }
 }
 
 fn net_income_after_tax(gross_income: f64, inputs: &Inputs, price_index: f64) -> f64 {
     let gross = gross_income.max(0.0);
-    let tax = income_tax_for_total_income(gross, inputs, price_index);
+    let tax = income_tax_for_total_income(gross, 0.0, inputs, price_index);
     (gross - tax).max(0.0)
 }
 
@@ -1336,7 +3693,7 @@ fn withdraw_from_taxable_for_net(
     taxable_value: &mut f64,
     taxable_basis: &mut f64,
     cgt_state: &mut CgtState,
-    cgt_rate: f64,
+    cgt_rates: CgtRates,
 ) -> f64 {
     if target_net <= 0.0 || *taxable_value <= 0.0 {
         return 0.0;
@@ -1346,8 +3703,8 @@ fn withdraw_from_taxable_for_net(
         *taxable_value,
         *taxable_value,
         *taxable_basis,
-        cgt_state.allowance_remaining,
-        cgt_rate,
+        cgt_state,
+        cgt_rates,
     );
 
     let desired_net = target_net.min(max_net);
@@ -1360,13 +3717,8 @@ fn withdraw_from_taxable_for_net(
 
     for _ in 0..40 {
         let mid = (lo + hi) * 0.5;
-        let net_mid = net_from_taxable_gross(
-            mid,
-            *taxable_value,
-            *taxable_basis,
-            cgt_state.allowance_remaining,
-            cgt_rate,
-        );
+        let net_mid =
+            net_from_taxable_gross(mid, *taxable_value, *taxable_basis, cgt_state, cgt_rates);
 
         if net_mid < desired_net {
             lo = mid;
@@ -1376,15 +3728,71 @@ fn withdraw_from_taxable_for_net(
     }
 
     let gross = hi.min(*taxable_value);
-    execute_taxable_sale(gross, taxable_value, taxable_basis, cgt_state, cgt_rate)
+    execute_taxable_sale(gross, taxable_value, taxable_basis, cgt_state, cgt_rates)
+}
+
+/// Sells only as much of the taxable pot as realizes gains within the remaining CGT
+/// allowance, so the sale pays no tax and net equals gross.
+fn withdraw_from_taxable_within_allowance(
+    target_net: f64,
+    taxable_value: &mut f64,
+    taxable_basis: &mut f64,
+    cgt_state: &mut CgtState,
+    cgt_rates: CgtRates,
+) -> f64 {
+    if target_net <= 0.0 {
+        return 0.0;
+    }
+    let gross = target_net.min(allowance_harvest_gross(
+        *taxable_value,
+        *taxable_basis,
+        cgt_state,
+    ));
+    execute_taxable_sale(gross, taxable_value, taxable_basis, cgt_state, cgt_rates)
+}
+
+/// Gross sale whose pro-rata gain exactly uses the remaining CGT allowance, or the whole
+/// pot when its unrealized gain fits inside it.
+fn allowance_harvest_gross(taxable_value: f64, taxable_basis: f64, cgt_state: &CgtState) -> f64 {
+    if taxable_value <= 0.0 {
+        return 0.0;
+    }
+    let unrealized_gain = taxable_value - taxable_basis.max(0.0);
+    let allowance = cgt_state.allowance_remaining.max(0.0);
+    if unrealized_gain <= allowance {
+        return taxable_value;
+    }
+    allowance * taxable_value / unrealized_gain
+}
+
+/// Basic-rate band left once `other_taxable_income` has used its share, in the year's
+/// nominal terms. Gains stack on top of income, so only this headroom gets the basic rate.
+fn cgt_basic_band_remaining(inputs: &Inputs, other_taxable_income: f64, price_index: f64) -> f64 {
+    let income = other_taxable_income.max(0.0);
+    let transfer = marriage_allowance_moved(inputs, income, 0.0, price_index);
+    let allowance = tapered_personal_allowance(
+        income,
+        inputs,
+        price_index,
+        primary_personal_allowance(inputs, transfer),
+    );
+    let basic_limit = (inputs.uk_basic_rate_limit * price_index).max(allowance);
+    (basic_limit - income.max(allowance)).max(0.0)
+}
+
+/// CGT on a gain after the annual exemption: the basic rate up to `basic_band_remaining`,
+/// the higher rate above it.
+fn cgt_on_taxable_gain(taxable_gain: f64, basic_band_remaining: f64, cgt_rates: CgtRates) -> f64 {
+    let basic_gain = taxable_gain.min(basic_band_remaining.max(0.0));
+    basic_gain * cgt_rates.basic.max(0.0) + (taxable_gain - basic_gain) * cgt_rates.higher.max(0.0)
 }
 
 fn net_from_taxable_gross(
     gross_sale: f64,
     value_before: f64,
     basis_before: f64,
-    allowance_remaining: f64,
-    cgt_rate: f64,
+    cgt_state: &CgtState,
+    cgt_rates: CgtRates,
 ) -> f64 {
     if gross_sale <= 0.0 || value_before <= 0.0 {
         return 0.0;
@@ -1397,9 +3805,9 @@ fn net_from_taxable_gross(
         return gross;
     }
 
-    let allowance_used = allowance_remaining.max(0.0).min(realized_gain);
+    let allowance_used = cgt_state.allowance_remaining.max(0.0).min(realized_gain);
     let taxable_gain = (realized_gain - allowance_used).max(0.0);
-    let tax = taxable_gain * cgt_rate.max(0.0);
+    let tax = cgt_on_taxable_gain(taxable_gain, cgt_state.basic_band_remaining, cgt_rates);
     (gross - tax).max(0.0)
 }
 
@@ -1408,7 +3816,7 @@ fn execute_taxable_sale(
     taxable_value: &mut f64,
     taxable_basis: &mut f64,
     cgt_state: &mut CgtState,
-    cgt_rate: f64,
+    cgt_rates: CgtRates,
 ) -> f64 {
     if gross_sale <= 0.0 || *taxable_value <= 0.0 {
         return 0.0;
@@ -1432,7 +3840,8 @@ fn execute_taxable_sale(
     cgt_state.allowance_remaining = (cgt_state.allowance_remaining - allowance_used).max(0.0);
 
     let taxable_gain = (realized_gain - allowance_used).max(0.0);
-    let tax = taxable_gain * cgt_rate.max(0.0);
+    let tax = cgt_on_taxable_gain(taxable_gain, cgt_state.basic_band_remaining, cgt_rates);
+    cgt_state.basic_band_remaining = (cgt_state.basic_band_remaining - taxable_gain).max(0.0);
     cgt_state.tax_paid += tax;
     (gross - tax).max(0.0)
 }
@@ -1446,30 +3855,462 @@ fn realized_real_return(start_invested: f64, end_invested: f64, inflation: f64)
     ((1.0 + nominal_return) / (1.0 + inflation)) - 1.0
 }
 
+/// ISA/taxable/pension return correlation matrix assembled from the pairwise inputs.
+fn return_correlation_matrix(inputs: &Inputs) -> [[f64; 3]; 3] {
+    if let Some(betas) = inputs.return_factor_betas {
+        // Pot i's shock is beta_i * f + sqrt(1 - beta_i^2) * e_i, so two pots share
+        // only the common factor and correlate at beta_i * beta_j.
+        let betas = betas.map(|beta| beta.clamp(-1.0, 1.0));
+        return std::array::from_fn(|i| {
+            std::array::from_fn(|j| if i == j { 1.0 } else { betas[i] * betas[j] })
+        });
+    }
+    let isa_taxable = inputs.isa_taxable_correlation.clamp(-1.0, 1.0);
+    let isa_pension = inputs.return_correlation.clamp(-1.0, 1.0);
+    let taxable_pension = inputs
+        .taxable_pension_correlation
+        .unwrap_or(isa_taxable * isa_pension)
+        .clamp(-1.0, 1.0);
+    [
+        [1.0, isa_taxable, isa_pension],
+        [isa_taxable, 1.0, taxable_pension],
+        [isa_pension, taxable_pension, 1.0],
+    ]
+}
+
+/// Lower-triangular Cholesky factor that tolerates singular (semidefinite) matrices by
+/// zeroing the column below a zero pivot. Non-PSD input yields a factor that does not
+/// reproduce the matrix; `return_correlation_cholesky` checks for that.
+fn lower_cholesky(matrix: &[[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let mut lower = [[0.0; 3]; 3];
+    for i in 0..3 {
+        for j in 0..=i {
+            let dot: f64 = (0..j).map(|k| lower[i][k] * lower[j][k]).sum();
+            if i == j {
+                lower[i][i] = (matrix[i][i] - dot).max(0.0).sqrt();
+            } else if lower[j][j] > 1e-12 {
+                lower[i][j] = (matrix[i][j] - dot) / lower[j][j];
+            }
+        }
+    }
+    lower
+}
+
+/// Cholesky factor of the return correlation matrix, or `None` when the pairwise
+/// correlations are not jointly achievable (the matrix is not positive semidefinite).
+pub fn return_correlation_cholesky(inputs: &Inputs) -> Option<[[f64; 3]; 3]> {
+    let matrix = return_correlation_matrix(inputs);
+    let lower = lower_cholesky(&matrix);
+    for i in 0..3 {
+        for j in 0..3 {
+            let rebuilt: f64 = (0..3).map(|k| lower[i][k] * lower[j][k]).sum();
+            if (rebuilt - matrix[i][j]).abs() > 1e-9 {
+                return None;
+            }
+        }
+    }
+    Some(lower)
+}
+
+/// Zero-mean, unit-variance shock for asset returns under the configured distribution.
+fn asset_shock(inputs: &Inputs, rng: &mut Rng) -> f64 {
+    match inputs.return_distribution {
+        ReturnDistribution::Normal => rng.standard_normal(),
+        ReturnDistribution::StudentT { dof } => {
+            // A t variate has variance dof / (dof - 2); rescale so `*_return_vol` keeps
+            // its meaning and only the tails change.
+            let dof = dof.max(2.0 + 1e-6);
+            rng.student_t(dof) * ((dof - 2.0) / dof).sqrt()
+        }
+    }
+}
+
+/// Per-scenario market state: the historical bootstrap walks through contiguous blocks
+/// of years, so each scenario keeps its position in the current block, a
+/// deterministic path is indexed by the number of years sampled so far, the GARCH
+/// model carries its conditional variance from one year to the next, and the regime
+/// model remembers whether the economy is in crisis. Under stratified inflation each
+/// sweep scenario also carries its inflation stratum, and under the ensemble the model
+/// it was assigned.
+struct MarketSampler {
+    historical_cursor: usize,
+    historical_block_remaining: usize,
+    years_sampled: usize,
+    garch_variance: Option<f64>,
+    in_crisis: bool,
+    inflation_stratum: Option<InflationStratum>,
+    ensemble_bootstrap: bool,
+}
+
+impl MarketSampler {
+    fn new() -> Self {
+        Self {
+            historical_cursor: 0,
+            historical_block_remaining: 0,
+            years_sampled: 0,
+            garch_variance: None,
+            in_crisis: false,
+            inflation_stratum: None,
+            ensemble_bootstrap: false,
+        }
+    }
+
+    /// Sampler for scenario `scenario_id` of a sweep, carrying its inflation strata when
+    /// `stratified_inflation` is on and its ensemble model.
+    fn for_scenario(inputs: &Inputs, scenario_id: u32) -> Self {
+        let ensemble_bootstrap = match inputs.market_model {
+            // Scenario `id` bootstraps when `floor((id + 1) * f)` steps past `floor(id * f)`,
+            // which spreads `floor(simulations * f)` bootstrap scenarios evenly.
+            MarketModel::Ensemble { bootstrap_fraction } => {
+                let fraction = bootstrap_fraction.clamp(0.0, 1.0);
+                let id = f64::from(scenario_id);
+                ((id + 1.0) * fraction).floor() > (id * fraction).floor()
+            }
+            _ => false,
+        };
+        Self {
+            inflation_stratum: inputs.stratified_inflation.then(|| InflationStratum {
+                scenario_id,
+                count: inputs.simulations.max(1),
+                key: splitmix64(inputs.seed),
+            }),
+            ensemble_bootstrap,
+            ..Self::new()
+        }
+    }
+
+    fn sample(&mut self, inputs: &Inputs, rng: &mut Rng) -> MarketSample {
+        let year = self.years_sampled;
+        self.years_sampled += 1;
+        let inflation_shock = self
+            .inflation_stratum
+            .map(|stratum| stratum.inflation_shock(year, rng));
+        match &inputs.market_model {
+            MarketModel::Parametric => sample_market_with(
+                inputs,
+                rng,
+                configured_return_means(inputs),
+                configured_return_vols(inputs),
+                inflation_shock,
+            ),
+            MarketModel::Ensemble { .. } if !self.ensemble_bootstrap => sample_market_with(
+                inputs,
+                rng,
+                configured_return_means(inputs),
+                configured_return_vols(inputs),
+                inflation_shock,
+            ),
+            MarketModel::HistoricalBootstrap | MarketModel::Ensemble { .. } => {
+                historical_market_sample(&HISTORICAL_YEARS[self.next_historical_index(inputs, rng)])
+            }
+            MarketModel::Deterministic { paths } => {
+                let (isa, taxable, pension, inflation) = paths
+                    .get(year)
+                    .or(paths.last())
+                    .copied()
+                    .unwrap_or_default();
+                MarketSample {
+                    isa_return: isa,
+                    taxable_return: taxable,
+                    pension_return: pension,
+                    inflation,
+                    bond_ladder_return: inputs.bond_ladder_yield,
+                }
+            }
+            &MarketModel::Garch {
+                alpha,
+                beta,
+                long_run_vol,
+            } => {
+                // The first year starts at the long-run level; each shock then feeds
+                // sigma^2' = (1 - alpha - beta) L^2 + alpha e^2 + beta sigma^2.
+                let long_run_variance = long_run_vol * long_run_vol;
+                let variance = self.garch_variance.unwrap_or(long_run_variance);
+                let vol = variance.sqrt();
+                let scale = if long_run_vol > 0.0 {
+                    vol / long_run_vol
+                } else {
+                    0.0
+                };
+                let sampled = sample_market_with(
+                    inputs,
+                    rng,
+                    configured_return_means(inputs),
+                    [
+                        vol,
+                        inputs.taxable_return_vol * scale,
+                        inputs.pension_return_vol * scale,
+                    ],
+                    inflation_shock,
+                );
+                let shock = sampled.isa_return - inputs.isa_return_mean;
+                self.garch_variance = Some(
+                    (1.0 - alpha - beta) * long_run_variance
+                        + alpha * shock * shock
+                        + beta * variance,
+                );
+                sampled
+            }
+            &MarketModel::RegimeSwitching {
+                p_normal_to_crisis,
+                p_crisis_to_normal,
+                crisis_return_mean,
+                crisis_return_vol,
+            } => {
+                // Draw this year's returns in the current regime, then move the chain on
+                // for next year.
+                let (means, vols) = if self.in_crisis {
+                    ([crisis_return_mean; 3], [crisis_return_vol; 3])
+                } else {
+                    (
+                        configured_return_means(inputs),
+                        configured_return_vols(inputs),
+                    )
+                };
+                let sampled = sample_market_with(inputs, rng, means, vols, inflation_shock);
+                let switch_probability = if self.in_crisis {
+                    p_crisis_to_normal
+                } else {
+                    p_normal_to_crisis
+                };
+                if rng.next_f64() < switch_probability {
+                    self.in_crisis = !self.in_crisis;
+                }
+                sampled
+            }
+        }
+    }
+
+    /// Runs a GARCH or regime-switching state forward `market_burn_in_years` years and
+    /// discards the draws, so the first simulated year starts from the stationary
+    /// distribution. The other models carry no state worth warming up.
+    fn burn_in(&mut self, inputs: &Inputs, rng: &mut Rng) {
+        if !matches!(
+            inputs.market_model,
+            MarketModel::Garch { .. } | MarketModel::RegimeSwitching { .. }
+        ) {
+            return;
+        }
+        for _ in 0..inputs.market_burn_in_years {
+            self.sample(inputs, rng);
+        }
+        self.years_sampled = 0;
+    }
+
+    /// Starts a new block of `bootstrap_block_years` consecutive years (a uniformly chosen
+    /// start that fits inside the table) whenever the current block is used up. A block
+    /// length of one is IID resampling.
+    fn next_historical_index(&mut self, inputs: &Inputs, rng: &mut Rng) -> usize {
+        let len = HISTORICAL_YEARS.len();
+        if self.historical_block_remaining == 0 {
+            let block_years = (inputs.bootstrap_block_years.max(1) as usize).min(len);
+            let start_choices = len - block_years + 1;
+            self.historical_cursor =
+                ((rng.next_f64() * start_choices as f64) as usize).min(start_choices - 1);
+            self.historical_block_remaining = block_years;
+        }
+        let index = self.historical_cursor;
+        self.historical_cursor += 1;
+        self.historical_block_remaining -= 1;
+        index
+    }
+}
+
+/// Scenario `scenario_id`'s place among `count` equal-probability inflation strata. Each
+/// year the strata are reassigned by an affine permutation keyed on the seed and the
+/// year, so every stratum is drawn exactly once per year across the sweep without the
+/// scenarios coordinating, and no scenario is stuck in the same tail year after year.
+#[derive(Clone, Copy)]
+struct InflationStratum {
+    scenario_id: u32,
+    count: u32,
+    key: u64,
+}
+
+impl InflationStratum {
+    fn index(self, year: usize) -> u64 {
+        let count = self.count as u64;
+        let hash = splitmix64(self.key ^ year as u64);
+        let mut multiplier = 1 + (hash >> 32) % count;
+        while gcd(multiplier, count) != 1 {
+            multiplier = multiplier % count + 1;
+        }
+        let offset = hash % count;
+        (multiplier * self.scenario_id as u64 + offset) % count
+    }
+
+    /// Standard normal inflation shock drawn uniformly within this year's stratum.
+    fn inflation_shock(self, year: usize, rng: &mut Rng) -> f64 {
+        let p = (self.index(year) as f64 + rng.next_f64()) / self.count as f64;
+        standard_normal_quantile(p)
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Inverse of the standard normal CDF (Acklam's rational approximation, relative error
+/// below 1.2e-9) for `p` in (0, 1).
+fn standard_normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const P_LOW: f64 = 0.024_25;
+
+    let p = p.clamp(1e-300, 1.0 - f64::EPSILON);
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p < P_LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - P_LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+/// Every pot is treated as equity-like and the bond ladder earns the historical bond
+/// return; the per-pot mean/volatility inputs are ignored.
+fn historical_market_sample(year: &HistoricalYear) -> MarketSample {
+    MarketSample {
+        isa_return: year.equity,
+        taxable_return: year.equity,
+        pension_return: year.equity,
+        inflation: year.inflation,
+        bond_ladder_return: year.bond,
+    }
+}
+
+#[cfg(test)]
 fn sample_market(inputs: &Inputs, rng: &mut Rng) -> MarketSample {
-    let z1 = rng.standard_normal();
-    let z2 = rng.standard_normal();
-    let z3 = rng.standard_normal();
+    sample_market_with(
+        inputs,
+        rng,
+        configured_return_means(inputs),
+        configured_return_vols(inputs),
+        None,
+    )
+}
+
+fn configured_return_means(inputs: &Inputs) -> [f64; 3] {
+    [
+        inputs.isa_return_mean,
+        inputs.taxable_return_mean,
+        inputs.pension_return_mean,
+    ]
+}
 
-    let corr = inputs.return_correlation;
-    let orth = (1.0 - corr * corr).sqrt();
+fn configured_return_vols(inputs: &Inputs) -> [f64; 3] {
+    [
+        inputs.isa_return_vol,
+        inputs.taxable_return_vol,
+        inputs.pension_return_vol,
+    ]
+}
+
+/// Parametric draw with `(isa, taxable, pension)` means and volatilities supplied by the
+/// caller, so models with time-varying parameters reuse the correlation structure. A
+/// stratified `inflation_shock` replaces the independent inflation draw.
+fn sample_market_with(
+    inputs: &Inputs,
+    rng: &mut Rng,
+    means: [f64; 3],
+    vols: [f64; 3],
+    inflation_shock: Option<f64>,
+) -> MarketSample {
+    let lower = lower_cholesky(&return_correlation_matrix(inputs));
+
+    let z1 = asset_shock(inputs, rng);
+    let z2 = asset_shock(inputs, rng);
+    let z3 = inflation_shock.unwrap_or_else(|| rng.standard_normal());
+    // Only draw the taxable-specific shock when it is used, so runs with ISA and taxable
+    // perfectly correlated keep the same random stream as the original two-factor model.
+    let z4 = if lower[1][1] > 0.0 {
+        asset_shock(inputs, rng)
+    } else {
+        0.0
+    };
 
-    let isa_return = (inputs.isa_return_mean + inputs.isa_return_vol * z1).clamp(-0.95, 2.5);
-    let taxable_return =
-        (inputs.taxable_return_mean + inputs.taxable_return_vol * z1).clamp(-0.95, 2.5);
-    let pension_return = (inputs.pension_return_mean
-        + inputs.pension_return_vol * (corr * z1 + orth * z2))
-        .clamp(-0.95, 2.5);
-    let inflation = (inputs.inflation_mean + inputs.inflation_vol * z3).clamp(-0.03, 0.20);
+    // Independent shocks for (ISA, taxable, pension) in Cholesky column order.
+    let shocks = [z1, z4, z2];
+    let correlated = |row: usize| -> f64 { (0..3).map(|k| lower[row][k] * shocks[k]).sum() };
+
+    let clamp_return = |r: f64| r.clamp(inputs.return_clamp_min, inputs.return_clamp_max);
+    let isa_return = clamp_return(means[0] + vols[0] * correlated(0));
+    let taxable_return = clamp_return(means[1] + vols[1] * correlated(1));
+    let pension_return = clamp_return(means[2] + vols[2] * correlated(2));
+    // Inflation loads on the equity factor `z1`, so it correlates with the ISA return
+    // exactly and with the other pots through their own ISA loading.
+    let infl_corr = inputs.inflation_return_correlation.clamp(-1.0, 1.0);
+    let infl_shock = infl_corr * z1 + (1.0 - infl_corr * infl_corr).sqrt() * z3;
+    let inflation = (inputs.inflation_mean + inputs.inflation_vol * infl_shock)
+        .clamp(inputs.inflation_clamp_min, inputs.inflation_clamp_max);
+    // The ladder shock loads on the inflation shock; it is only drawn with a nonzero
+    // volatility so fixed-yield runs keep their random stream.
+    let bond_ladder_return = if inputs.bond_ladder_yield_vol > 0.0 {
+        let bond_corr = inputs.bond_ladder_inflation_correlation.clamp(-1.0, 1.0);
+        let z5 = rng.standard_normal();
+        let bond_shock = bond_corr * infl_shock + (1.0 - bond_corr * bond_corr).sqrt() * z5;
+        clamp_return(inputs.bond_ladder_yield + inputs.bond_ladder_yield_vol * bond_shock)
+    } else {
+        inputs.bond_ladder_yield
+    };
 
     MarketSample {
         isa_return,
         taxable_return,
         pension_return,
         inflation,
+        bond_ladder_return,
     }
 }
 
+/// Seed for one scenario of the candidate at `reported_age`; with `age_invariant_draws`
+/// every candidate shares the seeds of age zero.
+fn scenario_seed(inputs: &Inputs, reported_age: u32, scenario_id: u32) -> u64 {
+    let age = if inputs.age_invariant_draws {
+        0
+    } else {
+        reported_age
+    };
+    derive_seed(inputs.seed, age, scenario_id)
+}
+
 fn derive_seed(base_seed: u64, age: u32, scenario_id: u32) -> u64 {
     let mixed = base_seed ^ ((age as u64) << 32) ^ scenario_id as u64;
     splitmix64(mixed)
@@ -1531,18 +4372,100 @@ impl Rng {
         self.cached_normal = Some(z1);
         z0
     }
+
+    /// Gamma(shape, 1) draw via Marsaglia-Tsang, boosted for shapes below one.
+    fn gamma(&mut self, shape: f64) -> f64 {
+        if shape < 1.0 {
+            let u = self.next_f64();
+            return self.gamma(shape + 1.0) * u.powf(1.0 / shape);
+        }
+
+        let d = shape - 1.0 / 3.0;
+        let c = 1.0 / (9.0 * d).sqrt();
+        loop {
+            let z = self.standard_normal();
+            let v = (1.0 + c * z).powi(3);
+            if v <= 0.0 {
+                continue;
+            }
+            let u = self.next_f64();
+            if u.ln() < 0.5 * z * z + d - d * v + d * v.ln() {
+                return d * v;
+            }
+        }
+    }
+
+    /// Student-t draw: a standard normal scaled by the square root of an inverse
+    /// chi-squared draw with `dof` degrees of freedom.
+    fn student_t(&mut self, dof: f64) -> f64 {
+        let z = self.standard_normal();
+        let chi_squared = 2.0 * self.gamma(0.5 * dof);
+        z / (chi_squared / dof).max(1e-300).sqrt()
+    }
 }
 
+#[cfg(test)]
 fn percentile(values: &mut [f64], p: f64) -> f64 {
-    if values.is_empty() {
+    sort_samples(values);
+    percentile_sorted(values, p)
+}
+
+fn sort_samples(values: &mut [f64]) {
+    values.sort_by(|a, b| a.total_cmp(b));
+}
+
+/// Half-width of the 95% Wilson score interval for a success rate `p` observed over `n`
+/// scenarios. Unlike the normal approximation it stays positive at `p = 0` or `p = 1`.
+pub fn binomial_ci_half_width(p: f64, n: u32) -> f64 {
+    if n == 0 {
         return 0.0;
     }
+    const Z: f64 = 1.96;
+    let p = p.clamp(0.0, 1.0);
+    let n = n as f64;
+    let z2 = Z * Z;
+    Z / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt()
+}
 
-    values.sort_by(|a, b| a.total_cmp(b));
+/// Bounds of the 95% Wilson score interval for a success rate `p` observed over `n`
+/// scenarios. The interval is centred on a point pulled towards one half, not on `p`.
+fn wilson_interval(p: f64, n: u32) -> (f64, f64) {
+    if n == 0 {
+        return (p, p);
+    }
+    const Z: f64 = 1.96;
+    let p = p.clamp(0.0, 1.0);
+    let z2_over_n = Z * Z / n as f64;
+    let centre = (p + z2_over_n / 2.0) / (1.0 + z2_over_n);
+    let half_width = binomial_ci_half_width(p, n);
+    (
+        (centre - half_width).max(0.0),
+        (centre + half_width).min(1.0),
+    )
+}
+
+/// Share of worst outcomes averaged by the CVaR (expected shortfall) metrics.
+const CVAR_TAIL_FRACTION: f64 = 0.05;
+
+/// Mean of the lowest `tail_fraction` of an already sorted sample. The tail always holds
+/// at least one value, so small runs average whatever falls in the bottom share.
+fn lower_tail_mean(sorted: &[f64], tail_fraction: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let count = ((sorted.len() as f64 * tail_fraction).ceil() as usize).clamp(1, sorted.len());
+    sorted[..count].iter().sum::<f64>() / count as f64
+}
+
+/// Linear-interpolated percentile of an already sorted sample.
+fn percentile_sorted(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
 
-    let n = values.len();
+    let n = sorted.len();
     if n == 1 {
-        return values[0];
+        return sorted[0];
     }
 
     let rank = (p / 100.0) * (n as f64 - 1.0);
@@ -1550,19 +4473,24 @@ fn percentile(values: &mut [f64], p: f64) -> f64 {
     let upper = rank.ceil() as usize;
 
     if lower == upper {
-        values[lower]
+        sorted[lower]
     } else {
         let w = rank - lower as f64;
-        values[lower] * (1.0 - w) + values[upper] * w
+        sorted[lower] * (1.0 - w) + sorted[upper] * w
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::types::Debt;
     use proptest::prelude::{any, prop_assert, prop_assume, proptest};
 
     const EPS: f64 = 1e-6;
+    const FLAT_CGT_20: CgtRates = CgtRates {
+        basic: 0.20,
+        higher: 0.20,
+    };
 
     fn assert_approx(actual: f64, expected: f64) {
         assert!(
@@ -1586,13 +4514,28 @@ mod tests {
             taxable_start: 15_000.0,
             taxable_cost_basis_start: 12_000.0,
             pension_start: 200_000.0,
+            household: false,
+            partner_pension_start: 0.0,
+            partner_pension_access_age: 57,
+            partner_personal_allowance: 12_570.0,
+            marriage_allowance_transfer: 0.0,
             cash_start: 0.0,
             bond_ladder_start: 0.0,
             isa_annual_contribution: 30_000.0,
             isa_annual_contribution_limit: 20_000.0,
+            lisa_start: 0.0,
+            lisa_annual_contribution: 0.0,
+            lisa_access_age: 60,
             taxable_annual_contribution: 5_000.0,
             pension_annual_contribution: 0.0,
+            employer_match_rate: 0.0,
+            employer_match_cap: None,
+            pension_annual_allowance: 60_000.0,
+            pension_overflow_to_taxable: true,
             contribution_growth_rate: 0.0,
+            contributions_track_inflation: false,
+            coast_contribution_fraction: 0.0,
+            pre_retirement_earnings: 0.0,
             isa_return_mean: 0.08,
             isa_return_vol: 0.12,
             taxable_return_mean: 0.07,
@@ -1600,11 +4543,31 @@ mod tests {
             pension_return_mean: 0.08,
             pension_return_vol: 0.12,
             return_correlation: 0.8,
-            capital_gains_tax_rate: 0.20,
+            isa_taxable_correlation: 1.0,
+            taxable_pension_correlation: None,
+            return_factor_betas: None,
+            return_distribution: ReturnDistribution::Normal,
+            market_model: MarketModel::Parametric,
+            bootstrap_block_years: 1,
+            market_burn_in_years: 0,
+            cgt_basic_rate: 0.20,
+            cgt_higher_rate: 0.20,
             capital_gains_allowance: 3_000.0,
             taxable_return_tax_drag: 0.01,
+            taxable_dividend_yield: 0.0,
+            dividend_allowance: 500.0,
+            dividend_basic_rate: 0.0875,
+            dividend_higher_rate: 0.3375,
+            dividend_additional_rate: 0.3935,
+            isa_fee_rate: 0.0,
+            taxable_fee_rate: 0.0,
+            pension_fee_rate: 0.0,
+            glide_start_equity: 1.0,
+            glide_end_equity: 1.0,
+            glide_end_age: None,
             pension_tax_mode: PensionTaxMode::FlatRate,
             pension_flat_tax_rate: 0.20,
+            pension_tax_free_pct: 0.0,
             uk_personal_allowance: 12_570.0,
             uk_basic_rate_limit: 50_270.0,
             uk_higher_rate_limit: 125_140.0,
@@ -1613,35 +4576,107 @@ mod tests {
             uk_additional_rate: 0.45,
             uk_allowance_taper_start: 100_000.0,
             uk_allowance_taper_end: 125_140.0,
+            thresholds_frozen_until_age: None,
+            scottish_starter_rate_limit: 15_397.0,
+            scottish_basic_rate_limit: 27_491.0,
+            scottish_intermediate_rate_limit: 43_662.0,
+            scottish_higher_rate_limit: 75_000.0,
+            scottish_advanced_rate_limit: 125_140.0,
+            scottish_starter_rate: 0.19,
+            scottish_basic_rate: 0.20,
+            scottish_intermediate_rate: 0.21,
+            scottish_higher_rate: 0.42,
+            scottish_advanced_rate: 0.45,
+            scottish_top_rate: 0.48,
             state_pension_start_age: 67,
             state_pension_annual_income: 0.0,
+            state_pension_real_growth: 0.0,
+            state_pension_growth_before_start: false,
+            state_pension_deferral_years: 0,
+            state_pension_deferral_uplift_rate: 0.058,
+            db_pension_start_age: 60,
+            db_pension_annual_income: 0.0,
+            annuity_purchase_age: None,
+            annuity_purchase_amount: 0.0,
+            annuity_rate: 0.05,
+            conversion_ladder_annual_amount: 0.0,
+            conversion_ladder_start_age: 57,
+            conversion_ladder_end_age: 67,
+            rental_income_start_age: 0,
+            rental_annual_income: 0.0,
+            rental_growth_rate: 0.0,
+            part_time_annual_income: 0.0,
+            part_time_start_age: 0,
+            part_time_end_age: 0,
             inflation_mean: 0.025,
             inflation_vol: 0.01,
+            inflation_return_correlation: 0.0,
+            return_clamp_min: -0.95,
+            return_clamp_max: 2.5,
+            inflation_clamp_min: -0.03,
+            inflation_clamp_max: 0.20,
             target_annual_income: 50_000.0,
+            essential_annual_income: 0.0,
+            spending_decline_rate: 0.0,
+            care_cost_age: 85,
+            care_cost_uplift: 0.0,
+            active_phase_end_age: 75,
+            active_phase_uplift: 0.0,
+            late_phase_start_age: 85,
+            late_phase_uplift: 0.0,
+            debts: Vec::new(),
             mortgage_annual_payment: 0.0,
             mortgage_end_age: None,
+            mortgage_balance: 0.0,
+            mortgage_rate: 0.05,
+            mortgage_term_years: 25,
+            mortgage_overpayments: Vec::new(),
+            lump_expenses: Vec::new(),
             max_retirement_age: 70,
             horizon_age: 90,
+            bequest_target: 0.0,
+            success_criterion: SuccessCriterion::EssentialFloorMet,
             simulations: 500,
+            threads: 1,
             success_threshold: 0.90,
+            selection_confidence: SelectionConfidence::PointEstimate,
+            risk_aversion: None,
+            utility_discount_rate: 0.0,
+            utility_spending_floor: 1_000.0,
             seed: 42,
+            age_invariant_draws: false,
+            stratified_inflation: false,
             bad_year_threshold: -0.05,
             good_year_threshold: 0.10,
             bad_year_cut: 0.10,
             good_year_raise: 0.05,
             min_income_floor: 0.80,
             max_income_ceiling: 2.0,
+            min_real_spending_floor: 0.0,
             withdrawal_strategy: WithdrawalStrategy::Guardrails,
             gk_lower_guardrail: 0.8,
             gk_upper_guardrail: 1.2,
+            gk_adjustment: 0.10,
             vpw_expected_real_return: 0.035,
+            constant_withdrawal_rate: 0.04,
+            cape_a: 0.0175,
+            cape_b: 0.5,
+            cape_start: 20.0,
+            cape_long_run_mean: 20.0,
+            cape_reversion_speed: 0.10,
             floor_upside_capture: 0.5,
             bucket_target_years: 2.0,
             good_year_extra_buffer_withdrawal: 0.10,
+            min_cash_months: 0.0,
             cash_growth_rate: 0.01,
             bond_ladder_yield: 0.03,
+            bond_ladder_yield_vol: 0.0,
+            bond_ladder_inflation_correlation: 0.0,
             bond_ladder_years: 10,
+            bond_ladder_reinvest: false,
+            bond_ladder_target_at_retirement: 0.0,
             post_access_withdrawal_order: WithdrawalOrder::ProRata,
+            pre_access_withdrawal_order: None,
         }
     }
 
@@ -1671,10 +4706,10 @@ mod tests {
         inputs.taxable_return_tax_drag = 0.0;
 
         inputs.target_annual_income = 0.0;
-        inputs.mortgage_annual_payment = 0.0;
-        inputs.mortgage_end_age = None;
+        inputs.debts.clear();
 
-        inputs.capital_gains_tax_rate = 0.0;
+        inputs.cgt_basic_rate = 0.0;
+        inputs.cgt_higher_rate = inputs.cgt_basic_rate;
         inputs.capital_gains_allowance = 0.0;
         inputs.pension_tax_mode = PensionTaxMode::FlatRate;
         inputs.pension_flat_tax_rate = 0.0;
@@ -1706,15 +4741,15 @@ mod tests {
         inputs.max_income_ceiling = 1.0;
         inputs.good_year_extra_buffer_withdrawal = 0.0;
 
-        inputs.capital_gains_tax_rate = 0.0;
+        inputs.cgt_basic_rate = 0.0;
+        inputs.cgt_higher_rate = inputs.cgt_basic_rate;
         inputs.capital_gains_allowance = 0.0;
         inputs.taxable_return_tax_drag = 0.0;
         inputs.pension_tax_mode = PensionTaxMode::FlatRate;
         inputs.pension_flat_tax_rate = 0.0;
         inputs.state_pension_start_age = 200;
         inputs.state_pension_annual_income = 0.0;
-        inputs.mortgage_annual_payment = 0.0;
-        inputs.mortgage_end_age = None;
+        inputs.debts.clear();
         inputs.cash_growth_rate = 0.0;
         inputs.bond_ladder_start = 0.0;
         inputs.bond_ladder_yield = 0.0;
@@ -1876,6 +4911,28 @@ mod tests {
                     a.median_avg_income_ratio,
                     b.median_avg_income_ratio,
                 ),
+                (
+                    "median_lifetime_tax",
+                    a.median_lifetime_tax,
+                    b.median_lifetime_tax,
+                ),
+                ("p90_lifetime_tax", a.p90_lifetime_tax, b.p90_lifetime_tax),
+                (
+                    "median_max_drawdown",
+                    a.median_max_drawdown,
+                    b.median_max_drawdown,
+                ),
+                ("p90_max_drawdown", a.p90_max_drawdown, b.p90_max_drawdown),
+                (
+                    "median_min_runway_years",
+                    a.median_min_runway_years,
+                    b.median_min_runway_years,
+                ),
+                (
+                    "p10_min_runway_years",
+                    a.p10_min_runway_years,
+                    b.p10_min_runway_years,
+                ),
             ] {
                 assert!(
                     (l - r).abs() <= 1e-9,
@@ -1926,6 +4983,12 @@ mod tests {
             ("p10_terminal_bond_ladder", age.p10_terminal_bond_ladder),
             ("p10_min_income_ratio", age.p10_min_income_ratio),
             ("median_avg_income_ratio", age.median_avg_income_ratio),
+            ("median_lifetime_tax", age.median_lifetime_tax),
+            ("p90_lifetime_tax", age.p90_lifetime_tax),
+            ("median_max_drawdown", age.median_max_drawdown),
+            ("p90_max_drawdown", age.p90_max_drawdown),
+            ("median_min_runway_years", age.median_min_runway_years),
+            ("p10_min_runway_years", age.p10_min_runway_years),
         ] {
             assert_finite_non_negative(value, label);
         }
@@ -1937,6 +5000,11 @@ mod tests {
         assert!(age.p10_retirement_cash <= age.median_retirement_cash + 1e-6);
         assert!(age.p10_retirement_bond_ladder <= age.median_retirement_bond_ladder + 1e-6);
         assert!(age.p10_terminal_pot <= age.median_terminal_pot + 1e-6);
+        assert!(age.median_lifetime_tax <= age.p90_lifetime_tax + 1e-6);
+        assert!(age.median_max_drawdown <= age.p90_max_drawdown + 1e-6);
+        assert!(age.p90_max_drawdown <= 1.0 + 1e-9);
+        assert!(age.p10_min_runway_years <= age.median_min_runway_years + 1e-6);
+        assert!(age.median_min_runway_years <= MAX_RUNWAY_YEARS + 1e-9);
         assert!(age.p10_terminal_isa <= age.median_terminal_isa + 1e-6);
         assert!(age.p10_terminal_taxable <= age.median_terminal_taxable + 1e-6);
         assert!(age.p10_terminal_pension <= age.median_terminal_pension + 1e-6);
@@ -2000,7 +5068,7 @@ mod tests {
             inputs.pension_annual_contribution = 0.0;
             inputs.good_year_extra_buffer_withdrawal = 0.0;
 
-            let model = run_model(&inputs);
+            let model = run_model(&inputs).expect("inputs are valid");
             prop_assert!(!model.age_results.is_empty());
             prop_assert!(model.best_index < model.age_results.len());
             if let Some(selected) = model.selected_index {
@@ -2059,7 +5127,8 @@ mod tests {
             inputs.cash_growth_rate = 0.0;
             inputs.taxable_return_tax_drag = 0.0;
 
-            inputs.capital_gains_tax_rate = 0.0;
+            inputs.cgt_basic_rate = 0.0;
+            inputs.cgt_higher_rate = inputs.cgt_basic_rate;
             inputs.capital_gains_allowance = 0.0;
             inputs.pension_tax_mode = PensionTaxMode::FlatRate;
             inputs.pension_flat_tax_rate = 0.0;
@@ -2075,7 +5144,7 @@ mod tests {
             inputs.max_income_ceiling = 1.0;
             inputs.good_year_extra_buffer_withdrawal = 0.0;
 
-            let model = run_model(&inputs);
+            let model = run_model(&inputs).expect("inputs are valid");
             let age = &model.age_results[0];
             prop_assert!((age.success_rate - 1.0).abs() < 1e-9);
 
@@ -2174,6 +5243,9 @@ mod tests {
                 ),
                 ("min_income_ratio", scenario.min_income_ratio),
                 ("avg_income_ratio", scenario.avg_income_ratio),
+                ("lifetime_tax_paid", scenario.lifetime_tax_paid),
+                ("max_drawdown", scenario.max_drawdown),
+                ("min_runway_years", scenario.min_runway_years),
             ] {
                 prop_assert!(value.is_finite(), "{label} must be finite");
                 prop_assert!(value >= -1e-6, "{label} must be non-negative");
@@ -2396,7 +5468,8 @@ mod tests {
             inputs.inflation_vol = 0.0;
             inputs.cash_growth_rate = cash_growth_bp as f64 / 10_000.0;
 
-            inputs.capital_gains_tax_rate = cgt_rate_bp as f64 / 10_000.0;
+            inputs.cgt_basic_rate = cgt_rate_bp as f64 / 10_000.0;
+            inputs.cgt_higher_rate = inputs.cgt_basic_rate;
             inputs.capital_gains_allowance = cgt_allowance as f64;
             inputs.pension_tax_mode = PensionTaxMode::FlatRate;
             inputs.pension_flat_tax_rate = pension_tax_bp as f64 / 10_000.0;
@@ -2416,17 +5489,28 @@ mod tests {
             let tax_state0 = TaxYearState {
                 non_pension_taxable_income: 0.0,
                 pension_gross_withdrawn: 0.0,
+                pension_tax_free_withdrawn: 0.0,
+                pension_tax_free_remaining: 0.0,
+                partner: PartnerTaxState::default(),
                 price_index: 1.0,
             };
             let taxable_net_capacity = net_from_taxable_gross(
                 inputs.taxable_start,
                 inputs.taxable_start,
                 inputs.taxable_cost_basis_start,
-                inputs.capital_gains_allowance,
-                inputs.capital_gains_tax_rate,
+                &CgtState {
+                    allowance_remaining: inputs.capital_gains_allowance,
+                    basic_band_remaining: 0.0,
+                    tax_paid: 0.0,
+                },
+                CgtRates::from_inputs(&inputs),
             );
+            let pots = PensionPots {
+                primary: inputs.pension_start,
+                partner: 0.0,
+            };
             let pension_net_capacity =
-                net_from_additional_pension_gross(inputs.pension_start, &tax_state0, &inputs);
+                net_from_additional_pension_gross(pots.total(), pots, &tax_state0, &inputs);
             let net_capacity =
                 inputs.cash_start
                     + inputs.isa_start
@@ -2448,24 +5532,27 @@ mod tests {
                 taxable_return: inputs.taxable_return_mean,
                 pension_return: inputs.pension_return_mean,
                 inflation: inputs.inflation_mean,
+                bond_ladder_return: inputs.bond_ladder_yield,
             };
             let mut portfolio = Portfolio {
                 isa: inputs.isa_start,
+                lisa: inputs.lisa_start,
                 taxable: inputs.taxable_start,
                 taxable_basis: inputs.taxable_cost_basis_start,
                 pension: inputs.pension_start,
+                partner_pension: 0.0,
                 cash_buffer: inputs.cash_start,
                 bond_ladder: inputs.bond_ladder_start,
             };
 
-            let total_start = portfolio.isa
-                + portfolio.taxable
-                + portfolio.pension
-                + portfolio.cash_buffer
-                + portfolio.bond_ladder;
+            let total_start = portfolio.total();
             let mut spending_state = SpendingState {
                 current_real_spending: inputs.target_annual_income,
                 initial_withdrawal_rate: inputs.target_annual_income / total_start.max(1e-9),
+                prev_nominal_spending: 0.0,
+                price_index: 1.0,
+                cape: 20.0,
+                smile_multiplier: 1.0,
             };
             let planned_real_spending = plan_real_spending(
                 &inputs,
@@ -2478,11 +5565,15 @@ mod tests {
 
             let mut cgt_state = CgtState {
                 allowance_remaining: inputs.capital_gains_allowance,
+                basic_band_remaining: 0.0,
                 tax_paid: 0.0,
             };
             let mut tax_state = TaxYearState {
                 non_pension_taxable_income: 0.0,
                 pension_gross_withdrawn: 0.0,
+                pension_tax_free_withdrawn: 0.0,
+                pension_tax_free_remaining: 0.0,
+                partner: PartnerTaxState::default(),
                 price_index: 1.0,
             };
 
@@ -2530,7 +5621,14 @@ mod tests {
                     <= 1e-3
             );
 
-            apply_post_retirement_growth(&inputs, &mut portfolio, &sampled);
+            apply_post_retirement_growth(
+                &inputs,
+                &mut portfolio,
+                &sampled,
+                inputs.current_age,
+                0.0,
+                1.0,
+            );
 
             let expected_isa_end = (after_withdraw_isa * (1.0 + inputs.isa_return_mean)).max(0.0);
             let expected_taxable_end = ((after_withdraw_taxable
@@ -2679,8 +5777,8 @@ mod tests {
             high.taxable_annual_contribution += delta;
             high.pension_annual_contribution += delta;
 
-            let low_model = run_model(&low);
-            let high_model = run_model(&high);
+            let low_model = run_model(&low).expect("inputs are valid");
+            let high_model = run_model(&high).expect("inputs are valid");
 
             for (low_age, high_age) in low_model.age_results.iter().zip(high_model.age_results.iter()) {
                 prop_assert!(high_age.success_rate + 1e-9 >= low_age.success_rate);
@@ -2736,8 +5834,8 @@ mod tests {
             higher.taxable_return_mean += delta;
             higher.pension_return_mean += delta;
 
-            let lower_model = run_model(&lower);
-            let higher_model = run_model(&higher);
+            let lower_model = run_model(&lower).expect("inputs are valid");
+            let higher_model = run_model(&higher).expect("inputs are valid");
 
             for (lo, hi) in lower_model.age_results.iter().zip(higher_model.age_results.iter()) {
                 prop_assert!(hi.success_rate + 1e-9 >= lo.success_rate);
@@ -2748,6 +5846,36 @@ mod tests {
     proptest! {
         #![proptest_config(proptest::test_runner::Config::with_cases(16))]
 
+        #[test]
+        fn prop_negative_inflation_correlation_pairs_high_inflation_with_low_returns(
+            seed in any::<u64>(),
+            correlation_pct in -95i32..-60,
+            isa_vol_bp in 800u32..2500,
+            inflation_vol_bp in 50u32..300
+        ) {
+            let mut inputs = sample_inputs();
+            inputs.isa_return_mean = 0.06;
+            inputs.isa_return_vol = isa_vol_bp as f64 / 10_000.0;
+            inputs.inflation_mean = 0.03;
+            inputs.inflation_vol = inflation_vol_bp as f64 / 10_000.0;
+            inputs.inflation_return_correlation = correlation_pct as f64 / 100.0;
+
+            // Under independence each quadrant holds a quarter of the draws; with
+            // correlation rho the high-inflation/low-return quadrant holds
+            // 1/4 + asin(-rho) / (2*pi), at least 0.35 for rho <= -0.6.
+            let draws = 4_000;
+            let mut rng = Rng::new(seed);
+            let mut high_inflation_low_return = 0;
+            for _ in 0..draws {
+                let s = sample_market(&inputs, &mut rng);
+                if s.inflation > inputs.inflation_mean && s.isa_return < inputs.isa_return_mean {
+                    high_inflation_low_return += 1;
+                }
+            }
+            let share = high_inflation_low_return as f64 / draws as f64;
+            prop_assert!(share > 0.30, "share {share}");
+        }
+
         #[test]
         fn prop_higher_target_income_does_not_improve_success_rate(
             seed in any::<u64>(),
@@ -2792,8 +5920,8 @@ mod tests {
             higher_income.target_annual_income =
                 lower_income.target_annual_income * income_multiplier_pct as f64 / 100.0;
 
-            let lower_model = run_model(&lower_income);
-            let higher_model = run_model(&higher_income);
+            let lower_model = run_model(&lower_income).expect("inputs are valid");
+            let higher_model = run_model(&higher_income).expect("inputs are valid");
 
             for (lo, hi) in lower_model.age_results.iter().zip(higher_model.age_results.iter()) {
                 prop_assert!(hi.success_rate <= lo.success_rate + 1e-9);
@@ -2816,8 +5944,8 @@ mod tests {
         inputs.pension_return_vol = 0.0;
         inputs.inflation_vol = 0.0;
 
-        let model_a = run_model(&inputs);
-        let model_b = run_model(&inputs);
+        let model_a = run_model(&inputs).expect("inputs are valid");
+        let model_b = run_model(&inputs).expect("inputs are valid");
         assert_models_approx_equal(&model_a, &model_b);
 
         let rows_a = run_yearly_cashflow_trace(&inputs, 36, 36, 36);
@@ -2999,515 +6127,3761 @@ mod tests {
     }
 
     #[test]
-    fn oracle_taxable_first_withdrawal_applies_cgt_and_preserves_pension() {
+    fn oracle_pension_annual_allowance_overflow_matches_hand_calculation() {
         let mut inputs = deterministic_oracle_inputs();
         inputs.current_age = 30;
-        inputs.max_retirement_age = 30;
-        inputs.horizon_age = 31;
-        inputs.pension_access_age = 30;
+        inputs.max_retirement_age = 33;
+        inputs.horizon_age = 34;
+        inputs.pension_access_age = 57;
 
-        inputs.isa_start = 100.0;
-        inputs.taxable_start = 100.0;
-        inputs.taxable_cost_basis_start = 50.0;
-        inputs.pension_start = 100.0;
+        inputs.isa_start = 0.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
         inputs.cash_start = 0.0;
-        inputs.target_annual_income = 180.0;
 
-        inputs.capital_gains_tax_rate = 0.20;
-        inputs.capital_gains_allowance = 0.0;
-        inputs.post_access_withdrawal_order = WithdrawalOrder::TaxableFirst;
+        inputs.isa_annual_contribution = 0.0;
+        inputs.taxable_annual_contribution = 5_000.0;
+        inputs.pension_annual_contribution = 50_000.0;
+        inputs.employer_match_rate = 0.5;
+        inputs.employer_match_cap = Some(10_000.0);
+        inputs.pension_annual_allowance = 40_000.0;
+        inputs.pension_overflow_to_taxable = true;
+        inputs.contribution_growth_rate = 0.10;
 
-        // Hand calculation:
-        // Taxable full sale: gross 100, gain 50, CGT 10, net 90.
-        // Remaining 90 from ISA. Pension untouched.
-        // Terminal: ISA 10, taxable 0, pension 100, total 110.
-        let mut rng = Rng::new(derive_seed(inputs.seed, 30, 0));
-        let scenario = simulate_scenario(&inputs, 30, 30, &mut rng, None);
+        // Hand calculation (allowance 40k, employer match 50% capped at 10k):
+        // Year 0: employer 10k, own 30k, taxable 5k + (50k-30k) = 25k
+        // Year 1: employer 10k, own 30k, taxable 5.5k + (55k-30k) = 30.5k
+        // Year 2: employer 10k, own 30k, taxable 6.05k + (60.5k-30k) = 36.55k
+        // Retirement balances: pension 120k, taxable 92.05k
+        let mut rng = Rng::new(derive_seed(inputs.seed, 33, 0));
+        let scenario = simulate_scenario(&inputs, 33, 33, &mut rng, None);
 
         assert!(scenario.success);
-        assert_approx(scenario.reported_retirement_total, 300.0);
-        assert_approx(scenario.reported_terminal_isa, 10.0);
-        assert_approx(scenario.reported_terminal_taxable, 0.0);
-        assert_approx(scenario.reported_terminal_pension, 100.0);
-        assert_approx(scenario.reported_terminal_total, 110.0);
-        assert_approx(scenario.min_income_ratio, 1.0);
+        assert_approx(scenario.reported_retirement_pension, 120_000.0);
+        assert_approx(scenario.reported_retirement_taxable, 92_050.0);
 
-        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
-        assert_eq!(rows.len(), 1);
-        assert_approx(rows[0].median_withdrawal_portfolio, 180.0);
-        assert_approx(rows[0].median_tax_cgt, 10.0);
-        assert_approx(rows[0].median_tax_income, 0.0);
-        assert_approx(rows[0].median_end_isa, 10.0);
-        assert_approx(rows[0].median_end_taxable, 0.0);
-        assert_approx(rows[0].median_end_pension, 100.0);
+        let rows = run_yearly_cashflow_trace(&inputs, 33, 33, 33);
+        assert_eq!(rows.len(), 4);
+
+        assert_approx(rows[0].median_contribution_pension, 30_000.0);
+        assert_approx(rows[0].median_contribution_employer, 10_000.0);
+        assert_approx(rows[0].median_contribution_taxable, 25_000.0);
+        assert_approx(rows[1].median_contribution_taxable, 30_500.0);
+        assert_approx(rows[2].median_contribution_taxable, 36_550.0);
+        assert_approx(rows[2].median_end_pension, 120_000.0);
+
+        inputs.pension_overflow_to_taxable = false;
+        let mut rng = Rng::new(derive_seed(inputs.seed, 33, 0));
+        let dropped = simulate_scenario(&inputs, 33, 33, &mut rng, None);
+        assert_approx(dropped.reported_retirement_pension, 120_000.0);
+        assert_approx(dropped.reported_retirement_taxable, 16_550.0);
     }
 
     #[test]
-    fn oracle_pension_withdrawal_uses_gross_up_for_flat_income_tax() {
+    fn oracle_lisa_bonus_is_added_and_contributions_stop_at_fifty() {
         let mut inputs = deterministic_oracle_inputs();
-        inputs.current_age = 30;
-        inputs.max_retirement_age = 30;
-        inputs.horizon_age = 31;
-        inputs.pension_access_age = 30;
+        inputs.current_age = 48;
+        inputs.max_retirement_age = 51;
+        inputs.horizon_age = 52;
+        inputs.pension_access_age = 57;
 
         inputs.isa_start = 0.0;
         inputs.taxable_start = 0.0;
         inputs.taxable_cost_basis_start = 0.0;
-        inputs.pension_start = 100.0;
+        inputs.pension_start = 0.0;
         inputs.cash_start = 0.0;
-        inputs.target_annual_income = 80.0;
 
-        inputs.pension_tax_mode = PensionTaxMode::FlatRate;
-        inputs.pension_flat_tax_rate = 0.20;
-        inputs.post_access_withdrawal_order = WithdrawalOrder::PensionFirst;
+        inputs.isa_annual_contribution = 20_000.0;
+        inputs.isa_annual_contribution_limit = 20_000.0;
+        inputs.lisa_annual_contribution = 5_000.0;
+
+        // Hand calculation:
+        // Ages 48 and 49: LISA 4k (capped) + 1k bonus; ISA 20k - 4k = 16k; taxable 4k.
+        // Age 50: no LISA payment, so the full 20k goes to the ISA.
+        // Retirement balances: LISA 10k, ISA 52k, taxable 8k.
+        let mut rng = Rng::new(derive_seed(inputs.seed, 51, 0));
+        let scenario = simulate_scenario(&inputs, 51, 51, &mut rng, None);
 
-        let mut rng = Rng::new(derive_seed(inputs.seed, 30, 0));
-        let scenario = simulate_scenario(&inputs, 30, 30, &mut rng, None);
         assert!(scenario.success);
-        assert_approx_tol(scenario.reported_terminal_pension, 0.0, 1e-6);
-        assert_approx_tol(scenario.reported_terminal_total, 0.0, 1e-6);
-        assert_approx(scenario.min_income_ratio, 1.0);
+        assert_approx(scenario.reported_retirement_isa, 52_000.0);
+        assert_approx(scenario.reported_retirement_taxable, 8_000.0);
+        assert_approx(scenario.reported_retirement_total, 70_000.0);
 
-        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
-        assert_eq!(rows.len(), 1);
-        assert_approx_tol(rows[0].median_withdrawal_portfolio, 80.0, 1e-5);
-        assert_approx_tol(rows[0].median_tax_income, 20.0, 1e-5);
-        assert_approx_tol(rows[0].median_end_pension, 0.0, 1e-6);
-        assert_approx_tol(rows[0].median_end_total, 0.0, 1e-6);
+        let rows = run_yearly_cashflow_trace(&inputs, 51, 51, 51);
+        assert_eq!(rows.len(), 4);
+
+        assert_approx(rows[0].median_contribution_lisa, 5_000.0);
+        assert_approx(rows[0].median_contribution_isa, 16_000.0);
+        assert_approx(rows[0].median_contribution_taxable, 4_000.0);
+        assert_approx(rows[0].median_contribution_total, 25_000.0);
+        assert_approx(rows[1].median_end_lisa, 10_000.0);
+        assert_approx(rows[2].median_contribution_lisa, 0.0);
+        assert_approx(rows[2].median_contribution_isa, 20_000.0);
+        assert_approx(rows[3].median_end_lisa, 10_000.0);
     }
 
     #[test]
-    fn oracle_bond_ladder_draws_evenly_before_other_pots() {
+    fn oracle_lisa_withdrawal_before_access_age_pays_the_penalty() {
         let mut inputs = deterministic_oracle_inputs();
-        inputs.current_age = 30;
-        inputs.max_retirement_age = 30;
-        inputs.horizon_age = 33;
-        inputs.pension_access_age = 57;
+        inputs.isa_start = 100.0;
+        inputs.lisa_start = 1_000.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.cash_start = 0.0;
+        inputs.target_annual_income = 400.0;
+        inputs.lisa_access_age = 60;
+        inputs.post_access_withdrawal_order = WithdrawalOrder::IsaFirst;
+
+        // Hand calculation:
+        // ISA gives 100; the remaining 300 net needs 300 / 0.75 = 400 from the LISA.
+        // Terminal: LISA 600.
+        let mut rng = Rng::new(derive_seed(inputs.seed, 30, 0));
+        let scenario = simulate_scenario(&inputs, 30, 30, &mut rng, None);
+        assert!(scenario.success);
+        assert_approx(scenario.reported_terminal_isa, 0.0);
+        assert_approx(scenario.reported_terminal_total, 600.0);
+
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_approx(rows[0].median_withdrawal_portfolio, 400.0);
+        assert_approx(rows[0].median_end_lisa, 600.0);
+
+        // From the access age the LISA is drawn like the ISA, without the penalty.
+        inputs.lisa_access_age = 30;
+        let mut rng = Rng::new(derive_seed(inputs.seed, 30, 0));
+        let scenario = simulate_scenario(&inputs, 30, 30, &mut rng, None);
+        assert!(scenario.success);
+        assert_approx(scenario.reported_terminal_total, 700.0);
+    }
+
+    #[test]
+    fn oracle_lifetime_tax_separates_pension_first_from_isa_first() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.horizon_age = 32;
+        inputs.isa_start = 100_000.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 100_000.0;
+        inputs.pension_tax_free_pct = 0.0;
+        inputs.pension_tax_mode = PensionTaxMode::UkBands;
+        inputs.target_annual_income = 20_000.0;
+
+        let lifetime_tax = |order: WithdrawalOrder| {
+            let mut inputs = inputs.clone();
+            inputs.post_access_withdrawal_order = order;
+            let mut rng = Rng::new(derive_seed(inputs.seed, 30, 0));
+            let scenario = simulate_scenario(&inputs, 30, 30, &mut rng, None);
+            assert!(scenario.success);
+            scenario.lifetime_tax_paid
+        };
+
+        // Pension first nets 20,000 from g - 20% (g - 12,570), so g = 21,857.50 and
+        // 1,857.50 of tax in each of the two years. ISA first pays nothing.
+        assert_approx(lifetime_tax(WithdrawalOrder::PensionFirst), 2.0 * 1_857.5);
+        assert_approx(lifetime_tax(WithdrawalOrder::IsaFirst), 0.0);
+    }
+
+    #[test]
+    fn oracle_trace_splits_income_tax_by_band_across_the_taper() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.pension_tax_mode = PensionTaxMode::UkBands;
+        inputs.db_pension_start_age = 30;
+        inputs.db_pension_annual_income = 110_000.0;
+        inputs.target_annual_income = 10_000.0;
+
+        // 110,000 is 10,000 into the taper, so the allowance falls by 5,000 to 7,570.
+        // Basic: (50,270 - 7,570) @ 20% = 8,540; higher: (110,000 - 50,270) @ 40%
+        // = 23,892. On the full allowance basic would be 7,540, so the taper costs
+        // 1,000.
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_approx(rows[0].median_tax_income, 32_432.0);
+        assert_approx(rows[0].median_tax_income_basic, 8_540.0);
+        assert_approx(rows[0].median_tax_income_higher, 23_892.0);
+        assert_approx(rows[0].median_tax_income_additional, 0.0);
+        assert_approx(rows[0].median_tax_income_taper, 1_000.0);
+
+        // Below the taper nothing is lost.
+        inputs.db_pension_annual_income = 90_000.0;
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_approx(rows[0].median_tax_income_taper, 0.0);
+        assert_approx(
+            rows[0].median_tax_income_basic + rows[0].median_tax_income_higher,
+            rows[0].median_tax_income,
+        );
+    }
+
+    #[test]
+    fn oracle_fill_basic_rate_stops_pension_at_the_band_edge() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.isa_start = 100_000.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 500_000.0;
+        inputs.pension_tax_free_pct = 0.0;
+        inputs.pension_tax_mode = PensionTaxMode::UkBands;
+        inputs.target_annual_income = 60_000.0;
+        inputs.post_access_withdrawal_order = WithdrawalOrder::FillBasicRate;
+
+        // Hand calculation: pension fills the band to 50,270 gross and pays
+        // (50,270 - 12,570) @ 20% = 7,540, netting 42,730. The other 17,270
+        // comes from the ISA.
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_approx(rows[0].median_end_pension, 500_000.0 - 50_270.0);
+        assert_approx(rows[0].median_end_isa, 100_000.0 - 17_270.0);
+        assert_approx(rows[0].median_tax_income, 7_540.0);
+        assert_approx(rows[0].median_spending_total, 60_000.0);
+
+        // Once the ISA is empty the pension is drawn above the band to meet spending.
+        inputs.isa_start = 10_000.0;
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_approx(rows[0].median_end_isa, 0.0);
+        assert_approx(rows[0].median_spending_total, 60_000.0);
+        // 7,270 more net at 40% needs 12,116.67 gross.
+        assert_approx(
+            rows[0].median_end_pension,
+            500_000.0 - 50_270.0 - 7_270.0 / 0.6,
+        );
+    }
+
+    #[test]
+    fn oracle_pre_and_post_access_orders_apply_on_either_side_of_access() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.horizon_age = 32;
+        inputs.pension_access_age = 31;
+        inputs.isa_start = 1_000.0;
+        inputs.taxable_start = 1_000.0;
+        inputs.taxable_cost_basis_start = 1_000.0;
+        inputs.pension_start = 1_000.0;
+        inputs.pension_tax_free_pct = 0.0;
+        inputs.target_annual_income = 100.0;
+        inputs.pre_access_withdrawal_order = Some(WithdrawalOrder::TaxableFirst);
+        inputs.post_access_withdrawal_order = WithdrawalOrder::PensionFirst;
+
+        // Age 30 draws taxable first; from 31 the pension goes first.
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_eq!(rows[0].age, 30);
+        assert_approx(rows[0].median_end_taxable, 900.0);
+        assert_approx(rows[0].median_end_isa, 1_000.0);
+        assert_approx(rows[0].median_end_pension, 1_000.0);
+        assert_eq!(rows[1].age, 31);
+        assert_approx(rows[1].median_end_taxable, 900.0);
+        assert_approx(rows[1].median_end_isa, 1_000.0);
+        assert_approx(rows[1].median_end_pension, 900.0);
+
+        // Without a pre-access order the ISA goes first until access.
+        inputs.pre_access_withdrawal_order = None;
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_approx(rows[0].median_end_isa, 900.0);
+        assert_approx(rows[0].median_end_taxable, 1_000.0);
+        assert_approx(rows[1].median_end_pension, 900.0);
+    }
+
+    #[test]
+    fn oracle_taxable_first_withdrawal_applies_cgt_and_preserves_pension() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.current_age = 30;
+        inputs.max_retirement_age = 30;
+        inputs.horizon_age = 31;
+        inputs.pension_access_age = 30;
+
+        inputs.isa_start = 100.0;
+        inputs.taxable_start = 100.0;
+        inputs.taxable_cost_basis_start = 50.0;
+        inputs.pension_start = 100.0;
+        inputs.cash_start = 0.0;
+        inputs.target_annual_income = 180.0;
+
+        inputs.cgt_basic_rate = 0.20;
+        inputs.cgt_higher_rate = inputs.cgt_basic_rate;
+        inputs.capital_gains_allowance = 0.0;
+        inputs.post_access_withdrawal_order = WithdrawalOrder::TaxableFirst;
+
+        // Hand calculation:
+        // Taxable full sale: gross 100, gain 50, CGT 10, net 90.
+        // Remaining 90 from ISA. Pension untouched.
+        // Terminal: ISA 10, taxable 0, pension 100, total 110.
+        let mut rng = Rng::new(derive_seed(inputs.seed, 30, 0));
+        let scenario = simulate_scenario(&inputs, 30, 30, &mut rng, None);
+
+        assert!(scenario.success);
+        assert_approx(scenario.reported_retirement_total, 300.0);
+        assert_approx(scenario.reported_terminal_isa, 10.0);
+        assert_approx(scenario.reported_terminal_taxable, 0.0);
+        assert_approx(scenario.reported_terminal_pension, 100.0);
+        assert_approx(scenario.reported_terminal_total, 110.0);
+        assert_approx(scenario.min_income_ratio, 1.0);
+
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_eq!(rows.len(), 1);
+        assert_approx(rows[0].median_withdrawal_portfolio, 180.0);
+        assert_approx(rows[0].median_tax_cgt, 10.0);
+        assert_approx(rows[0].median_tax_income, 0.0);
+        assert_approx(rows[0].median_end_isa, 10.0);
+        assert_approx(rows[0].median_end_taxable, 0.0);
+        assert_approx(rows[0].median_end_pension, 100.0);
+    }
+
+    #[test]
+    fn oracle_pension_withdrawal_uses_gross_up_for_flat_income_tax() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.current_age = 30;
+        inputs.max_retirement_age = 30;
+        inputs.horizon_age = 31;
+        inputs.pension_access_age = 30;
+
+        inputs.isa_start = 0.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 100.0;
+        inputs.cash_start = 0.0;
+        inputs.target_annual_income = 80.0;
+
+        inputs.pension_tax_mode = PensionTaxMode::FlatRate;
+        inputs.pension_flat_tax_rate = 0.20;
+        inputs.post_access_withdrawal_order = WithdrawalOrder::PensionFirst;
+
+        let mut rng = Rng::new(derive_seed(inputs.seed, 30, 0));
+        let scenario = simulate_scenario(&inputs, 30, 30, &mut rng, None);
+        assert!(scenario.success);
+        assert_approx_tol(scenario.reported_terminal_pension, 0.0, 1e-6);
+        assert_approx_tol(scenario.reported_terminal_total, 0.0, 1e-6);
+        assert_approx(scenario.min_income_ratio, 1.0);
+
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_eq!(rows.len(), 1);
+        assert_approx_tol(rows[0].median_withdrawal_portfolio, 80.0, 1e-5);
+        assert_approx_tol(rows[0].median_tax_income, 20.0, 1e-5);
+        assert_approx_tol(rows[0].median_end_pension, 0.0, 1e-6);
+        assert_approx_tol(rows[0].median_end_total, 0.0, 1e-6);
+    }
+
+    #[test]
+    fn oracle_pension_tax_free_lump_sum_is_drawn_before_tax_applies() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.isa_start = 0.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 100_000.0;
+        inputs.cash_start = 0.0;
+        inputs.target_annual_income = 85_000.0;
+
+        inputs.pension_tax_mode = PensionTaxMode::FlatRate;
+        inputs.pension_flat_tax_rate = 0.20;
+        inputs.pension_tax_free_pct = 0.25;
+        inputs.post_access_withdrawal_order = WithdrawalOrder::PensionFirst;
+
+        // Hand calculation: 25k tax-free + 75k taxed at 20% = 85k net, 15k tax.
+        // Without the lump sum the same pot would only deliver 80k.
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_eq!(rows.len(), 1);
+        assert_approx_tol(rows[0].median_withdrawal_portfolio, 85_000.0, 1e-3);
+        assert_approx_tol(rows[0].median_tax_income, 15_000.0, 1e-3);
+        assert_approx_tol(rows[0].median_end_pension, 0.0, 1e-3);
+
+        // The unused allowance carries into later years:
+        // Year 0: 25k tax-free + 18.75k gross for 15k net -> pension 56.25k.
+        // Year 1: 40k net needs 50k gross -> pension 6.25k.
+        inputs.horizon_age = 32;
+        inputs.target_annual_income = 40_000.0;
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_eq!(rows.len(), 2);
+        assert_approx_tol(rows[0].median_tax_income, 3_750.0, 1e-3);
+        assert_approx_tol(rows[0].median_end_pension, 56_250.0, 1e-3);
+        assert_approx_tol(rows[1].median_tax_income, 10_000.0, 1e-3);
+        assert_approx_tol(rows[1].median_end_pension, 6_250.0, 1e-3);
+    }
+
+    #[test]
+    fn db_pension_covering_spending_rescues_a_plan_that_runs_dry_at_sixty() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.current_age = 55;
+        inputs.max_retirement_age = 55;
+        inputs.horizon_age = 70;
+        inputs.pension_access_age = 57;
+        inputs.isa_start = 50_000.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.cash_start = 0.0;
+        inputs.target_annual_income = 10_000.0;
+
+        // The ISA funds ages 55-59 exactly and is empty at 60.
+        let mut rng = Rng::new(derive_seed(inputs.seed, 55, 0));
+        let without_db = simulate_scenario(&inputs, 55, 55, &mut rng, None);
+        assert!(!without_db.success);
+
+        inputs.db_pension_start_age = 60;
+        inputs.db_pension_annual_income = 10_000.0;
+        let mut rng = Rng::new(derive_seed(inputs.seed, 55, 0));
+        let with_db = simulate_scenario(&inputs, 55, 55, &mut rng, None);
+        assert!(with_db.success);
+        assert_approx(with_db.min_income_ratio, 1.0);
+
+        let rows = run_yearly_cashflow_trace(&inputs, 55, 55, 55);
+        assert_approx(rows[4].median_withdrawal_portfolio, 10_000.0);
+        assert_approx(rows[5].median_withdrawal_portfolio, 0.0);
+        assert_approx(rows[5].median_withdrawal_non_pension_income, 10_000.0);
+    }
 
+    #[test]
+    fn bond_ladder_yield_vol_spreads_terminal_ladder_values() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.horizon_age = 40;
+        inputs.simulations = 400;
         inputs.isa_start = 0.0;
         inputs.taxable_start = 0.0;
         inputs.taxable_cost_basis_start = 0.0;
         inputs.pension_start = 0.0;
         inputs.cash_start = 0.0;
-        inputs.bond_ladder_start = 90.0;
-        inputs.bond_ladder_yield = 0.0;
-        inputs.bond_ladder_years = 3;
-        inputs.target_annual_income = 30.0;
+        inputs.bond_ladder_start = 100_000.0;
+        inputs.bond_ladder_yield = 0.03;
+
+        let fixed = evaluate_age_candidate(&inputs, 30, 30, 30);
+        assert_approx(
+            fixed.median_terminal_bond_ladder,
+            100_000.0 * 1.03_f64.powi(10),
+        );
+        assert_approx(
+            fixed.p10_terminal_bond_ladder,
+            fixed.median_terminal_bond_ladder,
+        );
+
+        inputs.bond_ladder_yield_vol = 0.08;
+        let volatile = evaluate_age_candidate(&inputs, 30, 30, 30);
+        assert!(
+            volatile.p10_terminal_bond_ladder < 0.8 * volatile.median_terminal_bond_ladder,
+            "{} vs {}",
+            volatile.p10_terminal_bond_ladder,
+            volatile.median_terminal_bond_ladder
+        );
+
+        // Fully negatively correlated, the ladder's shock mirrors the inflation shock.
+        inputs.inflation_vol = 0.02;
+        inputs.inflation_clamp_min = -1.0;
+        inputs.inflation_clamp_max = 1.0;
+        inputs.bond_ladder_inflation_correlation = -1.0;
+        let mut rng = Rng::new(11);
+        for _ in 0..20 {
+            let s = sample_market(&inputs, &mut rng);
+            assert_approx(
+                (s.bond_ladder_return - inputs.bond_ladder_yield) / 0.08,
+                -(s.inflation - inputs.inflation_mean) / 0.02,
+            );
+        }
+    }
+
+    #[test]
+    fn oracle_bond_ladder_target_is_sold_from_isa_then_taxable_at_retirement() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.isa_start = 20_000.0;
+        inputs.taxable_start = 100_000.0;
+        inputs.taxable_cost_basis_start = 50_000.0;
+        inputs.pension_start = 0.0;
+        inputs.cash_start = 0.0;
+        inputs.bond_ladder_start = 10_000.0;
+        inputs.bond_ladder_target_at_retirement = 60_000.0;
+        inputs.cgt_basic_rate = 0.20;
+        inputs.cgt_higher_rate = 0.20;
+
+        // Hand calculation: the 50k gap takes the whole 20k ISA, then 30k net from the
+        // taxable pot. Half of each pound sold is gain taxed at 20%, so net = 0.9 * gross:
+        // gross 33,333.33 and CGT 3,333.33. Spending is zero, so nothing else moves.
+        let mut rng = Rng::new(derive_seed(inputs.seed, 30, 0));
+        let scenario = simulate_scenario(&inputs, 30, 30, &mut rng, None);
+        assert!(scenario.success);
+        assert_approx(scenario.reported_retirement_isa, 20_000.0);
+        assert_approx(scenario.reported_retirement_taxable, 100_000.0);
+        assert_approx(scenario.reported_terminal_bond_ladder, 60_000.0);
+        assert_approx(scenario.reported_terminal_isa, 0.0);
+        assert_approx(
+            scenario.reported_terminal_taxable,
+            100_000.0 - 100_000.0 / 3.0,
+        );
+        assert_approx(scenario.lifetime_tax_paid, 10_000.0 / 3.0);
+
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_eq!(rows.len(), 1);
+        assert_approx(rows[0].median_end_bond_ladder, 60_000.0);
+        assert_approx(rows[0].median_tax_cgt, 10_000.0 / 3.0);
+        assert_approx(rows[0].median_end_total, 60_000.0 + 200_000.0 / 3.0);
+    }
+
+    #[test]
+    fn oracle_annuity_purchase_moves_pension_and_pays_income_the_same_year() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.current_age = 60;
+        inputs.max_retirement_age = 60;
+        inputs.horizon_age = 62;
+        inputs.pension_access_age = 57;
+        inputs.isa_start = 0.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 100_000.0;
+        inputs.cash_start = 0.0;
+        inputs.target_annual_income = 2_000.0;
+        inputs.annuity_purchase_age = Some(60);
+        inputs.annuity_purchase_amount = 40_000.0;
+        inputs.annuity_rate = 0.05;
+
+        // Hand calculation: 40k buys 2k a year, which covers spending, so the remaining
+        // 60k pension is never drawn.
+        let mut rng = Rng::new(derive_seed(inputs.seed, 60, 0));
+        let scenario = simulate_scenario(&inputs, 60, 60, &mut rng, None);
+        assert!(scenario.success);
+        assert_approx(scenario.reported_retirement_pension, 100_000.0);
+        assert_approx(scenario.reported_terminal_pension, 60_000.0);
+
+        let rows = run_yearly_cashflow_trace(&inputs, 60, 60, 60);
+        assert_eq!(rows.len(), 2);
+        for row in &rows {
+            assert_approx(row.median_annuity_income, 2_000.0);
+            assert_approx(row.median_withdrawal_portfolio, 0.0);
+            assert_approx(row.median_spending_total, 2_000.0);
+            assert_approx(row.median_end_pension, 60_000.0);
+        }
+    }
+
+    #[test]
+    fn oracle_rental_income_covering_spending_needs_no_portfolio_withdrawal() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.horizon_age = 33;
+        inputs.isa_start = 50_000.0;
+        inputs.pension_start = 50_000.0;
+        inputs.target_annual_income = 20_000.0;
+        inputs.rental_income_start_age = 30;
+        inputs.rental_annual_income = 25_000.0;
+        inputs.rental_growth_rate = 0.02;
+        inputs.pension_flat_tax_rate = 0.20;
+
+        // Hand calculation: 25k gross less 20% tax is 20k net at 30, growing 2% a year.
+        // Spending is fully covered every year; the 2% surplus goes to cash.
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_eq!(rows.len(), 3);
+        assert_approx(rows[0].median_rental_income, 25_000.0);
+        assert_approx(rows[1].median_rental_income, 25_500.0);
+        assert_approx(rows[2].median_rental_income, 26_010.0);
+        for row in &rows {
+            assert_approx(row.median_withdrawal_portfolio, 0.0);
+            assert_approx(row.median_spending_total, 20_000.0);
+            assert_approx(row.median_end_isa, 50_000.0);
+            assert_approx(row.median_end_pension, 50_000.0);
+        }
+        assert_approx(rows[0].median_tax_income, 5_000.0);
+        assert_approx(rows[1].median_end_cash, 400.0);
+    }
+
+    #[test]
+    fn oracle_state_pension_real_growth_compounds_from_start_age() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.horizon_age = 45;
+        inputs.isa_start = 0.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.state_pension_start_age = 35;
+        inputs.state_pension_annual_income = 10_000.0;
+        inputs.state_pension_real_growth = 0.01;
+        inputs.pension_flat_tax_rate = 0.20;
+
+        // Hand calculation: flat 20% tax on gross, so tax is a fifth of the state
+        // pension. Year 1 (35) pays 10,000; year 10 (44) pays 10,000 * 1.01^9.
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_eq!(rows.len(), 15);
+        assert_approx(rows[4].median_tax_income, 0.0);
+        assert_approx(rows[5].median_tax_income, 2_000.0);
+        assert_approx(rows[14].median_tax_income, 2_000.0 * 1.01_f64.powi(9));
+
+        // Growing while deferred, the first payment already carries five years.
+        inputs.state_pension_growth_before_start = true;
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_approx(rows[5].median_tax_income, 2_000.0 * 1.01_f64.powi(5));
+        assert_approx(rows[14].median_tax_income, 2_000.0 * 1.01_f64.powi(14));
+    }
+
+    #[test]
+    fn oracle_state_pension_deferral_starts_later_with_uplift() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.horizon_age = 40;
+        inputs.isa_start = 0.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.state_pension_start_age = 35;
+        inputs.state_pension_annual_income = 10_000.0;
+        inputs.state_pension_deferral_years = 2;
+        inputs.state_pension_deferral_uplift_rate = 0.058;
+        inputs.pension_flat_tax_rate = 0.20;
+
+        // Hand calculation: claiming at 37 instead of 35 pays 10,000 * 1.058^2 =
+        // 11,193.64 a year, about 11.9% more; tax is a flat fifth of it.
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_eq!(rows.len(), 10);
+        assert_approx(rows[5].median_tax_income, 0.0);
+        assert_approx(rows[6].median_tax_income, 0.0);
+        assert_approx(rows[7].median_tax_income, 2_238.728);
+        assert_approx(rows[9].median_tax_income, 2_238.728);
+    }
+
+    #[test]
+    fn oracle_frozen_thresholds_raise_real_tax_on_the_same_real_income() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.horizon_age = 34;
+        inputs.isa_start = 0.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.inflation_mean = 0.10;
+        inputs.pension_tax_mode = PensionTaxMode::UkBands;
+        inputs.state_pension_start_age = 30;
+        inputs.state_pension_annual_income = 20_000.0;
+
+        // Hand calculation: indexed bands tax (20,000 - 12,570) @ 20% = 1,486 real every
+        // year.
+        let indexed = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_eq!(indexed.len(), 4);
+        for row in &indexed {
+            assert_approx(row.median_tax_income, 1_486.0);
+        }
+
+        // Frozen until 32: the allowance stays at 12,570 nominal while prices rise 10% a
+        // year, so real tax is 4,000 - 2,514 / price_index. Once indexing resumes the
+        // lost ground is not recovered.
+        inputs.thresholds_frozen_until_age = Some(32);
+        let frozen = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_approx(frozen[0].median_tax_income, 4_000.0 - 2_514.0 / 1.1);
+        assert_approx(frozen[1].median_tax_income, 4_000.0 - 2_514.0 / 1.21);
+        assert_approx(frozen[2].median_tax_income, 4_000.0 - 2_514.0 / 1.21);
+        assert_approx(frozen[3].median_tax_income, 4_000.0 - 2_514.0 / 1.21);
+    }
+
+    #[test]
+    fn oracle_household_split_uses_two_personal_allowances() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.isa_start = 0.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 1_000_000.0;
+        inputs.pension_tax_free_pct = 0.0;
+        inputs.pension_tax_mode = PensionTaxMode::UkBands;
+        inputs.target_annual_income = 34_514.0;
+
+        // Hand calculation: one person draws 40,000 gross and pays
+        // (40,000 - 12,570) @ 20% = 5,486, leaving 34,514.
+        let single = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_approx(single[0].median_tax_income, 5_486.0);
+        assert_approx(single[0].median_spending_total, 34_514.0);
+
+        // A couple with equal pots draws G gross, G / 2 each, and pays
+        // 2 * (G / 2 - 12,570) @ 20% = 0.2 G - 5,028. Netting 34,514 needs
+        // G = 36,857.50, so tax falls to 2,343.50.
+        inputs.household = true;
+        inputs.pension_start = 500_000.0;
+        inputs.partner_pension_start = 500_000.0;
+        inputs.partner_pension_access_age = 30;
+        let couple = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_approx(couple[0].median_tax_income, 2_343.5);
+        assert_approx(couple[0].median_spending_total, 34_514.0);
+        assert_approx(couple[0].median_end_pension, 1_000_000.0 - 36_857.5);
+
+        // The same 40,000 gross split evenly pays 2 * 1,486 = 2,972.
+        let tax_state = TaxYearState {
+            non_pension_taxable_income: 0.0,
+            pension_gross_withdrawn: 20_000.0,
+            pension_tax_free_withdrawn: 0.0,
+            pension_tax_free_remaining: 0.0,
+            partner: PartnerTaxState {
+                pension_gross_withdrawn: 20_000.0,
+                ..PartnerTaxState::default()
+            },
+            price_index: 1.0,
+        };
+        assert_approx(tax_state.income_tax(&inputs), 2_972.0);
+    }
+
+    #[test]
+    fn marriage_allowance_transfer_cuts_a_basic_rate_couples_tax() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.pension_tax_mode = PensionTaxMode::UkBands;
+        inputs.household = true;
+        let tax_state = TaxYearState {
+            non_pension_taxable_income: 30_000.0,
+            pension_gross_withdrawn: 0.0,
+            pension_tax_free_withdrawn: 0.0,
+            pension_tax_free_remaining: 0.0,
+            partner: PartnerTaxState::default(),
+            price_index: 1.0,
+        };
+        // (30,000 - 12,570) @ 20% = 3,486 with a non-earning partner.
+        assert_approx(tax_state.income_tax(&inputs), 3_486.0);
+
+        // Moving the full 1,260 saves 1,260 @ 20% = 252; the partner still pays nothing.
+        inputs.marriage_allowance_transfer = 1_260.0;
+        assert_approx(tax_state.income_tax(&inputs), 3_486.0 - 252.0);
+    }
+
+    #[test]
+    fn marriage_allowance_needs_a_basic_rate_recipient_and_a_low_income_transferor() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.pension_tax_mode = PensionTaxMode::UkBands;
+        inputs.household = true;
+        let tax_state = |primary: f64, partner: f64| TaxYearState {
+            non_pension_taxable_income: primary,
+            pension_gross_withdrawn: 0.0,
+            pension_tax_free_withdrawn: 0.0,
+            pension_tax_free_remaining: 0.0,
+            partner: PartnerTaxState {
+                pension_gross_withdrawn: partner,
+                ..PartnerTaxState::default()
+            },
+            price_index: 1.0,
+        };
+
+        // A higher-rate recipient keeps only their own allowance.
+        let higher_rate = tax_state(60_000.0, 0.0);
+        let without = higher_rate.income_tax(&inputs);
+        inputs.marriage_allowance_transfer = 1_260.0;
+        assert_approx(higher_rate.income_tax(&inputs), without);
+
+        // So does a basic-rate recipient whose partner earns above their own allowance:
+        // 17,430 @ 20% for the primary plus 7,430 @ 20% for the partner.
+        assert_approx(tax_state(30_000.0, 20_000.0).income_tax(&inputs), 4_972.0);
+    }
+
+    #[test]
+    fn split_pension_gross_fills_the_lower_earner_first() {
+        let tax_state = TaxYearState {
+            non_pension_taxable_income: 10_000.0,
+            pension_gross_withdrawn: 0.0,
+            pension_tax_free_withdrawn: 0.0,
+            pension_tax_free_remaining: 0.0,
+            partner: PartnerTaxState::default(),
+            price_index: 1.0,
+        };
+        let pots = PensionPots {
+            primary: 100_000.0,
+            partner: 100_000.0,
+        };
+        assert_eq!(
+            split_pension_gross(6_000.0, pots, &tax_state),
+            (0.0, 6_000.0)
+        );
+        assert_eq!(
+            split_pension_gross(30_000.0, pots, &tax_state),
+            (10_000.0, 20_000.0)
+        );
+
+        // A short partner pot hands the rest back to the primary.
+        let pots = PensionPots {
+            primary: 100_000.0,
+            partner: 5_000.0,
+        };
+        assert_eq!(
+            split_pension_gross(30_000.0, pots, &tax_state),
+            (25_000.0, 5_000.0)
+        );
+    }
+
+    #[test]
+    fn oracle_dividend_tax_is_deducted_from_taxable_and_reported_as_income_tax() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.pension_access_age = 57;
+        inputs.isa_start = 0.0;
+        inputs.taxable_start = 100_000.0;
+        inputs.taxable_cost_basis_start = 100_000.0;
+        inputs.pension_start = 0.0;
+        inputs.taxable_dividend_yield = 0.02;
+        inputs.dividend_allowance = 500.0;
+        inputs.state_pension_start_age = 30;
+        inputs.state_pension_annual_income = 30_000.0;
+
+        // Hand calculation: dividends 2,000 on top of 30,000 income, all in the basic
+        // band. Taxed: 2,000 - 500 allowance = 1,500 @ 8.75% = 131.25.
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_eq!(rows.len(), 1);
+        assert_approx(rows[0].median_tax_income, 131.25);
+        assert_approx(rows[0].median_tax_total, 131.25);
+        assert_approx(rows[0].median_end_taxable, 100_000.0 - 131.25);
+    }
+
+    #[test]
+    fn oracle_pre_retirement_dividends_stack_on_earnings() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.max_retirement_age = 31;
+        inputs.horizon_age = 32;
+        inputs.isa_start = 0.0;
+        inputs.taxable_start = 100_000.0;
+        inputs.taxable_cost_basis_start = 100_000.0;
+        inputs.pension_start = 0.0;
+        inputs.taxable_dividend_yield = 0.02;
+        inputs.dividend_allowance = 500.0;
+        let taxable_at_retirement = |earnings: f64| {
+            let inputs = Inputs {
+                pre_retirement_earnings: earnings,
+                ..inputs.clone()
+            };
+            let mut rng = Rng::new(derive_seed(inputs.seed, 31, 0));
+            simulate_scenario(&inputs, 31, 31, &mut rng, None).reported_retirement_taxable
+        };
+
+        // Hand calculation: 2,000 of dividends in the working year at 30. Without
+        // earnings they sit inside the personal allowance and pay nothing.
+        assert_approx(taxable_at_retirement(0.0), 100_000.0);
+        // On 60,000 of earnings they land in the higher band:
+        // (2,000 - 500 allowance) @ 33.75% = 506.25.
+        assert_approx(taxable_at_retirement(60_000.0), 100_000.0 - 506.25);
+    }
+
+    #[test]
+    fn dividend_tax_uses_the_band_the_dividends_land_in() {
+        let inputs = sample_inputs();
+        // Inside the personal allowance: nothing to pay.
+        assert_approx(dividend_tax(&inputs, 2_000.0, 0.0, 1.0), 0.0);
+        // The allowance fills the last 500 of the basic band; 1,500 lands in higher.
+        assert_approx(
+            dividend_tax(&inputs, 2_000.0, 49_770.0, 1.0),
+            1_500.0 * 0.3375,
+        );
+        // Straddling the basic limit: 1,000 basic, 500 higher.
+        assert_approx(
+            dividend_tax(&inputs, 2_000.0, 48_770.0, 1.0),
+            1_000.0 * 0.0875 + 500.0 * 0.3375,
+        );
+        // Below the allowance nothing is taxed wherever it lands.
+        assert_approx(dividend_tax(&inputs, 400.0, 200_000.0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn oracle_bond_ladder_draws_evenly_before_other_pots() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.current_age = 30;
+        inputs.max_retirement_age = 30;
+        inputs.horizon_age = 33;
+        inputs.pension_access_age = 57;
+
+        inputs.isa_start = 0.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.cash_start = 0.0;
+        inputs.bond_ladder_start = 90.0;
+        inputs.bond_ladder_yield = 0.0;
+        inputs.bond_ladder_years = 3;
+        inputs.target_annual_income = 30.0;
+
+        let mut rng = Rng::new(derive_seed(inputs.seed, 30, 0));
+        let scenario = simulate_scenario(&inputs, 30, 30, &mut rng, None);
+        assert!(scenario.success);
+        assert_approx(scenario.reported_terminal_bond_ladder, 0.0);
+        assert_approx(scenario.reported_terminal_total, 0.0);
+
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_eq!(rows.len(), 3);
+        assert_approx(rows[0].median_end_bond_ladder, 60.0);
+        assert_approx(rows[1].median_end_bond_ladder, 30.0);
+        assert_approx(rows[2].median_end_bond_ladder, 0.0);
+    }
+
+    #[test]
+    fn oracle_reinvested_bond_ladder_keeps_its_length() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.horizon_age = 33;
+        inputs.pension_access_age = 57;
+        inputs.isa_start = 100.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.cash_start = 0.0;
+        inputs.bond_ladder_start = 90.0;
+        inputs.bond_ladder_yield = 0.0;
+        inputs.bond_ladder_years = 3;
+        inputs.bond_ladder_reinvest = true;
+        inputs.target_annual_income = 30.0;
+
+        // Spending fully covered by the state pension: the ladder is never drawn.
+        inputs.state_pension_start_age = 30;
+        inputs.state_pension_annual_income = 30.0;
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        for row in &rows {
+            assert_approx(row.median_end_bond_ladder, 90.0);
+            assert_approx(row.median_end_isa, 100.0);
+        }
+
+        // Hand calculation, ladder and ISA only: each rung is a third of the balance.
+        // Ladder 90 -> 60 -> 40 -> 26.67, the ISA covering 0, 10 and 16.67.
+        inputs.state_pension_annual_income = 0.0;
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_approx(rows[0].median_end_bond_ladder, 60.0);
+        assert_approx(rows[1].median_end_bond_ladder, 40.0);
+        assert_approx(rows[2].median_end_bond_ladder, 80.0 / 3.0);
+        assert_approx(rows[2].median_end_isa, 100.0 - 10.0 - 50.0 / 3.0);
+
+        // Without reinvesting, the same ladder pays fixed rungs of 30 and is spent by
+        // the third year, leaving the ISA untouched.
+        inputs.bond_ladder_reinvest = false;
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_approx(rows[0].median_end_bond_ladder, 60.0);
+        assert_approx(rows[1].median_end_bond_ladder, 30.0);
+        assert_approx(rows[2].median_end_bond_ladder, 0.0);
+        assert_approx(rows[2].median_end_isa, 100.0);
+    }
+
+    #[test]
+    fn oracle_deterministic_crash_path_runs_the_plan_dry() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.horizon_age = 33;
+        inputs.isa_start = 100.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.cash_start = 0.0;
+        inputs.target_annual_income = 30.0;
+
+        // Flat markets: 100 covers three years of 30 with 10 left over.
+        inputs.market_model = MarketModel::Deterministic {
+            paths: vec![(0.0, 0.0, 0.0, 0.0)],
+        };
+        let mut rng = Rng::new(derive_seed(inputs.seed, 30, 0));
+        let scenario = simulate_scenario(&inputs, 30, 30, &mut rng, None);
+        assert!(scenario.success);
+        assert_approx(scenario.reported_terminal_total, 10.0);
+
+        // Halving the ISA in the first year leaves too little for the third.
+        inputs.market_model = MarketModel::Deterministic {
+            paths: vec![(-0.5, -0.5, -0.5, 0.0), (0.0, 0.0, 0.0, 0.0)],
+        };
+        let mut rng = Rng::new(derive_seed(inputs.seed, 30, 0));
+        let scenario = simulate_scenario(&inputs, 30, 30, &mut rng, None);
+        assert!(!scenario.success);
+        assert!(scenario.min_income_ratio < 1.0);
+    }
+
+    #[test]
+    fn oracle_max_drawdown_measures_the_crash_from_its_peak() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.horizon_age = 34;
+        inputs.isa_start = 100.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.cash_start = 0.0;
+
+        // 100 -> 120 (peak) -> 60 (trough) -> 120 -> 120: a 50% drawdown that the
+        // recovery does not undo.
+        inputs.market_model = MarketModel::Deterministic {
+            paths: vec![
+                (0.2, 0.2, 0.2, 0.0),
+                (-0.5, -0.5, -0.5, 0.0),
+                (1.0, 1.0, 1.0, 0.0),
+                (0.0, 0.0, 0.0, 0.0),
+            ],
+        };
+        let mut rng = Rng::new(derive_seed(inputs.seed, 30, 0));
+        let scenario = simulate_scenario(&inputs, 30, 30, &mut rng, None);
+        assert!(scenario.success);
+        assert_approx(scenario.reported_terminal_total, 120.0);
+        assert_approx(scenario.max_drawdown, 0.5);
+
+        // Spending 20 a year: 100 -> 96 -> 38 -> 36 -> 16. The peak is the starting 100,
+        // so spending alone carries the drawdown past the crash's 50%.
+        inputs.target_annual_income = 20.0;
+        let mut rng = Rng::new(derive_seed(inputs.seed, 30, 0));
+        let scenario = simulate_scenario(&inputs, 30, 30, &mut rng, None);
+        assert!(scenario.success);
+        assert_approx(scenario.reported_terminal_total, 16.0);
+        assert_approx(scenario.max_drawdown, 0.84);
+    }
+
+    #[test]
+    fn oracle_min_runway_reports_the_tightest_year() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.horizon_age = 34;
+        inputs.isa_start = 100.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.cash_start = 0.0;
+        inputs.target_annual_income = 20.0;
+
+        // Flat markets: the pot starts the years at 100, 80, 60 and 40, so at its
+        // tightest it holds two years of spending and still ends with 20.
+        let mut rng = Rng::new(derive_seed(inputs.seed, 30, 0));
+        let scenario = simulate_scenario(&inputs, 30, 30, &mut rng, None);
+        assert!(scenario.success);
+        assert_approx(scenario.reported_terminal_total, 20.0);
+        assert_approx(scenario.min_runway_years, 2.0);
+
+        // Nothing to fund: the runway reads as the cap rather than dividing by zero.
+        inputs.target_annual_income = 0.0;
+        let mut rng = Rng::new(derive_seed(inputs.seed, 30, 0));
+        let scenario = simulate_scenario(&inputs, 30, 30, &mut rng, None);
+        assert_approx(scenario.min_runway_years, MAX_RUNWAY_YEARS);
+    }
+
+    #[test]
+    fn oracle_conversion_ladder_moves_pension_into_isa_net_of_tax() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.isa_start = 0.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 100_000.0;
+        inputs.cash_start = 0.0;
+        inputs.target_annual_income = 0.0;
+        inputs.pension_tax_free_pct = 0.0;
+        inputs.pension_flat_tax_rate = 0.20;
+        inputs.isa_annual_contribution_limit = 20_000.0;
+        inputs.conversion_ladder_annual_amount = 30_000.0;
+        inputs.conversion_ladder_start_age = 30;
+        inputs.conversion_ladder_end_age = 31;
+
+        // Hand calculation: 30k gross less 20% tax is 24k net; the ISA takes its 20k
+        // limit and the other 4k lands in the taxable account.
+        let mut rng = Rng::new(derive_seed(inputs.seed, 30, 0));
+        let scenario = simulate_scenario(&inputs, 30, 30, &mut rng, None);
+        assert!(scenario.success);
+        assert_approx(scenario.reported_terminal_pension, 70_000.0);
+        assert_approx(scenario.lifetime_tax_paid, 6_000.0);
+
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_eq!(rows.len(), 1);
+        assert_approx(rows[0].median_pension_conversion, 30_000.0);
+        assert_approx(rows[0].median_tax_income, 6_000.0);
+        assert_approx(rows[0].median_end_pension, 70_000.0);
+        assert_approx(rows[0].median_end_isa, 20_000.0);
+        assert_approx(rows[0].median_end_taxable, 4_000.0);
+
+        // The end age is exclusive, so an empty window converts nothing.
+        inputs.conversion_ladder_end_age = 30;
+        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
+        assert_approx(rows[0].median_pension_conversion, 0.0);
+        assert_approx(rows[0].median_end_pension, 100_000.0);
+        assert_approx(rows[0].median_end_isa, 0.0);
+    }
+
+    #[test]
+    fn retirement_at_horizon_reports_finite_income_ratios() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.horizon_age = 31;
+
+        // Retiring at the horizon leaves the retirement loop with nothing to run.
+        let mut rng = Rng::new(derive_seed(inputs.seed, 31, 0));
+        let scenario = simulate_scenario(&inputs, 31, 31, &mut rng, None);
+        assert!(scenario.success);
+        assert!(scenario.avg_income_ratio.is_finite());
+        assert!(scenario.min_income_ratio.is_finite());
+        assert_approx(scenario.avg_income_ratio, 1.0);
+        assert_approx(scenario.min_income_ratio, 1.0);
+    }
+
+    #[test]
+    fn pre_retirement_contributions_apply_isa_cap_and_overflow() {
+        let inputs = sample_inputs();
+        let mut portfolio = Portfolio {
+            isa: 0.0,
+            lisa: 0.0,
+            taxable: 0.0,
+            taxable_basis: 0.0,
+            pension: 0.0,
+            partner_pension: 0.0,
+            cash_buffer: 0.0,
+            bond_ladder: 0.0,
+        };
+
+        apply_pre_retirement_contributions(&inputs, &mut portfolio, 0, 1.0, 1.0);
+        assert_approx(portfolio.isa, 20_000.0);
+        assert_approx(portfolio.taxable, 15_000.0);
+        assert_approx(portfolio.taxable_basis, 15_000.0);
+    }
+
+    #[test]
+    fn pre_retirement_contributions_clamp_negative_values() {
+        let mut inputs = sample_inputs();
+        inputs.isa_annual_contribution = -100.0;
+        inputs.taxable_annual_contribution = -50.0;
+        inputs.pension_annual_contribution = -1.0;
+
+        let mut portfolio = Portfolio {
+            isa: 1_000.0,
+            lisa: 0.0,
+            taxable: 2_000.0,
+            taxable_basis: 2_000.0,
+            pension: 3_000.0,
+            partner_pension: 0.0,
+            cash_buffer: 0.0,
+            bond_ladder: 0.0,
+        };
+
+        apply_pre_retirement_contributions(&inputs, &mut portfolio, 0, 1.0, 1.0);
+        assert_approx(portfolio.isa, 1_000.0);
+        assert_approx(portfolio.taxable, 2_000.0);
+        assert_approx(portfolio.pension, 3_000.0);
+    }
+
+    #[test]
+    fn pre_retirement_contributions_grow_annually_with_rate() {
+        let mut inputs = sample_inputs();
+        inputs.contribution_growth_rate = 0.10;
+        let mut portfolio = Portfolio {
+            isa: 0.0,
+            lisa: 0.0,
+            taxable: 0.0,
+            taxable_basis: 0.0,
+            pension: 0.0,
+            partner_pension: 0.0,
+            cash_buffer: 0.0,
+            bond_ladder: 0.0,
+        };
+
+        apply_pre_retirement_contributions(&inputs, &mut portfolio, 1, 1.0, 1.0);
+        assert_approx(portfolio.isa, 20_000.0);
+        assert_approx(portfolio.taxable, 18_500.0);
+        assert_approx(portfolio.taxable_basis, 18_500.0);
+    }
+
+    #[test]
+    fn oracle_inflation_tracked_contributions_stay_flat_in_real_terms() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.max_retirement_age = 34;
+        inputs.horizon_age = 35;
+        inputs.isa_start = 0.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.isa_annual_contribution = 1_000.0;
+        inputs.inflation_mean = 0.05;
+        inputs.contribution_growth_rate = 0.02;
+
+        // Compounding at 2% against 5% inflation: the real contribution in year y is
+        // 1,000 * 1.02^y / 1.05^(y + 1).
+        let rows = run_yearly_cashflow_trace(&inputs, 34, 34, 34);
+        for (y, row) in rows.iter().take(4).enumerate() {
+            let expected = 1_000.0 * 1.02_f64.powi(y as i32) / 1.05_f64.powi(y as i32 + 1);
+            assert_approx(row.median_contribution_isa, expected);
+        }
+
+        // Tracking the price index ignores the growth rate and pays 1,000 in today's
+        // money every year.
+        inputs.contributions_track_inflation = true;
+        let rows = run_yearly_cashflow_trace(&inputs, 34, 34, 34);
+        for row in rows.iter().take(4) {
+            assert_approx(row.median_contribution_isa, 1_000.0);
+        }
+        assert_approx(rows[4].median_contribution_isa, 0.0);
+    }
+
+    #[test]
+    fn uk_tax_bands_apply_progressive_rates() {
+        let mut inputs = sample_inputs();
+        inputs.pension_tax_mode = PensionTaxMode::UkBands;
+        let tax = income_tax_for_total_income(60_000.0, 0.0, &inputs, 1.0);
+        assert!((tax - 11_432.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn scottish_tax_bands_match_hmrc_worked_examples() {
+        let mut inputs = sample_inputs();
+        inputs.pension_tax_mode = PensionTaxMode::ScottishBands;
+
+        // 2025/26 Scottish rates on 30,000: starter 2,827 @ 19% = 537.13,
+        // basic 12,094 @ 20% = 2,418.80, intermediate 2,509 @ 21% = 526.89.
+        let tax = income_tax_for_total_income(30_000.0, 0.0, &inputs, 1.0);
+        assert!((tax - 3_482.82).abs() < 1e-3, "{tax}");
+
+        // 50,000 adds the full intermediate band (16,171 @ 21% = 3,395.91) and
+        // 6,338 @ 42% = 2,661.96 in the higher band.
+        let tax = income_tax_for_total_income(50_000.0, 0.0, &inputs, 1.0);
+        assert!((tax - 9_013.80).abs() < 1e-3, "{tax}");
+
+        // Income inside the allowance is untaxed, and the taper removes it entirely.
+        assert_eq!(
+            income_tax_for_total_income(12_000.0, 0.0, &inputs, 1.0),
+            0.0
+        );
+        let above_taper = income_tax_for_total_income(200_000.0, 0.0, &inputs, 1.0);
+        let expected = 15_397.0 * 0.19
+            + 12_094.0 * 0.20
+            + 16_171.0 * 0.21
+            + 31_338.0 * 0.42
+            + 50_140.0 * 0.45
+            + 74_860.0 * 0.48;
+        assert!((above_taper - expected).abs() < 1e-3, "{above_taper}");
+    }
+
+    #[test]
+    fn state_pension_can_cover_spending_without_assets() {
+        let mut inputs = sample_inputs();
+        inputs.current_age = 30;
+        inputs.horizon_age = 31;
+        inputs.max_retirement_age = 30;
+        inputs.isa_start = 0.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.isa_annual_contribution = 0.0;
+        inputs.taxable_annual_contribution = 0.0;
+        inputs.pension_annual_contribution = 0.0;
+        inputs.target_annual_income = 10_000.0;
+        inputs.isa_return_mean = 0.0;
+        inputs.taxable_return_mean = 0.0;
+        inputs.pension_return_mean = 0.0;
+        inputs.isa_return_vol = 0.0;
+        inputs.taxable_return_vol = 0.0;
+        inputs.pension_return_vol = 0.0;
+        inputs.inflation_mean = 0.0;
+        inputs.inflation_vol = 0.0;
+        inputs.pension_tax_mode = PensionTaxMode::UkBands;
+        inputs.state_pension_start_age = 30;
+        inputs.state_pension_annual_income = 10_000.0;
+
+        let mut rng = Rng::new(1);
+        let s = simulate_scenario(&inputs, 30, 30, &mut rng, None);
+        assert!(s.success);
+    }
+
+    #[test]
+    fn required_spending_drops_after_mortgage_end_age() {
+        let mut inputs = sample_inputs();
+        inputs.target_annual_income = 30_000.0;
+        inputs.mortgage_annual_payment = 12_000.0;
+        inputs.mortgage_end_age = Some(40);
+
+        assert_approx(required_real_spending(&inputs, 35, 39, 1.0), 42_000.0);
+        assert_approx(required_real_spending(&inputs, 35, 40, 1.0), 30_000.0);
+        assert_approx(required_real_spending(&inputs, 35, 41, 1.0), 30_000.0);
+    }
+
+    #[test]
+    fn required_spending_steps_down_as_each_debt_ends() {
+        let mut inputs = sample_inputs();
+        inputs.target_annual_income = 30_000.0;
+        inputs.debts = vec![
+            Debt {
+                annual_payment: 12_000.0,
+                end_age: 50,
+            },
+            Debt {
+                annual_payment: 4_000.0,
+                end_age: 42,
+            },
+        ];
+
+        let required: Vec<f64> = (40..52)
+            .map(|age| required_real_spending(&inputs, 40, age, 1.0))
+            .collect();
+        assert_approx(required[0], 46_000.0);
+        assert_approx(required[2], 42_000.0);
+        assert_approx(required[10], 30_000.0);
+        let steps = required.windows(2).filter(|pair| pair[1] < pair[0]).count();
+        assert_eq!(steps, 2);
+    }
+
+    #[test]
+    fn oracle_shortfall_above_essential_spending_is_still_a_success() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.current_age = 60;
+        inputs.max_retirement_age = 60;
+        inputs.horizon_age = 62;
+        inputs.pension_access_age = 60;
+        inputs.isa_start = 70_000.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.cash_start = 0.0;
+        inputs.bond_ladder_start = 0.0;
+        inputs.target_annual_income = 40_000.0;
+        inputs.min_income_floor = 1.0;
+        inputs.max_income_ceiling = 1.0;
+        inputs.withdrawal_strategy = WithdrawalStrategy::FixedReal;
+
+        // Hand calculation: year one spends the full 40k, leaving 30k. Year two can only
+        // fund 30k, short of target but above the 20k essential spend.
+        let mut rng = Rng::new(derive_seed(inputs.seed, 60, 0));
+        let without_essential = simulate_scenario(&inputs, 60, 60, &mut rng, None);
+        assert!(!without_essential.success);
+
+        inputs.essential_annual_income = 20_000.0;
+        let mut rng = Rng::new(derive_seed(inputs.seed, 60, 0));
+        let with_essential = simulate_scenario(&inputs, 60, 60, &mut rng, None);
+        assert!(with_essential.success);
+        assert_approx(with_essential.reported_terminal_total, 0.0);
+        assert_approx(with_essential.min_income_ratio, 1.5);
+        assert_approx(with_essential.avg_income_ratio, (2.0 + 1.5) / 2.0);
+
+        inputs.essential_annual_income = 35_000.0;
+        let mut rng = Rng::new(derive_seed(inputs.seed, 60, 0));
+        let above_essential = simulate_scenario(&inputs, 60, 60, &mut rng, None);
+        assert!(!above_essential.success);
+    }
+
+    #[test]
+    fn oracle_success_criterion_judges_the_same_path_differently() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.horizon_age = 31;
+        inputs.pension_access_age = 32;
+        inputs.isa_start = 30.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 100.0;
+        inputs.cash_start = 0.0;
+        inputs.target_annual_income = 40.0;
+        inputs.essential_annual_income = 20.0;
+        inputs.withdrawal_strategy = WithdrawalStrategy::FixedReal;
+
+        let success = |inputs: &Inputs, criterion: SuccessCriterion| {
+            let mut inputs = inputs.clone();
+            inputs.success_criterion = criterion;
+            let mut rng = Rng::new(derive_seed(inputs.seed, 30, 0));
+            simulate_scenario(&inputs, 30, 30, &mut rng, None).success
+        };
+
+        // One year: the ISA funds 30 of the planned 40, above the essential 20.
+        assert!(!success(&inputs, SuccessCriterion::FullSpending));
+        assert!(success(&inputs, SuccessCriterion::EssentialFloorMet));
+        assert!(success(&inputs, SuccessCriterion::NoDepletion));
+
+        // Three years: age 31 funds nothing while the pension is locked, then the
+        // pension pays 40 at 32. Only the depletion rule survives the gap.
+        inputs.horizon_age = 33;
+        assert!(!success(&inputs, SuccessCriterion::FullSpending));
+        assert!(!success(&inputs, SuccessCriterion::EssentialFloorMet));
+        assert!(success(&inputs, SuccessCriterion::NoDepletion));
+
+        // With no pension to fall back on, the empty ISA at 31 is a depletion.
+        inputs.pension_start = 0.0;
+        assert!(!success(&inputs, SuccessCriterion::NoDepletion));
+    }
+
+    #[test]
+    fn oracle_bequest_target_above_terminal_pot_fails_scenario() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.current_age = 60;
+        inputs.max_retirement_age = 60;
+        inputs.horizon_age = 65;
+        inputs.pension_access_age = 60;
+        inputs.isa_start = 200_000.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.cash_start = 0.0;
+        inputs.bond_ladder_start = 0.0;
+        inputs.target_annual_income = 20_000.0;
+        inputs.withdrawal_strategy = WithdrawalStrategy::FixedReal;
+
+        // Five years at 20k leave exactly 100k.
+        let mut rng = Rng::new(derive_seed(inputs.seed, 60, 0));
+        let no_bequest = simulate_scenario(&inputs, 60, 60, &mut rng, None);
+        assert!(no_bequest.success);
+        assert_approx(no_bequest.reported_terminal_total, 100_000.0);
+
+        inputs.bequest_target = 100_000.0;
+        let mut rng = Rng::new(derive_seed(inputs.seed, 60, 0));
+        assert!(simulate_scenario(&inputs, 60, 60, &mut rng, None).success);
+
+        inputs.bequest_target = 100_001.0;
+        let mut rng = Rng::new(derive_seed(inputs.seed, 60, 0));
+        let short_of_bequest = simulate_scenario(&inputs, 60, 60, &mut rng, None);
+        assert!(!short_of_bequest.success);
+        assert_approx(short_of_bequest.reported_terminal_total, 100_000.0);
+
+        inputs.simulations = 20;
+        let model = run_model(&inputs).expect("inputs are valid");
+        assert_approx(model.age_results[0].success_rate, 0.0);
+    }
+
+    #[test]
+    fn oracle_unaffordable_lump_expense_fails_scenario_that_passes_without_it() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.current_age = 60;
+        inputs.max_retirement_age = 60;
+        inputs.horizon_age = 65;
+        inputs.pension_access_age = 60;
+        inputs.isa_start = 200_000.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.cash_start = 0.0;
+        inputs.bond_ladder_start = 0.0;
+        inputs.target_annual_income = 20_000.0;
+        inputs.withdrawal_strategy = WithdrawalStrategy::FixedReal;
+        inputs.inflation_mean = 0.02;
+
+        let mut rng = Rng::new(derive_seed(inputs.seed, 60, 0));
+        let baseline = simulate_scenario(&inputs, 60, 60, &mut rng, None);
+        assert!(baseline.success);
+        assert_approx(baseline.min_income_ratio, 1.0);
+
+        let rows = run_yearly_cashflow_trace(&inputs, 60, 60, 60);
+        assert_approx(rows[2].median_spending_total, 20_000.0);
+
+        // A 70k expense at 62 is affordable; 150k on top of 100k of spending is not.
+        inputs.lump_expenses = vec![(62, 70_000.0)];
+        let rows = run_yearly_cashflow_trace(&inputs, 60, 60, 60);
+        assert_approx(rows[2].median_spending_total, 90_000.0);
+        assert_approx(rows[3].median_spending_total, 20_000.0);
+
+        inputs.lump_expenses = vec![(62, 150_000.0)];
+        let mut rng = Rng::new(derive_seed(inputs.seed, 60, 0));
+        let with_expense = simulate_scenario(&inputs, 60, 60, &mut rng, None);
+        assert!(!with_expense.success);
+    }
+
+    #[test]
+    fn plan_real_spending_floor_comes_from_essential_spending() {
+        let mut inputs = sample_inputs();
+        inputs.withdrawal_strategy = WithdrawalStrategy::Vpw;
+        inputs.target_annual_income = 50_000.0;
+        inputs.min_income_floor = 0.9;
+        inputs.essential_annual_income = 25_000.0;
+
+        let mut spending_state = SpendingState {
+            current_real_spending: 50_000.0,
+            initial_withdrawal_rate: 0.04,
+            prev_nominal_spending: 0.0,
+            price_index: 1.0,
+            cape: 20.0,
+            smile_multiplier: 1.0,
+        };
+        let planned = plan_real_spending(&inputs, 60, 0.0, 100.0, &mut spending_state);
+        assert_approx(planned, 25_000.0);
+    }
+
+    #[test]
+    fn absolute_spending_floor_holds_when_the_target_is_small() {
+        let mut inputs = sample_inputs();
+        inputs.withdrawal_strategy = WithdrawalStrategy::FixedReal;
+        inputs.target_annual_income = 10_000.0;
+        inputs.min_income_floor = 0.8;
+        inputs.max_income_ceiling = 1.3;
+        inputs.min_real_spending_floor = 20_000.0;
+
+        assert_eq!(spending_bounds(&inputs, 1.0), (20_000.0, 20_000.0));
+        let mut spending_state = SpendingState {
+            current_real_spending: 10_000.0,
+            initial_withdrawal_rate: 0.04,
+            prev_nominal_spending: 0.0,
+            price_index: 1.0,
+            cape: 20.0,
+            smile_multiplier: 1.0,
+        };
+        let planned = plan_real_spending(&inputs, 60, 0.0, 500_000.0, &mut spending_state);
+        assert_approx(planned, 20_000.0);
+
+        // A zero target no longer means a zero floor.
+        inputs.target_annual_income = 0.0;
+        assert_eq!(spending_bounds(&inputs, 1.0).0, 20_000.0);
+        // Below the proportional floor the absolute one has no effect.
+        inputs.target_annual_income = 50_000.0;
+        let (min, max) = spending_bounds(&inputs, 1.0);
+        assert_approx(min, 40_000.0);
+        assert_approx(max, 65_000.0);
+    }
+
+    #[test]
+    fn spending_bounds_normalize_inverted_and_negative_ranges() {
+        let mut inputs = sample_inputs();
+        inputs.target_annual_income = 40_000.0;
+        inputs.min_income_floor = 1.3;
+        inputs.max_income_ceiling = 0.8;
+        let (min, max) = spending_bounds(&inputs, 1.0);
+        assert_approx(min, 32_000.0);
+        assert_approx(max, 52_000.0);
+
+        inputs.min_income_floor = -0.5;
+        inputs.max_income_ceiling = -1.0;
+        assert_eq!(spending_bounds(&inputs, 1.0), (0.0, 0.0));
+
+        inputs.min_income_floor = 0.8;
+        inputs.max_income_ceiling = 1.3;
+        inputs.target_annual_income = -10_000.0;
+        assert_eq!(spending_bounds(&inputs, 1.0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn spending_phases_scale_the_target_in_their_age_ranges() {
+        let mut inputs = sample_inputs();
+        inputs.target_annual_income = 40_000.0;
+        inputs.debts.clear();
+        inputs.active_phase_end_age = 75;
+        inputs.active_phase_uplift = 0.2;
+        inputs.late_phase_start_age = 85;
+        inputs.late_phase_uplift = -0.3;
+
+        for (age, multiplier) in [
+            (60, 1.2),
+            (74, 1.2),
+            (75, 1.0),
+            (84, 1.0),
+            (85, 0.7),
+            (95, 0.7),
+        ] {
+            assert_approx(spending_smile_multiplier(&inputs, 60, age), multiplier);
+            assert_approx(
+                required_real_spending(&inputs, 60, age, 1.0),
+                40_000.0 * multiplier,
+            );
+        }
+
+        // Phases stack with the care uplift.
+        inputs.care_cost_age = 90;
+        inputs.care_cost_uplift = 0.5;
+        assert_approx(spending_smile_multiplier(&inputs, 60, 90), 0.7 * 1.5);
+    }
+
+    #[test]
+    fn spending_smile_multiplier_declines_then_steps_up_for_care() {
+        let mut inputs = sample_inputs();
+        inputs.spending_decline_rate = 0.01;
+        inputs.care_cost_age = 85;
+        inputs.care_cost_uplift = 0.25;
+
+        assert_approx(spending_smile_multiplier(&inputs, 60, 55), 1.0);
+        assert_approx(spending_smile_multiplier(&inputs, 60, 60), 1.0);
+        assert_approx(
+            spending_smile_multiplier(&inputs, 60, 70),
+            0.99_f64.powi(10),
+        );
+        assert_approx(
+            spending_smile_multiplier(&inputs, 60, 84),
+            0.99_f64.powi(24),
+        );
+        assert_approx(
+            spending_smile_multiplier(&inputs, 60, 85),
+            0.99_f64.powi(25) * 1.25,
+        );
+
+        inputs.target_annual_income = 40_000.0;
+        inputs.debts.clear();
+        assert_approx(
+            required_real_spending(&inputs, 60, 70, 1.0),
+            40_000.0 * 0.99_f64.powi(10),
+        );
+    }
+
+    #[test]
+    fn oracle_fixed_real_spending_follows_smile_and_is_judged_against_it() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.current_age = 60;
+        inputs.max_retirement_age = 60;
+        inputs.horizon_age = 70;
+        inputs.pension_access_age = 60;
+        inputs.isa_start = 1_000_000.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.target_annual_income = 40_000.0;
+        inputs.min_income_floor = 0.5;
+        inputs.max_income_ceiling = 2.0;
+        inputs.withdrawal_strategy = WithdrawalStrategy::FixedReal;
+        inputs.spending_decline_rate = 0.02;
+        inputs.care_cost_age = 68;
+        inputs.care_cost_uplift = 0.30;
+
+        let rows = run_yearly_cashflow_trace(&inputs, 60, 60, 60);
+        assert_eq!(rows.len(), 10);
+        for (offset, row) in rows.iter().enumerate() {
+            let expected = 40_000.0 * spending_smile_multiplier(&inputs, 60, 60 + offset as u32);
+            assert_approx(row.median_spending_total, expected);
+        }
+        assert!(rows[8].median_spending_total > rows[7].median_spending_total);
+
+        let mut rng = Rng::new(derive_seed(inputs.seed, 60, 0));
+        let scenario = simulate_scenario(&inputs, 60, 60, &mut rng, None);
+        assert!(scenario.success);
+        assert_approx(scenario.min_income_ratio, 1.0);
+        assert_approx(scenario.avg_income_ratio, 1.0);
+    }
+
+    #[test]
+    fn mortgage_end_age_reduces_required_spending_in_retirement() {
+        let mut inputs = sample_inputs();
+        inputs.current_age = 30;
+        inputs.max_retirement_age = 30;
+        inputs.horizon_age = 32;
+        inputs.pension_access_age = 30;
+        inputs.isa_start = 25_000.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.cash_start = 0.0;
+        inputs.isa_annual_contribution = 0.0;
+        inputs.taxable_annual_contribution = 0.0;
+        inputs.pension_annual_contribution = 0.0;
+        inputs.contribution_growth_rate = 0.0;
+        inputs.target_annual_income = 10_000.0;
+        inputs.debts = vec![Debt {
+            annual_payment: 5_000.0,
+            end_age: 31,
+        }];
+        inputs.isa_return_mean = 0.0;
+        inputs.taxable_return_mean = 0.0;
+        inputs.pension_return_mean = 0.0;
+        inputs.isa_return_vol = 0.0;
+        inputs.taxable_return_vol = 0.0;
+        inputs.pension_return_vol = 0.0;
+        inputs.inflation_mean = 0.0;
+        inputs.inflation_vol = 0.0;
+        inputs.cash_growth_rate = 0.0;
+        inputs.taxable_return_tax_drag = 0.0;
+        inputs.cgt_basic_rate = 0.0;
+        inputs.cgt_higher_rate = inputs.cgt_basic_rate;
+        inputs.capital_gains_allowance = 0.0;
+        inputs.pension_tax_mode = PensionTaxMode::FlatRate;
+        inputs.pension_flat_tax_rate = 0.0;
+        inputs.state_pension_start_age = 200;
+        inputs.state_pension_annual_income = 0.0;
+        inputs.withdrawal_strategy = WithdrawalStrategy::Guardrails;
+        inputs.bad_year_threshold = -1.0;
+        inputs.good_year_threshold = 1.0;
+        inputs.bad_year_cut = 0.0;
+        inputs.good_year_raise = 0.0;
+        inputs.min_income_floor = 1.0;
+        inputs.max_income_ceiling = 1.0;
+        inputs.good_year_extra_buffer_withdrawal = 0.0;
+        inputs.post_access_withdrawal_order = WithdrawalOrder::IsaFirst;
+
+        let mut rng = Rng::new(123);
+        let ends_early = simulate_scenario(&inputs, 30, 30, &mut rng, None);
+        assert!(ends_early.success);
+        assert_approx(ends_early.reported_terminal_total, 0.0);
+        assert_approx(ends_early.min_income_ratio, 1.0);
+
+        inputs.debts[0].end_age = 35;
+        let mut rng2 = Rng::new(123);
+        let ends_late = simulate_scenario(&inputs, 30, 30, &mut rng2, None);
+        assert!(!ends_late.success);
+        assert!(ends_late.min_income_ratio < 1.0);
+    }
+
+    #[test]
+    fn net_from_taxable_gross_with_no_gain_has_no_tax() {
+        let cgt = CgtState {
+            allowance_remaining: 3_000.0,
+            basic_band_remaining: 0.0,
+            tax_paid: 0.0,
+        };
+        let net = net_from_taxable_gross(100.0, 200.0, 200.0, &cgt, FLAT_CGT_20);
+        assert_approx(net, 100.0);
+    }
+
+    #[test]
+    fn net_from_taxable_gross_applies_allowance_then_tax() {
+        let cgt = CgtState {
+            allowance_remaining: 10.0,
+            basic_band_remaining: 0.0,
+            tax_paid: 0.0,
+        };
+        let net = net_from_taxable_gross(50.0, 100.0, 40.0, &cgt, FLAT_CGT_20);
+        assert_approx(net, 46.0);
+    }
+
+    #[test]
+    fn execute_taxable_sale_updates_value_basis_and_allowance() {
+        let mut taxable = 100.0;
+        let mut basis = 40.0;
+        let mut cgt = CgtState {
+            allowance_remaining: 10.0,
+            basic_band_remaining: 0.0,
+            tax_paid: 0.0,
+        };
+
+        let net = execute_taxable_sale(50.0, &mut taxable, &mut basis, &mut cgt, FLAT_CGT_20);
+        assert_approx(net, 46.0);
+        assert_approx(taxable, 50.0);
+        assert_approx(basis, 20.0);
+        assert_approx(cgt.allowance_remaining, 0.0);
+    }
+
+    #[test]
+    fn large_gain_spans_both_cgt_bands() {
+        let mut inputs = sample_inputs();
+        inputs.cgt_basic_rate = 0.18;
+        inputs.cgt_higher_rate = 0.24;
+        // 20,000 of other income leaves 50,270 - 20,000 of basic band for gains.
+        let headroom = cgt_basic_band_remaining(&inputs, 20_000.0, 1.0);
+        assert_approx(headroom, 30_270.0);
+        // Income inside the personal allowance still leaves only the band width.
+        assert_approx(cgt_basic_band_remaining(&inputs, 5_000.0, 1.0), 37_700.0);
+
+        let mut taxable = 100_000.0;
+        let mut basis = 20_000.0;
+        let mut cgt = CgtState {
+            allowance_remaining: 3_000.0,
+            basic_band_remaining: headroom,
+            tax_paid: 0.0,
+        };
+        let rates = CgtRates::from_inputs(&inputs);
+        let quoted = net_from_taxable_gross(taxable, taxable, basis, &cgt, rates);
+
+        // Gain 80,000 - 3,000 exempt = 77,000: 30,270 at 18%, 46,730 at 24%.
+        let expected_tax = 30_270.0 * 0.18 + 46_730.0 * 0.24;
+        let net = execute_taxable_sale(100_000.0, &mut taxable, &mut basis, &mut cgt, rates);
+        assert_approx(cgt.tax_paid, expected_tax);
+        assert_approx(net, 100_000.0 - expected_tax);
+        assert_approx(quoted, net);
+        assert_approx(cgt.basic_band_remaining, 0.0);
+    }
+
+    #[test]
+    fn withdraw_from_taxable_for_net_targets_net_amount() {
+        let mut taxable = 100.0;
+        let mut basis = 40.0;
+        let mut cgt = CgtState {
+            allowance_remaining: 10.0,
+            basic_band_remaining: 0.0,
+            tax_paid: 0.0,
+        };
+
+        let withdrawn =
+            withdraw_from_taxable_for_net(46.0, &mut taxable, &mut basis, &mut cgt, FLAT_CGT_20);
+        assert!((withdrawn - 46.0).abs() < 1e-3);
+        assert!(taxable < 100.0);
+        assert!(basis < 40.0);
+    }
+
+    #[test]
+    fn harvest_cgt_allowance_realizes_gains_up_to_the_allowance_tax_free() {
+        let inputs = sample_inputs();
+        let mut portfolio = Portfolio {
+            isa: 100_000.0,
+            lisa: 0.0,
+            taxable: 100_000.0,
+            taxable_basis: 50_000.0,
+            pension: 0.0,
+            partner_pension: 0.0,
+            cash_buffer: 0.0,
+            bond_ladder: 0.0,
+        };
+        let mut cgt = CgtState {
+            allowance_remaining: 3_000.0,
+            basic_band_remaining: 0.0,
+            tax_paid: 0.0,
+        };
+        let mut tax_state = TaxYearState {
+            non_pension_taxable_income: 0.0,
+            pension_gross_withdrawn: 0.0,
+            pension_tax_free_withdrawn: 0.0,
+            pension_tax_free_remaining: 0.0,
+            partner: PartnerTaxState::default(),
+            price_index: 1.0,
+        };
+
+        let withdrawn = withdraw_from_portfolio(
+            &inputs,
+            inputs.pension_access_age,
+            20_000.0,
+            &mut portfolio,
+            &mut cgt,
+            &mut tax_state,
+            WithdrawalOrder::HarvestCgtAllowance,
+        );
+
+        // Half of each pound sold is gain, so a 6,000 sale realizes exactly the 3,000
+        // allowance; the other 14,000 comes from the ISA.
+        assert_approx(withdrawn, 20_000.0);
+        assert_approx(portfolio.taxable, 94_000.0);
+        assert_approx(portfolio.taxable_basis, 47_000.0);
+        assert_approx(portfolio.isa, 86_000.0);
+        assert_approx(cgt.allowance_remaining, 0.0);
+        assert_approx(cgt.tax_paid, 0.0);
+    }
+
+    #[test]
+    fn portfolio_totals_cover_every_pot() {
+        // Distinct powers of two, so a pot left out of any sum shows up in the result.
+        let portfolio = Portfolio {
+            isa: 1.0,
+            lisa: 2.0,
+            taxable: 4.0,
+            taxable_basis: 1_000.0,
+            pension: 8.0,
+            partner_pension: 16.0,
+            cash_buffer: 32.0,
+            bond_ladder: 64.0,
+        };
+        assert_approx(portfolio.total(), 127.0);
+        assert_approx(portfolio.invested_total(), 95.0);
+
+        // The snapshot's pots add up to its total, so a pot added to `total()` but not
+        // to the snapshot (or the reverse) fails here.
+        let real = portfolio.to_real(2.0);
+        assert_approx(real.total, 63.5);
+        assert_approx(
+            real.isa + real.lisa + real.taxable + real.pension + real.cash + real.bond_ladder,
+            real.total,
+        );
+        assert_approx(real.pension, 12.0);
+    }
+
+    #[test]
+    fn withdraw_from_portfolio_before_pension_access_ignores_pension() {
+        let mut inputs = sample_inputs();
+        inputs.pension_access_age = 60;
+        let mut portfolio = Portfolio {
+            isa: 100.0,
+            lisa: 0.0,
+            taxable: 100.0,
+            taxable_basis: 100.0,
+            pension: 100.0,
+            partner_pension: 0.0,
+            cash_buffer: 0.0,
+            bond_ladder: 0.0,
+        };
+        let mut cgt = CgtState {
+            allowance_remaining: 3_000.0,
+            basic_band_remaining: 0.0,
+            tax_paid: 0.0,
+        };
+        let mut tax_state = TaxYearState {
+            non_pension_taxable_income: 0.0,
+            pension_gross_withdrawn: 0.0,
+            pension_tax_free_withdrawn: 0.0,
+            pension_tax_free_remaining: 0.0,
+            partner: PartnerTaxState::default(),
+            price_index: 1.0,
+        };
+
+        let withdrawn = withdraw_from_portfolio(
+            &inputs,
+            50,
+            150.0,
+            &mut portfolio,
+            &mut cgt,
+            &mut tax_state,
+            WithdrawalOrder::PensionFirst,
+        );
+
+        assert_approx(withdrawn, 150.0);
+        assert_approx(portfolio.pension, 100.0);
+    }
+
+    #[test]
+    fn run_withdrawal_year_adds_extra_to_cash_in_good_years() {
+        let mut inputs = sample_inputs();
+        inputs.good_year_threshold = 0.0;
+        inputs.good_year_extra_buffer_withdrawal = 0.10;
+        inputs.post_access_withdrawal_order = WithdrawalOrder::IsaFirst;
+
+        let mut portfolio = Portfolio {
+            isa: 200.0,
+            lisa: 0.0,
+            taxable: 0.0,
+            taxable_basis: 0.0,
+            pension: 0.0,
+            partner_pension: 0.0,
+            cash_buffer: 0.0,
+            bond_ladder: 0.0,
+        };
+        let mut cgt = CgtState {
+            allowance_remaining: 3_000.0,
+            basic_band_remaining: 0.0,
+            tax_paid: 0.0,
+        };
+        let mut tax_state = TaxYearState {
+            non_pension_taxable_income: 0.0,
+            pension_gross_withdrawn: 0.0,
+            pension_tax_free_withdrawn: 0.0,
+            pension_tax_free_remaining: 0.0,
+            partner: PartnerTaxState::default(),
+            price_index: 1.0,
+        };
+
+        let outcome = run_withdrawal_year(
+            &inputs,
+            60,
+            0,
+            100.0,
+            0.10,
+            100.0,
+            &mut portfolio,
+            &mut cgt,
+            &mut tax_state,
+            0.0,
+        );
+        assert_approx(outcome.realized_spending_net, 100.0);
+        assert_approx(portfolio.cash_buffer, 10.0);
+        assert_approx(portfolio.isa, 90.0);
+    }
+
+    fn guyton_klinger_inputs() -> Inputs {
+        let mut inputs = sample_inputs();
+        inputs.withdrawal_strategy = WithdrawalStrategy::GuytonKlinger;
+        inputs.horizon_age = 90;
+        inputs.gk_lower_guardrail = 0.8;
+        inputs.gk_upper_guardrail = 1.2;
+        inputs.gk_adjustment = 0.10;
+        inputs.target_annual_income = 50_000.0;
+        inputs.min_income_floor = 0.5;
+        inputs.max_income_ceiling = 2.0;
+        inputs
+    }
+
+    fn guyton_klinger_second_year_state() -> SpendingState {
+        // $1M portfolio, 5% initial withdrawal rate, 3% inflation during year two.
+        SpendingState {
+            current_real_spending: 50_000.0,
+            initial_withdrawal_rate: 0.05,
+            prev_nominal_spending: 50_000.0,
+            price_index: 1.03,
+            cape: 20.0,
+            smile_multiplier: 1.0,
+        }
+    }
+
+    #[test]
+    fn plan_real_spending_guyton_klinger_skips_rules_in_first_retirement_year() {
+        let inputs = guyton_klinger_inputs();
+        let mut spending_state = guyton_klinger_second_year_state();
+        spending_state.prev_nominal_spending = 0.0;
+
+        let planned = plan_real_spending(&inputs, 60, -0.20, 500_000.0, &mut spending_state);
+        assert_approx(planned, 50_000.0);
+    }
+
+    #[test]
+    fn plan_real_spending_guyton_klinger_withholds_inflation_after_losing_year() {
+        // Portfolio falls to $950k: 5.26% is above the initial rate but inside the upper
+        // guardrail (6%), so only the withdrawal rule fires and nominal spending stays $50k.
+        let inputs = guyton_klinger_inputs();
+        let mut spending_state = guyton_klinger_second_year_state();
+
+        let planned = plan_real_spending(&inputs, 60, -0.05, 950_000.0, &mut spending_state);
+        assert_approx(planned * 1.03, 50_000.0);
+    }
+
+    #[test]
+    fn plan_real_spending_guyton_klinger_keeps_inflation_raise_below_initial_rate() {
+        // A losing year alone is not enough; the rate must also exceed its starting level.
+        let inputs = guyton_klinger_inputs();
+        let mut spending_state = guyton_klinger_second_year_state();
+
+        let planned = plan_real_spending(&inputs, 60, -0.02, 1_100_000.0, &mut spending_state);
+        assert_approx(planned, 50_000.0);
+    }
+
+    #[test]
+    fn plan_real_spending_guyton_klinger_capital_preservation_cuts_frozen_withdrawal() {
+        // Portfolio falls to $800k: the frozen $50k nominal withdrawal is 6.07% of assets,
+        // above the 6% upper guardrail, so it is cut by 10% to $45k nominal.
+        let inputs = guyton_klinger_inputs();
+        let mut spending_state = guyton_klinger_second_year_state();
+
+        let planned = plan_real_spending(&inputs, 60, -0.20, 800_000.0, &mut spending_state);
+        assert_approx(planned * 1.03, 45_000.0);
+        assert_approx(spending_state.current_real_spending, planned);
+    }
+
+    #[test]
+    fn plan_real_spending_guyton_klinger_capital_preservation_sunsets_near_horizon() {
+        let inputs = guyton_klinger_inputs();
+        let mut spending_state = guyton_klinger_second_year_state();
+
+        let planned = plan_real_spending(&inputs, 75, -0.20, 800_000.0, &mut spending_state);
+        assert_approx(planned * 1.03, 50_000.0);
+    }
+
+    #[test]
+    fn plan_real_spending_guyton_klinger_prosperity_raises_below_lower_guardrail() {
+        // Portfolio grows to $1.3M: 3.85% is under the 4% lower guardrail, so spending
+        // takes its inflation raise and a further 10% prosperity raise.
+        let inputs = guyton_klinger_inputs();
+        let mut spending_state = guyton_klinger_second_year_state();
+
+        let planned = plan_real_spending(&inputs, 60, 0.25, 1_300_000.0, &mut spending_state);
+        assert_approx(planned, 55_000.0);
+    }
+
+    #[test]
+    fn run_withdrawal_year_guyton_klinger_draws_bond_ladder_after_losing_year() {
+        let mut inputs = sample_inputs();
+        inputs.withdrawal_strategy = WithdrawalStrategy::GuytonKlinger;
+        inputs.good_year_extra_buffer_withdrawal = 0.0;
+        inputs.bond_ladder_years = 10;
+        inputs.post_access_withdrawal_order = WithdrawalOrder::IsaFirst;
+
+        let run = |prev_real_return: f64| {
+            let mut portfolio = Portfolio {
+                isa: 500.0,
+                lisa: 0.0,
+                taxable: 0.0,
+                taxable_basis: 0.0,
+                pension: 0.0,
+                partner_pension: 0.0,
+                cash_buffer: 0.0,
+                bond_ladder: 200.0,
+            };
+            let mut cgt = CgtState {
+                allowance_remaining: 3_000.0,
+                basic_band_remaining: 0.0,
+                tax_paid: 0.0,
+            };
+            let mut tax_state = TaxYearState {
+                non_pension_taxable_income: 0.0,
+                pension_gross_withdrawn: 0.0,
+                pension_tax_free_withdrawn: 0.0,
+                pension_tax_free_remaining: 0.0,
+                partner: PartnerTaxState::default(),
+                price_index: 1.0,
+            };
+            let outcome = run_withdrawal_year(
+                &inputs,
+                60,
+                0,
+                100.0,
+                prev_real_return,
+                100.0,
+                &mut portfolio,
+                &mut cgt,
+                &mut tax_state,
+                0.0,
+            );
+            assert_approx(outcome.realized_spending_net, 100.0);
+            portfolio
+        };
+
+        let after_loss = run(-0.05);
+        assert_approx(after_loss.bond_ladder, 100.0);
+        assert_approx(after_loss.isa, 500.0);
+
+        let after_gain = run(0.05);
+        assert_approx(after_gain.bond_ladder, 180.0);
+        assert_approx(after_gain.isa, 420.0);
+    }
+
+    #[test]
+    fn plan_real_spending_vpw_spends_more_later_with_same_balance() {
+        let mut inputs = sample_inputs();
+        inputs.withdrawal_strategy = WithdrawalStrategy::Vpw;
+        inputs.vpw_expected_real_return = 0.03;
+
+        let mut early_state = SpendingState {
+            current_real_spending: 50_000.0,
+            initial_withdrawal_rate: 0.04,
+            prev_nominal_spending: 0.0,
+            price_index: 1.0,
+            cape: 20.0,
+            smile_multiplier: 1.0,
+        };
+        let early = plan_real_spending(&inputs, 60, 0.0, 1_000_000.0, &mut early_state);
+
+        let mut late_state = SpendingState {
+            current_real_spending: 50_000.0,
+            initial_withdrawal_rate: 0.04,
+            prev_nominal_spending: 0.0,
+            price_index: 1.0,
+            cape: 20.0,
+            smile_multiplier: 1.0,
+        };
+        let late = plan_real_spending(&inputs, 80, 0.0, 1_000_000.0, &mut late_state);
+
+        assert!(late > early);
+    }
+
+    #[test]
+    fn plan_real_spending_floor_upside_increases_after_positive_returns() {
+        let mut inputs = sample_inputs();
+        inputs.withdrawal_strategy = WithdrawalStrategy::FloorUpside;
+        inputs.floor_upside_capture = 0.50;
+
+        let mut spending_state = SpendingState {
+            current_real_spending: 50_000.0,
+            initial_withdrawal_rate: 0.04,
+            prev_nominal_spending: 0.0,
+            price_index: 1.0,
+            cape: 20.0,
+            smile_multiplier: 1.0,
+        };
+
+        let planned = plan_real_spending(&inputs, 60, 0.20, 1_000_000.0, &mut spending_state);
+        assert_approx(planned, 55_000.0);
+    }
+
+    #[test]
+    fn plan_real_spending_fixed_real_ignores_prior_returns() {
+        let mut inputs = sample_inputs();
+        inputs.withdrawal_strategy = WithdrawalStrategy::FixedReal;
+
+        for prev_real_return in [-0.30, 0.0, 0.25] {
+            let mut spending_state = SpendingState {
+                current_real_spending: 50_000.0,
+                initial_withdrawal_rate: 0.04,
+                prev_nominal_spending: 0.0,
+                price_index: 1.0,
+                cape: 20.0,
+                smile_multiplier: 1.0,
+            };
+            let planned = plan_real_spending(
+                &inputs,
+                60,
+                prev_real_return,
+                1_000_000.0,
+                &mut spending_state,
+            );
+            assert_approx(planned, 50_000.0);
+            assert_approx(spending_state.current_real_spending, 50_000.0);
+        }
+    }
+
+    #[test]
+    fn plan_real_spending_fixed_real_still_honors_floor_and_ceiling() {
+        let mut inputs = sample_inputs();
+        inputs.withdrawal_strategy = WithdrawalStrategy::FixedReal;
+        inputs.target_annual_income = 40_000.0;
+        inputs.min_income_floor = 0.9;
+        inputs.max_income_ceiling = 1.1;
+
+        let mut low_state = SpendingState {
+            current_real_spending: 10_000.0,
+            initial_withdrawal_rate: 0.04,
+            prev_nominal_spending: 0.0,
+            price_index: 1.0,
+            cape: 20.0,
+            smile_multiplier: 1.0,
+        };
+        let low = plan_real_spending(&inputs, 60, 0.0, 1_000_000.0, &mut low_state);
+        assert_approx(low, 36_000.0);
+
+        let mut high_state = SpendingState {
+            current_real_spending: 90_000.0,
+            initial_withdrawal_rate: 0.04,
+            prev_nominal_spending: 0.0,
+            price_index: 1.0,
+            cape: 20.0,
+            smile_multiplier: 1.0,
+        };
+        let high = plan_real_spending(&inputs, 60, 0.0, 1_000_000.0, &mut high_state);
+        assert_approx(high, 44_000.0);
+    }
+
+    #[test]
+    fn oracle_fixed_real_spending_is_flat_across_years_with_zero_volatility() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.current_age = 60;
+        inputs.max_retirement_age = 60;
+        inputs.horizon_age = 70;
+        inputs.pension_access_age = 60;
+        inputs.isa_start = 1_000_000.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.target_annual_income = 40_000.0;
+        inputs.isa_return_mean = 0.20;
+        inputs.inflation_mean = 0.03;
+
+        // Every year is a "good" year for guardrails, so any market-driven rule
+        // would ratchet spending upward; fixed-real must stay at the target.
+        inputs.good_year_threshold = 0.05;
+        inputs.good_year_raise = 0.10;
+        inputs.min_income_floor = 0.5;
+        inputs.max_income_ceiling = 3.0;
+        inputs.withdrawal_strategy = WithdrawalStrategy::FixedReal;
+
+        let rows = run_yearly_cashflow_trace(&inputs, 60, 60, 60);
+        assert_eq!(rows.len(), 10);
+        for row in &rows {
+            assert_approx(row.median_spending_total, 40_000.0);
+        }
+
+        inputs.withdrawal_strategy = WithdrawalStrategy::Guardrails;
+        let guardrail_rows = run_yearly_cashflow_trace(&inputs, 60, 60, 60);
+        assert!(guardrail_rows[9].median_spending_total > 40_000.0 + 1.0);
+    }
+
+    #[test]
+    fn plan_real_spending_constant_percentage_ignores_income_floor() {
+        let mut inputs = sample_inputs();
+        inputs.withdrawal_strategy = WithdrawalStrategy::ConstantPercentage;
+        inputs.constant_withdrawal_rate = 0.05;
+        inputs.target_annual_income = 50_000.0;
+        inputs.min_income_floor = 0.8;
+        inputs.max_income_ceiling = 2.0;
+
+        let mut spending_state = SpendingState {
+            current_real_spending: 50_000.0,
+            initial_withdrawal_rate: 0.04,
+            prev_nominal_spending: 0.0,
+            price_index: 1.0,
+            cape: 20.0,
+            smile_multiplier: 1.0,
+        };
+        let planned = plan_real_spending(&inputs, 60, -0.30, 200_000.0, &mut spending_state);
+        assert_approx(planned, 10_000.0);
+    }
+
+    #[test]
+    fn oracle_constant_percentage_withdraws_rate_times_balance_each_year() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.current_age = 60;
+        inputs.max_retirement_age = 60;
+        inputs.horizon_age = 65;
+        inputs.pension_access_age = 60;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.target_annual_income = 40_000.0;
+        inputs.max_income_ceiling = 2.0;
+        inputs.withdrawal_strategy = WithdrawalStrategy::ConstantPercentage;
+        inputs.constant_withdrawal_rate = 0.04;
+
+        // 4% of 5M is 200,000, well above the 80,000 ceiling, and is still withdrawn.
+        for start in [5_000_000.0, 1_000_000.0] {
+            inputs.isa_start = start;
+            let rows = run_yearly_cashflow_trace(&inputs, 60, 60, 60);
+            assert_eq!(rows.len(), 5);
+
+            let mut balance = start;
+            for row in &rows {
+                let expected = balance * 0.04;
+                assert_approx(row.median_withdrawal_portfolio, expected);
+                assert_approx(row.median_spending_total, expected);
+                balance -= expected;
+                assert_approx(row.median_end_total, balance);
+            }
+        }
+
+        let balance = 1_000_000.0 * 0.96_f64.powi(5);
+
+        let mut rng = Rng::new(derive_seed(inputs.seed, 60, 0));
+        let scenario = simulate_scenario(&inputs, 60, 60, &mut rng, None);
+        assert!(scenario.success);
+        assert_approx(scenario.min_income_ratio, balance / 0.96 * 0.04 / 40_000.0);
+    }
+
+    #[test]
+    fn evolve_cape_rises_with_strong_returns_and_reverts_toward_mean() {
+        let mut inputs = sample_inputs();
+        inputs.cape_long_run_mean = 20.0;
+        inputs.cape_reversion_speed = 0.0;
+        let boom = MarketSample {
+            isa_return: 0.30,
+            taxable_return: 0.30,
+            pension_return: 0.30,
+            inflation: 0.0,
+            bond_ladder_return: 0.0,
+        };
+        let after_boom = evolve_cape(&inputs, 20.0, &boom);
+        assert_approx(after_boom, 20.0 * 1.30 / (1.0 + CAPE_EARNINGS_REAL_GROWTH));
+        assert!(cape_withdrawal_rate(&inputs, after_boom) < cape_withdrawal_rate(&inputs, 20.0));
+
+        inputs.cape_reversion_speed = 0.5;
+        let steady = MarketSample {
+            isa_return: CAPE_EARNINGS_REAL_GROWTH,
+            taxable_return: CAPE_EARNINGS_REAL_GROWTH,
+            pension_return: CAPE_EARNINGS_REAL_GROWTH,
+            inflation: 0.0,
+            bond_ladder_return: 0.0,
+        };
+        assert_approx(evolve_cape(&inputs, 30.0, &steady), 25.0);
+    }
+
+    #[test]
+    fn oracle_cape_based_rate_is_fixed_when_constant_returns_hold_cape_steady() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.current_age = 58;
+        inputs.max_retirement_age = 60;
+        inputs.horizon_age = 65;
+        inputs.pension_access_age = 60;
+        inputs.isa_start = 1_000_000.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.target_annual_income = 40_000.0;
+        inputs.min_income_floor = 0.1;
+        inputs.max_income_ceiling = 3.0;
+        inputs.withdrawal_strategy = WithdrawalStrategy::CapeBased;
+        inputs.cape_a = 0.01;
+        inputs.cape_b = 0.5;
+        inputs.cape_start = 25.0;
+        inputs.cape_long_run_mean = 25.0;
+        inputs.cape_reversion_speed = 0.2;
+
+        // Real equity returns equal to trend earnings growth leave CAPE at 25 throughout,
+        // so every year withdraws 1% + 0.5 / 25 = 3% of the balance.
+        inputs.isa_return_mean = CAPE_EARNINGS_REAL_GROWTH;
+        let rate = 0.03;
+
+        let rows = run_yearly_cashflow_trace(&inputs, 60, 60, 60);
+        assert_eq!(rows.len(), 7);
+
+        let mut balance = 1_000_000.0 * (1.0 + CAPE_EARNINGS_REAL_GROWTH).powi(2);
+        assert_approx(rows[1].median_end_total, balance);
+        for row in &rows[2..] {
+            let expected = balance * rate;
+            assert_approx_tol(row.median_spending_total, expected, 1e-4);
+            balance = (balance - expected) * (1.0 + CAPE_EARNINGS_REAL_GROWTH);
+            assert_approx_tol(row.median_end_total, balance, 1e-4);
+        }
+    }
+
+    fn amortization_inputs() -> Inputs {
+        let mut inputs = sample_inputs();
+        inputs.withdrawal_strategy = WithdrawalStrategy::Amortization;
+        inputs.target_annual_income = 40_000.0;
+        inputs.min_income_floor = 0.0;
+        inputs.max_income_ceiling = 100.0;
+        inputs
+    }
+
+    fn amortization_planned_rate(inputs: &Inputs, age: u32) -> f64 {
+        let mut spending_state = SpendingState {
+            current_real_spending: 40_000.0,
+            initial_withdrawal_rate: 0.04,
+            prev_nominal_spending: 0.0,
+            price_index: 1.0,
+            cape: 20.0,
+            smile_multiplier: 1.0,
+        };
+        plan_real_spending(inputs, age, 0.0, 1_000_000.0, &mut spending_state) / 1_000_000.0
+    }
+
+    #[test]
+    fn plan_real_spending_amortization_rate_rises_with_age() {
+        let inputs = amortization_inputs();
+        let rates: Vec<f64> = [55, 65, 75, 85, 95]
+            .into_iter()
+            .map(|age| amortization_planned_rate(&inputs, age))
+            .collect();
+        assert!(rates.windows(2).all(|pair| pair[1] > pair[0]));
+        assert_approx(rates[1], 1.0 / remaining_life_expectancy(65));
+    }
+
+    #[test]
+    fn plan_real_spending_amortization_ignores_horizon_and_clamps_past_table() {
+        let mut inputs = amortization_inputs();
+        let at_100 = amortization_planned_rate(&inputs, 100);
+        inputs.horizon_age = 120;
+        assert_approx(amortization_planned_rate(&inputs, 100), at_100);
+        assert_approx(amortization_planned_rate(&inputs, 115), at_100);
+        assert!(at_100 < 1.0);
+    }
+
+    #[test]
+    fn run_withdrawal_year_bucket_refills_cash_toward_target_after_good_year() {
+        let mut inputs = sample_inputs();
+        inputs.withdrawal_strategy = WithdrawalStrategy::Bucket;
+        inputs.good_year_threshold = 0.0;
+        inputs.bucket_target_years = 2.0;
+        inputs.good_year_extra_buffer_withdrawal = 2.0;
+        inputs.post_access_withdrawal_order = WithdrawalOrder::IsaFirst;
+
+        let mut portfolio = Portfolio {
+            isa: 500.0,
+            lisa: 0.0,
+            taxable: 0.0,
+            taxable_basis: 0.0,
+            pension: 0.0,
+            partner_pension: 0.0,
+            cash_buffer: 0.0,
+            bond_ladder: 0.0,
+        };
+        let mut cgt = CgtState {
+            allowance_remaining: 3_000.0,
+            basic_band_remaining: 0.0,
+            tax_paid: 0.0,
+        };
+        let mut tax_state = TaxYearState {
+            non_pension_taxable_income: 0.0,
+            pension_gross_withdrawn: 0.0,
+            pension_tax_free_withdrawn: 0.0,
+            pension_tax_free_remaining: 0.0,
+            partner: PartnerTaxState::default(),
+            price_index: 1.0,
+        };
+
+        let outcome = run_withdrawal_year(
+            &inputs,
+            60,
+            0,
+            100.0,
+            0.10,
+            100.0,
+            &mut portfolio,
+            &mut cgt,
+            &mut tax_state,
+            0.0,
+        );
+
+        assert_approx(outcome.realized_spending_net, 100.0);
+        assert_approx(portfolio.cash_buffer, 200.0);
+    }
+
+    #[test]
+    fn run_withdrawal_year_cash_floor_rebuilds_guardrails_cash_after_good_year() {
+        let mut inputs = sample_inputs();
+        inputs.withdrawal_strategy = WithdrawalStrategy::Guardrails;
+        inputs.good_year_threshold = 0.0;
+        inputs.good_year_extra_buffer_withdrawal = 0.0;
+        inputs.min_cash_months = 6.0;
+        inputs.post_access_withdrawal_order = WithdrawalOrder::IsaFirst;
+
+        let run = |inputs: &Inputs, prev_real_return: f64| {
+            let mut portfolio = Portfolio {
+                isa: 500.0,
+                lisa: 0.0,
+                taxable: 0.0,
+                taxable_basis: 0.0,
+                pension: 0.0,
+                partner_pension: 0.0,
+                cash_buffer: 10.0,
+                bond_ladder: 0.0,
+            };
+            let mut cgt = CgtState {
+                allowance_remaining: 3_000.0,
+                basic_band_remaining: 0.0,
+                tax_paid: 0.0,
+            };
+            let mut tax_state = TaxYearState {
+                non_pension_taxable_income: 0.0,
+                pension_gross_withdrawn: 0.0,
+                pension_tax_free_withdrawn: 0.0,
+                pension_tax_free_remaining: 0.0,
+                partner: PartnerTaxState::default(),
+                price_index: 1.0,
+            };
+            let outcome = run_withdrawal_year(
+                inputs,
+                60,
+                0,
+                100.0,
+                prev_real_return,
+                100.0,
+                &mut portfolio,
+                &mut cgt,
+                &mut tax_state,
+                0.0,
+            );
+            assert_approx(outcome.realized_spending_net, 100.0);
+            portfolio
+        };
+
+        // Spending drains the 10 of cash; a good year then refills six months (50).
+        let after_good = run(&inputs, 0.10);
+        assert_approx(after_good.cash_buffer, 50.0);
+        assert_approx(after_good.isa, 500.0 - 90.0 - 50.0);
+
+        // A flat year leaves the buffer empty, as does a good year without the floor.
+        assert_approx(run(&inputs, 0.0).cash_buffer, 0.0);
+        inputs.min_cash_months = 0.0;
+        assert_approx(run(&inputs, 0.10).cash_buffer, 0.0);
+    }
+
+    #[test]
+    fn sample_market_zero_volatility_returns_means() {
+        let mut inputs = sample_inputs();
+        inputs.isa_return_vol = 0.0;
+        inputs.taxable_return_vol = 0.0;
+        inputs.pension_return_vol = 0.0;
+        inputs.inflation_vol = 0.0;
+
+        let mut rng = Rng::new(123);
+        let s = sample_market(&inputs, &mut rng);
+        assert_approx(s.isa_return, inputs.isa_return_mean);
+        assert_approx(s.taxable_return, inputs.taxable_return_mean);
+        assert_approx(s.pension_return, inputs.pension_return_mean);
+        assert_approx(s.inflation, inputs.inflation_mean);
+    }
+
+    #[test]
+    fn sample_market_isa_taxable_correlation_controls_divergence() {
+        let mut inputs = sample_inputs();
+        inputs.isa_return_mean = 0.05;
+        inputs.taxable_return_mean = 0.05;
+        inputs.isa_return_vol = 0.15;
+        inputs.taxable_return_vol = 0.15;
+
+        let draws = 20_000;
+        let sample_stats = |inputs: &Inputs| {
+            let mut rng = Rng::new(99);
+            let (mut max_gap, mut sum_xy, mut sum_x, mut sum_y, mut sum_xx, mut sum_yy) =
+                (0.0_f64, 0.0, 0.0, 0.0, 0.0, 0.0);
+            for _ in 0..draws {
+                let s = sample_market(inputs, &mut rng);
+                max_gap = max_gap.max((s.isa_return - s.taxable_return).abs());
+                sum_x += s.isa_return;
+                sum_y += s.taxable_return;
+                sum_xy += s.isa_return * s.taxable_return;
+                sum_xx += s.isa_return * s.isa_return;
+                sum_yy += s.taxable_return * s.taxable_return;
+            }
+            let n = draws as f64;
+            let cov = sum_xy / n - (sum_x / n) * (sum_y / n);
+            let var_x = sum_xx / n - (sum_x / n).powi(2);
+            let var_y = sum_yy / n - (sum_y / n).powi(2);
+            (max_gap, cov / (var_x * var_y).sqrt())
+        };
+
+        let (gap, corr) = sample_stats(&inputs);
+        assert!(gap < 1e-12);
+        assert!((corr - 1.0).abs() < 1e-9);
+
+        inputs.isa_taxable_correlation = 0.0;
+        let (gap, corr) = sample_stats(&inputs);
+        assert!(gap > 0.3);
+        assert!(corr.abs() < 0.03, "sample correlation {corr}");
+    }
+
+    #[test]
+    fn sample_market_cholesky_reproduces_target_correlations() {
+        let mut inputs = sample_inputs();
+        inputs.isa_return_mean = 0.0;
+        inputs.taxable_return_mean = 0.0;
+        inputs.pension_return_mean = 0.0;
+        inputs.isa_return_vol = 0.10;
+        inputs.taxable_return_vol = 0.20;
+        inputs.pension_return_vol = 0.15;
+        inputs.isa_taxable_correlation = 0.6;
+        inputs.return_correlation = 0.3;
+        inputs.taxable_pension_correlation = Some(-0.2);
+
+        let target = return_correlation_matrix(&inputs);
+        let lower = return_correlation_cholesky(&inputs).expect("matrix is PSD");
+        for i in 0..3 {
+            for j in 0..3 {
+                let rebuilt: f64 = (0..3).map(|k| lower[i][k] * lower[j][k]).sum();
+                assert_approx(rebuilt, target[i][j]);
+            }
+        }
+
+        let draws = 50_000;
+        let vols = [0.10, 0.20, 0.15];
+        let mut rng = Rng::new(2024);
+        let mut cross = [[0.0_f64; 3]; 3];
+        for _ in 0..draws {
+            let s = sample_market(&inputs, &mut rng);
+            let r = [s.isa_return, s.taxable_return, s.pension_return];
+            for i in 0..3 {
+                for j in 0..3 {
+                    cross[i][j] += r[i] * r[j];
+                }
+            }
+        }
+        for i in 0..3 {
+            for j in 0..3 {
+                let covariance = cross[i][j] / draws as f64;
+                let expected = target[i][j] * vols[i] * vols[j];
+                assert!(
+                    (covariance - expected).abs() < 0.02 * vols[i] * vols[j],
+                    "cov[{i}][{j}] = {covariance}, expected {expected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sample_market_equal_factor_betas_correlate_every_pot() {
+        let mut inputs = sample_inputs();
+        inputs.isa_return_mean = 0.0;
+        inputs.taxable_return_mean = 0.0;
+        inputs.pension_return_mean = 0.0;
+        inputs.isa_return_vol = 0.15;
+        inputs.taxable_return_vol = 0.15;
+        inputs.pension_return_vol = 0.15;
+        // Contradictory pairwise settings are ignored once the betas are set.
+        inputs.isa_taxable_correlation = 0.9;
+        inputs.return_correlation = 0.9;
+        inputs.taxable_pension_correlation = Some(-0.9);
+        inputs.return_factor_betas = Some([0.95; 3]);
+        assert!(return_correlation_cholesky(&inputs).is_some());
+
+        let draws = 50_000;
+        let mut rng = Rng::new(85);
+        let mut cross = [[0.0_f64; 3]; 3];
+        for _ in 0..draws {
+            let s = sample_market(&inputs, &mut rng);
+            let r = [s.isa_return, s.taxable_return, s.pension_return];
+            for i in 0..3 {
+                for j in 0..3 {
+                    cross[i][j] += r[i] * r[j];
+                }
+            }
+        }
+        for (i, j) in [(0, 1), (0, 2), (1, 2)] {
+            let correlation = cross[i][j] / (cross[i][i] * cross[j][j]).sqrt();
+            assert!(
+                (correlation - 0.9025).abs() < 0.01,
+                "corr[{i}][{j}] = {correlation}"
+            );
+        }
+    }
+
+    #[test]
+    fn return_correlation_cholesky_rejects_non_psd_matrix() {
+        let mut inputs = sample_inputs();
+        inputs.isa_taxable_correlation = 0.9;
+        inputs.return_correlation = 0.9;
+        inputs.taxable_pension_correlation = Some(-0.9);
+        assert!(return_correlation_cholesky(&inputs).is_none());
+
+        // Perfect ISA/taxable correlation is singular but valid when the implied
+        // taxable/pension correlation is used.
+        inputs.isa_taxable_correlation = 1.0;
+        inputs.taxable_pension_correlation = None;
+        assert!(return_correlation_cholesky(&inputs).is_some());
+        inputs.taxable_pension_correlation = Some(0.5);
+        assert!(return_correlation_cholesky(&inputs).is_none());
+    }
+
+    fn sample_moments(samples: &[f64]) -> (f64, f64) {
+        let n = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+        let m2 = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+        let m4 = samples.iter().map(|x| (x - mean).powi(4)).sum::<f64>() / n;
+        (m2, m4 / (m2 * m2))
+    }
+
+    #[test]
+    fn rng_student_t_low_dof_has_fatter_tails_than_gaussian() {
+        let mut rng = Rng::new(11);
+        let samples: Vec<f64> = (0..200_000).map(|_| rng.student_t(5.0)).collect();
+        let (variance, kurtosis) = sample_moments(&samples);
+        // Theoretical variance 5/3 and kurtosis 9 versus 3 for a Gaussian.
+        assert!((variance - 5.0 / 3.0).abs() < 0.1, "variance {variance}");
+        assert!(kurtosis > 5.0, "kurtosis {kurtosis}");
+    }
+
+    #[test]
+    fn rng_student_t_converges_to_normal_for_large_dof() {
+        let mut rng = Rng::new(12);
+        let samples: Vec<f64> = (0..200_000).map(|_| rng.student_t(10_000.0)).collect();
+        let (variance, kurtosis) = sample_moments(&samples);
+        assert!((variance - 1.0).abs() < 0.02, "variance {variance}");
+        assert!((kurtosis - 3.0).abs() < 0.1, "kurtosis {kurtosis}");
+    }
+
+    #[test]
+    fn sample_market_student_t_keeps_configured_volatility() {
+        let mut inputs = sample_inputs();
+        inputs.isa_return_mean = 0.0;
+        inputs.isa_return_vol = 0.10;
+        inputs.return_distribution = ReturnDistribution::StudentT { dof: 5.0 };
+
+        let mut rng = Rng::new(13);
+        let samples: Vec<f64> = (0..100_000)
+            .map(|_| sample_market(&inputs, &mut rng).isa_return)
+            .collect();
+        let (variance, kurtosis) = sample_moments(&samples);
+        assert!(
+            (variance.sqrt() - 0.10).abs() < 0.005,
+            "vol {}",
+            variance.sqrt()
+        );
+        assert!(kurtosis > 4.0, "kurtosis {kurtosis}");
+    }
+
+    fn assert_fan_monotonic(label: &str, fan: [f64; 7]) {
+        assert!(
+            fan.windows(2).all(|w| w[0] <= w[1] + 1e-9),
+            "{label}: fan not monotonic {fan:?}"
+        );
+    }
+
+    #[test]
+    fn terminal_and_cashflow_fans_are_monotonic() {
+        let mut inputs = sample_inputs();
+        inputs.simulations = (EXACT_PERCENTILE_SAMPLE_LIMIT + 500) as u32;
+        inputs.max_retirement_age = inputs.current_age + 2;
+        inputs.horizon_age = inputs.current_age + 25;
+
+        for r in &run_model(&inputs).expect("inputs are valid").age_results {
+            assert_fan_monotonic(
+                "terminal",
+                [
+                    r.p5_terminal_pot,
+                    r.p10_terminal_pot,
+                    r.p25_terminal_pot,
+                    r.median_terminal_pot,
+                    r.p75_terminal_pot,
+                    r.p90_terminal_pot,
+                    r.p95_terminal_pot,
+                ],
+            );
+        }
+
+        let retirement_age = inputs.current_age + 2;
+        for row in
+            run_yearly_cashflow_trace(&inputs, retirement_age, retirement_age, retirement_age)
+        {
+            assert_fan_monotonic(
+                "end total",
+                [
+                    row.p5_end_total,
+                    row.p10_end_total,
+                    row.p25_end_total,
+                    row.median_end_total,
+                    row.p75_end_total,
+                    row.p90_end_total,
+                    row.p95_end_total,
+                ],
+            );
+        }
+    }
+
+    #[test]
+    fn cashflow_bands_bracket_the_median() {
+        let mut inputs = sample_inputs();
+        inputs.simulations = 400;
+        inputs.max_retirement_age = inputs.current_age + 2;
+        inputs.horizon_age = inputs.current_age + 25;
+
+        let retirement_age = inputs.current_age + 2;
+        let rows =
+            run_yearly_cashflow_trace(&inputs, retirement_age, retirement_age, retirement_age);
+        for row in &rows {
+            for (label, p10, median, p90) in [
+                (
+                    "end total",
+                    row.p10_end_total,
+                    row.median_end_total,
+                    row.p90_end_total,
+                ),
+                (
+                    "spending",
+                    row.p10_spending_total,
+                    row.median_spending_total,
+                    row.p90_spending_total,
+                ),
+                (
+                    "withdrawal",
+                    row.p10_withdrawal_portfolio,
+                    row.median_withdrawal_portfolio,
+                    row.p90_withdrawal_portfolio,
+                ),
+            ] {
+                assert!(
+                    p10 <= median + 1e-9 && median <= p90 + 1e-9,
+                    "{label} at {}: {p10} / {median} / {p90}",
+                    row.age
+                );
+            }
+        }
+        // Market noise must actually spread spending in some retirement year.
+        assert!(
+            rows.iter()
+                .any(|row| row.p90_spending_total > row.p10_spending_total + 1.0)
+        );
+    }
+
+    #[test]
+    fn run_model_rejects_malformed_inputs() {
+        let mut inputs = sample_inputs();
+        inputs.max_retirement_age = inputs.current_age - 1;
+        assert_eq!(
+            run_model(&inputs).unwrap_err(),
+            ModelError::EmptyAgeRange {
+                current_age: 30,
+                last_age: 29
+            }
+        );
 
-        let mut rng = Rng::new(derive_seed(inputs.seed, 30, 0));
-        let scenario = simulate_scenario(&inputs, 30, 30, &mut rng, None);
-        assert!(scenario.success);
-        assert_approx(scenario.reported_terminal_bond_ladder, 0.0);
-        assert_approx(scenario.reported_terminal_total, 0.0);
+        let mut inputs = sample_inputs();
+        inputs.horizon_age = inputs.max_retirement_age;
+        assert_eq!(
+            run_model(&inputs).unwrap_err(),
+            ModelError::InfeasibleHorizon {
+                max_retirement_age: 70,
+                horizon_age: 70
+            }
+        );
 
-        let rows = run_yearly_cashflow_trace(&inputs, 30, 30, 30);
-        assert_eq!(rows.len(), 3);
-        assert_approx(rows[0].median_end_bond_ladder, 60.0);
-        assert_approx(rows[1].median_end_bond_ladder, 30.0);
-        assert_approx(rows[2].median_end_bond_ladder, 0.0);
+        let mut inputs = sample_inputs();
+        inputs.simulations = 0;
+        assert_eq!(run_model(&inputs).unwrap_err(), ModelError::ZeroSimulations);
+
+        let mut inputs = sample_inputs();
+        inputs.isa_return_vol = f64::NAN;
+        assert_eq!(
+            run_model(&inputs).unwrap_err(),
+            ModelError::NonFiniteParameter("isa_return_vol")
+        );
+
+        let mut inputs = sample_inputs();
+        inputs.employer_match_cap = Some(f64::INFINITY);
+        assert_eq!(
+            run_model(&inputs).unwrap_err(),
+            ModelError::NonFiniteParameter("employer_match_cap")
+        );
+
+        let mut inputs = sample_inputs();
+        inputs.market_model = MarketModel::Deterministic {
+            paths: vec![(0.05, 0.05, f64::NAN, 0.02)],
+        };
+        assert_eq!(
+            run_model(&inputs).unwrap_err(),
+            ModelError::NonFiniteParameter("market_model")
+        );
     }
 
     #[test]
-    fn pre_retirement_contributions_apply_isa_cap_and_overflow() {
+    fn run_coast_model_rejects_a_retirement_age_before_today() {
         let inputs = sample_inputs();
-        let mut portfolio = Portfolio {
-            isa: 0.0,
-            taxable: 0.0,
-            taxable_basis: 0.0,
-            pension: 0.0,
-            cash_buffer: 0.0,
-            bond_ladder: 0.0,
-        };
+        assert!(matches!(
+            run_coast_model(&inputs, inputs.current_age - 1),
+            Err(ModelError::EmptyAgeRange { .. })
+        ));
+        assert!(matches!(
+            run_coast_model(&inputs, inputs.horizon_age),
+            Err(ModelError::InfeasibleHorizon { .. })
+        ));
+    }
 
-        apply_pre_retirement_contributions(&inputs, &mut portfolio, 0);
-        assert_approx(portfolio.isa, 20_000.0);
-        assert_approx(portfolio.taxable, 15_000.0);
-        assert_approx(portfolio.taxable_basis, 15_000.0);
+    #[test]
+    fn run_model_reports_progress_once_per_candidate_age() {
+        let mut inputs = sample_inputs();
+        inputs.simulations = 20;
+        inputs.max_retirement_age = inputs.current_age + 6;
+
+        for threads in [1, 0] {
+            inputs.threads = threads;
+            let calls = std::sync::Mutex::new(Vec::new());
+            let model = run_model_with_progress(&inputs, &|completed, total| {
+                calls.lock().unwrap().push((completed, total));
+            })
+            .expect("inputs are valid");
+
+            let mut calls = calls.into_inner().unwrap();
+            calls.sort_unstable();
+            let candidates = model.age_results.len();
+            assert_eq!(candidates, 7);
+            assert_eq!(
+                calls,
+                (1..=candidates)
+                    .map(|c| (c, candidates))
+                    .collect::<Vec<_>>()
+            );
+        }
     }
 
     #[test]
-    fn pre_retirement_contributions_clamp_negative_values() {
+    fn streaming_percentiles_read_exact_ranks_from_one_sort() {
+        let mut fan = StreamingPercentiles::new(&FAN_PERCENTILES);
+        for value in (0..=100).rev() {
+            fan.push(value as f64);
+        }
+        for p in FAN_PERCENTILES {
+            assert_eq!(fan.value(p), p);
+        }
+    }
+
+    #[test]
+    fn success_ci_half_width_shrinks_as_simulations_grow() {
         let mut inputs = sample_inputs();
-        inputs.isa_annual_contribution = -100.0;
-        inputs.taxable_annual_contribution = -50.0;
-        inputs.pension_annual_contribution = -1.0;
+        let retirement_age = inputs.current_age + 5;
+        let mut previous = f64::INFINITY;
+        for simulations in [50, 200, 800, 3_200] {
+            inputs.simulations = simulations;
+            let result = run_retirement_age_evaluation(&inputs, retirement_age);
+            assert!(result.success_ci_half_width > 0.0);
+            assert!(
+                result.success_ci_half_width < previous,
+                "{simulations}: {} !< {previous}",
+                result.success_ci_half_width
+            );
+            previous = result.success_ci_half_width;
+        }
+    }
 
-        let mut portfolio = Portfolio {
-            isa: 1_000.0,
-            taxable: 2_000.0,
-            taxable_basis: 2_000.0,
-            pension: 3_000.0,
-            cash_buffer: 0.0,
-            bond_ladder: 0.0,
+    #[test]
+    fn wilson_half_width_matches_known_values() {
+        assert!((binomial_ci_half_width(0.5, 100) - 0.0962).abs() < 1e-4);
+        // Degenerate rates still carry uncertainty.
+        assert!((binomial_ci_half_width(1.0, 100) - 0.0185).abs() < 1e-4);
+        assert_eq!(binomial_ci_half_width(0.5, 0), 0.0);
+    }
+
+    #[test]
+    fn crra_utility_prefers_a_smooth_path_to_a_volatile_one_with_the_same_mean() {
+        let mut inputs = sample_inputs();
+        let path_utility = |inputs: &Inputs, path: &[f64]| -> f64 {
+            path.iter()
+                .enumerate()
+                .map(|(year, &spending)| year_utility(inputs, year as u32, spending))
+                .sum()
         };
+        let smooth = [30_000.0; 4];
+        let volatile = [15_000.0, 45_000.0, 20_000.0, 40_000.0];
+
+        for gamma in [0.5, 1.0, 3.0] {
+            inputs.risk_aversion = Some(gamma);
+            let (s, v) = (
+                path_utility(&inputs, &smooth),
+                path_utility(&inputs, &volatile),
+            );
+            assert!(s > v, "gamma {gamma}: smooth {s} vs volatile {v}");
+        }
 
-        apply_pre_retirement_contributions(&inputs, &mut portfolio, 0);
-        assert_approx(portfolio.isa, 1_000.0);
-        assert_approx(portfolio.taxable, 2_000.0);
-        assert_approx(portfolio.pension, 3_000.0);
+        inputs.risk_aversion = Some(1.0);
+        assert_approx(crra_utility(30_000.0, 1.0), 30_000.0_f64.ln());
+        assert_approx(year_utility(&inputs, 0, 0.0), 1_000.0_f64.ln());
+        inputs.utility_spending_floor = 5_000.0;
+        assert_approx(year_utility(&inputs, 0, 2_000.0), 5_000.0_f64.ln());
+        inputs.utility_discount_rate = 0.25;
+        assert_approx(
+            year_utility(&inputs, 2, 30_000.0),
+            30_000.0_f64.ln() / 1.5625,
+        );
+        inputs.risk_aversion = None;
+        assert_eq!(year_utility(&inputs, 0, 30_000.0), 0.0);
     }
 
     #[test]
-    fn pre_retirement_contributions_grow_annually_with_rate() {
+    fn expected_utility_is_reported_only_when_risk_aversion_is_set() {
         let mut inputs = sample_inputs();
-        inputs.contribution_growth_rate = 0.10;
-        let mut portfolio = Portfolio {
-            isa: 0.0,
-            taxable: 0.0,
-            taxable_basis: 0.0,
-            pension: 0.0,
-            cash_buffer: 0.0,
-            bond_ladder: 0.0,
-        };
+        inputs.simulations = 200;
+        let age = inputs.current_age + 10;
+        assert!(
+            evaluate_age_candidate(&inputs, age, age, age)
+                .expected_utility
+                .is_none()
+        );
 
-        apply_pre_retirement_contributions(&inputs, &mut portfolio, 1);
-        assert_approx(portfolio.isa, 20_000.0);
-        assert_approx(portfolio.taxable, 18_500.0);
-        assert_approx(portfolio.taxable_basis, 18_500.0);
+        inputs.risk_aversion = Some(2.0);
+        let utility = evaluate_age_candidate(&inputs, age, age, age)
+            .expected_utility
+            .expect("risk aversion is set");
+        let years = f64::from(inputs.horizon_age - inputs.current_age);
+        assert!(utility < 0.0 && utility >= years * crra_utility(0.0, 2.0));
     }
 
     #[test]
-    fn uk_tax_bands_apply_progressive_rates() {
+    fn selection_confidence_moves_the_selected_age_near_the_threshold() {
         let mut inputs = sample_inputs();
-        inputs.pension_tax_mode = PensionTaxMode::UkBands;
-        let tax = income_tax_for_total_income(60_000.0, &inputs, 1.0);
-        assert!((tax - 11_432.0).abs() < 1e-3);
+        inputs.simulations = 20;
+        let template = evaluate_age_candidate(&inputs, 40, 40, 40);
+        let age_results: Vec<AgeResult> = [0.80, 0.89, 0.91, 0.95, 0.99]
+            .into_iter()
+            .enumerate()
+            .map(|(idx, success_rate)| AgeResult {
+                retirement_age: 40 + idx as u32,
+                success_rate,
+                ..template.clone()
+            })
+            .collect();
+        inputs.simulations = 1_000;
+        inputs.success_threshold = 0.90;
+
+        let mut selected = |confidence| {
+            inputs.selection_confidence = confidence;
+            build_model_result(age_results.clone(), &inputs).selected_index
+        };
+        // At n = 1000: 0.89 has an upper bound of ~0.908, 0.91 a lower bound of ~0.891.
+        assert_eq!(selected(SelectionConfidence::PointEstimate), Some(2));
+        assert_eq!(selected(SelectionConfidence::Conservative), Some(3));
+        assert_eq!(selected(SelectionConfidence::Optimistic), Some(1));
     }
 
     #[test]
-    fn state_pension_can_cover_spending_without_assets() {
+    fn oracle_mortgage_amortization_matches_the_annuity_formula() {
         let mut inputs = sample_inputs();
-        inputs.current_age = 30;
-        inputs.horizon_age = 31;
-        inputs.max_retirement_age = 30;
-        inputs.isa_start = 0.0;
+        inputs.inflation_mean = 0.0;
+        inputs.mortgage_balance = 200_000.0;
+        inputs.mortgage_rate = 0.05;
+        inputs.mortgage_term_years = 25;
+
+        // P = B r / (1 - (1 + r)^-n)
+        let payment = 200_000.0 * 0.05 / (1.0 - 1.05_f64.powi(-25));
+        assert!((payment - 14_190.49).abs() < 0.01, "{payment}");
+
+        // Year by year: interest on the opening balance, then the payment clears the rest.
+        let mut balance = 200_000.0;
+        for year in 0..25 {
+            assert!((mortgage_balance_nominal(&inputs, year) - balance).abs() < 1e-6);
+            assert_approx(amortized_mortgage_real(&inputs, 30 + year, 1.0), payment);
+            let interest = balance * 0.05;
+            if year == 0 {
+                assert!(
+                    interest > payment - interest,
+                    "early payments are mostly interest"
+                );
+            }
+            balance -= payment - interest;
+        }
+        assert!(balance.abs() < 1e-6);
+        assert_eq!(amortized_mortgage_real(&inputs, 55, 1.0), 0.0);
+        assert_approx(debt_payment_real(&inputs, 40, 1.0), payment);
+
+        // A fixed nominal payment is worth less in real terms as the scenario's prices
+        // rise, whatever inflation was expected.
+        inputs.inflation_mean = 0.02;
+        assert_approx(amortized_mortgage_real(&inputs, 30, 1.02), payment / 1.02);
+        assert_approx(amortized_mortgage_real(&inputs, 39, 1.5), payment / 1.5);
+
+        // Overpaying 50k at 35 keeps the payment but ends the mortgage years early, with
+        // a smaller final payment, and costs less in total.
+        inputs.inflation_mean = 0.0;
+        inputs.mortgage_overpayments = vec![(35, 50_000.0)];
+        assert_approx(
+            amortized_mortgage_real(&inputs, 35, 1.0),
+            payment + 50_000.0,
+        );
+        assert_approx(amortized_mortgage_real(&inputs, 36, 1.0), payment);
+        let schedule: Vec<f64> = (30..55)
+            .map(|age| amortized_mortgage_real(&inputs, age, 1.0))
+            .collect();
+        let last_paid = schedule.iter().rposition(|&p| p > 0.0).expect("payments");
+        assert!(last_paid < 20, "cleared at year {last_paid}");
+        assert!(schedule[last_paid] < payment);
+        let total: f64 = schedule.iter().sum();
+        assert!(total < 25.0 * payment);
+        assert!(total > 200_000.0);
+    }
+
+    #[test]
+    fn oracle_pre_retirement_overpayment_is_paid_from_the_portfolio() {
+        let mut inputs = deterministic_oracle_inputs();
+        inputs.max_retirement_age = 32;
+        inputs.horizon_age = 33;
+        inputs.isa_start = 100_000.0;
         inputs.taxable_start = 0.0;
         inputs.taxable_cost_basis_start = 0.0;
         inputs.pension_start = 0.0;
-        inputs.isa_annual_contribution = 0.0;
-        inputs.taxable_annual_contribution = 0.0;
-        inputs.pension_annual_contribution = 0.0;
-        inputs.target_annual_income = 10_000.0;
-        inputs.isa_return_mean = 0.0;
-        inputs.taxable_return_mean = 0.0;
-        inputs.pension_return_mean = 0.0;
-        inputs.isa_return_vol = 0.0;
-        inputs.taxable_return_vol = 0.0;
-        inputs.pension_return_vol = 0.0;
+        inputs.cash_start = 5_000.0;
+        inputs.mortgage_balance = 100_000.0;
+        inputs.mortgage_rate = 0.0;
+        inputs.mortgage_term_years = 10;
+        let run = |inputs: &Inputs| {
+            let mut rng = Rng::new(derive_seed(inputs.seed, 32, 0));
+            simulate_scenario(inputs, 32, 32, &mut rng, None)
+        };
+        let baseline = run(&inputs);
+        assert_approx(baseline.reported_retirement_total, 105_000.0);
+
+        // Hand calculation: the 20k overpayment at 31 takes the 5k of cash, then 15k of
+        // ISA. The level payment stays with earnings.
+        inputs.mortgage_overpayments = vec![(31, 20_000.0)];
+        let overpaid = run(&inputs);
+        assert_approx(overpaid.reported_retirement_cash, 0.0);
+        assert_approx(overpaid.reported_retirement_isa, 85_000.0);
+        assert_approx(overpaid.reported_retirement_total, 85_000.0);
+    }
+
+    #[test]
+    fn pre_retirement_overpayment_cgt_uses_the_band_left_by_earnings() {
+        let mut inputs = sample_inputs();
         inputs.inflation_mean = 0.0;
-        inputs.inflation_vol = 0.0;
-        inputs.pension_tax_mode = PensionTaxMode::UkBands;
-        inputs.state_pension_start_age = 30;
-        inputs.state_pension_annual_income = 10_000.0;
+        inputs.cgt_basic_rate = 0.18;
+        inputs.cgt_higher_rate = 0.24;
+        inputs.mortgage_balance = 100_000.0;
+        inputs.mortgage_rate = 0.0;
+        inputs.mortgage_term_years = 10;
+        inputs.mortgage_overpayments = vec![(30, 20_000.0)];
+        let cgt_at = |earnings: f64| {
+            let inputs = Inputs {
+                pre_retirement_earnings: earnings,
+                ..inputs.clone()
+            };
+            let mut portfolio = Portfolio {
+                isa: 0.0,
+                lisa: 0.0,
+                taxable: 100_000.0,
+                taxable_basis: 0.0,
+                pension: 0.0,
+                partner_pension: 0.0,
+                cash_buffer: 0.0,
+                bond_ladder: 0.0,
+            };
+            fund_pre_retirement_overpayment(&inputs, &mut portfolio, 0, 1.0, 1.0)
+        };
 
-        let mut rng = Rng::new(1);
-        let s = simulate_scenario(&inputs, 30, 30, &mut rng, None);
-        assert!(s.success);
+        // Hand calculation for a sale of g, all gain, netting 20,000 after the 3,000
+        // exemption. At 40,000 of earnings 10,270 of the basic band is left:
+        // g - 0.18 * 10,270 - 0.24 * (g - 13,270) = 20,000, so g = 24,557.63.
+        assert_approx_tol(cgt_at(40_000.0), 4_557.63, 0.01);
+        // Above the basic-rate limit every gain pays the higher rate:
+        // g - 0.24 * (g - 3,000) = 20,000.
+        assert_approx_tol(cgt_at(60_000.0), 5_368.42, 0.01);
+        // Without earnings the whole gain fits in the basic band:
+        // g - 0.18 * (g - 3,000) = 20,000.
+        assert_approx_tol(cgt_at(0.0), 3_731.71, 0.01);
     }
 
     #[test]
-    fn required_spending_drops_after_mortgage_end_age() {
+    fn ensemble_extremes_match_the_standalone_models() {
         let mut inputs = sample_inputs();
-        inputs.target_annual_income = 30_000.0;
-        inputs.mortgage_annual_payment = 12_000.0;
-        inputs.mortgage_end_age = Some(40);
+        inputs.simulations = 200;
+        inputs.bootstrap_block_years = 5;
+        let run = |model: MarketModel| {
+            let inputs = Inputs {
+                market_model: model,
+                ..inputs.clone()
+            };
+            run_terminal_distribution(&inputs, 45, 45, 45)
+        };
+
+        assert_eq!(
+            run(MarketModel::Ensemble {
+                bootstrap_fraction: 0.0
+            }),
+            run(MarketModel::Parametric)
+        );
+        assert_eq!(
+            run(MarketModel::Ensemble {
+                bootstrap_fraction: 1.0
+            }),
+            run(MarketModel::HistoricalBootstrap)
+        );
 
-        assert_approx(required_real_spending(&inputs, 39), 42_000.0);
-        assert_approx(required_real_spending(&inputs, 40), 30_000.0);
-        assert_approx(required_real_spending(&inputs, 41), 30_000.0);
+        inputs.market_model = MarketModel::Ensemble {
+            bootstrap_fraction: 0.25,
+        };
+        let bootstrapped: Vec<u32> = (0..inputs.simulations)
+            .filter(|&id| MarketSampler::for_scenario(&inputs, id).ensemble_bootstrap)
+            .collect();
+        assert_eq!(bootstrapped.len(), 50);
+        assert_eq!(&bootstrapped[..3], &[3, 7, 11]);
     }
 
     #[test]
-    fn mortgage_end_age_reduces_required_spending_in_retirement() {
+    fn failure_attribution_flags_inflation_when_prices_outrun_fixed_returns() {
         let mut inputs = sample_inputs();
-        inputs.current_age = 30;
-        inputs.max_retirement_age = 30;
-        inputs.horizon_age = 32;
-        inputs.pension_access_age = 30;
-        inputs.isa_start = 25_000.0;
+        inputs.current_age = 60;
+        inputs.horizon_age = 90;
+        inputs.isa_start = 1_000_000.0;
         inputs.taxable_start = 0.0;
         inputs.taxable_cost_basis_start = 0.0;
         inputs.pension_start = 0.0;
-        inputs.cash_start = 0.0;
-        inputs.isa_annual_contribution = 0.0;
-        inputs.taxable_annual_contribution = 0.0;
-        inputs.pension_annual_contribution = 0.0;
-        inputs.contribution_growth_rate = 0.0;
-        inputs.target_annual_income = 10_000.0;
-        inputs.mortgage_annual_payment = 5_000.0;
-        inputs.mortgage_end_age = Some(31);
-        inputs.isa_return_mean = 0.0;
-        inputs.taxable_return_mean = 0.0;
-        inputs.pension_return_mean = 0.0;
+        inputs.isa_return_mean = 0.04;
         inputs.isa_return_vol = 0.0;
         inputs.taxable_return_vol = 0.0;
         inputs.pension_return_vol = 0.0;
-        inputs.inflation_mean = 0.0;
-        inputs.inflation_vol = 0.0;
-        inputs.cash_growth_rate = 0.0;
-        inputs.taxable_return_tax_drag = 0.0;
-        inputs.capital_gains_tax_rate = 0.0;
-        inputs.capital_gains_allowance = 0.0;
-        inputs.pension_tax_mode = PensionTaxMode::FlatRate;
-        inputs.pension_flat_tax_rate = 0.0;
-        inputs.state_pension_start_age = 200;
-        inputs.state_pension_annual_income = 0.0;
-        inputs.withdrawal_strategy = WithdrawalStrategy::Guardrails;
-        inputs.bad_year_threshold = -1.0;
-        inputs.good_year_threshold = 1.0;
-        inputs.bad_year_cut = 0.0;
-        inputs.good_year_raise = 0.0;
-        inputs.min_income_floor = 1.0;
-        inputs.max_income_ceiling = 1.0;
-        inputs.good_year_extra_buffer_withdrawal = 0.0;
-        inputs.post_access_withdrawal_order = WithdrawalOrder::IsaFirst;
+        inputs.inflation_mean = 0.03;
+        inputs.inflation_vol = 0.03;
+        inputs.target_annual_income = 38_000.0;
+        inputs.withdrawal_strategy = WithdrawalStrategy::FixedReal;
+        inputs.simulations = 400;
+
+        let result = evaluate_age_candidate(&inputs, 60, 60, 60);
+        let attribution = result
+            .failure_attribution
+            .expect("high-inflation paths should fail");
+        assert!(result.success_rate > 0.1 && result.success_rate < 0.9);
+        assert_eq!(attribution.dominant_factor, Some(FailureFactor::Inflation));
+        assert!(attribution.inflation_score > attribution.market_score);
+        // ISA withdrawals are tax-free, so tax cannot tell failures apart.
+        assert_approx(attribution.tax_score, 0.5);
+        assert_approx(attribution.median_tax_paid, 0.0);
+        assert!(attribution.median_cumulative_inflation > 1.03_f64.powi(20) - 1.0);
 
-        let mut rng = Rng::new(123);
-        let ends_early = simulate_scenario(&inputs, 30, 30, &mut rng, None);
-        assert!(ends_early.success);
-        assert_approx(ends_early.reported_terminal_total, 0.0);
-        assert_approx(ends_early.min_income_ratio, 1.0);
+        inputs.target_annual_income = 10_000.0;
+        let result = evaluate_age_candidate(&inputs, 60, 60, 60);
+        assert_eq!(result.success_rate, 1.0);
+        assert!(result.failure_attribution.is_none());
+    }
 
-        inputs.mortgage_end_age = Some(35);
-        let mut rng2 = Rng::new(123);
-        let ends_late = simulate_scenario(&inputs, 30, 30, &mut rng2, None);
-        assert!(!ends_late.success);
-        assert!(ends_late.min_income_ratio < 1.0);
+    #[test]
+    fn part_time_income_covering_early_spending_raises_success_rate() {
+        let mut inputs = sample_inputs();
+        inputs.current_age = 45;
+        inputs.horizon_age = 90;
+        inputs.isa_start = 600_000.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.target_annual_income = 30_000.0;
+        inputs.withdrawal_strategy = WithdrawalStrategy::FixedReal;
+        inputs.simulations = 400;
+        let without = evaluate_age_candidate(&inputs, 45, 45, 45).success_rate;
+
+        // Enough gross income to cover spending after tax for the first five years.
+        inputs.part_time_annual_income = 40_000.0;
+        inputs.part_time_start_age = 45;
+        inputs.part_time_end_age = 50;
+        let with = evaluate_age_candidate(&inputs, 45, 45, 45).success_rate;
+        assert!(with > without + 0.1, "{without} -> {with}");
+
+        let rows = run_yearly_cashflow_trace(&inputs, 45, 45, 45);
+        for row in &rows[..5] {
+            assert_approx(row.median_withdrawal_portfolio, 0.0);
+        }
+        assert!(rows[5].median_withdrawal_portfolio > 0.0);
     }
 
     #[test]
-    fn net_from_taxable_gross_with_no_gain_has_no_tax() {
-        let net = net_from_taxable_gross(100.0, 200.0, 200.0, 3_000.0, 0.20);
-        assert_approx(net, 100.0);
+    fn lower_tail_mean_matches_hand_built_distribution() {
+        let values = (1..=100).map(f64::from).collect::<Vec<_>>();
+        assert_eq!(lower_tail_mean(&values, CVAR_TAIL_FRACTION), 3.0);
+
+        // 30 samples: the bottom 5% is 1.5 scenarios, rounded up to two.
+        let values = (1..=30).map(f64::from).collect::<Vec<_>>();
+        assert_eq!(lower_tail_mean(&values, CVAR_TAIL_FRACTION), 1.5);
+
+        // Fewer than 20 samples: the tail is the single worst outcome.
+        assert_eq!(lower_tail_mean(&[-4.0, 2.0, 9.0], CVAR_TAIL_FRACTION), -4.0);
+        assert_eq!(lower_tail_mean(&[], CVAR_TAIL_FRACTION), 0.0);
     }
 
     #[test]
-    fn net_from_taxable_gross_applies_allowance_then_tax() {
-        let net = net_from_taxable_gross(50.0, 100.0, 40.0, 10.0, 0.20);
-        assert_approx(net, 46.0);
+    fn cvar_sits_at_or_below_p5() {
+        let mut inputs = sample_inputs();
+        inputs.simulations = 400;
+        let result = run_retirement_age_evaluation(&inputs, inputs.current_age + 5);
+        assert!(result.cvar5_terminal_pot <= result.p5_terminal_pot + 1e-9);
+        assert!(result.cvar5_min_income_ratio <= result.p10_min_income_ratio + 1e-9);
     }
 
     #[test]
-    fn execute_taxable_sale_updates_value_basis_and_allowance() {
-        let mut taxable = 100.0;
-        let mut basis = 40.0;
-        let mut cgt = CgtState {
-            allowance_remaining: 10.0,
-            tax_paid: 0.0,
-        };
+    fn streaming_median_is_exact_below_sample_limit() {
+        let mut median = StreamingPercentiles::new(&MEDIAN_ONLY);
+        for value in [5.0, 1.0, 4.0, 2.0, 3.0, 6.0] {
+            median.push(value);
+        }
+        assert_eq!(median.value(50.0), 3.5);
+    }
+
+    #[test]
+    fn p2_median_tracks_exact_sort_for_known_dataset() {
+        let mut rng = Rng::new(2024);
+        let values = (0..50_000)
+            .map(|_| 100.0 + 15.0 * rng.standard_normal())
+            .collect::<Vec<_>>();
+
+        let mut streaming = StreamingPercentiles::new(&MEDIAN_ONLY);
+        for &value in &values {
+            streaming.push(value);
+        }
+        assert!(!streaming.estimates.is_empty());
+        assert!(streaming.exact.is_empty());
+
+        let mut sorted = values.clone();
+        let exact = percentile(&mut sorted, 50.0);
+
+        // Pure P² from the first five observations, no exact warm-up.
+        let mut pure = P2Quantile::from_sorted(0.5, &{
+            let mut head = values[..5].to_vec();
+            head.sort_by(|a, b| a.total_cmp(b));
+            head
+        });
+        for &value in &values[5..] {
+            pure.push(value);
+        }
+
+        assert!((streaming.value(50.0) - exact).abs() < 0.5, "{exact}");
+        assert!((pure.value() - exact).abs() < 0.5, "{exact}");
+    }
+
+    #[test]
+    fn cashflow_trace_medians_match_exact_medians_past_sample_limit() {
+        let mut inputs = sample_inputs();
+        inputs.simulations = (EXACT_PERCENTILE_SAMPLE_LIMIT * 3) as u32;
+        inputs.horizon_age = inputs.current_age + 20;
+        let retirement_age = inputs.current_age + 10;
+
+        let rows =
+            run_yearly_cashflow_trace(&inputs, retirement_age, retirement_age, retirement_age);
+
+        let mut end_totals = vec![Vec::new(); rows.len()];
+        for scenario_id in 0..inputs.simulations {
+            let mut rng = Rng::new(derive_seed(inputs.seed, retirement_age, scenario_id));
+            let mut trace = Vec::new();
+            let _ = simulate_scenario(
+                &inputs,
+                retirement_age,
+                retirement_age,
+                &mut rng,
+                Some(&mut trace),
+            );
+            for (idx, point) in trace.iter().enumerate() {
+                end_totals[idx].push(point.end_total_real);
+            }
+        }
+
+        for (row, samples) in rows.iter().zip(end_totals.iter_mut()) {
+            let exact = percentile(samples, 50.0);
+            assert!(
+                (row.median_end_total - exact).abs() <= 0.01 * exact.abs().max(1.0),
+                "age {}: streaming {} vs exact {}",
+                row.age,
+                row.median_end_total,
+                exact
+            );
+        }
+    }
+
+    /// Benchmark-style check; run with `cargo test --release -- --ignored`. Fails, with
+    /// the elapsed time, if the trace takes longer than the budget.
+    #[test]
+    #[ignore]
+    fn cashflow_trace_benchmark_10k_scenarios_60_years() {
+        const BUDGET: std::time::Duration = std::time::Duration::from_secs(30);
+        let mut inputs = sample_inputs();
+        inputs.simulations = 10_000;
+        inputs.horizon_age = inputs.current_age + 60;
+        let retirement_age = inputs.current_age + 20;
+
+        let started = std::time::Instant::now();
+        let rows =
+            run_yearly_cashflow_trace(&inputs, retirement_age, retirement_age, retirement_age);
+        let elapsed = started.elapsed();
+        assert_eq!(rows.len(), 60);
+        assert!(
+            elapsed < BUDGET,
+            "cashflow trace: {} scenarios x {} years took {elapsed:?}, over the {BUDGET:?} budget",
+            inputs.simulations,
+            rows.len()
+        );
+    }
+
+    #[test]
+    fn parallel_sweep_is_bit_identical_to_sequential() {
+        let mut inputs = sample_inputs();
+        inputs.simulations = 300;
+        inputs.max_retirement_age = inputs.current_age + 4;
+        // Utility is summed in scenario order, so it catches a fold that reorders.
+        inputs.risk_aversion = Some(2.0);
+
+        inputs.threads = 1;
+        let sequential = run_model(&inputs).expect("inputs are valid");
+        let sequential_coast = run_coast_model(&inputs, inputs.max_retirement_age);
+        for threads in [0, 4] {
+            inputs.threads = threads;
+            assert_eq!(
+                format!("{:?}", run_model(&inputs).expect("inputs are valid")),
+                format!("{sequential:?}")
+            );
+            assert_eq!(
+                format!("{:?}", run_coast_model(&inputs, inputs.max_retirement_age)),
+                format!("{sequential_coast:?}")
+            );
+        }
+    }
 
-        let net = execute_taxable_sale(50.0, &mut taxable, &mut basis, &mut cgt, 0.20);
-        assert_approx(net, 46.0);
-        assert_approx(taxable, 50.0);
-        assert_approx(basis, 20.0);
-        assert_approx(cgt.allowance_remaining, 0.0);
+    #[test]
+    fn glide_equity_fraction_moves_linearly_then_holds() {
+        let mut inputs = sample_inputs();
+        inputs.glide_start_equity = 0.9;
+        inputs.glide_end_equity = 0.3;
+        inputs.glide_end_age = Some(60);
+
+        assert!((glide_equity_fraction(&inputs, 30) - 0.9).abs() < 1e-12);
+        assert!((glide_equity_fraction(&inputs, 45) - 0.6).abs() < 1e-12);
+        assert!((glide_equity_fraction(&inputs, 60) - 0.3).abs() < 1e-12);
+        assert!((glide_equity_fraction(&inputs, 80) - 0.3).abs() < 1e-12);
+
+        inputs.glide_end_age = None;
+        assert!((glide_equity_fraction(&inputs, 60) - 0.6).abs() < 1e-12);
     }
 
     #[test]
-    fn withdraw_from_taxable_for_net_targets_net_amount() {
-        let mut taxable = 100.0;
-        let mut basis = 40.0;
-        let mut cgt = CgtState {
-            allowance_remaining: 10.0,
-            tax_paid: 0.0,
+    fn glide_path_blends_pot_returns_with_the_bond_return() {
+        let mut inputs = sample_inputs();
+        inputs.glide_start_equity = 0.8;
+        inputs.glide_end_equity = 0.2;
+        inputs.glide_end_age = Some(50);
+        let sampled = MarketSample {
+            isa_return: 0.10,
+            taxable_return: 0.06,
+            pension_return: -0.20,
+            inflation: 0.03,
+            bond_ladder_return: 0.02,
         };
 
-        let withdrawn =
-            withdraw_from_taxable_for_net(46.0, &mut taxable, &mut basis, &mut cgt, 0.20);
-        assert!((withdrawn - 46.0).abs() < 1e-3);
-        assert!(taxable < 100.0);
-        assert!(basis < 40.0);
+        // 80% equity at 30: 0.8 * 10% + 0.2 * 2% = 8.4% for the ISA.
+        let start = glide_path_sample(&inputs, &sampled, 30);
+        assert!((start.isa_return - 0.084).abs() < 1e-12);
+        assert!((start.taxable_return - 0.052).abs() < 1e-12);
+        assert!((start.pension_return + 0.156).abs() < 1e-12);
+
+        // Halfway to 50 the equity share is 50%: 0.5 * 10% + 0.5 * 2% = 6%.
+        let halfway = glide_path_sample(&inputs, &sampled, 40);
+        assert!((halfway.isa_return - 0.06).abs() < 1e-12);
+        assert!((halfway.taxable_return - 0.04).abs() < 1e-12);
+        assert!((halfway.pension_return + 0.09).abs() < 1e-12);
+        assert_eq!(halfway.inflation, 0.03);
+        assert_eq!(halfway.bond_ladder_return, 0.02);
     }
 
     #[test]
-    fn withdraw_from_portfolio_before_pension_access_ignores_pension() {
+    fn declining_glide_path_narrows_terminal_wealth_spread() {
         let mut inputs = sample_inputs();
-        inputs.pension_access_age = 60;
-        let mut portfolio = Portfolio {
-            isa: 100.0,
-            taxable: 100.0,
-            taxable_basis: 100.0,
-            pension: 100.0,
-            cash_buffer: 0.0,
-            bond_ladder: 0.0,
-        };
-        let mut cgt = CgtState {
-            allowance_remaining: 3_000.0,
-            tax_paid: 0.0,
-        };
-        let mut tax_state = TaxYearState {
-            non_pension_taxable_income: 0.0,
-            pension_gross_withdrawn: 0.0,
-            price_index: 1.0,
-        };
+        inputs.max_retirement_age = 45;
+        inputs.target_annual_income = 20_000.0;
+        inputs.simulations = 400;
+        let all_equity = evaluate_age_candidate(&inputs, 45, 45, 45);
 
-        let withdrawn = withdraw_from_portfolio(
-            &inputs,
-            50,
-            150.0,
-            &mut portfolio,
-            &mut cgt,
-            &mut tax_state,
-            WithdrawalOrder::PensionFirst,
-        );
+        inputs.glide_start_equity = 1.0;
+        inputs.glide_end_equity = 0.2;
+        let glided = evaluate_age_candidate(&inputs, 45, 45, 45);
 
-        assert_approx(withdrawn, 150.0);
-        assert_approx(portfolio.pension, 100.0);
+        let spread = |r: &AgeResult| r.p90_terminal_pot - r.p10_terminal_pot;
+        assert!(
+            spread(&glided) < spread(&all_equity),
+            "glided spread {} should be below all-equity spread {}",
+            spread(&glided),
+            spread(&all_equity)
+        );
     }
 
     #[test]
-    fn run_withdrawal_year_adds_extra_to_cash_in_good_years() {
+    fn fund_fees_shrink_pots_by_exactly_the_fee_each_year_at_zero_return() {
         let mut inputs = sample_inputs();
-        inputs.good_year_threshold = 0.0;
-        inputs.good_year_extra_buffer_withdrawal = 0.10;
-        inputs.post_access_withdrawal_order = WithdrawalOrder::IsaFirst;
-
+        inputs.taxable_return_tax_drag = 0.0;
+        inputs.cash_growth_rate = 0.0;
+        inputs.isa_fee_rate = 0.01;
+        inputs.taxable_fee_rate = 0.01;
+        inputs.pension_fee_rate = 0.01;
+        let flat = MarketSample {
+            isa_return: 0.0,
+            taxable_return: 0.0,
+            pension_return: 0.0,
+            inflation: 0.0,
+            bond_ladder_return: 0.0,
+        };
         let mut portfolio = Portfolio {
-            isa: 200.0,
-            taxable: 0.0,
-            taxable_basis: 0.0,
-            pension: 0.0,
-            cash_buffer: 0.0,
+            isa: 100_000.0,
+            lisa: 0.0,
+            taxable: 50_000.0,
+            taxable_basis: 50_000.0,
+            pension: 200_000.0,
+            partner_pension: 0.0,
+            cash_buffer: 10_000.0,
             bond_ladder: 0.0,
         };
-        let mut cgt = CgtState {
-            allowance_remaining: 3_000.0,
-            tax_paid: 0.0,
-        };
-        let mut tax_state = TaxYearState {
-            non_pension_taxable_income: 0.0,
-            pension_gross_withdrawn: 0.0,
-            price_index: 1.0,
-        };
 
-        let outcome = run_withdrawal_year(
-            &inputs,
-            60,
-            0,
-            100.0,
-            0.10,
-            100.0,
-            &mut portfolio,
-            &mut cgt,
-            &mut tax_state,
-            0.0,
-        );
-        assert_approx(outcome.realized_spending_net, 100.0);
-        assert_approx(portfolio.cash_buffer, 10.0);
-        assert_approx(portfolio.isa, 90.0);
+        for year in 1..=10 {
+            if year <= 5 {
+                apply_pre_retirement_growth(&inputs, &mut portfolio, &flat, 30, 0.0, 1.0);
+            } else {
+                apply_post_retirement_growth(&inputs, &mut portfolio, &flat, 35, 0.0, 1.0);
+            }
+            let kept = 0.99_f64.powi(year);
+            assert!((portfolio.isa - 100_000.0 * kept).abs() < 1e-6);
+            assert!((portfolio.taxable - 50_000.0 * kept).abs() < 1e-6);
+            assert!((portfolio.pension - 200_000.0 * kept).abs() < 1e-6);
+            assert_eq!(portfolio.cash_buffer, 10_000.0);
+        }
     }
 
     #[test]
-    fn plan_real_spending_guyton_klinger_cuts_after_bad_year_above_guardrail() {
+    fn market_sampler_full_history_block_replays_actual_path() {
         let mut inputs = sample_inputs();
-        inputs.withdrawal_strategy = WithdrawalStrategy::GuytonKlinger;
-        inputs.bad_year_threshold = -0.05;
-        inputs.bad_year_cut = 0.10;
-        inputs.gk_upper_guardrail = 1.20;
+        inputs.market_model = MarketModel::HistoricalBootstrap;
+        inputs.bootstrap_block_years = HISTORICAL_YEARS.len() as u32;
+
+        let mut rng = Rng::new(11);
+        let mut market = MarketSampler::new();
+        for _pass in 0..2 {
+            for row in HISTORICAL_YEARS.iter() {
+                let s = market.sample(&inputs, &mut rng);
+                assert_eq!(s.isa_return, row.equity);
+                assert_eq!(s.bond_ladder_return, row.bond);
+                assert_eq!(s.inflation, row.inflation);
+            }
+        }
+    }
 
-        let mut spending_state = SpendingState {
-            current_real_spending: 50_000.0,
-            initial_withdrawal_rate: 0.04,
+    #[test]
+    fn market_sampler_replays_deterministic_path_then_holds_last_year() {
+        let mut inputs = sample_inputs();
+        inputs.market_model = MarketModel::Deterministic {
+            paths: vec![(-0.4, -0.3, -0.2, 0.09), (0.1, 0.2, 0.3, 0.02)],
         };
 
-        let planned = plan_real_spending(&inputs, 60, -0.10, 1_000_000.0, &mut spending_state);
-        assert_approx(planned, 45_000.0);
+        let mut rng = Rng::new(5);
+        let mut market = MarketSampler::new();
+        let s = market.sample(&inputs, &mut rng);
+        assert_eq!(
+            (
+                s.isa_return,
+                s.taxable_return,
+                s.pension_return,
+                s.inflation
+            ),
+            (-0.4, -0.3, -0.2, 0.09)
+        );
+        assert_eq!(s.bond_ladder_return, inputs.bond_ladder_yield);
+        for _ in 0..3 {
+            let s = market.sample(&inputs, &mut rng);
+            assert_eq!(
+                (
+                    s.isa_return,
+                    s.taxable_return,
+                    s.pension_return,
+                    s.inflation
+                ),
+                (0.1, 0.2, 0.3, 0.02)
+            );
+        }
     }
 
     #[test]
-    fn plan_real_spending_vpw_spends_more_later_with_same_balance() {
+    fn market_sampler_blocks_are_contiguous_runs_of_years() {
         let mut inputs = sample_inputs();
-        inputs.withdrawal_strategy = WithdrawalStrategy::Vpw;
-        inputs.vpw_expected_real_return = 0.03;
+        inputs.market_model = MarketModel::HistoricalBootstrap;
+        inputs.bootstrap_block_years = 5;
+
+        let mut rng = Rng::new(3);
+        let mut market = MarketSampler::new();
+        for _block in 0..50 {
+            let mut years = Vec::with_capacity(5);
+            for _ in 0..5 {
+                let s = market.sample(&inputs, &mut rng);
+                let row = HISTORICAL_YEARS
+                    .iter()
+                    .find(|row| s.isa_return == row.equity && s.inflation == row.inflation)
+                    .expect("sample must be a row of the historical table");
+                years.push(row.year);
+            }
+            assert!(years.windows(2).all(|w| w[1] == w[0] + 1), "{years:?}");
+        }
+    }
 
-        let mut early_state = SpendingState {
-            current_real_spending: 50_000.0,
-            initial_withdrawal_rate: 0.04,
+    #[test]
+    fn stratified_inflation_tracks_the_target_quantiles_more_closely() {
+        assert_approx(standard_normal_quantile(0.5), 0.0);
+        assert!((standard_normal_quantile(0.975) - 1.959_964).abs() < 1e-6);
+        assert!((standard_normal_quantile(0.001) + 3.090_232).abs() < 1e-6);
+
+        // Every year the strata form a permutation of the scenarios, whatever the count.
+        for count in [1, 36, 40, 97] {
+            for year in 0..10 {
+                let mut strata = (0..count)
+                    .map(|scenario_id| {
+                        InflationStratum {
+                            scenario_id,
+                            count,
+                            key: splitmix64(7),
+                        }
+                        .index(year)
+                    })
+                    .collect::<Vec<_>>();
+                strata.sort_unstable();
+                assert_eq!(strata, (0..count as u64).collect::<Vec<_>>());
+            }
+        }
+
+        let mut inputs = sample_inputs();
+        inputs.simulations = 40;
+        inputs.inflation_mean = 0.02;
+        inputs.inflation_vol = 0.01;
+        inputs.inflation_return_correlation = 0.0;
+        inputs.inflation_clamp_min = -1.0;
+        inputs.inflation_clamp_max = 1.0;
+
+        // Q-Q error: the mean gap between each sorted, standardized inflation draw and the
+        // normal quantile at its plotting position, averaged over ten years.
+        let qq_error = |stratified: bool| {
+            let inputs = Inputs {
+                stratified_inflation: stratified,
+                ..inputs.clone()
+            };
+            let n = inputs.simulations;
+            let mut scenarios = (0..n)
+                .map(|id| {
+                    (
+                        MarketSampler::for_scenario(&inputs, id),
+                        Rng::new(derive_seed(inputs.seed, 0, id)),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let years = 10;
+            let mut total = 0.0;
+            for _ in 0..years {
+                let mut z = scenarios
+                    .iter_mut()
+                    .map(|(market, rng)| (market.sample(&inputs, rng).inflation - 0.02) / 0.01)
+                    .collect::<Vec<_>>();
+                sort_samples(&mut z);
+                total += z
+                    .iter()
+                    .enumerate()
+                    .map(|(k, z)| (z - standard_normal_quantile((k as f64 + 0.5) / n as f64)).abs())
+                    .sum::<f64>()
+                    / n as f64;
+            }
+            total / years as f64
         };
-        let early = plan_real_spending(&inputs, 60, 0.0, 1_000_000.0, &mut early_state);
+        let (plain, stratified) = (qq_error(false), qq_error(true));
+        assert!(
+            stratified < 0.5 * plain,
+            "stratified {stratified} vs plain {plain}"
+        );
+    }
 
-        let mut late_state = SpendingState {
-            current_real_spending: 50_000.0,
-            initial_withdrawal_rate: 0.04,
+    #[test]
+    fn market_sampler_garch_raises_volatility_after_a_large_shock() {
+        let mut inputs = sample_inputs();
+        inputs.isa_return_mean = 0.0;
+        inputs.market_model = MarketModel::Garch {
+            alpha: 0.3,
+            beta: 0.6,
+            long_run_vol: 0.15,
         };
-        let late = plan_real_spending(&inputs, 80, 0.0, 1_000_000.0, &mut late_state);
 
-        assert!(late > early);
+        // Split scenarios by the size of their first-year shock and compare the spread
+        // of the second year's returns. A 2-sigma shock lifts the next variance to at
+        // least 1.9x the long-run level; a shock under half a sigma leaves it below 0.8x.
+        let (mut after_large, mut after_small) = (Vec::new(), Vec::new());
+        for scenario in 0..20_000 {
+            let mut rng = Rng::new(derive_seed(11, 0, scenario));
+            let mut market = MarketSampler::new();
+            let first = market.sample(&inputs, &mut rng).isa_return;
+            let second = market.sample(&inputs, &mut rng).isa_return;
+            if first.abs() > 0.30 {
+                after_large.push(second);
+            } else if first.abs() < 0.075 {
+                after_small.push(second);
+            }
+        }
+        let variance =
+            |samples: &[f64]| samples.iter().map(|r| r * r).sum::<f64>() / samples.len() as f64;
+        let long_run_variance = 0.15 * 0.15;
+        assert!(after_large.len() > 500 && after_small.len() > 5_000);
+        assert!(
+            variance(&after_large) > 1.6 * long_run_variance,
+            "variance after a large shock {}",
+            variance(&after_large)
+        );
+        assert!(
+            variance(&after_small) < 0.9 * long_run_variance,
+            "variance after a small shock {}",
+            variance(&after_small)
+        );
     }
 
     #[test]
-    fn plan_real_spending_floor_upside_increases_after_positive_returns() {
+    fn market_sampler_sticky_crisis_regime_clusters_bad_years() {
         let mut inputs = sample_inputs();
-        inputs.withdrawal_strategy = WithdrawalStrategy::FloorUpside;
-        inputs.floor_upside_capture = 0.50;
+        inputs.isa_return_mean = 0.07;
+        inputs.isa_return_vol = 0.15;
+        inputs.market_model = MarketModel::RegimeSwitching {
+            p_normal_to_crisis: 0.1,
+            p_crisis_to_normal: 0.2,
+            crisis_return_mean: -0.2,
+            crisis_return_vol: 0.1,
+        };
 
-        let mut spending_state = SpendingState {
-            current_real_spending: 50_000.0,
-            initial_withdrawal_rate: 0.04,
+        // A bad year loses more than 10%.
+        let bad_year_stats = |inputs: &Inputs| {
+            let mut rng = Rng::new(5);
+            let mut market = MarketSampler::new();
+            let years = 100_000;
+            let bad: Vec<bool> = (0..years)
+                .map(|_| market.sample(inputs, &mut rng).isa_return < -0.1)
+                .collect();
+            let bad_share = bad.iter().filter(|&&b| b).count() as f64 / years as f64;
+            let pair_share =
+                bad.windows(2).filter(|w| w[0] && w[1]).count() as f64 / (years - 1) as f64;
+            (bad_share, pair_share)
         };
 
-        let planned = plan_real_spending(&inputs, 60, 0.20, 1_000_000.0, &mut spending_state);
-        assert_approx(planned, 55_000.0);
+        // IID draws with the same bad-year rate would see back-to-back bad years at
+        // roughly the square of that rate; a sticky crisis regime sees far more.
+        let (bad_share, pair_share) = bad_year_stats(&inputs);
+        assert!(
+            pair_share > 1.3 * bad_share * bad_share,
+            "pairs {pair_share}, bad years {bad_share}"
+        );
+
+        inputs.market_model = MarketModel::Parametric;
+        let (bad_share, pair_share) = bad_year_stats(&inputs);
+        assert!(
+            (pair_share - bad_share * bad_share).abs() < 0.01,
+            "pairs {pair_share}, bad years {bad_share}"
+        );
     }
 
     #[test]
-    fn run_withdrawal_year_bucket_refills_cash_toward_target_after_good_year() {
+    fn market_burn_in_starts_stateful_models_in_their_long_run_distribution() {
         let mut inputs = sample_inputs();
-        inputs.withdrawal_strategy = WithdrawalStrategy::Bucket;
-        inputs.good_year_threshold = 0.0;
-        inputs.bucket_target_years = 2.0;
-        inputs.good_year_extra_buffer_withdrawal = 2.0;
-        inputs.post_access_withdrawal_order = WithdrawalOrder::IsaFirst;
-
-        let mut portfolio = Portfolio {
-            isa: 500.0,
-            taxable: 0.0,
-            taxable_basis: 0.0,
-            pension: 0.0,
-            cash_buffer: 0.0,
-            bond_ladder: 0.0,
-        };
-        let mut cgt = CgtState {
-            allowance_remaining: 3_000.0,
-            tax_paid: 0.0,
+        inputs.market_model = MarketModel::Garch {
+            alpha: 0.1,
+            beta: 0.85,
+            long_run_vol: 0.12,
         };
-        let mut tax_state = TaxYearState {
-            non_pension_taxable_income: 0.0,
-            pension_gross_withdrawn: 0.0,
-            price_index: 1.0,
+        inputs.market_burn_in_years = 50;
+        let variance = |market: &MarketSampler| market.garch_variance.unwrap_or(0.12 * 0.12);
+        let quantile = |values: &mut Vec<f64>, q: f64| {
+            sort_samples(values);
+            values[(q * (values.len() - 1) as f64) as usize]
         };
 
-        let outcome = run_withdrawal_year(
-            &inputs,
-            60,
-            0,
-            100.0,
-            0.10,
-            100.0,
-            &mut portfolio,
-            &mut cgt,
-            &mut tax_state,
-            0.0,
+        // The variance each first simulated year is drawn with, across scenarios...
+        let first_year = |inputs: &Inputs| -> Vec<f64> {
+            (0..5_000)
+                .map(|seed| {
+                    let mut market = MarketSampler::new();
+                    market.burn_in(inputs, &mut Rng::new(seed));
+                    assert_eq!(market.years_sampled, 0);
+                    variance(&market)
+                })
+                .collect()
+        };
+        // ...against the variance along one long path once it has forgotten its start.
+        let mut rng = Rng::new(1);
+        let mut market = MarketSampler::new();
+        let mut long_run: Vec<f64> = (0..10_200)
+            .map(|_| {
+                let v = variance(&market);
+                market.sample(&inputs, &mut rng);
+                v
+            })
+            .skip(200)
+            .collect();
+
+        let mut warmed = first_year(&inputs);
+        let long_run_mean = long_run.iter().sum::<f64>() / long_run.len() as f64;
+        let warmed_mean = warmed.iter().sum::<f64>() / warmed.len() as f64;
+        assert!(
+            (warmed_mean / long_run_mean - 1.0).abs() < 0.05,
+            "{warmed_mean} vs {long_run_mean}"
         );
+        for q in [0.1, 0.5, 0.9] {
+            let (a, b) = (quantile(&mut warmed, q), quantile(&mut long_run, q));
+            assert!((a / b - 1.0).abs() < 0.1, "q{q}: {a} vs {b}");
+        }
 
-        assert_approx(outcome.realized_spending_net, 100.0);
-        assert_approx(portfolio.cash_buffer, 200.0);
+        inputs.market_burn_in_years = 0;
+        assert!(first_year(&inputs).iter().all(|&v| v == 0.12 * 0.12));
+
+        // A crisis regime is occupied a third of the time in the long run.
+        inputs.market_model = MarketModel::RegimeSwitching {
+            p_normal_to_crisis: 0.1,
+            p_crisis_to_normal: 0.2,
+            crisis_return_mean: -0.2,
+            crisis_return_vol: 0.1,
+        };
+        inputs.market_burn_in_years = 50;
+        let in_crisis = (0..5_000)
+            .filter(|&seed| {
+                let mut market = MarketSampler::new();
+                market.burn_in(&inputs, &mut Rng::new(seed));
+                market.in_crisis
+            })
+            .count() as f64
+            / 5_000.0;
+        assert!((in_crisis - 1.0 / 3.0).abs() < 0.02, "{in_crisis}");
     }
 
     #[test]
-    fn sample_market_zero_volatility_returns_means() {
+    fn market_sampler_historical_bootstrap_only_returns_table_rows() {
         let mut inputs = sample_inputs();
-        inputs.isa_return_vol = 0.0;
-        inputs.taxable_return_vol = 0.0;
-        inputs.pension_return_vol = 0.0;
-        inputs.inflation_vol = 0.0;
+        inputs.market_model = MarketModel::HistoricalBootstrap;
+
+        let draw_years = |seed: u64| -> Vec<u16> {
+            let mut rng = Rng::new(seed);
+            let mut market = MarketSampler::new();
+            (0..5_000)
+                .map(|_| {
+                    let s = market.sample(&inputs, &mut rng);
+                    let row = HISTORICAL_YEARS
+                        .iter()
+                        .find(|row| {
+                            s.isa_return == row.equity
+                                && s.taxable_return == row.equity
+                                && s.pension_return == row.equity
+                                && s.bond_ladder_return == row.bond
+                                && s.inflation == row.inflation
+                        })
+                        .expect("sample must be a row of the historical table");
+                    row.year
+                })
+                .collect()
+        };
 
-        let mut rng = Rng::new(123);
-        let s = sample_market(&inputs, &mut rng);
-        assert_approx(s.isa_return, inputs.isa_return_mean);
-        assert_approx(s.taxable_return, inputs.taxable_return_mean);
-        assert_approx(s.pension_return, inputs.pension_return_mean);
-        assert_approx(s.inflation, inputs.inflation_mean);
+        let years = draw_years(77);
+        let mut distinct = years.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        assert_eq!(distinct.len(), HISTORICAL_YEARS.len());
+        assert_eq!(draw_years(77), years);
     }
 
     #[test]
@@ -3530,6 +9904,20 @@ mod tests {
         assert_approx(s.inflation, 0.20);
     }
 
+    #[test]
+    fn widened_inflation_clamp_lets_deflation_through() {
+        let mut inputs = sample_inputs();
+        inputs.inflation_mean = -0.08;
+        inputs.inflation_vol = 0.0;
+
+        let mut rng = Rng::new(1);
+        assert_approx(sample_market(&inputs, &mut rng).inflation, -0.03);
+
+        inputs.inflation_clamp_min = -0.10;
+        let mut rng = Rng::new(1);
+        assert_approx(sample_market(&inputs, &mut rng).inflation, -0.08);
+    }
+
     #[test]
     fn percentile_interpolates_between_points() {
         let mut values = vec![1.0, 2.0, 3.0, 4.0];
@@ -3546,6 +9934,31 @@ mod tests {
         assert_ne!(a, c);
     }
 
+    #[test]
+    fn age_invariant_draws_make_success_non_decreasing_in_retirement_age() {
+        let mut inputs = sample_inputs();
+        inputs.current_age = 40;
+        inputs.max_retirement_age = 60;
+        inputs.horizon_age = 90;
+        inputs.simulations = 200;
+        inputs.withdrawal_strategy = WithdrawalStrategy::FixedReal;
+        inputs.age_invariant_draws = true;
+
+        let result = run_model(&inputs).expect("inputs are valid");
+        let rates: Vec<f64> = result.age_results.iter().map(|r| r.success_rate).collect();
+        assert!(
+            rates.first() < rates.last(),
+            "sweep should span failures: {rates:?}"
+        );
+        for pair in rates.windows(2) {
+            assert!(
+                pair[1] >= pair[0],
+                "success fell with a later age: {rates:?}"
+            );
+        }
+        assert_eq!(scenario_seed(&inputs, 45, 3), scenario_seed(&inputs, 55, 3));
+    }
+
     #[test]
     fn simulate_scenario_respects_contribution_stop_age() {
         let mut inputs = sample_inputs();
@@ -3584,6 +9997,77 @@ mod tests {
         assert_approx(coast_from_32.reported_retirement_total, 2_000.0);
     }
 
+    #[test]
+    fn coast_contribution_fraction_halves_contributions_in_the_coast_window() {
+        let mut inputs = sample_inputs();
+        inputs.current_age = 30;
+        inputs.horizon_age = 36;
+        inputs.max_retirement_age = 35;
+        inputs.isa_start = 0.0;
+        inputs.taxable_start = 0.0;
+        inputs.taxable_cost_basis_start = 0.0;
+        inputs.pension_start = 0.0;
+        inputs.isa_annual_contribution = 1_000.0;
+        inputs.isa_annual_contribution_limit = 20_000.0;
+        inputs.taxable_annual_contribution = 0.0;
+        inputs.pension_annual_contribution = 4_000.0;
+        inputs.lisa_annual_contribution = 0.0;
+        inputs.employer_match_rate = 0.0;
+        inputs.contribution_growth_rate = 0.0;
+        inputs.target_annual_income = 1e-9;
+        inputs.inflation_mean = 0.0;
+        inputs.inflation_vol = 0.0;
+        inputs.coast_contribution_fraction = 0.5;
+
+        let mut trace = Vec::new();
+        simulate_scenario(&inputs, 35, 32, &mut Rng::new(7), Some(&mut trace));
+        let totals: Vec<f64> = trace
+            .iter()
+            .take(5)
+            .map(|row| row.contribution_total_real)
+            .collect();
+        assert_approx(totals[0], 5_000.0);
+        assert_approx(totals[1], 5_000.0);
+        for coasting in &totals[2..] {
+            assert_approx(*coasting, 2_500.0);
+        }
+    }
+
+    #[test]
+    fn full_employer_match_doubles_pension_contributions_up_to_the_cap() {
+        let mut inputs = sample_inputs();
+        inputs.isa_annual_contribution = 0.0;
+        inputs.taxable_annual_contribution = 0.0;
+        inputs.contribution_growth_rate = 0.0;
+        inputs.employer_match_rate = 1.0;
+        inputs.employer_match_cap = Some(5_000.0);
+
+        for (own, expected_employer) in [
+            (0.0, 0.0),
+            (2_000.0, 2_000.0),
+            (5_000.0, 5_000.0),
+            (8_000.0, 5_000.0),
+            (20_000.0, 5_000.0),
+        ] {
+            inputs.pension_annual_contribution = own;
+            let mut portfolio = Portfolio {
+                isa: 0.0,
+                lisa: 0.0,
+                taxable: 0.0,
+                taxable_basis: 0.0,
+                pension: 0.0,
+                partner_pension: 0.0,
+                cash_buffer: 0.0,
+                bond_ladder: 0.0,
+            };
+            let flow = apply_pre_retirement_contributions(&inputs, &mut portfolio, 0, 1.0, 1.0);
+            assert_approx(flow.pension, own);
+            assert_approx(flow.employer_pension, expected_employer);
+            assert_approx(flow.total(), own + expected_employer);
+            assert_approx(portfolio.pension, own + expected_employer);
+        }
+    }
+
     #[test]
     fn yearly_cashflow_trace_includes_contributions_spending_taxes_and_balances() {
         let mut inputs = sample_inputs();
@@ -3612,7 +10096,8 @@ mod tests {
         inputs.inflation_mean = 0.0;
         inputs.inflation_vol = 0.0;
         inputs.taxable_return_tax_drag = 0.0;
-        inputs.capital_gains_tax_rate = 0.0;
+        inputs.cgt_basic_rate = 0.0;
+        inputs.cgt_higher_rate = inputs.cgt_basic_rate;
         inputs.capital_gains_allowance = 0.0;
         inputs.pension_tax_mode = PensionTaxMode::FlatRate;
         inputs.pension_flat_tax_rate = 0.0;
@@ -3629,6 +10114,7 @@ mod tests {
         assert_approx(rows[0].median_contribution_isa, 10_000.0);
         assert_approx(rows[0].median_contribution_taxable, 4_000.0);
         assert_approx(rows[0].median_contribution_pension, 1_000.0);
+        assert_approx(rows[0].median_contribution_employer, 0.0);
         assert_approx(rows[0].median_contribution_total, 15_000.0);
         assert_approx(rows[1].median_contribution_total, 0.0);
         assert_approx(rows[1].median_spending_total, 10_000.0);
@@ -3649,7 +10135,7 @@ mod tests {
         inputs.inflation_vol = 0.0;
         inputs.target_annual_income = 0.01;
 
-        let model = run_model(&inputs);
+        let model = run_model(&inputs).expect("inputs are valid");
         let age = &model.age_results[0];
         assert!(age.median_retirement_isa >= 0.0);
         assert!(age.median_terminal_pot >= age.p10_terminal_pot);
@@ -0,0 +1,359 @@
+/// One calendar year of annual nominal returns and inflation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HistoricalYear {
+    pub year: u16,
+    /// S&P 500 total return including dividends.
+    pub equity: f64,
+    /// 10-year US Treasury total return.
+    pub bond: f64,
+    /// US CPI-U, December to December.
+    pub inflation: f64,
+}
+
+/// US market history 1970-2023 (consecutive years, oldest first), rounded to 0.01%.
+///
+/// The window starts after Bretton Woods so it covers the 1970s stagflation, the 2000-2002
+/// and 2008 crashes and the 2022 stock/bond sell-off without pre-war data quality issues.
+pub static HISTORICAL_YEARS: [HistoricalYear; 54] = [
+    HistoricalYear {
+        year: 1970,
+        equity: 0.0401,
+        bond: 0.1675,
+        inflation: 0.0560,
+    },
+    HistoricalYear {
+        year: 1971,
+        equity: 0.1431,
+        bond: 0.0979,
+        inflation: 0.0330,
+    },
+    HistoricalYear {
+        year: 1972,
+        equity: 0.1898,
+        bond: 0.0282,
+        inflation: 0.0340,
+    },
+    HistoricalYear {
+        year: 1973,
+        equity: -0.1466,
+        bond: 0.0366,
+        inflation: 0.0870,
+    },
+    HistoricalYear {
+        year: 1974,
+        equity: -0.2647,
+        bond: 0.0199,
+        inflation: 0.1230,
+    },
+    HistoricalYear {
+        year: 1975,
+        equity: 0.3720,
+        bond: 0.0361,
+        inflation: 0.0690,
+    },
+    HistoricalYear {
+        year: 1976,
+        equity: 0.2384,
+        bond: 0.1598,
+        inflation: 0.0490,
+    },
+    HistoricalYear {
+        year: 1977,
+        equity: -0.0718,
+        bond: 0.0129,
+        inflation: 0.0670,
+    },
+    HistoricalYear {
+        year: 1978,
+        equity: 0.0656,
+        bond: -0.0078,
+        inflation: 0.0900,
+    },
+    HistoricalYear {
+        year: 1979,
+        equity: 0.1844,
+        bond: 0.0067,
+        inflation: 0.1330,
+    },
+    HistoricalYear {
+        year: 1980,
+        equity: 0.3242,
+        bond: -0.0299,
+        inflation: 0.1250,
+    },
+    HistoricalYear {
+        year: 1981,
+        equity: -0.0491,
+        bond: 0.0820,
+        inflation: 0.0890,
+    },
+    HistoricalYear {
+        year: 1982,
+        equity: 0.2155,
+        bond: 0.3281,
+        inflation: 0.0380,
+    },
+    HistoricalYear {
+        year: 1983,
+        equity: 0.2256,
+        bond: 0.0320,
+        inflation: 0.0380,
+    },
+    HistoricalYear {
+        year: 1984,
+        equity: 0.0627,
+        bond: 0.1373,
+        inflation: 0.0390,
+    },
+    HistoricalYear {
+        year: 1985,
+        equity: 0.3173,
+        bond: 0.2571,
+        inflation: 0.0380,
+    },
+    HistoricalYear {
+        year: 1986,
+        equity: 0.1867,
+        bond: 0.2428,
+        inflation: 0.0110,
+    },
+    HistoricalYear {
+        year: 1987,
+        equity: 0.0525,
+        bond: -0.0496,
+        inflation: 0.0440,
+    },
+    HistoricalYear {
+        year: 1988,
+        equity: 0.1661,
+        bond: 0.0822,
+        inflation: 0.0440,
+    },
+    HistoricalYear {
+        year: 1989,
+        equity: 0.3169,
+        bond: 0.1769,
+        inflation: 0.0460,
+    },
+    HistoricalYear {
+        year: 1990,
+        equity: -0.0310,
+        bond: 0.0624,
+        inflation: 0.0610,
+    },
+    HistoricalYear {
+        year: 1991,
+        equity: 0.3047,
+        bond: 0.1500,
+        inflation: 0.0310,
+    },
+    HistoricalYear {
+        year: 1992,
+        equity: 0.0762,
+        bond: 0.0936,
+        inflation: 0.0290,
+    },
+    HistoricalYear {
+        year: 1993,
+        equity: 0.1008,
+        bond: 0.1421,
+        inflation: 0.0270,
+    },
+    HistoricalYear {
+        year: 1994,
+        equity: 0.0132,
+        bond: -0.0804,
+        inflation: 0.0270,
+    },
+    HistoricalYear {
+        year: 1995,
+        equity: 0.3758,
+        bond: 0.2348,
+        inflation: 0.0250,
+    },
+    HistoricalYear {
+        year: 1996,
+        equity: 0.2296,
+        bond: 0.0143,
+        inflation: 0.0330,
+    },
+    HistoricalYear {
+        year: 1997,
+        equity: 0.3336,
+        bond: 0.0994,
+        inflation: 0.0170,
+    },
+    HistoricalYear {
+        year: 1998,
+        equity: 0.2858,
+        bond: 0.1492,
+        inflation: 0.0160,
+    },
+    HistoricalYear {
+        year: 1999,
+        equity: 0.2104,
+        bond: -0.0825,
+        inflation: 0.0270,
+    },
+    HistoricalYear {
+        year: 2000,
+        equity: -0.0910,
+        bond: 0.1666,
+        inflation: 0.0340,
+    },
+    HistoricalYear {
+        year: 2001,
+        equity: -0.1189,
+        bond: 0.0557,
+        inflation: 0.0160,
+    },
+    HistoricalYear {
+        year: 2002,
+        equity: -0.2210,
+        bond: 0.1512,
+        inflation: 0.0240,
+    },
+    HistoricalYear {
+        year: 2003,
+        equity: 0.2868,
+        bond: 0.0038,
+        inflation: 0.0190,
+    },
+    HistoricalYear {
+        year: 2004,
+        equity: 0.1088,
+        bond: 0.0449,
+        inflation: 0.0330,
+    },
+    HistoricalYear {
+        year: 2005,
+        equity: 0.0491,
+        bond: 0.0287,
+        inflation: 0.0340,
+    },
+    HistoricalYear {
+        year: 2006,
+        equity: 0.1579,
+        bond: 0.0196,
+        inflation: 0.0250,
+    },
+    HistoricalYear {
+        year: 2007,
+        equity: 0.0549,
+        bond: 0.1021,
+        inflation: 0.0410,
+    },
+    HistoricalYear {
+        year: 2008,
+        equity: -0.3700,
+        bond: 0.2010,
+        inflation: 0.0010,
+    },
+    HistoricalYear {
+        year: 2009,
+        equity: 0.2646,
+        bond: -0.1112,
+        inflation: 0.0270,
+    },
+    HistoricalYear {
+        year: 2010,
+        equity: 0.1506,
+        bond: 0.0846,
+        inflation: 0.0150,
+    },
+    HistoricalYear {
+        year: 2011,
+        equity: 0.0211,
+        bond: 0.1604,
+        inflation: 0.0300,
+    },
+    HistoricalYear {
+        year: 2012,
+        equity: 0.1600,
+        bond: 0.0297,
+        inflation: 0.0170,
+    },
+    HistoricalYear {
+        year: 2013,
+        equity: 0.3239,
+        bond: -0.0910,
+        inflation: 0.0150,
+    },
+    HistoricalYear {
+        year: 2014,
+        equity: 0.1369,
+        bond: 0.1075,
+        inflation: 0.0080,
+    },
+    HistoricalYear {
+        year: 2015,
+        equity: 0.0138,
+        bond: 0.0128,
+        inflation: 0.0070,
+    },
+    HistoricalYear {
+        year: 2016,
+        equity: 0.1196,
+        bond: 0.0069,
+        inflation: 0.0210,
+    },
+    HistoricalYear {
+        year: 2017,
+        equity: 0.2183,
+        bond: 0.0280,
+        inflation: 0.0210,
+    },
+    HistoricalYear {
+        year: 2018,
+        equity: -0.0438,
+        bond: -0.0002,
+        inflation: 0.0190,
+    },
+    HistoricalYear {
+        year: 2019,
+        equity: 0.3149,
+        bond: 0.0964,
+        inflation: 0.0230,
+    },
+    HistoricalYear {
+        year: 2020,
+        equity: 0.1840,
+        bond: 0.1133,
+        inflation: 0.0140,
+    },
+    HistoricalYear {
+        year: 2021,
+        equity: 0.2871,
+        bond: -0.0442,
+        inflation: 0.0700,
+    },
+    HistoricalYear {
+        year: 2022,
+        equity: -0.1811,
+        bond: -0.1783,
+        inflation: 0.0650,
+    },
+    HistoricalYear {
+        year: 2023,
+        equity: 0.2629,
+        bond: 0.0388,
+        inflation: 0.0340,
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn historical_years_are_consecutive_and_plausible() {
+        for pair in HISTORICAL_YEARS.windows(2) {
+            assert_eq!(pair[1].year, pair[0].year + 1);
+        }
+        for row in &HISTORICAL_YEARS {
+            assert!((-0.5..0.6).contains(&row.equity), "{row:?}");
+            assert!((-0.3..0.4).contains(&row.bond), "{row:?}");
+            assert!((-0.05..0.15).contains(&row.inflation), "{row:?}");
+        }
+    }
+}
@@ -1,15 +1,28 @@
+mod builder;
 mod engine;
+mod history;
+mod mortality_table;
+mod sensitivity;
 mod solver;
 mod types;
+mod validation;
 
+pub use builder::InputsBuilder;
 pub use engine::{
-    run_coast_model, run_model, run_retirement_age_evaluation, run_yearly_cashflow_trace,
+    binomial_ci_half_width, return_correlation_cholesky, run_coast_model,
+    run_coast_model_with_progress, run_model, run_model_with_age_results, run_model_with_progress,
+    run_retirement_age_evaluation, run_terminal_distribution, run_yearly_cashflow_trace,
 };
+pub use history::{HISTORICAL_YEARS, HistoricalYear};
+pub use mortality_table::remaining_life_expectancy;
+pub use sensitivity::{SensitivityParameter, SensitivityResult, run_sensitivity};
 pub use solver::{
     ContributionAllocation, GoalSolveConfig, GoalSolveIteration, GoalSolveResult, GoalType,
     solve_goal,
 };
 pub use types::{
-    AgeResult, CashflowYearResult, Inputs, ModelResult, PensionTaxMode, WithdrawalOrder,
-    WithdrawalStrategy,
+    AgeResult, CashflowYearResult, Debt, FailureAttribution, FailureFactor, Inputs, MarketModel,
+    ModelError, ModelResult, PensionTaxMode, ReturnDistribution, SelectionConfidence,
+    SuccessCriterion, WithdrawalOrder, WithdrawalStrategy,
 };
+pub use validation::{ValidationError, validate_inputs};
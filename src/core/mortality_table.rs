@@ -0,0 +1,45 @@
+/// Remaining life expectancy in years by exact age (index = age), unisex average of the
+/// ONS national life tables for the UK, smoothed between five-year points.
+static REMAINING_LIFE_EXPECTANCY: [f64; 101] = [
+    80.60, 79.66, 78.72, 77.78, 76.84, 75.90, 74.90, 73.90, 72.90, 71.90, // ages 0-9
+    70.90, 69.92, 68.94, 67.96, 66.98, 66.00, 65.02, 64.04, 63.06, 62.08, // ages 10-19
+    61.10, 60.12, 59.14, 58.16, 57.18, 56.20, 55.22, 54.24, 53.26, 52.28, // ages 20-29
+    51.30, 50.32, 49.34, 48.36, 47.38, 46.40, 45.44, 44.48, 43.52, 42.56, // ages 30-39
+    41.60, 40.64, 39.68, 38.72, 37.76, 36.80, 35.88, 34.96, 34.04, 33.12, // ages 40-49
+    32.20, 31.28, 30.36, 29.44, 28.52, 27.60, 26.74, 25.88, 25.02, 24.16, // ages 50-59
+    23.30, 22.52, 21.74, 20.96, 20.18, 19.40, 18.68, 17.96, 17.24, 16.52, // ages 60-69
+    15.80, 15.12, 14.44, 13.76, 13.08, 12.40, 11.78, 11.16, 10.54, 9.92, // ages 70-79
+    9.30, 8.76, 8.22, 7.68, 7.14, 6.60, 6.18, 5.76, 5.34, 4.92, // ages 80-89
+    4.50, 4.20, 3.90, 3.60, 3.30, 3.00, 2.82, 2.64, 2.46, 2.28, // ages 90-99
+    2.10, // age 100
+];
+
+/// Shortest remaining life expectancy used, so the amortization divisor never reaches zero.
+const MIN_REMAINING_LIFE_EXPECTANCY: f64 = 1.0;
+
+/// Remaining life expectancy at `age`; ages past the end of the table use its last entry.
+pub fn remaining_life_expectancy(age: u32) -> f64 {
+    let idx = (age as usize).min(REMAINING_LIFE_EXPECTANCY.len() - 1);
+    REMAINING_LIFE_EXPECTANCY[idx].max(MIN_REMAINING_LIFE_EXPECTANCY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remaining_life_expectancy_falls_with_age() {
+        for age in 1..=100 {
+            assert!(remaining_life_expectancy(age) < remaining_life_expectancy(age - 1));
+        }
+    }
+
+    #[test]
+    fn remaining_life_expectancy_clamps_past_end_of_table() {
+        let last = remaining_life_expectancy(100);
+        assert_eq!(remaining_life_expectancy(101), last);
+        assert_eq!(remaining_life_expectancy(130), last);
+        assert_eq!(remaining_life_expectancy(u32::MAX), last);
+        assert!(last >= MIN_REMAINING_LIFE_EXPECTANCY);
+    }
+}
@@ -0,0 +1,203 @@
+use super::{Inputs, run_retirement_age_evaluation};
+
+/// An `Inputs` field `run_sensitivity` can perturb. Values are in `Inputs` units, so
+/// rates are fractions.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SensitivityParameter {
+    TargetAnnualIncome,
+    EssentialAnnualIncome,
+    IsaStart,
+    TaxableStart,
+    PensionStart,
+    IsaAnnualContribution,
+    TaxableAnnualContribution,
+    PensionAnnualContribution,
+    IsaReturnMean,
+    TaxableReturnMean,
+    PensionReturnMean,
+    IsaReturnVol,
+    TaxableReturnVol,
+    PensionReturnVol,
+    InflationMean,
+    IsaFeeRate,
+    TaxableFeeRate,
+    PensionFeeRate,
+    StatePensionAnnualIncome,
+}
+
+impl SensitivityParameter {
+    /// The `Inputs` field name.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::TargetAnnualIncome => "target_annual_income",
+            Self::EssentialAnnualIncome => "essential_annual_income",
+            Self::IsaStart => "isa_start",
+            Self::TaxableStart => "taxable_start",
+            Self::PensionStart => "pension_start",
+            Self::IsaAnnualContribution => "isa_annual_contribution",
+            Self::TaxableAnnualContribution => "taxable_annual_contribution",
+            Self::PensionAnnualContribution => "pension_annual_contribution",
+            Self::IsaReturnMean => "isa_return_mean",
+            Self::TaxableReturnMean => "taxable_return_mean",
+            Self::PensionReturnMean => "pension_return_mean",
+            Self::IsaReturnVol => "isa_return_vol",
+            Self::TaxableReturnVol => "taxable_return_vol",
+            Self::PensionReturnVol => "pension_return_vol",
+            Self::InflationMean => "inflation_mean",
+            Self::IsaFeeRate => "isa_fee_rate",
+            Self::TaxableFeeRate => "taxable_fee_rate",
+            Self::PensionFeeRate => "pension_fee_rate",
+            Self::StatePensionAnnualIncome => "state_pension_annual_income",
+        }
+    }
+
+    fn field(self, inputs: &mut Inputs) -> &mut f64 {
+        match self {
+            Self::TargetAnnualIncome => &mut inputs.target_annual_income,
+            Self::EssentialAnnualIncome => &mut inputs.essential_annual_income,
+            Self::IsaStart => &mut inputs.isa_start,
+            Self::TaxableStart => &mut inputs.taxable_start,
+            Self::PensionStart => &mut inputs.pension_start,
+            Self::IsaAnnualContribution => &mut inputs.isa_annual_contribution,
+            Self::TaxableAnnualContribution => &mut inputs.taxable_annual_contribution,
+            Self::PensionAnnualContribution => &mut inputs.pension_annual_contribution,
+            Self::IsaReturnMean => &mut inputs.isa_return_mean,
+            Self::TaxableReturnMean => &mut inputs.taxable_return_mean,
+            Self::PensionReturnMean => &mut inputs.pension_return_mean,
+            Self::IsaReturnVol => &mut inputs.isa_return_vol,
+            Self::TaxableReturnVol => &mut inputs.taxable_return_vol,
+            Self::PensionReturnVol => &mut inputs.pension_return_vol,
+            Self::InflationMean => &mut inputs.inflation_mean,
+            Self::IsaFeeRate => &mut inputs.isa_fee_rate,
+            Self::TaxableFeeRate => &mut inputs.taxable_fee_rate,
+            Self::PensionFeeRate => &mut inputs.pension_fee_rate,
+            Self::StatePensionAnnualIncome => &mut inputs.state_pension_annual_income,
+        }
+    }
+}
+
+/// One bar of a tornado chart: success at `retirement_age` with the parameter at its low
+/// and high value, everything else held at the base inputs.
+#[derive(Debug, Clone, Copy)]
+pub struct SensitivityResult {
+    pub parameter: SensitivityParameter,
+    pub low_value: f64,
+    pub high_value: f64,
+    pub low_success_rate: f64,
+    pub high_success_rate: f64,
+    /// `|high_success_rate - low_success_rate|`.
+    pub swing: f64,
+}
+
+/// One-at-a-time sensitivity at a fixed retirement age, largest swing first. Every run
+/// uses the same seed and age, so all of them see the same market draws and only the
+/// perturbed parameter differs. Perturbed values are not validated.
+pub fn run_sensitivity(
+    inputs: &Inputs,
+    retirement_age: u32,
+    parameters: &[(SensitivityParameter, f64, f64)],
+) -> Vec<SensitivityResult> {
+    let success_with = |parameter: SensitivityParameter, value: f64| {
+        let mut perturbed = inputs.clone();
+        *parameter.field(&mut perturbed) = value;
+        run_retirement_age_evaluation(&perturbed, retirement_age).success_rate
+    };
+
+    let mut results = parameters
+        .iter()
+        .map(|&(parameter, low_value, high_value)| {
+            let low_success_rate = success_with(parameter, low_value);
+            let high_success_rate = success_with(parameter, high_value);
+            SensitivityResult {
+                parameter,
+                low_value,
+                high_value,
+                low_success_rate,
+                high_success_rate,
+                swing: (high_success_rate - low_success_rate).abs(),
+            }
+        })
+        .collect::<Vec<_>>();
+    // Stable, so equal swings keep the caller's order.
+    results.sort_by(|a, b| b.swing.total_cmp(&a.swing));
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tornado_inputs() -> Inputs {
+        Inputs {
+            current_age: 40,
+            max_retirement_age: 55,
+            horizon_age: 90,
+            simulations: 200,
+            threads: 1,
+            ..Inputs::default()
+        }
+    }
+
+    #[test]
+    fn run_sensitivity_orders_the_largest_swing_first() {
+        let inputs = tornado_inputs();
+        let results = run_sensitivity(
+            &inputs,
+            50,
+            &[
+                (SensitivityParameter::PensionFeeRate, 0.0040, 0.0041),
+                (
+                    SensitivityParameter::TargetAnnualIncome,
+                    10_000.0,
+                    100_000.0,
+                ),
+                (SensitivityParameter::IsaReturnMean, 0.02, 0.08),
+            ],
+        );
+
+        let order: Vec<_> = results.iter().map(|r| r.parameter).collect();
+        assert_eq!(
+            order,
+            [
+                SensitivityParameter::TargetAnnualIncome,
+                SensitivityParameter::IsaReturnMean,
+                SensitivityParameter::PensionFeeRate,
+            ]
+        );
+        assert!(
+            results
+                .windows(2)
+                .all(|pair| pair[0].swing >= pair[1].swing)
+        );
+
+        let income = &results[0];
+        assert_eq!(income.low_value, 10_000.0);
+        assert!(income.low_success_rate > income.high_success_rate);
+        assert_eq!(
+            income.swing,
+            income.low_success_rate - income.high_success_rate
+        );
+        assert!(results[1].high_success_rate >= results[1].low_success_rate);
+    }
+
+    #[test]
+    fn run_sensitivity_leaves_the_base_inputs_alone() {
+        let inputs = tornado_inputs();
+        let base = run_retirement_age_evaluation(&inputs, 50).success_rate;
+        let results = run_sensitivity(
+            &inputs,
+            50,
+            &[(
+                SensitivityParameter::TargetAnnualIncome,
+                inputs.target_annual_income,
+                inputs.target_annual_income,
+            )],
+        );
+        assert_eq!(results[0].low_success_rate, base);
+        assert_eq!(results[0].swing, 0.0);
+        assert_eq!(
+            SensitivityParameter::TargetAnnualIncome.name(),
+            "target_annual_income"
+        );
+    }
+}
@@ -1,22 +1,48 @@
-use super::{Inputs, run_retirement_age_evaluation};
+use super::{Inputs, binomial_ci_half_width, run_retirement_age_evaluation};
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum GoalType {
     RequiredContribution,
     MaxIncome,
+    /// Earliest retirement age (integer scan) meeting the success target with the current
+    /// plan. Search bounds and tolerance are ignored; `solved_value` holds the age.
+    EarliestRetirementAge,
+    /// Highest income that meets the success target and keeps `cvar5_min_income_ratio`
+    /// (mean of the worst 5% of lowest-year income ratios) at or above
+    /// `min_cvar5_income_ratio`. A zero success target leaves only the tail constraint.
+    MaxIncomeCvarConstrained,
+    /// Best split of today's total ISA + taxable + pension contribution across the three
+    /// wrappers, searched on a grid of `SPLIT_GRID_STEPS` shares. Search bounds and
+    /// tolerance are ignored; `solved_value` holds the budget and `solved_contributions`
+    /// the split.
+    OptimalContributionSplit,
 }
 
+/// Grid resolution of `OptimalContributionSplit`: shares move in steps of 1/10 of the
+/// budget, 66 candidate splits in all.
+const SPLIT_GRID_STEPS: u32 = 10;
+
+/// Consecutive secant steps allowed before a bisection step is forced, so a noisy
+/// success curve cannot stall the bracket with a run of poor proposals.
+const SECANT_STEP_CAP: u32 = 3;
+
 #[derive(Debug, Clone, Copy)]
 pub struct GoalSolveConfig {
     pub goal_type: GoalType,
     pub target_retirement_age: u32,
     pub target_success_threshold: f64,
+    /// Floor on `cvar5_min_income_ratio` for `MaxIncomeCvarConstrained`; other goals
+    /// ignore it.
+    pub min_cvar5_income_ratio: f64,
     pub search_min: f64,
     pub search_max: f64,
     pub tolerance: f64,
     pub max_iterations: u32,
     pub simulations_per_iteration: u32,
     pub final_simulations: u32,
+    /// Propose bracketed candidates with secant steps through the two latest evaluations
+    /// instead of always bisecting. Bisection remains the fallback.
+    pub secant_acceleration: bool,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -27,6 +53,7 @@ pub struct GoalSolveIteration {
     pub candidate_value: f64,
     pub success_rate: f64,
     pub success_ci_half_width: f64,
+    pub cvar5_min_income_ratio: f64,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -41,6 +68,7 @@ pub struct GoalSolveResult {
     pub goal_type: GoalType,
     pub target_retirement_age: u32,
     pub target_success_threshold: f64,
+    pub min_cvar5_income_ratio: f64,
     pub search_min: f64,
     pub search_max: f64,
     pub tolerance: f64,
@@ -51,6 +79,7 @@ pub struct GoalSolveResult {
     pub solved_contributions: Option<ContributionAllocation>,
     pub achieved_success_rate: Option<f64>,
     pub achieved_success_ci_half_width: Option<f64>,
+    pub achieved_cvar5_min_income_ratio: Option<f64>,
     pub iterations: Vec<GoalSolveIteration>,
     pub converged: bool,
     pub feasible: bool,
@@ -101,9 +130,16 @@ impl ContributionMix {
 pub fn solve_goal(inputs: &Inputs, config: GoalSolveConfig) -> Result<GoalSolveResult, String> {
     validate_config(inputs, config)?;
 
+    if config.goal_type == GoalType::EarliestRetirementAge {
+        return Ok(solve_earliest_retirement_age(inputs, config));
+    }
+    if config.goal_type == GoalType::OptimalContributionSplit {
+        return Ok(solve_optimal_contribution_split(inputs, config));
+    }
+
     let mix = ContributionMix::from_inputs(inputs);
 
-    let mut iterations = Vec::with_capacity(config.max_iterations as usize);
+    let mut iterations = Vec::new();
     let low_eval = evaluate_candidate(inputs, config, config.search_min, mix);
     let high_eval = evaluate_candidate(inputs, config, config.search_max, mix);
 
@@ -114,46 +150,24 @@ pub fn solve_goal(inputs: &Inputs, config: GoalSolveConfig) -> Result<GoalSolveR
 
     match config.goal_type {
         GoalType::RequiredContribution => {
-            if low_eval.success_rate + 1e-12 >= config.target_success_threshold {
+            if low_eval.meets_target(config) {
                 solved_value = Some(config.search_min);
                 converged = true;
                 feasible = true;
                 message = "Already meets target at lower contribution bound.".to_string();
-            } else if high_eval.success_rate + 1e-12 < config.target_success_threshold {
+            } else if !high_eval.meets_target(config) {
                 feasible = false;
                 message = "No feasible contribution found within the search bounds.".to_string();
             } else {
-                let mut lo = config.search_min;
-                let mut hi = config.search_max;
-                let mut it = 0;
-                while it < config.max_iterations {
-                    it += 1;
-                    let mid = (lo + hi) * 0.5;
-                    let eval = evaluate_candidate(inputs, config, mid, mix);
-                    iterations.push(GoalSolveIteration {
-                        iteration: it,
-                        lower_bound: lo,
-                        upper_bound: hi,
-                        candidate_value: mid,
-                        success_rate: eval.success_rate,
-                        success_ci_half_width: eval.success_ci_half_width,
-                    });
-
-                    if eval.success_rate + 1e-12 >= config.target_success_threshold {
-                        hi = mid;
-                    } else {
-                        lo = mid;
-                    }
-
-                    if (hi - lo).abs() <= config.tolerance {
-                        converged = true;
-                        solved_value = Some(hi);
-                        break;
-                    }
-                }
-                if solved_value.is_none() {
-                    solved_value = Some(hi);
-                }
+                let search = search_bracket(
+                    config,
+                    (config.search_max, high_eval),
+                    (config.search_min, low_eval),
+                    |value| evaluate_candidate(inputs, config, value, mix),
+                );
+                converged = search.converged;
+                solved_value = Some(search.value);
+                iterations = search.iterations;
                 feasible = true;
                 message = if converged {
                     "Solved required contribution.".to_string()
@@ -163,11 +177,15 @@ pub fn solve_goal(inputs: &Inputs, config: GoalSolveConfig) -> Result<GoalSolveR
                 };
             }
         }
-        GoalType::MaxIncome => {
-            if low_eval.success_rate + 1e-12 < config.target_success_threshold {
+        GoalType::EarliestRetirementAge => unreachable!("handled by solve_earliest_retirement_age"),
+        GoalType::OptimalContributionSplit => {
+            unreachable!("handled by solve_optimal_contribution_split")
+        }
+        GoalType::MaxIncome | GoalType::MaxIncomeCvarConstrained => {
+            if !low_eval.meets_target(config) {
                 feasible = false;
                 message = "No feasible income found within the search bounds.".to_string();
-            } else if high_eval.success_rate + 1e-12 >= config.target_success_threshold {
+            } else if high_eval.meets_target(config) {
                 solved_value = Some(config.search_max);
                 converged = true;
                 feasible = true;
@@ -175,37 +193,15 @@ pub fn solve_goal(inputs: &Inputs, config: GoalSolveConfig) -> Result<GoalSolveR
                     "Upper income bound is still feasible; increase search max for higher target."
                         .to_string();
             } else {
-                let mut lo = config.search_min;
-                let mut hi = config.search_max;
-                let mut it = 0;
-                while it < config.max_iterations {
-                    it += 1;
-                    let mid = (lo + hi) * 0.5;
-                    let eval = evaluate_candidate(inputs, config, mid, mix);
-                    iterations.push(GoalSolveIteration {
-                        iteration: it,
-                        lower_bound: lo,
-                        upper_bound: hi,
-                        candidate_value: mid,
-                        success_rate: eval.success_rate,
-                        success_ci_half_width: eval.success_ci_half_width,
-                    });
-
-                    if eval.success_rate + 1e-12 >= config.target_success_threshold {
-                        lo = mid;
-                    } else {
-                        hi = mid;
-                    }
-
-                    if (hi - lo).abs() <= config.tolerance {
-                        converged = true;
-                        solved_value = Some(lo);
-                        break;
-                    }
-                }
-                if solved_value.is_none() {
-                    solved_value = Some(lo);
-                }
+                let search = search_bracket(
+                    config,
+                    (config.search_min, low_eval),
+                    (config.search_max, high_eval),
+                    |value| evaluate_candidate(inputs, config, value, mix),
+                );
+                converged = search.converged;
+                solved_value = Some(search.value);
+                iterations = search.iterations;
                 feasible = true;
                 message = if converged {
                     "Solved maximum sustainable income.".to_string()
@@ -219,6 +215,7 @@ pub fn solve_goal(inputs: &Inputs, config: GoalSolveConfig) -> Result<GoalSolveR
 
     let mut achieved_success_rate = None;
     let mut achieved_success_ci_half_width = None;
+    let mut achieved_cvar5_min_income_ratio = None;
     let mut solved_contributions = None;
     if let Some(value) = solved_value {
         let final_eval_with_samples = evaluate_candidate(
@@ -232,6 +229,7 @@ pub fn solve_goal(inputs: &Inputs, config: GoalSolveConfig) -> Result<GoalSolveR
         );
         achieved_success_rate = Some(final_eval_with_samples.success_rate);
         achieved_success_ci_half_width = Some(final_eval_with_samples.success_ci_half_width);
+        achieved_cvar5_min_income_ratio = Some(final_eval_with_samples.cvar5_min_income_ratio);
         if config.goal_type == GoalType::RequiredContribution {
             solved_contributions = Some(mix.allocation_for_total(value));
         }
@@ -241,6 +239,7 @@ pub fn solve_goal(inputs: &Inputs, config: GoalSolveConfig) -> Result<GoalSolveR
         goal_type: config.goal_type,
         target_retirement_age: config.target_retirement_age,
         target_success_threshold: config.target_success_threshold,
+        min_cvar5_income_ratio: config.min_cvar5_income_ratio,
         search_min: config.search_min,
         search_max: config.search_max,
         tolerance: config.tolerance,
@@ -251,6 +250,7 @@ pub fn solve_goal(inputs: &Inputs, config: GoalSolveConfig) -> Result<GoalSolveR
         solved_contributions,
         achieved_success_rate,
         achieved_success_ci_half_width,
+        achieved_cvar5_min_income_ratio,
         iterations,
         converged,
         feasible,
@@ -258,10 +258,250 @@ pub fn solve_goal(inputs: &Inputs, config: GoalSolveConfig) -> Result<GoalSolveR
     })
 }
 
+/// Ages are integers and success rises (noisily) with age, so a linear scan from
+/// `current_age` that stops at the first feasible age replaces bisection. Every age is
+/// evaluated at `final_simulations`, so the achieved rate is the scan's own estimate.
+fn solve_earliest_retirement_age(inputs: &Inputs, config: GoalSolveConfig) -> GoalSolveResult {
+    let mut scan_inputs = inputs.clone();
+    scan_inputs.simulations = config.final_simulations.max(1);
+
+    let first_age = inputs.current_age;
+    let last_age = inputs
+        .max_retirement_age
+        .min(inputs.horizon_age.saturating_sub(1));
+    let mut iterations = Vec::new();
+    let mut solved = None;
+    for (idx, age) in (first_age..=last_age).enumerate() {
+        let result = run_retirement_age_evaluation(&scan_inputs, age);
+        iterations.push(GoalSolveIteration {
+            iteration: idx as u32 + 1,
+            lower_bound: first_age as f64,
+            upper_bound: last_age as f64,
+            candidate_value: age as f64,
+            success_rate: result.success_rate,
+            success_ci_half_width: result.success_ci_half_width,
+            cvar5_min_income_ratio: result.cvar5_min_income_ratio,
+        });
+        if result.success_rate + 1e-12 >= config.target_success_threshold {
+            solved = Some((age, result.success_rate, result.success_ci_half_width));
+            break;
+        }
+    }
+
+    let feasible = solved.is_some();
+    let message = match solved {
+        Some((age, _, _)) => format!("Earliest feasible retirement age is {age}."),
+        None => format!("No retirement age up to {last_age} meets the success target."),
+    };
+    GoalSolveResult {
+        goal_type: config.goal_type,
+        target_retirement_age: config.target_retirement_age,
+        target_success_threshold: config.target_success_threshold,
+        min_cvar5_income_ratio: config.min_cvar5_income_ratio,
+        search_min: first_age as f64,
+        search_max: last_age as f64,
+        tolerance: config.tolerance,
+        max_iterations: config.max_iterations,
+        simulations_per_iteration: config.final_simulations,
+        final_simulations: config.final_simulations,
+        solved_value: solved.map(|(age, _, _)| age as f64),
+        solved_contributions: None,
+        achieved_success_rate: solved.map(|(_, rate, _)| rate),
+        achieved_success_ci_half_width: solved.map(|(_, _, ci)| ci),
+        achieved_cvar5_min_income_ratio: None,
+        iterations,
+        converged: feasible,
+        feasible,
+        message,
+    }
+}
+
+/// Scores every split of the current contribution total on the share grid at the target
+/// age and keeps the highest success rate; ties go to the larger median terminal pot.
+/// Each iteration logs one split, with its pension amount as `candidate_value`.
+fn solve_optimal_contribution_split(inputs: &Inputs, config: GoalSolveConfig) -> GoalSolveResult {
+    let budget = ContributionMix::from_inputs(inputs).total;
+    let step = budget / SPLIT_GRID_STEPS as f64;
+    let evaluate = |allocation: ContributionAllocation, simulations: u32| {
+        let mut split_inputs = inputs.clone();
+        split_inputs.simulations = simulations.max(1);
+        split_inputs.isa_annual_contribution = allocation.isa;
+        split_inputs.taxable_annual_contribution = allocation.taxable;
+        split_inputs.pension_annual_contribution = allocation.pension;
+        run_retirement_age_evaluation(&split_inputs, config.target_retirement_age)
+    };
+
+    let mut iterations = Vec::new();
+    let mut best: Option<(ContributionAllocation, f64, f64)> = None;
+    for pension_steps in 0..=SPLIT_GRID_STEPS {
+        for isa_steps in 0..=(SPLIT_GRID_STEPS - pension_steps) {
+            let taxable_steps = SPLIT_GRID_STEPS - pension_steps - isa_steps;
+            let allocation = ContributionAllocation {
+                isa: step * isa_steps as f64,
+                taxable: step * taxable_steps as f64,
+                pension: step * pension_steps as f64,
+            };
+            let result = evaluate(allocation, config.simulations_per_iteration);
+            iterations.push(GoalSolveIteration {
+                iteration: iterations.len() as u32 + 1,
+                lower_bound: 0.0,
+                upper_bound: budget,
+                candidate_value: allocation.pension,
+                success_rate: result.success_rate,
+                success_ci_half_width: result.success_ci_half_width,
+                cvar5_min_income_ratio: result.cvar5_min_income_ratio,
+            });
+            let score = (result.success_rate, result.median_terminal_pot);
+            if best.is_none_or(|(_, rate, pot)| score > (rate, pot)) {
+                best = Some((allocation, score.0, score.1));
+            }
+        }
+    }
+
+    let (allocation, _, _) = best.expect("the share grid is never empty");
+    let final_eval = evaluate(allocation, config.final_simulations);
+    let meets_target = final_eval.success_rate + 1e-12 >= config.target_success_threshold;
+    GoalSolveResult {
+        goal_type: config.goal_type,
+        target_retirement_age: config.target_retirement_age,
+        target_success_threshold: config.target_success_threshold,
+        min_cvar5_income_ratio: config.min_cvar5_income_ratio,
+        search_min: 0.0,
+        search_max: budget,
+        tolerance: step,
+        max_iterations: iterations.len() as u32,
+        simulations_per_iteration: config.simulations_per_iteration,
+        final_simulations: config.final_simulations,
+        solved_value: Some(budget),
+        solved_contributions: Some(allocation),
+        achieved_success_rate: Some(final_eval.success_rate),
+        achieved_success_ci_half_width: Some(final_eval.success_ci_half_width),
+        achieved_cvar5_min_income_ratio: Some(final_eval.cvar5_min_income_ratio),
+        iterations,
+        converged: meets_target,
+        feasible: meets_target,
+        message: if meets_target {
+            "Found the best contribution split; it meets the success target.".to_string()
+        } else {
+            "Found the best contribution split, but it falls short of the success target."
+                .to_string()
+        },
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 struct CandidateEval {
     success_rate: f64,
     success_ci_half_width: f64,
+    cvar5_min_income_ratio: f64,
+}
+
+impl CandidateEval {
+    /// Margin over the target; non-negative (up to rounding) when the candidate meets
+    /// it. For the CVaR goal this is the tighter of the two constraints.
+    fn residual(self, config: GoalSolveConfig) -> f64 {
+        let success_margin = self.success_rate - config.target_success_threshold;
+        match config.goal_type {
+            GoalType::MaxIncomeCvarConstrained => {
+                success_margin.min(self.cvar5_min_income_ratio - config.min_cvar5_income_ratio)
+            }
+            GoalType::RequiredContribution | GoalType::MaxIncome => success_margin,
+            GoalType::EarliestRetirementAge | GoalType::OptimalContributionSplit => {
+                unreachable!("scanned goals are not bracketed")
+            }
+        }
+    }
+
+    fn meets_target(self, config: GoalSolveConfig) -> bool {
+        self.residual(config) + 1e-12 >= 0.0
+    }
+}
+
+struct BracketSearch {
+    /// The feasible end of the final bracket.
+    value: f64,
+    converged: bool,
+    iterations: Vec<GoalSolveIteration>,
+}
+
+/// Shrinks a bracket whose `feasible` end meets the target and whose `infeasible` end
+/// does not, until it is narrower than the tolerance. Each candidate is the midpoint,
+/// or with `secant_acceleration` the secant root through the two latest evaluations.
+/// A secant root outside the bracket, a flat secant or `SECANT_STEP_CAP` secant steps
+/// in a row fall back to bisection. Secant candidates are kept half a tolerance inside
+/// the bracket, so a step that lands just short of the root pushes the next one across.
+fn search_bracket(
+    config: GoalSolveConfig,
+    feasible: (f64, CandidateEval),
+    infeasible: (f64, CandidateEval),
+    mut evaluate: impl FnMut(f64) -> CandidateEval,
+) -> BracketSearch {
+    let mut feasible_end = feasible.0;
+    let mut infeasible_end = infeasible.0;
+    let mut previous = (infeasible.0, infeasible.1.residual(config));
+    let mut latest = (feasible.0, feasible.1.residual(config));
+    let mut secant_run = 0;
+    let mut iterations = Vec::with_capacity(config.max_iterations as usize);
+    let mut converged = false;
+
+    while (iterations.len() as u32) < config.max_iterations {
+        let lo = feasible_end.min(infeasible_end);
+        let hi = feasible_end.max(infeasible_end);
+        let secant = if config.secant_acceleration && secant_run < SECANT_STEP_CAP {
+            secant_root(previous, latest).filter(|root| *root > lo && *root < hi)
+        } else {
+            None
+        };
+        let candidate = match secant {
+            Some(root) => {
+                secant_run += 1;
+                let margin = (config.tolerance * 0.5).min((hi - lo) * 0.5);
+                root.clamp(lo + margin, hi - margin)
+            }
+            None => {
+                secant_run = 0;
+                (lo + hi) * 0.5
+            }
+        };
+
+        let eval = evaluate(candidate);
+        iterations.push(GoalSolveIteration {
+            iteration: iterations.len() as u32 + 1,
+            lower_bound: lo,
+            upper_bound: hi,
+            candidate_value: candidate,
+            success_rate: eval.success_rate,
+            success_ci_half_width: eval.success_ci_half_width,
+            cvar5_min_income_ratio: eval.cvar5_min_income_ratio,
+        });
+
+        if eval.meets_target(config) {
+            feasible_end = candidate;
+        } else {
+            infeasible_end = candidate;
+        }
+        previous = latest;
+        latest = (candidate, eval.residual(config));
+
+        if (feasible_end - infeasible_end).abs() <= config.tolerance {
+            converged = true;
+            break;
+        }
+    }
+
+    BracketSearch {
+        value: feasible_end,
+        converged,
+        iterations,
+    }
+}
+
+fn secant_root((x0, f0): (f64, f64), (x1, f1): (f64, f64)) -> Option<f64> {
+    let slope = (f1 - f0) / (x1 - x0);
+    if !slope.is_finite() || slope.abs() <= 1e-12 {
+        return None;
+    }
+    Some(x1 - f1 / slope).filter(|root| root.is_finite())
 }
 
 fn evaluate_candidate(
@@ -280,36 +520,46 @@ fn evaluate_candidate(
             inputs.taxable_annual_contribution = allocation.taxable;
             inputs.pension_annual_contribution = allocation.pension;
         }
-        GoalType::MaxIncome => {
+        GoalType::MaxIncome | GoalType::MaxIncomeCvarConstrained => {
             inputs.target_annual_income = candidate_value.max(0.0);
         }
+        GoalType::EarliestRetirementAge | GoalType::OptimalContributionSplit => {
+            unreachable!("scanned goals evaluate their own candidates")
+        }
     }
 
     let age = run_retirement_age_evaluation(&inputs, config.target_retirement_age);
     CandidateEval {
         success_rate: age.success_rate,
         success_ci_half_width: binomial_ci_half_width(age.success_rate, inputs.simulations),
+        cvar5_min_income_ratio: age.cvar5_min_income_ratio,
     }
 }
 
-fn binomial_ci_half_width(p: f64, n: u32) -> f64 {
-    if n == 0 {
-        return 0.0;
-    }
-    let p = p.clamp(0.0, 1.0);
-    1.96 * (p * (1.0 - p) / n as f64).sqrt()
-}
-
 fn validate_config(inputs: &Inputs, config: GoalSolveConfig) -> Result<(), String> {
-    if config.target_retirement_age < inputs.current_age {
-        return Err("target_retirement_age must be >= current_age".to_string());
-    }
-    if config.target_retirement_age >= inputs.horizon_age {
-        return Err("target_retirement_age must be < horizon_age".to_string());
+    // The earliest-age goal scans its own ages and never reads `target_retirement_age`.
+    if config.goal_type != GoalType::EarliestRetirementAge {
+        if config.target_retirement_age < inputs.current_age {
+            return Err("target_retirement_age must be >= current_age".to_string());
+        }
+        if config.target_retirement_age >= inputs.horizon_age {
+            return Err("target_retirement_age must be < horizon_age".to_string());
+        }
     }
     if !(0.0..=1.0).contains(&config.target_success_threshold) {
         return Err("target_success_threshold must be between 0 and 1".to_string());
     }
+    if config.goal_type == GoalType::OptimalContributionSplit
+        && ContributionMix::from_inputs(inputs).total <= 0.0
+    {
+        return Err(
+            "optimal contribution split needs a positive ISA + taxable + pension contribution"
+                .to_string(),
+        );
+    }
+    if !config.min_cvar5_income_ratio.is_finite() || config.min_cvar5_income_ratio < 0.0 {
+        return Err("min_cvar5_income_ratio must be >= 0".to_string());
+    }
     if !config.search_min.is_finite() || !config.search_max.is_finite() {
         return Err("search bounds must be finite".to_string());
     }
@@ -334,7 +584,10 @@ fn validate_config(inputs: &Inputs, config: GoalSolveConfig) -> Result<(), Strin
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::{PensionTaxMode, WithdrawalOrder, WithdrawalStrategy};
+    use crate::core::{
+        MarketModel, PensionTaxMode, ReturnDistribution, SelectionConfidence, SuccessCriterion,
+        WithdrawalOrder, WithdrawalStrategy,
+    };
 
     fn assert_close(actual: f64, expected: f64, tol: f64) {
         assert!(
@@ -351,13 +604,28 @@ mod tests {
             taxable_start: 0.0,
             taxable_cost_basis_start: 0.0,
             pension_start: 0.0,
+            household: false,
+            partner_pension_start: 0.0,
+            partner_pension_access_age: 57,
+            partner_personal_allowance: 12_570.0,
+            marriage_allowance_transfer: 0.0,
             cash_start: 0.0,
             bond_ladder_start: 0.0,
             isa_annual_contribution: 1.0,
             isa_annual_contribution_limit: 20_000.0,
+            lisa_start: 0.0,
+            lisa_annual_contribution: 0.0,
+            lisa_access_age: 60,
             taxable_annual_contribution: 0.0,
             pension_annual_contribution: 0.0,
+            employer_match_rate: 0.0,
+            employer_match_cap: None,
+            pension_annual_allowance: 60_000.0,
+            pension_overflow_to_taxable: true,
             contribution_growth_rate: 0.0,
+            contributions_track_inflation: false,
+            coast_contribution_fraction: 0.0,
+            pre_retirement_earnings: 0.0,
             isa_return_mean: 0.0,
             isa_return_vol: 0.0,
             taxable_return_mean: 0.0,
@@ -365,11 +633,31 @@ mod tests {
             pension_return_mean: 0.0,
             pension_return_vol: 0.0,
             return_correlation: 0.0,
-            capital_gains_tax_rate: 0.0,
+            isa_taxable_correlation: 1.0,
+            taxable_pension_correlation: None,
+            return_factor_betas: None,
+            return_distribution: ReturnDistribution::Normal,
+            market_model: MarketModel::Parametric,
+            bootstrap_block_years: 1,
+            market_burn_in_years: 0,
+            cgt_basic_rate: 0.0,
+            cgt_higher_rate: 0.0,
             capital_gains_allowance: 0.0,
             taxable_return_tax_drag: 0.0,
+            taxable_dividend_yield: 0.0,
+            dividend_allowance: 500.0,
+            dividend_basic_rate: 0.0875,
+            dividend_higher_rate: 0.3375,
+            dividend_additional_rate: 0.3935,
+            isa_fee_rate: 0.0,
+            taxable_fee_rate: 0.0,
+            pension_fee_rate: 0.0,
+            glide_start_equity: 1.0,
+            glide_end_equity: 1.0,
+            glide_end_age: None,
             pension_tax_mode: PensionTaxMode::FlatRate,
             pension_flat_tax_rate: 0.0,
+            pension_tax_free_pct: 0.0,
             uk_personal_allowance: 12_570.0,
             uk_basic_rate_limit: 50_270.0,
             uk_higher_rate_limit: 125_140.0,
@@ -378,35 +666,107 @@ mod tests {
             uk_additional_rate: 0.45,
             uk_allowance_taper_start: 100_000.0,
             uk_allowance_taper_end: 125_140.0,
+            thresholds_frozen_until_age: None,
+            scottish_starter_rate_limit: 15_397.0,
+            scottish_basic_rate_limit: 27_491.0,
+            scottish_intermediate_rate_limit: 43_662.0,
+            scottish_higher_rate_limit: 75_000.0,
+            scottish_advanced_rate_limit: 125_140.0,
+            scottish_starter_rate: 0.19,
+            scottish_basic_rate: 0.20,
+            scottish_intermediate_rate: 0.21,
+            scottish_higher_rate: 0.42,
+            scottish_advanced_rate: 0.45,
+            scottish_top_rate: 0.48,
             state_pension_start_age: 200,
             state_pension_annual_income: 0.0,
+            state_pension_real_growth: 0.0,
+            state_pension_growth_before_start: false,
+            state_pension_deferral_years: 0,
+            state_pension_deferral_uplift_rate: 0.058,
+            db_pension_start_age: 200,
+            db_pension_annual_income: 0.0,
+            annuity_purchase_age: None,
+            annuity_purchase_amount: 0.0,
+            annuity_rate: 0.0,
+            conversion_ladder_annual_amount: 0.0,
+            conversion_ladder_start_age: 0,
+            conversion_ladder_end_age: 0,
+            rental_income_start_age: 0,
+            rental_annual_income: 0.0,
+            rental_growth_rate: 0.0,
+            part_time_annual_income: 0.0,
+            part_time_start_age: 0,
+            part_time_end_age: 0,
             inflation_mean: 0.0,
             inflation_vol: 0.0,
+            inflation_return_correlation: 0.0,
+            return_clamp_min: -0.95,
+            return_clamp_max: 2.5,
+            inflation_clamp_min: -0.03,
+            inflation_clamp_max: 0.20,
             target_annual_income: 100.0,
+            essential_annual_income: 0.0,
+            spending_decline_rate: 0.0,
+            care_cost_age: 85,
+            care_cost_uplift: 0.0,
+            active_phase_end_age: 75,
+            active_phase_uplift: 0.0,
+            late_phase_start_age: 85,
+            late_phase_uplift: 0.0,
+            debts: Vec::new(),
             mortgage_annual_payment: 0.0,
             mortgage_end_age: None,
+            mortgage_balance: 0.0,
+            mortgage_rate: 0.05,
+            mortgage_term_years: 25,
+            mortgage_overpayments: Vec::new(),
+            lump_expenses: Vec::new(),
             max_retirement_age: 31,
             horizon_age: 32,
+            bequest_target: 0.0,
+            success_criterion: SuccessCriterion::EssentialFloorMet,
             simulations: 1,
+            threads: 1,
             success_threshold: 1.0,
+            selection_confidence: SelectionConfidence::PointEstimate,
+            risk_aversion: None,
+            utility_discount_rate: 0.0,
+            utility_spending_floor: 1_000.0,
             seed: 7,
+            age_invariant_draws: false,
+            stratified_inflation: false,
             bad_year_threshold: -1.0,
             good_year_threshold: 1.0,
             bad_year_cut: 0.0,
             good_year_raise: 0.0,
             min_income_floor: 1.0,
             max_income_ceiling: 1.0,
+            min_real_spending_floor: 0.0,
             withdrawal_strategy: WithdrawalStrategy::Guardrails,
             gk_lower_guardrail: 0.8,
             gk_upper_guardrail: 1.2,
+            gk_adjustment: 0.10,
             vpw_expected_real_return: 0.03,
+            constant_withdrawal_rate: 0.04,
+            cape_a: 0.0175,
+            cape_b: 0.5,
+            cape_start: 20.0,
+            cape_long_run_mean: 20.0,
+            cape_reversion_speed: 0.10,
             floor_upside_capture: 0.5,
             bucket_target_years: 2.0,
             good_year_extra_buffer_withdrawal: 0.0,
+            min_cash_months: 0.0,
             cash_growth_rate: 0.0,
             bond_ladder_yield: 0.0,
+            bond_ladder_yield_vol: 0.0,
+            bond_ladder_inflation_correlation: 0.0,
             bond_ladder_years: 0,
+            bond_ladder_reinvest: false,
+            bond_ladder_target_at_retirement: 0.0,
             post_access_withdrawal_order: WithdrawalOrder::IsaFirst,
+            pre_access_withdrawal_order: None,
         }
     }
 
@@ -417,12 +777,14 @@ mod tests {
             goal_type: GoalType::RequiredContribution,
             target_retirement_age: 31,
             target_success_threshold: 1.0,
+            min_cvar5_income_ratio: 0.0,
             search_min: 0.0,
             search_max: 200.0,
             tolerance: 0.5,
             max_iterations: 24,
             simulations_per_iteration: 1,
             final_simulations: 1,
+            secant_acceleration: false,
         };
 
         let result = solve_goal(&inputs, config).expect("must solve");
@@ -452,12 +814,14 @@ mod tests {
             goal_type: GoalType::MaxIncome,
             target_retirement_age: 30,
             target_success_threshold: 1.0,
+            min_cvar5_income_ratio: 0.0,
             search_min: 0.0,
             search_max: 600.0,
             tolerance: 0.5,
             max_iterations: 24,
             simulations_per_iteration: 1,
             final_simulations: 1,
+            secant_acceleration: false,
         };
 
         let result = solve_goal(&inputs, config).expect("must solve");
@@ -470,6 +834,150 @@ mod tests {
         );
     }
 
+    #[test]
+    fn secant_steps_converge_faster_than_bisection_on_a_smooth_curve() {
+        // A logistic success curve rising with the contribution; 90% is reached at
+        // 5,000 + 1,000 ln 9.
+        let eval_at = |value: f64| CandidateEval {
+            success_rate: 1.0 / (1.0 + (-(value - 5_000.0) / 1_000.0).exp()),
+            success_ci_half_width: 0.0,
+            cvar5_min_income_ratio: 0.0,
+        };
+        let search = |secant_acceleration: bool| {
+            let config = GoalSolveConfig {
+                goal_type: GoalType::RequiredContribution,
+                target_retirement_age: 40,
+                target_success_threshold: 0.9,
+                min_cvar5_income_ratio: 0.0,
+                search_min: 0.0,
+                search_max: 20_000.0,
+                tolerance: 1.0,
+                max_iterations: 64,
+                simulations_per_iteration: 1,
+                final_simulations: 1,
+                secant_acceleration,
+            };
+            search_bracket(
+                config,
+                (config.search_max, eval_at(config.search_max)),
+                (config.search_min, eval_at(config.search_min)),
+                eval_at,
+            )
+        };
+
+        let root = 5_000.0 + 1_000.0 * 9.0_f64.ln();
+        let bisection = search(false);
+        let secant = search(true);
+        assert!(bisection.converged && secant.converged);
+        for result in [&bisection, &secant] {
+            assert!(result.value >= root && result.value <= root + 1.0);
+            assert!(result.iterations.iter().all(|row| {
+                row.candidate_value > row.lower_bound && row.candidate_value < row.upper_bound
+            }));
+        }
+        assert_eq!(bisection.iterations.len(), 15);
+        assert!(
+            secant.iterations.len() < bisection.iterations.len(),
+            "secant took {} iterations",
+            secant.iterations.len()
+        );
+    }
+
+    #[test]
+    fn employer_match_makes_the_pension_heavy_split_win() {
+        let mut inputs = deterministic_inputs();
+        inputs.max_retirement_age = 40;
+        inputs.horizon_age = 50;
+        inputs.isa_annual_contribution = 500.0;
+        inputs.taxable_annual_contribution = 500.0;
+        inputs.employer_match_rate = 0.5;
+        inputs.target_annual_income = 1_400.0;
+
+        let config = GoalSolveConfig {
+            goal_type: GoalType::OptimalContributionSplit,
+            target_retirement_age: 40,
+            target_success_threshold: 1.0,
+            min_cvar5_income_ratio: 0.0,
+            search_min: 0.0,
+            search_max: 1.0,
+            tolerance: 0.01,
+            max_iterations: 1,
+            simulations_per_iteration: 1,
+            final_simulations: 1,
+            secant_acceleration: false,
+        };
+        let result = solve_goal(&inputs, config).expect("must solve");
+
+        // Ten years of 1,000 a year funds ten years of 1,000 from the ISA, but the
+        // 50% match turns pension money into 1,500. Only splits that are at least 80%
+        // pension reach 1,400 a year, and the all-pension split leaves the most over.
+        let split = result.solved_contributions.expect("split expected");
+        assert_close(split.pension, 1_000.0, 1e-9);
+        assert_close(split.isa + split.taxable, 0.0, 1e-9);
+        assert_eq!(result.solved_value, Some(1_000.0));
+        assert_eq!(result.iterations.len(), 66);
+        assert!(result.achieved_success_rate.expect("final evaluation") >= 1.0 - 1e-12);
+        assert!(result.feasible && result.converged);
+        assert!(
+            result
+                .iterations
+                .iter()
+                .filter(|iteration| iteration.candidate_value < 800.0 - 1e-9)
+                .all(|iteration| iteration.success_rate < 1.0)
+        );
+
+        // Even the all-pension split funds only 1,500 a year, so the best split is
+        // still reported but flagged as missing the target.
+        inputs.target_annual_income = 1_600.0;
+        let result = solve_goal(&inputs, config).expect("must solve");
+        assert!(result.solved_contributions.is_some());
+        assert!(!result.feasible && !result.converged);
+    }
+
+    #[test]
+    fn tightening_the_cvar_floor_lowers_the_solved_income() {
+        let mut inputs = deterministic_inputs();
+        inputs.max_retirement_age = 30;
+        inputs.horizon_age = 40;
+        inputs.isa_start = 5_000.0;
+
+        let solve_with_floor = |floor: f64| {
+            let config = GoalSolveConfig {
+                goal_type: GoalType::MaxIncomeCvarConstrained,
+                target_retirement_age: 30,
+                target_success_threshold: 0.0,
+                min_cvar5_income_ratio: floor,
+                // A zero target scores an income ratio of zero, so start above it.
+                search_min: 100.0,
+                search_max: 1_000.0,
+                tolerance: 0.01,
+                max_iterations: 40,
+                simulations_per_iteration: 1,
+                final_simulations: 1,
+                secant_acceleration: false,
+            };
+            solve_goal(&inputs, config).expect("must solve")
+        };
+
+        // Ten years from 5,000 at zero returns: up to 500 every year is paid in full.
+        // Above that the tenth year gets (5,000 - 9 I) / I of the target, so a floor of
+        // one half allows I = 5,000 / 9.5.
+        let loose = solve_with_floor(0.5);
+        let tight = solve_with_floor(1.0);
+        assert!(loose.feasible && tight.feasible);
+        let loose_income = loose.solved_value.expect("value expected");
+        let tight_income = tight.solved_value.expect("value expected");
+        assert_close(loose_income, 5_000.0 / 9.5, 0.05);
+        assert_close(tight_income, 500.0, 0.05);
+        assert!(tight_income < loose_income);
+        assert!(
+            tight
+                .achieved_cvar5_min_income_ratio
+                .expect("final evaluation")
+                >= 1.0 - 1e-9
+        );
+    }
+
     #[test]
     fn required_contribution_solver_reports_infeasible_when_bounds_too_low() {
         let inputs = deterministic_inputs();
@@ -477,16 +985,88 @@ mod tests {
             goal_type: GoalType::RequiredContribution,
             target_retirement_age: 31,
             target_success_threshold: 1.0,
+            min_cvar5_income_ratio: 0.0,
             search_min: 0.0,
             search_max: 50.0,
             tolerance: 0.5,
             max_iterations: 16,
             simulations_per_iteration: 1,
             final_simulations: 1,
+            secant_acceleration: false,
+        };
+
+        let result = solve_goal(&inputs, config).expect("must return result");
+        assert!(!result.feasible);
+        assert!(result.solved_value.is_none());
+    }
+
+    #[test]
+    fn earliest_retirement_age_solver_moves_later_with_higher_income() {
+        let mut inputs = deterministic_inputs();
+        inputs.isa_annual_contribution = 1_000.0;
+        inputs.max_retirement_age = 60;
+        inputs.horizon_age = 61;
+        let config = GoalSolveConfig {
+            goal_type: GoalType::EarliestRetirementAge,
+            target_retirement_age: 60,
+            target_success_threshold: 1.0,
+            min_cvar5_income_ratio: 0.0,
+            search_min: 0.0,
+            search_max: 1.0,
+            tolerance: 1.0,
+            max_iterations: 1,
+            simulations_per_iteration: 1,
+            final_simulations: 1,
+            secant_acceleration: false,
+        };
+
+        // Zero returns: the pot at age A is 1,000 * (A - 30) and must fund (61 - A) years.
+        inputs.target_annual_income = 1_000.0;
+        let modest = solve_goal(&inputs, config).expect("must solve");
+        inputs.target_annual_income = 2_000.0;
+        let richer = solve_goal(&inputs, config).expect("must solve");
+
+        assert!(modest.feasible && richer.feasible);
+        assert_eq!(modest.solved_value, Some(46.0));
+        assert_eq!(richer.solved_value, Some(51.0));
+        assert_eq!(modest.iterations.len(), 17);
+        assert_close(modest.achieved_success_rate.expect("rate"), 1.0, 1e-9);
+
+        // The scan ignores `target_retirement_age`, so an out-of-range one is not an error.
+        inputs.target_annual_income = 1_000.0;
+        for target_retirement_age in [0, 61, 200] {
+            let config = GoalSolveConfig {
+                target_retirement_age,
+                ..config
+            };
+            let result = solve_goal(&inputs, config).expect("must solve");
+            assert_eq!(result.solved_value, Some(46.0));
+        }
+    }
+
+    #[test]
+    fn earliest_retirement_age_solver_reports_infeasible_plan() {
+        let mut inputs = deterministic_inputs();
+        inputs.max_retirement_age = 40;
+        inputs.horizon_age = 50;
+        inputs.target_annual_income = 1_000_000.0;
+        let config = GoalSolveConfig {
+            goal_type: GoalType::EarliestRetirementAge,
+            target_retirement_age: 40,
+            target_success_threshold: 1.0,
+            min_cvar5_income_ratio: 0.0,
+            search_min: 0.0,
+            search_max: 1.0,
+            tolerance: 1.0,
+            max_iterations: 1,
+            simulations_per_iteration: 1,
+            final_simulations: 1,
+            secant_acceleration: false,
         };
 
         let result = solve_goal(&inputs, config).expect("must return result");
         assert!(!result.feasible);
         assert!(result.solved_value.is_none());
+        assert_eq!(result.iterations.len(), 11);
     }
 }
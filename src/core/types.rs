@@ -1,30 +1,128 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum WithdrawalOrder {
     ProRata,
     IsaFirst,
     TaxableFirst,
     PensionFirst,
     BondLadderFirst,
+    /// Pension up to the top of the basic-rate band, then ISA and taxable; pension only
+    /// beyond the band once those are empty.
+    FillBasicRate,
+    /// Taxable sales up to the gain that uses the CGT allowance, then ISA and pension;
+    /// the rest of the taxable pot last.
+    HarvestCgtAllowance,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum WithdrawalStrategy {
     Guardrails,
     GuytonKlinger,
     Vpw,
     FloorUpside,
     Bucket,
+    FixedReal,
+    ConstantPercentage,
+    CapeBased,
+    Amortization,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum MarketModel {
+    /// Correlated draws from the configured return and inflation distributions.
+    Parametric,
+    /// Whole historical years resampled from `core::HISTORICAL_YEARS`.
+    HistoricalBootstrap,
+    /// Replays `(isa, taxable, pension, inflation)` rates indexed by year since
+    /// `current_age`; the last entry repeats once the path runs out.
+    Deterministic { paths: Vec<(f64, f64, f64, f64)> },
+    /// Parametric draws whose volatility follows a GARCH(1,1) recursion on the previous
+    /// year's ISA (equity) shock. `long_run_vol` is the ISA's unconditional volatility
+    /// and replaces `isa_return_vol`; taxable and pension volatilities scale with it.
+    Garch {
+        alpha: f64,
+        beta: f64,
+        long_run_vol: f64,
+    },
+    /// Parametric draws from a two-state Markov chain. Normal years use the configured
+    /// means and volatilities; crisis years draw every pot from `crisis_return_mean` and
+    /// `crisis_return_vol`. Scenarios start in the normal regime.
+    RegimeSwitching {
+        p_normal_to_crisis: f64,
+        p_crisis_to_normal: f64,
+        crisis_return_mean: f64,
+        crisis_return_vol: f64,
+    },
+    /// Each sweep scenario runs wholly under one model: a `bootstrap_fraction` share of
+    /// them, spread evenly over the scenario ids, resample history as
+    /// `HistoricalBootstrap` and the rest draw as `Parametric`. Results pool as usual.
+    Ensemble { bootstrap_fraction: f64 },
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReturnDistribution {
+    Normal,
+    /// Fat-tailed shocks rescaled to unit variance; `dof` must exceed 2.
+    StudentT {
+        dof: f64,
+    },
+}
+
+/// What a retirement year must achieve for the scenario to keep succeeding.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SuccessCriterion {
+    /// Every year delivers the planned spending, even when an essential spend is set.
+    FullSpending,
+    /// Only running out fails: a year falls short while every pot is empty.
+    NoDepletion,
+    /// Every year delivers the essential spend when one is set, otherwise the planned
+    /// spending.
+    EssentialFloorMet,
+}
+
+/// Which end of a candidate's 95% Wilson interval must clear `success_threshold` for the
+/// age to be selected.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SelectionConfidence {
+    /// The observed success rate, ignoring sampling noise.
+    PointEstimate,
+    /// The lower bound: only ages that clear the threshold with 95% confidence.
+    Conservative,
+    /// The upper bound: ages that could plausibly clear the threshold.
+    Optimistic,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum PensionTaxMode {
     UkBands,
+    /// Scottish starter to top bands; shares the UK personal allowance and taper.
+    ScottishBands,
     FlatRate,
 }
 
-#[derive(Debug, Clone)]
+/// A fixed real payment due every year before `end_age`.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Debt {
+    /// Payment per year in today's money.
+    pub annual_payment: f64,
+    pub end_age: u32,
+}
+
+/// Resolved model configuration. Rates, shares and correlations are fractions (`0.05`
+/// is 5%), not the percentages the CLI and API accept; money is in today's pounds unless
+/// a field says otherwise. Serializes with camelCase field names, and fields missing
+/// when deserializing take their `Inputs::default()` value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 pub struct Inputs {
     pub current_age: u32,
     pub pension_access_age: u32,
@@ -32,13 +130,49 @@ pub struct Inputs {
     pub taxable_start: f64,
     pub taxable_cost_basis_start: f64,
     pub pension_start: f64,
+    /// Models a couple: a second pension pot drawn and taxed on the partner's own
+    /// allowance and bands. Other income is taxed as the primary person's.
+    pub household: bool,
+    pub partner_pension_start: f64,
+    /// Primary person's age when the partner can first draw their pension.
+    pub partner_pension_access_age: u32,
+    /// Partner's personal allowance in today's money; bands are shared with the primary.
+    pub partner_personal_allowance: f64,
+    /// Marriage allowance moved from the partner (or an unmodelled non-earning spouse) to
+    /// the primary person, in today's money. Capped at 10% of the personal allowance, and
+    /// only moved in years when the primary is a basic-rate payer and the transferor's
+    /// income is under their allowance.
+    pub marriage_allowance_transfer: f64,
     pub cash_start: f64,
     pub bond_ladder_start: f64,
     pub isa_annual_contribution: f64,
     pub isa_annual_contribution_limit: f64,
+    /// Lifetime ISA: invested like the ISA, with a 25% bonus on contributions (which
+    /// share the ISA limit) and a 25% penalty on withdrawals before `lisa_access_age`.
+    pub lisa_start: f64,
+    pub lisa_annual_contribution: f64,
+    pub lisa_access_age: u32,
     pub taxable_annual_contribution: f64,
     pub pension_annual_contribution: f64,
+    /// Employer top-up as a fraction of each year's own pension contribution.
+    pub employer_match_rate: f64,
+    /// Maximum employer top-up per year; `None` leaves the match uncapped.
+    pub employer_match_cap: Option<f64>,
+    /// Cap on total pension contributions (own plus employer) per year.
+    pub pension_annual_allowance: f64,
+    /// Send own pension contributions above the allowance to the taxable pot instead of
+    /// dropping them.
+    pub pension_overflow_to_taxable: bool,
     pub contribution_growth_rate: f64,
+    /// Scale contributions by the realized price index instead of compounding them at
+    /// `contribution_growth_rate`, keeping them constant in real terms.
+    pub contributions_track_inflation: bool,
+    /// Share of the full contributions still paid between the contribution stop age and
+    /// retirement in coast mode, e.g. `0.5` for part-time work. `0` stops them entirely.
+    pub coast_contribution_fraction: f64,
+    /// Taxable earnings per year before retirement, in today's money and rising with
+    /// prices. Pre-retirement dividends and gains stack on top of them for tax.
+    pub pre_retirement_earnings: f64,
     pub isa_return_mean: f64,
     pub isa_return_vol: f64,
     pub taxable_return_mean: f64,
@@ -46,11 +180,47 @@ pub struct Inputs {
     pub pension_return_mean: f64,
     pub pension_return_vol: f64,
     pub return_correlation: f64,
-    pub capital_gains_tax_rate: f64,
+    pub isa_taxable_correlation: f64,
+    /// `None` keeps taxable tied to pension only through the ISA factor
+    /// (`isa_taxable_correlation * return_correlation`).
+    pub taxable_pension_correlation: Option<f64>,
+    /// One-factor model: `(isa, taxable, pension)` loadings in [-1, 1] on a common equity
+    /// factor, each pot topped up with its own noise. Replaces the three correlations
+    /// above with `beta_i * beta_j` when set.
+    pub return_factor_betas: Option<[f64; 3]>,
+    pub return_distribution: ReturnDistribution,
+    pub market_model: MarketModel,
+    /// Length of contiguous historical runs drawn by the bootstrap; 1 is IID resampling.
+    pub bootstrap_block_years: u32,
+    /// Years the GARCH variance or crisis regime is run forward, draws discarded, before
+    /// each scenario starts, so it begins in the stationary state rather than a calm one.
+    pub market_burn_in_years: u32,
+    /// CGT rate on gains inside the basic-rate band left over after other taxable income.
+    pub cgt_basic_rate: f64,
+    pub cgt_higher_rate: f64,
     pub capital_gains_allowance: f64,
     pub taxable_return_tax_drag: f64,
+    /// Share of the taxable pot paid out as dividends each year (part of its return).
+    pub taxable_dividend_yield: f64,
+    /// Nominal dividends per year taxed at 0%, like `capital_gains_allowance`.
+    pub dividend_allowance: f64,
+    pub dividend_basic_rate: f64,
+    pub dividend_higher_rate: f64,
+    pub dividend_additional_rate: f64,
+    /// Annual fund fee (TER/platform) charged on each pot after the market return.
+    pub isa_fee_rate: f64,
+    pub taxable_fee_rate: f64,
+    pub pension_fee_rate: f64,
+    /// Equity share of each pot at `current_age`; the rest earns the bond return.
+    pub glide_start_equity: f64,
+    /// Equity share reached at `glide_end_age` and held afterwards.
+    pub glide_end_equity: f64,
+    /// Age at which the glide path ends; `None` glides all the way to `horizon_age`.
+    pub glide_end_age: Option<u32>,
     pub pension_tax_mode: PensionTaxMode,
     pub pension_flat_tax_rate: f64,
+    /// Share of the pension value at access that can be drawn tax-free (UK: 25%).
+    pub pension_tax_free_pct: f64,
     pub uk_personal_allowance: f64,
     pub uk_basic_rate_limit: f64,
     pub uk_higher_rate_limit: f64,
@@ -59,35 +229,176 @@ pub struct Inputs {
     pub uk_additional_rate: f64,
     pub uk_allowance_taper_start: f64,
     pub uk_allowance_taper_end: f64,
+    /// Income tax thresholds stay at today's nominal values before this age and index
+    /// with inflation from it; `None` indexes every year.
+    pub thresholds_frozen_until_age: Option<u32>,
+    /// Upper income bounds (today's money) of the Scottish starter to advanced bands.
+    pub scottish_starter_rate_limit: f64,
+    pub scottish_basic_rate_limit: f64,
+    pub scottish_intermediate_rate_limit: f64,
+    pub scottish_higher_rate_limit: f64,
+    pub scottish_advanced_rate_limit: f64,
+    pub scottish_starter_rate: f64,
+    pub scottish_basic_rate: f64,
+    pub scottish_intermediate_rate: f64,
+    pub scottish_higher_rate: f64,
+    pub scottish_advanced_rate: f64,
+    pub scottish_top_rate: f64,
     pub state_pension_start_age: u32,
     pub state_pension_annual_income: f64,
+    /// Real growth a year on top of inflation, e.g. the triple lock's margin over CPI.
+    pub state_pension_real_growth: f64,
+    /// Compound `state_pension_real_growth` from `current_age` rather than from
+    /// `state_pension_start_age`, treating the income as today's entitlement.
+    pub state_pension_growth_before_start: bool,
+    /// Years past `state_pension_start_age` before claiming; payments start later but
+    /// larger.
+    pub state_pension_deferral_years: u32,
+    /// Compound uplift per deferred year (the UK pays about 5.8%).
+    pub state_pension_deferral_uplift_rate: f64,
+    /// Defined-benefit pension paying a fixed real income from its own start age.
+    pub db_pension_start_age: u32,
+    pub db_pension_annual_income: f64,
+    /// Age at which part of the pension buys a level real annuity; `None` never buys one.
+    pub annuity_purchase_age: Option<u32>,
+    /// Pension (today's money) spent on the annuity, capped at the pot.
+    pub annuity_purchase_amount: f64,
+    /// Real income per year as a fraction of the purchase price.
+    pub annuity_rate: f64,
+    /// Pension conversion ladder: each retirement year from `conversion_ladder_start_age`
+    /// up to (not including) `conversion_ladder_end_age`, this much pension (today's
+    /// money) is drawn, taxed as income, and the net moved into the ISA, with any excess
+    /// over the ISA limit going to the taxable account.
+    pub conversion_ladder_annual_amount: f64,
+    pub conversion_ladder_start_age: u32,
+    pub conversion_ladder_end_age: u32,
+    /// Net rental income (today's money) received from this age; taxed as non-savings
+    /// income alongside pensions.
+    pub rental_income_start_age: u32,
+    pub rental_annual_income: f64,
+    /// Real growth per year of the rental income, compounded from `current_age`.
+    pub rental_growth_rate: f64,
+    /// Earned income (today's money) from part-time work, received each retirement year
+    /// from `part_time_start_age` until before `part_time_end_age`. Taxed as non-savings
+    /// income alongside pensions.
+    pub part_time_annual_income: f64,
+    pub part_time_start_age: u32,
+    pub part_time_end_age: u32,
     pub inflation_mean: f64,
     pub inflation_vol: f64,
+    /// Correlation of the inflation shock with the ISA (equity) return shock.
+    pub inflation_return_correlation: f64,
+    /// Bounds applied to each sampled parametric pot return.
+    pub return_clamp_min: f64,
+    pub return_clamp_max: f64,
+    /// Bounds applied to each sampled parametric inflation rate.
+    pub inflation_clamp_min: f64,
+    pub inflation_clamp_max: f64,
     pub target_annual_income: f64,
+    pub essential_annual_income: f64,
+    pub spending_decline_rate: f64,
+    pub care_cost_age: u32,
+    pub care_cost_uplift: f64,
+    /// Go-go years: target spending is scaled by `1 + active_phase_uplift` before
+    /// `active_phase_end_age`.
+    pub active_phase_end_age: u32,
+    pub active_phase_uplift: f64,
+    /// No-go years: target spending is scaled by `1 + late_phase_uplift` from
+    /// `late_phase_start_age`; usually negative.
+    pub late_phase_start_age: u32,
+    pub late_phase_uplift: f64,
+    /// Fixed real payments, such as a mortgage or a car loan, each due every year before
+    /// its own end age and counted as spending from retirement.
+    pub debts: Vec<Debt>,
+    /// Shorthand for one more entry in `debts`: a fixed real payment due every year
+    /// before `mortgage_end_age`, which is required when the payment is above zero.
     pub mortgage_annual_payment: f64,
     pub mortgage_end_age: Option<u32>,
+    /// Amortizing mortgage, an alternative to the fixed payments above: the balance owed
+    /// at `current_age` (0 for none), its nominal interest rate and remaining term. The
+    /// level nominal payment that clears it is derived, and its real cost falls at
+    /// `inflation_mean`. Counted as spending from retirement only.
+    pub mortgage_balance: f64,
+    pub mortgage_rate: f64,
+    pub mortgage_term_years: u32,
+    /// Lump-sum overpayments in today's money keyed by age; they cut the balance and so
+    /// shorten the term, keeping the payment level.
+    pub mortgage_overpayments: Vec<(u32, f64)>,
+    /// One-off real expenses keyed by the age they fall due.
+    pub lump_expenses: Vec<(u32, f64)>,
     pub max_retirement_age: u32,
     pub horizon_age: u32,
+    /// Real terminal estate that must remain at `horizon_age` for a scenario to succeed.
+    pub bequest_target: f64,
+    pub success_criterion: SuccessCriterion,
     pub simulations: u32,
+    /// Worker threads for the simulation: 0 uses every core, 1 runs sequentially.
+    /// Results do not depend on this; each scenario has its own derived seed.
+    pub threads: usize,
     pub success_threshold: f64,
+    pub selection_confidence: SelectionConfidence,
+    /// CRRA risk aversion (gamma) for scoring each scenario's real spending path; `1` is
+    /// log utility and `None` skips the score.
+    pub risk_aversion: Option<f64>,
+    /// Real rate at which later retirement years' utility is discounted.
+    pub utility_discount_rate: f64,
+    /// Real spending below which CRRA utility stops falling. Without it a year of zero
+    /// spending scores minus infinity at `gamma >= 1`, and one failed scenario would
+    /// swamp the mean.
+    pub utility_spending_floor: f64,
     pub seed: u64,
+    /// Seed scenarios from `(seed, scenario_id)` alone, so every candidate age replays the
+    /// same market paths (common random numbers) and the success curve does not jitter.
+    pub age_invariant_draws: bool,
+    /// Draw the independent inflation shock from `simulations` equal-probability strata,
+    /// one scenario per stratum each year, so small runs still cover the inflation tails.
+    /// Parametric, GARCH and regime-switching models only.
+    pub stratified_inflation: bool,
     pub bad_year_threshold: f64,
     pub good_year_threshold: f64,
     pub bad_year_cut: f64,
     pub good_year_raise: f64,
     pub min_income_floor: f64,
     pub max_income_ceiling: f64,
+    /// Real spending floor in today's money, applied on top of the proportional
+    /// `min_income_floor` (or essential spend), so the floor holds even for a small target.
+    pub min_real_spending_floor: f64,
     pub withdrawal_strategy: WithdrawalStrategy,
     pub gk_lower_guardrail: f64,
     pub gk_upper_guardrail: f64,
+    pub gk_adjustment: f64,
     pub vpw_expected_real_return: f64,
+    pub constant_withdrawal_rate: f64,
+    pub cape_a: f64,
+    pub cape_b: f64,
+    pub cape_start: f64,
+    pub cape_long_run_mean: f64,
+    pub cape_reversion_speed: f64,
     pub floor_upside_capture: f64,
     pub bucket_target_years: f64,
     pub good_year_extra_buffer_withdrawal: f64,
+    /// Cash floor, in months of planned spending, that good years refill under every
+    /// strategy; 0 leaves only the bucket target and the extra good-year withdrawal.
+    pub min_cash_months: f64,
     pub cash_growth_rate: f64,
     pub bond_ladder_yield: f64,
+    /// Volatility of the ladder's return around `bond_ladder_yield` in parametric draws;
+    /// 0 keeps the yield fixed. Must be 0 under `MarketModel::Deterministic`.
+    pub bond_ladder_yield_vol: f64,
+    /// Correlation of the ladder's return with the inflation shock, in [-1, 1]. Negative
+    /// values make bonds lose in high-inflation years.
+    pub bond_ladder_inflation_correlation: f64,
     pub bond_ladder_years: u32,
+    /// Keep the ladder at `bond_ladder_years` by rolling unspent rungs back in, so each
+    /// year's rung is `1 / bond_ladder_years` of the balance instead of running it off.
+    pub bond_ladder_reinvest: bool,
+    /// Ladder size, in today's money, to build at retirement by selling ISA and then
+    /// taxable holdings; 0 keeps only `bond_ladder_start`.
+    pub bond_ladder_target_at_retirement: f64,
     pub post_access_withdrawal_order: WithdrawalOrder,
+    /// Order used before pension access; `None` keeps the pension-free form of
+    /// `post_access_withdrawal_order`. Orders that draw pension first are rejected.
+    pub pre_access_withdrawal_order: Option<WithdrawalOrder>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -95,6 +406,8 @@ pub struct Inputs {
 pub struct AgeResult {
     pub retirement_age: u32,
     pub success_rate: f64,
+    /// Half-width of the 95% Wilson interval around `success_rate`.
+    pub success_ci_half_width: f64,
     pub median_retirement_pot: f64,
     pub p10_retirement_pot: f64,
     pub median_retirement_isa: f64,
@@ -108,7 +421,14 @@ pub struct AgeResult {
     pub median_retirement_bond_ladder: f64,
     pub p10_retirement_bond_ladder: f64,
     pub median_terminal_pot: f64,
+    pub p5_terminal_pot: f64,
     pub p10_terminal_pot: f64,
+    pub p25_terminal_pot: f64,
+    pub p75_terminal_pot: f64,
+    pub p90_terminal_pot: f64,
+    pub p95_terminal_pot: f64,
+    /// Mean of the worst 5% of terminal totals (expected shortfall).
+    pub cvar5_terminal_pot: f64,
     pub median_terminal_isa: f64,
     pub p10_terminal_isa: f64,
     pub median_terminal_taxable: f64,
@@ -120,7 +440,59 @@ pub struct AgeResult {
     pub median_terminal_bond_ladder: f64,
     pub p10_terminal_bond_ladder: f64,
     pub p10_min_income_ratio: f64,
+    /// Mean of the worst 5% of minimum income ratios.
+    pub cvar5_min_income_ratio: f64,
     pub median_avg_income_ratio: f64,
+    /// Percentiles of the tax (CGT, income and dividend) each scenario pays over its
+    /// retirement years, in today's money.
+    pub median_lifetime_tax: f64,
+    pub p90_lifetime_tax: f64,
+    /// Percentiles of each scenario's deepest peak-to-trough fall in the real total
+    /// portfolio during retirement, as a fraction of the peak.
+    pub median_max_drawdown: f64,
+    pub p90_max_drawdown: f64,
+    /// Percentiles of each scenario's tightest year: start-of-year real portfolio over
+    /// that year's required real spending, capped at 100 years.
+    pub median_min_runway_years: f64,
+    pub p10_min_runway_years: f64,
+    /// What the failed scenarios had in common; `None` when every scenario succeeded.
+    pub failure_attribution: Option<FailureAttribution>,
+    /// Mean over scenarios of the discounted CRRA utility of real spending from
+    /// retirement to the horizon; `None` without `risk_aversion`. A failed scenario
+    /// stops at its failure year, and every year after it scores at
+    /// `utility_spending_floor` even if some money was left.
+    pub expected_utility: Option<f64>,
+}
+
+/// Diagnostics over the failed scenarios of one candidate age, each measured from
+/// retirement to the year the scenario failed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailureAttribution {
+    pub failed_scenarios: u32,
+    /// Median price rise from retirement to the failure year (`0.5` is 50%).
+    pub median_cumulative_inflation: f64,
+    /// Median of each failure's worst annual real return on the invested pots.
+    pub median_worst_real_return: f64,
+    /// Median tax paid up to the failure year, in today's money.
+    pub median_tax_paid: f64,
+    /// Mean rank of the failures among all scenarios on annualised inflation, from 0 to
+    /// 1 with higher meaning worse; `0.5` means failures saw typical inflation.
+    pub inflation_score: f64,
+    /// The same rank on the worst annual real return.
+    pub market_score: f64,
+    /// The same rank on average real tax paid per retirement year.
+    pub tax_score: f64,
+    /// Highest-scoring factor, or `None` when no factor scores above 0.5.
+    pub dominant_factor: Option<FailureFactor>,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FailureFactor {
+    Inflation,
+    Market,
+    Tax,
 }
 
 #[derive(Debug, Clone)]
@@ -130,24 +502,91 @@ pub struct ModelResult {
     pub best_index: usize,
 }
 
+/// Inputs `run_model` cannot produce meaningful results for. Callers that go through
+/// `validate_inputs` never see these.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModelError {
+    /// No candidate ages: the last candidate is below `current_age`.
+    EmptyAgeRange {
+        current_age: u32,
+        last_age: u32,
+    },
+    /// Every candidate would retire at or after the end of the horizon.
+    InfeasibleHorizon {
+        max_retirement_age: u32,
+        horizon_age: u32,
+    },
+    ZeroSimulations,
+    NonFiniteParameter(&'static str),
+}
+
+impl std::fmt::Display for ModelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyAgeRange {
+                current_age,
+                last_age,
+            } => write!(
+                f,
+                "no candidate ages between current age {current_age} and {last_age}"
+            ),
+            Self::InfeasibleHorizon {
+                max_retirement_age,
+                horizon_age,
+            } => write!(
+                f,
+                "horizon age {horizon_age} must be after max retirement age {max_retirement_age}"
+            ),
+            Self::ZeroSimulations => f.write_str("simulations must be > 0"),
+            Self::NonFiniteParameter(name) => write!(f, "{name} must be finite"),
+        }
+    }
+}
+
+impl std::error::Error for ModelError {}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CashflowYearResult {
     pub age: u32,
     pub median_contribution_isa: f64,
+    pub median_contribution_lisa: f64,
     pub median_contribution_taxable: f64,
     pub median_contribution_pension: f64,
+    pub median_contribution_employer: f64,
     pub median_contribution_total: f64,
     pub median_withdrawal_portfolio: f64,
+    pub p10_withdrawal_portfolio: f64,
+    pub p90_withdrawal_portfolio: f64,
     pub median_withdrawal_non_pension_income: f64,
+    pub median_annuity_income: f64,
+    pub median_rental_income: f64,
+    pub median_pension_conversion: f64,
     pub median_spending_total: f64,
+    pub p10_spending_total: f64,
+    pub p90_spending_total: f64,
     pub median_tax_cgt: f64,
     pub median_tax_income: f64,
+    /// Medians of the year's income tax (excluding dividend tax) in each band. They are
+    /// separate medians, so they need not sum to `median_tax_income`.
+    pub median_tax_income_basic: f64,
+    pub median_tax_income_higher: f64,
+    pub median_tax_income_additional: f64,
+    /// Median of the income tax caused by the personal-allowance taper; already
+    /// included in the band medians.
+    pub median_tax_income_taper: f64,
     pub median_tax_total: f64,
     pub median_end_isa: f64,
+    pub median_end_lisa: f64,
     pub median_end_taxable: f64,
     pub median_end_pension: f64,
     pub median_end_cash: f64,
     pub median_end_bond_ladder: f64,
     pub median_end_total: f64,
+    pub p5_end_total: f64,
+    pub p10_end_total: f64,
+    pub p25_end_total: f64,
+    pub p75_end_total: f64,
+    pub p90_end_total: f64,
+    pub p95_end_total: f64,
 }
@@ -0,0 +1,1013 @@
+use serde::Serialize;
+
+use super::engine::return_correlation_cholesky;
+use super::types::{Inputs, MarketModel, ReturnDistribution, WithdrawalOrder};
+
+/// One rejected input. `field` is the CLI flag without its dashes, or the JSON payload
+/// key once the error comes back through the API.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidationError {
+    pub field: String,
+    pub message: String,
+}
+
+impl ValidationError {
+    pub fn new(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.trim_start_matches('-').to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Checks a fully built `Inputs` and reports every violation at once. Messages name the
+/// CLI flags and quote rate bounds in the CLI's percent units, so the same errors read
+/// correctly whether the inputs came from flags, the API or `InputsBuilder`.
+pub fn validate_inputs(inputs: &Inputs) -> Result<(), Vec<ValidationError>> {
+    let mut errors = Vec::new();
+
+    if inputs.pension_access_age < inputs.current_age {
+        errors.push(ValidationError::new(
+            "pension-access-age",
+            "--pension-access-age must be >= --current-age",
+        ));
+    }
+
+    if inputs.max_retirement_age < inputs.current_age {
+        errors.push(ValidationError::new(
+            "max-age",
+            "--max-age must be >= --current-age",
+        ));
+    }
+
+    if inputs.horizon_age <= inputs.max_retirement_age {
+        errors.push(ValidationError::new(
+            "horizon-age",
+            "--horizon-age must be > --max-age",
+        ));
+    }
+
+    if !inputs.bequest_target.is_finite() || inputs.bequest_target < 0.0 {
+        errors.push(ValidationError::new(
+            "bequest-target",
+            "--bequest-target must be >= 0",
+        ));
+    }
+
+    if inputs.simulations == 0 {
+        errors.push(ValidationError::new(
+            "simulations",
+            "--simulations must be > 0",
+        ));
+    }
+
+    if !(0.0..=1.0).contains(&inputs.success_threshold) {
+        errors.push(ValidationError::new(
+            "success-threshold",
+            "--success-threshold must be between 0 and 100",
+        ));
+    }
+
+    if !(-1.0..=1.0).contains(&inputs.return_correlation) {
+        errors.push(ValidationError::new(
+            "return-correlation",
+            "--return-correlation must be between -1 and 1",
+        ));
+    }
+
+    if !(-1.0..=1.0).contains(&inputs.isa_taxable_correlation) {
+        errors.push(ValidationError::new(
+            "isa-taxable-correlation",
+            "--isa-taxable-correlation must be between -1 and 1",
+        ));
+    }
+
+    if let ReturnDistribution::StudentT { dof } = inputs.return_distribution
+        && !(dof.is_finite() && dof > 2.0)
+    {
+        errors.push(ValidationError::new(
+            "student-t-dof",
+            "--student-t-dof must be > 2",
+        ));
+    }
+
+    if inputs.bootstrap_block_years == 0 {
+        errors.push(ValidationError::new(
+            "bootstrap-block-years",
+            "--bootstrap-block-years must be >= 1",
+        ));
+    }
+
+    if inputs.market_burn_in_years > 1_000 {
+        errors.push(ValidationError::new(
+            "market-burn-in-years",
+            "--market-burn-in-years must be <= 1000",
+        ));
+    }
+
+    if let MarketModel::Deterministic { paths } = &inputs.market_model {
+        if paths.is_empty() {
+            errors.push(ValidationError::new(
+                "market-path-year",
+                "--market-path-year is required when --market-model=deterministic",
+            ));
+        }
+        if paths.iter().any(|&(isa, taxable, pension, inflation)| {
+            [isa, taxable, pension, inflation]
+                .iter()
+                .any(|rate| !(rate.is_finite() && *rate > -1.0))
+        }) {
+            errors.push(ValidationError::new(
+                "market-path-year",
+                "--market-path-year rates must be > -100",
+            ));
+        }
+        if inputs.bond_ladder_yield_vol != 0.0 {
+            errors.push(ValidationError::new(
+                "bond-ladder-yield-vol",
+                "--bond-ladder-yield-vol must be 0 when --market-model=deterministic",
+            ));
+        }
+    }
+
+    if let MarketModel::Garch {
+        alpha,
+        beta,
+        long_run_vol,
+    } = inputs.market_model
+    {
+        if !(alpha.is_finite() && alpha >= 0.0) {
+            errors.push(ValidationError::new(
+                "garch-alpha",
+                "--garch-alpha must be >= 0",
+            ));
+        }
+        if !(beta.is_finite() && beta >= 0.0 && alpha + beta < 1.0) {
+            errors.push(ValidationError::new(
+                "garch-beta",
+                "--garch-beta must be >= 0 with --garch-alpha + --garch-beta < 1",
+            ));
+        }
+        if !(long_run_vol.is_finite() && long_run_vol >= 0.0) {
+            errors.push(ValidationError::new(
+                "garch-long-run-vol",
+                "--garch-long-run-vol must be >= 0",
+            ));
+        }
+    }
+
+    if let MarketModel::Ensemble { bootstrap_fraction } = inputs.market_model
+        && !(0.0..=1.0).contains(&bootstrap_fraction)
+    {
+        errors.push(ValidationError::new(
+            "bootstrap-fraction",
+            "--bootstrap-fraction must be between 0 and 1",
+        ));
+    }
+
+    if let MarketModel::RegimeSwitching {
+        p_normal_to_crisis,
+        p_crisis_to_normal,
+        crisis_return_mean,
+        crisis_return_vol,
+    } = inputs.market_model
+    {
+        if !(0.0..=1.0).contains(&p_normal_to_crisis) {
+            errors.push(ValidationError::new(
+                "normal-to-crisis-probability",
+                "--normal-to-crisis-probability must be between 0 and 1",
+            ));
+        }
+        if !(0.0..=1.0).contains(&p_crisis_to_normal) {
+            errors.push(ValidationError::new(
+                "crisis-to-normal-probability",
+                "--crisis-to-normal-probability must be between 0 and 1",
+            ));
+        }
+        if !(crisis_return_mean.is_finite() && crisis_return_mean > -1.0) {
+            errors.push(ValidationError::new(
+                "crisis-return-mean",
+                "--crisis-return-mean must be > -100",
+            ));
+        }
+        if !(crisis_return_vol.is_finite() && crisis_return_vol >= 0.0) {
+            errors.push(ValidationError::new(
+                "crisis-return-volatility",
+                "--crisis-return-volatility must be >= 0",
+            ));
+        }
+    }
+
+    if !(-1.0..=1.0).contains(&inputs.inflation_return_correlation) {
+        errors.push(ValidationError::new(
+            "inflation-return-correlation",
+            "--inflation-return-correlation must be between -1 and 1",
+        ));
+    }
+
+    for (name, min, max) in [
+        ("return", inputs.return_clamp_min, inputs.return_clamp_max),
+        (
+            "inflation",
+            inputs.inflation_clamp_min,
+            inputs.inflation_clamp_max,
+        ),
+    ] {
+        if !(min.is_finite() && max.is_finite() && min > -1.0 && min < max) {
+            errors.push(ValidationError::new(
+                &format!("{name}-clamp-min"),
+                format!("--{name}-clamp-min must be > -100 and below --{name}-clamp-max"),
+            ));
+        }
+    }
+
+    if let Some(corr) = inputs.taxable_pension_correlation
+        && !(-1.0..=1.0).contains(&corr)
+    {
+        errors.push(ValidationError::new(
+            "taxable-pension-correlation",
+            "--taxable-pension-correlation must be between -1 and 1",
+        ));
+    }
+
+    if let Some(betas) = inputs.return_factor_betas {
+        for (name, beta) in ["isa", "taxable", "pension"].iter().zip(betas) {
+            if !(-1.0..=1.0).contains(&beta) {
+                errors.push(ValidationError::new(
+                    &format!("{name}-beta"),
+                    format!("--{name}-beta must be between -1 and 1"),
+                ));
+            }
+        }
+    }
+
+    if inputs.target_annual_income <= 0.0 {
+        errors.push(ValidationError::new(
+            "target-annual-income",
+            "--target-annual-income must be > 0",
+        ));
+    }
+
+    if !inputs.essential_annual_income.is_finite()
+        || !(0.0..=inputs.target_annual_income).contains(&inputs.essential_annual_income)
+    {
+        errors.push(ValidationError::new(
+            "essential-annual-income",
+            "--essential-annual-income must be between 0 and --target-annual-income",
+        ));
+    }
+
+    if !(0.0..1.0).contains(&inputs.spending_decline_rate) {
+        errors.push(ValidationError::new(
+            "spending-decline-rate",
+            "--spending-decline-rate must be >= 0 and < 100",
+        ));
+    }
+
+    if !inputs.care_cost_uplift.is_finite() || inputs.care_cost_uplift < 0.0 {
+        errors.push(ValidationError::new(
+            "care-cost-uplift",
+            "--care-cost-uplift must be >= 0",
+        ));
+    }
+
+    if !(inputs.active_phase_uplift.is_finite() && inputs.active_phase_uplift > -1.0) {
+        errors.push(ValidationError::new(
+            "active-phase-uplift",
+            "--active-phase-uplift must be > -100",
+        ));
+    }
+
+    if !(inputs.late_phase_uplift.is_finite() && inputs.late_phase_uplift > -1.0) {
+        errors.push(ValidationError::new(
+            "late-phase-uplift",
+            "--late-phase-uplift must be > -100",
+        ));
+    }
+
+    if inputs.active_phase_end_age > inputs.late_phase_start_age {
+        errors.push(ValidationError::new(
+            "late-phase-start-age",
+            "--late-phase-start-age must be >= --active-phase-end-age",
+        ));
+    }
+
+    if inputs
+        .debts
+        .iter()
+        .any(|debt| !debt.annual_payment.is_finite() || debt.annual_payment < 0.0)
+    {
+        errors.push(ValidationError::new("debt", "--debt payments must be >= 0"));
+    }
+
+    if inputs
+        .debts
+        .iter()
+        .any(|debt| debt.end_age <= inputs.current_age)
+    {
+        errors.push(ValidationError::new(
+            "debt",
+            "--debt end ages must be > --current-age",
+        ));
+    }
+
+    if !inputs.mortgage_annual_payment.is_finite() || inputs.mortgage_annual_payment < 0.0 {
+        errors.push(ValidationError::new(
+            "mortgage-annual-payment",
+            "--mortgage-annual-payment must be >= 0",
+        ));
+    }
+
+    if inputs.mortgage_annual_payment > 0.0 {
+        match inputs.mortgage_end_age {
+            None => errors.push(ValidationError::new(
+                "mortgage-end-age",
+                "--mortgage-end-age is required when --mortgage-annual-payment > 0",
+            )),
+            Some(end_age) if end_age <= inputs.current_age => errors.push(ValidationError::new(
+                "mortgage-end-age",
+                "--mortgage-end-age must be > --current-age",
+            )),
+            Some(_) => {}
+        }
+    }
+
+    if !inputs.mortgage_balance.is_finite() || inputs.mortgage_balance < 0.0 {
+        errors.push(ValidationError::new(
+            "mortgage-balance",
+            "--mortgage-balance must be >= 0",
+        ));
+    }
+
+    if inputs.mortgage_balance > 0.0 {
+        if inputs.mortgage_annual_payment > 0.0 {
+            errors.push(ValidationError::new(
+                "mortgage-balance",
+                "--mortgage-balance cannot be combined with --mortgage-annual-payment",
+            ));
+        }
+        if !(inputs.mortgage_rate.is_finite() && inputs.mortgage_rate > -1.0) {
+            errors.push(ValidationError::new(
+                "mortgage-rate",
+                "--mortgage-rate must be > -100",
+            ));
+        }
+        if inputs.mortgage_term_years == 0 {
+            errors.push(ValidationError::new(
+                "mortgage-term-years",
+                "--mortgage-term-years must be >= 1",
+            ));
+        }
+    }
+
+    if inputs
+        .mortgage_overpayments
+        .iter()
+        .any(|&(age, amount)| !amount.is_finite() || amount < 0.0 || age < inputs.current_age)
+    {
+        errors.push(ValidationError::new(
+            "mortgage-overpayment",
+            "--mortgage-overpayment amounts must be >= 0 at ages from --current-age",
+        ));
+    }
+
+    if inputs
+        .lump_expenses
+        .iter()
+        .any(|(_, amount)| !amount.is_finite() || *amount < 0.0)
+    {
+        errors.push(ValidationError::new(
+            "lump-expense",
+            "--lump-expense amounts must be >= 0",
+        ));
+    }
+
+    if !inputs.partner_pension_start.is_finite() || inputs.partner_pension_start < 0.0 {
+        errors.push(ValidationError::new(
+            "partner-pension-start",
+            "--partner-pension-start must be >= 0",
+        ));
+    }
+
+    if !inputs.partner_personal_allowance.is_finite() || inputs.partner_personal_allowance < 0.0 {
+        errors.push(ValidationError::new(
+            "partner-personal-allowance",
+            "--partner-personal-allowance must be >= 0",
+        ));
+    }
+
+    if inputs.cash_start < 0.0 {
+        errors.push(ValidationError::new(
+            "cash-start",
+            "--cash-start must be >= 0",
+        ));
+    }
+
+    if !inputs.bond_ladder_target_at_retirement.is_finite()
+        || inputs.bond_ladder_target_at_retirement < 0.0
+    {
+        errors.push(ValidationError::new(
+            "bond-ladder-target-at-retirement",
+            "--bond-ladder-target-at-retirement must be >= 0",
+        ));
+    }
+
+    if inputs.bond_ladder_start < 0.0 {
+        errors.push(ValidationError::new(
+            "bond-ladder-start",
+            "--bond-ladder-start must be >= 0",
+        ));
+    }
+
+    if !inputs.lisa_start.is_finite() || inputs.lisa_start < 0.0 {
+        errors.push(ValidationError::new(
+            "lisa-start",
+            "--lisa-start must be >= 0",
+        ));
+    }
+
+    if !inputs.lisa_annual_contribution.is_finite() || inputs.lisa_annual_contribution < 0.0 {
+        errors.push(ValidationError::new(
+            "lisa-annual-contribution",
+            "--lisa-annual-contribution must be >= 0",
+        ));
+    }
+
+    if !inputs.bond_ladder_yield.is_finite() || inputs.bond_ladder_yield <= -1.0 {
+        errors.push(ValidationError::new(
+            "bond-ladder-yield",
+            "--bond-ladder-yield must be > -100",
+        ));
+    }
+
+    if !inputs.bond_ladder_yield_vol.is_finite() || inputs.bond_ladder_yield_vol < 0.0 {
+        errors.push(ValidationError::new(
+            "bond-ladder-yield-vol",
+            "--bond-ladder-yield-vol must be >= 0",
+        ));
+    }
+
+    if !(-1.0..=1.0).contains(&inputs.bond_ladder_inflation_correlation) {
+        errors.push(ValidationError::new(
+            "bond-ladder-inflation-correlation",
+            "--bond-ladder-inflation-correlation must be between -1 and 1",
+        ));
+    }
+
+    for (name, rate) in [
+        ("--cgt-basic-rate", inputs.cgt_basic_rate),
+        ("--cgt-higher-rate", inputs.cgt_higher_rate),
+    ] {
+        if !(0.0..=1.0).contains(&rate) {
+            errors.push(ValidationError::new(
+                name,
+                format!("{name} must be between 0 and 100"),
+            ));
+        }
+    }
+
+    if inputs.capital_gains_allowance < 0.0 {
+        errors.push(ValidationError::new(
+            "capital-gains-allowance",
+            "--capital-gains-allowance must be >= 0",
+        ));
+    }
+
+    if !(0.0..=1.0).contains(&inputs.taxable_return_tax_drag) {
+        errors.push(ValidationError::new(
+            "taxable-return-tax-drag",
+            "--taxable-return-tax-drag must be between 0 and 100",
+        ));
+    }
+
+    if !(0.0..=1.0).contains(&inputs.taxable_dividend_yield) {
+        errors.push(ValidationError::new(
+            "taxable-dividend-yield",
+            "--taxable-dividend-yield must be between 0 and 100",
+        ));
+    }
+
+    if !inputs.dividend_allowance.is_finite() || inputs.dividend_allowance < 0.0 {
+        errors.push(ValidationError::new(
+            "dividend-allowance",
+            "--dividend-allowance must be >= 0",
+        ));
+    }
+
+    for (name, rate) in [
+        ("--dividend-basic-rate", inputs.dividend_basic_rate),
+        ("--dividend-higher-rate", inputs.dividend_higher_rate),
+        (
+            "--dividend-additional-rate",
+            inputs.dividend_additional_rate,
+        ),
+    ] {
+        if !(0.0..=1.0).contains(&rate) {
+            errors.push(ValidationError::new(
+                name,
+                format!("{name} must be between 0 and 100"),
+            ));
+        }
+    }
+
+    for (name, rate) in [
+        ("--isa-fee-rate", inputs.isa_fee_rate),
+        ("--taxable-fee-rate", inputs.taxable_fee_rate),
+        ("--pension-fee-rate", inputs.pension_fee_rate),
+    ] {
+        if !(0.0..=1.0).contains(&rate) {
+            errors.push(ValidationError::new(
+                name,
+                format!("{name} must be between 0 and 100"),
+            ));
+        }
+    }
+
+    for (name, share) in [
+        ("--glide-start-equity", inputs.glide_start_equity),
+        ("--glide-end-equity", inputs.glide_end_equity),
+    ] {
+        if !(0.0..=1.0).contains(&share) {
+            errors.push(ValidationError::new(
+                name,
+                format!("{name} must be between 0 and 100"),
+            ));
+        }
+    }
+
+    if let Some(end_age) = inputs.glide_end_age
+        && (end_age <= inputs.current_age || end_age > inputs.horizon_age)
+    {
+        errors.push(ValidationError::new(
+            "glide-end-age",
+            "--glide-end-age must be > --current-age and <= --horizon-age",
+        ));
+    }
+
+    if inputs.taxable_cost_basis_start < 0.0
+        || inputs.taxable_cost_basis_start > inputs.taxable_start
+    {
+        errors.push(ValidationError::new(
+            "taxable-cost-basis-start",
+            "--taxable-cost-basis-start must be between 0 and taxable-start",
+        ));
+    }
+
+    if inputs.min_income_floor <= 0.0 || inputs.max_income_ceiling <= 0.0 {
+        errors.push(ValidationError::new(
+            "min-income-floor",
+            "--min-income-floor and --max-income-ceiling must be > 0",
+        ));
+    }
+
+    if inputs.min_income_floor > inputs.max_income_ceiling {
+        errors.push(ValidationError::new(
+            "min-income-floor",
+            "--min-income-floor cannot exceed --max-income-ceiling",
+        ));
+    }
+
+    if inputs
+        .risk_aversion
+        .is_some_and(|gamma| !gamma.is_finite() || gamma <= 0.0)
+    {
+        errors.push(ValidationError::new(
+            "risk-aversion",
+            "--risk-aversion must be > 0",
+        ));
+    }
+
+    if !inputs.utility_discount_rate.is_finite() || inputs.utility_discount_rate <= -1.0 {
+        errors.push(ValidationError::new(
+            "utility-discount-rate",
+            "--utility-discount-rate must be > -100",
+        ));
+    }
+
+    if !inputs.utility_spending_floor.is_finite() || inputs.utility_spending_floor <= 0.0 {
+        errors.push(ValidationError::new(
+            "utility-spending-floor",
+            "--utility-spending-floor must be > 0",
+        ));
+    }
+
+    if !inputs.min_real_spending_floor.is_finite() || inputs.min_real_spending_floor < 0.0 {
+        errors.push(ValidationError::new(
+            "min-real-spending-floor",
+            "--min-real-spending-floor must be >= 0",
+        ));
+    }
+
+    if !inputs.gk_lower_guardrail.is_finite() || inputs.gk_lower_guardrail <= 0.0 {
+        errors.push(ValidationError::new(
+            "gk-lower-guardrail",
+            "--gk-lower-guardrail must be > 0",
+        ));
+    }
+
+    if !inputs.gk_upper_guardrail.is_finite() || inputs.gk_upper_guardrail <= 0.0 {
+        errors.push(ValidationError::new(
+            "gk-upper-guardrail",
+            "--gk-upper-guardrail must be > 0",
+        ));
+    }
+
+    if inputs.gk_upper_guardrail < inputs.gk_lower_guardrail {
+        errors.push(ValidationError::new(
+            "gk-upper-guardrail",
+            "--gk-upper-guardrail must be >= --gk-lower-guardrail",
+        ));
+    }
+
+    if !(0.0..1.0).contains(&inputs.gk_adjustment) {
+        errors.push(ValidationError::new(
+            "gk-adjustment",
+            "--gk-adjustment must be >= 0 and < 100",
+        ));
+    }
+
+    if !inputs.vpw_expected_real_return.is_finite() || inputs.vpw_expected_real_return <= -1.0 {
+        errors.push(ValidationError::new(
+            "vpw-expected-real-return",
+            "--vpw-expected-real-return must be > -100",
+        ));
+    }
+
+    if !(0.0..=1.0).contains(&inputs.constant_withdrawal_rate) {
+        errors.push(ValidationError::new(
+            "constant-withdrawal-rate",
+            "--constant-withdrawal-rate must be between 0 and 100",
+        ));
+    }
+
+    if !(0.0..=1.0).contains(&inputs.cape_a) {
+        errors.push(ValidationError::new(
+            "cape-a",
+            "--cape-a must be between 0 and 100",
+        ));
+    }
+
+    if !inputs.cape_b.is_finite() || inputs.cape_b < 0.0 {
+        errors.push(ValidationError::new("cape-b", "--cape-b must be >= 0"));
+    }
+
+    if !inputs.cape_start.is_finite() || inputs.cape_start <= 0.0 {
+        errors.push(ValidationError::new(
+            "cape-start",
+            "--cape-start must be > 0",
+        ));
+    }
+
+    if !inputs.cape_long_run_mean.is_finite() || inputs.cape_long_run_mean <= 0.0 {
+        errors.push(ValidationError::new(
+            "cape-long-run-mean",
+            "--cape-long-run-mean must be > 0",
+        ));
+    }
+
+    if !(0.0..=1.0).contains(&inputs.cape_reversion_speed) {
+        errors.push(ValidationError::new(
+            "cape-reversion-speed",
+            "--cape-reversion-speed must be between 0 and 100",
+        ));
+    }
+
+    if !(0.0..=3.0).contains(&inputs.floor_upside_capture) {
+        errors.push(ValidationError::new(
+            "floor-upside-capture",
+            "--floor-upside-capture must be between 0 and 300",
+        ));
+    }
+
+    if !inputs.bucket_target_years.is_finite() || inputs.bucket_target_years < 0.0 {
+        errors.push(ValidationError::new(
+            "bucket-target-years",
+            "--bucket-target-years must be >= 0",
+        ));
+    }
+
+    if !inputs.min_cash_months.is_finite() || inputs.min_cash_months < 0.0 {
+        errors.push(ValidationError::new(
+            "min-cash-months",
+            "--min-cash-months must be >= 0",
+        ));
+    }
+
+    if inputs.isa_annual_contribution_limit < 0.0 {
+        errors.push(ValidationError::new(
+            "isa-annual-contribution-limit",
+            "--isa-annual-contribution-limit must be >= 0",
+        ));
+    }
+
+    if !inputs.employer_match_rate.is_finite() || inputs.employer_match_rate < 0.0 {
+        errors.push(ValidationError::new(
+            "employer-match-rate",
+            "--employer-match-rate must be >= 0",
+        ));
+    }
+
+    if inputs
+        .employer_match_cap
+        .is_some_and(|cap| !cap.is_finite() || cap < 0.0)
+    {
+        errors.push(ValidationError::new(
+            "employer-match-cap",
+            "--employer-match-cap must be >= 0",
+        ));
+    }
+
+    if !inputs.pension_annual_allowance.is_finite() || inputs.pension_annual_allowance < 0.0 {
+        errors.push(ValidationError::new(
+            "pension-annual-allowance",
+            "--pension-annual-allowance must be >= 0",
+        ));
+    }
+
+    if !inputs.contribution_growth_rate.is_finite() || inputs.contribution_growth_rate <= -1.0 {
+        errors.push(ValidationError::new(
+            "contribution-growth-rate",
+            "--contribution-growth-rate must be > -100",
+        ));
+    }
+
+    if !(0.0..=1.0).contains(&inputs.coast_contribution_fraction) {
+        errors.push(ValidationError::new(
+            "coast-contribution-fraction",
+            "--coast-contribution-fraction must be between 0 and 1",
+        ));
+    }
+
+    if !inputs.pre_retirement_earnings.is_finite() || inputs.pre_retirement_earnings < 0.0 {
+        errors.push(ValidationError::new(
+            "pre-retirement-earnings",
+            "--pre-retirement-earnings must be >= 0",
+        ));
+    }
+
+    if !(0.0..=1.0).contains(&inputs.pension_flat_tax_rate) {
+        errors.push(ValidationError::new(
+            "pension-income-tax-rate",
+            "--pension-income-tax-rate must be between 0 and 100",
+        ));
+    }
+
+    if !(0.0..=1.0).contains(&inputs.pension_tax_free_pct) {
+        errors.push(ValidationError::new(
+            "pension-tax-free-pct",
+            "--pension-tax-free-pct must be between 0 and 100",
+        ));
+    }
+
+    for (name, rate) in [
+        ("--uk-basic-rate", inputs.uk_basic_rate),
+        ("--uk-higher-rate", inputs.uk_higher_rate),
+        ("--uk-additional-rate", inputs.uk_additional_rate),
+    ] {
+        if !(0.0..=1.0).contains(&rate) {
+            errors.push(ValidationError::new(
+                name,
+                format!("{name} must be between 0 and 100"),
+            ));
+        }
+    }
+
+    if inputs.uk_personal_allowance < 0.0
+        || inputs.uk_basic_rate_limit < 0.0
+        || inputs.uk_higher_rate_limit < 0.0
+        || inputs.uk_allowance_taper_start < 0.0
+        || inputs.uk_allowance_taper_end < 0.0
+    {
+        errors.push(ValidationError::new(
+            "uk-personal-allowance",
+            "UK tax thresholds must be >= 0",
+        ));
+    }
+
+    // The statutory transfer is 10% of the personal allowance, rounded up to the next £10.
+    let marriage_allowance_cap = (inputs.uk_personal_allowance / 100.0).ceil() * 10.0;
+    if !(0.0..=marriage_allowance_cap).contains(&inputs.marriage_allowance_transfer) {
+        errors.push(ValidationError::new(
+            "marriage-allowance-transfer",
+            format!(
+                "--marriage-allowance-transfer must be between 0 and {marriage_allowance_cap} (10% of --uk-personal-allowance)"
+            ),
+        ));
+    }
+
+    if inputs.uk_basic_rate_limit < inputs.uk_personal_allowance {
+        errors.push(ValidationError::new(
+            "uk-basic-rate-limit",
+            "--uk-basic-rate-limit must be >= --uk-personal-allowance",
+        ));
+    }
+
+    if inputs.uk_higher_rate_limit < inputs.uk_basic_rate_limit {
+        errors.push(ValidationError::new(
+            "uk-higher-rate-limit",
+            "--uk-higher-rate-limit must be >= --uk-basic-rate-limit",
+        ));
+    }
+
+    if inputs.uk_allowance_taper_end <= inputs.uk_allowance_taper_start {
+        errors.push(ValidationError::new(
+            "uk-allowance-taper-end",
+            "--uk-allowance-taper-end must be > --uk-allowance-taper-start",
+        ));
+    }
+
+    for (name, rate) in [
+        ("--scottish-starter-rate", inputs.scottish_starter_rate),
+        ("--scottish-basic-rate", inputs.scottish_basic_rate),
+        (
+            "--scottish-intermediate-rate",
+            inputs.scottish_intermediate_rate,
+        ),
+        ("--scottish-higher-rate", inputs.scottish_higher_rate),
+        ("--scottish-advanced-rate", inputs.scottish_advanced_rate),
+        ("--scottish-top-rate", inputs.scottish_top_rate),
+    ] {
+        if !(0.0..=1.0).contains(&rate) {
+            errors.push(ValidationError::new(
+                name,
+                format!("{name} must be between 0 and 100"),
+            ));
+        }
+    }
+
+    let mut previous_limit = ("--uk-personal-allowance", inputs.uk_personal_allowance);
+    for (name, limit) in [
+        (
+            "--scottish-starter-rate-limit",
+            inputs.scottish_starter_rate_limit,
+        ),
+        (
+            "--scottish-basic-rate-limit",
+            inputs.scottish_basic_rate_limit,
+        ),
+        (
+            "--scottish-intermediate-rate-limit",
+            inputs.scottish_intermediate_rate_limit,
+        ),
+        (
+            "--scottish-higher-rate-limit",
+            inputs.scottish_higher_rate_limit,
+        ),
+        (
+            "--scottish-advanced-rate-limit",
+            inputs.scottish_advanced_rate_limit,
+        ),
+    ] {
+        if !limit.is_finite() || limit < previous_limit.1 {
+            errors.push(ValidationError::new(
+                name,
+                format!("{name} must be >= {}", previous_limit.0),
+            ));
+        }
+        previous_limit = (name, limit);
+    }
+
+    if inputs.state_pension_annual_income < 0.0 {
+        errors.push(ValidationError::new(
+            "state-pension-annual-income",
+            "--state-pension-annual-income must be >= 0",
+        ));
+    }
+
+    if !inputs.state_pension_real_growth.is_finite() || inputs.state_pension_real_growth <= -1.0 {
+        errors.push(ValidationError::new(
+            "state-pension-real-growth",
+            "--state-pension-real-growth must be > -100",
+        ));
+    }
+
+    if inputs.state_pension_deferral_years > 0
+        && inputs
+            .state_pension_start_age
+            .saturating_add(inputs.state_pension_deferral_years)
+            >= inputs.horizon_age
+    {
+        errors.push(ValidationError::new(
+            "state-pension-deferral-years",
+            "--state-pension-start-age plus --state-pension-deferral-years must be < --horizon-age",
+        ));
+    }
+
+    if !inputs.state_pension_deferral_uplift_rate.is_finite()
+        || inputs.state_pension_deferral_uplift_rate < 0.0
+    {
+        errors.push(ValidationError::new(
+            "state-pension-deferral-uplift-rate",
+            "--state-pension-deferral-uplift-rate must be >= 0",
+        ));
+    }
+
+    if !inputs.db_pension_annual_income.is_finite() || inputs.db_pension_annual_income < 0.0 {
+        errors.push(ValidationError::new(
+            "db-pension-annual-income",
+            "--db-pension-annual-income must be >= 0",
+        ));
+    }
+
+    if let Some(purchase_age) = inputs.annuity_purchase_age
+        && (purchase_age < inputs.pension_access_age || purchase_age >= inputs.horizon_age)
+    {
+        errors.push(ValidationError::new(
+            "annuity-purchase-age",
+            "--annuity-purchase-age must be >= --pension-access-age and < --horizon-age",
+        ));
+    }
+
+    if !inputs.annuity_purchase_amount.is_finite() || inputs.annuity_purchase_amount < 0.0 {
+        errors.push(ValidationError::new(
+            "annuity-purchase-amount",
+            "--annuity-purchase-amount must be >= 0",
+        ));
+    }
+
+    if !(0.0..=1.0).contains(&inputs.annuity_rate) {
+        errors.push(ValidationError::new(
+            "annuity-rate",
+            "--annuity-rate must be between 0 and 100",
+        ));
+    }
+
+    if !inputs.conversion_ladder_annual_amount.is_finite()
+        || inputs.conversion_ladder_annual_amount < 0.0
+    {
+        errors.push(ValidationError::new(
+            "conversion-ladder-annual-amount",
+            "--conversion-ladder-annual-amount must be >= 0",
+        ));
+    }
+
+    if inputs.conversion_ladder_end_age < inputs.conversion_ladder_start_age {
+        errors.push(ValidationError::new(
+            "conversion-ladder-end-age",
+            "--conversion-ladder-end-age must be >= --conversion-ladder-start-age",
+        ));
+    }
+
+    if !inputs.rental_annual_income.is_finite() || inputs.rental_annual_income < 0.0 {
+        errors.push(ValidationError::new(
+            "rental-annual-income",
+            "--rental-annual-income must be >= 0",
+        ));
+    }
+
+    if !inputs.rental_growth_rate.is_finite() || inputs.rental_growth_rate <= -1.0 {
+        errors.push(ValidationError::new(
+            "rental-growth-rate",
+            "--rental-growth-rate must be > -100",
+        ));
+    }
+
+    if !inputs.part_time_annual_income.is_finite() || inputs.part_time_annual_income < 0.0 {
+        errors.push(ValidationError::new(
+            "part-time-annual-income",
+            "--part-time-annual-income must be >= 0",
+        ));
+    }
+
+    if inputs.part_time_annual_income > 0.0 && inputs.part_time_end_age < inputs.part_time_start_age
+    {
+        errors.push(ValidationError::new(
+            "part-time-end-age",
+            "--part-time-end-age must be >= --part-time-start-age",
+        ));
+    }
+
+    if matches!(
+        inputs.pre_access_withdrawal_order,
+        Some(WithdrawalOrder::PensionFirst | WithdrawalOrder::FillBasicRate)
+    ) {
+        errors.push(ValidationError::new(
+            "pre-access-withdrawal-order",
+            "--pre-access-withdrawal-order cannot draw on the pension before it is accessible",
+        ));
+    }
+
+    // Out-of-range correlations are already reported; only test the matrix built from
+    // valid ones.
+    let correlations_in_range = !errors
+        .iter()
+        .any(|error| error.field.ends_with("correlation"));
+    if correlations_in_range && return_correlation_cholesky(inputs).is_none() {
+        errors.push(ValidationError::new(
+            "return-correlation",
+            "--return-correlation, --isa-taxable-correlation and --taxable-pension-correlation must form a valid (positive semidefinite) correlation matrix",
+        ));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
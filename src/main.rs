@@ -4,18 +4,23 @@ use std::env;
 async fn main() {
     let raw_args: Vec<String> = env::args().collect();
     if raw_args.get(1).map(|s| s.as_str()) == Some("serve") {
-        let port = raw_args
-            .get(2)
-            .and_then(|s| s.parse::<u16>().ok())
-            .or_else(|| env::var("PORT").ok().and_then(|s| s.parse::<u16>().ok()))
-            .unwrap_or(8080);
-        if let Err(e) = fire::api::run_http_server(port).await {
-            eprintln!("Server error: {e}");
+        if let Err(e) = fire::api::run_serve_command(raw_args.iter().skip(1)).await {
+            eprintln!("{e}");
             std::process::exit(1);
         }
         return;
     }
+    if raw_args.get(1).map(|s| s.as_str()) == Some("simulate") {
+        if let Err(e) =
+            fire::api::run_simulate_command(raw_args.iter().skip(1), &mut std::io::stdout())
+        {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+        return;
+    }
 
-    eprintln!("Usage: cargo run -- serve [port]");
+    eprintln!("Usage: cargo run -- serve [port] [--cors-origin URL ...]");
+    eprintln!("       cargo run -- simulate --current-age 30 ... [--json | --jsonl]");
     std::process::exit(1);
 }
@@ -0,0 +1,76 @@
+use std::process::Command;
+
+const REQUIRED_ARGS: [&str; 18] = [
+    "--current-age",
+    "30",
+    "--pension-access-age",
+    "57",
+    "--isa-start",
+    "100000",
+    "--pension-start",
+    "200000",
+    "--isa-annual-contribution",
+    "20000",
+    "--pension-annual-contribution",
+    "10000",
+    "--isa-growth-rate",
+    "7",
+    "--pension-growth-rate",
+    "7",
+    "--target-annual-income",
+    "30000",
+];
+
+fn run_simulate(extra: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_fire"))
+        .arg("simulate")
+        .args(REQUIRED_ARGS)
+        .args(["--simulations", "200", "--max-age", "34", "--seed", "5"])
+        .args(extra)
+        .output()
+        .expect("failed to run fire binary")
+}
+
+#[test]
+fn simulate_prints_one_table_row_per_tested_age() {
+    let output = run_simulate(&[]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).expect("utf-8 output");
+
+    let rows = stdout
+        .lines()
+        .filter(|line| {
+            line.split_whitespace()
+                .next()
+                .and_then(|age| age.trim_end_matches('*').parse::<u32>().ok())
+                .is_some()
+        })
+        .count();
+    assert!(stdout.starts_with(" age   success"), "{stdout}");
+    assert_eq!(rows, 5, "{stdout}");
+}
+
+#[test]
+fn simulate_json_matches_api_shape() {
+    let output = run_simulate(&["--json"]);
+    assert!(output.status.success(), "{output:?}");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid json");
+
+    assert_eq!(json["mode"], "retirement");
+    assert_eq!(json["ageResults"].as_array().map(Vec::len), Some(5));
+    assert!(
+        json["cashflowYears"]
+            .as_array()
+            .is_some_and(|y| !y.is_empty())
+    );
+}
+
+#[test]
+fn simulate_reports_invalid_flags_on_stderr() {
+    let output = run_simulate(&["--isa-fee-rate", "150"]);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("--isa-fee-rate must be between 0 and 100")
+    );
+}
@@ -136,19 +136,43 @@
     "pensionStart",
     "cashStart",
     "bondLadderStart",
+    "bondLadderTargetAtRetirement",
+    "lisaStart",
     "isaContribution",
     "isaLimit",
+    "lisaContribution",
     "taxableContribution",
     "pensionContribution",
+    "employerMatchCap",
+    "pensionAnnualAllowance",
     "cgtAllowance",
+    "dividendAllowance",
     "statePensionIncome",
+    "partnerPensionStart",
+    "partnerPersonalAllowance",
+    "dbPensionIncome",
+    "annuityPurchaseAmount",
+    "rentalIncome",
+    "partTimeIncome",
+    "minRealSpendingFloor",
     "ukPersonalAllowance",
+    "marriageAllowanceTransfer",
     "ukBasicRateLimit",
     "ukHigherRateLimit",
     "ukAllowanceTaperStart",
     "ukAllowanceTaperEnd",
+    "scottishStarterRateLimit",
+    "scottishBasicRateLimit",
+    "scottishIntermediateRateLimit",
+    "scottishHigherRateLimit",
+    "scottishAdvancedRateLimit",
     "targetIncome",
+    "essentialIncome",
+    "bequestTarget",
     "mortgageAnnualPayment",
+    "mortgageBalance",
+    "utilitySpendingFloor",
+    "preRetirementEarnings",
     "goalSearchMin",
     "goalSearchMax",
     "goalTolerance"
@@ -156,21 +180,52 @@
 
   const PERCENT_FIELDS = new Set([
     "contributionGrowth",
-    "cgtRate",
+    "cgtBasicRate",
+    "cgtHigherRate",
     "pensionIncomeTaxRate",
+    "pensionTaxFreePct",
+    "annuityRate",
+    "rentalGrowthRate",
+    "mortgageRate",
+    "statePensionRealGrowth",
+    "statePensionDeferralUpliftRate",
     "ukBasicRate",
     "ukHigherRate",
     "ukAdditionalRate",
+    "scottishStarterRate",
+    "scottishBasicRate",
+    "scottishIntermediateRate",
+    "scottishHigherRate",
+    "scottishAdvancedRate",
+    "scottishTopRate",
     "isaMean",
     "isaVol",
     "taxableMean",
     "taxableVol",
     "taxableTaxDrag",
+    "taxableDividendYield",
+    "dividendBasicRate",
+    "dividendHigherRate",
+    "dividendAdditionalRate",
+    "isaFeeRate",
+    "taxableFeeRate",
+    "pensionFeeRate",
+    "employerMatchRate",
+    "glideStartEquity",
+    "glideEndEquity",
     "pensionMean",
     "pensionVol",
+    "garchLongRunVol",
+    "crisisReturnMean",
+    "crisisReturnVolatility",
     "inflationMean",
     "inflationVol",
+    "returnClampMin",
+    "returnClampMax",
+    "inflationClampMin",
+    "inflationClampMax",
     "bondLadderYield",
+    "bondLadderYieldVol",
     "badThreshold",
     "goodThreshold",
     "badCut",
@@ -181,10 +236,19 @@
     "cashGrowth",
     "gkLowerGuardrail",
     "gkUpperGuardrail",
+    "gkAdjustment",
     "vpwRealReturn",
+    "constantWithdrawalRate",
+    "spendingDeclineRate",
+    "careCostUplift",
+    "activePhaseUplift",
+    "latePhaseUplift",
+    "capeA",
+    "capeReversionSpeed",
     "floorUpsideCapture",
     "successThreshold",
-    "goalTargetSuccessThreshold"
+    "goalTargetSuccessThreshold",
+    "utilityDiscountRate"
   ]);
 
   const AGE_FIELDS = new Set([
@@ -193,8 +257,23 @@
     "maxAge",
     "horizonAge",
     "statePensionStartAge",
+    "dbPensionStartAge",
+    "annuityPurchaseAge",
+    "conversionLadderStartAge",
+    "conversionLadderEndAge",
+    "rentalIncomeStartAge",
+    "partTimeStartAge",
+    "partTimeEndAge",
     "coastRetirementAge",
+    "cashflowAge",
     "mortgageEndAge",
+    "glideEndAge",
+    "thresholdsFrozenUntilAge",
+    "partnerPensionAccessAge",
+    "lisaAccessAge",
+    "careCostAge",
+    "activePhaseEndAge",
+    "latePhaseStartAge",
     "goalTargetRetirementAge"
   ]);
 
@@ -205,8 +284,31 @@
     "goalSimulationsPerIteration",
     "goalFinalSimulations"
   ]);
-  const YEAR_FIELDS = new Set(["bucketYearsTarget", "bondLadderYears"]);
-  const RATIO_FIELDS = new Set(["correlation"]);
+  const YEAR_FIELDS = new Set([
+    "bucketYearsTarget",
+    "bondLadderYears",
+    "bootstrapBlockYears",
+    "marketBurnInYears",
+    "statePensionDeferralYears",
+    "mortgageTermYears"
+  ]);
+  const RATIO_FIELDS = new Set([
+    "correlation",
+    "isaTaxableCorrelation",
+    "taxablePensionCorrelation",
+    "isaBeta",
+    "taxableBeta",
+    "pensionBeta",
+    "inflationReturnCorrelation",
+    "bondLadderInflationCorrelation",
+    "bootstrapFraction",
+    "coastContributionFraction",
+    "riskAversion",
+    "garchAlpha",
+    "garchBeta",
+    "normalToCrisisProbability",
+    "crisisToNormalProbability"
+  ]);
 
   const form = document.getElementById("config-form");
   const inputModeSelect = document.getElementById("input-mode");
@@ -332,6 +434,7 @@
     const header = [
       "age",
       "success_rate",
+      "success_ci_half_width",
       "median_retirement_total_today",
       "p10_retirement_total_today",
       "median_retirement_isa_today",
@@ -346,6 +449,12 @@
       "p10_retirement_bond_ladder_today",
       "median_total_today",
       "p10_total_today",
+      "p5_total_today",
+      "p25_total_today",
+      "p75_total_today",
+      "p90_total_today",
+      "p95_total_today",
+      "cvar5_total_today",
       "median_isa_today",
       "median_taxable_today",
       "median_pension_today",
@@ -357,12 +466,14 @@
       "p10_cash_today",
       "p10_bond_ladder_today",
       "p10_min_income_ratio",
+      "cvar5_min_income_ratio",
       "median_avg_income_ratio"
     ];
 
     const rows = lastResults.ageResults.map((r) => [
       r.retirementAge,
       r.successRate,
+      r.successCiHalfWidth,
       r.medianRetirementPot,
       r.p10RetirementPot,
       r.medianRetirementIsa,
@@ -377,6 +488,12 @@
       r.p10RetirementBondLadder,
       r.medianTerminalPot,
       r.p10TerminalPot,
+      r.p5TerminalPot,
+      r.p25TerminalPot,
+      r.p75TerminalPot,
+      r.p90TerminalPot,
+      r.p95TerminalPot,
+      r.cvar5TerminalPot,
       r.medianTerminalIsa,
       r.medianTerminalTaxable,
       r.medianTerminalPension,
@@ -388,6 +505,7 @@
       r.p10TerminalCash,
       r.p10TerminalBondLadder,
       r.p10MinIncomeRatio,
+      r.cvar5MinIncomeRatio,
       r.medianAvgIncomeRatio
     ]);
 
@@ -439,6 +557,7 @@
         body: JSON.stringify(payloadBody)
       });
       const payload = await response.json();
+      markServerErrors(response.ok ? [] : payload.errors);
 
       if (!response.ok) {
         throw new Error(payload.error || "Simulation failed");
@@ -539,7 +658,7 @@
       const params = buildApiParams();
       const payloadBody = buildGoalSolvePayload(buildApiPayload(params));
       const started = performance.now();
-      const response = await fetch("/api/solve-goal", {
+      const response = await fetch("/api/solve", {
         method: "POST",
         headers: {
           "Content-Type": "application/json"
@@ -547,6 +666,7 @@
         body: JSON.stringify(payloadBody)
       });
       const payload = await response.json();
+      markServerErrors(response.ok ? [] : payload.errors);
 
       if (!response.ok) {
         throw new Error(payload.error || "Goal solve failed");
@@ -581,10 +701,24 @@
       return;
     }
 
-    const isMaxIncome = String(result.goalType || "") === "max-income";
-    const goalType = isMaxIncome
-      ? "Max Sustainable Income"
-      : "Required Contribution";
+    const isCvarIncome =
+      String(result.goalType || "") === "max-income-cvar-constrained";
+    const isMaxIncome = isCvarIncome || String(result.goalType || "") === "max-income";
+    const isEarliestAge =
+      String(result.goalType || "") === "earliest-retirement-age";
+    const isSplit = String(result.goalType || "") === "optimal-contribution-split";
+    const goalType = isEarliestAge
+      ? "Earliest Retirement Age"
+      : isSplit
+        ? "Optimal Contribution Split"
+        : isCvarIncome
+        ? "Max Income With Tail Floor (CVaR)"
+        : isMaxIncome
+          ? "Max Sustainable Income"
+          : "Required Contribution";
+    const formatValue = isEarliestAge
+      ? (value) => String(Math.round(value))
+      : money;
     const solvedValue = Number(result.solvedValue);
     const achieved = Number(result.achievedSuccessRate);
     const achievedCi = Number(result.achievedSuccessCiHalfWidth);
@@ -592,12 +726,24 @@
     const targetRetirementAge = Number(result.targetRetirementAge);
     const isFeasible = Boolean(result.feasible);
     const isConverged = Boolean(result.converged);
-    const canApply = isFeasible && Number.isFinite(solvedValue);
-
-    const solvedValueLabel = isMaxIncome
-      ? "Solved Annual Target Income"
-      : "Solved Annual Contribution (Total)";
-    const interpretation = isMaxIncome
+    const canApply = !isEarliestAge && isFeasible && Number.isFinite(solvedValue);
+
+    const solvedValueLabel = isEarliestAge
+      ? "Earliest Feasible Retirement Age"
+      : isMaxIncome
+        ? "Solved Annual Target Income"
+        : "Solved Annual Contribution (Total)";
+    const interpretation = isEarliestAge
+      ? Number.isFinite(solvedValue)
+        ? `Interpretation: age ${Math.round(
+            solvedValue
+          )} is the earliest retirement age that meets your success target with the current contributions and income.`
+        : "Interpretation: no retirement age up to the maximum retirement age met your success target."
+      : isSplit
+      ? `Interpretation: for retiring at age ${Math.round(
+          targetRetirementAge
+        )}, this is how to divide your current total contribution across the ISA, taxable account and pension for the highest success rate.`
+      : isMaxIncome
       ? Number.isFinite(solvedValue)
         ? `Interpretation: for retiring at age ${Math.round(
             targetRetirementAge
@@ -625,7 +771,7 @@
       ],
       [
         solvedValueLabel,
-        Number.isFinite(solvedValue) ? money(solvedValue) : "No solution"
+        Number.isFinite(solvedValue) ? formatValue(solvedValue) : "No solution"
       ],
       [
         "Achieved Success",
@@ -645,7 +791,18 @@
       ["Message", String(result.message || "")]
     ];
 
-    if (String(result.goalType || "") === "required-contribution") {
+    if (isCvarIncome) {
+      const achievedCvar = Number(result.achievedCvar5MinIncomeRatio);
+      const targetCvar = Number(result.targetCvar5IncomeRatio);
+      cards.push([
+        "Worst-5% Lowest-Year Income (Achieved / Floor)",
+        `${Number.isFinite(achievedCvar) ? `${(achievedCvar * 100).toFixed(1)}%` : "-"} / ${
+          Number.isFinite(targetCvar) ? `${(targetCvar * 100).toFixed(1)}%` : "-"
+        }`
+      ]);
+    }
+
+    if (isSplit || String(result.goalType || "") === "required-contribution") {
       const isa = Number(result.solvedContributionIsa);
       const taxable = Number(result.solvedContributionTaxable);
       const pension = Number(result.solvedContributionPension);
@@ -687,9 +844,9 @@
         const ci = Number(row.successCiHalfWidth);
         return `<tr>
           <td>${Math.round(Number(row.iteration || 0))}</td>
-          <td>${money(lower)}</td>
-          <td>${money(upper)}</td>
-          <td>${money(candidate)}</td>
+          <td>${formatValue(lower)}</td>
+          <td>${formatValue(upper)}</td>
+          <td>${formatValue(candidate)}</td>
           <td>${(success * 100).toFixed(2)}%</td>
           <td>± ${(ci * 100).toFixed(2)}%</td>
         </tr>`;
@@ -702,7 +859,9 @@
       return;
     }
 
-    const isMaxIncome = String(lastSolveResult.goalType || "") === "max-income";
+    const isMaxIncome = ["max-income", "max-income-cvar-constrained"].includes(
+      String(lastSolveResult.goalType || "")
+    );
     const solvedValue = Number(lastSolveResult.solvedValue);
     if (!Number.isFinite(solvedValue)) {
       if (solveMeta) {
@@ -852,7 +1011,9 @@
       .map(
         (r) => `<tr>
           <td>${r.retirementAge}</td>
-          <td>${(r.successRate * 100).toFixed(1)}%</td>
+          <td title="95% confidence interval">${(r.successRate * 100).toFixed(1)}% ± ${(
+            Number(r.successCiHalfWidth || 0) * 100
+          ).toFixed(1)}</td>
           <td>${money(r.medianRetirementPot)}</td>
           <td>${money(r.p10RetirementPot)}</td>
           <td>${money(r.medianRetirementIsa)}</td>
@@ -918,7 +1079,7 @@
 
     if (rows.length === 0) {
       cashflowTableBody.innerHTML =
-        '<tr><td colspan="17">No yearly cashflow trace available for this run.</td></tr>';
+        '<tr><td colspan="24">No yearly cashflow trace available for this run.</td></tr>';
       return;
     }
 
@@ -927,16 +1088,23 @@
         (row) => `<tr>
           <td>${Math.round(Number(row.age || 0))}</td>
           <td>${money(row.medianContributionIsa)}</td>
+          <td>${money(row.medianContributionLisa)}</td>
           <td>${money(row.medianContributionTaxable)}</td>
           <td>${money(row.medianContributionPension)}</td>
+          <td>${money(row.medianContributionEmployer)}</td>
           <td>${money(row.medianContributionTotal)}</td>
           <td>${money(row.medianWithdrawalPortfolio)}</td>
           <td>${money(row.medianWithdrawalNonPensionIncome)}</td>
+          <td>${money(row.medianAnnuityIncome)}</td>
+          <td>${money(row.medianRentalIncome)}</td>
+          <td>${money(row.medianPensionConversion)}</td>
           <td>${money(row.medianSpendingTotal)}</td>
           <td>${money(row.medianTaxCgt)}</td>
           <td>${money(row.medianTaxIncome)}</td>
+          <td>${money(row.medianTaxIncomeTaper)}</td>
           <td>${money(row.medianTaxTotal)}</td>
           <td>${money(row.medianEndIsa)}</td>
+          <td>${money(row.medianEndLisa)}</td>
           <td>${money(row.medianEndTaxable)}</td>
           <td>${money(row.medianEndPension)}</td>
           <td>${money(row.medianEndCash)}</td>
@@ -1190,22 +1358,46 @@
       if (text === "") {
         continue;
       }
+      if (key === "lumpExpenses" || key === "mortgageOverpayments") {
+        payload[key] = parseLumpExpenses(text);
+        continue;
+      }
+      if (key === "seedLabel") {
+        payload[key] = text;
+        continue;
+      }
+      if (key === "debts") {
+        payload[key] = parseDebts(text);
+        continue;
+      }
+      if (key === "marketPath") {
+        payload[key] = parseMarketPath(text);
+        continue;
+      }
       if (isNumericLiteral(text)) {
         payload[key] = Number(text);
+      } else if (text === "true" || text === "false") {
+        payload[key] = text === "true";
       } else {
         payload[key] = text;
       }
     }
+    // The API lets a numeric seed override the label; in the form the label wins.
+    if (typeof payload.seedLabel === "string") {
+      delete payload.seed;
+    }
     return payload;
   }
 
   function buildGoalSolvePayload(basePayload) {
     const payload = { ...basePayload };
     payload.goalType = selectedValue("goalType") || "required-contribution";
+    payload.secantAcceleration = selectedValue("goalSecantAcceleration") === "true";
 
     const numericFields = [
       ["targetRetirementAge", "goalTargetRetirementAge"],
       ["targetSuccessThreshold", "goalTargetSuccessThreshold"],
+      ["targetCvar5IncomeRatio", "goalTargetCvar5IncomeRatio"],
       ["searchMin", "goalSearchMin"],
       ["searchMax", "goalSearchMax"],
       ["tolerance", "goalTolerance"],
@@ -1228,6 +1420,49 @@
     return payload;
   }
 
+  function parseLumpExpenses(text) {
+    const expenses = [];
+    for (const entry of text.split(",")) {
+      const [age, amount] = entry.split(":").map((part) => Number(part.trim()));
+      if (Number.isFinite(age) && Number.isFinite(amount)) {
+        expenses.push({ age, amount });
+      }
+    }
+    return expenses;
+  }
+
+  function parseDebts(text) {
+    const debts = [];
+    for (const entry of text.split(",")) {
+      const [annualPayment, endAge] = entry.split(":").map((part) => Number(part.trim()));
+      if (Number.isFinite(annualPayment) && Number.isFinite(endAge)) {
+        debts.push({ annualPayment, endAge });
+      }
+    }
+    return debts;
+  }
+
+  function markServerErrors(errors) {
+    form.querySelectorAll("[aria-invalid='true']").forEach((el) => el.removeAttribute("aria-invalid"));
+    for (const { field } of errors || []) {
+      const input = form.elements.namedItem(field);
+      if (input instanceof HTMLElement) {
+        input.setAttribute("aria-invalid", "true");
+      }
+    }
+  }
+
+  function parseMarketPath(text) {
+    const path = [];
+    for (const entry of text.split(",")) {
+      const [isa, taxable, pension, inflation] = entry.split(":").map((part) => Number(part.trim()));
+      if ([isa, taxable, pension, inflation].every(Number.isFinite)) {
+        path.push({ isa, taxable, pension, inflation });
+      }
+    }
+    return path;
+  }
+
   function isNumericLiteral(text) {
     if (!/^[+-]?(?:\d+\.?\d*|\.\d+)$/.test(text)) {
       return false;
@@ -1279,7 +1514,15 @@
       setFormParam(params, name);
     }
 
-    for (const name of ["bondLadderStart", "bondLadderYield", "bondLadderYears"]) {
+    for (const name of [
+      "bondLadderStart",
+      "bondLadderYield",
+      "bondLadderYieldVol",
+      "bondLadderInflationCorrelation",
+      "bondLadderYears",
+      "bondLadderReinvest",
+      "bondLadderTargetAtRetirement"
+    ]) {
       setFormParam(params, name);
     }
 
@@ -1811,7 +2054,9 @@
     const pensionStart = parseNumber("pensionStart");
     const cashStart = parseNumber("cashStart");
     const bondLadderStart = parseNumber("bondLadderStart");
-    const startTotal = isaStart + taxableStart + pensionStart + cashStart + bondLadderStart;
+    const lisaStart = parseNumber("lisaStart");
+    const startTotal =
+      isaStart + taxableStart + pensionStart + cashStart + bondLadderStart + lisaStart;
 
     const isaLimit = Math.max(parseNumber("isaLimit"), 0);
     const isaContribution = parseNumber("isaContribution");
@@ -1837,12 +2082,15 @@
       mode === "basic"
         ? `${selectedOptionText("riskProfile")} profile`
         : selectedOptionText("withdrawalPolicy");
+    const mortgageBalance = mode === "basic" ? 0 : parseNumber("mortgageBalance");
     const mortgageSummary =
-      mortgageAnnualPayment <= 0
-        ? "None"
-        : mortgageEndAge !== null && Number.isFinite(mortgageEndAge)
-          ? `${money(mortgageAnnualPayment)}/yr until age ${Math.round(mortgageEndAge)}`
-          : `${money(mortgageAnnualPayment)}/yr (end age not set)`;
+      mortgageBalance > 0
+        ? `${money(mortgageBalance)} at ${trimZeros(parseNumber("mortgageRate"), 2)}% over ${Math.round(parseNumber("mortgageTermYears"))} yrs`
+        : mortgageAnnualPayment <= 0
+          ? "None"
+          : mortgageEndAge !== null && Number.isFinite(mortgageEndAge)
+            ? `${money(mortgageAnnualPayment)}/yr until age ${Math.round(mortgageEndAge)}`
+            : `${money(mortgageAnnualPayment)}/yr (end age not set)`;
 
     setSummaryText("startTotal", money(startTotal));
     setSummaryText("annualContribution", money(annualContribution));
@@ -1894,6 +2142,8 @@
     const analysisMode = mode === "basic" ? "retirement-sweep" : selectedValue("analysisMode");
     const coastAgeRaw = selectedValue("coastRetirementAge");
     const coastAge = coastAgeRaw === "" ? null : Number(coastAgeRaw);
+    const cashflowAgeRaw = selectedValue("cashflowAge");
+    const cashflowAge = cashflowAgeRaw === "" ? null : Number(cashflowAgeRaw);
     const strategy = mode === "basic" ? "guardrails" : selectedValue("withdrawalPolicy");
     const goalTargetRetirementAge = parseNumber("goalTargetRetirementAge");
     const goalTargetSuccessThreshold = parseNumber("goalTargetSuccessThreshold");
@@ -1952,6 +2202,9 @@
         errors.push("Coast retirement age must be below horizon age.");
       }
     }
+    if (mode !== "basic" && cashflowAge !== null && (cashflowAge < currentAge || cashflowAge > maxAge)) {
+      errors.push("Cashflow chart age must be between current age and max retirement age.");
+    }
     if (mode === "advanced") {
       if (goalTargetRetirementAge < currentAge) {
         errors.push("Goal target retirement age must be at least current age.");
@@ -2106,6 +2359,14 @@
         return "Floor + Upside";
       case "bucket":
         return "Bucket";
+      case "fixed-real":
+        return "Fixed Real";
+      case "constant-percentage":
+        return "Constant Percentage";
+      case "cape-based":
+        return "CAPE-Based";
+      case "amortization":
+        return "Amortization";
       case "guardrails":
       default:
         return "Dynamic Guardrails";